@@ -0,0 +1,277 @@
+//! A transformation that renames all user symbols in a problem and its proof.
+//!
+//! This is useful to share proofs of proprietary problems in bug reports. Every function,
+//! constant, variable and sort name is replaced by a fresh, meaningless name, consistently across
+//! the problem and the proof, so the anonymized proof is still valid with respect to the
+//! anonymized problem.
+
+use super::*;
+use std::collections::HashMap;
+
+/// Renames all user symbols in `problem` and `proof`, returning the anonymized problem and proof.
+///
+/// Function, constant and variable names are replaced by names of the form `x<n>`, while sort
+/// names are replaced by names of the form `S<n>`. Names are assigned in order of first occurrence,
+/// so the result is deterministic. Step ids, string constants and the logic string are preserved.
+pub fn anonymize(pool: &mut PrimitivePool, problem: &Problem, proof: &Proof) -> (Problem, Proof) {
+    let mut anonymizer = Anonymizer {
+        pool,
+        names: HashMap::new(),
+        sort_names: HashMap::new(),
+        cache: HashMap::new(),
+    };
+
+    let prelude = ProblemPrelude {
+        sort_declarations: problem
+            .prelude
+            .sort_declarations
+            .iter()
+            .map(|(name, arity)| (anonymizer.sort_name(name), *arity))
+            .collect(),
+        datatypes: {
+            let mut datatypes = Datatypes::new();
+            for def in problem.prelude.datatypes.iter() {
+                datatypes.insert(anonymizer.datatype(def));
+            }
+            datatypes
+        },
+        function_declarations: problem
+            .prelude
+            .function_declarations
+            .iter()
+            .map(|(name, sort)| (anonymizer.name(name), anonymizer.term(sort)))
+            .collect(),
+        logic: problem.prelude.logic.clone(),
+    };
+    let premises = problem
+        .premises
+        .iter()
+        .map(|t| anonymizer.term(t))
+        .collect();
+    let assumptions = problem
+        .assumptions
+        .iter()
+        .map(|t| anonymizer.term(t))
+        .collect();
+
+    let constant_definitions = proof
+        .constant_definitions
+        .iter()
+        .map(|(name, value)| (anonymizer.name(name), anonymizer.term(value)))
+        .collect();
+    let commands = anonymizer.commands(&proof.commands);
+
+    (
+        Problem {
+            prelude,
+            premises,
+            assumptions,
+            parsing_context: None,
+        },
+        Proof { constant_definitions, commands },
+    )
+}
+
+struct Anonymizer<'a> {
+    pool: &'a mut PrimitivePool,
+    names: HashMap<String, String>,
+    sort_names: HashMap<String, String>,
+    cache: HashMap<Rc<Term>, Rc<Term>>,
+}
+
+impl<'a> Anonymizer<'a> {
+    fn name(&mut self, name: &str) -> String {
+        let next = self.names.len();
+        self.names
+            .entry(name.to_owned())
+            .or_insert_with(|| format!("x{}", next))
+            .clone()
+    }
+
+    fn sort_name(&mut self, name: &str) -> String {
+        let next = self.sort_names.len();
+        self.sort_names
+            .entry(name.to_owned())
+            .or_insert_with(|| format!("S{}", next))
+            .clone()
+    }
+
+    fn datatype(&mut self, def: &DatatypeDef) -> DatatypeDef {
+        let constructors = def
+            .constructors
+            .iter()
+            .map(|c| Constructor {
+                name: self.name(&c.name),
+                selectors: c.selectors.iter().map(|s| self.sorted_var(s)).collect(),
+            })
+            .collect();
+        DatatypeDef {
+            name: self.sort_name(&def.name),
+            constructors,
+        }
+    }
+
+    fn sorted_var(&mut self, (name, value): &SortedVar) -> SortedVar {
+        (self.name(name), self.term(value))
+    }
+
+    fn terms(&mut self, terms: &[Rc<Term>]) -> Vec<Rc<Term>> {
+        terms.iter().map(|t| self.term(t)).collect()
+    }
+
+    fn term(&mut self, term: &Rc<Term>) -> Rc<Term> {
+        if let Some(t) = self.cache.get(term) {
+            return t.clone();
+        }
+        let result = match term.as_ref() {
+            Term::Const(_) => term.clone(),
+            Term::Var(name, sort) => {
+                let var = Term::Var(self.name(name), self.term(sort));
+                self.pool.add(var)
+            }
+            Term::App(func, args) => {
+                let app = Term::App(self.term(func), self.terms(args));
+                self.pool.add(app)
+            }
+            Term::Op(op, args) => {
+                let op = Term::Op(*op, self.terms(args));
+                self.pool.add(op)
+            }
+            Term::Sort(sort) => {
+                let sort = Term::Sort(self.sort(sort));
+                self.pool.add(sort)
+            }
+            Term::Binder(binder, bindings, inner) => {
+                let bindings = BindingList(bindings.iter().map(|b| self.sorted_var(b)).collect());
+                let binder = Term::Binder(*binder, bindings, self.term(inner));
+                self.pool.add(binder)
+            }
+            Term::Let(bindings, inner) => {
+                let bindings = BindingList(bindings.iter().map(|b| self.sorted_var(b)).collect());
+                let let_term = Term::Let(bindings, self.term(inner));
+                self.pool.add(let_term)
+            }
+            Term::ParamOp { op, op_args, args } => {
+                let param_op = Term::ParamOp {
+                    op: *op,
+                    op_args: self.terms(op_args),
+                    args: self.terms(args),
+                };
+                self.pool.add(param_op)
+            }
+        };
+        self.cache.insert(term.clone(), result.clone());
+        result
+    }
+
+    fn sort(&mut self, sort: &Sort) -> Sort {
+        match sort {
+            Sort::Function(sorts) => Sort::Function(self.terms(sorts)),
+            Sort::Atom(name, args) => Sort::Atom(self.sort_name(name), self.terms(args)),
+            Sort::Array(x, y) => Sort::Array(self.term(x), self.term(y)),
+            Sort::Set(x) => Sort::Set(self.term(x)),
+            Sort::Seq(x) => Sort::Seq(self.term(x)),
+            Sort::Bool
+            | Sort::Int
+            | Sort::Real
+            | Sort::String
+            | Sort::RegLan
+            | Sort::BitVec(_)
+            | Sort::FloatingPoint(_, _)
+            | Sort::RoundingMode
+            | Sort::FiniteField(_)
+            | Sort::RareList
+            | Sort::Type => sort.clone(),
+        }
+    }
+
+    fn commands(&mut self, commands: &[ProofCommand]) -> Vec<ProofCommand> {
+        commands
+            .iter()
+            .map(|c| match c {
+                ProofCommand::Assume { id, term } => ProofCommand::Assume {
+                    id: id.clone(),
+                    term: self.term(term),
+                },
+                ProofCommand::Step(s) => ProofCommand::Step(ProofStep {
+                    clause: self.terms(&s.clause),
+                    args: self.terms(&s.args),
+                    arg_vars: s
+                        .arg_vars
+                        .iter()
+                        .map(|v| v.as_ref().map(|v| self.sorted_var(v)))
+                        .collect(),
+                    // Attributes added by other tools may reveal information about the problem,
+                    // and can't be anonymized since they are not interpreted
+                    attributes: Vec::new(),
+                    ..s.clone()
+                }),
+                ProofCommand::Subproof(s) => {
+                    let args = s
+                        .args
+                        .iter()
+                        .map(|a| match a {
+                            AnchorArg::Variable(var) => AnchorArg::Variable(self.sorted_var(var)),
+                            AnchorArg::Assign(var, value) => {
+                                AnchorArg::Assign(self.sorted_var(var), self.term(value))
+                            }
+                        })
+                        .collect();
+                    ProofCommand::Subproof(Subproof {
+                        commands: self.commands(&s.commands),
+                        args,
+                        context_id: s.context_id,
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{checker, parser};
+
+    #[test]
+    fn test_anonymize() {
+        let problem: &[u8] = b"
+            (declare-sort |My Sort| 0)
+            (declare-fun |secret f| (|My Sort|) Bool)
+            (declare-const c |My Sort|)
+            (assert (forall ((y |My Sort|)) (|secret f| y)))
+            (assert (not (|secret f| c)))
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (forall ((y |My Sort|)) (|secret f| y)))
+            (assume h2 (not (|secret f| c)))
+            (step t3 (cl (or (not (forall ((y |My Sort|)) (|secret f| y))) (|secret f| c)))
+                :rule forall_inst :args (c))
+            (step t3.1 (cl (not (forall ((y |My Sort|)) (|secret f| y))) (|secret f| c))
+                :rule or :premises (t3))
+            (anchor :step t4 :args ((z |My Sort|) (:= (w |My Sort|) c)))
+            (step t4.t1 (cl (= z c)) :rule hole)
+            (step t4 (cl (= (|secret f| z) (|secret f| c))) :rule hole)
+            (step t5 (cl) :rule resolution :premises (h1 h2 t3.1))
+        ";
+        let (problem, proof, mut pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let (problem, proof) = anonymize(&mut pool, &problem, &proof);
+
+        let printed = format!(
+            "{}{:?}{:?}",
+            problem.prelude, problem.premises, proof.commands
+        );
+        for name in ["My Sort", "secret f", "(c ", " c)", "y ", "z ", "w "] {
+            assert!(
+                !printed.contains(name),
+                "found '{}' in anonymized output",
+                name
+            );
+        }
+
+        checker::ProofChecker::new(&mut pool, checker::Config::new())
+            .check(&problem, &proof)
+            .unwrap();
+    }
+}
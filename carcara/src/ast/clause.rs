@@ -0,0 +1,310 @@
+//! A clause representation that caches information used to compare clauses quickly.
+
+use super::{Rc, Term};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::OnceLock,
+};
+
+/// The number of 64-bit words in the wide signature of large clauses.
+const WIDE_SIGNATURE_WORDS: usize = 16;
+
+/// Clauses with more distinct literals than this also use a wide signature when checking for
+/// subsumption. With this many literals, most bits of a 64-bit signature are already set, so it
+/// rarely allows rejecting a candidate early.
+const LARGE_CLAUSE_SIZE: usize = 32;
+
+/// Mixes the address of a term, giving a hash whose highest bits are used to choose signature bits.
+fn mix_address(term: &Rc<Term>) -> u64 {
+    let address = Rc::as_ptr(term) as *const u8 as u64;
+    address.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+/// Returns a bit mask with a single bit set, chosen according to the address of the term.
+fn signature_bit(term: &Rc<Term>) -> u64 {
+    1 << (mix_address(term) >> 58)
+}
+
+/// Returns the word and bit mask of the bit of a wide signature that corresponds to the term.
+fn wide_signature_bit(term: &Rc<Term>) -> (usize, u64) {
+    let index = mix_address(term) >> 54;
+    ((index >> 6) as usize, 1 << (index & 63))
+}
+
+/// A clause, that is, a list of literals, together with some cached information about it.
+///
+/// Building a `Clause` takes linear time in the number of literals. After that:
+/// - Hashing a clause takes constant time, and comparing two clauses for equality usually takes
+///   constant time when they are different, since their cached hashes are compared first.
+/// - Checking if two clauses have the same set of literals, or if one subsumes the other, usually
+///   takes constant time when the answer is negative, since each clause caches a 64-bit signature
+///   of its literals. Otherwise, it takes linear time, using a sorted view of the literals that is
+///   computed the first time it is needed. For large clauses, a wider bitset signature is also
+///   computed, since most bits of the 64-bit signature are set.
+///
+/// Since terms are hash consed, literals are compared by reference.
+#[derive(Debug, Clone)]
+pub struct Clause {
+    literals: Vec<Rc<Term>>,
+    hash: u64,
+    signature: u64,
+    sorted: OnceLock<Vec<Rc<Term>>>,
+    wide_signature: OnceLock<[u64; WIDE_SIGNATURE_WORDS]>,
+}
+
+impl Clause {
+    pub fn new(literals: Vec<Rc<Term>>) -> Self {
+        let mut hasher = DefaultHasher::new();
+        literals.hash(&mut hasher);
+        let signature = literals.iter().fold(0, |acc, l| acc | signature_bit(l));
+        Self {
+            literals,
+            hash: hasher.finish(),
+            signature,
+            sorted: OnceLock::new(),
+            wide_signature: OnceLock::new(),
+        }
+    }
+
+    pub fn literals(&self) -> &[Rc<Term>] {
+        &self.literals
+    }
+
+    pub fn into_literals(self) -> Vec<Rc<Term>> {
+        self.literals
+    }
+
+    /// Returns the distinct literals of the clause, sorted by their addresses.
+    pub fn sorted(&self) -> &[Rc<Term>] {
+        self.sorted.get_or_init(|| {
+            let mut sorted = self.literals.clone();
+            sorted.sort_unstable_by_key(|l| Rc::as_ptr(l) as *const u8 as usize);
+            sorted.dedup();
+            sorted
+        })
+    }
+
+    /// Returns `true` if both clauses have the same set of literals, ignoring their order and any
+    /// repetitions.
+    pub fn same_literals(&self, other: &Clause) -> bool {
+        self.signature == other.signature && self.sorted() == other.sorted()
+    }
+
+    fn wide_signature(&self) -> &[u64; WIDE_SIGNATURE_WORDS] {
+        self.wide_signature.get_or_init(|| {
+            let mut signature = [0; WIDE_SIGNATURE_WORDS];
+            for l in &self.literals {
+                let (word, bit) = wide_signature_bit(l);
+                signature[word] |= bit;
+            }
+            signature
+        })
+    }
+
+    /// Returns `true` if every literal of this clause is also in `other`.
+    pub fn subsumes(&self, other: &Clause) -> bool {
+        if self.signature & !other.signature != 0 {
+            return false;
+        }
+        if other.sorted().len() > LARGE_CLAUSE_SIZE {
+            let (a, b) = (self.wide_signature(), other.wide_signature());
+            if a.iter().zip(b).any(|(a, b)| a & !b != 0) {
+                return false;
+            }
+        }
+        let key = |l: &Rc<Term>| Rc::as_ptr(l) as *const u8 as usize;
+        let (mut i, mut j) = (self.sorted().iter(), other.sorted().iter());
+        'outer: for l in i.by_ref() {
+            for m in j.by_ref() {
+                if l == m {
+                    continue 'outer;
+                }
+                if key(m) > key(l) {
+                    return false;
+                }
+            }
+            return false;
+        }
+        true
+    }
+
+    /// Returns `true` if every literal of this clause occurs in `other` at least as many times as
+    /// it occurs in this clause. Unlike `Clause::subsumes`, this takes repeated literals into
+    /// account.
+    pub fn is_contained_in(&self, other: &Clause) -> bool {
+        if !self.subsumes(other) {
+            return false;
+        }
+        if self.sorted().len() == self.len() {
+            return true;
+        }
+        let mut counts = literal_counts(other);
+        self.iter().all(|l| match counts.get_mut(l) {
+            Some(n) if *n > 0 => {
+                *n -= 1;
+                true
+            }
+            _ => false,
+        })
+    }
+
+    /// If this clause is contained in `target` (see `Clause::is_contained_in`), returns the
+    /// literals of this clause, followed by the literals of `target` that are missing from it, in
+    /// the order they appear in `target`. This is the conclusion of a `weakening` step with this
+    /// clause as its premise, that can be reordered into `target`. Otherwise, returns `None`.
+    pub fn weaken_to(&self, target: &[Rc<Term>]) -> Option<Vec<Rc<Term>>> {
+        let mut missing = literal_counts(target);
+        for l in self.iter() {
+            match missing.get_mut(l) {
+                Some(n) if *n > 0 => *n -= 1,
+                _ => return None,
+            }
+        }
+        let mut result = self.literals.clone();
+        for l in target {
+            if let Some(n) = missing.get_mut(l).filter(|n| **n > 0) {
+                *n -= 1;
+                result.push(l.clone());
+            }
+        }
+        Some(result)
+    }
+
+    /// Returns a pair of complementary literals in the clause, that is, a literal and its negation,
+    /// if one exists. Literals are compared after removing all leading negations, so `p` and
+    /// `(not (not (not p)))` are considered complementary.
+    pub fn complementary_pair(&self) -> Option<(&Rc<Term>, &Rc<Term>)> {
+        let mut atoms: Vec<_> = self
+            .literals
+            .iter()
+            .map(|l| {
+                let (polarity, atom) = l.remove_all_negations_with_polarity();
+                (Rc::as_ptr(atom) as *const u8 as usize, polarity, l)
+            })
+            .collect();
+        atoms.sort_unstable_by_key(|&(atom, polarity, _)| (atom, polarity));
+        atoms
+            .windows(2)
+            .find(|w| w[0].0 == w[1].0 && w[0].1 != w[1].1)
+            .map(|w| (w[0].2, w[1].2))
+    }
+
+    /// Returns `true` if the clause contains a literal and its negation. See
+    /// `Clause::complementary_pair`.
+    pub fn is_tautology(&self) -> bool {
+        self.complementary_pair().is_some()
+    }
+}
+
+fn literal_counts(literals: &[Rc<Term>]) -> HashMap<&Rc<Term>, usize> {
+    let mut counts = HashMap::with_capacity(literals.len());
+    for l in literals {
+        *counts.entry(l).or_default() += 1;
+    }
+    counts
+}
+
+impl PartialEq for Clause {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.literals == other.literals
+    }
+}
+
+impl Eq for Clause {}
+
+impl Hash for Clause {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl Deref for Clause {
+    type Target = [Rc<Term>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.literals
+    }
+}
+
+impl From<Vec<Rc<Term>>> for Clause {
+    fn from(literals: Vec<Rc<Term>>) -> Self {
+        Self::new(literals)
+    }
+}
+
+impl From<&[Rc<Term>]> for Clause {
+    fn from(literals: &[Rc<Term>]) -> Self {
+        Self::new(literals.to_vec())
+    }
+}
+
+/// An index of clauses, associating each distinct clause with the values that were inserted with
+/// it, in insertion order. This is used to find the steps in a proof that conclude the same
+/// clause, which is a common inefficiency in proofs produced by SMT solvers.
+///
+/// Two clauses are considered the same if they have the same literals in the same order. Since
+/// each [`Clause`] caches its hash, inserting and looking up clauses takes constant time.
+#[derive(Debug, Clone)]
+pub struct ClauseIndex<T> {
+    entries: HashMap<Clause, Vec<T>>,
+}
+
+impl<T> Default for ClauseIndex<T> {
+    fn default() -> Self {
+        Self { entries: HashMap::new() }
+    }
+}
+
+impl<T> ClauseIndex<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a value associated with a clause. If the clause was already in the index, returns
+    /// the value first inserted with it, which is the representative of the clause.
+    pub fn insert(&mut self, clause: Clause, value: T) -> Option<&T> {
+        let values = self.entries.entry(clause).or_default();
+        values.push(value);
+        if values.len() > 1 {
+            values.first()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the representative of a clause, that is, the value first inserted with it.
+    pub fn representative(&self, clause: &Clause) -> Option<&T> {
+        self.entries.get(clause).and_then(|values| values.first())
+    }
+
+    /// Returns all values inserted with a clause, in insertion order.
+    pub fn get(&self, clause: &Clause) -> &[T] {
+        self.entries.get(clause).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the number of distinct clauses in the index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of values that were inserted with a clause that was already in the index.
+    pub fn num_duplicates(&self) -> usize {
+        self.entries.values().map(|values| values.len() - 1).sum()
+    }
+
+    /// Returns an iterator over the clauses that were inserted with at least `min_count` values,
+    /// together with those values. The order of the clauses is unspecified.
+    pub fn duplicates(&self, min_count: usize) -> impl Iterator<Item = (&Clause, &[T])> {
+        let min_count = min_count.max(2);
+        self.entries
+            .iter()
+            .filter(move |(_, values)| values.len() >= min_count)
+            .map(|(clause, values)| (clause, values.as_slice()))
+    }
+}
@@ -1,40 +1,68 @@
 use crate::ast::*;
-use std::sync::{atomic::AtomicUsize, Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex, OnceLock,
+};
 
 #[derive(Debug)]
 pub struct Context {
     pub args: Vec<AnchorArg>,
-    pub cumulative_substitution: Option<Substitution>,
+
+    /// The composition of the substitutions introduced by this context and all the contexts that
+    /// enclose it. This is computed the first time it is needed, by whichever thread needs it
+    /// first, and never changes afterwards.
+    cumulative_substitution: OnceLock<Substitution>,
 }
 
 impl Context {
-    /// Builds a new context form the arguments to an `anchor`. This does not initalize the
-    /// `cumulative_substitution` field.
+    /// Builds a new context form the arguments to an `anchor`. This does not compute the
+    /// cumulative substitution.
     fn new(args: Vec<AnchorArg>) -> Self {
-        Self { args, cumulative_substitution: None }
+        Self {
+            args,
+            cumulative_substitution: OnceLock::new(),
+        }
     }
 }
 
-/// A tuple that will represent a single `Context` and allows a `Context` to be shared between threads.
-///
-/// `0`: Number of threads that will use this context.
-///
-/// `1`: Shareable and droppable slot for the context.
-type ContextInfo = (AtomicUsize, RwLock<Option<Context>>);
-
-/// Struct that implements a thread-shared context stack. That way, this stack
-/// tries to use an already existing global `Context` (built by another thread).
-/// If it was still not built, then the current thread is going to build this
-/// context so other threads can also use it.
+/// A slot that holds the context of a single subproof, shared between threads.
+#[derive(Debug, Default)]
+struct ContextSlot {
+    /// The number of activations of this context that have not yet ended, counting each time a
+    /// thread is scheduled to enter the subproof. When this reaches zero, no thread needs the
+    /// context anymore, and it is dropped.
+    pending: AtomicUsize,
+
+    /// The context itself. It is built by the first thread that enters the subproof, and is then
+    /// shared immutably by all others.
+    context: Mutex<Option<Arc<Context>>>,
+}
+
+/// An activation of a context in the stack of a single thread.
+#[derive(Debug)]
+struct Frame {
+    slot: usize,
+    context: Arc<Context>,
+
+    /// This thread's copy of the cumulative substitution. Since applying a substitution caches its
+    /// results, each thread applies its own copy, instead of locking the shared one.
+    substitution: Option<Substitution>,
+}
+
+/// Struct that implements a thread-shared context stack. The context of each subproof is built
+/// once, by the first thread that enters it, and is shared immutably by all other threads that are
+/// scheduled to enter the subproof. Each activation holds a reference to the context, so it can't
+/// be dropped while it is in use. The context is removed from the shared storage when the last
+/// scheduled activation ends.
 #[derive(Default, Debug)]
 pub struct ContextStack {
-    /// The context vector that is shared globally between all the threads.
-    /// The contexts storage is index based (which the index of each context is
-    /// defined by the anchor/subproof id obtained in the parser).
-    context_vec: Arc<Vec<ContextInfo>>,
-    /// The stack of contexts id (works just like a map to `context_vec`).
-    stack: Vec<usize>,
-    num_cumulative_calculated: usize,
+    /// The context slots that are shared globally between all the threads. The slots are index
+    /// based, and the index of each context is defined by the anchor/subproof id obtained in the
+    /// parser.
+    slots: Arc<Vec<ContextSlot>>,
+
+    /// The contexts activated by this thread.
+    stack: Vec<Frame>,
 }
 
 impl ContextStack {
@@ -42,20 +70,19 @@ impl ContextStack {
         Default::default()
     }
 
-    /// Creates an empty stack from contexts usage info (a vector indicating how
-    /// many threads are going to use each context).
-    pub fn from_usage(context_usage: &Vec<usize>) -> Self {
-        let mut context_vec: Arc<Vec<ContextInfo>> = Arc::new(vec![]);
-        let ctx_ref = Arc::get_mut(&mut context_vec).unwrap();
-
-        for &usage in context_usage {
-            ctx_ref.push((AtomicUsize::new(usage), RwLock::new(None)));
-        }
-
+    /// Creates an empty stack from contexts usage info (a vector indicating how many times each
+    /// context will be entered, by all threads combined).
+    pub fn from_usage(context_usage: &[usize]) -> Self {
+        let slots = context_usage
+            .iter()
+            .map(|&usage| ContextSlot {
+                pending: AtomicUsize::new(usage),
+                context: Mutex::new(None),
+            })
+            .collect();
         Self {
-            context_vec,
-            stack: vec![],
-            num_cumulative_calculated: 0,
+            slots: Arc::new(slots),
+            stack: Vec::new(),
         }
     }
 
@@ -63,9 +90,8 @@ impl ContextStack {
     /// already instantiated).
     pub fn from_previous(&self) -> Self {
         Self {
-            context_vec: self.context_vec.clone(),
-            stack: vec![],
-            num_cumulative_calculated: 0,
+            slots: self.slots.clone(),
+            stack: Vec::new(),
         }
     }
 
@@ -77,29 +103,34 @@ impl ContextStack {
         self.len() == 0
     }
 
-    pub fn last(&self) -> Option<RwLockReadGuard<Option<Context>>> {
-        self.stack
-            .last()
-            .map(|id| self.context_vec[*id].1.read().unwrap())
+    pub fn last(&self) -> Option<&Context> {
+        self.stack.last().map(|frame| frame.context.as_ref())
     }
 
-    pub fn last_mut(&mut self) -> Option<RwLockWriteGuard<Option<Context>>> {
-        self.stack
-            .last_mut()
-            .map(|id| self.context_vec[*id].1.write().unwrap())
+    /// Returns `true` if a variable with the given name is bound by any context in the stack,
+    /// either as a fixed variable or on the left-hand side of an assignment.
+    pub fn binds(&self, name: &str) -> bool {
+        self.stack.iter().any(|frame| {
+            frame.context.args.iter().any(|arg| match arg {
+                AnchorArg::Variable((n, _)) | AnchorArg::Assign((n, _), _) => n == name,
+            })
+        })
     }
 
     /// A function used to force the creation of a new context at the end of the
-    /// `context_vec`. This function should be called before a
+    /// `slots` vector. This function should be called before a
     /// `ContextStack::push` in a single thread operation. Since a single
     /// thread doesn't require a schedule balancing, then there is no info about
     /// how many contexts there are in the proof (and it's not needed since we
     /// can always add a new context at the end of the vector just like an usual
     /// stack)
     fn force_new_context(&mut self) -> usize {
-        let ctx_vec = Arc::get_mut(&mut self.context_vec).unwrap();
-        ctx_vec.push((AtomicUsize::new(1), RwLock::new(None)));
-        ctx_vec.len() - 1
+        let slots = Arc::get_mut(&mut self.slots).unwrap();
+        slots.push(ContextSlot {
+            pending: AtomicUsize::new(1),
+            context: Mutex::new(None),
+        });
+        slots.len() - 1
     }
 
     pub fn push(&mut self, args: &[AnchorArg]) {
@@ -108,115 +139,91 @@ impl ContextStack {
     }
 
     pub fn push_with_id(&mut self, args: &[AnchorArg], context_id: usize) {
-        // The write guard was yielded to this thread
-        if let Ok(mut ctx_write_guard) = self.context_vec[context_id].1.try_write() {
-            // It's the first thread trying to build this context. It will
-            // build this context at the context vec (accessible for all threads)
-            if ctx_write_guard.is_none() {
-                *ctx_write_guard = Some(Context::new(args.to_vec()));
-            }
-        }
-        // Adds this context in the stack
-        // Notice that even though the context is not ready for use, the write
-        // guard is still being held by some thread, then if this context is
-        // required at any moment, then we are assured it will wait until the
-        // fully context construction
-        self.stack.push(context_id);
+        // Only one thread can hold the lock, so the context is built exactly once. Other threads
+        // entering the same subproof just take a new reference to it
+        let context = self.slots[context_id]
+            .context
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| Arc::new(Context::new(args.to_vec())))
+            .clone();
+        self.stack.push(Frame {
+            slot: context_id,
+            context,
+            substitution: None,
+        });
     }
 
     pub fn pop(&mut self) {
-        use std::sync::atomic::Ordering;
-
-        if let Some(id) = self.stack.pop() {
-            let this_context = &self.context_vec[id];
-
-            let mut remaining_threads = this_context.0.load(Ordering::Acquire);
-            remaining_threads = remaining_threads
-                .checked_sub(1)
-                .expect("A thread tried to access a context not allocated for it.");
-
-            if remaining_threads == 0 {
-                // Drop this context since the last thread stopped using it
-                *this_context.1.write().unwrap() = None;
-            }
-            this_context.0.store(remaining_threads, Ordering::Release);
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        let slot = &self.slots[frame.slot];
+        let previous = slot
+            .pending
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+            .expect("A thread tried to access a context not allocated for it.");
+        if previous == 1 {
+            // This was the last scheduled activation, so we drop the shared context. Since the
+            // counter is updated atomically, exactly one thread gets here
+            *slot.context.lock().unwrap() = None;
         }
-
-        self.num_cumulative_calculated =
-            std::cmp::min(self.num_cumulative_calculated, self.stack.len());
     }
 
     fn catch_up_cumulative(&mut self, pool: &mut dyn TermPool, up_to: usize) {
-        for i in self.num_cumulative_calculated..std::cmp::max(up_to + 1, self.len()) {
-            // Requires read guard. Since the i-th context will be mutated far
-            // below this line, we first take the read guard here and then, when
-            // necessary, we require the write guard. This tries to avoid bigger
-            // overheads
-            let context_guard = self.context_vec[self.stack[i]].1.read().unwrap();
-            let current_context = context_guard.as_ref().unwrap();
-
-            let mut substitution = if i > 0 {
-                // Waits until OS allows to read this previous context. The code structure
-                // makes sure that this context, when released for reading, will be already
-                // instantiated since there are only 2 cases:
-                //  - This thread was responsible for building this previous context. Then
-                //      this context has already been built.
-                //  - Another thread was assigned to build this context. Then, it doesn't
-                //      matter if this other thread has already finished the process, the
-                //      current thread will have to wait until the guard is released.
-                let guard = self.context_vec[self.stack[i - 1]].1.read().unwrap();
-                guard
-                    .as_ref()
-                    .unwrap()
-                    .cumulative_substitution
-                    .clone()
-                    .unwrap()
-            } else {
-                Substitution::empty()
-            };
-
-            for a in &current_context.args {
-                match a {
-                    AnchorArg::Variable((name, sort)) => {
-                        let var_term = pool.add(Term::new_var(name, sort.clone()));
-                        substitution.remove(&var_term);
-                    }
-                    AnchorArg::Assign(var, value) => {
-                        let var_term = pool.add(var.clone().into());
-                        let new_value = substitution.apply(pool, value);
-                        // It is safe to unwrap here because we ensure by contruction that
-                        // `var_term` is a variable term, with he same sort as `value`
-                        substitution
-                            .insert(pool, var_term, new_value.clone())
-                            .unwrap();
-                    }
-                }
+        for i in 0..=up_to {
+            let (previous, rest) = self.stack.split_at_mut(i);
+            let frame = &mut rest[0];
+            if frame.substitution.is_some() {
+                continue;
             }
 
-            // Drop the read guard, and acquire a write guard
-            drop(context_guard);
-            let mut context_guard = self.context_vec[self.stack[i]].1.write().unwrap();
-            context_guard.as_mut().unwrap().cumulative_substitution = Some(substitution);
-            self.num_cumulative_calculated = i + 1;
+            // The cumulative substitutions of the enclosing contexts were already computed in the
+            // previous iterations
+            let shared = frame.context.cumulative_substitution.get_or_init(|| {
+                let mut substitution = match previous.last() {
+                    Some(p) => p.context.cumulative_substitution.get().unwrap().clone(),
+                    None => Substitution::empty(),
+                };
+                for a in &frame.context.args {
+                    match a {
+                        AnchorArg::Variable((name, sort)) => {
+                            let var_term = pool.add(Term::new_var(name, sort.clone()));
+                            substitution.remove(&var_term);
+                        }
+                        AnchorArg::Assign(var, value) => {
+                            let var_term = pool.add(var.clone().into());
+                            let new_value = substitution.apply(pool, value);
+                            // It is safe to unwrap here because we ensure by contruction that
+                            // `var_term` is a variable term, with he same sort as `value`
+                            substitution
+                                .insert(pool, var_term, new_value.clone())
+                                .unwrap();
+                        }
+                    }
+                }
+                substitution
+            });
+            frame.substitution = Some(shared.clone());
         }
     }
 
+    /// Returns the cumulative substitution of the innermost context, that is, the substitution
+    /// that `apply` uses. Returns `None` if the stack is empty.
+    pub fn cumulative_substitution(&mut self, pool: &mut dyn TermPool) -> Option<Substitution> {
+        let index = self.len().checked_sub(1)?;
+        self.catch_up_cumulative(pool, index);
+        self.stack[index].substitution.clone()
+    }
+
     pub fn apply_previous(&mut self, pool: &mut dyn TermPool, term: &Rc<Term>) -> Rc<Term> {
         if self.len() < 2 {
             term.clone()
         } else {
             let index = self.len() - 2;
             self.catch_up_cumulative(pool, index);
-            self.context_vec[self.stack[index]]
-                .1
-                .write()
-                .unwrap()
-                .as_mut()
-                .unwrap()
-                .cumulative_substitution
-                .as_mut()
-                .unwrap()
-                .apply(pool, term)
+            let substitution = self.stack[index].substitution.as_mut().unwrap();
+            substitution.apply(pool, term)
         }
     }
 
@@ -226,16 +233,153 @@ impl ContextStack {
         } else {
             let index = self.len() - 1;
             self.catch_up_cumulative(pool, index);
-            self.context_vec[self.stack[index]]
-                .1
-                .write()
-                .unwrap()
-                .as_mut()
-                .unwrap()
-                .cumulative_substitution
-                .as_mut()
-                .unwrap()
-                .apply(pool, term)
+            let substitution = self.stack[index].substitution.as_mut().unwrap();
+            substitution.apply(pool, term)
+        }
+    }
+}
+
+/// The context in effect at a given step of a proof.
+#[derive(Debug, Clone)]
+pub struct StepContext {
+    /// The ids of the subproofs that enclose the step, from outermost to innermost. Each subproof
+    /// is identified by the id of its last step, which is also the id given in its `anchor`.
+    pub anchors: Vec<String>,
+
+    /// The variables bound by the anchors of the enclosing subproofs, from outermost to innermost.
+    /// This includes both the fixed variables and the variables on the left-hand side of
+    /// assignments.
+    pub bound_variables: Vec<SortedVar>,
+
+    /// The composition of the substitutions introduced by the assignments in the enclosing
+    /// anchors. This is the substitution used by rules like `refl` and `cong` when checking the
+    /// step.
+    pub substitution: Substitution,
+}
+
+/// Returns the context in effect at the command with the given id, or `None` if there is no such
+/// command in the proof. If the id is that of the last step of a subproof, its own anchor is
+/// included in the context, since that step is checked inside the subproof.
+pub fn context_at(
+    pool: &mut dyn TermPool,
+    commands: &[ProofCommand],
+    id: &str,
+) -> Option<StepContext> {
+    fn find(
+        pool: &mut dyn TermPool,
+        commands: &[ProofCommand],
+        id: &str,
+        stack: &mut ContextStack,
+        anchors: &mut Vec<(String, Vec<SortedVar>)>,
+    ) -> Option<StepContext> {
+        for c in commands {
+            if let ProofCommand::Subproof(s) = c {
+                let bound = s
+                    .args
+                    .iter()
+                    .map(|a| match a {
+                        AnchorArg::Variable(var) | AnchorArg::Assign(var, _) => var.clone(),
+                    })
+                    .collect();
+                anchors.push((c.id().to_owned(), bound));
+                stack.push(&s.args);
+                if let Some(result) = find(pool, &s.commands, id, stack, anchors) {
+                    return Some(result);
+                }
+                stack.pop();
+                anchors.pop();
+            } else if c.id() == id {
+                return Some(StepContext {
+                    anchors: anchors.iter().map(|(id, _)| id.clone()).collect(),
+                    bound_variables: anchors.iter().flat_map(|(_, b)| b.clone()).collect(),
+                    substitution: stack
+                        .cumulative_substitution(pool)
+                        .unwrap_or_else(Substitution::empty),
+                });
+            }
+        }
+        None
+    }
+
+    find(
+        pool,
+        commands,
+        id,
+        &mut ContextStack::new(),
+        &mut Vec::new(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::pool::advanced::{ContextPool, LocalPool};
+    use std::sync::Barrier;
+
+    #[test]
+    fn test_concurrent_activations() {
+        const NUM_THREADS: usize = 8;
+
+        let mut pool = PrimitivePool::new();
+        let int_sort = pool.add(Term::Sort(Sort::Int));
+        let x = pool.add(Term::new_var("x", int_sort.clone()));
+        let one = pool.add(Term::new_int(1));
+        let outer = [AnchorArg::Assign(
+            ("x".to_owned(), int_sort.clone()),
+            one.clone(),
+        )];
+        let inner = [AnchorArg::Variable(("y".to_owned(), int_sort))];
+        let pool = Arc::new(pool);
+
+        // Every thread enters both subproofs once
+        let stack = ContextStack::from_usage(&[NUM_THREADS, NUM_THREADS]);
+        let context_pool = ContextPool::from_global(&pool);
+        let barrier = Barrier::new(NUM_THREADS);
+        let contexts: Vec<usize> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..NUM_THREADS)
+                .map(|_| {
+                    let mut stack = stack.from_previous();
+                    let mut local_pool = LocalPool::from_previous(&context_pool);
+                    let (barrier, outer, inner, x, one) = (&barrier, &outer, &inner, &x, &one);
+                    s.spawn(move || {
+                        stack.push_with_id(outer, 0);
+                        stack.push_with_id(inner, 1);
+
+                        // All threads hold both activations at the same time, and compute the
+                        // cumulative substitutions concurrently
+                        barrier.wait();
+                        assert_eq!(stack.apply(&mut local_pool, x), *one);
+                        assert_eq!(stack.apply_previous(&mut local_pool, x), *one);
+                        let context = stack.last().unwrap() as *const Context as usize;
+                        barrier.wait();
+
+                        stack.pop();
+                        stack.pop();
+                        assert!(stack.is_empty());
+                        context
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        // The context was built only once, and shared by all threads
+        assert!(contexts.iter().all(|&c| c == contexts[0]));
+
+        // Once the last activation ended, the contexts were dropped
+        for slot in stack.slots.iter() {
+            assert_eq!(slot.pending.load(Ordering::Acquire), 0);
+            assert!(slot.context.lock().unwrap().is_none());
         }
     }
+
+    #[test]
+    #[should_panic(expected = "not allocated")]
+    fn test_too_many_activations() {
+        let mut stack = ContextStack::from_usage(&[1]);
+        stack.push_with_id(&[], 0);
+        stack.pop();
+        stack.push_with_id(&[], 0);
+        stack.pop();
+    }
 }
@@ -0,0 +1,38 @@
+//! Extraction of the `check-sat-assuming` literals used by a proof.
+
+use super::{find_refutations, Polyeq, Problem, Proof, ProofCommand, Rc, Term};
+use indexmap::IndexSet;
+
+/// Returns the literals of the problem's `check-sat-assuming` command that are used by the proof,
+/// in the order they appear in the problem. These form an unsatisfiable core of the assumptions,
+/// together with the problem's assertions.
+///
+/// An assumption is used if an `assume` command in the root proof that is needed to derive the
+/// empty clause introduces it. Like when checking the proof, an `assume` command may introduce
+/// the assumption up to reordering of equalities and flattening of n-ary operators.
+pub fn assumption_core(problem: &Problem, proof: &Proof) -> Vec<Rc<Term>> {
+    if problem.assumptions.is_empty() {
+        return Vec::new();
+    }
+
+    let mut assumed = IndexSet::new();
+    for refutation in find_refutations(proof) {
+        for command in refutation.proof.commands {
+            if let ProofCommand::Assume { term, .. } = command {
+                assumed.insert(term);
+            }
+        }
+    }
+
+    problem
+        .assumptions
+        .iter()
+        .filter(|a| {
+            assumed.contains(*a)
+                || assumed
+                    .iter()
+                    .any(|t| Polyeq::new().mod_reordering(true).mod_nary(true).eq(t, a))
+        })
+        .cloned()
+        .collect()
+}
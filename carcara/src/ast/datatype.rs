@@ -0,0 +1,99 @@
+//! Algebraic datatypes, declared with the `declare-datatype` and `declare-datatypes` commands.
+//!
+//! A datatype `D` is represented by the sort `Sort::Atom("D", [])`. Its constructors and selectors
+//! are declared as regular functions, so applications of them are regular `Term::App` terms, and a
+//! nullary constructor is a variable of sort `D`. The testers, written `(_ is C)`, are represented
+//! by the `ParamOperator::DtTester` operator, whose single operator argument is the constructor.
+
+use super::{Rc, Sort, Term};
+use indexmap::IndexMap;
+
+/// A constructor of a datatype.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constructor {
+    /// The name of the constructor.
+    pub name: String,
+
+    /// The selectors of the constructor, each represented by its name and the sort of the field it
+    /// selects.
+    pub selectors: Vec<(String, Rc<Term>)>,
+}
+
+/// A datatype declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatatypeDef {
+    /// The name of the datatype.
+    pub name: String,
+
+    /// The constructors of the datatype, in the order they were declared.
+    pub constructors: Vec<Constructor>,
+}
+
+/// The datatypes declared in a problem, indexed by name.
+#[derive(Debug, Clone, Default)]
+pub struct Datatypes(IndexMap<String, DatatypeDef>);
+
+impl Datatypes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a datatype declaration.
+    pub fn insert(&mut self, def: DatatypeDef) {
+        self.0.insert(def.name.clone(), def);
+    }
+
+    /// Returns the declaration of the datatype with the given name, if it exists.
+    pub fn get(&self, name: &str) -> Option<&DatatypeDef> {
+        self.0.get(name)
+    }
+
+    /// Returns the declaration of the datatype of the given sort, if it is a datatype sort.
+    pub fn of_sort(&self, sort: &Sort) -> Option<&DatatypeDef> {
+        match sort {
+            Sort::Atom(name, args) if args.is_empty() => self.get(name),
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the datatype declarations, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &DatatypeDef> {
+        self.0.values()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// If `term` is a constructor symbol, that is, a variable whose name is a constructor and whose
+    /// sort is the sort of that constructor, returns the constructor and its datatype. A bound
+    /// variable with the same name and sort is the same term, so callers must check that the name
+    /// is not bound where `term` occurs.
+    pub fn as_constructor(&self, term: &Rc<Term>) -> Option<(&DatatypeDef, &Constructor)> {
+        let Term::Var(name, sort) = term.as_ref() else {
+            return None;
+        };
+        let result_sort = match sort.as_sort()? {
+            Sort::Function(sorts) => sorts.last()?.as_sort()?,
+            other => other,
+        };
+        let def = self.of_sort(result_sort)?;
+        let constructor = def.constructors.iter().find(|c| &c.name == name)?;
+        Some((def, constructor))
+    }
+
+    /// If `term` is a constructor application, or a nullary constructor, returns the constructor,
+    /// its datatype, and the arguments of the application.
+    pub fn as_constructor_app<'t>(
+        &self,
+        term: &'t Rc<Term>,
+    ) -> Option<(&DatatypeDef, &Constructor, &'t [Rc<Term>])> {
+        let (head, args) = match term.as_ref() {
+            Term::App(f, args) => (f, args.as_slice()),
+            Term::Var(..) => (term, [].as_slice()),
+            _ => return None,
+        };
+        let (def, constructor) = self.as_constructor(head)?;
+        (constructor.selectors.len() == args.len()).then_some((def, constructor, args))
+    }
+}
@@ -0,0 +1,128 @@
+//! Output dialects for the proof printer.
+//!
+//! Different tools consume slightly different variations of the Alethe format. All of the
+//! differences between these variations are centralized here, so the printer only needs to ask
+//! the dialect how each construct should be written.
+
+use std::{fmt, str::FromStr};
+
+/// A variation of the Alethe format that the printer can target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OutputDialect {
+    /// The Alethe format, as described in version 1.2 of the specification. This is the default.
+    #[default]
+    Alethe12,
+
+    /// The format used by older versions of veriT, before the Alethe specification was stabilized.
+    VeritLegacy,
+
+    /// The flavor of Alethe produced and consumed by cvc5.
+    Cvc5,
+}
+
+/// Rules whose names changed between the legacy veriT format and Alethe. Each entry is a pair of
+/// the Alethe name and the legacy name.
+pub const VERIT_LEGACY_RULE_NAMES: &[(&str, &str)] = &[
+    ("ac_simp", "tmp_AC_simp"),
+    ("bfun_elim", "tmp_bfun_elim"),
+    ("nary_elim", "tmp_nary_elim"),
+    ("distinct_elim", "tmp_distinct_elim"),
+];
+
+/// Rules that were introduced after the legacy veriT format, and that older versions of veriT do
+/// not understand.
+const VERIT_LEGACY_UNSUPPORTED_RULES: &[&str] = &[
+    "all_simplify",
+    "rare_rewrite",
+    "hole",
+    "lia_generic",
+    "bind_let",
+    "la_mult_pos",
+    "la_mult_neg",
+];
+
+/// Rules that are not part of the Alethe specification, and that only Carcara understands.
+const EXTENSION_RULES: &[&str] = &["la_trichotomy"];
+
+impl OutputDialect {
+    /// All the available dialects.
+    pub const ALL: [OutputDialect; 3] = [Self::Alethe12, Self::VeritLegacy, Self::Cvc5];
+
+    /// Returns the name of the dialect, as accepted by its `FromStr` implementation.
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputDialect::Alethe12 => "alethe-1.2",
+            OutputDialect::VeritLegacy => "verit-legacy",
+            OutputDialect::Cvc5 => "cvc5",
+        }
+    }
+
+    /// Returns `true` if assign-style anchor arguments should include the sort hint, that is, if
+    /// they should be written as `(:= (<symbol> <sort>) <term>)` instead of `(:= <symbol> <term>)`.
+    pub fn sorted_assign_args(self) -> bool {
+        self != OutputDialect::VeritLegacy
+    }
+
+    /// Returns `true` if arithmetic constants must be written in strict SMT-LIB notation, that is,
+    /// without using the GMP notation for negative numbers and fractions.
+    pub fn smt_lib_numerals(self) -> bool {
+        matches!(self, OutputDialect::VeritLegacy | OutputDialect::Cvc5)
+    }
+
+    /// Returns `true` if assumptions should be written as steps that use the `input` rule, that
+    /// is, as `(step <id> (cl <term>) :rule input)` instead of `(assume <id> <term>)`. Older
+    /// versions of veriT introduce input formulas this way.
+    pub fn assumes_as_input_steps(self) -> bool {
+        self == OutputDialect::VeritLegacy
+    }
+
+    /// Returns `true` if tools that consume this dialect are expected to understand `lambda`
+    /// terms. Older versions of veriT only accept first-order terms.
+    pub fn supports_lambda(self) -> bool {
+        self != OutputDialect::VeritLegacy
+    }
+
+    /// Returns the keyword used to list the premises of a step. Older versions of veriT use
+    /// `:clauses` instead of `:premises`.
+    pub fn premises_keyword(self) -> &'static str {
+        match self {
+            OutputDialect::Alethe12 | OutputDialect::Cvc5 => "premises",
+            OutputDialect::VeritLegacy => "clauses",
+        }
+    }
+
+    /// Returns the name that should be printed for `rule` in this dialect.
+    pub fn rule_name(self, rule: &str) -> &str {
+        if self == OutputDialect::VeritLegacy {
+            if let Some((_, legacy)) = VERIT_LEGACY_RULE_NAMES.iter().find(|(r, _)| *r == rule) {
+                return legacy;
+            }
+        }
+        rule
+    }
+
+    /// Returns `true` if tools that consume this dialect are expected to understand `rule`.
+    pub fn supports_rule(self, rule: &str) -> bool {
+        if EXTENSION_RULES.contains(&rule) {
+            return false;
+        }
+        match self {
+            OutputDialect::VeritLegacy => !VERIT_LEGACY_UNSUPPORTED_RULES.contains(&rule),
+            OutputDialect::Alethe12 | OutputDialect::Cvc5 => true,
+        }
+    }
+}
+
+impl fmt::Display for OutputDialect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for OutputDialect {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|d| d.name() == s).ok_or(())
+    }
+}
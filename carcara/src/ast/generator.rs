@@ -0,0 +1,156 @@
+//! Enumeration of small well-sorted terms over a signature.
+//!
+//! This is mostly useful to generate inputs for rule tests and seeds for fuzzing. Instead of
+//! writing a handful of test terms by hand, a test can enumerate every term of a given sort up to
+//! some depth, and check that the rule behaves as expected for all of them.
+
+use super::{Operator, Rc, Sort, Term, TermPool};
+use indexmap::IndexMap;
+
+/// An operator in the signature, with the sorts of its arguments and the sort of its result.
+#[derive(Debug, Clone)]
+struct Signature {
+    op: Operator,
+    args: Vec<Sort>,
+    result: Sort,
+}
+
+/// A generator that enumerates all well-sorted terms over a signature, up to a given depth.
+///
+/// The depth of a leaf term is zero, and the depth of an application is one more than the
+/// maximum depth of its arguments. Terms are generated in order of increasing depth, and each
+/// term is generated only once.
+#[derive(Debug, Clone, Default)]
+pub struct TermGenerator {
+    leaves: Vec<Rc<Term>>,
+    operators: Vec<Signature>,
+    limit: Option<usize>,
+}
+
+impl TermGenerator {
+    /// Constructs a new generator with an empty signature.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a generator over the boolean connectives `not`, `and`, `or`, `=>` and `=`, with
+    /// the constants `true` and `false` and the given boolean variables as leaves. The n-ary
+    /// connectives are only applied to two arguments.
+    pub fn boolean(pool: &mut dyn TermPool, variables: &[&str]) -> Self {
+        let bool_sort = pool.add(Term::Sort(Sort::Bool));
+        let mut result = Self::new().leaf(pool.bool_true()).leaf(pool.bool_false());
+        for &name in variables {
+            result = result.leaf(pool.add(Term::new_var(name, bool_sort.clone())));
+        }
+        result = result.operator(Operator::Not, vec![Sort::Bool], Sort::Bool);
+        for op in [
+            Operator::And,
+            Operator::Or,
+            Operator::Implies,
+            Operator::Equals,
+        ] {
+            result = result.operator(op, vec![Sort::Bool, Sort::Bool], Sort::Bool);
+        }
+        result
+    }
+
+    /// Adds a leaf term to the signature. Its sort is computed when generating terms.
+    pub fn leaf(mut self, term: Rc<Term>) -> Self {
+        self.leaves.push(term);
+        self
+    }
+
+    /// Adds an operator to the signature. Polymorphic operators, like `=`, must be added once for
+    /// each instantiation of their argument sorts.
+    pub fn operator(mut self, op: Operator, args: Vec<Sort>, result: Sort) -> Self {
+        self.operators.push(Signature { op, args, result });
+        self
+    }
+
+    /// Sets the maximum number of terms, of any sort, that the generator may build. Once this
+    /// limit is reached, generation stops, even if the maximum depth was not reached. Since the
+    /// number of terms grows very quickly with the depth, this is useful to keep tests fast.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Returns all terms of sort `sort` with depth at most `max_depth`, in order of increasing
+    /// depth.
+    pub fn generate(
+        &self,
+        pool: &mut dyn TermPool,
+        sort: &Sort,
+        max_depth: usize,
+    ) -> Vec<Rc<Term>> {
+        let limit = self.limit.unwrap_or(usize::MAX);
+
+        // Maps each generated term to its sort and depth
+        let mut generated: IndexMap<Rc<Term>, (Sort, usize)> = IndexMap::new();
+        for leaf in &self.leaves {
+            if generated.len() >= limit {
+                break;
+            }
+            let leaf_sort = pool.sort(leaf).as_sort().unwrap().clone();
+            generated.entry(leaf.clone()).or_insert((leaf_sort, 0));
+        }
+
+        'outer: for depth in 1..=max_depth {
+            let previous: Vec<_> = generated
+                .iter()
+                .map(|(t, (s, d))| (t.clone(), s.clone(), *d))
+                .collect();
+            for signature in &self.operators {
+                let candidates: Vec<Vec<_>> = signature
+                    .args
+                    .iter()
+                    .map(|arg_sort| previous.iter().filter(|(_, s, _)| s == arg_sort).collect())
+                    .collect();
+                if candidates.iter().any(Vec::is_empty) {
+                    continue;
+                }
+
+                // We iterate over the cartesian product of the candidates, keeping only the
+                // combinations in which at least one argument has the previous depth. Otherwise,
+                // the term would have already been generated
+                let mut indices = vec![0; candidates.len()];
+                loop {
+                    let args: Vec<_> = indices
+                        .iter()
+                        .zip(&candidates)
+                        .map(|(&i, c)| c[i])
+                        .collect();
+                    if args.iter().any(|(_, _, d)| *d == depth - 1) {
+                        let args = args.iter().map(|(t, _, _)| t.clone()).collect();
+                        let term = pool.add(Term::Op(signature.op, args));
+                        generated
+                            .entry(term)
+                            .or_insert((signature.result.clone(), depth));
+                        if generated.len() >= limit {
+                            break 'outer;
+                        }
+                    }
+
+                    // Advance to the next combination
+                    let mut k = 0;
+                    while k < indices.len() {
+                        indices[k] += 1;
+                        if indices[k] < candidates[k].len() {
+                            break;
+                        }
+                        indices[k] = 0;
+                        k += 1;
+                    }
+                    if k == indices.len() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        generated
+            .into_iter()
+            .filter_map(|(t, (s, _))| (&s == sort).then_some(t))
+            .collect()
+    }
+}
@@ -71,6 +71,16 @@ impl<'a> ProofIter<'a> {
         }
     }
 
+    /// Returns `true` if the premise index of the form (depth, index in subproof) refers to a command
+    /// that can be referenced by the current command. That is, a command that came before it in
+    /// the current subproof or in one of the enclosing subproofs. Commands inside subproofs that
+    /// were already closed can only be referenced through the subproof itself.
+    pub fn is_in_scope(&self, (depth, index): (usize, usize)) -> bool {
+        // For each frame in the stack, the command being visited (either the current command or
+        // the subproof that contains it) is at position `i - 1`
+        self.stack.get(depth).is_some_and(|&(i, _)| index + 1 < i)
+    }
+
     /// Returns the command referenced by a premise index of the form (depth, index in subproof).
     /// This method may panic if the premise index does not refer to a valid command.
     pub fn get_premise(&self, (depth, index): (usize, usize)) -> &ProofCommand {
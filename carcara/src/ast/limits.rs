@@ -0,0 +1,171 @@
+//! Guards against building terms that are too large or too deep.
+//!
+//! Some rules and elaboration passes build new terms whose size depends on the input in ways that
+//! are easy to exploit, such as `nary_elim`, which builds a quadratic number of disequalities, or
+//! `bfun_elim`, which expands quantifiers into exponentially many instances. On adversarial proofs,
+//! these can exhaust the available memory. The utilities in this module allow such code to stop
+//! early, with a clean error, when a configurable limit is exceeded.
+
+use super::{Rc, Term};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum TermLimitError {
+    #[error("term has more than {0} nodes")]
+    TooLarge(usize),
+
+    #[error("term is more than {0} levels deep")]
+    TooDeep(usize),
+}
+
+/// Limits on the size and depth of new terms. The size of a term is its number of distinct
+/// subterms, that is, the number of nodes in its DAG representation. By default, there are no
+/// limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TermLimits {
+    /// The maximum size of a term.
+    pub max_size: Option<usize>,
+
+    /// The maximum depth of a term. Constants and variables have depth zero.
+    pub max_depth: Option<usize>,
+}
+
+impl TermLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_size(mut self, value: Option<usize>) -> Self {
+        self.max_size = value;
+        self
+    }
+
+    pub fn max_depth(mut self, value: Option<usize>) -> Self {
+        self.max_depth = value;
+        self
+    }
+
+    /// Returns `true` if neither the size nor the depth of terms is limited.
+    pub fn is_unlimited(&self) -> bool {
+        self.max_size.is_none() && self.max_depth.is_none()
+    }
+
+    /// Checks that a term is within the limits. The traversal stops as soon as a limit is
+    /// exceeded, so this is cheap even for very large terms.
+    pub fn check(&self, term: &Rc<Term>) -> Result<(), TermLimitError> {
+        if let Some(max) = self.max_size {
+            check_size(term, max)?;
+        }
+        if let Some(max) = self.max_depth {
+            check_depth(term, max)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that a term with `n` direct arguments would be within the size limit. This should be
+    /// used before building a term whose number of arguments depends on the input, to avoid
+    /// allocating them in the first place.
+    pub fn check_len(&self, n: usize) -> Result<(), TermLimitError> {
+        match self.max_size {
+            Some(max) if n >= max => Err(TermLimitError::TooLarge(max)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that a term nested `depth` levels deep would be within the depth limit. Like
+    /// [`TermLimits::check_len`], this should be used before building the term.
+    pub fn check_nesting(&self, depth: usize) -> Result<(), TermLimitError> {
+        match self.max_depth {
+            Some(max) if depth > max => Err(TermLimitError::TooDeep(max)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns a [`TermBudget`] that allows building at most as many terms as the size limit.
+    pub fn budget(&self) -> TermBudget {
+        TermBudget {
+            remaining: self.max_size,
+            limit: self.max_size,
+        }
+    }
+}
+
+/// A budget for the number of terms built by an algorithm that constructs many terms
+/// incrementally, such as an expansion that may grow exponentially. Each new term must be charged
+/// to the budget, which fails once it is exhausted.
+#[derive(Debug, Clone)]
+pub struct TermBudget {
+    remaining: Option<usize>,
+    limit: Option<usize>,
+}
+
+impl TermBudget {
+    /// Charges `n` new terms to the budget.
+    pub fn charge(&mut self, n: usize) -> Result<(), TermLimitError> {
+        let (Some(remaining), Some(limit)) = (&mut self.remaining, self.limit) else {
+            return Ok(());
+        };
+        match remaining.checked_sub(n) {
+            Some(r) => {
+                *remaining = r;
+                Ok(())
+            }
+            None => Err(TermLimitError::TooLarge(limit)),
+        }
+    }
+}
+
+fn children(term: &Term) -> Vec<&Rc<Term>> {
+    match term {
+        Term::Const(_) | Term::Var(..) | Term::Sort(_) => Vec::new(),
+        Term::App(f, args) => std::iter::once(f).chain(args).collect(),
+        Term::Op(_, args) => args.iter().collect(),
+        Term::Binder(_, _, inner) => vec![inner],
+        Term::Let(bindings, inner) => bindings
+            .iter()
+            .map(|(_, value)| value)
+            .chain(std::iter::once(inner))
+            .collect(),
+        Term::ParamOp { op_args, args, .. } => op_args.iter().chain(args).collect(),
+    }
+}
+
+fn check_size(term: &Rc<Term>, max: usize) -> Result<(), TermLimitError> {
+    let mut seen = HashSet::new();
+    let mut stack = vec![term];
+    while let Some(term) = stack.pop() {
+        if !seen.insert(term) {
+            continue;
+        }
+        if seen.len() > max {
+            return Err(TermLimitError::TooLarge(max));
+        }
+        stack.extend(children(term));
+    }
+    Ok(())
+}
+
+fn check_depth(term: &Rc<Term>, max: usize) -> Result<(), TermLimitError> {
+    // To avoid stack overflows in exactly the terms we are trying to guard against, we compute the
+    // depth of each subterm iteratively, in postorder
+    let mut depths: HashMap<&Rc<Term>, usize> = HashMap::new();
+    let mut stack = vec![(term, false)];
+    while let Some((term, is_done)) = stack.pop() {
+        if depths.contains_key(term) {
+            continue;
+        }
+        let children = children(term);
+        if is_done {
+            let depth = children.iter().map(|c| depths[c] + 1).max().unwrap_or(0);
+            if depth > max {
+                return Err(TermLimitError::TooDeep(max));
+            }
+            depths.insert(term, depth);
+        } else {
+            stack.push((term, true));
+            stack.extend(children.into_iter().map(|c| (c, false)));
+        }
+    }
+    Ok(())
+}
@@ -172,15 +172,40 @@ macro_rules! match_term {
     (@GET_VARIANT bbterm)   => { $crate::ast::Operator::BvBbTerm };
     (@GET_VARIANT bvult)    => { $crate::ast::Operator::BvULt };
     (@GET_VARIANT bvadd)    => { $crate::ast::Operator::BvAdd };
+    (@GET_VARIANT bvnot)    => { $crate::ast::Operator::BvNot };
+    (@GET_VARIANT bvand)    => { $crate::ast::Operator::BvAnd };
+    (@GET_VARIANT bvor)     => { $crate::ast::Operator::BvOr };
+    (@GET_VARIANT bvxor)    => { $crate::ast::Operator::BvXor };
+    (@GET_VARIANT concat)   => { $crate::ast::Operator::BvConcat };
+    (@GET_VARIANT bv2nat)   => { $crate::ast::Operator::Bv2Nat };
+    (@GET_VARIANT select)   => { $crate::ast::Operator::Select };
+    (@GET_VARIANT store)    => { $crate::ast::Operator::Store };
+
+    (@GET_VARIANT setunion)     => { $crate::ast::Operator::SetUnion };
+    (@GET_VARIANT setinter)     => { $crate::ast::Operator::SetInter };
+    (@GET_VARIANT setminus)     => { $crate::ast::Operator::SetMinus };
+    (@GET_VARIANT setmember)    => { $crate::ast::Operator::SetMember };
+    (@GET_VARIANT setsingleton) => { $crate::ast::Operator::SetSingleton };
+
+    (@GET_VARIANT exp)    => { $crate::ast::Operator::Exp };
+    (@GET_VARIANT sin)    => { $crate::ast::Operator::Sin };
+    (@GET_VARIANT cos)    => { $crate::ast::Operator::Cos };
+
+    (@GET_VARIANT seqconcat) => { $crate::ast::Operator::SeqConcat };
+    (@GET_VARIANT seqlen)    => { $crate::ast::Operator::SeqLen };
+    (@GET_VARIANT sequnit)   => { $crate::ast::Operator::SeqUnit };
 
     (@GET_VARIANT extract)     => { $crate::ast::ParamOperator::BvExtract };
     (@GET_VARIANT bit_of)      => { $crate::ast::ParamOperator::BvBitOf };
     (@GET_VARIANT zero_extend) => { $crate::ast::ParamOperator::ZeroExtend };
     (@GET_VARIANT sign_extend) => { $crate::ast::ParamOperator::SignExtend };
+    (@GET_VARIANT divisible)   => { $crate::ast::ParamOperator::Divisible };
+    (@GET_VARIANT int2bv)      => { $crate::ast::ParamOperator::Int2Bv };
 
     (@GET_VARIANT strconcat) => { $crate::ast::Operator::StrConcat };
     (@GET_VARIANT strsubstr) => { $crate::ast::Operator::Substring };
     (@GET_VARIANT strlen)    => { $crate::ast::Operator::StrLen };
+    (@GET_VARIANT strtocode) => { $crate::ast::Operator::StrToCode };
 
     (@GET_VARIANT strinre)    => { $crate::ast::Operator::StrInRe };
     (@GET_VARIANT reinter)    => { $crate::ast::Operator::ReIntersection };
@@ -4,8 +4,15 @@
 
 #[macro_use]
 mod macros;
+mod anonymizer;
+mod clause;
 mod context;
+mod core;
+mod datatype;
+mod dialect;
+mod generator;
 mod iter;
+mod limits;
 mod node;
 mod polyeq;
 pub mod pool;
@@ -13,22 +20,39 @@ pub(crate) mod printer;
 mod problem;
 mod proof;
 mod rc;
+mod refutation;
 mod substitution;
 mod term;
 #[cfg(test)]
 mod tests;
+mod tptp;
+mod unused;
 
-pub use context::{Context, ContextStack};
+pub use anonymizer::anonymize;
+pub use clause::{Clause, ClauseIndex};
+pub use context::{context_at, Context, ContextStack, StepContext};
+pub use core::assumption_core;
+pub use datatype::{Constructor, DatatypeDef, Datatypes};
+pub use dialect::OutputDialect;
+pub(crate) use dialect::VERIT_LEGACY_RULE_NAMES;
+pub use generator::TermGenerator;
 pub use iter::ProofIter;
-pub use node::{ProofNode, StepNode, SubproofNode};
-pub use polyeq::{alpha_equiv, polyeq, Polyeq, PolyeqComparable, PolyeqConfig};
-pub use pool::{PrimitivePool, TermPool};
-pub use printer::{print_proof, USE_SHARING_IN_TERM_DISPLAY};
+pub use limits::{TermBudget, TermLimitError, TermLimits};
+pub use node::{max_live_commands, ProofNode, StepNode, SubproofNode};
+pub use polyeq::{
+    alpha_equiv, clear_shared_cache, polyeq, Polyeq, PolyeqCacheStats, PolyeqComparable,
+    PolyeqConfig,
+};
+pub use pool::{NaryOptions, PrimitivePool, TermPool};
+pub use printer::{print_proof, write_proof, USE_SHARING_IN_TERM_DISPLAY};
 pub use problem::*;
 pub use proof::*;
 pub use rc::Rc;
-pub use substitution::{Substitution, SubstitutionError};
+pub use refutation::{find_refutations, Refutation};
+pub use substitution::{match_term, Substitution, SubstitutionError};
 pub use term::{Binder, BindingList, Constant, Operator, ParamOperator, Sort, SortedVar, Term};
+pub use tptp::write_tptp_proof;
+pub use unused::{find_unused_declarations, strip_unused_declarations, UnusedDeclarations};
 
 #[cfg(test)]
 pub(crate) use node::compare_nodes;
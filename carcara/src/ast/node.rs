@@ -1,4 +1,5 @@
 use super::*;
+use std::collections::HashMap;
 
 /// An alternative, graph-based representation for an Alethe proof.
 ///
@@ -120,7 +121,44 @@ impl ProofNode {
 
 impl Rc<ProofNode> {
     pub fn into_commands(&self) -> Vec<ProofCommand> {
-        proof_node_to_list(self)
+        proof_node_to_list(self, None)
+    }
+
+    /// Converts the proof to a list of commands, ordering them to reduce the number of commands
+    /// that are "live" at any point, that is, that were already introduced but are still used as a
+    /// premise by a later command. This is useful for consumers that process the proof as a stream,
+    /// and can forget each command after its last use.
+    ///
+    /// The premises of each step are emitted right before it, starting with the premise whose
+    /// derivation needs the most live commands, in the style of the Sethi-Ullman algorithm. Since
+    /// premises may be shared between steps, this is a heuristic, and doesn't always find the
+    /// optimal order. See also [`max_live_commands`].
+    pub fn into_commands_min_live(&self) -> Vec<ProofCommand> {
+        let mut needs: HashMap<Rc<ProofNode>, usize> = HashMap::new();
+        self.traverse(|node| {
+            let need = match node.as_ref() {
+                ProofNode::Assume { .. } => 1,
+                ProofNode::Step(s) => {
+                    let mut premise_needs: Vec<_> = s
+                        .premises
+                        .iter()
+                        .chain(&s.discharge)
+                        .chain(&s.previous_step)
+                        .map(|p| needs[p])
+                        .collect();
+                    premise_needs.sort_unstable_by(|a, b| b.cmp(a));
+                    premise_needs
+                        .iter()
+                        .enumerate()
+                        .map(|(i, n)| n + i)
+                        .max()
+                        .unwrap_or(1)
+                }
+                ProofNode::Subproof(s) => needs[&s.last_step],
+            };
+            needs.insert(node.clone(), need);
+        });
+        proof_node_to_list(self, Some(&needs))
     }
 
     /// Visits every node of the proof, in postorder, and calls `visit_func` on them.
@@ -220,12 +258,21 @@ pub struct StepNode {
     /// The step arguments, given via the `:args` attribute.
     pub args: Vec<Rc<Term>>,
 
+    /// The variables assigned by arguments that are assignments. See [`ProofStep::arg_vars`].
+    pub arg_vars: Vec<Option<SortedVar>>,
+
     /// The local premises that this step discharges, given via the `:discharge` attribute.
     pub discharge: Vec<Rc<ProofNode>>,
 
     /// If this step is the last step in a subproof, this holds the (implicitly referenced) previous
     /// step in the subproof.
     pub previous_step: Option<Rc<ProofNode>>,
+
+    /// If this step was introduced during elaboration, this records where it came from.
+    pub origin: Option<StepOrigin>,
+
+    /// Any other attributes given to the step, which are not interpreted by Carcara.
+    pub attributes: Vec<StepAttribute>,
 }
 
 /// A subproof.
@@ -302,8 +349,11 @@ fn proof_list_to_node(commands: Vec<ProofCommand>, root_id: Option<&str>) -> Opt
                     rule: s.rule,
                     premises,
                     args: s.args,
+                    arg_vars: s.arg_vars,
                     discharge,
                     previous_step,
+                    origin: s.origin,
+                    attributes: s.attributes,
                 })
             }
             Some(ProofCommand::Subproof(s)) => {
@@ -353,8 +403,59 @@ fn proof_list_to_node(commands: Vec<ProofCommand>, root_id: Option<&str>) -> Opt
 }
 
 /// Converts a `ProofNode` into a list of proof commands.
-fn proof_node_to_list(root: &Rc<ProofNode>) -> Vec<ProofCommand> {
-    use std::collections::{HashMap, HashSet};
+/// Returns the maximum number of commands in the root proof that are live at the same time. A
+/// command is live from the point it is introduced until its last use as a premise, including uses
+/// by commands inside subproofs.
+pub fn max_live_commands(commands: &[ProofCommand]) -> usize {
+    // Every use of a root command by a command nested in a subproof counts as a use by the root
+    // command that contains that subproof
+    fn mark_uses(command: &ProofCommand, root_index: usize, last_use: &mut [usize]) {
+        match command {
+            ProofCommand::Assume { .. } => (),
+            ProofCommand::Step(s) => {
+                for &(depth, i) in s.premises.iter().chain(&s.discharge) {
+                    if depth == 0 {
+                        last_use[i] = last_use[i].max(root_index);
+                    }
+                }
+            }
+            ProofCommand::Subproof(s) => {
+                for inner in &s.commands {
+                    mark_uses(inner, root_index, last_use);
+                }
+            }
+        }
+    }
+
+    let mut last_use = vec![0; commands.len()];
+    for (root_index, command) in commands.iter().enumerate() {
+        mark_uses(command, root_index, &mut last_use);
+    }
+
+    // Sweep over the commands, counting how many intervals `[i, last_use[i])` contain each point
+    let mut deltas = vec![0isize; commands.len() + 1];
+    for (i, &last) in last_use.iter().enumerate() {
+        if last > i {
+            deltas[i] += 1;
+            deltas[last] -= 1;
+        }
+    }
+    let mut live = 0;
+    let mut max = 0;
+    for d in deltas {
+        live += d;
+        max = max.max(live);
+    }
+    max as usize
+}
+
+/// Converts a proof node back to a list of commands. If `needs` is given, the premises of each
+/// step are emitted in decreasing order of need.
+fn proof_node_to_list(
+    root: &Rc<ProofNode>,
+    needs: Option<&HashMap<Rc<ProofNode>, usize>>,
+) -> Vec<ProofCommand> {
+    use std::collections::HashSet;
 
     let mut stack: Vec<Vec<ProofCommand>> = vec![Vec::new()];
 
@@ -382,8 +483,17 @@ fn proof_node_to_list(root: &Rc<ProofNode>) -> Vec<ProofCommand> {
                     todo.push((previous, false));
                 }
 
-                let premises_and_discharge = s.premises.iter().chain(s.discharge.iter()).rev();
-                todo.extend(premises_and_discharge.map(|node| (node, false)));
+                let mut premises_and_discharge: Vec<_> =
+                    s.premises.iter().chain(s.discharge.iter()).collect();
+                if let Some(needs) = needs {
+                    premises_and_discharge.sort_by(|a, b| needs[*b].cmp(&needs[*a]));
+                }
+                todo.extend(
+                    premises_and_discharge
+                        .into_iter()
+                        .rev()
+                        .map(|node| (node, false)),
+                );
                 continue;
             }
             ProofNode::Step(s) => {
@@ -395,7 +505,10 @@ fn proof_node_to_list(root: &Rc<ProofNode>) -> Vec<ProofCommand> {
                     rule: s.rule.clone(),
                     premises,
                     args: s.args.clone(),
+                    arg_vars: s.arg_vars.clone(),
                     discharge,
+                    origin: s.origin.clone(),
+                    attributes: s.attributes.clone(),
                 })
             }
             ProofNode::Subproof(s) if !is_done => {
@@ -12,8 +12,13 @@ use rug::Rational;
 use super::{
     AnchorArg, BindingList, Constant, Operator, ProofCommand, ProofStep, Rc, Sort, Subproof, Term,
 };
-use crate::utils::HashMapStack;
-use std::time::{Duration, Instant};
+use crate::utils::{HashMapStack, LruCache};
+use std::{
+    cell::RefCell,
+    fmt,
+    ops::AddAssign,
+    time::{Duration, Instant},
+};
 
 /// An helper enum that allow a construction of lists with easy differentiation over the nature of the term
 /// (String constant or other). Therefore, is easy to manipulate, attach and detach terms of lists of
@@ -49,7 +54,10 @@ pub trait PolyeqComparable {
 ///
 /// This function records how long it takes to run, and adds that duration to the `time` argument.
 pub fn polyeq(a: &Rc<Term>, b: &Rc<Term>, time: &mut Duration) -> bool {
-    Polyeq::new().mod_reordering(true).eq_with_time(a, b, time)
+    Polyeq::new()
+        .mod_reordering(true)
+        .shared_cache(true)
+        .eq_with_time(a, b, time)
 }
 
 /// Similar to `polyeq`, but instead compares terms for alpha equivalence.
@@ -64,9 +72,97 @@ pub fn alpha_equiv(a: &Rc<Term>, b: &Rc<Term>, time: &mut Duration) -> bool {
     Polyeq::new()
         .mod_reordering(true)
         .alpha_equiv(true)
+        .shared_cache(true)
         .eq_with_time(a, b, time)
 }
 
+/// The maximum number of entries in the shared deep-equality cache of each thread.
+const SHARED_CACHE_CAPACITY: usize = 1 << 16;
+
+thread_local! {
+    /// The results of previous comparisons between terms, shared by all `Polyeq` comparators in
+    /// the current thread that have the shared cache enabled. Unlike the cache in each `Polyeq`,
+    /// this also remembers negative results.
+    static SHARED_CACHE: RefCell<SharedCache> = RefCell::new(SharedCache::new());
+}
+
+struct SharedCache {
+    // The last component of the key encodes the comparator configuration, since the same two
+    // terms may be equal under one configuration but not under another
+    results: LruCache<(Rc<Term>, Rc<Term>, u8), bool>,
+    stats: PolyeqCacheStats,
+}
+
+impl SharedCache {
+    fn new() -> Self {
+        Self {
+            results: LruCache::new(SHARED_CACHE_CAPACITY),
+            stats: PolyeqCacheStats::default(),
+        }
+    }
+}
+
+/// Statistics about the deep-equality cache shared across steps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PolyeqCacheStats {
+    /// The number of comparisons whose result was found in the cache.
+    pub hits: u64,
+
+    /// The number of comparisons whose result had to be computed.
+    pub misses: u64,
+}
+
+impl PolyeqCacheStats {
+    /// Returns the statistics for the shared cache of the current thread, accumulated since the
+    /// thread started.
+    pub fn current() -> Self {
+        SHARED_CACHE.with(|c| c.borrow().stats)
+    }
+
+    /// Returns the statistics accumulated since `earlier` was taken.
+    pub fn since(self, earlier: Self) -> Self {
+        Self {
+            hits: self.hits - earlier.hits,
+            misses: self.misses - earlier.misses,
+        }
+    }
+
+    /// Returns the fraction of comparisons that were answered by the cache, or zero if there were
+    /// no comparisons.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+impl AddAssign for PolyeqCacheStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.hits += rhs.hits;
+        self.misses += rhs.misses;
+    }
+}
+
+impl fmt::Display for PolyeqCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "polyeq cache hits:         {}", self.hits)?;
+        writeln!(f, "    misses:               {}", self.misses)?;
+        writeln!(
+            f,
+            "    hit rate:             {:.02}%",
+            100.0 * self.hit_rate()
+        )
+    }
+}
+
+/// Clears the shared deep-equality cache of the current thread. The statistics are kept.
+pub fn clear_shared_cache() {
+    SHARED_CACHE.with(|c| c.borrow_mut().results.clear());
+}
+
 /// Configuration for a `Polyeq`.
 ///
 /// - If `is_mod_reordering` is `true`, the comparator will compare terms modulo reordering of
@@ -121,6 +217,7 @@ pub struct Polyeq {
     de_bruijn_map: Option<DeBruijnMap>,
     is_mod_nary: bool,
     is_mod_string_concat: bool,
+    use_shared_cache: bool,
 
     current_depth: usize,
     max_depth: usize,
@@ -146,6 +243,7 @@ impl Polyeq {
             de_bruijn_map: config.is_alpha_equivalence.then(DeBruijnMap::new),
             is_mod_nary: config.is_mod_nary,
             is_mod_string_concat: config.is_mod_string_concat,
+            use_shared_cache: false,
             current_depth: 0,
             max_depth: 0,
         }
@@ -171,6 +269,21 @@ impl Polyeq {
         self
     }
 
+    /// If `value` is `true`, the results of top-level comparisons between terms are stored in, and
+    /// looked up from, a bounded cache shared by all comparators in the current thread. This avoids
+    /// recomputing the same comparisons in different steps.
+    pub fn shared_cache(mut self, value: bool) -> Self {
+        self.use_shared_cache = value;
+        self
+    }
+
+    fn config_key(&self) -> u8 {
+        u8::from(self.is_mod_reordering)
+            | u8::from(self.de_bruijn_map.is_some()) << 1
+            | u8::from(self.is_mod_nary) << 2
+            | u8::from(self.is_mod_string_concat) << 3
+    }
+
     pub fn eq<T>(&mut self, a: &T, b: &T) -> bool
     where
         T: PolyeqComparable + ?Sized,
@@ -482,6 +595,30 @@ impl PolyeqComparable for Rc<Term> {
             return true;
         }
 
+        // Only top-level comparisons use the shared cache, since the result of comparing subterms
+        // may depend on the bound variables in scope
+        if comp.use_shared_cache && comp.current_depth == 0 && !possibly_renamed {
+            let key = (a.clone(), b.clone(), comp.config_key());
+            let cached = SHARED_CACHE.with(|c| {
+                let mut c = c.borrow_mut();
+                let cached = c.results.get(&key).copied();
+                if cached.is_some() {
+                    c.stats.hits += 1;
+                } else {
+                    c.stats.misses += 1;
+                }
+                cached
+            });
+            if let Some(result) = cached {
+                return result;
+            }
+            comp.use_shared_cache = false;
+            let result = PolyeqComparable::eq(comp, a, b);
+            comp.use_shared_cache = true;
+            SHARED_CACHE.with(|c| c.borrow_mut().results.insert(key, result));
+            return result;
+        }
+
         comp.current_depth += 1;
         comp.max_depth = std::cmp::max(comp.max_depth, comp.current_depth);
         let result = comp.eq(a.as_ref(), b.as_ref());
@@ -629,12 +766,19 @@ impl PolyeqComparable for Sort {
             | (Sort::Real, Sort::Real)
             | (Sort::String, Sort::String)
             | (Sort::RegLan, Sort::RegLan)
+            | (Sort::RoundingMode, Sort::RoundingMode)
             | (Sort::RareList, Sort::RareList)
             | (Sort::Type, Sort::Type) => true,
             (Sort::Array(x_a, y_a), Sort::Array(x_b, y_b)) => {
                 comp.eq(x_a, x_b) && comp.eq(y_a, y_b)
             }
-            (Sort::BitVec(a), Sort::BitVec(b)) => a == b,
+            (Sort::Set(a), Sort::Set(b)) | (Sort::Seq(a), Sort::Seq(b)) => comp.eq(a, b),
+            (Sort::BitVec(a), Sort::BitVec(b)) | (Sort::FiniteField(a), Sort::FiniteField(b)) => {
+                a == b
+            }
+            (Sort::FloatingPoint(e_a, s_a), Sort::FloatingPoint(e_b, s_b)) => {
+                e_a == e_b && s_a == s_b
+            }
             _ => false,
         }
     }
@@ -703,6 +847,7 @@ impl PolyeqComparable for ProofStep {
             && a.rule == b.rule
             && a.premises == b.premises
             && comp.eq(&a.args, &b.args)
+            && a.arg_vars == b.arg_vars
             && a.discharge == b.discharge
     }
 }
@@ -817,9 +962,51 @@ fn nary_case(op: Operator) -> Option<NaryCase> {
             None
         }
 
+        // Transcendentals
+        Operator::Exp
+        | Operator::Sin
+        | Operator::Cos
+        | Operator::Tan
+        | Operator::Csc
+        | Operator::Sec
+        | Operator::Cot
+        | Operator::ArcSin
+        | Operator::ArcCos
+        | Operator::ArcTan
+        | Operator::ArcCsc
+        | Operator::ArcSec
+        | Operator::ArcCot
+        | Operator::Sqrt
+        | Operator::Pi => None,
+
         // Arrays
         Operator::Select | Operator::Store => None,
 
+        // Sets
+        Operator::SetUnion | Operator::SetInter | Operator::SetMinus => Some(NaryCase::LeftAssoc),
+        Operator::SetInsert => Some(NaryCase::RightAssoc),
+        Operator::SetComplement
+        | Operator::SetMember
+        | Operator::SetSubset
+        | Operator::SetSingleton
+        | Operator::SetCard => None,
+
+        // Sequences
+        Operator::SeqConcat => Some(NaryCase::LeftAssoc),
+        Operator::SeqLen
+        | Operator::SeqExtract
+        | Operator::SeqAt
+        | Operator::SeqNth
+        | Operator::SeqUnit
+        | Operator::SeqUpdate
+        | Operator::SeqContains
+        | Operator::SeqPrefixOf
+        | Operator::SeqSuffixOf
+        | Operator::SeqIndexOf
+        | Operator::SeqReplace
+        | Operator::SeqReplaceAll
+        | Operator::SeqRev => None,
+
         // Strings
         Operator::StrConcat
         | Operator::StrLessThan
@@ -844,6 +1031,11 @@ fn nary_case(op: Operator) -> Option<NaryCase> {
         | Operator::StrFromCode
         | Operator::StrToInt
         | Operator::StrFromInt
+        | Operator::StrUpdate
+        | Operator::StrRev
+        | Operator::StrToLower
+        | Operator::StrToUpper
+        | Operator::IndexOfRe
         | Operator::StrToRe
         | Operator::StrInRe
         | Operator::ReNone
@@ -885,6 +1077,45 @@ fn nary_case(op: Operator) -> Option<NaryCase> {
         | Operator::BvSGt
         | Operator::BvSGe
         | Operator::BvBbTerm
-        | Operator::RareList => None,
+        | Operator::Bv2Nat
+        | Operator::UbvToInt
+        | Operator::SbvToInt => None,
+
+        // Floating-point
+        Operator::RoundNearestTiesToEven
+        | Operator::RoundNearestTiesToAway
+        | Operator::RoundTowardPositive
+        | Operator::RoundTowardNegative
+        | Operator::RoundTowardZero
+        | Operator::Fp
+        | Operator::FpAbs
+        | Operator::FpNeg
+        | Operator::FpAdd
+        | Operator::FpSub
+        | Operator::FpMul
+        | Operator::FpDiv
+        | Operator::FpFma
+        | Operator::FpSqrt
+        | Operator::FpRem
+        | Operator::FpRoundToIntegral
+        | Operator::FpMin
+        | Operator::FpMax
+        | Operator::FpIsNormal
+        | Operator::FpIsSubnormal
+        | Operator::FpIsZero
+        | Operator::FpIsInfinite
+        | Operator::FpIsNaN
+        | Operator::FpIsNegative
+        | Operator::FpIsPositive
+        | Operator::FpToReal => None,
+        Operator::FpLeq | Operator::FpLt | Operator::FpGeq | Operator::FpGt | Operator::FpEq => {
+            Some(NaryCase::Chainable)
+        }
+
+        // Finite fields
+        Operator::FfAdd | Operator::FfMul => Some(NaryCase::LeftAssoc),
+        Operator::FfNeg | Operator::FfBitSum => None,
+
+        Operator::RareList => None,
     }
 }
@@ -65,6 +65,11 @@ impl TermPool for ContextPool {
             .unwrap()
             .free_vars_with_priorities(term, [&self.global_pool])
     }
+
+    // Patterns are only added by the parser, so they can only be in the global pool
+    fn patterns(&self, term: &Rc<Term>) -> Vec<Vec<Rc<Term>>> {
+        self.global_pool.patterns(term)
+    }
 }
 
 // =========================================================================
@@ -96,6 +101,14 @@ impl LocalPool {
             inner: PrimitivePool::new(),
         }
     }
+
+    /// Returns the number of distinct terms currently stored in the pool, including the ones
+    /// stored in the context and global pools.
+    pub fn num_terms(&self) -> usize {
+        self.ctx_pool.global_pool.num_terms()
+            + self.ctx_pool.inner.read().unwrap().num_terms()
+            + self.inner.num_terms()
+    }
 }
 
 impl TermPool for LocalPool {
@@ -133,4 +146,8 @@ impl TermPool for LocalPool {
             ],
         )
     }
+
+    fn patterns(&self, term: &Rc<Term>) -> Vec<Vec<Rc<Term>>> {
+        self.ctx_pool.patterns(term)
+    }
 }
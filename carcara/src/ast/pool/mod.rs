@@ -45,6 +45,96 @@ pub trait TermPool {
     /// This method uses a cache, so there is no additional cost to computing the free variables of
     /// a term multiple times.
     fn free_vars(&mut self, term: &Rc<Term>) -> IndexSet<Rc<Term>>;
+
+    /// Returns the instantiation patterns that were attached to the given term, using the
+    /// `:pattern` attribute, when it was parsed. Each pattern is a list of terms, that together
+    /// form a multi-trigger. Pools that don't record patterns return no patterns.
+    fn patterns(&self, _term: &Rc<Term>) -> Vec<Vec<Rc<Term>>> {
+        Vec::new()
+    }
+
+    /// Builds the equality between two terms.
+    fn mk_eq(&mut self, a: Rc<Term>, b: Rc<Term>) -> Rc<Term> {
+        self.add(Term::Op(Operator::Equals, vec![a, b]))
+    }
+
+    /// Builds the conjunction of the given terms, without any flattening or duplicate removal. If
+    /// there is only one term, it is returned unchanged, and if there are none, this returns
+    /// `true`.
+    fn mk_and(&mut self, args: Vec<Rc<Term>>) -> Rc<Term> {
+        self.mk_nary(Operator::And, args, NaryOptions::new())
+    }
+
+    /// Builds the disjunction of the given terms, without any flattening or duplicate removal. If
+    /// there is only one term, it is returned unchanged, and if there are none, this returns
+    /// `false`.
+    fn mk_or(&mut self, args: Vec<Rc<Term>>) -> Rc<Term> {
+        self.mk_nary(Operator::Or, args, NaryOptions::new())
+    }
+
+    /// Builds an application of the n-ary operator `op` to the given terms, normalizing them
+    /// according to `options`.
+    ///
+    /// For `and` and `or`, if only one term remains after normalization, it is returned unchanged,
+    /// and if none remain, this returns `true` or `false`, respectively. For other operators, the
+    /// application is always built as is.
+    fn mk_nary(&mut self, op: Operator, args: Vec<Rc<Term>>, options: NaryOptions) -> Rc<Term> {
+        let mut args = if options.flatten {
+            let mut flattened = Vec::with_capacity(args.len());
+            flatten_into(op, args, &mut flattened);
+            flattened
+        } else {
+            args
+        };
+        if options.remove_duplicates {
+            let mut seen = std::collections::HashSet::with_capacity(args.len());
+            args.retain(|a| seen.insert(a.clone()));
+        }
+        match (op, args.len()) {
+            (Operator::And | Operator::Or, 0) => self.bool_constant(op == Operator::And),
+            (Operator::And | Operator::Or, 1) => args.pop().unwrap(),
+            _ => self.add(Term::Op(op, args)),
+        }
+    }
+}
+
+/// Options for building n-ary operator terms with [`TermPool::mk_nary`].
+///
+/// - If `flatten` is `true`, arguments that are themselves applications of the same operator are
+///   replaced by their arguments, recursively. This should only be used with associative operators.
+/// - If `remove_duplicates` is `true`, only the first occurrence of each argument is kept. This
+///   should only be used with idempotent operators, like `and` and `or`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NaryOptions {
+    pub flatten: bool,
+    pub remove_duplicates: bool,
+}
+
+impl NaryOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn flatten(mut self, value: bool) -> Self {
+        self.flatten = value;
+        self
+    }
+
+    pub fn remove_duplicates(mut self, value: bool) -> Self {
+        self.remove_duplicates = value;
+        self
+    }
+}
+
+fn flatten_into(op: Operator, args: Vec<Rc<Term>>, result: &mut Vec<Rc<Term>>) {
+    for a in args {
+        match a.as_ref() {
+            Term::Op(inner, inner_args) if *inner == op => {
+                flatten_into(op, inner_args.clone(), result);
+            }
+            _ => result.push(a),
+        }
+    }
 }
 
 /// A structure to store and manage all allocated terms.
@@ -61,6 +151,7 @@ pub struct PrimitivePool {
     pub(crate) storage: Storage,
     pub(crate) free_vars_cache: IndexMap<Rc<Term>, IndexSet<Rc<Term>>>,
     pub(crate) sorts_cache: IndexMap<Rc<Term>, Rc<Term>>,
+    pub(crate) patterns: IndexMap<Rc<Term>, Vec<Vec<Rc<Term>>>>,
 }
 
 impl PrimitivePool {
@@ -70,6 +161,20 @@ impl PrimitivePool {
         Self::default()
     }
 
+    /// Returns the number of distinct terms currently stored in the pool.
+    pub fn num_terms(&self) -> usize {
+        self.storage.len()
+    }
+
+    /// Records an instantiation pattern for the given term. Adding the same pattern more than once
+    /// has no effect.
+    pub fn add_pattern(&mut self, term: &Rc<Term>, pattern: Vec<Rc<Term>>) {
+        let patterns = self.patterns.entry(term.clone()).or_default();
+        if !patterns.contains(&pattern) {
+            patterns.push(pattern);
+        }
+    }
+
     /// Computes the sort of a term and adds it to the sort cache.
     fn compute_sort(&mut self, term: &Rc<Term>) -> Rc<Term> {
         if let Some(sort) = self.sorts_cache.get(term) {
@@ -82,6 +187,7 @@ impl PrimitivePool {
                 Constant::Real(_) => Sort::Real,
                 Constant::String(_) => Sort::String,
                 Constant::BitVec(_, w) => Sort::BitVec(w.clone()),
+                Constant::FiniteField(_, p) => Sort::FiniteField(p.clone()),
             },
             Term::Var(_, sort) => sort.as_sort().unwrap().clone(),
             Term::Op(op, args) => match op {
@@ -99,6 +205,11 @@ impl PrimitivePool {
                 | Operator::LessEq
                 | Operator::GreaterEq
                 | Operator::IsInt
+                | Operator::SetMember
+                | Operator::SetSubset
+                | Operator::SeqContains
+                | Operator::SeqPrefixOf
+                | Operator::SeqSuffixOf
                 | Operator::StrLessThan
                 | Operator::StrLessEq
                 | Operator::PrefixOf
@@ -115,7 +226,19 @@ impl PrimitivePool {
                 | Operator::BvSGt
                 | Operator::BvSGe
                 | Operator::BvShl
-                | Operator::BvLShr => Sort::Bool,
+                | Operator::BvLShr
+                | Operator::FpLeq
+                | Operator::FpLt
+                | Operator::FpGeq
+                | Operator::FpGt
+                | Operator::FpEq
+                | Operator::FpIsNormal
+                | Operator::FpIsSubnormal
+                | Operator::FpIsZero
+                | Operator::FpIsInfinite
+                | Operator::FpIsNaN
+                | Operator::FpIsNegative
+                | Operator::FpIsPositive => Sort::Bool,
                 Operator::BvAdd
                 | Operator::BvSub
                 | Operator::BvNot
@@ -142,6 +265,7 @@ impl PrimitivePool {
                 }
                 Operator::BvComp => Sort::BitVec(Integer::ONE.into()),
                 Operator::BvBbTerm => Sort::BitVec(Integer::from(args.len())),
+                Operator::Bv2Nat | Operator::UbvToInt | Operator::SbvToInt => Sort::Int,
                 Operator::BvConcat => {
                     let mut total_width = Integer::ZERO;
                     for arg in args {
@@ -166,15 +290,55 @@ impl PrimitivePool {
                     }
                 }
                 Operator::RealDiv | Operator::ToReal => Sort::Real,
+                Operator::Exp
+                | Operator::Sin
+                | Operator::Cos
+                | Operator::Tan
+                | Operator::Csc
+                | Operator::Sec
+                | Operator::Cot
+                | Operator::ArcSin
+                | Operator::ArcCos
+                | Operator::ArcTan
+                | Operator::ArcCsc
+                | Operator::ArcSec
+                | Operator::ArcCot
+                | Operator::Sqrt
+                | Operator::Pi => Sort::Real,
                 Operator::IntDiv | Operator::Mod | Operator::Abs | Operator::ToInt => Sort::Int,
                 Operator::Select => match self.compute_sort(&args[0]).as_sort().unwrap() {
                     Sort::Array(_, y) => y.as_sort().unwrap().clone(),
                     _ => unreachable!(),
                 },
                 Operator::Store => self.compute_sort(&args[0]).as_sort().unwrap().clone(),
-                Operator::StrLen | Operator::IndexOf | Operator::StrToCode | Operator::StrToInt => {
-                    Sort::Int
+                Operator::SetUnion
+                | Operator::SetInter
+                | Operator::SetMinus
+                | Operator::SetComplement
+                | Operator::SetInsert => {
+                    let set = args.last().unwrap();
+                    self.compute_sort(set).as_sort().unwrap().clone()
                 }
+                Operator::SetSingleton => Sort::Set(self.compute_sort(&args[0])),
+                Operator::SetCard => Sort::Int,
+                Operator::SeqConcat
+                | Operator::SeqExtract
+                | Operator::SeqAt
+                | Operator::SeqUpdate
+                | Operator::SeqReplace
+                | Operator::SeqReplaceAll
+                | Operator::SeqRev => self.compute_sort(&args[0]).as_sort().unwrap().clone(),
+                Operator::SeqUnit => Sort::Seq(self.compute_sort(&args[0])),
+                Operator::SeqNth => match self.compute_sort(&args[0]).as_sort().unwrap() {
+                    Sort::Seq(x) => x.as_sort().unwrap().clone(),
+                    _ => unreachable!(),
+                },
+                Operator::SeqLen | Operator::SeqIndexOf => Sort::Int,
+                Operator::StrLen
+                | Operator::IndexOf
+                | Operator::IndexOfRe
+                | Operator::StrToCode
+                | Operator::StrToInt => Sort::Int,
                 Operator::StrConcat
                 | Operator::CharAt
                 | Operator::Substring
@@ -183,7 +347,11 @@ impl PrimitivePool {
                 | Operator::ReplaceRe
                 | Operator::ReplaceReAll
                 | Operator::StrFromCode
-                | Operator::StrFromInt => Sort::String,
+                | Operator::StrFromInt
+                | Operator::StrUpdate
+                | Operator::StrRev
+                | Operator::StrToLower
+                | Operator::StrToUpper => Sort::String,
                 Operator::StrToRe
                 | Operator::ReNone
                 | Operator::ReAll
@@ -197,6 +365,37 @@ impl PrimitivePool {
                 | Operator::ReKleeneCross
                 | Operator::ReOption
                 | Operator::ReRange => Sort::RegLan,
+                Operator::RoundNearestTiesToEven
+                | Operator::RoundNearestTiesToAway
+                | Operator::RoundTowardPositive
+                | Operator::RoundTowardNegative
+                | Operator::RoundTowardZero => Sort::RoundingMode,
+                Operator::Fp => {
+                    let [Sort::BitVec(e), Sort::BitVec(m)] = [&args[1], &args[2]]
+                        .map(|a| self.compute_sort(a).as_sort().unwrap().clone())
+                    else {
+                        unreachable!()
+                    };
+                    Sort::FloatingPoint(e, m + Integer::ONE)
+                }
+                Operator::FpAbs
+                | Operator::FpNeg
+                | Operator::FpRem
+                | Operator::FpMin
+                | Operator::FpMax => self.compute_sort(&args[0]).as_sort().unwrap().clone(),
+                Operator::FpAdd
+                | Operator::FpSub
+                | Operator::FpMul
+                | Operator::FpDiv
+                | Operator::FpFma
+                | Operator::FpSqrt
+                | Operator::FpRoundToIntegral => {
+                    self.compute_sort(&args[1]).as_sort().unwrap().clone()
+                }
+                Operator::FpToReal => Sort::Real,
+                Operator::FfAdd | Operator::FfMul | Operator::FfNeg | Operator::FfBitSum => {
+                    self.compute_sort(&args[0]).as_sort().unwrap().clone()
+                }
                 Operator::RareList => Sort::RareList,
             },
             Term::App(f, _) => {
@@ -231,12 +430,46 @@ impl PrimitivePool {
                         };
                         Sort::BitVec(extension_width + bv_width)
                     }
+                    ParamOperator::BvRepeat => {
+                        let times = op_args[0].as_integer().unwrap();
+                        let Sort::BitVec(bv_width) =
+                            self.compute_sort(&args[0]).as_sort().unwrap().clone()
+                        else {
+                            unreachable!()
+                        };
+                        Sort::BitVec(times * bv_width)
+                    }
+                    ParamOperator::BvRotateLeft | ParamOperator::BvRotateRight => {
+                        self.compute_sort(&args[0]).as_sort().unwrap().clone()
+                    }
                     ParamOperator::BvConst => unreachable!(
                         "bv const should be handled by the parser and transfromed into a constant"
                     ),
-                    ParamOperator::BvBitOf => Sort::Bool,
+                    ParamOperator::BvBitOf | ParamOperator::DtTester | ParamOperator::Divisible => {
+                        Sort::Bool
+                    }
                     ParamOperator::RePower | ParamOperator::ReLoop => Sort::RegLan,
-                    ParamOperator::ArrayConst => op_args[0].as_sort().unwrap().clone(),
+                    ParamOperator::FpPosZero
+                    | ParamOperator::FpNegZero
+                    | ParamOperator::FpPosInfinity
+                    | ParamOperator::FpNegInfinity
+                    | ParamOperator::FpNaN
+                    | ParamOperator::ToFp
+                    | ParamOperator::ToFpUnsigned => {
+                        let e = op_args[0].as_integer().unwrap();
+                        let s = op_args[1].as_integer().unwrap();
+                        Sort::FloatingPoint(e, s)
+                    }
+                    ParamOperator::Int2Bv | ParamOperator::IntToBv => {
+                        Sort::BitVec(op_args[0].as_integer().unwrap())
+                    }
+                    ParamOperator::FpToUbv | ParamOperator::FpToSbv => {
+                        Sort::BitVec(op_args[0].as_integer().unwrap())
+                    }
+                    ParamOperator::ArrayConst
+                    | ParamOperator::SetEmpty
+                    | ParamOperator::SetUniverse
+                    | ParamOperator::SeqEmpty => op_args[0].as_sort().unwrap().clone(),
                 };
                 sort
             }
@@ -354,4 +587,8 @@ impl TermPool for PrimitivePool {
     fn free_vars(&mut self, term: &Rc<Term>) -> IndexSet<Rc<Term>> {
         self.free_vars_with_priorities(term, [])
     }
+
+    fn patterns(&self, term: &Rc<Term>) -> Vec<Vec<Rc<Term>>> {
+        self.patterns.get(term).cloned().unwrap_or_default()
+    }
 }
@@ -55,6 +55,10 @@ impl Storage {
         self.0.get(term).map(|t| &t.0)
     }
 
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
     // This method is only necessary for the hash consing tests
     #[cfg(test)]
     pub fn into_vec(self) -> Vec<Rc<Term>> {
@@ -5,7 +5,8 @@ use crate::{
     parser::Token,
     utils::{is_symbol_character, DedupIterator},
 };
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
+use rug::{ops::Pow, Integer, Rational};
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
@@ -20,14 +21,48 @@ pub static USE_SHARING_IN_TERM_DISPLAY: AtomicBool = AtomicBool::new(false);
 /// If `use_sharing` is `true`, terms that are used multiple times will make use of sharing. The
 /// first time a novel term appears, it receives a unique name using the `:named` attribute. After
 /// that, any occurrence of that term will simply use this name, instead of printing the whole term.
+///
+/// The `dialect` controls which variation of the Alethe format is printed. See [`OutputDialect`]
+/// for more details.
 pub fn print_proof(
     pool: &mut PrimitivePool,
     prelude: &ProblemPrelude,
     proof: &Proof,
     use_sharing: bool,
+    dialect: OutputDialect,
 ) -> io::Result<()> {
+    let unsupported: IndexSet<_> = proof
+        .iter()
+        .filter_map(|c| match c {
+            ProofCommand::Step(s) if !dialect.supports_rule(&s.rule) => Some(s.rule.as_str()),
+            _ => None,
+        })
+        .collect();
+    for rule in unsupported {
+        log::warn!(
+            "rule '{}' may not be supported by the '{}' dialect",
+            rule,
+            dialect
+        );
+    }
+
     let mut stdout = io::stdout();
-    AlethePrinter::new(pool, prelude, use_sharing, &mut stdout).write_proof(proof)
+    let mut printer = AlethePrinter::new(pool, prelude, use_sharing, &mut stdout);
+    printer.dialect = dialect;
+    printer.smt_lib_strict = dialect.smt_lib_numerals();
+    printer.write_proof(proof)
+}
+
+/// Writes a proof to `dest`, using the default dialect. See [`print_proof`] for the meaning of
+/// `use_sharing`.
+pub fn write_proof(
+    pool: &mut PrimitivePool,
+    prelude: &ProblemPrelude,
+    dest: &mut dyn io::Write,
+    proof: &Proof,
+    use_sharing: bool,
+) -> io::Result<()> {
+    AlethePrinter::new(pool, prelude, use_sharing, dest).write_proof(proof)
 }
 
 /// Given the conclusion clause of a `lia_generic` step, this method will write to `dest` the
@@ -50,6 +85,26 @@ pub fn write_lia_smt_instance(
     printer.write_lia_smt_instance(clause)
 }
 
+/// Writes each term as an SMT-LIB `assert` command to `dest`. Like in
+/// [`write_lia_smt_instance`], the output is strictly compliant with SMT-LIB.
+pub fn write_smt_assertions(
+    pool: &mut PrimitivePool,
+    prelude: &ProblemPrelude,
+    dest: &mut dyn io::Write,
+    terms: &[Rc<Term>],
+    use_sharing: bool,
+) -> io::Result<()> {
+    let mut printer = AlethePrinter::new(pool, prelude, use_sharing, dest);
+    printer.term_sharing_variable_prefix = "p_";
+    printer.smt_lib_strict = true;
+    for term in terms {
+        write!(printer.inner, "(assert ")?;
+        term.print_with_sharing(&mut printer)?;
+        writeln!(printer.inner, ")")?;
+    }
+    Ok(())
+}
+
 trait PrintProof {
     fn write_proof(&mut self, proof: &Proof) -> io::Result<()>;
 }
@@ -149,6 +204,8 @@ struct AlethePrinter<'a> {
     global_vars: HashSet<Rc<Term>>,
     defined_constants: HashMap<Rc<Term>, String>,
     smt_lib_strict: bool,
+    dialect: OutputDialect,
+    warned_lambda: bool,
 }
 
 impl<'a> PrintProof for AlethePrinter<'a> {
@@ -169,6 +226,11 @@ impl<'a> PrintProof for AlethePrinter<'a> {
         let mut iter = proof.iter();
         while let Some(command) = iter.next() {
             match command {
+                ProofCommand::Assume { id, term } if self.dialect.assumes_as_input_steps() => {
+                    write!(self.inner, "(step {} (cl ", quote_symbol(id))?;
+                    term.print_with_sharing(self)?;
+                    write!(self.inner, ") :rule input)")?;
+                }
                 ProofCommand::Assume { id, term } => {
                     write!(self.inner, "(assume {} ", quote_symbol(id))?;
                     term.print_with_sharing(self)?;
@@ -194,11 +256,7 @@ impl<'a> PrintProof for AlethePrinter<'a> {
                                     write!(self.inner, ")")?;
                                 }
                                 AnchorArg::Assign(var, value) => {
-                                    write!(self.inner, "(:= ")?;
-                                    var.print_with_sharing(self)?;
-                                    write!(self.inner, " ")?;
-                                    value.print_with_sharing(self)?;
-                                    write!(self.inner, ")")?;
+                                    self.write_assignment(var, value)?;
                                 }
                             }
                         }
@@ -239,6 +297,8 @@ impl<'a> AlethePrinter<'a> {
             global_vars: global_variables,
             defined_constants: HashMap::new(),
             smt_lib_strict: false,
+            dialect: OutputDialect::default(),
+            warned_lambda: false,
         }
     }
 
@@ -306,6 +366,9 @@ impl<'a> AlethePrinter<'a> {
             }
             Term::Sort(sort) => write!(self.inner, "{}", sort),
             Term::Binder(binder, bindings, term) => {
+                if *binder == Binder::Lambda && !self.dialect.supports_lambda() {
+                    self.warn_unsupported_lambda();
+                }
                 write!(self.inner, "({} ", binder)?;
                 bindings.print_with_sharing(self)?;
                 write!(self.inner, " ")?;
@@ -323,7 +386,11 @@ impl<'a> AlethePrinter<'a> {
                 if !args.is_empty() {
                     write!(self.inner, "(")?;
                 }
-                write!(self.inner, "(_ {}", op)?;
+                if op.is_qualified() {
+                    write!(self.inner, "(as {}", op)?;
+                } else {
+                    write!(self.inner, "(_ {}", op)?;
+                }
                 self.write_s_expr_tail(op_args)?;
                 if !args.is_empty() {
                     self.write_s_expr_tail(args)?;
@@ -333,6 +400,32 @@ impl<'a> AlethePrinter<'a> {
         }
     }
 
+    /// Warns that the proof uses lambda terms, which the current dialect does not support. This
+    /// is only done once per printer.
+    fn warn_unsupported_lambda(&mut self) {
+        if !self.warned_lambda {
+            log::warn!(
+                "lambda terms may not be supported by the '{}' dialect",
+                self.dialect
+            );
+            self.warned_lambda = true;
+        }
+    }
+
+    /// Writes an assignment argument, of an anchor or a step. Depending on the dialect, the
+    /// assigned variable may be written without its sort.
+    fn write_assignment(&mut self, var: &SortedVar, value: &Rc<Term>) -> io::Result<()> {
+        write!(self.inner, "(:= ")?;
+        if self.dialect.sorted_assign_args() {
+            var.print_with_sharing(self)?;
+        } else {
+            write!(self.inner, "{}", quote_symbol(&var.0))?;
+        }
+        write!(self.inner, " ")?;
+        value.print_with_sharing(self)?;
+        write!(self.inner, ")")
+    }
+
     fn write_step(&mut self, iter: &mut ProofIter, step: &ProofStep) -> io::Result<()> {
         write!(self.inner, "(step {} (cl", quote_symbol(&step.id))?;
 
@@ -342,11 +435,16 @@ impl<'a> AlethePrinter<'a> {
         }
         write!(self.inner, ")")?;
 
-        write!(self.inner, " :rule {}", step.rule)?;
+        write!(
+            self.inner,
+            " :rule {}",
+            quote_symbol(self.dialect.rule_name(&step.rule))
+        )?;
 
         if let [head, tail @ ..] = step.premises.as_slice() {
             let id = iter.get_premise(*head).id();
-            write!(self.inner, " :premises ({}", quote_symbol(id))?;
+            let keyword = self.dialect.premises_keyword();
+            write!(self.inner, " :{} ({}", keyword, quote_symbol(id))?;
             for premise in tail {
                 let id = iter.get_premise(*premise).id();
                 write!(self.inner, " {}", quote_symbol(id))?;
@@ -354,19 +452,23 @@ impl<'a> AlethePrinter<'a> {
             write!(self.inner, ")")?;
         }
 
-        if let [head, tail @ ..] = step.args.as_slice() {
+        if !step.args.is_empty() {
             write!(self.inner, " :args (")?;
-            head.print_with_sharing(self)?;
-            for arg in tail {
-                write!(self.inner, " ")?;
-                arg.print_with_sharing(self)?;
+            for (i, arg) in step.args.iter().enumerate() {
+                if i > 0 {
+                    write!(self.inner, " ")?;
+                }
+                match step.assigned_var(i) {
+                    Some(var) => self.write_assignment(var, arg)?,
+                    None => arg.print_with_sharing(self)?,
+                }
             }
             write!(self.inner, ")")?;
         }
 
         if let [head, tail @ ..] = step.discharge.as_slice() {
             let id = iter.get_premise(*head).id();
-            write!(self.inner, " :discharge ({}", id)?;
+            write!(self.inner, " :discharge ({}", quote_symbol(id))?;
             for discharge in tail {
                 let id = iter.get_premise(*discharge).id();
                 write!(self.inner, " {}", quote_symbol(id))?;
@@ -374,6 +476,22 @@ impl<'a> AlethePrinter<'a> {
             write!(self.inner, ")")?;
         }
 
+        if let Some(origin) = &step.origin {
+            write!(
+                self.inner,
+                " :origin ({} {})",
+                quote_symbol(&origin.step),
+                quote_symbol(&origin.pass)
+            )?;
+        }
+
+        for attribute in &step.attributes {
+            write!(self.inner, " :{}", attribute.keyword)?;
+            if let Some(value) = &attribute.value {
+                write!(self.inner, " {}", value)?;
+            }
+        }
+
         write!(self.inner, ")")?;
         Ok(())
     }
@@ -400,20 +518,35 @@ where
     write!(f, ")")
 }
 
+/// Quotes a symbol with `|...|` if it can't be printed as a simple symbol.
+///
+/// SMT-LIB has no way of escaping the `|` and `\` characters in a quoted symbol, so symbols that
+/// contain them can't be printed. The parser never produces such symbols, so this function panics if
+/// it encounters one.
 fn quote_symbol(symbol: &str) -> Cow<str> {
     use crate::parser::Reserved;
     use std::str::FromStr;
 
-    assert!(symbol.chars().all(|c| c != '|'));
+    assert!(
+        symbol.chars().all(|c| c != '|' && c != '\\'),
+        "symbol '{}' cannot be represented in SMT-LIB",
+        symbol,
+    );
 
     // Any symbol that:
     // - is an empty string,
     // - starts with a digit,
+    // - starts with `-` followed by a digit, which would be read as a negative numeral,
     // - is a reserved word, or
-    // - contains non-symbol characters
+    // - contains non-symbol characters (including whitespace and any non-ASCII character)
     // must be quoted
-    if symbol.is_empty()
-        || symbol.chars().next().unwrap().is_ascii_digit()
+    let mut chars = symbol.chars();
+    let starts_like_numeral = match chars.next() {
+        None => true,
+        Some('-') => chars.next().is_some_and(|c| c.is_ascii_digit()),
+        Some(c) => c.is_ascii_digit(),
+    };
+    if starts_like_numeral
         || Reserved::from_str(symbol).is_ok()
         || symbol.chars().any(|c| !is_symbol_character(c))
     {
@@ -423,12 +556,25 @@ fn quote_symbol(symbol: &str) -> Cow<str> {
     }
 }
 
+/// Escapes a string literal so it can be printed between `"` characters. Besides doubling any `"`
+/// characters, this uses `\u{...}` escape sequences for backslashes and for any character that is
+/// not printable ASCII, so the string is read back unchanged. Characters that are too large to fit
+/// in an escape sequence are printed as they are.
 fn escape_string(string: &str) -> Cow<str> {
-    if string.contains('"') {
-        Cow::Owned(string.replace('"', "\"\""))
-    } else {
-        Cow::Borrowed(string)
+    let needs_escape =
+        |c: char| c == '"' || c == '\\' || (!(' '..='~').contains(&c) && c as u32 <= 0xFFFFF);
+    if !string.chars().any(needs_escape) {
+        return Cow::Borrowed(string);
     }
+    let mut result = String::with_capacity(string.len() + 8);
+    for c in string.chars() {
+        match c {
+            '"' => result.push_str("\"\""),
+            c if needs_escape(c) => result.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    Cow::Owned(result)
 }
 
 impl fmt::Display for Term {
@@ -447,6 +593,8 @@ impl fmt::Display for Term {
             global_vars: HashSet::new(),
             defined_constants: HashMap::new(),
             smt_lib_strict: false,
+            dialect: OutputDialect::default(),
+            warned_lambda: false,
         };
         printer.write_raw_term(self).unwrap();
         let result = std::str::from_utf8(&buf).unwrap();
@@ -474,6 +622,7 @@ impl fmt::Display for Constant {
             }
             Constant::String(s) => write!(f, "\"{}\"", escape_string(s)),
             Constant::BitVec(val, width) => write!(f, "(_ bv{} {})", val, width), // TODO: comeback to this
+            Constant::FiniteField(val, order) => write!(f, "#f{}m{}", val, order),
         }
     }
 }
@@ -521,7 +670,12 @@ impl fmt::Display for Sort {
             Sort::String => write!(f, "String"),
             Sort::RegLan => write!(f, "RegLan"),
             Sort::Array(x, y) => write_s_expr(f, "Array", &[x, y]),
+            Sort::Set(x) => write_s_expr(f, "Set", &[x]),
+            Sort::Seq(x) => write_s_expr(f, "Seq", &[x]),
             Sort::BitVec(w) => write!(f, "(_ BitVec {})", w),
+            Sort::FloatingPoint(e, s) => write!(f, "(_ FloatingPoint {} {})", e, s),
+            Sort::RoundingMode => write!(f, "RoundingMode"),
+            Sort::FiniteField(p) => write!(f, "(_ FiniteField {})", p),
             Sort::RareList => unreachable!("RARE list sort should never be displayed"),
             Sort::Type => write!(f, "Type"),
         }
@@ -536,10 +690,11 @@ impl fmt::Display for Token {
             Token::Symbol(s) => write!(f, "{}", quote_symbol(s)),
             Token::Keyword(k) => write!(f, ":{}", k),
             Token::Numeral(n) => write!(f, "{}", n),
-            Token::Decimal(r) => write!(f, "{}", r),
+            Token::Decimal(r) => write_decimal(f, r),
             Token::Bitvector { value, width } => {
                 write!(f, "#b{v:0>w$b}", v = value, w = *width as usize)
             }
+            Token::FiniteField { value, order } => write!(f, "#f{}m{}", value, order),
             Token::String(s) => write!(f, "\"{}\"", escape_string(s)),
             Token::ReservedWord(r) => write!(f, "{}", r),
             Token::Eof => write!(f, "EOF"),
@@ -547,6 +702,30 @@ impl fmt::Display for Token {
     }
 }
 
+/// Writes a rational number in decimal notation, like `1.25`. If the number has no finite decimal
+/// representation, it is written as a fraction instead.
+fn write_decimal(f: &mut fmt::Formatter, r: &Rational) -> fmt::Result {
+    let mut denom = r.denom().clone();
+    let (mut twos, mut fives) = (0, 0);
+    while denom.is_divisible_u(2) {
+        denom /= 2;
+        twos += 1;
+    }
+    while denom.is_divisible_u(5) {
+        denom /= 5;
+        fives += 1;
+    }
+    if denom != 1 {
+        return write!(f, "{}", r);
+    }
+    let num_digits = std::cmp::max(std::cmp::max(twos, fives), 1);
+    let scaled = Rational::from(r.abs_ref()) * Integer::from(10u32).pow(num_digits);
+    let digits = format!("{:0>w$}", scaled.numer(), w = num_digits as usize + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - num_digits as usize);
+    let sign = if r.is_negative() { "-" } else { "" };
+    write!(f, "{}{}.{}", sign, integer, fraction)
+}
+
 impl fmt::Display for ProblemPrelude {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "(set-logic {})", self.logic.as_deref().unwrap_or("ALL"))?;
@@ -555,6 +734,37 @@ impl fmt::Display for ProblemPrelude {
             writeln!(f, "(declare-sort {} {})", quote_symbol(name), arity)?;
         }
 
+        if !self.datatypes.is_empty() {
+            // All datatypes are declared in a single command, since they may be mutually recursive
+            write!(f, "(declare-datatypes (")?;
+            for (i, def) in self.datatypes.iter().enumerate() {
+                write!(
+                    f,
+                    "{}({} 0)",
+                    if i == 0 { "" } else { " " },
+                    quote_symbol(&def.name)
+                )?;
+            }
+            write!(f, ") (")?;
+            for (i, def) in self.datatypes.iter().enumerate() {
+                write!(f, "{}(", if i == 0 { "" } else { " " })?;
+                for (j, c) in def.constructors.iter().enumerate() {
+                    write!(
+                        f,
+                        "{}({}",
+                        if j == 0 { "" } else { " " },
+                        quote_symbol(&c.name)
+                    )?;
+                    for (name, sort) in &c.selectors {
+                        write!(f, " ({} {})", quote_symbol(name), sort)?;
+                    }
+                    write!(f, ")")?;
+                }
+                write!(f, ")")?;
+            }
+            writeln!(f, "))")?;
+        }
+
         for (name, sort) in &self.function_declarations {
             write!(f, "(declare-fun {} ", quote_symbol(name))?;
             if let Sort::Function(sorts) = sort.as_sort().unwrap() {
@@ -568,6 +778,17 @@ impl fmt::Display for ProblemPrelude {
     }
 }
 
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.prelude)?;
+        for premise in &self.premises {
+            // Problems must be valid SMT-LIB, so we can't use sharing when printing the premises
+            writeln!(f, "(assert {:#})", premise)?;
+        }
+        writeln!(f, "(check-sat)")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -615,4 +836,393 @@ mod tests {
 
         assert_eq!(expected, std::str::from_utf8(&buf).unwrap());
     }
+
+    #[test]
+    fn test_dialects() {
+        use crate::parser;
+
+        let definitions: &[u8] = b"
+            (declare-fun f (Real) Real)
+            (declare-const a Real)
+            (assert (= a 0.5))
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (= a 0.5))
+            (anchor :step t1 :args ((:= (x Real) (- 1.5))))
+            (step t1.t1 (cl (= x (- 1.5))) :rule hole)
+            (step t1 (cl (= (f a) (f a))) :rule bfun_elim)
+            (step t2 (cl) :rule forall_inst :args ((:= (y Real) a) (:= z (f a)) a))
+            (step t3 (cl) :rule resolution :premises (h1 t2))
+        ";
+        let (problem, proof, mut pool) =
+            parser::parse_instance(definitions, proof, parser::Config::new()).unwrap();
+
+        let mut print = |dialect: OutputDialect| {
+            let mut buf = Vec::new();
+            let mut printer = AlethePrinter::new(&mut pool, &problem.prelude, false, &mut buf);
+            printer.dialect = dialect;
+            printer.smt_lib_strict = dialect.smt_lib_numerals();
+            printer.write_proof(&proof).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+
+        assert_eq!(
+            print(OutputDialect::Alethe12),
+            "(assume h1 (= a 1/2))\n\
+            (anchor :step t1 :args ((:= (x Real) (- 3/2))))\n\
+            (step t1.t1 (cl (= x (- 3/2))) :rule hole)\n\
+            (step t1 (cl (= (f a) (f a))) :rule bfun_elim)\n\
+            (step t2 (cl) :rule forall_inst :args ((:= (y Real) a) (:= (z Real) (f a)) a))\n\
+            (step t3 (cl) :rule resolution :premises (h1 t2))\n"
+        );
+        assert_eq!(
+            print(OutputDialect::VeritLegacy),
+            "(step h1 (cl (= a (/ 1.0 2.0))) :rule input)\n\
+            (anchor :step t1 :args ((:= x (- (/ 3.0 2.0)))))\n\
+            (step t1.t1 (cl (= x (- (/ 3.0 2.0)))) :rule hole)\n\
+            (step t1 (cl (= (f a) (f a))) :rule tmp_bfun_elim)\n\
+            (step t2 (cl) :rule forall_inst :args ((:= y a) (:= z (f a)) a))\n\
+            (step t3 (cl) :rule resolution :clauses (h1 t2))\n"
+        );
+        assert_eq!(
+            print(OutputDialect::Cvc5),
+            "(assume h1 (= a (/ 1.0 2.0)))\n\
+            (anchor :step t1 :args ((:= (x Real) (- (/ 3.0 2.0)))))\n\
+            (step t1.t1 (cl (= x (- (/ 3.0 2.0)))) :rule hole)\n\
+            (step t1 (cl (= (f a) (f a))) :rule bfun_elim)\n\
+            (step t2 (cl) :rule forall_inst :args ((:= (y Real) a) (:= (z Real) (f a)) a))\n\
+            (step t3 (cl) :rule resolution :premises (h1 t2))\n"
+        );
+    }
+
+    #[test]
+    fn test_write_smt_assertions() {
+        let mut pool = PrimitivePool::new();
+        let definitions = "
+            (declare-fun p () Bool)
+            (declare-fun x () Real)
+        ";
+        let [a, b] = crate::parser::tests::parse_terms(
+            &mut pool,
+            definitions,
+            ["(or p (> x 0.5))", "(not p)"],
+        );
+
+        let mut buf = Vec::new();
+        let prelude = ProblemPrelude::default();
+        write_smt_assertions(&mut pool, &prelude, &mut buf, &[a, b], false).unwrap();
+        let expected = "(assert (or p (> x (/ 1.0 2.0))))\n(assert (not p))\n";
+        assert_eq!(expected, std::str::from_utf8(&buf).unwrap());
+    }
+
+    #[test]
+    fn test_qualified_ops_and_lambdas() {
+        let mut pool = PrimitivePool::new();
+        let definitions = "
+            (declare-fun a () (Array Int Real))
+            (declare-fun f (Int) Int)
+        ";
+        let terms = [
+            "(= a ((as const (Array Int Real)) 0.0))",
+            "(= f (lambda ((x Int)) (+ x 1)))",
+            "(= (select ((as const (Array Int Real)) 2.0) 0) ((lambda ((y Real)) y) 2.0))",
+        ];
+        let parsed = crate::parser::tests::parse_terms(&mut pool, definitions, terms);
+        for (expected, term) in terms.iter().zip(&parsed) {
+            assert_eq!(*expected, format!("{:#}", term));
+        }
+
+        // Qualified operators must also be written with `as` in SMT-LIB problems
+        let mut buf = Vec::new();
+        let prelude = ProblemPrelude::default();
+        write_smt_assertions(&mut pool, &prelude, &mut buf, &parsed, false).unwrap();
+        let expected: String = terms.iter().map(|t| format!("(assert {})\n", t)).collect();
+        assert_eq!(expected, std::str::from_utf8(&buf).unwrap());
+    }
+
+    #[test]
+    fn test_step_attributes_round_trip() {
+        use crate::parser;
+
+        let definitions: &[u8] = b"(declare-const a Bool)";
+        let proof = "\
+            (step t1 (cl a) :rule hole :solver-id 42 :time 1.50 :flag :note \"x \"\"y\"\"\")\n\
+            (step t2 (cl a) :rule hole :premises (t1) :origin (t1 uncrowd) :meta (|a b| (#b01 -)))\n\
+        ";
+        let expected = "\
+            (step t1 (cl a) :rule hole :solver-id 42 :time 1.5 :flag :note \"x \"\"y\"\"\")\n\
+            (step t2 (cl a) :rule hole :premises (t1) :origin (t1 uncrowd) :meta (|a b| (#b01 -)))\n\
+        ";
+        let (problem, proof, mut pool) =
+            parser::parse_instance(definitions, proof.as_bytes(), parser::Config::new()).unwrap();
+
+        let ProofCommand::Step(step) = &proof.commands[0] else {
+            unreachable!()
+        };
+        let value = |keyword| step.attribute(keyword).unwrap().value.as_deref();
+        assert_eq!(value("solver-id"), Some("42"));
+        assert_eq!(value("flag"), None);
+        assert!(step.attribute("premises").is_none());
+
+        let mut buf = Vec::new();
+        AlethePrinter::new(&mut pool, &problem.prelude, false, &mut buf)
+            .write_proof(&proof)
+            .unwrap();
+        assert_eq!(expected, std::str::from_utf8(&buf).unwrap());
+    }
+
+    #[test]
+    fn test_symbol_quoting_round_trip() {
+        use crate::parser;
+
+        let definitions = "
+            (declare-sort |my sort| 0)
+            (declare-fun |a b| () Bool)
+            (declare-fun |-5| () Int)
+            (declare-fun |1x| () Int)
+            (declare-fun |let| () Bool)
+            (declare-fun |ünï| () Bool)
+            (declare-fun |(p)| () Bool)
+            (declare-fun |x:y| () Bool)
+            (declare-fun || () Bool)
+            (declare-fun |\"q\"| (|my sort|) Bool)
+            (declare-const s String)
+        ";
+        let proof = r#"
+            (assume |h 1| (and |a b| |let| |ünï| |(p)| |x:y| ||))
+            (step |t 2| (cl (= |-5| |1x| -5) (= s "a\u{5c}u{41}\u{e9}\u{9}""")) :rule |my rule|
+                :premises (|h 1|))
+            (anchor :step |t 3| :args ((|x y| |my sort|)))
+            (assume |t 3.h 1| (|"q"| |x y|))
+            (step |t 3| (cl (not (|"q"| |x y|))) :rule subproof :discharge (|t 3.h 1|))
+        "#;
+
+        let print = |definitions: &str, proof: &str| {
+            let (problem, proof, mut pool) = parser::parse_instance(
+                definitions.as_bytes(),
+                proof.as_bytes(),
+                parser::Config::new(),
+            )
+            .unwrap();
+            let mut buf = Vec::new();
+            AlethePrinter::new(&mut pool, &problem.prelude, false, &mut buf)
+                .write_proof(&proof)
+                .unwrap();
+            (problem.prelude.to_string(), String::from_utf8(buf).unwrap())
+        };
+
+        let (printed_definitions, printed_proof) = print(definitions, proof);
+        for expected in [
+            "|a b|",
+            "|-5|",
+            "|1x|",
+            "|let|",
+            "|ünï|",
+            "|(p)|",
+            "|x:y|",
+            "||",
+            "|my sort|",
+            "|h 1|",
+            "|t 2|",
+            "|my rule|",
+            "|t 3.h 1|",
+            "-5)",
+            r#""a\u{5c}u{41}\u{e9}\u{9}""""#,
+        ] {
+            assert!(
+                printed_definitions.contains(expected) || printed_proof.contains(expected),
+                "expected '{}' in printed proof:\n{}",
+                expected,
+                printed_proof,
+            );
+        }
+        assert_eq!(
+            (printed_definitions.clone(), printed_proof.clone()),
+            print(&printed_definitions, &printed_proof)
+        );
+    }
+
+    /// Generates random proofs as text, to test that printing a parsed proof and parsing it back
+    /// gives the same proof.
+    struct ProofGenerator {
+        rng: rand::rngs::StdRng,
+        bound: Vec<(String, &'static str)>,
+        next_var: usize,
+    }
+
+    impl ProofGenerator {
+        const BOOL_CONSTS: [&'static str; 4] = ["|p q|", "r", "|let|", "|ünï|"];
+        const INT_CONSTS: [&'static str; 3] = ["a", "|-5|", "|1x|"];
+        const STRING_CONSTS: [&'static str; 2] = ["s", "|s t|"];
+        const STRING_CHARS: [char; 10] = ['a', 'u', '{', '}', ' ', '"', '\\', '|', 'é', '\t'];
+
+        fn new(seed: u64) -> Self {
+            use rand::SeedableRng;
+            Self {
+                rng: rand::rngs::StdRng::seed_from_u64(seed),
+                bound: Vec::new(),
+                next_var: 0,
+            }
+        }
+
+        fn pick(&mut self, options: &[&'static str]) -> &'static str {
+            use rand::seq::SliceRandom;
+            options.choose(&mut self.rng).unwrap()
+        }
+
+        fn atom(&mut self, sort: &'static str) -> String {
+            use rand::Rng;
+
+            let bound: Vec<_> = self.bound.iter().filter(|(_, s)| *s == sort).collect();
+            if !bound.is_empty() && self.rng.gen_bool(0.5) {
+                return bound[self.rng.gen_range(0..bound.len())].0.clone();
+            }
+            match sort {
+                "Bool" => self.pick(&["true", "false"]).to_owned(),
+                "Int" if self.rng.gen_bool(0.5) => self.rng.gen_range(0..100).to_string(),
+                "String" if self.rng.gen_bool(0.5) => {
+                    let len = self.rng.gen_range(0..6);
+                    let contents: String = (0..len)
+                        .map(|_| {
+                            let c = Self::STRING_CHARS[self.rng.gen_range(0..10)];
+                            match c {
+                                '"' => "\"\"".to_owned(),
+                                c if c.is_ascii_alphanumeric() => c.to_string(),
+                                c => format!("\\u{{{:x}}}", c as u32),
+                            }
+                        })
+                        .collect();
+                    format!("\"{}\"", contents)
+                }
+                "Int" => self.pick(&Self::INT_CONSTS).to_owned(),
+                _ => self.pick(&Self::STRING_CONSTS).to_owned(),
+            }
+        }
+
+        fn binder(&mut self, binder: &str, depth: usize) -> String {
+            let name = format!("|x {}|", self.next_var);
+            self.next_var += 1;
+            let sort = self.pick(&["Int", "String"]);
+            let header = if binder == "let" {
+                format!("(let (({} {}))", name, self.term(sort, depth - 1))
+            } else {
+                format!("({} (({} {}))", binder, name, sort)
+            };
+            self.bound.push((name, sort));
+            let body = self.term("Bool", depth - 1);
+            self.bound.pop();
+            format!("{} {})", header, body)
+        }
+
+        fn term(&mut self, sort: &'static str, depth: usize) -> String {
+            use rand::Rng;
+
+            if depth == 0 || self.rng.gen_bool(0.2) {
+                return if sort == "Bool" && self.rng.gen_bool(0.5) {
+                    self.pick(&Self::BOOL_CONSTS).to_owned()
+                } else {
+                    self.atom(sort)
+                };
+            }
+            let choice = self.rng.gen_range(0..8);
+            let (op, sorts): (_, &[_]) = match (sort, choice) {
+                ("Bool", 0) => ("not", &["Bool"]),
+                ("Bool", 1) => (self.pick(&["and", "or", "=>", "xor"]), &["Bool"; 3]),
+                ("Bool", 2) => (self.pick(&["=", "<", "distinct"]), &["Int", "Int"]),
+                ("Bool", 3) => ("=", &["String", "String"]),
+                ("Bool", 4) => ("ite", &["Bool"; 3]),
+                ("Bool", 5) => return self.binder("forall", depth),
+                ("Bool", 6) => return self.binder("exists", depth),
+                ("Bool", _) => return self.binder("let", depth),
+                ("Int", 0..=2) => (self.pick(&["+", "-", "*"]), &["Int", "Int"]),
+                ("Int", 3) => ("-", &["Int"]),
+                ("Int", 4) => ("ite", &["Bool", "Int", "Int"]),
+                ("Int", _) => ("str.len", &["String"]),
+                (_, 0..=3) => ("str.++", &["String", "String"]),
+                (_, _) => ("ite", &["Bool", "String", "String"]),
+            };
+            let args: Vec<_> = sorts.iter().map(|s| self.term(s, depth - 1)).collect();
+            format!("({} {})", op, args.join(" "))
+        }
+
+        fn proof(&mut self, num_steps: usize) -> String {
+            use rand::Rng;
+
+            let mut result = format!("(assume h0 {})\n", self.term("Bool", 3));
+            for i in 1..=num_steps {
+                let clause: Vec<_> = (0..self.rng.gen_range(0..3))
+                    .map(|_| self.term("Bool", 4))
+                    .collect();
+                result += &format!(
+                    "(step t{} (cl {}) :rule hole :premises (h0))\n",
+                    i,
+                    clause.join(" ")
+                );
+            }
+            result += "(anchor :step t.sub :args ((|y z| Int)))\n";
+            result += &format!("(step t.sub.t1 (cl {}) :rule hole)\n", self.term("Bool", 3));
+            result += "(step t.sub (cl) :rule hole)\n";
+            result
+        }
+    }
+
+    /// Asserts that printing the proof and parsing it back gives the same proof. Since the proof is
+    /// parsed again with the same configuration and into the same pool, the terms can be compared by
+    /// reference.
+    fn assert_round_trip(definitions: &str, proof: &str, config: crate::parser::Config) {
+        use crate::parser::parse_instance_with_pool;
+
+        let mut pool = PrimitivePool::new();
+        let (problem, parsed) =
+            parse_instance_with_pool(definitions.as_bytes(), proof.as_bytes(), config, &mut pool)
+                .unwrap_or_else(|e| panic!("{}\nin generated proof:\n{}", e, proof));
+        for use_sharing in [false, true] {
+            let mut buf = Vec::new();
+            AlethePrinter::new(&mut pool, &problem.prelude, use_sharing, &mut buf)
+                .write_proof(&parsed)
+                .unwrap();
+            let printed = String::from_utf8(buf).unwrap();
+            let (_, reparsed) = parse_instance_with_pool(
+                definitions.as_bytes(),
+                printed.as_bytes(),
+                config,
+                &mut pool,
+            )
+            .unwrap_or_else(|e| panic!("{}\nin printed proof:\n{}", e, printed));
+            assert!(
+                parsed.commands == reparsed.commands,
+                "printed proof is different from the original.\noriginal:\n{}\nprinted:\n{}",
+                proof,
+                printed,
+            );
+        }
+    }
+
+    #[test]
+    fn test_print_parse_round_trip() {
+        use crate::parser::Config;
+
+        let definitions = "
+            (declare-fun |p q| () Bool)
+            (declare-fun r () Bool)
+            (declare-fun |let| () Bool)
+            (declare-fun |ünï| () Bool)
+            (declare-fun a () Int)
+            (declare-fun |-5| () Int)
+            (declare-fun |1x| () Int)
+            (declare-fun s () String)
+            (declare-fun |s t| () String)
+        ";
+        let configs = [
+            Config::new(),
+            Config { expand_lets: true, ..Config::new() },
+            Config { strict: true, ..Config::new() },
+        ];
+        for seed in 0..50 {
+            let proof = ProofGenerator::new(seed).proof(5);
+            for config in configs {
+                assert_round_trip(definitions, &proof, config);
+            }
+        }
+    }
 }
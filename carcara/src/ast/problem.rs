@@ -1,5 +1,7 @@
-use super::{Rc, Term};
+use super::{Datatypes, Rc, Term};
+use crate::parser::ParsingContext;
 use indexmap::IndexSet;
+use std::sync::Arc;
 
 /// An SMT problem in the SMT-LIB format.
 #[derive(Debug, Clone, Default)]
@@ -9,8 +11,18 @@ pub struct Problem {
 
     /// The proof's premises.
     ///
-    /// Those are the terms introduced in the original problem's `assert` commands.
+    /// Those are the terms introduced in the original problem's `assert` commands, and the
+    /// literals of its `check-sat-assuming` command, if any.
     pub premises: IndexSet<Rc<Term>>,
+
+    /// The literals of the problem's `check-sat-assuming` command. These are also included in the
+    /// premises.
+    pub assumptions: IndexSet<Rc<Term>>,
+
+    /// The context in which the problem and its proof were parsed, which allows parsing other terms
+    /// that use their declarations and definitions. This is `None` if the problem was not built by
+    /// the parser.
+    pub parsing_context: Option<Arc<ParsingContext>>,
 }
 
 impl Problem {
@@ -21,12 +33,17 @@ impl Problem {
 
 /// The prelude of an SMT-LIB problem instance.
 ///
-/// This stores the sort declarations, function declarations and the problem's logic string.
+/// This stores the sort declarations, datatype declarations, function declarations and the
+/// problem's logic string.
 #[derive(Debug, Clone, Default)]
 pub struct ProblemPrelude {
     /// The sort declarations, each represented by its name and arity.
     pub(crate) sort_declarations: Vec<(String, usize)>,
 
+    /// The datatype declarations. The constructors and selectors of each datatype are not included
+    /// in the function declarations.
+    pub(crate) datatypes: Datatypes,
+
     /// The function declarations, each represented by its name and body.
     pub(crate) function_declarations: Vec<(String, Rc<Term>)>,
 
@@ -38,4 +55,9 @@ impl ProblemPrelude {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Returns the datatypes declared in the problem.
+    pub fn datatypes(&self) -> &Datatypes {
+        &self.datatypes
+    }
 }
@@ -47,9 +47,59 @@ pub struct ProofStep {
     /// The step arguments, given via the `:args` attribute.
     pub args: Vec<Rc<Term>>,
 
+    /// The variables assigned by arguments of the form `(:= (<symbol> <sort>) <term>)`, in which
+    /// case the assigned term is stored in `args`. This is either empty, if no argument is an
+    /// assignment, or has an entry for each argument, which is `None` if the argument is a term.
+    pub arg_vars: Vec<Option<SortedVar>>,
+
     /// The local premises that this step discharges, given via the `:discharge` attribute, and
     /// indexed similarly to premises.
     pub discharge: Vec<(usize, usize)>,
+
+    /// If this step was introduced during elaboration, this records where it came from. It is
+    /// given via the `:origin` attribute.
+    pub origin: Option<StepOrigin>,
+
+    /// Any other attributes given to the step. These are not interpreted by Carcara, but are
+    /// preserved when the proof is printed, so other tools can attach metadata to steps.
+    pub attributes: Vec<StepAttribute>,
+}
+
+impl ProofStep {
+    /// Returns the first attribute of the step with the given keyword, if any. The keyword is
+    /// given without the leading `:`. This only finds attributes that are not interpreted by
+    /// Carcara, so it never returns attributes like `:premises` or `:args`.
+    pub fn attribute(&self, keyword: &str) -> Option<&StepAttribute> {
+        self.attributes.iter().find(|a| a.keyword == keyword)
+    }
+
+    /// Returns the variable assigned by the argument at index `i`, if that argument is an
+    /// assignment.
+    pub fn assigned_var(&self, i: usize) -> Option<&SortedVar> {
+        self.arg_vars.get(i).and_then(Option::as_ref)
+    }
+}
+
+/// An attribute of a step that is not interpreted by Carcara, written as `:<keyword> <value>`.
+/// This can be used to attach metadata to steps, like solver-internal ids or timestamps.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StepAttribute {
+    /// The attribute keyword, without the leading `:`.
+    pub keyword: String,
+
+    /// The attribute value, as SMT-LIB text. This is either a single token, like a symbol, numeral
+    /// or string literal, or an s-expression. If the attribute has no value, this is `None`.
+    pub value: Option<String>,
+}
+
+/// The provenance of a step introduced during elaboration, written as `:origin (<step> <pass>)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StepOrigin {
+    /// The id of the step in the original proof from which this step was derived.
+    pub step: String,
+
+    /// The name of the elaboration pass that introduced this step.
+    pub pass: String,
 }
 
 /// A subproof.
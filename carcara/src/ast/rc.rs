@@ -87,6 +87,13 @@ impl<T: ?Sized> Deref for Rc<T> {
     }
 }
 
+impl<T: ?Sized> Rc<T> {
+    /// Similar to [`std::rc::Rc::as_ptr`].
+    pub fn as_ptr(this: &Self) -> *const T {
+        sync::Arc::as_ptr(&this.0)
+    }
+}
+
 // Note: Since `Eq` and `Hash` are implemented differently for `Rc<T>` than they are for `T`, we
 // _cannot_ implement `Borrow<T>` for `Rc<T>`
 impl<T: ?Sized> AsRef<T> for Rc<T> {
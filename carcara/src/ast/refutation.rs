@@ -0,0 +1,142 @@
+//! Detection of independent refutations in a proof.
+//!
+//! Some pipelines concatenate the proofs of several goals into a single file, so the resulting
+//! proof has more than one step concluding the empty clause. The utilities in this module find
+//! these steps and split the proof into one slice for each of them, so they can be checked
+//! separately.
+
+use super::{Proof, ProofCommand};
+use std::collections::HashSet;
+
+/// A refutation in a proof: a step in the root proof that concludes the empty clause, and that is
+/// not used, directly or transitively, as a premise by another such step.
+#[derive(Debug, Clone)]
+pub struct Refutation {
+    /// The id of the step that concludes the empty clause.
+    pub id: String,
+
+    /// The slice of the proof consisting of the refutation step and all of its transitive
+    /// premises, in their original order.
+    pub proof: Proof,
+
+    /// The ids of the steps in this refutation that are also used by another refutation in the
+    /// same proof. If this is empty for all refutations, their dependency cones are disjoint.
+    /// Shared `assume` commands are not included.
+    pub shared_steps: Vec<String>,
+}
+
+/// Calls `f` on the index of every root-level command that is used as a premise by `command`,
+/// including premises of steps nested in a subproof.
+fn for_each_root_premise<F: FnMut(usize)>(command: &ProofCommand, f: &mut F) {
+    match command {
+        ProofCommand::Assume { .. } => (),
+        ProofCommand::Step(s) => {
+            for &(depth, i) in s.premises.iter().chain(&s.discharge) {
+                if depth == 0 {
+                    f(i);
+                }
+            }
+        }
+        ProofCommand::Subproof(s) => s.commands.iter().for_each(|c| for_each_root_premise(c, f)),
+    }
+}
+
+/// Returns the indices of the root-level commands that `root` transitively depends on, including
+/// `root` itself, in increasing order.
+fn cone(commands: &[ProofCommand], root: usize) -> Vec<usize> {
+    let mut seen = HashSet::from([root]);
+    let mut todo = vec![root];
+    while let Some(i) = todo.pop() {
+        for_each_root_premise(&commands[i], &mut |p| {
+            if seen.insert(p) {
+                todo.push(p);
+            }
+        });
+    }
+    let mut result: Vec<_> = seen.into_iter().collect();
+    result.sort_unstable();
+    result
+}
+
+/// Rewrites the root-level premises of `command` according to `new_index`.
+fn remap_premises(command: &mut ProofCommand, new_index: &[Option<usize>]) {
+    match command {
+        ProofCommand::Assume { .. } => (),
+        ProofCommand::Step(s) => {
+            for (d, i) in s.premises.iter_mut().chain(&mut s.discharge) {
+                if *d == 0 {
+                    *i = new_index[*i].unwrap();
+                }
+            }
+        }
+        ProofCommand::Subproof(s) => s
+            .commands
+            .iter_mut()
+            .for_each(|c| remap_premises(c, new_index)),
+    }
+}
+
+/// Builds a proof containing only the root-level commands with the given indices.
+fn slice(proof: &Proof, indices: &[usize]) -> Proof {
+    let mut new_index = vec![None; proof.commands.len()];
+    for (new, &old) in indices.iter().enumerate() {
+        new_index[old] = Some(new);
+    }
+    let commands = indices
+        .iter()
+        .map(|&i| {
+            let mut command = proof.commands[i].clone();
+            remap_premises(&mut command, &new_index);
+            command
+        })
+        .collect();
+    Proof {
+        constant_definitions: proof.constant_definitions.clone(),
+        commands,
+    }
+}
+
+/// Finds all refutations in a proof, in the order they appear. Each refutation contains the slice
+/// of the proof needed to derive it, so it can be checked independently of the others. Commands
+/// that are not used by any refutation are not part of any slice.
+///
+/// A refutation step may be used as a premise by another step concluding the empty clause, for
+/// example in a proof that derives the empty clause twice. In that case, only the last of these
+/// steps is considered a refutation.
+pub fn find_refutations(proof: &Proof) -> Vec<Refutation> {
+    let commands = &proof.commands;
+    let candidates: Vec<usize> = (0..commands.len())
+        .filter(|&i| matches!(&commands[i], ProofCommand::Step(s) if s.clause.is_empty()))
+        .collect();
+    let cones: Vec<_> = candidates.iter().map(|&i| cone(commands, i)).collect();
+
+    // A candidate is terminal if it is not in the cone of any other candidate
+    let mut used = HashSet::new();
+    for (&i, cone) in candidates.iter().zip(&cones) {
+        used.extend(cone.iter().copied().filter(|&j| j != i));
+    }
+    let terminal: Vec<_> = candidates
+        .iter()
+        .zip(cones)
+        .filter(|(i, _)| !used.contains(*i))
+        .collect();
+
+    let mut occurrences = vec![0usize; commands.len()];
+    for (_, cone) in &terminal {
+        for &i in cone {
+            occurrences[i] += 1;
+        }
+    }
+    terminal
+        .iter()
+        .map(|(&i, cone)| Refutation {
+            id: commands[i].id().to_owned(),
+            proof: slice(proof, cone),
+            shared_steps: cone
+                .iter()
+                .filter(|&&j| occurrences[j] > 1 && !commands[j].is_assume())
+                .map(|&j| commands[j].id().to_owned())
+                .collect(),
+        })
+        .collect()
+}
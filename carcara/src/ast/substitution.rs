@@ -1,6 +1,8 @@
-//! Algorithms for creating and applying capture-avoiding substitutions over terms.
+//! Algorithms for creating and applying capture-avoiding substitutions over terms, and for
+//! matching a pattern term against a concrete term.
 
 use super::{Binder, BindingList, Rc, Sort, SortedVar, Term, TermPool};
+use crate::error_code::ErrorCode;
 use indexmap::{IndexMap, IndexSet};
 use thiserror::Error;
 
@@ -16,6 +18,17 @@ pub enum SubstitutionError {
     DifferentSorts(Rc<Term>, Rc<Term>),
 }
 
+impl SubstitutionError {
+    /// Returns the stable code identifying this kind of error.
+    pub fn code(&self) -> ErrorCode {
+        let number = match self {
+            Self::NotAVariable(..) => 800,
+            Self::DifferentSorts(..) => 801,
+        };
+        ErrorCode::new(number)
+    }
+}
+
 type SubstitutionResult<T> = Result<T, SubstitutionError>;
 
 /// Represents a capture-avoiding substitution over terms.
@@ -86,14 +99,24 @@ impl Substitution {
         })
     }
 
+    /// Returns an iterator over the substitution's mappings, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Rc<Term>, &Rc<Term>)> {
+        self.map.iter()
+    }
+
     /// Returns `true` if the substitution is empty.
     pub fn is_empty(&self) -> bool {
         self.map.is_empty()
     }
 
+    /// Returns the term that `x` is mapped to, if it exists.
+    pub fn get(&self, x: &Rc<Term>) -> Option<&Rc<Term>> {
+        self.map.get(x)
+    }
+
     /// Extends the substitution by adding a new mapping from `x` to `t`. This returns an error if
     /// the sorts of the given terms are not the same, or if `x` is not a variable term.
-    pub(crate) fn insert(
+    pub fn insert(
         &mut self,
         pool: &mut dyn TermPool,
         x: Rc<Term>,
@@ -240,6 +263,14 @@ impl Substitution {
                 let [x, y] = [x, y].map(|s| self.apply(pool, s));
                 pool.add(Term::Sort(Sort::Array(x, y)))
             }
+            Term::Sort(Sort::Set(x)) => {
+                let x = self.apply(pool, x);
+                pool.add(Term::Sort(Sort::Set(x)))
+            }
+            Term::Sort(Sort::Seq(x)) => {
+                let x = self.apply(pool, x);
+                pool.add(Term::Sort(Sort::Seq(x)))
+            }
             Term::Sort(_) => term.clone(),
         };
 
@@ -375,6 +406,112 @@ impl Substitution {
     }
 }
 
+/// Matches `pattern` against `term`, treating the terms in `variables` as pattern variables.
+///
+/// If there is a substitution over `variables` that makes `pattern` equal to `term`, this returns
+/// it. Every occurrence of a pattern variable must be mapped to the same term, and to a term of the
+/// same sort. Any other free variable in the pattern only matches itself. This is one-way matching:
+/// variables in `term` are never instantiated.
+///
+/// Binder terms match only if their binding lists are the same. Inside a binder, a pattern
+/// variable that is shadowed by the binding list is treated as an ordinary bound variable.
+///
+/// # Examples
+///
+/// ```
+/// # use carcara::{ast::*, parser};
+/// let mut pool = PrimitivePool::new();
+/// let mut parser = parser::Parser::new(&mut pool, parser::Config::new(), "".as_bytes()).unwrap();
+/// parser.reset("(declare-const x Int) (declare-const a Int)".as_bytes()).unwrap();
+/// parser.parse_problem().unwrap();
+/// let [pattern, term, x, result] = ["(+ x x)", "(+ (* 2 a) (* 2 a))", "x", "(* 2 a)"].map(|s| {
+///     parser.reset(s.as_bytes()).unwrap();
+///     parser.parse_term().unwrap()
+/// });
+///
+/// let substitution = match_term(&mut pool, &pattern, &term, &[x.clone()]).unwrap();
+/// assert_eq!(substitution.get(&x), Some(&result));
+/// ```
+pub fn match_term(
+    pool: &mut dyn TermPool,
+    pattern: &Rc<Term>,
+    term: &Rc<Term>,
+    variables: &[Rc<Term>],
+) -> Option<Substitution> {
+    let variables: IndexSet<_> = variables.iter().cloned().collect();
+    let mut map = IndexMap::new();
+    match_term_rec(pattern, term, &variables, &mut map).then_some(())?;
+    Substitution::new(pool, map).ok()
+}
+
+fn match_term_rec(
+    pattern: &Rc<Term>,
+    term: &Rc<Term>,
+    variables: &IndexSet<Rc<Term>>,
+    map: &mut IndexMap<Rc<Term>, Rc<Term>>,
+) -> bool {
+    if variables.contains(pattern) {
+        return match map.get(pattern) {
+            Some(previous) => previous == term,
+            None => {
+                map.insert(pattern.clone(), term.clone());
+                true
+            }
+        };
+    }
+
+    let match_all = |a: &[Rc<Term>], b: &[Rc<Term>], map: &mut IndexMap<_, _>| {
+        a.len() == b.len()
+            && a.iter()
+                .zip(b)
+                .all(|(a, b)| match_term_rec(a, b, variables, map))
+    };
+
+    // Pattern variables shadowed by a binder are no longer pattern variables in the binder body
+    let shadow = |bindings: &BindingList| -> IndexSet<Rc<Term>> {
+        variables
+            .iter()
+            .filter(|v| !bindings.iter().any(|(name, _)| v.as_var() == Some(name)))
+            .cloned()
+            .collect()
+    };
+
+    match (pattern.as_ref(), term.as_ref()) {
+        (Term::App(f, a), Term::App(g, b)) => {
+            match_term_rec(f, g, variables, map) && match_all(a, b, map)
+        }
+        (Term::Op(op_a, a), Term::Op(op_b, b)) => op_a == op_b && match_all(a, b, map),
+        (
+            Term::ParamOp {
+                op: op_a,
+                op_args: op_args_a,
+                args: a,
+            },
+            Term::ParamOp {
+                op: op_b,
+                op_args: op_args_b,
+                args: b,
+            },
+        ) => op_a == op_b && op_args_a == op_args_b && match_all(a, b, map),
+        (Term::Binder(binder_a, bindings_a, a), Term::Binder(binder_b, bindings_b, b)) => {
+            binder_a == binder_b
+                && bindings_a == bindings_b
+                && match_term_rec(a, b, &shadow(bindings_a), map)
+        }
+        (Term::Let(bindings_a, a), Term::Let(bindings_b, b)) => {
+            let values_a: Vec<_> = bindings_a.iter().map(|(_, v)| v.clone()).collect();
+            let values_b: Vec<_> = bindings_b.iter().map(|(_, v)| v.clone()).collect();
+            bindings_a
+                .iter()
+                .map(|(n, _)| n)
+                .eq(bindings_b.iter().map(|(n, _)| n))
+                && match_all(&values_a, &values_b, map)
+                && match_term_rec(a, b, &shadow(bindings_a), map)
+        }
+        _ => pattern == term,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -457,4 +594,59 @@ mod tests {
             // TODO: Add tests for `choice`, `let`, and `lambda` terms
         }
     }
+
+    #[test]
+    fn test_match_term() {
+        let definitions = "
+            (declare-fun x () Int)
+            (declare-fun y () Int)
+            (declare-fun a () Int)
+            (declare-fun f (Int Int) Int)
+        ";
+        let cases = [
+            ("(f x y)", "(f a 1)", Some(("a", "1"))),
+            ("(f x x)", "(f a a)", Some(("a", "y"))),
+            ("(f x x)", "(f a 1)", None),
+            ("(+ x a)", "(+ 1 a)", Some(("1", "y"))),
+            ("(+ x a)", "(+ 1 2)", None),
+            ("(- x)", "(- 1 2)", None),
+            (
+                "(forall ((z Int)) (> z x))",
+                "(forall ((z Int)) (> z 0))",
+                Some(("0", "y")),
+            ),
+            (
+                "(forall ((x Int)) (> x y))",
+                "(forall ((x Int)) (> x a))",
+                Some(("x", "a")),
+            ),
+            (
+                "(forall ((x Int)) (> x y))",
+                "(forall ((x Int)) (> 1 a))",
+                None,
+            ),
+        ];
+        for (pattern, term, expected) in cases {
+            let mut pool = PrimitivePool::new();
+            let [pattern, term, x, y] = crate::parser::tests::parse_terms(
+                &mut pool,
+                definitions,
+                [pattern, term, "x", "y"],
+            );
+            let got = match_term(&mut pool, &pattern, &term, &[x.clone(), y.clone()]);
+
+            // Pattern variables that don't occur in the pattern are left unmapped, which we
+            // represent in the expected values by mapping them to themselves
+            let got = got.map(|s| {
+                let x = s.get(&x).unwrap_or(&x).clone();
+                let y = s.get(&y).unwrap_or(&y).clone();
+                (x, y)
+            });
+            let expected = expected.map(|(a, b)| {
+                let [a, b] = crate::parser::tests::parse_terms(&mut pool, definitions, [a, b]);
+                (a, b)
+            });
+            assert_eq!(expected, got, "matching '{}' against '{}'", pattern, term);
+        }
+    }
 }
@@ -79,11 +79,36 @@ pub enum Sort {
     ///
     /// The two associated terms are the sort arguments for this sort.
     Array(Rc<Term>, Rc<Term>),
+
+    /// A `Set` sort, from cvc5's theory of finite sets.
+    ///
+    /// The associated term is the sort of the elements of the set.
+    Set(Rc<Term>),
+
+    /// A `Seq` sort, from cvc5's theory of sequences.
+    ///
+    /// The associated term is the sort of the elements of the sequence.
+    Seq(Rc<Term>),
+
     ///  `BitVec` sort.
     ///
     /// The associated term is the BV width of this sort.
     BitVec(Integer),
 
+    /// A `FloatingPoint` sort.
+    ///
+    /// The associated integers are the exponent width and the significand width of this sort. As
+    /// in SMT-LIB, the significand width includes the hidden bit.
+    FloatingPoint(Integer, Integer),
+
+    /// The `RoundingMode` primitive sort.
+    RoundingMode,
+
+    /// A `FiniteField` sort, from cvc5's theory of finite fields.
+    ///
+    /// The associated integer is the order of the field, which must be a prime number.
+    FiniteField(Integer),
+
     /// The sort of RARE lists.
     RareList,
 
@@ -107,6 +132,10 @@ pub enum Constant {
     String(String),
 
     BitVec(Integer, Integer),
+
+    /// A finite field element. The associated integers are the value, which is always in the range
+    /// `[0, p)`, and the order `p` of the field.
+    FiniteField(Integer, Integer),
 }
 
 /// A binder, either a quantifier (`forall` or `exists`), `choice`, or `lambda`.
@@ -210,6 +239,52 @@ pub enum Operator {
     /// The `is_int` operator.
     IsInt,
 
+    // Transcendentals
+    /// The `exp` operator.
+    Exp,
+
+    /// The `sin` operator.
+    Sin,
+
+    /// The `cos` operator.
+    Cos,
+
+    /// The `tan` operator.
+    Tan,
+
+    /// The `csc` operator.
+    Csc,
+
+    /// The `sec` operator.
+    Sec,
+
+    /// The `cot` operator.
+    Cot,
+
+    /// The `arcsin` operator.
+    ArcSin,
+
+    /// The `arccos` operator.
+    ArcCos,
+
+    /// The `arctan` operator.
+    ArcTan,
+
+    /// The `arccsc` operator.
+    ArcCsc,
+
+    /// The `arcsec` operator.
+    ArcSec,
+
+    /// The `arccot` operator.
+    ArcCot,
+
+    /// The `sqrt` operator.
+    Sqrt,
+
+    /// The `real.pi` operator.
+    Pi,
+
     // Arrays
     /// The `select` operator.
     Select,
@@ -217,6 +292,77 @@ pub enum Operator {
     /// The `store` operator.
     Store,
 
+    // Sets
+    /// The `set.union` operator.
+    SetUnion,
+
+    /// The `set.inter` operator.
+    SetInter,
+
+    /// The `set.minus` operator.
+    SetMinus,
+
+    /// The `set.complement` operator.
+    SetComplement,
+
+    /// The `set.member` operator.
+    SetMember,
+
+    /// The `set.subset` operator.
+    SetSubset,
+
+    /// The `set.singleton` operator.
+    SetSingleton,
+
+    /// The `set.insert` operator.
+    SetInsert,
+
+    /// The `set.card` operator.
+    SetCard,
+
+    // Sequences
+    /// The `seq.++` operator.
+    SeqConcat,
+
+    /// The `seq.len` operator.
+    SeqLen,
+
+    /// The `seq.extract` operator.
+    SeqExtract,
+
+    /// The `seq.at` operator.
+    SeqAt,
+
+    /// The `seq.nth` operator.
+    SeqNth,
+
+    /// The `seq.unit` operator.
+    SeqUnit,
+
+    /// The `seq.update` operator.
+    SeqUpdate,
+
+    /// The `seq.contains` operator.
+    SeqContains,
+
+    /// The `seq.prefixof` operator.
+    SeqPrefixOf,
+
+    /// The `seq.suffixof` operator.
+    SeqSuffixOf,
+
+    /// The `seq.indexof` operator.
+    SeqIndexOf,
+
+    /// The `seq.replace` operator.
+    SeqReplace,
+
+    /// The `seq.replace_all` operator.
+    SeqReplaceAll,
+
+    /// The `seq.rev` operator.
+    SeqRev,
+
     // Strings
     /// The `str.++` operator.
     StrConcat,
@@ -275,6 +421,21 @@ pub enum Operator {
     /// The `str.from_int` operator.
     StrFromInt,
 
+    /// The `str.update` operator. This is a cvc5 extension.
+    StrUpdate,
+
+    /// The `str.rev` operator. This is a cvc5 extension.
+    StrRev,
+
+    /// The `str.to_lower` operator. This is a cvc5 extension.
+    StrToLower,
+
+    /// The `str.to_upper` operator. This is a cvc5 extension.
+    StrToUpper,
+
+    /// The `str.indexof_re` operator. This is a cvc5 extension.
+    IndexOfRe,
+
     // Regular Expressions
     /// The `str.to_re` operator.
     StrToRe,
@@ -354,6 +515,126 @@ pub enum Operator {
     BvSGe,
     BvBbTerm,
 
+    /// The `bv2nat` operator, which converts a bitvector to the non-negative integer it
+    /// represents.
+    Bv2Nat,
+
+    /// The `ubv_to_int` operator. This is the same as `bv2nat`, under its SMT-LIB 2.7 name.
+    UbvToInt,
+
+    /// The `sbv_to_int` operator, which converts a bitvector to the integer it represents in
+    /// two's complement.
+    SbvToInt,
+
+    // Floating-point
+    /// The `RNE` rounding mode, also called `roundNearestTiesToEven`.
+    RoundNearestTiesToEven,
+
+    /// The `RNA` rounding mode, also called `roundNearestTiesToAway`.
+    RoundNearestTiesToAway,
+
+    /// The `RTP` rounding mode, also called `roundTowardPositive`.
+    RoundTowardPositive,
+
+    /// The `RTN` rounding mode, also called `roundTowardNegative`.
+    RoundTowardNegative,
+
+    /// The `RTZ` rounding mode, also called `roundTowardZero`.
+    RoundTowardZero,
+
+    /// The `fp` operator, that builds a floating-point literal from its sign, exponent and
+    /// significand bit-vectors.
+    Fp,
+
+    /// The `fp.abs` operator.
+    FpAbs,
+
+    /// The `fp.neg` operator.
+    FpNeg,
+
+    /// The `fp.add` operator.
+    FpAdd,
+
+    /// The `fp.sub` operator.
+    FpSub,
+
+    /// The `fp.mul` operator.
+    FpMul,
+
+    /// The `fp.div` operator.
+    FpDiv,
+
+    /// The `fp.fma` operator.
+    FpFma,
+
+    /// The `fp.sqrt` operator.
+    FpSqrt,
+
+    /// The `fp.rem` operator.
+    FpRem,
+
+    /// The `fp.roundToIntegral` operator.
+    FpRoundToIntegral,
+
+    /// The `fp.min` operator.
+    FpMin,
+
+    /// The `fp.max` operator.
+    FpMax,
+
+    /// The `fp.leq` operator.
+    FpLeq,
+
+    /// The `fp.lt` operator.
+    FpLt,
+
+    /// The `fp.geq` operator.
+    FpGeq,
+
+    /// The `fp.gt` operator.
+    FpGt,
+
+    /// The `fp.eq` operator.
+    FpEq,
+
+    /// The `fp.isNormal` operator.
+    FpIsNormal,
+
+    /// The `fp.isSubnormal` operator.
+    FpIsSubnormal,
+
+    /// The `fp.isZero` operator.
+    FpIsZero,
+
+    /// The `fp.isInfinite` operator.
+    FpIsInfinite,
+
+    /// The `fp.isNaN` operator.
+    FpIsNaN,
+
+    /// The `fp.isNegative` operator.
+    FpIsNegative,
+
+    /// The `fp.isPositive` operator.
+    FpIsPositive,
+
+    /// The `fp.to_real` operator.
+    FpToReal,
+
+    // Finite fields
+    /// The `ff.add` operator.
+    FfAdd,
+
+    /// The `ff.mul` operator.
+    FfMul,
+
+    /// The `ff.neg` operator.
+    FfNeg,
+
+    /// The `ff.bitsum` operator. The term `(ff.bitsum x_0 ... x_n)` is equal to the sum of
+    /// `2^i * x_i`, for each `i`.
+    FfBitSum,
+
     // Misc.
     /// The `rare-list` operator, used to represent RARE lists.
     RareList,
@@ -366,13 +647,43 @@ pub enum ParamOperator {
     BvBitOf,
     ZeroExtend,
     SignExtend,
+    BvRepeat,
+    BvRotateLeft,
+    BvRotateRight,
     BvConst,
 
+    /// The integer divisibility predicate `(_ divisible n)`, which holds if its argument is a
+    /// multiple of `n`.
+    Divisible,
+
+    /// The `(_ int2bv n)` operator, which converts an integer to a bitvector of width `n`, modulo
+    /// `2^n`.
+    Int2Bv,
+
+    /// The `(_ int_to_bv n)` operator. This is the same as `int2bv`, under its SMT-LIB 2.7 name.
+    IntToBv,
+
     RePower,
     ReLoop,
 
+    FpPosZero,
+    FpNegZero,
+    FpPosInfinity,
+    FpNegInfinity,
+    FpNaN,
+    ToFp,
+    ToFpUnsigned,
+    FpToUbv,
+    FpToSbv,
+
+    /// The datatype tester `(_ is C)`. The single operator argument is the constructor `C`.
+    DtTester,
+
     // Qualified operators
     ArrayConst,
+    SetEmpty,
+    SetUniverse,
+    SeqEmpty,
 }
 
 impl_str_conversion_traits!(Operator {
@@ -403,9 +714,50 @@ impl_str_conversion_traits!(Operator {
     ToInt: "to_int",
     IsInt: "is_int",
 
+    Exp: "exp",
+    Sin: "sin",
+    Cos: "cos",
+    Tan: "tan",
+    Csc: "csc",
+    Sec: "sec",
+    Cot: "cot",
+    ArcSin: "arcsin",
+    ArcCos: "arccos",
+    ArcTan: "arctan",
+    ArcCsc: "arccsc",
+    ArcSec: "arcsec",
+    ArcCot: "arccot",
+    Sqrt: "sqrt",
+    Pi: "real.pi",
+
     Select: "select",
     Store: "store",
 
+    SetUnion: "set.union",
+    SetInter: "set.inter",
+    SetMinus: "set.minus",
+    SetComplement: "set.complement",
+    SetMember: "set.member",
+    SetSubset: "set.subset",
+    SetSingleton: "set.singleton",
+    SetInsert: "set.insert",
+    SetCard: "set.card",
+
+    SeqConcat: "seq.++",
+    SeqLen: "seq.len",
+    SeqExtract: "seq.extract",
+    SeqAt: "seq.at",
+    SeqNth: "seq.nth",
+    SeqUnit: "seq.unit",
+    SeqUpdate: "seq.update",
+    SeqContains: "seq.contains",
+    SeqPrefixOf: "seq.prefixof",
+    SeqSuffixOf: "seq.suffixof",
+    SeqIndexOf: "seq.indexof",
+    SeqReplace: "seq.replace",
+    SeqReplaceAll: "seq.replace_all",
+    SeqRev: "seq.rev",
+
     StrConcat: "str.++",
     StrLen: "str.len",
     StrLessThan: "str.<",
@@ -425,6 +777,11 @@ impl_str_conversion_traits!(Operator {
     StrFromCode: "str.from_code",
     StrToInt: "str.to_int",
     StrFromInt: "str.from_int",
+    StrUpdate: "str.update",
+    StrRev: "str.rev",
+    StrToLower: "str.to_lower",
+    StrToUpper: "str.to_upper",
+    IndexOfRe: "str.indexof_re",
 
     StrToRe: "str.to_re",
     StrInRe: "str.in_re",
@@ -472,6 +829,46 @@ impl_str_conversion_traits!(Operator {
     BvSGt: "bvsgt",
     BvSGe: "bvsge",
     BvBbTerm: "bbterm",
+    Bv2Nat: "bv2nat",
+    UbvToInt: "ubv_to_int",
+    SbvToInt: "sbv_to_int",
+
+    RoundNearestTiesToEven: "RNE",
+    RoundNearestTiesToAway: "RNA",
+    RoundTowardPositive: "RTP",
+    RoundTowardNegative: "RTN",
+    RoundTowardZero: "RTZ",
+    Fp: "fp",
+    FpAbs: "fp.abs",
+    FpNeg: "fp.neg",
+    FpAdd: "fp.add",
+    FpSub: "fp.sub",
+    FpMul: "fp.mul",
+    FpDiv: "fp.div",
+    FpFma: "fp.fma",
+    FpSqrt: "fp.sqrt",
+    FpRem: "fp.rem",
+    FpRoundToIntegral: "fp.roundToIntegral",
+    FpMin: "fp.min",
+    FpMax: "fp.max",
+    FpLeq: "fp.leq",
+    FpLt: "fp.lt",
+    FpGeq: "fp.geq",
+    FpGt: "fp.gt",
+    FpEq: "fp.eq",
+    FpIsNormal: "fp.isNormal",
+    FpIsSubnormal: "fp.isSubnormal",
+    FpIsZero: "fp.isZero",
+    FpIsInfinite: "fp.isInfinite",
+    FpIsNaN: "fp.isNaN",
+    FpIsNegative: "fp.isNegative",
+    FpIsPositive: "fp.isPositive",
+    FpToReal: "fp.to_real",
+
+    FfAdd: "ff.add",
+    FfMul: "ff.mul",
+    FfNeg: "ff.neg",
+    FfBitSum: "ff.bitsum",
 
     RareList: "rare-list",
 });
@@ -481,14 +878,50 @@ impl_str_conversion_traits!(ParamOperator {
     BvBitOf: "bit_of",
     ZeroExtend: "zero_extend",
     SignExtend: "sign_extend",
+    BvRepeat: "repeat",
+    BvRotateLeft: "rotate_left",
+    BvRotateRight: "rotate_right",
     BvConst: "bv",
 
+    Divisible: "divisible",
+    Int2Bv: "int2bv",
+    IntToBv: "int_to_bv",
+
     RePower: "re.^",
     ReLoop: "re.loop",
 
+    FpPosZero: "+zero",
+    FpNegZero: "-zero",
+    FpPosInfinity: "+oo",
+    FpNegInfinity: "-oo",
+    FpNaN: "NaN",
+    ToFp: "to_fp",
+    ToFpUnsigned: "to_fp_unsigned",
+    FpToUbv: "fp.to_ubv",
+    FpToSbv: "fp.to_sbv",
+
+    DtTester: "is",
+
     ArrayConst: "const",
+    SetEmpty: "set.empty",
+    SetUniverse: "set.universe",
+    SeqEmpty: "seq.empty",
 });
 
+impl ParamOperator {
+    /// Returns `true` if the operator is a qualified operator, that is, one that is written as
+    /// `(as <op> <sort>)` instead of `(_ <op> <args>...)`.
+    pub fn is_qualified(&self) -> bool {
+        matches!(
+            self,
+            ParamOperator::ArrayConst
+                | ParamOperator::SetEmpty
+                | ParamOperator::SetUniverse
+                | ParamOperator::SeqEmpty
+        )
+    }
+}
+
 impl std::ops::Not for Binder {
     type Output = Self;
 
@@ -564,6 +997,12 @@ impl Term {
         Term::Const(Constant::BitVec(value.into(), widht.into()))
     }
 
+    /// Constructs a new finite field term. The value is reduced modulo the field order.
+    pub fn new_ff(value: impl Into<Integer>, order: impl Into<Integer>) -> Self {
+        let order = order.into();
+        Term::Const(Constant::FiniteField(value.into().modulo(&order), order))
+    }
+
     /// Constructs a new variable term.
     pub fn new_var(name: impl Into<String>, sort: Rc<Term>) -> Self {
         Term::Var(name.into(), sort)
@@ -873,6 +1312,7 @@ impl Constant {
             Constant::Real(_) => Sort::Real,
             Constant::String(_) => Sort::String,
             Constant::BitVec(_, width) => Sort::BitVec(width.clone()),
+            Constant::FiniteField(_, order) => Sort::FiniteField(order.clone()),
         }
     }
 
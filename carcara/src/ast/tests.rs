@@ -1,5 +1,8 @@
 use crate::{
-    ast::{node::ProofNode, pool::PrimitivePool, Polyeq, TermPool},
+    ast::{
+        node::ProofNode, pool::PrimitivePool, Clause, ClauseIndex, NaryOptions, Operator, Polyeq,
+        PolyeqCacheStats, Sort, Term, TermGenerator, TermLimitError, TermLimits, TermPool,
+    },
     parser::tests::parse_terms,
 };
 use indexmap::IndexSet;
@@ -123,6 +126,32 @@ fn test_polyeq() {
     );
 }
 
+#[test]
+fn test_polyeq_shared_cache() {
+    let mut pool = PrimitivePool::new();
+    let definitions = "
+        (declare-fun a () Int)
+        (declare-fun b () Int)
+    ";
+    let [t, u, v] = parse_terms(&mut pool, definitions, ["(= a b)", "(= b a)", "(= a a)"]);
+    let compare = |a, b| {
+        Polyeq::new()
+            .mod_reordering(true)
+            .shared_cache(true)
+            .eq(a, b)
+    };
+
+    // Both positive and negative results are cached, and each configuration has its own entries
+    let before = PolyeqCacheStats::current();
+    assert!(compare(&t, &u));
+    assert!(!compare(&t, &v));
+    assert!(compare(&t, &u));
+    assert!(!compare(&t, &v));
+    assert!(!Polyeq::new().shared_cache(true).eq(&t, &u));
+    let stats = PolyeqCacheStats::current().since(before);
+    assert_eq!(PolyeqCacheStats { hits: 2, misses: 3 }, stats);
+}
+
 #[test]
 fn test_node() {
     use crate::parser::tests::*;
@@ -162,3 +191,490 @@ fn test_node() {
     let got = node.into_commands();
     assert_eq!(expected.commands, got);
 }
+
+#[test]
+fn test_min_live_order() {
+    use crate::{ast::max_live_commands, parser::tests::*};
+
+    let original = "
+        (assume h0 (= 0 0))
+        (assume h1 (= 1 1))
+        (assume h2 (= 2 2))
+        (step t3 (cl true) :rule blah :premises (h1 h2))
+        (step t4 (cl) :rule blah :premises (h0 t3))
+    ";
+    let expected = "
+        (assume h1 (= 1 1))
+        (assume h2 (= 2 2))
+        (step t3 (cl true) :rule blah :premises (h1 h2))
+        (assume h0 (= 0 0))
+        (step t4 (cl) :rule blah :premises (h0 t3))
+    ";
+    let mut pool = PrimitivePool::new();
+    let original = parse_proof(&mut pool, original);
+    let expected = parse_proof(&mut pool, expected);
+    assert_eq!(max_live_commands(&original.commands), 3);
+
+    let got = ProofNode::from_commands(original.commands).into_commands_min_live();
+    assert_eq!(expected.commands, got);
+    assert_eq!(max_live_commands(&got), 2);
+}
+
+#[test]
+fn test_min_live_order_subproofs() {
+    use crate::{
+        ast::{max_live_commands, ProofCommand},
+        parser::tests::*,
+    };
+
+    fn ids(commands: &[ProofCommand]) -> Vec<String> {
+        commands
+            .iter()
+            .flat_map(|c| match c {
+                ProofCommand::Subproof(s) => ids(&s.commands),
+                other => vec![other.id().to_owned()],
+            })
+            .collect()
+    }
+
+    let original = "
+        (assume h0 (= 0 0))
+        (assume h1 (= 1 1))
+        (anchor :step t2)
+        (assume t2.a0 (= 2 2))
+        (anchor :step t2.t1)
+        (assume t2.t1.a0 (= 3 3))
+        (step t2.t1.t1 (cl true) :rule blah :premises (h1 t2.t1.a0 t2.a0))
+        (step t2.t1 (cl true) :rule blah :discharge (t2.t1.a0))
+        (step t2.t2 (cl true) :rule blah :premises (t2.t1 h0))
+        (step t2 (cl true) :rule blah :discharge (t2.a0))
+        (assume h3 (= 3 3))
+        (step t4 (cl) :rule blah :premises (h3 t2))
+    ";
+    let mut pool = PrimitivePool::new();
+    let original = parse_proof(&mut pool, original);
+
+    // `h0` and `h1` are used inside the subproof `t2`, so they stay live until `t2`
+    assert_eq!(max_live_commands(&original.commands), 2);
+
+    let got = ProofNode::from_commands(original.commands).into_commands_min_live();
+    let expected = [
+        "h0", "h1", "t2.a0", "t2.t1.a0", "t2.t1.t1", "t2.t1", "t2.t2", "t2", "h3", "t4",
+    ];
+    assert_eq!(ids(&got), expected);
+    assert_eq!(max_live_commands(&got), 2);
+}
+
+#[test]
+fn test_context_at() {
+    use crate::{
+        ast::context_at,
+        parser::{self, parse_instance_with_pool},
+    };
+
+    let definitions = "
+        (declare-fun a () Int) (declare-fun b () Int)
+        (declare-fun x () Int) (declare-fun y () Int) (declare-fun z () Int)
+    ";
+    let proof = "
+        (step t1 (cl) :rule hole)
+        (anchor :step t2 :args ((:= (x Int) a) (y Int)))
+            (step t2.t1 (cl) :rule hole)
+            (anchor :step t2.t2 :args ((:= (z Int) (+ x b))))
+                (step t2.t2.t1 (cl) :rule hole)
+                (step t2.t2 (cl) :rule hole)
+            (step t2 (cl) :rule hole)
+    ";
+    let mut pool = PrimitivePool::new();
+    let [a, b, x, y, z] = parse_terms(&mut pool, definitions, ["a", "b", "x", "y", "z"]);
+    let (_, proof) = parse_instance_with_pool(
+        definitions.as_bytes(),
+        proof.as_bytes(),
+        parser::Config::new(),
+        &mut pool,
+    )
+    .unwrap();
+
+    let top_level = context_at(&mut pool, &proof.commands, "t1").unwrap();
+    assert!(top_level.anchors.is_empty() && top_level.substitution.is_empty());
+
+    let context = context_at(&mut pool, &proof.commands, "t2.t2.t1").unwrap();
+    assert_eq!(context.anchors, ["t2", "t2.t2"]);
+    let bound: Vec<_> = context
+        .bound_variables
+        .iter()
+        .map(|(n, _)| n.as_str())
+        .collect();
+    assert_eq!(bound, ["x", "y", "z"]);
+    let expected = pool.add(Term::Op(Operator::Add, vec![a.clone(), b]));
+    let mappings: Vec<_> = context.substitution.iter().collect();
+    assert_eq!(mappings, [(&x, &a), (&z, &expected)]);
+
+    // The context at the last step of a subproof includes its own anchor
+    let context = context_at(&mut pool, &proof.commands, "t2").unwrap();
+    assert_eq!(context.anchors, ["t2"]);
+    assert!(context.substitution.iter().all(|(k, _)| *k != y));
+
+    assert!(context_at(&mut pool, &proof.commands, "t3").is_none());
+}
+
+#[test]
+fn test_clause() {
+    let mut pool = PrimitivePool::new();
+    let [a, b, c, not_a, not_not_b] = parse_terms(
+        &mut pool,
+        "(declare-const a Bool) (declare-const b Bool) (declare-const c Bool)",
+        ["a", "b", "c", "(not a)", "(not (not b))"],
+    );
+    let clause = |literals: &[_]| Clause::from(literals);
+
+    assert_eq!(
+        clause(&[a.clone(), b.clone()]),
+        clause(&[a.clone(), b.clone()])
+    );
+    assert_ne!(
+        clause(&[a.clone(), b.clone()]),
+        clause(&[b.clone(), a.clone()])
+    );
+
+    let ab = clause(&[a.clone(), b.clone()]);
+    assert!(ab.same_literals(&clause(&[b.clone(), a.clone(), b.clone()])));
+    assert!(!ab.same_literals(&clause(&[a.clone(), c.clone()])));
+
+    assert!(clause(&[]).subsumes(&ab));
+    assert!(clause(std::slice::from_ref(&b)).subsumes(&ab));
+    assert!(ab.subsumes(&clause(&[c.clone(), b.clone(), a.clone()])));
+    assert!(!ab.subsumes(&clause(&[a.clone(), c.clone()])));
+    assert!(!ab.subsumes(&clause(std::slice::from_ref(&b))));
+
+    assert!(clause(&[a.clone(), c.clone(), not_a.clone()]).is_tautology());
+    assert!(!clause(&[a.clone(), b.clone(), not_not_b.clone()]).is_tautology());
+    assert!(!clause(&[not_a.clone(), not_not_b.clone()]).is_tautology());
+    let taut = clause(&[b.clone(), not_a.clone(), c.clone(), a.clone()]);
+    let (x, y) = taut.complementary_pair().unwrap();
+    assert!((x, y) == (&a, &not_a) || (x, y) == (&not_a, &a));
+
+    let aab = clause(&[a.clone(), a.clone(), b.clone()]);
+    assert!(aab.is_contained_in(&clause(&[b.clone(), a.clone(), c.clone(), a.clone()])));
+    assert!(!aab.is_contained_in(&clause(&[b.clone(), a.clone(), c.clone()])));
+    assert_eq!(
+        aab.weaken_to(&[c.clone(), a.clone(), b.clone(), a.clone(), c.clone()]),
+        Some(vec![a.clone(), a.clone(), b.clone(), c.clone(), c.clone()])
+    );
+    assert_eq!(aab.weaken_to(&[a.clone(), b.clone(), c.clone()]), None);
+
+    // Large clauses also use a wide signature when checking for subsumption
+    let bool_sort = pool.add(Term::Sort(Sort::Bool));
+    let atoms: Vec<_> = (0..100)
+        .map(|i| pool.add(Term::new_var(format!("p{i}"), bool_sort.clone())))
+        .collect();
+    let large = clause(&atoms);
+    assert!(clause(&atoms[..60]).subsumes(&large));
+    assert!(!clause(&atoms[50..]).subsumes(&clause(&atoms[..80])));
+    assert!(clause(&atoms[50..]).is_contained_in(&large));
+}
+
+#[test]
+fn test_clause_index() {
+    let mut pool = PrimitivePool::new();
+    let [a, b] = parse_terms(
+        &mut pool,
+        "(declare-const a Bool) (declare-const b Bool)",
+        ["a", "b"],
+    );
+    let ab = Clause::from(&[a.clone(), b.clone()][..]);
+    let ba = Clause::from(&[b.clone(), a.clone()][..]);
+
+    let mut index = ClauseIndex::new();
+    assert_eq!(index.insert(ab.clone(), "t1"), None);
+    assert_eq!(index.insert(ba.clone(), "t2"), None);
+    assert_eq!(index.insert(ab.clone(), "t3"), Some(&"t1"));
+    assert_eq!(index.insert(ab.clone(), "t4"), Some(&"t1"));
+
+    assert_eq!(index.len(), 2);
+    assert_eq!(index.num_duplicates(), 2);
+    assert_eq!(index.representative(&ba), Some(&"t2"));
+    assert_eq!(index.get(&ab), ["t1", "t3", "t4"]);
+    assert!(index.get(&Clause::from(vec![a])).is_empty());
+
+    let duplicates: Vec<_> = index.duplicates(3).collect();
+    assert_eq!(duplicates, [(&ab, &["t1", "t3", "t4"][..])]);
+    assert_eq!(index.duplicates(4).count(), 0);
+}
+
+#[test]
+fn test_mk_nary() {
+    let mut pool = PrimitivePool::new();
+    let [p, q, r, p_and_q, or_nested] = parse_terms(
+        &mut pool,
+        "(declare-const p Bool) (declare-const q Bool) (declare-const r Bool)",
+        ["p", "q", "r", "(and p q)", "(or p (or q p) r)"],
+    );
+
+    assert_eq!(pool.mk_and(Vec::new()), pool.bool_true());
+    assert_eq!(pool.mk_or(Vec::new()), pool.bool_false());
+    assert_eq!(pool.mk_and(vec![p.clone()]), p);
+    assert_eq!(pool.mk_and(vec![p.clone(), q.clone()]), p_and_q);
+
+    let args = vec![p_and_q.clone(), r.clone(), p.clone()];
+    let [expected_flat, expected_dedup] = parse_terms(
+        &mut pool,
+        "(declare-const p Bool) (declare-const q Bool) (declare-const r Bool)",
+        ["(and p q r p)", "(and p q r)"],
+    );
+    let options = NaryOptions::new().flatten(true);
+    assert_eq!(
+        pool.mk_nary(Operator::And, args.clone(), options),
+        expected_flat
+    );
+    let options = options.remove_duplicates(true);
+    assert_eq!(pool.mk_nary(Operator::And, args, options), expected_dedup);
+
+    // Flattening only applies to applications of the same operator
+    let args = vec![or_nested.clone(), p_and_q.clone()];
+    let [expected] = parse_terms(
+        &mut pool,
+        "(declare-const p Bool) (declare-const q Bool) (declare-const r Bool)",
+        ["(or p q r (and p q))"],
+    );
+    let options = NaryOptions::new().flatten(true).remove_duplicates(true);
+    assert_eq!(pool.mk_nary(Operator::Or, args, options), expected);
+
+    let [a, b, a_eq_b] = parse_terms(
+        &mut pool,
+        "(declare-const a Int) (declare-const b Int)",
+        ["a", "b", "(= a b)"],
+    );
+    assert_eq!(pool.mk_eq(a, b), a_eq_b);
+}
+
+#[test]
+fn test_unused_declarations() {
+    use crate::{
+        ast::{find_unused_declarations, strip_unused_declarations},
+        parser,
+    };
+
+    let problem = "
+        (declare-sort U 0)
+        (declare-sort V 0)
+        (declare-sort W 0)
+        (declare-fun f (U) Int)
+        (declare-fun g (V) Int)
+        (declare-fun u () U)
+        (declare-fun w () W)
+        (declare-fun x () W)
+        (declare-fun p () Bool)
+        (declare-fun q () Bool)
+        (assert (= (f u) 0))
+    ";
+    let proof = "
+        (define-fun c1 () Bool (and p p))
+        (define-fun c2 () Bool (or q q))
+        (assume h1 (= (f u) 0))
+        (assume h2 (forall ((q Bool)) q))
+        (anchor :step t2 :args ((x W)))
+            (step t2.t1 (cl c1 (= x x)) :rule hole)
+            (step t2 (cl) :rule hole)
+    ";
+    let (mut problem, mut proof, _) =
+        parser::parse_instance(problem.as_bytes(), proof.as_bytes(), parser::Config::new())
+            .unwrap();
+
+    let unused = find_unused_declarations(&problem, &proof);
+    assert_eq!(unused.sorts, ["V"]);
+    // `x` and `q` only occur as bound variables
+    assert_eq!(unused.functions, ["g", "w", "x", "q"]);
+    assert_eq!(unused.definitions, ["c2"]);
+
+    let removed = strip_unused_declarations(&mut problem, &mut proof);
+    assert_eq!(removed, unused);
+    assert!(find_unused_declarations(&problem, &proof).is_empty());
+    let names: Vec<_> = proof.constant_definitions.iter().map(|(n, _)| n).collect();
+    assert_eq!(names, ["c1"]);
+}
+
+#[test]
+fn test_term_generator() {
+    let mut pool = PrimitivePool::new();
+    let generator = TermGenerator::boolean(&mut pool, &["a"]);
+
+    let leaves = generator.generate(&mut pool, &Sort::Bool, 0);
+    assert_eq!(leaves.len(), 3);
+
+    // Besides the 3 leaves, there are 3 applications of `not` and 9 applications of each of the 4
+    // binary connectives
+    let terms = generator.generate(&mut pool, &Sort::Bool, 1);
+    assert_eq!(terms.len(), 3 + 3 + 4 * 9);
+    assert_eq!(terms.iter().collect::<IndexSet<_>>().len(), terms.len());
+    assert_eq!(&terms[..3], leaves.as_slice());
+
+    // There are no terms of other sorts in the signature
+    assert!(generator.generate(&mut pool, &Sort::Int, 2).is_empty());
+
+    let limited = generator
+        .clone()
+        .limit(10)
+        .generate(&mut pool, &Sort::Bool, 3);
+    assert_eq!(limited, terms[..10]);
+
+    // Leaves can have any sort, and only well-sorted applications are generated
+    let int_sort = pool.add(Term::Sort(Sort::Int));
+    let x = pool.add(Term::new_var("x", int_sort));
+    let generator = TermGenerator::new()
+        .leaf(x)
+        .leaf(pool.add(Term::new_int(1)))
+        .operator(Operator::Add, vec![Sort::Int, Sort::Int], Sort::Int)
+        .operator(Operator::LessThan, vec![Sort::Int, Sort::Int], Sort::Bool);
+    assert_eq!(generator.generate(&mut pool, &Sort::Int, 1).len(), 2 + 4);
+    assert_eq!(generator.generate(&mut pool, &Sort::Bool, 1).len(), 4);
+    assert_eq!(generator.generate(&mut pool, &Sort::Bool, 2).len(), 4 + 32);
+}
+
+#[test]
+fn test_term_limits() {
+    let mut pool = PrimitivePool::new();
+    let [term] = parse_terms(
+        &mut pool,
+        "(declare-const p Bool) (declare-const q Bool)",
+        ["(and (or p q) (not (or p q)) p)"],
+    );
+
+    // The term has 5 distinct subterms, and depth 3
+    let limits = |size, depth| TermLimits::new().max_size(size).max_depth(depth);
+    assert_eq!(limits(None, None).check(&term), Ok(()));
+    assert_eq!(limits(Some(5), Some(3)).check(&term), Ok(()));
+    assert_eq!(
+        limits(Some(4), None).check(&term),
+        Err(TermLimitError::TooLarge(4))
+    );
+    assert_eq!(
+        limits(None, Some(2)).check(&term),
+        Err(TermLimitError::TooDeep(2))
+    );
+
+    assert_eq!(limits(Some(5), None).check_len(4), Ok(()));
+    assert!(limits(Some(5), None).check_len(5).is_err());
+    assert_eq!(limits(None, Some(3)).check_nesting(3), Ok(()));
+    assert!(limits(None, Some(3)).check_nesting(4).is_err());
+
+    let mut budget = limits(Some(5), None).budget();
+    assert_eq!(budget.charge(3), Ok(()));
+    assert_eq!(budget.charge(2), Ok(()));
+    assert_eq!(budget.charge(1), Err(TermLimitError::TooLarge(5)));
+    assert_eq!(limits(None, None).budget().charge(usize::MAX), Ok(()));
+}
+
+#[test]
+fn test_find_refutations() {
+    use crate::{
+        ast::{find_refutations, ProofCommand},
+        checker, parser,
+    };
+
+    let problem = "
+        (declare-const a Bool)
+        (declare-const b Bool)
+        (assert a)
+        (assert (not a))
+        (assert b)
+        (assert (not b))
+    ";
+    let proof = "
+        (assume h1 a)
+        (assume h2 (not a))
+        (assume h3 b)
+        (assume h4 (not b))
+        (step t1 (cl) :rule resolution :premises (h1 h2))
+        (step t2 (cl (not b)) :rule hole)
+        (step t3 (cl) :rule resolution :premises (h1 h4))
+        (step t4 (cl) :rule resolution :premises (t3))
+        (anchor :step t5)
+            (assume t5.a1 a)
+            (step t5.t1 (cl false) :rule hole :premises (t5.a1 h2))
+        (step t5 (cl (not a) false) :rule subproof :discharge (t5.a1))
+        (step t6 (cl false) :rule resolution :premises (h1 t5))
+        (step t7 (cl (not false)) :rule false)
+        (step t8 (cl) :rule resolution :premises (t6 t7))
+    ";
+    let (problem, proof, mut pool) =
+        parser::parse_instance(problem.as_bytes(), proof.as_bytes(), parser::Config::new())
+            .unwrap();
+
+    let refutations = find_refutations(&proof);
+    let ids: Vec<_> = refutations.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(ids, ["t1", "t4", "t8"]);
+
+    let commands: Vec<Vec<_>> = refutations
+        .iter()
+        .map(|r| r.proof.commands.iter().map(ProofCommand::id).collect())
+        .collect();
+    assert_eq!(commands[0], ["h1", "h2", "t1"]);
+    assert_eq!(commands[1], ["h1", "h4", "t3", "t4"]);
+    assert_eq!(commands[2], ["h1", "h2", "t5", "t6", "t7", "t8"]);
+    assert!(refutations.iter().all(|r| r.shared_steps.is_empty()));
+
+    let results: Vec<_> = refutations
+        .iter()
+        .map(|r| {
+            let mut checker = checker::ProofChecker::new(&mut pool, checker::Config::new());
+            checker.check(&problem, &r.proof).is_ok()
+        })
+        .collect();
+    assert_eq!(results, [true, false, true]);
+
+    // Refutations that use the same step are still split, but the shared step is reported
+    let proof = "
+        (assume h1 a)
+        (assume h2 (not a))
+        (step t1 (cl a) :rule resolution :premises (h1))
+        (step t2 (cl) :rule resolution :premises (t1 h2))
+        (step t3 (cl) :rule resolution :premises (h2 t1))
+    ";
+    let problem = "(declare-const a Bool) (assert a) (assert (not a))";
+    let (_, proof, _) =
+        parser::parse_instance(problem.as_bytes(), proof.as_bytes(), parser::Config::new())
+            .unwrap();
+    let refutations = find_refutations(&proof);
+    assert_eq!(refutations.len(), 2);
+    assert!(refutations.iter().all(|r| r.shared_steps == ["t1"]));
+}
+
+#[test]
+fn test_assumption_core() {
+    use crate::{ast::assumption_core, parser};
+
+    let problem: &[u8] = b"
+        (declare-const a Bool)
+        (declare-const b Bool)
+        (declare-const x Int)
+        (declare-const y Int)
+        (assert (or (not a) (not (= x y))))
+        (check-sat-assuming (b a (= x y)))
+    ";
+    let proof: &[u8] = b"
+        (assume h1 (or (not a) (not (= x y))))
+        (assume h2 a)
+        (assume h3 (= y x))
+        (assume h4 b)
+        (step t1 (cl (not a) (not (= x y))) :rule or :premises (h1))
+        (step t2 (cl (= x y)) :rule symm :premises (h3))
+        (step t3 (cl) :rule resolution :premises (t1 h2 t2))
+    ";
+    let (problem, proof, mut pool) =
+        parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+    assert_eq!(problem.assumptions.len(), 3);
+    assert!(problem
+        .assumptions
+        .iter()
+        .all(|a| problem.premises.contains(a)));
+
+    // `b` is assumed, but not used to derive the empty clause, and `(= x y)` is assumed with its
+    // sides flipped
+    let [a, x_eq_y] = parse_terms(
+        &mut pool,
+        "(declare-const a Bool) (declare-const x Int) (declare-const y Int)",
+        ["a", "(= x y)"],
+    );
+    assert_eq!(assumption_core(&problem, &proof), [a, x_eq_y]);
+}
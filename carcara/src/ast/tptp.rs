@@ -0,0 +1,490 @@
+//! An exporter from Alethe proofs to TPTP derivations.
+//!
+//! The derivation is written in the TFF (typed first-order form) dialect of TPTP, so it can be
+//! consumed by tools from the ATP ecosystem, such as the GDV derivation verifier and the IDV
+//! derivation viewer. Each Alethe step becomes an annotated formula whose source is an
+//! `inference` record. Since TPTP has no fixed set of inference rules, the Alethe rule name is used
+//! as the inference name, and is also kept in an `alethe_rule` useful info term, together with the
+//! step arguments.
+//!
+//! Built-in Boolean and arithmetic operators are translated to their TPTP counterparts. Operators
+//! without a TPTP counterpart (for example, string and bit-vector operators) are written as
+//! uninterpreted functions whose name is the SMT-LIB operator name. `choice` and `lambda` terms
+//! are written as uninterpreted constants.
+
+use super::*;
+use indexmap::{IndexMap, IndexSet};
+use std::io;
+
+/// Writes `proof` to `dest` as a TPTP derivation, in the TFF dialect.
+///
+/// The derivation starts with type declarations for the sorts and functions declared in the
+/// problem, and for the variables introduced by subproof anchors.
+pub fn write_tptp_proof(
+    pool: &mut PrimitivePool,
+    prelude: &ProblemPrelude,
+    proof: &Proof,
+    dest: &mut dyn io::Write,
+) -> io::Result<()> {
+    TptpPrinter::new(pool, dest).write_proof(prelude, proof)
+}
+
+struct TptpPrinter<'a> {
+    pool: &'a mut PrimitivePool,
+    inner: &'a mut dyn io::Write,
+
+    /// The scopes of bound variables. Each variable bound by a quantifier is mapped to its TPTP
+    /// variable name, and each variable bound by a `let` term is mapped to the translation of its
+    /// value.
+    scopes: Vec<IndexMap<String, String>>,
+
+    /// The number of TPTP variables created so far, used to generate fresh variable names.
+    num_variables: usize,
+}
+
+impl<'a> TptpPrinter<'a> {
+    fn new(pool: &'a mut PrimitivePool, inner: &'a mut dyn io::Write) -> Self {
+        Self {
+            pool,
+            inner,
+            scopes: Vec::new(),
+            num_variables: 0,
+        }
+    }
+
+    fn write_proof(&mut self, prelude: &ProblemPrelude, proof: &Proof) -> io::Result<()> {
+        let mut declared = IndexSet::new();
+        for (name, arity) in &prelude.sort_declarations {
+            let tt = "$tType";
+            let sort = match arity {
+                0 => tt.to_owned(),
+                1 => format!("{} > {}", tt, tt),
+                n => format!("({}) > {}", vec![tt; *n].join(" * "), tt),
+            };
+            self.write_type_declaration(name, &sort)?;
+        }
+        for (name, sort) in &prelude.function_declarations {
+            let sort = self.function_sort(sort);
+            self.write_type_declaration(name, &sort)?;
+            declared.insert(name.as_str());
+        }
+
+        // Variables introduced by anchors appear free in the steps of their subproofs, so they must
+        // be declared as constants
+        for command in proof.iter() {
+            if let ProofCommand::Subproof(s) = command {
+                for arg in &s.args {
+                    let (AnchorArg::Variable((name, sort)) | AnchorArg::Assign((name, sort), _)) =
+                        arg;
+                    if declared.insert(name) {
+                        let sort = self.sort(sort);
+                        self.write_type_declaration(name, &sort)?;
+                    }
+                }
+            }
+        }
+
+        let mut iter = proof.iter();
+        while let Some(command) = iter.next() {
+            match command {
+                ProofCommand::Assume { id, term } => {
+                    let formula = self.term(term);
+                    if iter.is_in_subproof() {
+                        writeln!(
+                            self.inner,
+                            "tff({}, assumption, {}, introduced(assumption, [alethe_rule(assume)])).",
+                            quote_name(id),
+                            formula,
+                        )?;
+                    } else {
+                        writeln!(self.inner, "tff({}, axiom, {}).", quote_name(id), formula)?;
+                    }
+                }
+                ProofCommand::Step(step) => {
+                    let formula = self.clause(&step.clause);
+                    let mut info = vec![
+                        "status(thm)".to_owned(),
+                        format!("alethe_rule({})", quote_name(&step.rule)),
+                    ];
+                    if !step.args.is_empty() {
+                        let args: Vec<_> = step
+                            .args
+                            .iter()
+                            .enumerate()
+                            .map(|(i, a)| match step.assigned_var(i) {
+                                Some((name, sort)) => format!("(:= ({} {}) {})", name, sort, a),
+                                None => a.to_string(),
+                            })
+                            .map(|a| quote_name(&a))
+                            .collect();
+                        info.push(format!("alethe_args([{}])", args.join(", ")));
+                    }
+                    let mut parents: Vec<_> = step
+                        .premises
+                        .iter()
+                        .map(|&p| quote_name(iter.get_premise(p).id()))
+                        .collect();
+                    if !step.discharge.is_empty() {
+                        let discharge: Vec<_> = step
+                            .discharge
+                            .iter()
+                            .map(|&p| quote_name(iter.get_premise(p).id()))
+                            .collect();
+                        info.push(format!("alethe_discharge([{}])", discharge.join(", ")));
+                    }
+
+                    // The step that ends a subproof implicitly depends on the command that precedes
+                    // it, so that is added as a parent
+                    if iter.is_end_step() {
+                        let subproof = iter.current_subproof().unwrap();
+                        if let [.., previous, _] = subproof {
+                            parents.push(quote_name(previous.id()));
+                        }
+                    }
+                    writeln!(
+                        self.inner,
+                        "tff({}, plain, {}, inference({}, [{}], [{}])).",
+                        quote_name(&step.id),
+                        formula,
+                        quote_name(&step.rule),
+                        info.join(", "),
+                        parents.join(", "),
+                    )?;
+                }
+                ProofCommand::Subproof(s) => {
+                    if s.args.is_empty() {
+                        writeln!(self.inner, "% anchor {}", command.id())?;
+                    } else {
+                        let args: Vec<_> = s
+                            .args
+                            .iter()
+                            .map(|arg| match arg {
+                                AnchorArg::Variable((name, sort)) => format!("({} {})", name, sort),
+                                AnchorArg::Assign((name, sort), value) => {
+                                    format!("(:= ({} {}) {})", name, sort, value)
+                                }
+                            })
+                            .collect();
+                        writeln!(
+                            self.inner,
+                            "% anchor {} :args ({})",
+                            command.id(),
+                            args.join(" ")
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_type_declaration(&mut self, name: &str, sort: &str) -> io::Result<()> {
+        writeln!(
+            self.inner,
+            "tff({}, type, {}: {}).",
+            quote_name(&format!("type_{}", name)),
+            quote_name(name),
+            sort
+        )
+    }
+
+    fn function_sort(&self, sort: &Rc<Term>) -> String {
+        match sort.as_ref() {
+            Term::Sort(Sort::Function(sorts)) => {
+                let (result, args) = sorts.split_last().unwrap();
+                let args: Vec<_> = args.iter().map(|s| self.sort(s)).collect();
+                let args = match args.as_slice() {
+                    [single] => single.clone(),
+                    _ => format!("({})", args.join(" * ")),
+                };
+                format!("{} > {}", args, self.sort(result))
+            }
+            _ => self.sort(sort),
+        }
+    }
+
+    fn sort(&self, sort: &Rc<Term>) -> String {
+        match sort.as_ref() {
+            Term::Sort(Sort::Bool) => "$o".to_owned(),
+            Term::Sort(Sort::Int) => "$int".to_owned(),
+            Term::Sort(Sort::Real) => "$real".to_owned(),
+            Term::Sort(Sort::Atom(name, args)) if args.is_empty() => quote_name(name),
+            Term::Sort(Sort::Function(_)) => self.function_sort(sort),
+            other => quote_name(&other.to_string()),
+        }
+    }
+
+    fn clause(&mut self, clause: &[Rc<Term>]) -> String {
+        match clause {
+            [] => "$false".to_owned(),
+            [single] => self.term(single),
+            _ => {
+                let literals: Vec<_> = clause.iter().map(|t| self.term(t)).collect();
+                format!("({})", literals.join(" | "))
+            }
+        }
+    }
+
+    fn term(&mut self, term: &Rc<Term>) -> String {
+        match term.as_ref() {
+            Term::Const(c) => constant(c),
+            Term::Var(name, _) => {
+                let bound = self.scopes.iter().rev().find_map(|scope| scope.get(name));
+                match bound {
+                    Some(s) => s.clone(),
+                    None => quote_name(name),
+                }
+            }
+            Term::App(f, args) => {
+                let f = self.term(f);
+                let args = self.terms(args);
+                format!("{}({})", f, args.join(", "))
+            }
+            Term::Op(op, args) => self.operation(*op, args),
+            Term::Sort(_) => self.sort(term),
+            Term::Binder(binder @ (Binder::Forall | Binder::Exists), bindings, inner) => {
+                let mut scope = IndexMap::new();
+                let mut variables = Vec::new();
+                for (name, sort) in bindings {
+                    let var = self.fresh_variable(name);
+                    variables.push(format!("{}: {}", var, self.sort(sort)));
+                    scope.insert(name.clone(), var);
+                }
+                self.scopes.push(scope);
+                let inner = self.term(inner);
+                self.scopes.pop();
+
+                let quantifier = if *binder == Binder::Forall { '!' } else { '?' };
+                format!("({} [{}] : {})", quantifier, variables.join(", "), inner)
+            }
+            Term::Binder(..) => quote_name(&term.to_string()),
+            Term::Let(bindings, inner) => {
+                let scope = bindings
+                    .iter()
+                    .map(|(name, value)| (name.clone(), self.term(value)))
+                    .collect();
+                self.scopes.push(scope);
+                let inner = self.term(inner);
+                self.scopes.pop();
+                inner
+            }
+            // TPTP has no divisibility predicate, so we translate it using the euclidean remainder,
+            // as we do for `mod`
+            Term::ParamOp {
+                op: ParamOperator::Divisible,
+                op_args,
+                args,
+            } => format!(
+                "($remainder_e({}, {}) = 0)",
+                self.term(&args[0]),
+                op_args[0]
+            ),
+            Term::ParamOp { op, op_args, args } => {
+                let name = std::iter::once(op.to_string())
+                    .chain(op_args.iter().map(ToString::to_string))
+                    .collect::<Vec<_>>()
+                    .join("_");
+                if args.is_empty() {
+                    quote_name(&name)
+                } else {
+                    format!("{}({})", quote_name(&name), self.terms(args).join(", "))
+                }
+            }
+        }
+    }
+
+    fn terms(&mut self, terms: &[Rc<Term>]) -> Vec<String> {
+        terms.iter().map(|t| self.term(t)).collect()
+    }
+
+    fn fresh_variable(&mut self, name: &str) -> String {
+        let suffix: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let var = format!("X{}_{}", self.num_variables, suffix);
+        self.num_variables += 1;
+        var
+    }
+
+    fn operation(&mut self, op: Operator, args: &[Rc<Term>]) -> String {
+        let infix = |args: Vec<String>, connective: &str| format!("({})", args.join(connective));
+        let function = |name: &str, args: &[String]| format!("{}({})", name, args.join(", "));
+        let fold = |name: &str, args: Vec<String>| {
+            let mut iter = args.into_iter();
+            let first = iter.next().unwrap_or_default();
+            iter.fold(first, |acc, a| format!("{}({}, {})", name, acc, a))
+        };
+        // Chainable operators like `=` and `<` are translated to a conjunction of the relations
+        // between adjacent arguments
+        let chain = |args: Vec<String>, relation: &dyn Fn(&str, &str) -> String| {
+            let pairs: Vec<_> = args.windows(2).map(|w| relation(&w[0], &w[1])).collect();
+            match pairs.len() {
+                1 => pairs.into_iter().next().unwrap(),
+                _ => format!("({})", pairs.join(" & ")),
+            }
+        };
+
+        let first_sort = args.first().map(|a| self.pool.sort(a));
+        let first_sort = first_sort.as_ref().and_then(|s| s.as_sort());
+        let is_bool = first_sort == Some(&Sort::Bool);
+        let is_real = first_sort == Some(&Sort::Real);
+        let translated = self.terms(args);
+        match op {
+            Operator::True => "$true".to_owned(),
+            Operator::False => "$false".to_owned(),
+            Operator::Not => format!("~ {}", translated[0]),
+            Operator::And => infix(translated, " & "),
+            Operator::Or => infix(translated, " | "),
+            // TPTP's `<~>` is binary, so an n-ary `xor` is nested to the left
+            Operator::Xor => {
+                let mut iter = translated.into_iter();
+                let first = iter.next().unwrap();
+                iter.fold(first, |acc, a| format!("({} <~> {})", acc, a))
+            }
+            Operator::Implies => {
+                let mut iter = translated.into_iter().rev();
+                let last = iter.next().unwrap();
+                iter.fold(last, |acc, a| format!("({} => {})", a, acc))
+            }
+            Operator::Equals if is_bool => chain(translated, &|a, b| format!("({} <=> {})", a, b)),
+            Operator::Equals => chain(translated, &|a, b| format!("({} = {})", a, b)),
+            Operator::Distinct => function("$distinct", &translated),
+            Operator::Ite => function("$ite", &translated),
+            Operator::Add => fold("$sum", translated),
+            Operator::Sub if translated.len() == 1 => function("$uminus", &translated),
+            Operator::Sub => fold("$difference", translated),
+            Operator::Mult => fold("$product", translated),
+            Operator::RealDiv => fold("$quotient", translated),
+            Operator::IntDiv => fold("$quotient_e", translated),
+            Operator::Mod => function("$remainder_e", &translated),
+            Operator::Abs => {
+                let a = &translated[0];
+                let zero = if is_real { "0.0" } else { "0" };
+                format!("$ite($less({}, {}), $uminus({}), {})", a, zero, a, a)
+            }
+            Operator::LessThan => chain(translated, &|a, b| format!("$less({}, {})", a, b)),
+            Operator::GreaterThan => chain(translated, &|a, b| format!("$greater({}, {})", a, b)),
+            Operator::LessEq => chain(translated, &|a, b| format!("$lesseq({}, {})", a, b)),
+            Operator::GreaterEq => chain(translated, &|a, b| format!("$greatereq({}, {})", a, b)),
+            Operator::ToReal => function("$to_real", &translated),
+            Operator::ToInt => function("$to_int", &translated),
+            Operator::IsInt => function("$is_int", &translated),
+            other if translated.is_empty() => quote_name(&other.to_string()),
+            other => function(&quote_name(&other.to_string()), &translated),
+        }
+    }
+}
+
+fn constant(c: &Constant) -> String {
+    match c {
+        Constant::Integer(i) => i.to_string(),
+        Constant::Real(r) if r.is_integer() => format!("{}.0", r.numer()),
+        Constant::Real(r) => format!("$quotient({}.0, {}.0)", r.numer(), r.denom()),
+        Constant::String(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+        Constant::BitVec(..) | Constant::FiniteField(..) => quote_name(&c.to_string()),
+    }
+}
+
+/// Returns `name` as a TPTP atomic word. If `name` is not a valid lower word, it is quoted with
+/// single quotes.
+fn quote_name(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_lower_word = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_lower_word {
+        name.to_owned()
+    } else {
+        format!("'{}'", name.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn test_write_tptp_proof() {
+        let problem: &[u8] = b"
+            (declare-sort U 0)
+            (declare-fun f (U Int) Int)
+            (declare-const a U)
+            (declare-const p Bool)
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (forall ((x Int)) (< (f a x) 2)))
+            (assume h2 (not (= p (<= (f a 1) 3))))
+            (anchor :step t3 :args ((y Int)))
+            (step t3.t1 (cl (= y y)) :rule refl)
+            (step t3 (cl (= (forall ((x Int)) (< (f a x) 2)) (forall ((y Int)) (< (f a y) 2))))
+                :rule bind)
+            (step t4 (cl (or p (not (= 1.0 2.5)))) :rule my_rule :args (1 \"it's\"))
+            (step t5 (cl) :rule resolution :premises (h1 t3 h2 t4))
+            (step t6 (cl ((_ divisible 2) (f a 1))) :rule hole)
+        ";
+        let (problem, proof, mut pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+
+        let mut output = Vec::new();
+        write_tptp_proof(&mut pool, &problem.prelude, &proof, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let expected = [
+            "tff(type_U, type, 'U': $tType).",
+            "tff(type_f, type, f: ('U' * $int) > $int).",
+            "tff(type_a, type, a: 'U').",
+            "tff(type_p, type, p: $o).",
+            "tff(type_y, type, y: $int).",
+            "tff(h1, axiom, (! [X0_x: $int] : $less(f(a, X0_x), 2))).",
+            "tff(h2, axiom, ~ (p <=> $lesseq(f(a, 1), 3))).",
+            "% anchor t3 :args ((y Int))",
+            "tff('t3.t1', plain, (y = y), inference(refl, [status(thm), alethe_rule(refl)], [])).",
+            "tff(t3, plain, ((! [X1_x: $int] : $less(f(a, X1_x), 2)) <=> \
+            (! [X2_y: $int] : $less(f(a, X2_y), 2))), \
+            inference(bind, [status(thm), alethe_rule(bind)], ['t3.t1'])).",
+            "tff(t4, plain, (p | ~ (1.0 = $quotient(5.0, 2.0))), inference(my_rule, \
+            [status(thm), alethe_rule(my_rule), alethe_args(['1', '\"it\\'s\"'])], [])).",
+            "tff(t5, plain, $false, inference(resolution, [status(thm), \
+            alethe_rule(resolution)], [h1, t3, h2, t4])).",
+            "tff(t6, plain, ($remainder_e(f(a, 1), 2) = 0), inference(hole, [status(thm), \
+            alethe_rule(hole)], [])).",
+        ];
+        assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_write_tptp_operators() {
+        let problem: &[u8] = b"
+            (declare-const p Bool)
+            (declare-const q Bool)
+            (declare-const r Bool)
+            (declare-const x Real)
+            (declare-const n Int)
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (xor p q r))
+            (assume h3 (= (abs n) 1))
+        ";
+        let (problem, mut proof, mut pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+
+        // The parser only accepts `abs` over integers, so the real-valued case is built directly
+        let real = pool.add(Term::Sort(Sort::Real));
+        let x = pool.add(Term::new_var("x", real));
+        let abs_x = pool.add(Term::Op(Operator::Abs, vec![x]));
+        proof
+            .commands
+            .insert(1, ProofCommand::Assume { id: "h2".to_owned(), term: abs_x });
+
+        let mut output = Vec::new();
+        write_tptp_proof(&mut pool, &problem.prelude, &proof, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let axioms: Vec<_> = output.lines().filter(|l| l.contains("axiom")).collect();
+        let expected = [
+            "tff(h1, axiom, ((p <~> q) <~> r)).",
+            "tff(h2, axiom, $ite($less(x, 0.0), $uminus(x), x)).",
+            "tff(h3, axiom, ($ite($less(n, 0), $uminus(n), n) = 1)).",
+        ];
+        assert_eq!(axioms, expected);
+    }
+}
@@ -0,0 +1,226 @@
+//! Detection and removal of declarations that are never used by a problem or its proof.
+
+use super::{AnchorArg, Problem, Proof, ProofCommand, Rc, Sort, Term};
+use std::collections::{HashMap, HashSet};
+
+/// The declarations in a problem and proof that are never used.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UnusedDeclarations {
+    /// The names of the sorts declared with `declare-sort` in the problem.
+    pub sorts: Vec<String>,
+
+    /// The names of the functions declared with `declare-fun` or `declare-const` in the problem.
+    pub functions: Vec<String>,
+
+    /// The names of the constants defined with `define-fun` in the proof.
+    pub definitions: Vec<String>,
+}
+
+impl UnusedDeclarations {
+    pub fn is_empty(&self) -> bool {
+        self.sorts.is_empty() && self.functions.is_empty() && self.definitions.is_empty()
+    }
+}
+
+/// Collects the free variables, sort names and subterms that appear in a set of terms.
+#[derive(Default)]
+struct UsageCollector<'a> {
+    visited: HashSet<&'a Rc<Term>>,
+    free_vars_cache: HashMap<&'a Rc<Term>, HashSet<&'a Rc<Term>>>,
+    vars: HashSet<(&'a str, &'a Rc<Term>)>,
+    sort_names: HashSet<&'a str>,
+}
+
+impl<'a> UsageCollector<'a> {
+    /// Marks a term, and the sorts and free variables in it, as used. Free variables whose names
+    /// are bound by an enclosing anchor are ignored.
+    fn use_term(&mut self, term: &'a Rc<Term>, bound: &HashSet<&'a str>) {
+        self.visit(term);
+        for var in self.free_vars(term) {
+            if let Term::Var(name, sort) = var.as_ref() {
+                if !bound.contains(name.as_str()) {
+                    self.vars.insert((name, sort));
+                }
+            }
+        }
+    }
+
+    /// Computes the free variables of a term. Since a binder shadows any variable with the same
+    /// name, bound variables are removed by name.
+    fn free_vars(&mut self, term: &'a Rc<Term>) -> HashSet<&'a Rc<Term>> {
+        if let Some(vars) = self.free_vars_cache.get(term) {
+            return vars.clone();
+        }
+        let vars = match term.as_ref() {
+            Term::Const(_) | Term::Sort(_) => HashSet::new(),
+            Term::Var(..) => HashSet::from([term]),
+            Term::App(f, args) => {
+                let mut vars = self.free_vars(f);
+                args.iter().for_each(|a| vars.extend(self.free_vars(a)));
+                vars
+            }
+            Term::Op(_, args) | Term::ParamOp { args, .. } => {
+                let mut vars = HashSet::new();
+                args.iter().for_each(|a| vars.extend(self.free_vars(a)));
+                vars
+            }
+            Term::Binder(_, bindings, inner) => {
+                let mut vars = self.free_vars(inner);
+                vars.retain(|v| !bindings.iter().any(|(name, _)| is_named(v, name)));
+                vars
+            }
+            Term::Let(bindings, inner) => {
+                let mut vars = self.free_vars(inner);
+                vars.retain(|v| !bindings.iter().any(|(name, _)| is_named(v, name)));
+                bindings
+                    .iter()
+                    .for_each(|(_, value)| vars.extend(self.free_vars(value)));
+                vars
+            }
+        };
+        self.free_vars_cache.insert(term, vars.clone());
+        vars
+    }
+
+    /// Visits every subterm of a term, collecting the sort names that appear in it.
+    fn visit(&mut self, term: &'a Rc<Term>) {
+        if !self.visited.insert(term) {
+            return;
+        }
+        match term.as_ref() {
+            Term::Const(_) => (),
+            Term::Var(_, sort) => self.visit(sort),
+            Term::App(f, args) => {
+                self.visit(f);
+                args.iter().for_each(|a| self.visit(a));
+            }
+            Term::Op(_, args) => args.iter().for_each(|a| self.visit(a)),
+            Term::Sort(sort) => match sort {
+                Sort::Function(sorts) => sorts.iter().for_each(|s| self.visit(s)),
+                Sort::Atom(name, args) => {
+                    self.sort_names.insert(name);
+                    args.iter().for_each(|a| self.visit(a));
+                }
+                Sort::Array(x, y) => {
+                    self.visit(x);
+                    self.visit(y);
+                }
+                Sort::Set(x) | Sort::Seq(x) => self.visit(x),
+                Sort::Bool
+                | Sort::Int
+                | Sort::Real
+                | Sort::String
+                | Sort::RegLan
+                | Sort::BitVec(_)
+                | Sort::FloatingPoint(_, _)
+                | Sort::RoundingMode
+                | Sort::FiniteField(_)
+                | Sort::RareList
+                | Sort::Type => (),
+            },
+            Term::Binder(_, bindings, inner) => {
+                bindings.iter().for_each(|(_, sort)| self.visit(sort));
+                self.visit(inner);
+            }
+            Term::Let(bindings, inner) => {
+                bindings.iter().for_each(|(_, value)| self.visit(value));
+                self.visit(inner);
+            }
+            Term::ParamOp { op_args, args, .. } => {
+                op_args.iter().for_each(|a| self.visit(a));
+                args.iter().for_each(|a| self.visit(a));
+            }
+        }
+    }
+
+    fn visit_commands(&mut self, commands: &'a [ProofCommand], bound: &HashSet<&'a str>) {
+        for command in commands {
+            match command {
+                ProofCommand::Assume { term, .. } => self.use_term(term, bound),
+                ProofCommand::Step(s) => {
+                    s.clause.iter().for_each(|t| self.use_term(t, bound));
+                    s.args.iter().for_each(|t| self.use_term(t, bound));
+                }
+                ProofCommand::Subproof(s) => {
+                    let mut bound = bound.clone();
+                    for arg in &s.args {
+                        match arg {
+                            AnchorArg::Variable((name, sort)) => {
+                                self.visit(sort);
+                                bound.insert(name);
+                            }
+                            AnchorArg::Assign((name, sort), value) => {
+                                self.visit(sort);
+                                self.use_term(value, &bound);
+                                bound.insert(name);
+                            }
+                        }
+                    }
+                    self.visit_commands(&s.commands, &bound);
+                }
+            }
+        }
+    }
+}
+
+fn is_named(var: &Rc<Term>, name: &str) -> bool {
+    matches!(var.as_ref(), Term::Var(n, _) if n == name)
+}
+
+/// Finds the declarations in the problem prelude, and the constant definitions in the proof, that
+/// are never used by the problem's assertions or by the proof.
+///
+/// A function is considered used if it occurs free in any of the terms, so occurrences of variables
+/// with the same name that are bound by a binder or by an anchor are not counted. A sort is
+/// considered used if it appears in the sort of any term that is used. In particular, a
+/// sort that is only used by unused functions is also considered unused. Since constant definitions
+/// are expanded when the proof is parsed, a definition is considered used if its body appears in the
+/// proof.
+pub fn find_unused_declarations(problem: &Problem, proof: &Proof) -> UnusedDeclarations {
+    let mut collector = UsageCollector::default();
+    let bound = HashSet::new();
+    problem
+        .premises
+        .iter()
+        .for_each(|t| collector.use_term(t, &bound));
+    collector.visit_commands(&proof.commands, &bound);
+
+    let prelude = &problem.prelude;
+    UnusedDeclarations {
+        sorts: prelude
+            .sort_declarations
+            .iter()
+            .filter(|(name, _)| !collector.sort_names.contains(name.as_str()))
+            .map(|(name, _)| name.clone())
+            .collect(),
+        functions: prelude
+            .function_declarations
+            .iter()
+            .filter(|(name, sort)| !collector.vars.contains(&(name.as_str(), sort)))
+            .map(|(name, _)| name.clone())
+            .collect(),
+        definitions: proof
+            .constant_definitions
+            .iter()
+            .filter(|(_, body)| !collector.visited.contains(body))
+            .map(|(name, _)| name.clone())
+            .collect(),
+    }
+}
+
+/// Removes the declarations and constant definitions that are never used by the problem's
+/// assertions or by the proof (see [`find_unused_declarations`]), and returns them.
+pub fn strip_unused_declarations(problem: &mut Problem, proof: &mut Proof) -> UnusedDeclarations {
+    let unused = find_unused_declarations(problem, proof);
+    let prelude = &mut problem.prelude;
+    prelude
+        .sort_declarations
+        .retain(|(name, _)| !unused.sorts.contains(name));
+    prelude
+        .function_declarations
+        .retain(|(name, _)| !unused.functions.contains(name));
+    proof
+        .constant_definitions
+        .retain(|(name, _)| !unused.definitions.contains(name));
+    unused
+}
@@ -0,0 +1,112 @@
+use super::features::collect_subterms;
+use crate::ast::{write_proof, PrimitivePool, ProblemPrelude, Proof, ProofCommand};
+use std::{collections::HashSet, fmt, io};
+
+/// Measures of the size of a proof, used to compare a proof before and after elaboration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProofMetrics {
+    /// The number of steps, including the ones inside subproofs.
+    pub steps: usize,
+
+    /// The number of distinct terms in the conclusions and arguments of all steps.
+    pub terms: usize,
+
+    /// The size, in bytes, of the proof when printed without term sharing.
+    pub size: usize,
+
+    /// The maximum subproof nesting depth of any step.
+    pub max_depth: usize,
+}
+
+/// An `io::Write` implementation that discards its input, and only counts how many bytes were
+/// written.
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ProofMetrics {
+    pub fn new(pool: &mut PrimitivePool, prelude: &ProblemPrelude, proof: &Proof) -> Self {
+        let mut steps = 0;
+        let mut terms = HashSet::new();
+        let mut max_depth = 0;
+
+        let mut iter = proof.iter();
+        while let Some(command) = iter.next() {
+            match command {
+                ProofCommand::Step(s) => {
+                    steps += 1;
+                    max_depth = max_depth.max(iter.depth());
+                    for t in s.clause.iter().chain(&s.args) {
+                        collect_subterms(t, &mut terms);
+                    }
+                }
+                ProofCommand::Assume { term, .. } => collect_subterms(term, &mut terms),
+                ProofCommand::Subproof(_) => (),
+            }
+        }
+        let terms = terms.len();
+
+        let mut counter = ByteCounter(0);
+        // Writing to a `ByteCounter` never fails
+        write_proof(pool, prelude, &mut counter, proof, false).unwrap();
+
+        Self {
+            steps,
+            terms,
+            size: counter.0,
+            max_depth,
+        }
+    }
+}
+
+/// The change in the [`ProofMetrics`] of a proof caused by elaboration. Its `Display`
+/// implementation prints a table with the metrics before and after elaboration, and the
+/// difference between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricDeltas {
+    pub before: ProofMetrics,
+    pub after: ProofMetrics,
+}
+
+impl fmt::Display for MetricDeltas {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows = [
+            ("steps", self.before.steps, self.after.steps),
+            ("terms", self.before.terms, self.after.terms),
+            ("size (bytes)", self.before.size, self.after.size),
+            ("max depth", self.before.max_depth, self.after.max_depth),
+        ];
+        writeln!(
+            f,
+            "{:<20}{:>12}{:>12}{:>12}",
+            "proof metrics:", "input", "elaborated", "delta"
+        )?;
+        for (name, before, after) in rows {
+            let delta = after as i128 - before as i128;
+            let percent = if before == 0 {
+                String::new()
+            } else {
+                format!(" ({:+.02}%)", delta as f64 * 100.0 / before as f64)
+            };
+            writeln!(
+                f,
+                "    {:<16}{:>12}{:>12}{:>+12}{}",
+                format!("{}:", name),
+                before,
+                after,
+                delta,
+                percent
+            )?;
+        }
+        Ok(())
+    }
+}
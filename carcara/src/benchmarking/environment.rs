@@ -0,0 +1,151 @@
+use super::JsonString;
+use std::{fmt, io, path::PathBuf};
+
+/// An input file used in a run, identified by its contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputFile {
+    pub path: PathBuf,
+
+    /// The size of the file, in bytes.
+    pub size: u64,
+
+    /// The SHA-256 hash of the file contents, as a lowercase hexadecimal string. This is the same
+    /// as the output of the `sha256sum` tool.
+    pub sha256: String,
+}
+
+/// The conditions under which a run happened, such as the Carcara version, the configuration used,
+/// the host machine, and the input files.
+///
+/// This is included in reports and statistics, so results can be traced back to the exact
+/// conditions that produced them. Capturing the environment is left to the tools built on the
+/// library, since they know the configuration and inputs of the run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Environment {
+    /// The Carcara version, including any details the tool that captured it may add, like the git
+    /// commit.
+    pub version: String,
+
+    /// The configuration used in the run, as a list of key-value pairs.
+    pub configuration: Vec<(String, String)>,
+
+    /// The operating system, as given by `std::env::consts::OS`.
+    pub os: String,
+
+    /// The CPU architecture, as given by `std::env::consts::ARCH`.
+    pub arch: String,
+
+    /// The name of the host machine, if it could be determined.
+    pub hostname: Option<String>,
+
+    /// The number of CPUs available to the process.
+    pub num_cpus: usize,
+
+    pub input_files: Vec<InputFile>,
+}
+
+impl Environment {
+    pub fn add_configuration(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.configuration.push((key.into(), value.into()));
+    }
+
+    /// Writes the environment as a JSON object. The object is not followed by a newline, so it can
+    /// be nested in other JSON values.
+    pub fn write_json(&self, dest: &mut dyn io::Write) -> io::Result<()> {
+        write!(dest, "{{\"version\": {}", JsonString(&self.version))?;
+        write!(dest, ", \"configuration\": {{")?;
+        for (i, (key, value)) in self.configuration.iter().enumerate() {
+            let separator = if i == 0 { "" } else { ", " };
+            write!(
+                dest,
+                "{}{}: {}",
+                separator,
+                JsonString(key),
+                JsonString(value)
+            )?;
+        }
+        write!(dest, "}}, \"os\": {}", JsonString(&self.os))?;
+        write!(dest, ", \"arch\": {}", JsonString(&self.arch))?;
+        match &self.hostname {
+            Some(name) => write!(dest, ", \"hostname\": {}", JsonString(name))?,
+            None => write!(dest, ", \"hostname\": null")?,
+        }
+        write!(dest, ", \"num_cpus\": {}", self.num_cpus)?;
+        write!(dest, ", \"input_files\": [")?;
+        for (i, file) in self.input_files.iter().enumerate() {
+            let separator = if i == 0 { "" } else { ", " };
+            write!(
+                dest,
+                "{}{{\"path\": {}, \"size\": {}, \"sha256\": {}}}",
+                separator,
+                JsonString(&file.path.to_string_lossy()),
+                file.size,
+                JsonString(&file.sha256),
+            )?;
+        }
+        write!(dest, "]}}")
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "version:             {}", self.version)?;
+        writeln!(
+            f,
+            "host:                {} ({}-{}, {} cpus)",
+            self.hostname.as_deref().unwrap_or("unknown"),
+            self.arch,
+            self.os,
+            self.num_cpus,
+        )?;
+        for (key, value) in &self.configuration {
+            writeln!(f, "{:<20} {}", format!("{}:", key), value)?;
+        }
+        for file in &self.input_files {
+            writeln!(
+                f,
+                "input file:          {} ({})",
+                file.path.display(),
+                file.sha256
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_environment_json() {
+        let mut env = Environment {
+            version: "1.0 \"test\"".to_owned(),
+            os: "linux".to_owned(),
+            num_cpus: 4,
+            ..Default::default()
+        };
+        env.add_configuration("command-line", "carcara check\tfoo");
+        let mut out = Vec::new();
+        env.write_json(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with(
+            r#"{"version": "1.0 \"test\"", "configuration": {"command-line": "carcara check\tfoo"}"#
+        ));
+        assert!(out.contains(r#""os": "linux""#));
+        assert!(out.contains(r#""hostname": null, "num_cpus": 4"#));
+        assert!(out.ends_with(r#""input_files": []}"#));
+
+        env.input_files.push(InputFile {
+            path: PathBuf::from("a.smt2"),
+            size: 3,
+            sha256: "abc".to_owned(),
+        });
+        let mut out = Vec::new();
+        env.write_json(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(
+            out.ends_with(r#""input_files": [{"path": "a.smt2", "size": 3, "sha256": "abc"}]}"#)
+        );
+    }
+}
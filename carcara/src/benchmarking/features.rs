@@ -0,0 +1,303 @@
+use super::{Environment, LiteralStatistics};
+use crate::ast::{Proof, ProofCommand, Rc, Term, TermPool};
+use indexmap::IndexMap;
+use std::{collections::HashSet, io};
+
+/// The rules whose uses are counted individually in the features. This list is fixed, and does not
+/// follow the rules implemented by the checker, so that the feature vector keeps the same dimension
+/// and layout as rules are added. Steps using any other rule are counted together in `rule.other`.
+/// Changing this list changes the layout, and breaks models trained on features from older versions.
+const FEATURE_RULES: &[&str] = &[
+    "true",
+    "false",
+    "not_not",
+    "and_pos",
+    "and_neg",
+    "or_pos",
+    "or_neg",
+    "xor_pos1",
+    "xor_pos2",
+    "xor_neg1",
+    "xor_neg2",
+    "implies_pos",
+    "implies_neg1",
+    "implies_neg2",
+    "equiv_pos1",
+    "equiv_pos2",
+    "equiv_neg1",
+    "equiv_neg2",
+    "ite_pos1",
+    "ite_pos2",
+    "ite_neg1",
+    "ite_neg2",
+    "eq_reflexive",
+    "eq_transitive",
+    "eq_congruent",
+    "eq_congruent_pred",
+    "distinct_elim",
+    "la_rw_eq",
+    "la_generic",
+    "la_disequality",
+    "la_totality",
+    "la_tautology",
+    "la_trichotomy",
+    "forall_inst",
+    "qnt_join",
+    "qnt_rm_unused",
+    "resolution",
+    "th_resolution",
+    "refl",
+    "trans",
+    "cong",
+    "ho_cong",
+    "and",
+    "tautology",
+    "not_or",
+    "or",
+    "not_and",
+    "xor1",
+    "xor2",
+    "not_xor1",
+    "not_xor2",
+    "implies",
+    "not_implies1",
+    "not_implies2",
+    "equiv1",
+    "equiv2",
+    "not_equiv1",
+    "not_equiv2",
+    "ite1",
+    "ite2",
+    "not_ite1",
+    "not_ite2",
+    "ite_intro",
+    "contraction",
+    "connective_def",
+    "ite_simplify",
+    "eq_simplify",
+    "and_simplify",
+    "or_simplify",
+    "not_simplify",
+    "implies_simplify",
+    "equiv_simplify",
+    "bool_simplify",
+    "all_simplify",
+    "rare_rewrite",
+    "qnt_simplify",
+    "div_simplify",
+    "prod_simplify",
+    "unary_minus_simplify",
+    "minus_simplify",
+    "sum_simplify",
+    "comp_simplify",
+    "nary_elim",
+    "ac_simp",
+    "bfun_elim",
+    "bind",
+    "qnt_cnf",
+    "subproof",
+    "let",
+    "onepoint",
+    "sko_ex",
+    "sko_forall",
+    "reordering",
+    "symm",
+    "not_symm",
+    "eq_symmetric",
+    "weakening",
+    "bind_let",
+    "la_mult_pos",
+    "la_mult_neg",
+    "mod_simplify",
+    "bitblast_extract",
+    "bitblast_bvadd",
+    "bitblast_ult",
+    "concat_eq",
+    "concat_unify",
+    "concat_conflict",
+    "concat_csplit_prefix",
+    "concat_csplit_suffix",
+    "concat_split_prefix",
+    "concat_split_suffix",
+    "concat_lprop_prefix",
+    "concat_lprop_suffix",
+    "concat_cprop_prefix",
+    "concat_cprop_suffix",
+    "string_decompose",
+    "string_length_pos",
+    "string_length_non_empty",
+    "re_inter",
+    "re_unfold_neg",
+    "re_unfold_neg_concat_fixed_prefix",
+    "re_unfold_neg_concat_fixed_suffix",
+    "hole",
+    "lia_generic",
+    "strict_resolution",
+];
+
+/// A fixed-dimension numeric summary of a proof.
+///
+/// Every proof has the same features, in the same order, regardless of its contents. This makes
+/// the features of different proofs directly comparable, and suitable as input for models that
+/// predict, for example, the time it takes to check a proof. The features are:
+///
+/// - The number of commands, `assume` commands, steps and subproofs
+/// - The maximum and mean subproof depth of steps, and the mean number of premises per step
+/// - The mean and maximum clause length
+/// - The mean and maximum size of literals, and the number of distinct subterms in all clauses,
+///   where the size of a term is its number of distinct subterms
+/// - The fraction of literals that are arithmetic atoms, and the fraction of negative literals
+/// - The number of steps using each rule in a fixed list of rules, plus the number of steps using
+///   any other rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofFeatures {
+    /// The name and value of each feature.
+    pub features: IndexMap<String, f64>,
+}
+
+impl ProofFeatures {
+    /// Extracts the features of a proof, including the steps inside subproofs.
+    pub fn new(pool: &dyn TermPool, proof: &Proof) -> Self {
+        let mut num_assumes = 0;
+        let mut num_subproofs = 0;
+        let mut depths = Vec::new();
+        let mut premises = Vec::new();
+        let mut literal_sizes = Vec::new();
+        let mut all_subterms = HashSet::new();
+        let mut rules: IndexMap<&str, usize> = FEATURE_RULES.iter().map(|&r| (r, 0)).collect();
+        let mut other_rules = 0;
+
+        let mut iter = proof.iter();
+        while let Some(command) = iter.next() {
+            match command {
+                ProofCommand::Assume { .. } => num_assumes += 1,
+                ProofCommand::Subproof(_) => num_subproofs += 1,
+                ProofCommand::Step(s) => {
+                    depths.push(iter.depth());
+                    premises.push(s.premises.len());
+                    match rules.get_mut(s.rule.as_str()) {
+                        Some(count) => *count += 1,
+                        None => other_rules += 1,
+                    }
+                    for literal in &s.clause {
+                        let mut subterms = HashSet::new();
+                        collect_subterms(literal, &mut subterms);
+                        literal_sizes.push(subterms.len());
+                        all_subterms.extend(subterms);
+                    }
+                }
+            }
+        }
+        let literals = LiteralStatistics::from_proof(pool, proof);
+        let clause_lengths: Vec<_> = literals
+            .clause_lengths
+            .iter()
+            .flat_map(|(&length, &count)| std::iter::repeat(length).take(count))
+            .collect();
+        let ratio = |n: usize| {
+            let total = literals.num_literals();
+            if total == 0 {
+                0.0
+            } else {
+                n as f64 / total as f64
+            }
+        };
+
+        let mut features = IndexMap::new();
+        let mut add = |name: &str, value: f64| {
+            features.insert(name.to_owned(), value);
+        };
+        add(
+            "commands",
+            (num_assumes + num_subproofs + depths.len()) as f64,
+        );
+        add("assumes", num_assumes as f64);
+        add("steps", depths.len() as f64);
+        add("subproofs", num_subproofs as f64);
+        add("depth.max", max(&depths));
+        add("depth.mean", mean(&depths));
+        add("premises.mean", mean(&premises));
+        add("clause_length.mean", mean(&clause_lengths));
+        add("clause_length.max", max(&clause_lengths));
+        add("term_size.mean", mean(&literal_sizes));
+        add("term_size.max", max(&literal_sizes));
+        add("term_size.distinct", all_subterms.len() as f64);
+        add("arithmetic_density", ratio(literals.num_arithmetic));
+        add("negative_literals", ratio(literals.num_negative));
+        for (rule, count) in rules {
+            add(&format!("rule.{}", rule), count as f64);
+        }
+        add("rule.other", other_rules as f64);
+        Self { features }
+    }
+
+    /// Writes the features as a JSON object, mapping each feature name to its value. If an
+    /// environment is given, it is included in the object under the `environment` key.
+    pub fn write_json(
+        &self,
+        dest: &mut dyn io::Write,
+        environment: Option<&Environment>,
+    ) -> io::Result<()> {
+        write!(dest, "{{")?;
+        for (i, (name, value)) in self.features.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            write!(dest, "{}\n  \"{}\": {}", separator, name, value)?;
+        }
+        if let Some(environment) = environment {
+            write!(dest, ",\n  \"environment\": ")?;
+            environment.write_json(dest)?;
+        }
+        writeln!(dest, "\n}}")
+    }
+
+    /// Writes the features as CSV. If `header` is true, a line with the feature names is written
+    /// before the line with their values.
+    pub fn write_csv(&self, dest: &mut dyn io::Write, header: bool) -> io::Result<()> {
+        if header {
+            let names: Vec<_> = self.features.keys().map(String::as_str).collect();
+            writeln!(dest, "{}", names.join(","))?;
+        }
+        let values: Vec<_> = self.features.values().map(f64::to_string).collect();
+        writeln!(dest, "{}", values.join(","))
+    }
+}
+
+fn mean(values: &[usize]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<usize>() as f64 / values.len() as f64
+    }
+}
+
+fn max(values: &[usize]) -> f64 {
+    values.iter().copied().max().unwrap_or(0) as f64
+}
+
+/// Collects all distinct subterms of a term, not including sorts.
+pub(super) fn collect_subterms<'a>(term: &'a Rc<Term>, seen: &mut HashSet<&'a Rc<Term>>) {
+    // To avoid stack overflows in very deep terms, we traverse the term iteratively
+    let mut stack = vec![term];
+    while let Some(term) = stack.pop() {
+        if !seen.insert(term) {
+            continue;
+        }
+        match term.as_ref() {
+            Term::Const(_) | Term::Var(..) | Term::Sort(_) => (),
+            Term::App(f, args) => {
+                stack.push(f);
+                stack.extend(args);
+            }
+            Term::Op(_, args) => stack.extend(args),
+            Term::Binder(_, _, inner) => stack.push(inner),
+            Term::Let(bindings, inner) => {
+                stack.extend(bindings.iter().map(|(_, value)| value));
+                stack.push(inner);
+            }
+            Term::ParamOp { op_args, args, .. } => {
+                stack.extend(op_args);
+                stack.extend(args);
+            }
+        }
+    }
+}
@@ -0,0 +1,466 @@
+use super::{CollectResults, Environment, RunId, RunMeasurement};
+use crate::{
+    ast::{AnchorArg, Proof, ProofCommand, ProofStep, Rc, Term},
+    Error,
+};
+use indexmap::IndexMap;
+use std::{fmt, io, time::Duration};
+
+/// The time spent checking each step of a proof, by step id.
+///
+/// This implements `CollectResults`, so it can be passed to the checker to record the time of
+/// each step. Steps that were not checked, like steps that use unknown rules that are ignored by
+/// the checker, have no recorded time.
+#[derive(Debug, Default, Clone)]
+pub struct StepTimes {
+    times: IndexMap<String, Duration>,
+}
+
+impl StepTimes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the time spent checking the step with the given id, if it was recorded.
+    pub fn get(&self, id: &str) -> Option<Duration> {
+        self.times.get(id).copied()
+    }
+
+    /// Returns the longest time spent checking a single step.
+    pub fn max(&self) -> Duration {
+        self.times.values().copied().max().unwrap_or_default()
+    }
+}
+
+impl CollectResults for StepTimes {
+    fn add_step_measurement(&mut self, _: &str, step_id: &str, rule: &str, time: Duration) {
+        // The checker also measures the time to open each subproof, using the id of its last step.
+        // We don't include that, so the time of the last step isn't inflated
+        if !rule.starts_with("anchor") {
+            *self.times.entry(step_id.to_owned()).or_default() += time;
+        }
+    }
+
+    fn add_assume_measurement(&mut self, file: &str, id: &str, _: bool, time: Duration) {
+        self.add_step_measurement(file, id, "assume", time);
+    }
+
+    fn add_polyeq_depth(&mut self, _: usize) {}
+
+    fn add_run_measurement(&mut self, _: &RunId, _: RunMeasurement) {}
+
+    fn register_holey(&mut self) {}
+
+    fn register_error(&mut self, _: &Error) {}
+
+    fn combine(mut a: Self, b: Self) -> Self {
+        for (id, time) in b.times {
+            *a.times.entry(id).or_default() += time;
+        }
+        a
+    }
+}
+
+/// The outcome of checking a command, as shown in the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Passed,
+    Failed,
+    NotChecked,
+}
+
+impl Status {
+    fn class(self) -> &'static str {
+        match self {
+            Status::Passed => "passed",
+            Status::Failed => "failed",
+            Status::NotChecked => "not-checked",
+        }
+    }
+}
+
+/// A standalone HTML page that renders a proof as a collapsible tree.
+///
+/// Each step can be expanded to show its premises, which link to the steps that conclude them, as
+/// well as its arguments. Subproofs are rendered as nested collapsible sections. Rules are colored
+/// consistently, so steps using the same rule are easy to spot. If step times are given, each step
+/// shows a bar proportional to the time spent checking it. If the proof failed to check, the
+/// failing step is highlighted with the error, and the steps after it are marked as not checked.
+///
+/// The page has no external dependencies, so it can be shared as a single file.
+pub struct HtmlReport<'a> {
+    proof: &'a Proof,
+    title: String,
+    times: Option<&'a StepTimes>,
+    error: Option<&'a Error>,
+    environment: Option<&'a Environment>,
+}
+
+impl<'a> HtmlReport<'a> {
+    pub fn new(proof: &'a Proof) -> Self {
+        Self {
+            proof,
+            title: "Carcara proof report".to_owned(),
+            times: None,
+            error: None,
+            environment: None,
+        }
+    }
+
+    pub fn title(mut self, value: impl Into<String>) -> Self {
+        self.title = value.into();
+        self
+    }
+
+    pub fn step_times(mut self, value: &'a StepTimes) -> Self {
+        self.times = Some(value);
+        self
+    }
+
+    /// Sets the error encountered while checking the proof, if any.
+    pub fn error(mut self, value: Option<&'a Error>) -> Self {
+        self.error = value;
+        self
+    }
+
+    pub fn environment(mut self, value: &'a Environment) -> Self {
+        self.environment = Some(value);
+        self
+    }
+
+    /// Returns the id of the command that failed, if the error happened while checking one.
+    fn failed_command(&self) -> Option<&str> {
+        match self.error {
+            Some(Error::Checker { step, .. }) => Some(step),
+            _ => None,
+        }
+    }
+
+    pub fn write(&self, dest: &mut dyn io::Write) -> io::Result<()> {
+        writeln!(dest, "<!DOCTYPE html>")?;
+        writeln!(dest, "<html lang=\"en\">")?;
+        writeln!(dest, "<head>")?;
+        writeln!(dest, "<meta charset=\"utf-8\">")?;
+        writeln!(dest, "<title>{}</title>", Escaped(&self.title))?;
+        writeln!(dest, "<style>{}</style>", STYLE)?;
+        writeln!(dest, "</head>")?;
+        writeln!(dest, "<body>")?;
+        writeln!(dest, "<h1>{}</h1>", Escaped(&self.title))?;
+        self.write_summary(dest)?;
+        writeln!(
+            dest,
+            "<p><button onclick=\"setAll(true)\">Expand all</button> \
+            <button onclick=\"setAll(false)\">Collapse all</button></p>"
+        )?;
+        writeln!(dest, "<div class=\"proof\">")?;
+        self.write_commands(dest)?;
+        writeln!(dest, "</div>")?;
+        if let Some(environment) = self.environment {
+            writeln!(dest, "<h2>Environment</h2>")?;
+            writeln!(dest, "<pre>{}</pre>", Escaped(&environment.to_string()))?;
+        }
+        writeln!(dest, "<script>{}</script>", SCRIPT)?;
+        writeln!(dest, "</body>")?;
+        writeln!(dest, "</html>")
+    }
+
+    fn write_summary(&self, dest: &mut dyn io::Write) -> io::Result<()> {
+        let mut num_steps = 0;
+        let mut rules: IndexMap<&str, usize> = IndexMap::new();
+        for command in self.proof.iter() {
+            if let ProofCommand::Step(step) = command {
+                num_steps += 1;
+                *rules.entry(step.rule.as_str()).or_default() += 1;
+            }
+        }
+        rules.sort_by(|_, a, _, b| b.cmp(a));
+
+        match self.error {
+            Some(e) => writeln!(
+                dest,
+                "<p class=\"banner failed\">invalid: {}: {}</p>",
+                e.code(),
+                Escaped(&e.to_string())
+            )?,
+            None => writeln!(dest, "<p class=\"banner passed\">valid</p>")?,
+        }
+        write!(
+            dest,
+            "<p>{} commands, {} steps",
+            self.proof.iter().count(),
+            num_steps
+        )?;
+        if let Some(times) = self.times {
+            let total: Duration = times.times.values().sum();
+            write!(dest, ", {:?} checking steps", total)?;
+        }
+        writeln!(dest, "</p>")?;
+        write!(dest, "<p class=\"legend\">")?;
+        for (rule, count) in rules {
+            write!(dest, "{} ", RuleBadge(rule))?;
+            write!(dest, "<span class=\"count\">&times;{}</span> ", count)?;
+        }
+        writeln!(dest, "</p>")
+    }
+
+    fn write_commands(&self, dest: &mut dyn io::Write) -> io::Result<()> {
+        let failed = self.failed_command();
+        let max_time = self.times.map(StepTimes::max).unwrap_or_default();
+        let mut status = Status::Passed;
+
+        let mut iter = self.proof.iter();
+        while let Some(command) = iter.next() {
+            if failed == Some(command.id()) && !matches!(command, ProofCommand::Subproof(_)) {
+                status = Status::Failed;
+            }
+            match command {
+                ProofCommand::Assume { id, term } => {
+                    write!(
+                        dest,
+                        "<div class=\"command {}\" id=\"{}\">",
+                        status.class(),
+                        Anchor(id)
+                    )?;
+                    write!(dest, "<span class=\"id\">{}</span> ", Escaped(id))?;
+                    self.write_time(dest, id, max_time)?;
+                    write!(dest, "{} ", RuleBadge("assume"))?;
+                    write!(dest, "<code>{}</code>", Escaped(&term.to_string()))?;
+                    if status == Status::Failed {
+                        self.write_error(dest)?;
+                    }
+                    writeln!(dest, "</div>")?;
+                }
+                ProofCommand::Step(step) => {
+                    // The failing step is expanded, so its error is shown without clicking on it
+                    let open = if status == Status::Failed {
+                        " open"
+                    } else {
+                        ""
+                    };
+                    write!(
+                        dest,
+                        "<details class=\"command {}\" id=\"{}\"{}><summary>",
+                        status.class(),
+                        Anchor(&step.id),
+                        open
+                    )?;
+                    write!(dest, "<span class=\"id\">{}</span> ", Escaped(&step.id))?;
+                    self.write_time(dest, &step.id, max_time)?;
+                    write!(dest, "{} ", RuleBadge(&step.rule))?;
+                    write!(
+                        dest,
+                        "<code>{}</code>",
+                        Escaped(&clause_to_string(&step.clause))
+                    )?;
+                    write!(dest, "</summary><div class=\"body\">")?;
+                    let premise_ids: Vec<_> = step
+                        .premises
+                        .iter()
+                        .map(|&p| iter.get_premise(p).id())
+                        .collect();
+                    let discharge_ids: Vec<_> = step
+                        .discharge
+                        .iter()
+                        .map(|&p| iter.get_premise(p).id())
+                        .collect();
+                    write_step_body(dest, step, &premise_ids, &discharge_ids)?;
+                    if status == Status::Failed {
+                        self.write_error(dest)?;
+                    }
+                    writeln!(dest, "</div></details>")?;
+                    if iter.is_end_step() {
+                        writeln!(dest, "</div></details>")?;
+                    }
+                }
+                ProofCommand::Subproof(subproof) => {
+                    let args: Vec<_> = subproof.args.iter().map(anchor_arg_to_string).collect();
+                    write!(dest, "<details class=\"subproof\" open><summary>")?;
+                    write!(dest, "<span class=\"keyword\">anchor</span> ")?;
+                    write!(dest, "<span class=\"id\">{}</span>", Escaped(command.id()))?;
+                    if !args.is_empty() {
+                        write!(dest, " <code>{}</code>", Escaped(&args.join(" ")))?;
+                    }
+                    writeln!(dest, "</summary><div class=\"commands\">")?;
+                }
+            }
+            if status == Status::Failed && !matches!(command, ProofCommand::Subproof(_)) {
+                status = Status::NotChecked;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_time(&self, dest: &mut dyn io::Write, id: &str, max: Duration) -> io::Result<()> {
+        let Some(times) = self.times else {
+            return Ok(());
+        };
+        let (width, label) = match times.get(id) {
+            Some(time) if !max.is_zero() => (
+                100.0 * time.as_secs_f64() / max.as_secs_f64(),
+                format!("{:?}", time),
+            ),
+            Some(time) => (0.0, format!("{:?}", time)),
+            None => (0.0, String::new()),
+        };
+        write!(
+            dest,
+            "<span class=\"bar\" title=\"{}\"><span style=\"width: {:.1}%\"></span></span> ",
+            label, width
+        )
+    }
+
+    fn write_error(&self, dest: &mut dyn io::Write) -> io::Result<()> {
+        if let Some(Error::Checker { inner, .. }) = self.error {
+            write!(
+                dest,
+                "<p class=\"error\">{}: {}</p>",
+                inner.code(),
+                Escaped(&inner.to_string())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn write_step_body(
+    dest: &mut dyn io::Write,
+    step: &ProofStep,
+    premises: &[&str],
+    discharge: &[&str],
+) -> io::Result<()> {
+    write!(
+        dest,
+        "<p><code>{}</code></p>",
+        Escaped(&clause_to_string(&step.clause))
+    )?;
+    for (name, ids) in [("premises", premises), ("discharge", discharge)] {
+        if ids.is_empty() {
+            continue;
+        }
+        write!(dest, "<p><span class=\"keyword\">:{}</span>", name)?;
+        for id in ids {
+            write!(dest, " <a href=\"#{}\">{}</a>", Anchor(id), Escaped(id))?;
+        }
+        write!(dest, "</p>")?;
+    }
+    if !step.args.is_empty() {
+        let args: Vec<_> = step
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| match step.assigned_var(i) {
+                Some((name, sort)) => format!("(:= ({} {}) {})", name, sort, arg),
+                None => arg.to_string(),
+            })
+            .collect();
+        write!(
+            dest,
+            "<p><span class=\"keyword\">:args</span> <code>({})</code></p>",
+            Escaped(&args.join(" "))
+        )?;
+    }
+    Ok(())
+}
+
+fn clause_to_string(clause: &[Rc<Term>]) -> String {
+    let mut result = "(cl".to_owned();
+    for term in clause {
+        result.push(' ');
+        result.push_str(&term.to_string());
+    }
+    result.push(')');
+    result
+}
+
+fn anchor_arg_to_string(arg: &AnchorArg) -> String {
+    match arg {
+        AnchorArg::Variable((name, sort)) => format!("({} {})", name, sort),
+        AnchorArg::Assign((name, sort), value) => format!("(:= ({} {}) {})", name, sort, value),
+    }
+}
+
+/// Escapes a string so it can be included in HTML text or attribute values.
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => write!(f, "&amp;")?,
+                '<' => write!(f, "&lt;")?,
+                '>' => write!(f, "&gt;")?,
+                '"' => write!(f, "&quot;")?,
+                '\'' => write!(f, "&#39;")?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The HTML id of the element for a command. Command ids are prefixed to avoid clashes with other
+/// elements in the page.
+struct Anchor<'a>(&'a str);
+
+impl fmt::Display for Anchor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "step-{}", Escaped(self.0))
+    }
+}
+
+/// A label for a rule, with a background color derived from the rule name.
+struct RuleBadge<'a>(&'a str);
+
+impl fmt::Display for RuleBadge<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // We use the FNV-1a hash of the rule name, so the colors are the same across reports
+        let hash = self.0.bytes().fold(0x811c_9dc5_u32, |hash, b| {
+            (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+        });
+        write!(
+            f,
+            "<span class=\"rule\" style=\"background: hsl({}, 70%, 85%)\">{}</span>",
+            hash % 360,
+            Escaped(self.0)
+        )
+    }
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+code { font-family: monospace; }
+.banner { padding: 0.5em; border-radius: 4px; font-weight: bold; }
+.banner.passed { background: #d4f4d4; }
+.banner.failed { background: #f8d0d0; }
+.legend .count { color: #666; font-size: 0.8em; margin-right: 0.5em; }
+.command { padding: 2px 4px; border-left: 4px solid transparent; }
+.command > summary, div.command { white-space: nowrap; overflow: hidden; text-overflow: ellipsis; }
+.command.failed { background: #fde0e0; border-left-color: #d00; }
+.command.not-checked { opacity: 0.5; }
+.command:target { outline: 2px solid #48f; }
+.command .body { margin-left: 2em; }
+.command .body code { white-space: pre-wrap; }
+.subproof { margin: 2px 0; }
+.subproof > .commands { margin-left: 1.5em; border-left: 1px dashed #aaa; padding-left: 0.5em; }
+.id { font-weight: bold; }
+.keyword { color: #808; }
+.rule { padding: 0 4px; border-radius: 3px; font-size: 0.9em; }
+.bar { display: inline-block; width: 60px; height: 0.6em; background: #eee; }
+.bar > span { display: block; height: 100%; background: #e80; }
+.error { color: #b00; font-weight: bold; }
+";
+
+const SCRIPT: &str = "
+function setAll(open) {
+  document.querySelectorAll('details').forEach(d => d.open = open);
+}
+function reveal() {
+  const id = decodeURIComponent(location.hash.slice(1));
+  let el = id && document.getElementById(id);
+  for (; el; el = el.parentElement) {
+    if (el.tagName === 'DETAILS') el.open = true;
+  }
+}
+window.addEventListener('hashchange', reveal);
+reveal();
+";
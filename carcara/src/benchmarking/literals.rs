@@ -0,0 +1,143 @@
+use crate::ast::{Operator, Proof, ProofCommand, Rc, Sort, Term, TermPool};
+use indexmap::{IndexMap, IndexSet};
+use std::fmt;
+
+/// Statistics about the literals in the conclusions of proof steps.
+///
+/// These describe the shape of the clauses in a proof, and are useful when comparing proofs
+/// produced by different solvers.
+#[derive(Debug, Default, Clone)]
+pub struct LiteralStatistics {
+    /// For each clause length, the number of steps whose conclusion has that length.
+    pub clause_lengths: IndexMap<usize, usize>,
+
+    /// The distinct atoms that appear in some literal, that is, the literals with all leading
+    /// negations removed.
+    pub atoms: IndexSet<Rc<Term>>,
+
+    /// The number of literals with an even number of leading negations.
+    pub num_positive: usize,
+
+    /// The number of literals with an odd number of leading negations.
+    pub num_negative: usize,
+
+    /// The number of literals whose atom is an arithmetic comparison, or an equality or
+    /// disequality between terms of sort `Int` or `Real`.
+    pub num_arithmetic: usize,
+}
+
+impl LiteralStatistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collects the statistics of a single proof. See [`LiteralStatistics::add_proof`].
+    pub fn from_proof(pool: &dyn TermPool, proof: &Proof) -> Self {
+        let mut result = Self::new();
+        result.add_proof(pool, proof);
+        result
+    }
+
+    /// Adds the conclusions of all steps in `proof`, including the ones inside subproofs.
+    pub fn add_proof(&mut self, pool: &dyn TermPool, proof: &Proof) {
+        for command in proof.iter() {
+            if let ProofCommand::Step(s) = command {
+                *self.clause_lengths.entry(s.clause.len()).or_default() += 1;
+                for literal in &s.clause {
+                    self.add_literal(pool, literal);
+                }
+            }
+        }
+    }
+
+    fn add_literal(&mut self, pool: &dyn TermPool, literal: &Rc<Term>) {
+        let mut atom = literal;
+        let mut polarity = true;
+        while let Some((Operator::Not, [inner])) = atom.as_op() {
+            atom = inner;
+            polarity = !polarity;
+        }
+        if polarity {
+            self.num_positive += 1;
+        } else {
+            self.num_negative += 1;
+        }
+        if is_arithmetic_atom(pool, atom) {
+            self.num_arithmetic += 1;
+        }
+        self.atoms.insert(atom.clone());
+    }
+
+    /// The total number of literals, counting repetitions.
+    pub fn num_literals(&self) -> usize {
+        self.num_positive + self.num_negative
+    }
+
+    /// The total number of steps whose conclusions were added.
+    pub fn num_clauses(&self) -> usize {
+        self.clause_lengths.values().sum()
+    }
+}
+
+fn is_arithmetic_atom(pool: &dyn TermPool, atom: &Rc<Term>) -> bool {
+    match atom.as_op() {
+        Some((
+            Operator::LessThan | Operator::GreaterThan | Operator::LessEq | Operator::GreaterEq,
+            _,
+        )) => true,
+        Some((Operator::Equals | Operator::Distinct, [first, ..])) => {
+            matches!(pool.sort(first).as_sort(), Some(Sort::Int | Sort::Real))
+        }
+        _ => false,
+    }
+}
+
+impl fmt::Display for LiteralStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let num_literals = self.num_literals();
+        let percent = |n: usize| {
+            if num_literals == 0 {
+                0.0
+            } else {
+                (n as f64) * 100.0 / (num_literals as f64)
+            }
+        };
+
+        writeln!(f, "literals:            {}", num_literals)?;
+        writeln!(
+            f,
+            "    positive:        {} ({:.02}%)",
+            self.num_positive,
+            percent(self.num_positive)
+        )?;
+        writeln!(
+            f,
+            "    negative:        {} ({:.02}%)",
+            self.num_negative,
+            percent(self.num_negative)
+        )?;
+        writeln!(
+            f,
+            "    arithmetic:      {} ({:.02}%)",
+            self.num_arithmetic,
+            percent(self.num_arithmetic)
+        )?;
+        writeln!(f, "distinct atoms:      {}", self.atoms.len())?;
+
+        let num_clauses = self.num_clauses();
+        if num_clauses > 0 {
+            writeln!(
+                f,
+                "mean clause length:  {:.4}",
+                (num_literals as f64) / (num_clauses as f64)
+            )?;
+        }
+        let mut lengths: Vec<_> = self.clause_lengths.iter().collect();
+        lengths.sort_unstable();
+        writeln!(f, "clause lengths:")?;
+        for (length, count) in lengths {
+            writeln!(f, "    {: <16}{}", length, count)?;
+        }
+        Ok(())
+    }
+}
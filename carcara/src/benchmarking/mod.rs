@@ -1,8 +1,20 @@
+mod deltas;
+mod environment;
+mod features;
+mod html;
+mod literals;
 mod metrics;
+mod openmetrics;
 #[cfg(test)]
 mod tests;
 
+pub use deltas::{MetricDeltas, ProofMetrics};
+pub use environment::{Environment, InputFile};
+pub use features::ProofFeatures;
+pub use html::{HtmlReport, StepTimes};
+pub use literals::LiteralStatistics;
 pub use metrics::*;
+pub use openmetrics::OpenMetricsResults;
 
 use indexmap::{map::Entry, IndexMap, IndexSet};
 use std::{fmt, hash::Hash, io, sync::Arc, time::Duration};
@@ -44,7 +56,7 @@ impl fmt::Display for StepId {
 
 type RunId = (String, usize);
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct RunMeasurement {
     pub parsing: Duration,
     pub checking: Duration,
@@ -522,3 +534,25 @@ impl CollectResults for CsvBenchmarkResults {
         a
     }
 }
+
+/// Formats a string as a JSON string literal, escaping it as needed. This is used when writing
+/// reports as JSON.
+pub struct JsonString<'a>(pub &'a str);
+
+impl fmt::Display for JsonString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                '\t' => write!(f, "\\t")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
@@ -0,0 +1,216 @@
+use super::{CollectResults, RunId, RunMeasurement};
+use crate::Error;
+use indexmap::IndexMap;
+use std::{fmt, io, time::Duration};
+
+/// The number of observations and their total, as reported by an OpenMetrics summary.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Summary {
+    count: u64,
+    sum: Duration,
+}
+
+impl Summary {
+    fn add(&mut self, time: Duration) {
+        self.count += 1;
+        self.sum += time;
+    }
+
+    fn combine(self, other: Self) -> Self {
+        Self {
+            count: self.count + other.count,
+            sum: self.sum + other.sum,
+        }
+    }
+}
+
+/// Counters and timing summaries meant to be exported to a monitoring system, in the OpenMetrics
+/// text format (which is also understood by Prometheus).
+///
+/// This implements `CollectResults`, so it can be passed to the checker to record the time spent
+/// in each rule. Since not every proof checked reaches the point where its run is measured (for
+/// example, proofs that fail to parse), the number of proofs must be counted separately, using
+/// [`OpenMetricsResults::register_proof`].
+#[derive(Debug, Default, Clone)]
+pub struct OpenMetricsResults {
+    proofs: u64,
+    holey: u64,
+    failures: IndexMap<String, u64>,
+    rules: IndexMap<String, Summary>,
+    phases: IndexMap<&'static str, Summary>,
+}
+
+impl OpenMetricsResults {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a proof as checked, regardless of the result.
+    pub fn register_proof(&mut self) {
+        self.proofs += 1;
+    }
+
+    /// Writes all metrics in the OpenMetrics text format. If the resident memory of the current
+    /// process can be determined, it is also included.
+    pub fn write(&self, dest: &mut dyn io::Write) -> io::Result<()> {
+        write!(dest, "{}", self)
+    }
+}
+
+impl fmt::Display for OpenMetricsResults {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let header = |f: &mut fmt::Formatter, name, kind, help| {
+            writeln!(f, "# TYPE {} {}", name, kind)?;
+            writeln!(f, "# HELP {} {}", name, help)
+        };
+
+        header(f, "carcara_proofs", "counter", "Number of proofs checked.")?;
+        writeln!(f, "carcara_proofs_total {}", self.proofs)?;
+
+        header(
+            f,
+            "carcara_holey_proofs",
+            "counter",
+            "Number of valid proofs with holes.",
+        )?;
+        writeln!(f, "carcara_holey_proofs_total {}", self.holey)?;
+
+        header(
+            f,
+            "carcara_failures",
+            "counter",
+            "Number of failed proofs, by error code.",
+        )?;
+        for (code, count) in &self.failures {
+            writeln!(
+                f,
+                "carcara_failures_total{{code={}}} {}",
+                Label(code),
+                count
+            )?;
+        }
+
+        header(
+            f,
+            "carcara_rule_seconds",
+            "summary",
+            "Time spent checking steps, by rule.",
+        )?;
+        for (rule, summary) in &self.rules {
+            write_summary(f, "carcara_rule_seconds", "rule", rule, summary)?;
+        }
+
+        header(
+            f,
+            "carcara_phase_seconds",
+            "summary",
+            "Time spent in each phase of a run.",
+        )?;
+        for (phase, summary) in &self.phases {
+            write_summary(f, "carcara_phase_seconds", "phase", phase, summary)?;
+        }
+
+        if let Some(bytes) = resident_memory() {
+            header(
+                f,
+                "carcara_resident_memory_bytes",
+                "gauge",
+                "Resident memory size.",
+            )?;
+            writeln!(f, "carcara_resident_memory_bytes {}", bytes)?;
+        }
+        writeln!(f, "# EOF")
+    }
+}
+
+fn write_summary(
+    f: &mut fmt::Formatter,
+    name: &str,
+    label: &str,
+    value: &str,
+    summary: &Summary,
+) -> fmt::Result {
+    let label = format!("{}={}", label, Label(value));
+    writeln!(f, "{}_count{{{}}} {}", name, label, summary.count)?;
+    writeln!(f, "{}_sum{{{}}} {}", name, label, summary.sum.as_secs_f64())
+}
+
+/// Formats a label value as a quoted string, escaping it as required by the OpenMetrics format.
+struct Label<'a>(&'a str);
+
+impl fmt::Display for Label<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+/// Returns the resident memory size of the current process, in bytes. This is only available on
+/// Linux.
+fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+impl CollectResults for OpenMetricsResults {
+    fn add_step_measurement(&mut self, _: &str, _: &str, rule: &str, time: Duration) {
+        // Opening a subproof is measured as an `anchor(...)` pseudo-rule. We don't include these,
+        // so the number of steps checked by each rule is accurate
+        if !rule.starts_with("anchor") {
+            self.rules.entry(rule.to_owned()).or_default().add(time);
+        }
+    }
+
+    fn add_assume_measurement(&mut self, file: &str, id: &str, _: bool, time: Duration) {
+        self.add_step_measurement(file, id, "assume", time);
+    }
+
+    fn add_polyeq_depth(&mut self, _: usize) {}
+
+    fn add_run_measurement(&mut self, _: &RunId, measurement: RunMeasurement) {
+        let phases = [
+            ("parsing", measurement.parsing),
+            ("checking", measurement.checking),
+            ("elaboration", measurement.elaboration),
+            ("total", measurement.total),
+        ];
+        for (phase, time) in phases {
+            self.phases.entry(phase).or_default().add(time);
+        }
+    }
+
+    fn register_holey(&mut self) {
+        self.holey += 1;
+    }
+
+    fn register_error(&mut self, error: &Error) {
+        *self.failures.entry(error.code().to_string()).or_default() += 1;
+    }
+
+    fn combine(mut a: Self, b: Self) -> Self {
+        a.proofs += b.proofs;
+        a.holey += b.holey;
+        for (code, count) in b.failures {
+            *a.failures.entry(code).or_default() += count;
+        }
+        for (rule, summary) in b.rules {
+            let entry = a.rules.entry(rule).or_default();
+            *entry = entry.combine(summary);
+        }
+        for (phase, summary) in b.phases {
+            let entry = a.phases.entry(phase).or_default();
+            *entry = entry.combine(summary);
+        }
+        a
+    }
+}
@@ -1,4 +1,8 @@
-use super::{Duration, Metrics, MetricsUnit, OfflineMetrics, OnlineMetrics};
+use super::{
+    Duration, HtmlReport, LiteralStatistics, MetricDeltas, Metrics, MetricsUnit, OfflineMetrics,
+    OnlineMetrics, OpenMetricsResults, ProofFeatures, ProofMetrics, StepTimes,
+};
+use crate::{ast, parser};
 use rand::{prelude::ThreadRng, Rng};
 use std::fmt;
 
@@ -135,3 +139,299 @@ fn test_metrics_combine() {
     // `Metrics::add` with that entry, which makes the numerical error small again
     run_tests(10_000, 1, 1.0e-6);
 }
+
+#[test]
+fn test_literal_statistics() {
+    let problem: &[u8] = b"
+        (declare-const a Bool)
+        (declare-const b Bool)
+        (declare-const x Int)
+        (declare-const y Real)
+    ";
+    let proof: &[u8] = b"
+        (assume h1 (not a))
+        (step t1 (cl a (not b) (not (not (< x 0)))) :rule hole)
+        (anchor :step t2)
+        (step t2.t1 (cl (= x x)) :rule refl)
+        (step t2 (cl (= x x)) :rule bind)
+        (step t3 (cl (not (= a b)) (= y 1.0) (not a)) :rule hole)
+        (step t4 (cl) :rule hole)
+    ";
+    let (_, proof, pool) = parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+
+    let mut stats = LiteralStatistics::new();
+    stats.add_proof(&pool, &proof);
+    assert_eq!(stats.num_clauses(), 5);
+    assert_eq!(stats.num_literals(), 8);
+    assert_eq!(stats.num_positive, 5);
+    assert_eq!(stats.num_negative, 3);
+    assert_eq!(stats.num_arithmetic, 4);
+    assert_eq!(stats.atoms.len(), 6);
+
+    let mut lengths: Vec<_> = stats.clause_lengths.into_iter().collect();
+    lengths.sort_unstable();
+    assert_eq!(lengths, [(0, 1), (1, 2), (3, 2)]);
+}
+
+#[test]
+fn test_proof_features() {
+    let problem: &[u8] = b"
+        (declare-const a Bool)
+        (declare-const b Bool)
+        (declare-const x Int)
+    ";
+    let proof: &[u8] = b"
+        (assume h1 (not a))
+        (step t1 (cl a (not b) (not (not (< x 0)))) :rule hole)
+        (anchor :step t2)
+        (step t2.t1 (cl (= x x)) :rule refl)
+        (step t2 (cl (= x x)) :rule bind)
+        (step t3 (cl a) :rule resolution :premises (h1 t1))
+        (step t4 (cl) :rule some_unknown_rule :premises (t3))
+    ";
+    let (_, proof, pool) = parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+    let features = ProofFeatures::new(&pool, &proof).features;
+
+    assert_eq!(features["commands"], 7.0);
+    assert_eq!(features["assumes"], 1.0);
+    assert_eq!(features["steps"], 5.0);
+    assert_eq!(features["subproofs"], 1.0);
+    assert_eq!(features["depth.max"], 1.0);
+    assert_is_close!(features["premises.mean"], 0.6);
+    assert_eq!(features["clause_length.max"], 3.0);
+    assert_eq!(features["term_size.max"], 5.0);
+    assert_is_close!(features["arithmetic_density"], 0.5);
+    assert_eq!(features["rule.hole"], 1.0);
+    assert_eq!(features["rule.resolution"], 1.0);
+    assert_eq!(features["rule.other"], 1.0);
+
+    // The layout is fixed, and doesn't depend on the rules implemented by the checker: 14 general
+    // features, 125 rule counts and the count of other rules
+    assert_eq!(features.len(), 140);
+
+    // The features of every proof have the same dimension
+    let (_, empty, pool) = parser::parse_instance(problem, b"", parser::Config::new()).unwrap();
+    let empty_features = ProofFeatures::new(&pool, &empty).features;
+    assert!(features.keys().eq(empty_features.keys()));
+    assert_eq!(empty_features["term_size.mean"], 0.0);
+
+    let mut csv = Vec::new();
+    ProofFeatures { features }
+        .write_csv(&mut csv, true)
+        .unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].split(',').count(), lines[1].split(',').count());
+    assert!(lines[0].starts_with("commands,assumes,steps,"));
+    assert!(lines[1].starts_with("7,1,5,"));
+}
+
+#[test]
+fn test_html_report() {
+    use crate::checker::{CheckerStatistics, Config, ProofChecker};
+
+    let problem: &[u8] = b"
+        (declare-const a Bool)
+        (assert (not a))
+    ";
+    let proof: &[u8] = b"
+        (assume h1 (not a))
+        (anchor :step t1 :args ((y Int) (:= (x Int) y)))
+        (step t1.t1 (cl (= x y)) :rule refl)
+        (step t1.t2 (cl (= (> x 0) (> y 0))) :rule cong :premises (t1.t1))
+        (step t1 (cl (= (forall ((x Int)) (> x 0)) (forall ((y Int)) (> y 0)))) :rule bind)
+        (step t2 (cl a) :rule resolution :premises (h1))
+        (step t3 (cl) :rule resolution :premises (t2 h1))
+    ";
+    let (problem, proof, mut pool) =
+        parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+    let mut stats = CheckerStatistics {
+        file_name: "test",
+        polyeq_time: Duration::ZERO,
+        assume_time: Duration::ZERO,
+        assume_core_time: Duration::ZERO,
+        polyeq_cache: Default::default(),
+        results: StepTimes::new(),
+    };
+    let error = ProofChecker::new(&mut pool, Config::new())
+        .check_with_stats(&problem, &proof, &mut stats)
+        .unwrap_err();
+    assert!(stats.results.get("h1").is_some());
+    assert!(stats.results.get("t1.t1").is_some());
+    assert!(stats.results.get("t2").is_none());
+
+    let mut out = Vec::new();
+    HtmlReport::new(&proof)
+        .title("<test>")
+        .step_times(&stats.results)
+        .error(Some(&error))
+        .write(&mut out)
+        .unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("<title>&lt;test&gt;</title>"));
+    assert!(out.contains("<details class=\"command failed\" id=\"step-t2\" open>"));
+    assert!(out.contains("<details class=\"command not-checked\" id=\"step-t3\">"));
+    assert!(out.contains("<details class=\"command passed\" id=\"step-t1.t1\">"));
+    assert!(out.contains("<a href=\"#step-t2\">t2</a>"));
+    assert!(out.contains("<code>(y Int) (:= (x Int) y)</code>"));
+}
+
+#[test]
+fn test_proof_metrics() {
+    let problem: &[u8] = b"
+        (declare-const a Bool)
+        (declare-const x Int)
+    ";
+    let proof: &[u8] = b"
+        (assume h1 (not a))
+        (anchor :step t1)
+        (step t1.t1 (cl (= x x)) :rule refl)
+        (step t1 (cl (= x x)) :rule bind)
+        (step t2 (cl a) :rule hole)
+        (step t3 (cl) :rule resolution :premises (h1 t2) :args (a true))
+    ";
+    let (problem, proof, mut pool) =
+        parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+
+    let before = ProofMetrics::new(&mut pool, &problem.prelude, &proof);
+    assert_eq!(before.steps, 4);
+    // `(not a)`, `a`, `(= x x)`, `x` and `true`
+    assert_eq!(before.terms, 5);
+    assert_eq!(before.max_depth, 1);
+    // The proof is printed with one command per line, exactly as it is written above, without the
+    // indentation
+    assert_eq!(before.size, 202);
+
+    let after = ProofMetrics { steps: 6, ..before };
+    let table = MetricDeltas { before, after }.to_string();
+    assert!(table.contains("+2 (+50.00%)"));
+}
+
+#[test]
+fn test_custom_collector() {
+    use crate::checker::{Config, ProofChecker, StatisticsCollector};
+    use crate::elaborator::{self, ElaborationStep, Elaborator};
+
+    #[derive(Default)]
+    struct EventLog {
+        started: Vec<String>,
+        finished: Vec<String>,
+        assumes: Vec<(String, bool)>,
+        passes: Vec<String>,
+    }
+
+    impl StatisticsCollector for EventLog {
+        fn step_started(&mut self, id: &str, _: &str) {
+            self.started.push(id.to_owned());
+        }
+
+        fn step_finished(&mut self, id: &str, rule: &str, _: Duration, _: Duration) {
+            self.finished.push(format!("{id}:{rule}"));
+        }
+
+        fn assume_checked(
+            &mut self,
+            id: &str,
+            is_easy: bool,
+            _: Duration,
+            _: Duration,
+            _: Duration,
+        ) {
+            self.assumes.push((id.to_owned(), is_easy));
+        }
+
+        fn elaboration_pass(&mut self, pass: &str, _: Duration) {
+            self.passes.push(pass.to_owned());
+        }
+    }
+
+    let problem: &[u8] = b"
+        (declare-const a Bool)
+        (assert (not a))
+    ";
+    let proof: &[u8] = b"
+        (assume h1 (not a))
+        (anchor :step t1 :args ((y Int) (:= (x Int) y)))
+        (step t1.t1 (cl (= x y)) :rule refl)
+        (step t1.t2 (cl (= (> x 0) (> y 0))) :rule cong :premises (t1.t1))
+        (step t1 (cl (= (forall ((x Int)) (> x 0)) (forall ((y Int)) (> y 0)))) :rule bind)
+        (step t2 (cl a) :rule hole)
+        (step t3 (cl) :rule resolution :premises (h1 t2))
+    ";
+    let (problem, proof, mut pool) =
+        parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+
+    let mut log = EventLog::default();
+    ProofChecker::new(&mut pool, Config::new())
+        .check_with_collector(&problem, &proof, &mut log)
+        .unwrap();
+    assert_eq!(log.started, ["t1", "t1.t1", "t1.t2", "t1", "t2", "t3"]);
+    assert_eq!(
+        log.finished,
+        [
+            "t1:anchor(bind)",
+            "t1.t1:refl",
+            "t1.t2:cong",
+            "t1:bind",
+            "t2:hole",
+            "t3:resolution"
+        ]
+    );
+    assert_eq!(log.assumes, [("h1".to_owned(), true)]);
+
+    let node = ast::ProofNode::from_commands(proof.commands);
+    let mut log = EventLog::default();
+    Elaborator::new(&mut pool, &problem, elaborator::Config::new())
+        .elaborate_with_collector(
+            &node,
+            vec![ElaborationStep::Polyeq, ElaborationStep::Uncrowd],
+            &mut log,
+        )
+        .unwrap();
+    assert_eq!(log.passes, ["polyeq", "uncrowd"]);
+}
+
+#[test]
+fn test_open_metrics() {
+    use super::{CollectResults, RunMeasurement};
+
+    let ms = Duration::from_millis;
+    let mut a = OpenMetricsResults::new();
+    a.register_proof();
+    a.add_step_measurement("a", "t1", "refl", ms(2));
+    a.add_step_measurement("a", "t2", "anchor(bind)", ms(1));
+    a.add_step_measurement("a", "t2", "bind", ms(3));
+    a.add_assume_measurement("a", "h1", true, ms(1));
+    let run = RunMeasurement {
+        parsing: ms(10),
+        total: ms(50),
+        ..Default::default()
+    };
+    a.add_run_measurement(&("a".to_owned(), 0), run);
+    a.register_holey();
+
+    let mut b = OpenMetricsResults::new();
+    b.register_proof();
+    b.add_step_measurement("b", "t1", "refl", ms(4));
+    b.register_error(&crate::Error::DoesNotReachEmptyClause);
+
+    let out = OpenMetricsResults::combine(a, b).to_string();
+    let lines: Vec<_> = out.lines().collect();
+    for expected in [
+        "# TYPE carcara_proofs counter",
+        "carcara_proofs_total 2",
+        "carcara_holey_proofs_total 1",
+        "carcara_failures_total{code=\"E0002\"} 1",
+        "carcara_rule_seconds_count{rule=\"refl\"} 2",
+        "carcara_rule_seconds_sum{rule=\"refl\"} 0.006",
+        "carcara_rule_seconds_count{rule=\"bind\"} 1",
+        "carcara_rule_seconds_count{rule=\"assume\"} 1",
+        "carcara_phase_seconds_sum{phase=\"parsing\"} 0.01",
+        "carcara_phase_seconds_count{phase=\"total\"} 1",
+    ] {
+        assert!(lines.contains(&expected), "missing line: {}", expected);
+    }
+    assert!(!out.contains("anchor"));
+    assert_eq!(lines.last(), Some(&"# EOF"));
+}
@@ -0,0 +1,117 @@
+use super::IMPLEMENTED_RULES;
+use crate::ast::{Proof, ProofCommand};
+use indexmap::IndexMap;
+use std::fmt;
+
+/// Records how many steps used each rule, across one or more proofs.
+///
+/// This is useful to see which implemented rules are exercised by a set of proofs, and which are
+/// never used.
+#[derive(Debug, Default, Clone)]
+pub struct RuleCoverage {
+    counts: IndexMap<String, usize>,
+}
+
+impl RuleCoverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the steps of `proof`, including the ones inside subproofs, to the coverage.
+    pub fn add_proof(&mut self, proof: &Proof) {
+        for command in proof.iter() {
+            if let ProofCommand::Step(s) = command {
+                *self.counts.entry(s.rule.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    /// Returns the number of steps that used each rule.
+    pub fn counts(&self) -> &IndexMap<String, usize> {
+        &self.counts
+    }
+
+    /// Returns the implemented rules that were not used by any step.
+    pub fn unused_rules(&self) -> Vec<&'static str> {
+        IMPLEMENTED_RULES
+            .iter()
+            .copied()
+            .filter(|r| !self.counts.contains_key(*r))
+            .collect()
+    }
+
+    /// Returns the rules that were used by some step, but that are not implemented by the checker.
+    pub fn unknown_rules(&self) -> Vec<&str> {
+        self.counts
+            .keys()
+            .map(String::as_str)
+            .filter(|r| !IMPLEMENTED_RULES.contains(r))
+            .collect()
+    }
+
+    pub fn combine(mut a: Self, b: Self) -> Self {
+        for (rule, count) in b.counts {
+            *a.counts.entry(rule).or_default() += count;
+        }
+        a
+    }
+}
+
+impl fmt::Display for RuleCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut counts: Vec<_> = self.counts.iter().collect();
+        counts.sort_by(|(a_rule, a), (b_rule, b)| b.cmp(a).then(a_rule.cmp(b_rule)));
+
+        writeln!(f, "rule coverage:")?;
+        for (rule, count) in counts {
+            writeln!(f, "    {: <32}{}", rule, count)?;
+        }
+        let unknown = self.unknown_rules();
+        if !unknown.is_empty() {
+            writeln!(f, "unknown rules: {}", unknown.join(", "))?;
+        }
+        writeln!(f, "never used: {}", self.unused_rules().join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn test_rule_coverage() {
+        let proof: &[u8] = b"
+            (assume h1 a)
+            (step t1 (cl (or a b)) :rule hole)
+            (step t2 (cl a b) :rule or :premises (t1))
+            (anchor :step t3)
+            (step t3.t1 (cl (= a a)) :rule refl)
+            (step t3 (cl (= a a)) :rule bind)
+            (step t4 (cl a b) :rule or :premises (t1))
+            (step t5 (cl) :rule my_rule)
+        ";
+        let problem: &[u8] = b"(declare-const a Bool) (declare-const b Bool)";
+        let (_, proof, _) = parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+
+        let mut coverage = RuleCoverage::new();
+        coverage.add_proof(&proof);
+        let expected: IndexMap<_, _> = [
+            ("hole", 1),
+            ("or", 2),
+            ("refl", 1),
+            ("bind", 1),
+            ("my_rule", 1),
+        ]
+        .into_iter()
+        .map(|(r, c)| (r.to_owned(), c))
+        .collect();
+        assert_eq!(&expected, coverage.counts());
+        assert_eq!(vec!["my_rule"], coverage.unknown_rules());
+        assert!(!coverage.unused_rules().contains(&"or"));
+        assert!(coverage.unused_rules().contains(&"resolution"));
+
+        let combined = RuleCoverage::combine(coverage.clone(), coverage);
+        assert_eq!(combined.counts()["or"], 4);
+    }
+}
@@ -0,0 +1,137 @@
+use super::{rules::Rule, Config, ProofChecker, IMPLEMENTED_RULES};
+
+/// The maximum displacement tried for each bucket when building a [`RuleTable`]. If no
+/// displacement up to this value fits a bucket, building the table fails.
+const MAX_DISPLACEMENT: u32 = 1 << 16;
+
+/// Hashes a rule name, eight bytes at a time.
+fn hash_name(name: &str) -> u64 {
+    let bytes = name.as_bytes();
+    let mut h = (bytes.len() as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    for chunk in bytes.chunks(8) {
+        let mut buf = [0u8; 8];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        h = (h ^ u64::from_le_bytes(buf)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        h ^= h >> 29;
+    }
+    h = h.wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
+
+/// Combines a name hash with a displacement value, giving a new hash.
+fn displace(hash: u64, displacement: u32) -> u64 {
+    let h = (hash ^ u64::from(displacement).wrapping_mul(0x9e37_79b9_7f4a_7c15))
+        .wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h ^ (h >> 29)
+}
+
+/// A table that maps rule names to their implementations, for a given checker configuration.
+///
+/// The table uses a perfect hash function, built with the "hash and displace" method: names are
+/// first split into buckets according to their hash, and then each bucket is assigned a
+/// displacement value, chosen such that the names in all buckets are mapped to distinct slots.
+/// Looking up a rule thus requires computing two cheap hashes and a single string comparison,
+/// instead of comparing the name against every rule name in sequence, as the `match` in
+/// `ProofChecker::get_rule` does. Since a lookup is done for every step, this matters for proofs
+/// with a very large number of small steps.
+#[derive(Clone)]
+pub struct RuleTable {
+    displacements: Vec<u32>,
+    slots: Vec<Option<(&'static str, Rule)>>,
+}
+
+impl RuleTable {
+    pub fn new(config: &Config) -> Self {
+        let rules: Vec<_> = IMPLEMENTED_RULES
+            .iter()
+            .filter_map(|&name| Some((name, ProofChecker::get_rule(name, config)?)))
+            .collect();
+
+        let num_buckets = (rules.len() / 4 + 1).next_power_of_two();
+        let num_slots = (rules.len() * 2).next_power_of_two();
+        let mut buckets = vec![Vec::new(); num_buckets];
+        for &(name, rule) in &rules {
+            let hash = hash_name(name);
+            buckets[hash as usize & (num_buckets - 1)].push((hash, name, rule));
+        }
+
+        // We assign displacements to the largest buckets first, since they are the hardest to fit
+        let mut order: Vec<_> = (0..num_buckets).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(buckets[i].len()));
+
+        let mut displacements = vec![0; num_buckets];
+        let mut slots = vec![None; num_slots];
+        for i in order {
+            let bucket = &buckets[i];
+            let displacement = (0..MAX_DISPLACEMENT)
+                .find(|&d| {
+                    let mut chosen = Vec::with_capacity(bucket.len());
+                    bucket.iter().all(|&(hash, _, _)| {
+                        let slot = displace(hash, d) as usize & (num_slots - 1);
+                        let free = slots[slot].is_none() && !chosen.contains(&slot);
+                        chosen.push(slot);
+                        free
+                    })
+                })
+                .unwrap_or_else(|| {
+                    // This can only happen if two rule names have the same hash, so this is a bug
+                    // in the list of implemented rules or in the hash function
+                    let names: Vec<_> = bucket.iter().map(|&(_, name, _)| name).collect();
+                    panic!(
+                        "could not build the rule table: no displacement fits the rules {:?}",
+                        names
+                    )
+                });
+            displacements[i] = displacement;
+            for &(hash, name, rule) in bucket {
+                slots[displace(hash, displacement) as usize & (num_slots - 1)] = Some((name, rule));
+            }
+        }
+        Self { displacements, slots }
+    }
+
+    pub fn get(&self, rule_name: &str) -> Option<Rule> {
+        let hash = hash_name(rule_name);
+        let displacement = self.displacements[hash as usize & (self.displacements.len() - 1)];
+        let slot = displace(hash, displacement) as usize & (self.slots.len() - 1);
+        match self.slots[slot] {
+            Some((name, rule)) if name == rule_name => Some(rule),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_table() {
+        let configs = [
+            Config::new(),
+            Config::new().elaborated(true),
+            Config::new().strict_resolution(true),
+        ];
+        for config in configs {
+            let table = RuleTable::new(&config);
+            for &name in IMPLEMENTED_RULES {
+                let expected = ProofChecker::get_rule(name, &config).map(|r| r as usize);
+                assert_eq!(table.get(name).map(|r| r as usize), expected);
+            }
+            assert!(table.get("not_a_rule").is_none());
+        }
+    }
+
+    #[test]
+    fn test_hash_name() {
+        // Names that only differ in their middle bytes must not collide
+        let a = "a_very_long_rule_name_with_one_middle_and_the_same_ends";
+        let b = "a_very_long_rule_name_with_two_middle_and_the_same_ends";
+        assert_ne!(hash_name(a), hash_name(b));
+
+        let mut hashes: Vec<_> = IMPLEMENTED_RULES.iter().map(|n| hash_name(n)).collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(hashes.len(), IMPLEMENTED_RULES.len());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     ast::*,
-    checker::rules::linear_arithmetic::LinearComb,
+    error_code::ErrorCode,
+    linear::LinearComb,
     utils::{Range, TypeName},
 };
 use rug::{Integer, Rational};
@@ -18,6 +19,9 @@ pub enum CheckerError {
     #[error("could not match term to any of the original problem premises: {0}")]
     Assume(Rc<Term>),
 
+    #[error("premise at depth {0}, index {1} is not in scope")]
+    PremiseOutOfScope(usize, usize),
+
     // Rule specific errors
     #[error(transparent)]
     Resolution(#[from] crate::resolution::ResolutionError),
@@ -34,6 +38,15 @@ pub enum CheckerError {
     #[error(transparent)]
     Subproof(#[from] SubproofError),
 
+    #[error(transparent)]
+    Datatype(#[from] DatatypeError),
+
+    #[error(transparent)]
+    Rare(#[from] RareError),
+
+    #[error(transparent)]
+    Drat(#[from] DratError),
+
     #[error("reflexivity failed with terms '{0}' and '{1}'")]
     ReflexivityFailed(Rc<Term>, Rc<Term>),
 
@@ -47,6 +60,9 @@ pub enum CheckerError {
     #[error("encountered cycle when simplifying term: '{0}'")]
     CycleInSimplification(Rc<Term>),
 
+    #[error("could not find a sequence of rewrites from '{0}' to '{1}'")]
+    RewriteSequenceNotFound(Rc<Term>, Rc<Term>),
+
     #[error("'{0}' is not a valid simplification result for this rule")]
     SumProdSimplifyInvalidConclusion(Rc<Term>),
 
@@ -65,6 +81,12 @@ pub enum CheckerError {
     #[error("term '{0}' was not expected in conclusion clause")]
     ContractionExtraTerm(Rc<Term>),
 
+    #[error("premise term '{0}' is missing in weakened clause")]
+    WeakeningMissingTerm(Rc<Term>),
+
+    #[error("bound variable '{0}' occurs free in term '{1}'")]
+    BoundVarOccursFree(String, Rc<Term>),
+
     #[error("term '{0}' is not a valid n-ary operation")]
     NotValidNaryTerm(Rc<Term>),
 
@@ -162,6 +184,86 @@ pub enum CheckerError {
 
     #[error("unknown rule")]
     UnknownRule,
+
+    #[error(transparent)]
+    TermLimit(#[from] TermLimitError),
+}
+
+impl CheckerError {
+    /// Returns the stable code identifying this kind of error. Errors that wrap a more specific
+    /// error use the code of the wrapped error.
+    pub fn code(&self) -> ErrorCode {
+        let number = match self {
+            Self::Substitution(e) => return e.code(),
+            Self::Resolution(e) => return e.code(),
+            Self::Cong(e) => return e.code(),
+            Self::Quant(e) => return e.code(),
+            Self::LinearArithmetic(e) => return e.code(),
+            Self::Subproof(e) => return e.code(),
+            Self::Datatype(e) => return e.code(),
+            Self::Rare(e) => return e.code(),
+            Self::Drat(e) => return e.code(),
+            Self::Unspecified => 200,
+            Self::Assume(..) => 201,
+            Self::UnknownRule => 202,
+            Self::BrokenTransitivityChain(..) => 203,
+            Self::ReflexivityFailed(..) => 204,
+            Self::SimplificationFailed { .. } => 205,
+            Self::CycleInSimplification(..) => 206,
+            Self::RewriteSequenceNotFound(..) => 207,
+            Self::SumProdSimplifyInvalidConclusion(..) => 208,
+            Self::TermIsNotConnective(..) => 209,
+            Self::IsNotValidIteIntro(..) => 210,
+            Self::ContractionMissingTerm(..) => 211,
+            Self::ContractionExtraTerm(..) => 212,
+            Self::NotValidNaryTerm(..) => 213,
+            Self::LengthCannotBeEvaluated(..) => 214,
+            Self::NoIthChildInTerm(..) => 215,
+            Self::WrongNumberOfPremises(..) => 216,
+            Self::WrongLengthOfClause(..) => 217,
+            Self::WrongNumberOfArgs(..) => 218,
+            Self::WrongNumberOfTermsInOp(..) => 219,
+            Self::TermDoesntApperInOp(..) => 220,
+            Self::WrongLengthOfPremiseClause(..) => 221,
+            Self::TermOfWrongForm(..) => 222,
+            Self::ExpectedBoolConstant(..) => 223,
+            Self::ExpectedAnyBoolConstant(..) => 224,
+            Self::ExpectedStringConstantOfLengthOne(..) => 225,
+            Self::ExpectedDifferentConstantPrefixes(..) => 226,
+            Self::ExpectedNumber(..) => 227,
+            Self::ExpectedInteger(..) => 228,
+            Self::ExpectedAnyNumber(..) => 229,
+            Self::ExpectedAnyInteger(..) => 230,
+            Self::ExpectedNonnegInteger(..) => 231,
+            Self::ExpectedOperationTerm(..) => 232,
+            Self::ExpectedQuantifierTerm(..) => 233,
+            Self::ExpectedBinderTerm(..) => 234,
+            Self::ExpectedLetTerm(..) => 235,
+            Self::ExpectedToBePrefix(..) => 236,
+            Self::ExpectedToBeSuffix(..) => 237,
+            Self::ExpectedToNotBeEmpty(..) => 238,
+            Self::MustBeLastStepInSubproof => 239,
+            Self::DivOrModByZero => 240,
+            Self::TermLimit(TermLimitError::TooLarge(_)) => 241,
+            Self::TermLimit(TermLimitError::TooDeep(_)) => 242,
+            Self::PremiseOutOfScope(..) => 243,
+            Self::WeakeningMissingTerm(..) => 244,
+            Self::BoundVarOccursFree(..) => 245,
+            Self::TermEquality(e) => 250 + e.offset(),
+            Self::QuantifierEquality(e) => 252 + e.offset(),
+            Self::BindingListEquality(e) => 254 + e.offset(),
+        };
+        ErrorCode::new(number)
+    }
+}
+
+impl<T: TypeName> EqualityError<T> {
+    fn offset(&self) -> u16 {
+        match self {
+            Self::ExpectedEqual(..) => 0,
+            Self::ExpectedToBe { .. } => 1,
+        }
+    }
 }
 
 /// Errors in which we expected two things to be equal but they weren't.
@@ -234,6 +336,9 @@ pub enum QuantifierError {
     #[error("no argument was given for binding '{0}'")]
     NoArgGivenForBinding(String),
 
+    #[error("no instantiation given by the patterns of '{0}' matches the result")]
+    NoPatternMatches(Rc<Term>),
+
     #[error("union of bindings '{left_outer}' and '{left_inner}' does not equal '{right}'")]
     JoinFailed {
         left_outer: BindingList,
@@ -274,6 +379,30 @@ pub enum LinearArithmeticError {
 
     #[error("expected term '{0}' to be less than or equal to term '{1}'")]
     ExpectedLessEq(Rc<Term>, Rc<Term>),
+
+    #[error("'(<= {1} {2})' does not match the sides of the equality '{0}'")]
+    NotEquivalentDisequality(Rc<Term>, Rc<Term>, Rc<Term>),
+
+    #[error("'{0}' is not equivalent to '({1} {2} {3})'")]
+    NotEquivalentBound(Rc<Term>, Operator, Rc<Term>, Rc<Term>),
+
+    #[error("negation of clause is satisfiable in linear integer arithmetic")]
+    LiaClauseNotValid,
+
+    #[error("exceeded the limit of {0} nodes when searching for an integer solution")]
+    LiaSearchLimit(usize),
+
+    #[error("simplex conflict has an invalid certificate")]
+    InvalidLiaCertificate,
+
+    #[error("could not prove clause using sign and interval reasoning on its nonlinear terms")]
+    NlaClauseNotProven,
+
+    #[error("cannot compute an enclosure of the value of term '{0}'")]
+    CannotEnclose(Rc<Term>),
+
+    #[error("could not prove any literal of the clause using interval arithmetic")]
+    BoundNotProven,
 }
 
 /// Errors relevant to all rules that end subproofs (not just the `subproof` rule).
@@ -320,6 +449,205 @@ pub enum SubproofError {
 
     #[error("expected binding list in right-hand side to be '{0}'")]
     OnepointWrongRightBindings(BindingList),
+
+    #[error("discharged command '{0}' is not in the current subproof")]
+    DischargeNotLocal(String),
+}
+
+impl CongruenceError {
+    /// Returns the stable code identifying this kind of error.
+    pub fn code(&self) -> ErrorCode {
+        let number = match self {
+            Self::TooManyPremises => 400,
+            Self::MissingPremise(..) => 401,
+            Self::PremiseDoesntJustifyArgs { .. } => 402,
+            Self::DifferentFunctions(..) => 403,
+            Self::DifferentOperators(..) => 404,
+            Self::DifferentNumberOfArguments(..) => 405,
+            Self::NotApplicationOrOperation(..) => 406,
+            Self::DifferentIndexedOperators(..) => 407,
+        };
+        ErrorCode::new(number)
+    }
+}
+
+impl QuantifierError {
+    /// Returns the stable code identifying this kind of error.
+    pub fn code(&self) -> ErrorCode {
+        let number = match self {
+            Self::NoBindingMatchesArg(..) => 500,
+            Self::NoArgGivenForBinding(..) => 501,
+            Self::JoinFailed { .. } => 502,
+            Self::CnfNewBindingIntroduced(..) => 503,
+            Self::CnfBindingIsMissing(..) => 504,
+            Self::ClauseDoesntAppearInCnf(..) => 505,
+            Self::NoPatternMatches(..) => 506,
+        };
+        ErrorCode::new(number)
+    }
+}
+
+impl LinearArithmeticError {
+    /// Returns the stable code identifying this kind of error.
+    pub fn code(&self) -> ErrorCode {
+        let number = match self {
+            Self::NotValidTautologyCase(..) => 600,
+            Self::InvalidDisequalityOp(..) => 601,
+            Self::TooManyArgsInDisequality(..) => 602,
+            Self::DisequalityIsNotContradiction(..) => 603,
+            Self::DisequalityIsNotTautology(..) => 604,
+            Self::ExpectedLessThan(..) => 605,
+            Self::ExpectedLessEq(..) => 606,
+            Self::NotEquivalentDisequality(..) => 607,
+            Self::NotEquivalentBound(..) => 608,
+            Self::LiaClauseNotValid => 609,
+            Self::LiaSearchLimit(..) => 610,
+            Self::InvalidLiaCertificate => 611,
+            Self::NlaClauseNotProven => 612,
+            Self::CannotEnclose(..) => 613,
+            Self::BoundNotProven => 614,
+        };
+        ErrorCode::new(number)
+    }
+}
+
+impl SubproofError {
+    /// Returns the stable code identifying this kind of error.
+    pub fn code(&self) -> ErrorCode {
+        let number = match self {
+            Self::DischargeMustBeAssume(..) => 700,
+            Self::LocalAssumeNotDischarged(..) => 701,
+            Self::DischargeInWrongRule => 702,
+            Self::BindBindingIsFreeVarInPhi(..) => 703,
+            Self::BindUnexpectedVarArgument(..) => 704,
+            Self::BindDifferentNumberOfBindings(..) => 705,
+            Self::BindingIsNotInContext(..) => 706,
+            Self::WrongNumberOfLetBindings(..) => 707,
+            Self::PremiseDoesntJustifyLet { .. } => 708,
+            Self::NoPointForSubstitution(..) => 709,
+            Self::OnepointWrongLeftBindings(..) => 710,
+            Self::OnepointWrongRightBindings(..) => 711,
+            Self::DischargeNotLocal(..) => 712,
+        };
+        ErrorCode::new(number)
+    }
+}
+
+/// Errors in the rules of the theory of datatypes.
+#[derive(Debug, Error)]
+pub enum DatatypeError {
+    #[error("'{0}' is not a constructor application")]
+    NotConstructorApp(Rc<Term>),
+
+    #[error("expected the same constructor, got '{0}' and '{1}'")]
+    DifferentConstructors(String, String),
+
+    #[error("expected different constructors, but both terms use '{0}'")]
+    SameConstructor(String),
+
+    #[error("'{0}' is not a datatype sort")]
+    NotDatatypeSort(Sort),
+}
+
+impl DatatypeError {
+    /// Returns the stable code identifying this kind of error.
+    pub fn code(&self) -> ErrorCode {
+        let number = match self {
+            Self::NotConstructorApp(..) => 900,
+            Self::DifferentConstructors(..) => 901,
+            Self::SameConstructor(..) => 902,
+            Self::NotDatatypeSort(..) => 903,
+        };
+        ErrorCode::new(number)
+    }
+}
+
+/// Errors in the instantiation of RARE rewrite rules.
+#[derive(Debug, Error)]
+pub enum RareError {
+    #[error("unknown symbol '{0}' in RARE rule")]
+    UnknownSymbol(String),
+
+    #[error("list parameter '{0}' can only be used as an argument of an application")]
+    ListNotInApplication(String),
+
+    #[error("'{0}' has no neutral element, and can't be applied to an empty list")]
+    EmptyApplication(Operator),
+
+    #[error("condition '{0}' does not evaluate to true")]
+    ConditionNotSatisfied(Rc<Term>),
+
+    #[error("index '{0}' does not evaluate to an integer constant")]
+    InvalidIndex(Rc<Term>),
+
+    #[error("parameter '{0}' of sort '{1}' can't be instantiated with a term of sort '{2}'")]
+    WrongSort(String, String, Rc<Term>),
+}
+
+impl RareError {
+    /// Returns the stable code identifying this kind of error.
+    pub fn code(&self) -> ErrorCode {
+        let number = match self {
+            Self::UnknownSymbol(..) => 1000,
+            Self::ListNotInApplication(..) => 1001,
+            Self::EmptyApplication(..) => 1002,
+            Self::ConditionNotSatisfied(..) => 1003,
+            Self::InvalidIndex(..) => 1004,
+            Self::WrongSort(..) => 1005,
+        };
+        ErrorCode::new(number)
+    }
+}
+
+/// Errors in the checking of DRAT and LRAT certificates.
+#[derive(Debug, Error)]
+pub enum DratError {
+    #[error(
+        "unknown certificate format '{0}', expected 'drat', 'lrat', 'drat-file' or 'lrat-file'"
+    )]
+    UnknownFormat(String),
+
+    #[error("could not read certificate file '{0}': {1}")]
+    CouldNotRead(String, String),
+
+    #[error("entry {0} of the certificate is malformed")]
+    MalformedEntry(usize),
+
+    #[error("entry {0} of the certificate uses invalid clause id {1}")]
+    InvalidClauseId(usize, i64),
+
+    #[error("the clause added in entry {0} of the certificate is not redundant")]
+    NotRedundant(usize),
+
+    #[error("the certificate does not derive the empty clause")]
+    NoEmptyClause,
+
+    #[error("reading certificate file '{0}' is not allowed")]
+    FilesNotAllowed(String),
+
+    #[error("certificate file '{0}' is not a regular file")]
+    NotARegularFile(String),
+
+    #[error("certificate file '{0}' is outside of the certificate directory")]
+    OutsideCertificateDir(String),
+}
+
+impl DratError {
+    /// Returns the stable code identifying this kind of error.
+    pub fn code(&self) -> ErrorCode {
+        let number = match self {
+            Self::UnknownFormat(..) => 1100,
+            Self::CouldNotRead(..) => 1101,
+            Self::MalformedEntry(..) => 1102,
+            Self::InvalidClauseId(..) => 1103,
+            Self::NotRedundant(..) => 1104,
+            Self::NoEmptyClause => 1105,
+            Self::FilesNotAllowed(..) => 1106,
+            Self::NotARegularFile(..) => 1107,
+            Self::OutsideCertificateDir(..) => 1108,
+        };
+        ErrorCode::new(number)
+    }
 }
 
 /// A wrapper struct that implements `fmt::Display` for linear combinations.
@@ -1,21 +1,31 @@
+mod coverage;
+mod dispatch;
 pub mod error;
 mod parallel;
 mod rules;
+mod statistics;
+mod trace;
 
-use crate::{
-    ast::*,
-    benchmarking::{CollectResults, OnlineBenchmarkResults},
-    CarcaraResult, Error,
-};
+use crate::{ast::*, benchmarking::CollectResults, rewriting::RuleDatabase, CarcaraResult, Error};
+pub use coverage::RuleCoverage;
+use dispatch::RuleTable;
 use error::{CheckerError, SubproofError};
 use indexmap::IndexSet;
 pub use parallel::{scheduler::Scheduler, ParallelProofChecker};
+pub(crate) use rules::{
+    linear_arithmetic::disequality_ratio, simplification::SIMPLIFICATION_REWRITES,
+};
 use rules::{Premise, Rule, RuleArgs, RuleResult};
+pub use statistics::StatisticsCollector;
 use std::{
     collections::HashSet,
     fmt,
+    path::PathBuf,
+    sync::Arc,
     time::{Duration, Instant},
 };
+pub use trace::TraceSelection;
+use trace::TraceState;
 
 #[derive(Clone)]
 pub struct CheckerStatistics<'s, CR: CollectResults + Send + Default> {
@@ -26,6 +36,9 @@ pub struct CheckerStatistics<'s, CR: CollectResults + Send + Default> {
     // This is the time to compare the `assume` term with the `assert` that matches it. That is,
     // this excludes the time spent searching for the correct `assert` premise.
     pub assume_core_time: Duration,
+
+    // Hits and misses of the deep-equality cache that is shared across steps.
+    pub polyeq_cache: PolyeqCacheStats,
     pub results: CR,
 }
 
@@ -38,10 +51,190 @@ impl<CR: CollectResults + Send + Default> fmt::Debug for CheckerStatistics<'_, C
             .field("polyeq_time", &self.polyeq_time)
             .field("assume_time", &self.assume_time)
             .field("assume_core_time", &self.assume_core_time)
+            .field("polyeq_cache", &self.polyeq_cache)
             .finish()
     }
 }
 
+/// The names of all rules implemented by the checker. Rules that are only accepted as holes, like
+/// `hole` and `lia_generic`, are also included.
+pub const IMPLEMENTED_RULES: &[&str] = &[
+    "true",
+    "false",
+    "not_not",
+    "and_pos",
+    "and_neg",
+    "or_pos",
+    "or_neg",
+    "xor_pos1",
+    "xor_pos2",
+    "xor_neg1",
+    "xor_neg2",
+    "implies_pos",
+    "implies_neg1",
+    "implies_neg2",
+    "equiv_pos1",
+    "equiv_pos2",
+    "equiv_neg1",
+    "equiv_neg2",
+    "ite_pos1",
+    "ite_pos2",
+    "ite_neg1",
+    "ite_neg2",
+    "eq_reflexive",
+    "eq_transitive",
+    "eq_congruent",
+    "eq_congruent_pred",
+    "distinct_elim",
+    "la_rw_eq",
+    "la_generic",
+    "la_disequality",
+    "la_totality",
+    "la_tautology",
+    "la_trichotomy",
+    "forall_inst",
+    "qnt_join",
+    "qnt_rm_unused",
+    "resolution",
+    "th_resolution",
+    "refl",
+    "trans",
+    "cong",
+    "ho_cong",
+    "and",
+    "tautology",
+    "not_or",
+    "or",
+    "not_and",
+    "xor1",
+    "xor2",
+    "not_xor1",
+    "not_xor2",
+    "implies",
+    "not_implies1",
+    "not_implies2",
+    "equiv1",
+    "equiv2",
+    "not_equiv1",
+    "not_equiv2",
+    "ite1",
+    "ite2",
+    "not_ite1",
+    "not_ite2",
+    "ite_intro",
+    "contraction",
+    "connective_def",
+    "ite_simplify",
+    "eq_simplify",
+    "and_simplify",
+    "or_simplify",
+    "not_simplify",
+    "implies_simplify",
+    "equiv_simplify",
+    "bool_simplify",
+    "all_simplify",
+    "rare_rewrite",
+    "qnt_simplify",
+    "div_simplify",
+    "prod_simplify",
+    "unary_minus_simplify",
+    "minus_simplify",
+    "sum_simplify",
+    "comp_simplify",
+    "nary_elim",
+    "ac_simp",
+    "bfun_elim",
+    "bind",
+    "qnt_cnf",
+    "subproof",
+    "let",
+    "onepoint",
+    "sko_ex",
+    "sko_forall",
+    "reordering",
+    "symm",
+    "not_symm",
+    "eq_symmetric",
+    "weakening",
+    "bind_let",
+    "la_mult_pos",
+    "la_mult_neg",
+    "mod_simplify",
+    "bitblast_extract",
+    "bitblast_bvadd",
+    "bitblast_ult",
+    "bv_bitblast_step_var",
+    "bv_bitblast_step_const",
+    "bv_bitblast_step_bvnot",
+    "bv_bitblast_step_bvand",
+    "bv_bitblast_step_bvor",
+    "bv_bitblast_step_bvxor",
+    "bv_bitblast_step_concat",
+    "bv_bitblast_step_extract",
+    "bv_bitblast_step_bvadd",
+    "bv_bitblast_step_bvult",
+    "bv_bitblast_step_bvequal",
+    "concat_eq",
+    "concat_unify",
+    "concat_conflict",
+    "concat_csplit_prefix",
+    "concat_csplit_suffix",
+    "concat_split_prefix",
+    "concat_split_suffix",
+    "concat_lprop_prefix",
+    "concat_lprop_suffix",
+    "concat_cprop_prefix",
+    "concat_cprop_suffix",
+    "string_decompose",
+    "string_length_pos",
+    "string_length_non_empty",
+    "string_code_inj",
+    "re_inter",
+    "re_unfold_neg",
+    "re_unfold_neg_concat_fixed_prefix",
+    "re_unfold_neg_concat_fixed_suffix",
+    "str_in_re_eval",
+    "fp_eval",
+    "ff_eval",
+    "dt_unif",
+    "dt_clash",
+    "dt_split",
+    "ext",
+    "sets_ext",
+    "sets_singleton_inj",
+    "sets_member",
+    "nla_generic",
+    "arith_trans_bound",
+    "arith_trans_pi",
+    "arith_trans_exp_positivity",
+    "arith_trans_exp_neg",
+    "arith_trans_exp_zero",
+    "arith_trans_exp_super_lin",
+    "arith_trans_sine_bounds",
+    "arith_trans_sine_symmetry",
+    "drat_refutation",
+    "hole",
+    "lia_generic",
+    "strict_resolution",
+];
+
+/// What the checker requires the proof to conclude.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum ExpectedConclusion {
+    /// Some step in the proof must conclude the empty clause.
+    #[default]
+    AnyEmptyClause,
+
+    /// The last step in the root proof must conclude the empty clause.
+    FinalEmptyClause,
+
+    /// The last step in the root proof must conclude the given clause, written as `(cl <term>*)`.
+    /// This allows checking proofs that are not refutations. The terms in the clause may use the
+    /// sorts and functions declared or defined in the problem, and are parsed with the same
+    /// configuration as the proof.
+    FinalGoal(String),
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Config {
     /// If `true`, the checker will assume that the proof is elaborated, and enforce extra
@@ -52,12 +245,53 @@ pub struct Config {
     /// - `resolution` and `th_resolution`: the pivots must be provided as arguments
     pub elaborated: bool,
 
+    /// If `true`, `resolution` and `th_resolution` steps are checked using the strict semantics
+    /// described in the Alethe specification. In that case, the pivots must be provided as
+    /// arguments, and the conclusion must be exactly the clause obtained by resolving the premises
+    /// in order: the literals must appear in the order they are introduced, and duplicates are not
+    /// implicitly removed. Any reordering or contraction must be done explicitly, using the
+    /// `reordering` and `contraction` rules.
+    pub strict_resolution: bool,
+
     /// If `true`, the checker will skip any steps with rules that it does not recognize, and will
     /// consider them as holes. Normally, using an unknown rule is considered an error.
     pub ignore_unknown_rules: bool,
 
     /// A set of rule names that the checker will allow, considering them holes in the proof.
     pub allowed_rules: HashSet<String>,
+
+    /// The steps whose checking should be traced. For each of these steps, the checker prints the
+    /// premises, arguments, conclusion and context substitution, as well as any intermediate
+    /// values computed by the rule. This is only supported by the sequential checker.
+    pub trace: Option<TraceSelection>,
+
+    /// Limits on the size and depth of the terms built by rules while checking. A step that would
+    /// exceed them is considered invalid. By default, there are no limits.
+    pub term_limits: TermLimits,
+
+    /// If `Some(n)`, the checker logs a warning for each clause that is concluded by at least `n`
+    /// steps in the root proof. This usually indicates an inefficiency in the solver that produced
+    /// the proof. This is only supported by the sequential checker.
+    pub duplicate_conclusion_threshold: Option<usize>,
+
+    /// If `true`, `lia_generic` steps are checked using an internal decision procedure for linear
+    /// integer arithmetic, instead of being considered holes. Steps whose validity can't be
+    /// established by the procedure are considered invalid.
+    pub native_lia_generic: bool,
+
+    /// What the proof is required to conclude. By default, it is enough for any step in the proof
+    /// to conclude the empty clause.
+    pub expected_conclusion: ExpectedConclusion,
+
+    /// The RARE rewrite rules used to check `rare_rewrite` steps. Steps that use a rule not in this
+    /// database are considered to use an unknown rule. These rules are also available to umbrella
+    /// rules like `all_simplify`.
+    pub rare_rules: Option<Arc<RuleDatabase>>,
+
+    /// The directory against which the certificate files referenced by `drat_refutation` steps are
+    /// resolved, usually the directory of the proof file. If this is `None`, which is the default,
+    /// steps may only give their certificates inline, and the checker never reads any files.
+    pub certificate_dir: Option<PathBuf>,
 }
 
 impl Config {
@@ -70,10 +304,68 @@ impl Config {
         self
     }
 
+    pub fn strict_resolution(mut self, value: bool) -> Self {
+        self.strict_resolution = value;
+        self
+    }
+
     pub fn ignore_unknown_rules(mut self, value: bool) -> Self {
         self.ignore_unknown_rules = value;
         self
     }
+
+    pub fn trace(mut self, value: Option<TraceSelection>) -> Self {
+        self.trace = value;
+        self
+    }
+
+    pub fn term_limits(mut self, value: TermLimits) -> Self {
+        self.term_limits = value;
+        self
+    }
+
+    pub fn duplicate_conclusion_threshold(mut self, value: Option<usize>) -> Self {
+        self.duplicate_conclusion_threshold = value;
+        self
+    }
+
+    pub fn native_lia_generic(mut self, value: bool) -> Self {
+        self.native_lia_generic = value;
+        self
+    }
+
+    pub fn expected_conclusion(mut self, value: ExpectedConclusion) -> Self {
+        self.expected_conclusion = value;
+        self
+    }
+
+    pub fn rare_rules(mut self, value: Option<Arc<RuleDatabase>>) -> Self {
+        self.rare_rules = value;
+        self
+    }
+
+    pub fn certificate_dir(mut self, value: Option<PathBuf>) -> Self {
+        self.certificate_dir = value;
+        self
+    }
+}
+
+/// Returns the rule name with which opening a subproof is reported to statistics collectors.
+fn anchor_rule_name(subproof: &Subproof) -> String {
+    match subproof.commands.last() {
+        Some(ProofCommand::Step(step)) => format!("anchor({})", &step.rule),
+        _ => "anchor".to_owned(),
+    }
+}
+
+/// Builds the database of rewrites used by umbrella rules like `all_simplify`, which contains the
+/// checker's simplification rules, as well as the RARE rules given in the configuration.
+fn rewrite_database(config: &Config) -> RuleDatabase {
+    let mut result = RuleDatabase::simplification();
+    if let Some(rare_rules) = &config.rare_rules {
+        result.extend(rare_rules);
+    }
+    result
 }
 
 pub struct ProofChecker<'c> {
@@ -82,25 +374,48 @@ pub struct ProofChecker<'c> {
     context: ContextStack,
     reached_empty_clause: bool,
     is_holey: bool,
+    trace: TraceState,
+    rules: RuleTable,
+    rewrites: RuleDatabase,
+    datatypes: Datatypes,
 }
 
 impl<'c> ProofChecker<'c> {
     pub fn new(pool: &'c mut PrimitivePool, config: Config) -> Self {
+        let trace = TraceState::new(config.trace.clone());
+        let rules = RuleTable::new(&config);
+        let rewrites = rewrite_database(&config);
         ProofChecker {
             pool,
             config,
             context: ContextStack::new(),
             reached_empty_clause: false,
             is_holey: false,
+            trace,
+            rules,
+            rewrites,
+            datatypes: Datatypes::new(),
         }
     }
 
     pub fn check(&mut self, problem: &Problem, proof: &Proof) -> CarcaraResult<bool> {
-        self.check_impl(
-            problem,
-            proof,
-            None::<&mut CheckerStatistics<OnlineBenchmarkResults>>,
-        )
+        let result = self.check_impl(problem, proof, None);
+        // The shared deep-equality cache may keep large terms alive, so we don't let it outlive
+        // the check
+        clear_shared_cache();
+        result
+    }
+
+    /// Checks the proof, reporting every event to the given statistics collector.
+    pub fn check_with_collector(
+        &mut self,
+        problem: &Problem,
+        proof: &Proof,
+        collector: &mut dyn StatisticsCollector,
+    ) -> CarcaraResult<bool> {
+        let result = self.check_impl(problem, proof, Some(collector));
+        clear_shared_cache();
+        result
     }
 
     pub fn check_with_stats<CR: CollectResults + Send + Default>(
@@ -109,40 +424,49 @@ impl<'c> ProofChecker<'c> {
         proof: &Proof,
         stats: &mut CheckerStatistics<CR>,
     ) -> CarcaraResult<bool> {
-        self.check_impl(problem, proof, Some(stats))
+        let cache_before = PolyeqCacheStats::current();
+        let result = self.check_impl(problem, proof, Some(&mut *stats));
+        stats.polyeq_cache += PolyeqCacheStats::current().since(cache_before);
+        clear_shared_cache();
+        result
     }
 
-    fn check_impl<CR: CollectResults + Send + Default>(
+    fn check_impl(
         &mut self,
         problem: &Problem,
         proof: &Proof,
-        mut stats: Option<&mut CheckerStatistics<CR>>,
+        mut stats: Option<&mut dyn StatisticsCollector>,
     ) -> CarcaraResult<bool> {
+        self.datatypes = problem.prelude.datatypes.clone();
+        let threshold = self.config.duplicate_conclusion_threshold;
+        let mut conclusions = threshold.map(|_| ClauseIndex::new());
+
         // Similarly to the parser, to avoid stack overflows in proofs with many nested subproofs,
         // we check the subproofs iteratively, instead of recursively
         let mut iter = proof.iter();
         while let Some(command) = iter.next() {
             match command {
                 ProofCommand::Step(step) => {
+                    if let Some(conclusions) = &mut conclusions {
+                        if iter.depth() == 0 {
+                            conclusions.insert(step.clause.as_slice().into(), step.id.as_str());
+                        }
+                    }
                     let is_end_of_subproof = iter.is_end_step();
-
-                    // If this step ends a subproof, it might need to implicitly reference the
-                    // previous command in the subproof
-                    let previous_command = if is_end_of_subproof {
-                        let subproof = iter.current_subproof().unwrap();
-                        let index = subproof.len() - 2;
-                        subproof
-                            .get(index)
-                            .map(|command| Premise::new((iter.depth(), index), command))
-                    } else {
-                        None
-                    };
-                    self.check_step(step, previous_command, &iter, &mut stats)
-                        .map_err(|e| Error::Checker {
-                            inner: e,
-                            rule: step.rule.clone(),
-                            step: step.id.clone(),
-                        })?;
+                    let previous_command = previous_command(&iter);
+                    let traced = self.trace.should_trace(&step.id);
+                    if traced {
+                        trace::begin();
+                    }
+                    let result = self.check_step(step, previous_command, &iter, &mut stats);
+                    if traced {
+                        self.print_trace(step, previous_command, &iter, &result);
+                    }
+                    result.map_err(|e| Error::Checker {
+                        inner: e,
+                        rule: step.rule.clone(),
+                        step: step.id.clone(),
+                    })?;
 
                     // If this is the last command of a subproof, we have to pop the subproof
                     // commands off of the stack. The parser already ensures that the last command
@@ -156,22 +480,17 @@ impl<'c> ProofChecker<'c> {
                     }
                 }
                 ProofCommand::Subproof(s) => {
-                    let time = Instant::now();
                     let step_id = command.id();
+                    let rule_name = stats.as_ref().map(|_| anchor_rule_name(s));
+                    if let (Some(stats), Some(rule_name)) = (&mut stats, &rule_name) {
+                        stats.step_started(step_id, rule_name);
+                    }
+                    let time = Instant::now();
 
                     self.context.push(&s.args);
 
-                    if let Some(stats) = &mut stats {
-                        let rule_name = match s.commands.last() {
-                            Some(ProofCommand::Step(step)) => format!("anchor({})", &step.rule),
-                            _ => "anchor".to_owned(),
-                        };
-                        stats.results.add_step_measurement(
-                            stats.file_name,
-                            step_id,
-                            &rule_name,
-                            time.elapsed(),
-                        );
+                    if let (Some(stats), Some(rule_name)) = (&mut stats, &rule_name) {
+                        stats.step_finished(step_id, rule_name, time.elapsed(), Duration::ZERO);
                     }
                 }
                 ProofCommand::Assume { id, term } => {
@@ -185,20 +504,69 @@ impl<'c> ProofChecker<'c> {
                 }
             }
         }
-        if self.reached_empty_clause {
-            Ok(self.is_holey)
-        } else {
-            Err(Error::DoesNotReachEmptyClause)
+        if let (Some(conclusions), Some(threshold)) = (conclusions, threshold) {
+            warn_duplicate_conclusions(&conclusions, threshold);
         }
+        check_conclusion(&self.config, problem, proof, self.reached_empty_clause)?;
+        Ok(self.is_holey)
+    }
+
+    /// Re-checks a single step of the proof, identified by its id, after applying `modify` to a
+    /// copy of it. The step is checked in the same context in which it appears in the proof, but
+    /// no other step is checked. This allows exploring what change would make a failing step pass,
+    /// for example by fixing one of its arguments or premises.
+    ///
+    /// The state of the checker is not affected by this. Returns an error if the modified step
+    /// fails, or if there is no step with the given id in the proof.
+    pub fn recheck_step<F>(&mut self, proof: &Proof, id: &str, modify: F) -> CarcaraResult<()>
+    where
+        F: FnOnce(&mut ProofStep),
+    {
+        let context = std::mem::replace(&mut self.context, ContextStack::new());
+        let is_holey = self.is_holey;
+        let result = self.recheck_step_impl(proof, id, modify);
+        self.context = context;
+        self.is_holey = is_holey;
+        result
     }
 
-    fn check_assume<'i, CR: CollectResults + Send + Default>(
+    fn recheck_step_impl<F>(&mut self, proof: &Proof, id: &str, modify: F) -> CarcaraResult<()>
+    where
+        F: FnOnce(&mut ProofStep),
+    {
+        // We only need to rebuild the context stack up to the step, so we skip checking every
+        // other command
+        let mut iter = proof.iter();
+        while let Some(command) = iter.next() {
+            match command {
+                ProofCommand::Step(step) if step.id == id => {
+                    let mut step = step.clone();
+                    modify(&mut step);
+                    let previous_command = previous_command(&iter);
+                    let mut stats = None;
+                    return self
+                        .check_step(&step, previous_command, &iter, &mut stats)
+                        .map_err(|e| Error::Checker {
+                            inner: e,
+                            rule: step.rule.clone(),
+                            step: step.id.clone(),
+                        });
+                }
+                ProofCommand::Step(_) if iter.is_end_step() => self.context.pop(),
+                ProofCommand::Subproof(s) => self.context.push(&s.args),
+                _ => (),
+            }
+        }
+        Err(Error::StepNotFound(id.to_owned()))
+    }
+
+    fn check_assume<'i>(
         &mut self,
         id: &str,
         term: &Rc<Term>,
         premises: &IndexSet<Rc<Term>>,
         iter: &'i ProofIter<'i>,
-        mut stats: &mut Option<&mut CheckerStatistics<CR>>,
+        stats: &mut Option<&mut dyn StatisticsCollector>,
     ) -> bool {
         let time = Instant::now();
 
@@ -211,11 +579,7 @@ impl<'c> ProofChecker<'c> {
 
         if premises.contains(term) {
             if let Some(s) = stats {
-                let time = time.elapsed();
-
-                s.assume_time += time;
-                s.results
-                    .add_assume_measurement(s.file_name, id, true, time);
+                s.assume_checked(id, true, time.elapsed(), Duration::ZERO, Duration::ZERO);
             }
             return true;
         }
@@ -237,8 +601,8 @@ impl<'c> ProofChecker<'c> {
 
             polyeq_time += this_polyeq_time;
 
-            if let Some(s) = &mut stats {
-                s.results.add_polyeq_depth(depth);
+            if let Some(s) = stats {
+                s.polyeq_depth(depth);
             }
             if result {
                 core_time = this_polyeq_time;
@@ -250,34 +614,53 @@ impl<'c> ProofChecker<'c> {
             return false;
         };
 
-        if let Some(s) = &mut stats {
-            let time = time.elapsed();
-
-            s.assume_time += time;
-            s.assume_core_time += core_time;
-            s.polyeq_time += polyeq_time;
-            s.results
-                .add_assume_measurement(s.file_name, id, false, time);
+        if let Some(s) = stats {
+            s.assume_checked(id, false, time.elapsed(), core_time, polyeq_time);
         }
 
         true
     }
 
-    fn check_step<'i, CR: CollectResults + Send + Default>(
+    fn check_step<'i>(
         &mut self,
         step: &ProofStep,
         previous_command: Option<Premise>,
         iter: &'i ProofIter<'i>,
-        stats: &mut Option<&mut CheckerStatistics<CR>>,
+        stats: &mut Option<&mut dyn StatisticsCollector>,
     ) -> RuleResult {
+        if let Some(s) = stats {
+            s.step_started(&step.id, &step.rule);
+        }
         let time = Instant::now();
+        let pool_size = self.pool.num_terms();
         let mut polyeq_time = Duration::ZERO;
 
+        // Steps whose rule is ignored are also reported as finished, so every `step_started` event
+        // is matched by a `step_finished` event unless checking fails
+        self.check_step_rule(step, previous_command, iter, &mut polyeq_time)?;
+
+        if let Some(s) = stats {
+            s.step_finished(&step.id, &step.rule, time.elapsed(), polyeq_time);
+            let new_size = self.pool.num_terms();
+            if new_size > pool_size {
+                s.pool_grew(new_size);
+            }
+        }
+        Ok(())
+    }
+
+    fn check_step_rule<'i>(
+        &mut self,
+        step: &ProofStep,
+        previous_command: Option<Premise>,
+        iter: &'i ProofIter<'i>,
+        polyeq_time: &mut Duration,
+    ) -> RuleResult {
         if !step.discharge.is_empty() && step.rule != "subproof" {
             return Err(CheckerError::Subproof(SubproofError::DischargeInWrongRule));
         }
 
-        let rule = match Self::get_rule(&step.rule, self.config.elaborated) {
+        let rule = match self.rules.get(&step.rule) {
             Some(r) => r,
             None if self.config.ignore_unknown_rules
                 || self.config.allowed_rules.contains(&step.rule) =>
@@ -288,10 +671,16 @@ impl<'c> ProofChecker<'c> {
             None => return Err(CheckerError::UnknownRule),
         };
 
-        if step.rule == "hole" || step.rule == "lia_generic" {
+        if step.rule == "hole" || (step.rule == "lia_generic" && !self.config.native_lia_generic) {
             self.is_holey = true;
         }
 
+        check_premise_scopes(
+            step,
+            iter.depth(),
+            |p| iter.is_in_scope(p),
+            |p| iter.get_premise(p).id().to_owned(),
+        )?;
         let premises: Vec<_> = step
             .premises
             .iter()
@@ -310,28 +699,83 @@ impl<'c> ProofChecker<'c> {
             conclusion: &step.clause,
             premises: &premises,
             args: &step.args,
+            arg_vars: &step.arg_vars,
             pool: self.pool,
             context: &mut self.context,
             previous_command,
             discharge: &discharge,
-            polyeq_time: &mut polyeq_time,
+            polyeq_time,
+            term_limits: self.config.term_limits,
+            datatypes: &self.datatypes,
+            rewrites: &self.rewrites,
+            certificate_dir: self.config.certificate_dir.as_deref(),
         };
 
-        rule(rule_args)?;
+        match rule(rule_args) {
+            // Some rules, like `rare_rewrite`, may only find out that the step uses an unknown
+            // rule when checking it
+            Err(CheckerError::UnknownRule)
+                if self.config.ignore_unknown_rules
+                    || self.config.allowed_rules.contains(&step.rule) =>
+            {
+                self.is_holey = true;
+                return Ok(());
+            }
+            result => result?,
+        }
 
         if iter.is_end_step() {
             let subproof = iter.current_subproof().unwrap();
             Self::check_discharge(subproof, iter.depth(), &step.discharge)?;
         }
+        Ok(())
+    }
 
-        if let Some(s) = stats {
-            let time = time.elapsed();
+    fn print_trace(
+        &mut self,
+        step: &ProofStep,
+        previous_command: Option<Premise>,
+        iter: &ProofIter,
+        result: &RuleResult,
+    ) {
+        let values = trace::end();
+        let clause_to_string = |clause: &[Rc<Term>]| {
+            let mut result = "(cl".to_owned();
+            for t in clause {
+                result += &format!(" {}", t);
+            }
+            result + ")"
+        };
 
-            s.results
-                .add_step_measurement(s.file_name, &step.id, &step.rule, time);
-            s.polyeq_time += polyeq_time;
+        log::warn!("trace of step '{}' (rule '{}'):", step.id, step.rule);
+        for &p in &step.premises {
+            let command = iter.get_premise(p);
+            let clause = clause_to_string(command.clause());
+            log::warn!("    premise {}: {}", command.id(), clause);
+        }
+        if let Some(previous) = previous_command {
+            let clause = clause_to_string(previous.clause);
+            log::warn!("    previous command {}: {}", previous.id, clause);
+        }
+        for &d in &step.discharge {
+            log::warn!("    discharges {}", iter.get_premise(d).id());
+        }
+        for arg in &step.args {
+            log::warn!("    argument: {}", arg);
+        }
+        if let Some(substitution) = self.context.cumulative_substitution(self.pool) {
+            for (var, value) in substitution.iter() {
+                log::warn!("    context: {} -> {}", var, value);
+            }
+        }
+        log::warn!("    conclusion: {}", clause_to_string(&step.clause));
+        for value in values {
+            log::warn!("    {}", value);
+        }
+        match result {
+            Ok(()) => log::warn!("    result: ok"),
+            Err(e) => log::warn!("    result: {}", e),
         }
-        Ok(())
     }
 
     fn check_discharge(
@@ -353,7 +797,7 @@ impl<'c> ProofChecker<'c> {
         }
     }
 
-    pub fn get_rule(rule_name: &str, elaborated: bool) -> Option<Rule> {
+    pub fn get_rule(rule_name: &str, config: &Config) -> Option<Rule> {
         use rules::*;
 
         Some(match rule_name {
@@ -389,12 +833,17 @@ impl<'c> ProofChecker<'c> {
             "la_disequality" => linear_arithmetic::la_disequality,
             "la_totality" => linear_arithmetic::la_totality,
             "la_tautology" => linear_arithmetic::la_tautology,
+            "la_trichotomy" => linear_arithmetic::la_trichotomy,
             "forall_inst" => quantifier::forall_inst,
             "qnt_join" => quantifier::qnt_join,
             "qnt_rm_unused" => quantifier::qnt_rm_unused,
-            "resolution" | "th_resolution" if elaborated => resolution::resolution_with_args,
+            "resolution" | "th_resolution" if config.strict_resolution => {
+                resolution::strict_resolution
+            }
+            "resolution" | "th_resolution" if config.elaborated => resolution::resolution_with_args,
             "resolution" | "th_resolution" => resolution::resolution,
-            "refl" if elaborated => reflexivity::strict_refl,
+            "refl" if config.elaborated => reflexivity::strict_refl,
+            "weakening" if config.elaborated => extras::strict_weakening,
             "refl" => reflexivity::refl,
             "trans" => transitivity::trans,
             "cong" => congruence::cong,
@@ -430,6 +879,8 @@ impl<'c> ProofChecker<'c> {
             "implies_simplify" => simplification::implies_simplify,
             "equiv_simplify" => simplification::equiv_simplify,
             "bool_simplify" => simplification::bool_simplify,
+            "all_simplify" => all_simplify::all_simplify,
+            "rare_rewrite" => all_simplify::rare_rewrite,
             "qnt_simplify" => simplification::qnt_simplify,
             "div_simplify" => simplification::div_simplify,
             "prod_simplify" => simplification::prod_simplify,
@@ -461,6 +912,17 @@ impl<'c> ProofChecker<'c> {
             "bitblast_extract" => bitvectors::extract,
             "bitblast_bvadd" => bitvectors::add,
             "bitblast_ult" => bitvectors::ult,
+            "bv_bitblast_step_var" => bitvectors::var,
+            "bv_bitblast_step_const" => bitvectors::constant,
+            "bv_bitblast_step_bvnot" => bitvectors::not,
+            "bv_bitblast_step_bvand" => bitvectors::and,
+            "bv_bitblast_step_bvor" => bitvectors::or,
+            "bv_bitblast_step_bvxor" => bitvectors::xor,
+            "bv_bitblast_step_concat" => bitvectors::concat,
+            "bv_bitblast_step_extract" => bitvectors::extract,
+            "bv_bitblast_step_bvadd" => bitvectors::add,
+            "bv_bitblast_step_bvult" => bitvectors::ult,
+            "bv_bitblast_step_bvequal" => bitvectors::equal,
 
             "concat_eq" => strings::concat_eq,
             "concat_unify" => strings::concat_unify,
@@ -477,15 +939,43 @@ impl<'c> ProofChecker<'c> {
             "string_decompose" => strings::string_decompose,
             "string_length_pos" => strings::string_length_pos,
             "string_length_non_empty" => strings::string_length_non_empty,
+            "string_code_inj" => strings::string_code_inj,
 
             "re_inter" => strings::re_inter,
             "re_unfold_neg" => strings::re_unfold_neg,
             "re_unfold_neg_concat_fixed_prefix" => strings::re_unfold_neg_concat_fixed_prefix,
             "re_unfold_neg_concat_fixed_suffix" => strings::re_unfold_neg_concat_fixed_suffix,
+            "str_in_re_eval" => strings::str_in_re_eval,
+
+            "fp_eval" => floating_point::fp_eval,
+
+            "ff_eval" => finite_field::ff_eval,
+
+            "dt_unif" => datatypes::dt_unif,
+            "dt_clash" => datatypes::dt_clash,
+            "dt_split" => datatypes::dt_split,
+
+            "ext" => arrays::ext,
+
+            "sets_ext" => sets::sets_ext,
+            "sets_singleton_inj" => sets::sets_singleton_inj,
+            "sets_member" => sets::sets_member,
+
+            "nla_generic" => nonlinear_arithmetic::nla_generic,
+            "arith_trans_bound" => transcendental::arith_trans_bound,
+            "arith_trans_pi" => transcendental::arith_trans_pi,
+            "arith_trans_exp_positivity" => transcendental::arith_trans_exp_positivity,
+            "arith_trans_exp_neg" => transcendental::arith_trans_exp_neg,
+            "arith_trans_exp_zero" => transcendental::arith_trans_exp_zero,
+            "arith_trans_exp_super_lin" => transcendental::arith_trans_exp_super_lin,
+            "arith_trans_sine_bounds" => transcendental::arith_trans_sine_bounds,
+            "arith_trans_sine_symmetry" => transcendental::arith_trans_sine_symmetry,
+            "drat_refutation" => drat::drat_refutation,
 
             // Special rules that always check as valid, and are used to indicate holes in the
             // proof.
             "hole" => |_| Ok(()),
+            "lia_generic" if config.native_lia_generic => linear_arithmetic::lia_generic,
             "lia_generic" => |_| {
                 log::warn!("encountered \"lia_generic\" rule, ignoring");
                 Ok(())
@@ -500,3 +990,332 @@ impl<'c> ProofChecker<'c> {
         })
     }
 }
+
+/// Checks that the proof concludes what is required by the checker configuration. The flag
+/// `reached_empty_clause` indicates whether any step in the proof concluded the empty clause.
+fn check_conclusion(
+    config: &Config,
+    problem: &Problem,
+    proof: &Proof,
+    reached_empty_clause: bool,
+) -> CarcaraResult<()> {
+    let goal = match &config.expected_conclusion {
+        ExpectedConclusion::AnyEmptyClause if reached_empty_clause => return Ok(()),
+        ExpectedConclusion::AnyEmptyClause => return Err(Error::DoesNotReachEmptyClause),
+        ExpectedConclusion::FinalEmptyClause => None,
+        ExpectedConclusion::FinalGoal(goal) => Some(goal),
+    };
+    let Some(last) = proof.commands.last() else {
+        return Err(Error::DoesNotReachEmptyClause);
+    };
+    let step = last.id().to_owned();
+    let Some(goal) = goal else {
+        if last.clause().is_empty() {
+            return Ok(());
+        }
+        return Err(Error::FinalStepIsNotEmptyClause(step));
+    };
+
+    // The goal is parsed in a separate pool, so the clauses are compared by their printed forms.
+    // If possible, it is parsed in the same context as the proof, so it may use the functions
+    // defined in the problem, and is parsed using the same configuration
+    let mut pool = PrimitivePool::new();
+    let expected = match &problem.parsing_context {
+        Some(context) => crate::parser::parse_clause_in_context(goal, context, &mut pool)?,
+        None => crate::parser::parse_clause_with_prelude(
+            goal,
+            &problem.prelude,
+            crate::parser::Config::new(),
+            &mut pool,
+        )?,
+    };
+    let print = |clause: &[Rc<Term>]| clause.iter().map(ToString::to_string).collect::<Vec<_>>();
+    if print(last.clause()) == print(&expected) {
+        Ok(())
+    } else {
+        Err(Error::FinalStepIsNotGoal { step, goal: goal.clone() })
+    }
+}
+
+/// Checks that the premises and discharged commands of a step only reference commands that are in
+/// scope. Discharged commands must also be local to the current subproof.
+fn check_premise_scopes(
+    step: &ProofStep,
+    depth: usize,
+    is_in_scope: impl Fn((usize, usize)) -> bool,
+    get_id: impl Fn((usize, usize)) -> String,
+) -> RuleResult {
+    for &(d, i) in step.premises.iter().chain(&step.discharge) {
+        if !is_in_scope((d, i)) {
+            return Err(CheckerError::PremiseOutOfScope(d, i));
+        }
+    }
+    if let Some(&p) = step.discharge.iter().find(|(d, _)| *d != depth) {
+        return Err(SubproofError::DischargeNotLocal(get_id(p)).into());
+    }
+    Ok(())
+}
+
+/// If the current command ends a subproof, returns the previous command in the subproof, which
+/// the step might need to implicitly reference.
+fn previous_command<'a>(iter: &'a ProofIter<'a>) -> Option<Premise<'a>> {
+    if !iter.is_end_step() {
+        return None;
+    }
+    let subproof = iter.current_subproof().unwrap();
+    let index = subproof.len() - 2;
+    subproof
+        .get(index)
+        .map(|command| Premise::new((iter.depth(), index), command))
+}
+
+/// Logs a warning for each clause that is concluded by at least `threshold` steps.
+fn warn_duplicate_conclusions(conclusions: &ClauseIndex<&str>, threshold: usize) {
+    let mut duplicates: Vec<_> = conclusions.duplicates(threshold).collect();
+    duplicates.sort_by_key(|(_, ids)| std::cmp::Reverse(ids.len()));
+    for (clause, ids) in duplicates {
+        let literals: String = clause.iter().map(|l| format!(" {}", l)).collect();
+        log::warn!(
+            "{} steps conclude the clause (cl{}), first concluded by step '{}'",
+            ids.len(),
+            literals,
+            ids[0],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_code::ErrorCode;
+
+    #[test]
+    fn test_implemented_rules() {
+        for rule in IMPLEMENTED_RULES {
+            assert!(
+                ProofChecker::get_rule(rule, &Config::new()).is_some(),
+                "rule '{}' is listed as implemented, but has no implementation",
+                rule
+            );
+        }
+    }
+
+    #[test]
+    fn test_term_limits() {
+        let problem = b"
+            (declare-fun a () Int)
+            (declare-fun b () Int)
+            (declare-fun c () Int)
+            (declare-fun d () Int)
+            (declare-fun f (Bool Bool) Bool)
+            (assert (forall ((x Bool) (y Bool)) (f x y)))
+        ";
+        let proof = b"
+            (step t1 (cl (= (distinct a b c d) (and (distinct a b) (distinct a c) (distinct a d)
+                (distinct b c) (distinct b d) (distinct c d)))) :rule nary_elim)
+            (assume h1 (forall ((x Bool) (y Bool)) (f x y)))
+            (step t2 (cl (and (f false false) (f true false) (f false true) (f true true)))
+                :rule bfun_elim :premises (h1))
+            (step t3 (cl) :rule hole)
+        ";
+        let (problem, proof, mut pool) =
+            crate::parser::parse_instance(&problem[..], &proof[..], crate::parser::Config::new())
+                .unwrap();
+
+        let mut check = |limits| {
+            let config = Config::new().term_limits(limits);
+            match ProofChecker::new(&mut pool, config).check(&problem, &proof) {
+                Ok(_) => None,
+                Err(crate::Error::Checker { inner, step, .. }) => Some((inner.code(), step)),
+                Err(e) => panic!("unexpected error: {}", e),
+            }
+        };
+        let error = |number, step: &str| Some((ErrorCode::new(number), step.to_owned()));
+
+        assert_eq!(check(TermLimits::new()), None);
+        assert_eq!(check(TermLimits::new().max_size(Some(10))), None);
+        assert_eq!(check(TermLimits::new().max_size(Some(6))), error(241, "t1"));
+        assert_eq!(check(TermLimits::new().max_size(Some(7))), error(241, "t2"));
+    }
+
+    #[test]
+    fn test_expected_conclusion() {
+        let problem = b"
+            (declare-fun p () Bool)
+            (declare-fun f (Int) Int)
+            (assert p)
+            (assert (not p))
+        ";
+        let proof = b"
+            (assume h1 p)
+            (assume h2 (not p))
+            (step t3 (cl) :rule resolution :premises (h1 h2))
+            (step t4 (cl (= (f 1) (f 1))) :rule refl)
+        ";
+        let (problem, proof, mut pool) =
+            crate::parser::parse_instance(&problem[..], &proof[..], crate::parser::Config::new())
+                .unwrap();
+
+        let mut check = |expected| {
+            let config = Config::new().expected_conclusion(expected);
+            ProofChecker::new(&mut pool, config)
+                .check(&problem, &proof)
+                .map_err(|e| e.code())
+        };
+        let goal = |s: &str| ExpectedConclusion::FinalGoal(s.to_owned());
+
+        assert_eq!(check(ExpectedConclusion::AnyEmptyClause), Ok(false));
+        assert_eq!(
+            check(ExpectedConclusion::FinalEmptyClause),
+            Err(ErrorCode::new(5))
+        );
+        assert_eq!(check(goal("(cl (= (f 1) (f 1)))")), Ok(false));
+        assert_eq!(check(goal("(cl (= (f 1) (f 2)))")), Err(ErrorCode::new(6)));
+        assert_eq!(check(goal("(cl)")), Err(ErrorCode::new(6)));
+        assert_eq!(
+            check(goal("(cl (= (g 1) (f 1)))")),
+            Err(ErrorCode::new(115))
+        );
+
+        // The goal is parsed in the same context as the proof
+        let problem = b"
+            (declare-fun p () Bool)
+            (define-fun q () Bool (not p))
+        ";
+        let proof = b"(step t1 (cl (or q (not q))) :rule hole)";
+        let parser_config = crate::parser::Config {
+            expand_lets: true,
+            ..crate::parser::Config::new()
+        };
+        let (problem, proof, mut pool) =
+            crate::parser::parse_instance(&problem[..], &proof[..], parser_config).unwrap();
+        let mut check = |goal: &str| {
+            let config =
+                Config::new().expected_conclusion(ExpectedConclusion::FinalGoal(goal.into()));
+            ProofChecker::new(&mut pool, config)
+                .check(&problem, &proof)
+                .map_err(|e| e.code())
+        };
+        assert_eq!(check("(cl (or q (not q)))"), Ok(true));
+        assert_eq!(check("(cl (let ((r q)) (or r (not r))))"), Ok(true));
+    }
+
+    #[test]
+    fn test_premise_scopes() {
+        let problem = b"(assert true)";
+        let proof = b"
+            (assume h1 true)
+            (anchor :step t2)
+            (step t2.t1 (cl true) :rule true)
+            (step t2 (cl true) :rule hole :premises (t2.t1))
+            (step t3 (cl true) :rule hole :premises (h1))
+            (step t4 (cl) :rule hole)
+        ";
+        let (problem, proof, mut pool) =
+            crate::parser::parse_instance(&problem[..], &proof[..], crate::parser::Config::new())
+                .unwrap();
+
+        // The parser rejects illegal references, so we introduce them after parsing
+        let mut check = |premises: Vec<(usize, usize)>| {
+            let mut proof = proof.clone();
+            let ProofCommand::Step(step) = &mut proof.commands[2] else {
+                unreachable!()
+            };
+            step.premises = premises;
+            match ProofChecker::new(&mut pool, Config::new()).check(&problem, &proof) {
+                Ok(_) => None,
+                Err(crate::Error::Checker { inner, step, .. }) => Some((inner.code(), step)),
+                Err(e) => panic!("unexpected error: {}", e),
+            }
+        };
+        let error = Some((ErrorCode::new(243), "t3".to_owned()));
+
+        assert_eq!(check(vec![(0, 0), (0, 1)]), None);
+        assert_eq!(check(vec![(1, 0)]), error); // Inside the closed subproof
+        assert_eq!(check(vec![(0, 2)]), error); // The step itself
+        assert_eq!(check(vec![(0, 3)]), error); // A later step
+    }
+
+    #[test]
+    fn test_shared_cache_is_cleared() {
+        let problem = b"
+            (declare-fun a () Int)
+            (declare-fun b () Int)
+        ";
+        let proof = b"
+            (step t1 (cl (= (= a b) (= b a))) :rule refl)
+            (step t2 (cl) :rule hole)
+        ";
+        let (problem, proof, mut pool) =
+            crate::parser::parse_instance(&problem[..], &proof[..], crate::parser::Config::new())
+                .unwrap();
+        let mut stats = CheckerStatistics {
+            file_name: "",
+            polyeq_time: Duration::ZERO,
+            assume_time: Duration::ZERO,
+            assume_core_time: Duration::ZERO,
+            polyeq_cache: PolyeqCacheStats::default(),
+            results: crate::benchmarking::OnlineBenchmarkResults::new(),
+        };
+        let mut checker = ProofChecker::new(&mut pool, Config::new());
+        assert!(checker
+            .check_with_stats(&problem, &proof, &mut stats)
+            .is_ok());
+        assert_eq!(PolyeqCacheStats { hits: 0, misses: 1 }, stats.polyeq_cache);
+
+        // The two sides were compared during the check, but that result is no longer cached once
+        // the check finishes
+        let ProofCommand::Step(step) = &proof.commands[0] else {
+            unreachable!()
+        };
+        let (left, right) = match_term!((= l r) = &step.clause[0]).unwrap();
+        let mut time = Duration::ZERO;
+        let before = PolyeqCacheStats::current();
+        assert!(alpha_equiv(left, right, &mut time));
+        let after = PolyeqCacheStats::current().since(before);
+        assert_eq!(PolyeqCacheStats { hits: 0, misses: 1 }, after);
+    }
+
+    #[test]
+    fn test_recheck_step() {
+        let problem = b"
+            (declare-fun p () Bool)
+            (declare-fun z () Real)
+            (assert p)
+            (assert (not p))
+        ";
+        let proof = b"
+            (assume h1 p)
+            (assume h2 (not p))
+            (anchor :step t3 :args ((y Real) (:= (x Real) y)))
+            (step t3.t1 (cl (= x z)) :rule refl)
+            (step t3 (cl) :rule hole)
+            (step t4 (cl) :rule resolution :premises (h1 h1))
+        ";
+        let (problem, proof, mut pool) =
+            crate::parser::parse_instance(&problem[..], &proof[..], crate::parser::Config::new())
+                .unwrap();
+        let mut checker = ProofChecker::new(&mut pool, Config::new());
+        assert!(checker.check(&problem, &proof).is_err());
+
+        // The step is checked in the context of its subproof, so `x` is substituted by `y`
+        let [x, y] = ["x", "y"].map(|name| {
+            let var = Term::new_var(name, checker.pool.add(Term::Sort(Sort::Real)));
+            checker.pool.add(var)
+        });
+        let fixed = checker.pool.add(Term::Op(Operator::Equals, vec![x, y]));
+        assert!(checker.recheck_step(&proof, "t3.t1", |_| ()).is_err());
+        assert!(checker
+            .recheck_step(&proof, "t3.t1", |step| step.clause = vec![fixed])
+            .is_ok());
+
+        assert!(checker.recheck_step(&proof, "t4", |_| ()).is_err());
+        assert!(checker
+            .recheck_step(&proof, "t4", |step| step.premises[1] = (0, 1))
+            .is_ok());
+        assert!(matches!(
+            checker.recheck_step(&proof, "t5", |_| ()),
+            Err(Error::StepNotFound(id)) if id == "t5"
+        ));
+    }
+}
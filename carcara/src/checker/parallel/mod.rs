@@ -1,14 +1,17 @@
 pub mod scheduler;
 
 use super::{
+    anchor_rule_name, check_conclusion, check_premise_scopes,
     error::{CheckerError, SubproofError},
+    rewrite_database,
     rules::{Premise, RuleArgs, RuleResult},
-    Config, ProofChecker,
+    Config, ProofChecker, RuleTable,
 };
-use crate::benchmarking::{CollectResults, OnlineBenchmarkResults};
-use crate::checker::CheckerStatistics;
+use crate::benchmarking::CollectResults;
+use crate::checker::{CheckerStatistics, StatisticsCollector};
 use crate::{
     ast::{pool::advanced::*, *},
+    rewriting::RuleDatabase,
     CarcaraResult, Error,
 };
 use indexmap::IndexSet;
@@ -28,6 +31,8 @@ pub struct ParallelProofChecker<'c> {
     reached_empty_clause: bool,
     is_holey: bool,
     stack_size: usize,
+    rules: Arc<RuleTable>,
+    rewrites: Arc<RuleDatabase>,
 }
 
 impl<'c> ParallelProofChecker<'c> {
@@ -35,9 +40,11 @@ impl<'c> ParallelProofChecker<'c> {
         pool: Arc<PrimitivePool>,
         config: Config,
         prelude: &'c ProblemPrelude,
-        context_usage: &Vec<usize>,
+        context_usage: &[usize],
         stack_size: usize,
     ) -> Self {
+        let rules = Arc::new(RuleTable::new(&config));
+        let rewrites = Arc::new(rewrite_database(&config));
         ParallelProofChecker {
             pool,
             config,
@@ -46,6 +53,8 @@ impl<'c> ParallelProofChecker<'c> {
             reached_empty_clause: false,
             is_holey: false,
             stack_size,
+            rules,
+            rewrites,
         }
     }
 
@@ -59,6 +68,8 @@ impl<'c> ParallelProofChecker<'c> {
             reached_empty_clause: false,
             is_holey: false,
             stack_size: self.stack_size,
+            rules: self.rules.clone(),
+            rewrites: self.rewrites.clone(),
         }
     }
 
@@ -94,7 +105,7 @@ impl<'c> ParallelProofChecker<'c> {
                                 schedule,
                                 local_pool,
                                 should_abort,
-                                None::<&mut CheckerStatistics<OnlineBenchmarkResults>>,
+                                None,
                             )
                         })
                         .unwrap()
@@ -126,11 +137,8 @@ impl<'c> ParallelProofChecker<'c> {
             // If an error happend
             err?;
 
-            if reached {
-                Ok(holey)
-            } else {
-                Err(Error::DoesNotReachEmptyClause)
-            }
+            check_conclusion(&self.config, problem, proof, reached)?;
+            Ok(holey)
         })
     }
 
@@ -157,6 +165,7 @@ impl<'c> ParallelProofChecker<'c> {
                         polyeq_time: Duration::ZERO,
                         assume_time: Duration::ZERO,
                         assume_core_time: Duration::ZERO,
+                        polyeq_cache: Default::default(),
                         results: CR::default(),
                     };
                     // Shares the proof checker between threads
@@ -170,16 +179,19 @@ impl<'c> ParallelProofChecker<'c> {
                         .spawn_scoped(
                             s,
                             move || -> CarcaraResult<(bool, bool, CheckerStatistics<CR>)> {
-                                local_self
-                                    .worker_thread_check(
-                                        problem,
-                                        proof,
-                                        schedule,
-                                        local_pool,
-                                        should_abort,
-                                        Some(&mut local_stats),
-                                    )
-                                    .map(|r| (r.0, r.1, local_stats))
+                                let cache_before = PolyeqCacheStats::current();
+                                let result = local_self.worker_thread_check(
+                                    problem,
+                                    proof,
+                                    schedule,
+                                    local_pool,
+                                    should_abort,
+                                    Some(&mut local_stats),
+                                );
+                                local_stats.polyeq_cache +=
+                                    PolyeqCacheStats::current().since(cache_before);
+                                clear_shared_cache();
+                                result.map(|r| (r.0, r.1, local_stats))
                             },
                         )
                         .unwrap()
@@ -207,6 +219,7 @@ impl<'c> ParallelProofChecker<'c> {
                             stats.polyeq_time += local_stats.polyeq_time;
                             stats.assume_time += local_stats.assume_time;
                             stats.assume_core_time += local_stats.assume_core_time;
+                            stats.polyeq_cache += local_stats.polyeq_cache;
 
                             // Mask the result booleans
                             (reached, holey) = (reached | local_reached, holey | local_holey);
@@ -224,41 +237,29 @@ impl<'c> ParallelProofChecker<'c> {
             // If an error happend
             err?;
 
-            if reached {
-                Ok(holey)
-            } else {
-                Err(Error::DoesNotReachEmptyClause)
-            }
+            check_conclusion(&self.config, problem, proof, reached)?;
+            Ok(holey)
         })
     }
 
-    fn worker_thread_check<CR: CollectResults + Send + Default>(
+    fn worker_thread_check(
         &mut self,
         problem: &Problem,
         proof: &Proof,
         schedule: &Schedule,
         mut pool: LocalPool,
         should_abort: Arc<AtomicBool>,
-        mut stats: Option<&mut CheckerStatistics<CR>>,
+        mut stats: Option<&mut dyn StatisticsCollector>,
     ) -> CarcaraResult<(bool, bool)> {
         use std::sync::atomic::Ordering;
 
         let mut iter = schedule.iter(&proof.commands[..]);
-        let mut last_depth = 0;
 
         while let Some(command) = iter.next() {
-            // If there is any depth difference between the current and last step
-            while (last_depth - iter.depth() as i64 > 0)
-                || (last_depth - iter.depth() as i64 == 0
-                    && matches!(command, ProofCommand::Subproof(_)))
-            {
-                // If this is the last command of a subproof, we have to pop off the subproof
-                // commands of the stack. The parser already ensures that the last command
-                // in a subproof is always a `step` command
+            // Pop the contexts of the subproofs that the schedule left since the last command
+            for _ in 0..iter.num_closed() {
                 self.context.pop();
-                last_depth -= 1;
             }
-            last_depth = iter.depth() as i64;
 
             match command {
                 ProofCommand::Step(step) => {
@@ -290,25 +291,17 @@ impl<'c> ParallelProofChecker<'c> {
                     }
                 }
                 ProofCommand::Subproof(s) => {
-                    let time = Instant::now();
                     let step_id = command.id();
+                    let rule_name = stats.as_ref().map(|_| anchor_rule_name(s));
+                    if let (Some(stats), Some(rule_name)) = (&mut stats, &rule_name) {
+                        stats.step_started(step_id, rule_name);
+                    }
+                    let time = Instant::now();
 
                     self.context.push_with_id(&s.args, s.context_id);
 
-                    if let Some(stats) = &mut stats {
-                        // Collects statistics
-                        let rule_name = match s.commands.last() {
-                            Some(ProofCommand::Step(step)) => {
-                                format!("anchor({})", &step.rule)
-                            }
-                            _ => "anchor".to_owned(),
-                        };
-                        stats.results.add_step_measurement(
-                            stats.file_name,
-                            step_id,
-                            &rule_name,
-                            time.elapsed(),
-                        );
+                    if let (Some(stats), Some(rule_name)) = (&mut stats, &rule_name) {
+                        stats.step_finished(step_id, rule_name, time.elapsed(), Duration::ZERO);
                     }
                 }
                 ProofCommand::Assume { id, term } => {
@@ -329,6 +322,11 @@ impl<'c> ParallelProofChecker<'c> {
             }
         }
 
+        // The schedule may end inside a subproof, so we release the contexts that are still open
+        while !self.context.is_empty() {
+            self.context.pop();
+        }
+
         // Returns Ok(reached empty clause, isHoley)
         if self.reached_empty_clause {
             Ok((true, self.is_holey))
@@ -337,13 +335,13 @@ impl<'c> ParallelProofChecker<'c> {
         }
     }
 
-    fn check_assume<CR: CollectResults + Send + Default>(
+    fn check_assume(
         &mut self,
         id: &str,
         term: &Rc<Term>,
         premises: &IndexSet<Rc<Term>>,
         iter: &ScheduleIter,
-        mut stats: &mut Option<&mut CheckerStatistics<CR>>,
+        stats: &mut Option<&mut dyn StatisticsCollector>,
     ) -> bool {
         let time = Instant::now();
 
@@ -355,10 +353,7 @@ impl<'c> ParallelProofChecker<'c> {
 
         if premises.contains(term) {
             if let Some(s) = stats {
-                let time = time.elapsed();
-                s.assume_time += time;
-                s.results
-                    .add_assume_measurement(s.file_name, id, true, time);
+                s.assume_checked(id, true, time.elapsed(), Duration::ZERO, Duration::ZERO);
             }
             return true;
         }
@@ -380,8 +375,8 @@ impl<'c> ParallelProofChecker<'c> {
 
             polyeq_time += this_polyeq_time;
 
-            if let Some(s) = &mut stats {
-                s.results.add_polyeq_depth(depth);
+            if let Some(s) = stats {
+                s.polyeq_depth(depth);
             }
             if result {
                 core_time = this_polyeq_time;
@@ -395,33 +390,53 @@ impl<'c> ParallelProofChecker<'c> {
         }
 
         if let Some(s) = stats {
-            let time = time.elapsed();
-            s.assume_time += time;
-            s.assume_core_time += core_time;
-            s.polyeq_time += polyeq_time;
-            s.results
-                .add_assume_measurement(s.file_name, id, false, time);
+            s.assume_checked(id, false, time.elapsed(), core_time, polyeq_time);
         }
 
         true
     }
 
-    fn check_step<CR: CollectResults + Send + Default>(
+    fn check_step(
         &mut self,
         step: &ProofStep,
         previous_command: Option<Premise>,
         iter: &ScheduleIter,
         pool: &mut LocalPool,
-        stats: &mut Option<&mut CheckerStatistics<CR>>,
+        stats: &mut Option<&mut dyn StatisticsCollector>,
     ) -> RuleResult {
+        if let Some(s) = stats {
+            s.step_started(&step.id, &step.rule);
+        }
         let time = Instant::now();
+        let pool_size = pool.num_terms();
         let mut polyeq_time = Duration::ZERO;
 
+        // Like in the sequential checker, steps whose rule is ignored are also reported as finished
+        self.check_step_rule(step, previous_command, iter, pool, &mut polyeq_time)?;
+
+        if let Some(s) = stats {
+            s.step_finished(&step.id, &step.rule, time.elapsed(), polyeq_time);
+            let new_size = pool.num_terms();
+            if new_size > pool_size {
+                s.pool_grew(new_size);
+            }
+        }
+        Ok(())
+    }
+
+    fn check_step_rule(
+        &mut self,
+        step: &ProofStep,
+        previous_command: Option<Premise>,
+        iter: &ScheduleIter,
+        pool: &mut LocalPool,
+        polyeq_time: &mut Duration,
+    ) -> RuleResult {
         if !step.discharge.is_empty() && step.rule != "subproof" {
             return Err(CheckerError::Subproof(SubproofError::DischargeInWrongRule));
         }
 
-        let rule = match ProofChecker::get_rule(&step.rule, self.config.elaborated) {
+        let rule = match self.rules.get(&step.rule) {
             Some(r) => r,
             None if self.config.ignore_unknown_rules => {
                 self.is_holey = true;
@@ -430,10 +445,16 @@ impl<'c> ParallelProofChecker<'c> {
             None => return Err(CheckerError::UnknownRule),
         };
 
-        if step.rule == "hole" || step.rule == "lia_generic" {
+        if step.rule == "hole" || (step.rule == "lia_generic" && !self.config.native_lia_generic) {
             self.is_holey = true;
         }
 
+        check_premise_scopes(
+            step,
+            iter.depth(),
+            |p| iter.is_in_scope(p),
+            |p| iter.get_premise(p).id().to_owned(),
+        )?;
         let premises: Vec<_> = step
             .premises
             .iter()
@@ -452,26 +473,120 @@ impl<'c> ParallelProofChecker<'c> {
             conclusion: &step.clause,
             premises: &premises,
             args: &step.args,
+            arg_vars: &step.arg_vars,
             pool,
             context: &mut self.context,
             previous_command,
             discharge: &discharge,
-            polyeq_time: &mut polyeq_time,
+            polyeq_time,
+            term_limits: self.config.term_limits,
+            datatypes: &self.prelude.datatypes,
+            rewrites: &self.rewrites,
+            certificate_dir: self.config.certificate_dir.as_deref(),
         };
 
-        rule(rule_args)?;
+        match rule(rule_args) {
+            // Some rules, like `rare_rewrite`, may only find out that the step uses an unknown
+            // rule when checking it
+            Err(CheckerError::UnknownRule)
+                if self.config.ignore_unknown_rules
+                    || self.config.allowed_rules.contains(&step.rule) =>
+            {
+                self.is_holey = true;
+                return Ok(());
+            }
+            result => result?,
+        }
 
         if iter.is_end_step() {
             let subproof = iter.current_subproof().unwrap();
             ProofChecker::check_discharge(subproof, iter.depth(), &step.discharge)?;
         }
+        Ok(())
+    }
+}
 
-        if let Some(s) = stats {
-            let time = time.elapsed();
-            s.results
-                .add_step_measurement(s.file_name, &step.id, &step.rule, time);
-            s.polyeq_time += polyeq_time;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[derive(Default)]
+    struct EventLog {
+        started: Vec<String>,
+        finished: Vec<String>,
+        pool_grew: usize,
+    }
+
+    impl StatisticsCollector for EventLog {
+        fn step_started(&mut self, id: &str, rule: &str) {
+            self.started.push(format!("{id}:{rule}"));
         }
-        Ok(())
+
+        fn step_finished(&mut self, id: &str, rule: &str, _: Duration, _: Duration) {
+            self.finished.push(format!("{id}:{rule}"));
+        }
+
+        fn pool_grew(&mut self, _: usize) {
+            self.pool_grew += 1;
+        }
+    }
+
+    #[test]
+    fn test_collector_events() {
+        let problem = "
+            (declare-const a Bool)
+            (declare-const b Bool)
+            (assert (not a))
+        ";
+        let proof = "
+            (assume h1 (not a))
+            (anchor :step t1 :args ((y Int) (:= (x Int) y)))
+            (step t1.t1 (cl (= x y)) :rule refl)
+            (step t1.t2 (cl (= (> x 0) (> y 0))) :rule cong :premises (t1.t1))
+            (step t1 (cl (= (forall ((x Int)) (> x 0)) (forall ((y Int)) (> y 0)))) :rule bind)
+            (step t2 (cl (= (not (not (ite true b a))) b)) :rule all_simplify)
+            (step t3 (cl a) :rule hole)
+            (step t4 (cl b) :rule unknown_rule)
+            (step t5 (cl) :rule resolution :premises (h1 t3))
+        ";
+        let parse = || {
+            parser::parse_instance(problem.as_bytes(), proof.as_bytes(), parser::Config::new())
+                .unwrap()
+        };
+
+        let (problem, proof, mut pool) = parse();
+        let mut sequential = EventLog::default();
+        ProofChecker::new(&mut pool, Config::new().ignore_unknown_rules(true))
+            .check_with_collector(&problem, &proof, &mut sequential)
+            .unwrap();
+
+        let (problem, proof, pool) = parse();
+        let pool = Arc::new(pool);
+        let (scheduler, context_usage) = Scheduler::new(1, &proof);
+        let mut checker = ParallelProofChecker::new(
+            pool.clone(),
+            Config::new().ignore_unknown_rules(true),
+            &problem.prelude,
+            &context_usage,
+            8 * 1024 * 1024,
+        );
+        let mut parallel = EventLog::default();
+        checker
+            .worker_thread_check(
+                &problem,
+                &proof,
+                &scheduler.loads[0],
+                LocalPool::from_previous(&ContextPool::from_global(&pool)),
+                Arc::new(AtomicBool::new(false)),
+                Some(&mut parallel),
+            )
+            .unwrap();
+
+        assert_eq!(sequential.started, sequential.finished);
+        assert_eq!(sequential.started, parallel.started);
+        assert_eq!(sequential.finished, parallel.finished);
+        assert!(sequential.pool_grew > 0);
+        assert_eq!(sequential.pool_grew, parallel.pool_grew);
     }
 }
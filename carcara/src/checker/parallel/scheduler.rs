@@ -1,18 +1,27 @@
 use crate::ast::{Proof, ProofCommand};
-use std::{
-    cmp::Ordering,
-    collections::{BinaryHeap, HashSet},
-};
+use std::{cmp::Ordering, collections::BinaryHeap};
 
-/// Struct responsible for storing a thread work schedule.
+/// A maximal run of commands assigned to the same schedule that are all in the same subproof.
 ///
-/// Here, each step from the original proof is represented as a tuple:
-/// (depth, subproof index). The first element is the subproof nesting `depth`
-/// (in the subproof stack) and `subproof index` is the index where this step is
-/// located in the subproof vector.
-#[derive(Clone, Default)]
+/// Instead of recording when subproofs are opened and closed, each level records the full path to
+/// the subproof that contains its commands. When moving from one level to the next, the iterator
+/// closes and opens only the subproofs in which the two paths differ. This means a schedule may
+/// leave a subproof and later enter it again, if it is assigned more commands from it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScheduleLevel {
+    /// The path from the root proof to the subproof that contains the commands. Each element is the
+    /// index of a subproof command in the enclosing subproof, so this is empty for the root proof.
+    pub path: Vec<usize>,
+
+    /// The indices of the commands in the subproof, in the order they should be checked. Subproof
+    /// commands are never included, since they are entered implicitly by a later level.
+    pub commands: Vec<usize>,
+}
+
+/// Struct responsible for storing a thread work schedule, as a sequence of [`ScheduleLevel`]s.
+#[derive(Debug, Clone, Default)]
 pub struct Schedule {
-    steps: Vec<(usize, usize)>,
+    levels: Vec<ScheduleLevel>,
 }
 
 impl Schedule {
@@ -20,24 +29,31 @@ impl Schedule {
         Self::default()
     }
 
-    /// Inserts a new step into the end of the schedule steps vector
-    pub fn push(&mut self, cmd: (usize, usize)) {
-        self.steps.push(cmd);
+    /// Inserts a new command at the end of the schedule, given the path to the subproof that
+    /// contains it and its index in that subproof.
+    pub fn push(&mut self, path: &[usize], index: usize) {
+        match self.levels.last_mut() {
+            Some(level) if level.path == path => level.commands.push(index),
+            _ => self.levels.push(ScheduleLevel {
+                path: path.to_vec(),
+                commands: vec![index],
+            }),
+        }
     }
 
-    /// Removes the last step from the end of the steps vector
-    pub fn pop(&mut self) {
-        self.steps.pop();
+    /// Returns the levels of the schedule.
+    pub fn levels(&self) -> &[ScheduleLevel] {
+        &self.levels
     }
 
-    /// Returns the last schedule step
-    pub fn last(&self) -> Option<&(usize, usize)> {
-        self.steps.last()
+    /// Returns `true` if no commands were assigned to this schedule.
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
     }
 
     /// Returns an iterator over the proof commands. See [`ScheduleIter`].
     pub fn iter<'a>(&'a self, proof: &'a [ProofCommand]) -> ScheduleIter {
-        ScheduleIter::new(proof, &self.steps)
+        ScheduleIter::new(proof, &self.levels)
     }
 }
 
@@ -67,25 +83,9 @@ impl PartialEq for AssignedLoad {
     }
 }
 
-/// Represents a level in the proof stack. It holds the subproof itself,
-/// its prerequisite step (anchor) and which schedules used any step inside
-/// this layer
-struct StackLevel<'a> {
-    id: usize,
-    cmds: &'a [ProofCommand],
-    pre_req: Option<(usize, usize)>,
-    used_by: HashSet<usize>,
-}
-
-impl<'a> StackLevel<'a> {
-    pub fn new(id: usize, cmds: &'a [ProofCommand], pre_req: Option<(usize, usize)>) -> Self {
-        Self {
-            id,
-            cmds,
-            pre_req,
-            used_by: HashSet::new(),
-        }
-    }
+/// Returns the length of the longest common prefix of two paths.
+fn common_prefix(a: &[usize], b: &[usize]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
 }
 
 /// Struct that stores the schedules for each thread.
@@ -100,124 +100,99 @@ impl Scheduler {
     /// amount of async overhead.
     ///
     /// Returns a scheduler itself and context usage info (a vector holding
-    /// how many threads are going to use each of the contexts. This vector maps
-    /// the contexts based in the subproof hashing value (i.e. `subproof_id`)
-    /// created in the parser).
+    /// how many times each context is entered by all schedules combined. This
+    /// vector maps the contexts based in the subproof hashing value (i.e.
+    /// `subproof_id`) created in the parser). These counters are what allows
+    /// a context to be dropped once the last schedule that needs it leaves it.
     pub fn new(num_workers: usize, proof: &Proof) -> (Self, Vec<usize>) {
-        // Initializes the control and result variables
-        let cmds = &proof.commands;
         let mut loads = vec![Schedule::new(); num_workers];
-        let mut stack = vec![StackLevel::new(0, cmds, None)];
         let mut pq = BinaryHeap::<AssignedLoad>::new();
-        let mut context_usage = vec![];
         for i in 0..num_workers {
             pq.push(AssignedLoad(0, i));
         }
 
-        loop {
-            // Pop the finished subproofs
-            while !stack.is_empty() && {
-                let top = stack.last().unwrap();
-                top.id == top.cmds.len()
-            } {
-                for schedule_id in &stack.last().unwrap().used_by {
-                    let last = loads[*schedule_id].last().unwrap();
-                    // If it's an useless context insertion
-                    if last.0 < stack.len()
-                        && matches!(stack[last.0].cmds[last.1], ProofCommand::Subproof(_))
-                    {
-                        // Make sure this context usage count is reduced
-                        let subproof_id = match &stack[last.0].cmds[last.1] {
-                            ProofCommand::Subproof(s) => s.context_id,
-                            _ => unreachable!(),
-                        };
-                        context_usage[subproof_id] -= 1;
-
-                        loads[*schedule_id].pop();
-                    }
-                    // Creates a closing step for each schedule that used this subproof
-                    else {
-                        loads[*schedule_id].push((stack.len() - 1, usize::MAX));
-                    }
-                }
+        // Each stack level holds the commands of an open subproof and the index of the next
+        // command to be assigned. The path to the innermost subproof is kept alongside it
+        let mut stack = vec![(proof.commands.as_slice(), 0)];
+        let mut path = Vec::new();
+        while let Some((cmds, i)) = stack.last_mut() {
+            let Some(command) = cmds.get(*i) else {
                 stack.pop();
-            }
-            if stack.is_empty() {
-                break;
-            }
-            //
-            let AssignedLoad(mut load, load_index) = pq.pop().unwrap();
-            {
-                let top = stack.last().unwrap();
-                let step_weight = get_step_weight(&top.cmds[top.id]);
-                load = load
-                    .checked_add(step_weight)
-                    .expect("Weight balancing overflow!");
-                pq.push(AssignedLoad(load, load_index));
-            }
-
-            let depth = stack.len() - 1;
-            let mut i = 1;
-            let initial_layer = {
-                let tmp = loads[load_index].last().unwrap_or(&(0, 0));
-                if tmp.1 == usize::MAX {
-                    tmp.0 - 1
-                } else {
-                    tmp.0
-                }
+                path.pop();
+                continue;
             };
-            // If this step needs the context of the subproof oppening step
-            // but it was not assigned to this schedule yet
-            while initial_layer + i <= depth {
-                let subproof_oppening = stack[initial_layer + i].pre_req.unwrap();
-                let last_inserted = *loads[load_index].last().unwrap_or(&(usize::MAX, 0));
-
-                if last_inserted != subproof_oppening {
-                    loads[load_index].push(subproof_oppening);
-                    stack[subproof_oppening.0].used_by.insert(load_index);
-
-                    // Now this subproof is used by another schedule
-                    let subproof_id = match &stack[subproof_oppening.0].cmds[subproof_oppening.1] {
-                        ProofCommand::Subproof(s) => s.context_id,
-                        _ => unreachable!(),
-                    };
-                    context_usage[subproof_id] += 1;
-                }
-                i += 1;
+            let index = *i;
+            *i += 1;
+
+            // Subproofs are not assigned to any schedule. Instead, a schedule enters a subproof
+            // when it is assigned one of its commands
+            if let ProofCommand::Subproof(s) = command {
+                stack.push((&s.commands, 0));
+                path.push(index);
+                continue;
             }
 
-            let top = stack.last_mut().unwrap();
-            // Assign a step to some Schedule
-            loads[load_index].push((depth, top.id));
-            top.used_by.insert(load_index);
-
-            // Go to next step
-            let last_id = top.id;
-            top.id += 1;
-            if let ProofCommand::Subproof(s) = &top.cmds[last_id] {
-                stack.push(StackLevel::new(0, &s.commands, Some((depth, last_id))));
-                stack.last_mut().unwrap().used_by.insert(load_index);
-                // First schedule using this subproof
-                context_usage.push(1);
-            }
+            let AssignedLoad(load, load_index) = pq.pop().unwrap();
+            let load = load
+                .checked_add(get_step_weight(command))
+                .expect("Weight balancing overflow!");
+            pq.push(AssignedLoad(load, load_index));
+            loads[load_index].push(&path, index);
         }
+
+        let context_usage = count_context_usage(&proof.commands, &loads);
         (Scheduler { loads }, context_usage)
     }
 }
 
+/// Counts how many times each subproof context is entered by all schedules combined, indexed by
+/// the subproof's `context_id`.
+fn count_context_usage(commands: &[ProofCommand], loads: &[Schedule]) -> Vec<usize> {
+    let mut usage = Vec::new();
+    for schedule in loads {
+        let mut current: &[usize] = &[];
+        for level in schedule.levels() {
+            let start = common_prefix(current, &level.path);
+            let mut cmds = commands;
+            for (depth, &i) in level.path.iter().enumerate() {
+                let ProofCommand::Subproof(s) = &cmds[i] else {
+                    unreachable!()
+                };
+                if depth >= start {
+                    if usage.len() <= s.context_id {
+                        usage.resize(s.context_id + 1, 0);
+                    }
+                    usage[s.context_id] += 1;
+                }
+                cmds = &s.commands;
+            }
+            current = &level.path;
+        }
+    }
+    usage
+}
+
 /// Iterates through schedule steps
 pub struct ScheduleIter<'a> {
     proof_stack: Vec<&'a [ProofCommand]>,
-    steps: &'a Vec<(usize, usize)>,
-    step_id: usize,
+    path: Vec<usize>,
+    levels: &'a [ScheduleLevel],
+    level: usize,
+    position: usize,
+    last_index: Option<usize>,
+    num_closed: usize,
 }
 
 impl<'a> ScheduleIter<'a> {
-    pub fn new(proof_commands: &'a [ProofCommand], steps: &'a Vec<(usize, usize)>) -> Self {
+    pub fn new(proof_commands: &'a [ProofCommand], levels: &'a [ScheduleLevel]) -> Self {
         Self {
             proof_stack: vec![proof_commands],
-            steps,
-            step_id: 0,
+            path: Vec::new(),
+            levels,
+            level: 0,
+            position: 0,
+            last_index: None,
+            num_closed: 0,
         }
     }
 
@@ -243,8 +218,13 @@ impl<'a> ScheduleIter<'a> {
     /// Returns `true` if the most recently returned step is the last step of
     /// the current subproof.
     pub fn is_end_step(&self) -> bool {
-        self.is_in_subproof()
-            && self.steps[self.step_id - 1].1 == self.proof_stack.last().unwrap().len() - 1
+        self.is_in_subproof() && self.last_index == Some(self.proof_stack.last().unwrap().len() - 1)
+    }
+
+    /// Returns how many subproofs were closed between the previous command and the most recently
+    /// returned one.
+    pub fn num_closed(&self) -> usize {
+        self.num_closed
     }
 
     /// Returns the command referenced by a premise index of the form (depth, index in subproof).
@@ -252,36 +232,61 @@ impl<'a> ScheduleIter<'a> {
     pub fn get_premise(&self, (depth, index): (usize, usize)) -> &ProofCommand {
         &self.proof_stack[depth][index]
     }
+
+    /// Returns `true` if the premise index of the form (depth, index in subproof) refers to a command
+    /// that can be referenced by the most recently returned command. See
+    /// [`ProofIter::is_in_scope`].
+    pub fn is_in_scope(&self, (depth, index): (usize, usize)) -> bool {
+        let current = if depth < self.depth() {
+            self.path.get(depth).copied()
+        } else if depth == self.depth() {
+            self.last_index
+        } else {
+            None
+        };
+        current.is_some_and(|i| index < i)
+    }
 }
 
 impl<'a> Iterator for ScheduleIter<'a> {
     type Item = &'a ProofCommand;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // If it is the end of the steps
-        if self.step_id >= self.steps.len() {
-            return None;
+        self.num_closed = 0;
+
+        // Skip to the next level if the current one is finished
+        while self.levels.get(self.level)?.commands.len() == self.position {
+            self.level += 1;
+            self.position = 0;
         }
+        let level = &self.levels[self.level];
 
-        // If current step is an closing subproof step
-        while let (_, usize::MAX) = self.steps[self.step_id] {
+        // Close the subproofs that are not shared with the path of the current level
+        let common = common_prefix(&self.path, &level.path);
+        while self.path.len() > common {
+            self.path.pop();
             self.proof_stack.pop();
-            self.step_id += 1;
-            // If reached the last closing step of the whole proof
-            if self.step_id == self.steps.len() {
-                return None;
-            }
+            self.num_closed += 1;
         }
-        let cur_step = self.steps[self.step_id];
-        self.step_id += 1;
 
         let top = self.proof_stack.last().unwrap();
-        let command = &top[cur_step.1];
-        // Opens a new subproof
-        if let ProofCommand::Subproof(subproof) = command {
+
+        // Opens the next subproof in the path of the current level, if there is one
+        if let Some(&index) = level.path.get(self.path.len()) {
+            let command = &top[index];
+            let ProofCommand::Subproof(subproof) = command else {
+                unreachable!()
+            };
             self.proof_stack.push(&subproof.commands);
+            self.path.push(index);
+            self.last_index = None;
+            return Some(command);
         }
-        Some(command)
+
+        let index = level.commands[self.position];
+        self.position += 1;
+        self.last_index = Some(index);
+        Some(&top[index])
     }
 }
 
@@ -339,6 +344,7 @@ pub fn get_step_weight(step: &ProofCommand) -> u64 {
                 "la_disequality" => 919,
                 "la_totality" => 0, //-1
                 "la_tautology" => 4291,
+                "la_trichotomy" => 0, //-1
                 "forall_inst" => 7877,
                 "qnt_join" => 2347,
                 "qnt_rm_unused" => 3659,
@@ -413,3 +419,105 @@ pub fn get_step_weight(step: &ProofCommand) -> u64 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{checker, parser};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_scheduler() {
+        let problem: &[u8] = b"
+            (declare-const a Bool)
+            (assert (not a))
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (not a))
+            (anchor :step t1 :args ((y Int) (:= (x Int) y)))
+            (step t1.t1 (cl (= x y)) :rule refl)
+            (anchor :step t1.t2 :args ((w Int) (:= (z Int) w)))
+            (step t1.t2.t1 (cl (= z w)) :rule refl)
+            (step t1.t2.t2 (cl (= (> z x) (> w y))) :rule cong :premises (t1.t2.t1 t1.t1))
+            (step t1.t2 (cl (= (forall ((z Int)) (> z x)) (forall ((w Int)) (> w y))))
+                :rule bind)
+            (step t1 (cl (= (forall ((x Int)) (forall ((z Int)) (> z x)))
+                            (forall ((y Int)) (forall ((w Int)) (> w y)))))
+                :rule bind)
+            (step t2 (cl a) :rule hole)
+            (step t3 (cl) :rule resolution :premises (h1 t2))
+        ";
+        let (problem, proof, pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let pool = Arc::new(pool);
+
+        for num_workers in 1..=4 {
+            let (scheduler, context_usage) = Scheduler::new(num_workers, &proof);
+
+            // Every command that is not a subproof must be assigned to exactly one schedule
+            let mut ids: Vec<_> = scheduler
+                .loads
+                .iter()
+                .flat_map(|s| s.iter(&proof.commands))
+                .filter(|c| !c.is_subproof())
+                .map(ProofCommand::id)
+                .collect();
+            ids.sort_unstable();
+            let expected = [
+                "h1", "t1", "t1.t1", "t1.t2", "t1.t2.t1", "t1.t2.t2", "t2", "t3",
+            ];
+            assert_eq!(ids, expected);
+
+            // Each subproof is entered at least once, by some schedule
+            assert_eq!(context_usage.len(), 2);
+            assert!(context_usage.iter().all(|&n| n >= 1));
+
+            let mut checker = checker::ParallelProofChecker::new(
+                pool.clone(),
+                checker::Config::new(),
+                &problem.prelude,
+                &context_usage,
+                8 * 1024 * 1024,
+            );
+            assert!(checker.check(&problem, &proof, &scheduler).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_schedule_iter_reentry() {
+        let proof: &[u8] = b"
+            (anchor :step t1)
+            (step t1.t1 (cl) :rule hole)
+            (anchor :step t1.t2)
+            (step t1.t2.t1 (cl) :rule hole)
+            (step t1.t2 (cl) :rule hole)
+            (step t1 (cl) :rule hole)
+            (step t2 (cl) :rule hole)
+        ";
+        let (_, proof, _) = parser::parse_instance(&b""[..], proof, parser::Config::new()).unwrap();
+
+        // A schedule that leaves the subproof `t1` and enters it again later
+        let mut schedule = Schedule::new();
+        schedule.push(&[0], 0);
+        schedule.push(&[], 1);
+        schedule.push(&[0, 1], 0);
+        schedule.push(&[0], 2);
+
+        let mut iter = schedule.iter(&proof.commands);
+        let mut visited = Vec::new();
+        while let Some(command) = iter.next() {
+            visited.push((command.id(), iter.depth(), iter.num_closed()));
+        }
+        let expected = [
+            ("t1", 1, 0),
+            ("t1.t1", 1, 0),
+            ("t2", 0, 1),
+            ("t1", 1, 0),
+            ("t1.t2", 2, 0),
+            ("t1.t2.t1", 2, 0),
+            ("t1", 1, 1),
+        ];
+        assert_eq!(visited, expected);
+        assert!(iter.is_end_step());
+    }
+}
@@ -0,0 +1,145 @@
+use super::{
+    assert_clause_len, assert_num_args, rare::check_rare_rewrite, CheckerError, RuleArgs,
+    RuleResult,
+};
+use crate::{ast::*, checker::trace::trace_value, rewriting::Rewriter};
+
+/// Checks that the equality in `conclusion` can be proved by a sequence of rewrites, tracing the
+/// sequence found.
+fn check_rewrite(
+    rewriter: &Rewriter,
+    conclusion: &[Rc<Term>],
+    pool: &mut dyn TermPool,
+) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let (left, right) = match_term_err!((= l r) = &conclusion[0])?;
+    let sequence = rewriter
+        .find_sequence(pool, left, right)
+        .ok_or_else(|| CheckerError::RewriteSequenceNotFound(left.clone(), right.clone()))?;
+
+    trace_value(|| {
+        let steps: Vec<_> = sequence
+            .iter()
+            .map(|s| format!("{}: {} -> {}", s.rule, s.redex, s.contractum))
+            .collect();
+        format!("rewrite sequence: {}", steps.join(", "))
+    });
+    Ok(())
+}
+
+pub fn all_simplify(RuleArgs { conclusion, pool, rewrites, .. }: RuleArgs) -> RuleResult {
+    check_rewrite(&Rewriter::new(rewrites), conclusion, pool)
+}
+
+/// Checks a `rare_rewrite` step, whose first argument is the name of the RARE rewrite rule used.
+/// The step is checked by instantiating the rule with that name with the remaining arguments. If the
+/// user didn't load a RARE rule with that name, the step is considered to use an unknown rule.
+pub fn rare_rewrite(
+    RuleArgs {
+        conclusion, args, pool, rewrites, ..
+    }: RuleArgs,
+) -> RuleResult {
+    assert_num_args(args, 1..)?;
+
+    let Term::Const(Constant::String(name)) = args[0].as_ref() else {
+        return Err(CheckerError::TermOfWrongForm(
+            "\"<rule name>\"",
+            args[0].clone(),
+        ));
+    };
+    match rewrites.get_rare(name) {
+        Some(rule) => check_rare_rewrite(rule, conclusion, args, pool),
+        None => Err(CheckerError::UnknownRule),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn all_simplify() {
+        test_cases! {
+            definitions = "
+                (declare-fun p () Bool)
+                (declare-fun q () Bool)
+                (declare-fun r () Bool)
+                (declare-fun a () Int)
+                (declare-fun b () Int)
+            ",
+            "Single rewrite" {
+                "(step t1 (cl (= (ite true p q) p)) :rule all_simplify)": true,
+                "(step t1 (cl (= (not (not p)) p)) :rule all_simplify)": true,
+                "(step t1 (cl (= (ite true p q) q)) :rule all_simplify)": false,
+            }
+            "Rewrites in subterms" {
+                "(step t1 (cl (= (and r (ite false q p)) (and r p))) :rule all_simplify)": true,
+                "(step t1 (cl (= (or (not (not p)) (= a a)) (or p true))) :rule all_simplify)": true,
+                "(step t1 (cl (= (or (not (not p)) q) (or p r))) :rule all_simplify)": false,
+            }
+            "Multiple rewrites" {
+                "(step t1 (cl (= (ite (not p) false true) p)) :rule all_simplify)": true,
+                "(step t1 (cl (= (=> (not (not p)) (ite true q r)) (=> p q))) :rule all_simplify)": true,
+            }
+            "Rules are only applied from left to right" {
+                "(step t1 (cl (= p (ite true p q))) :rule all_simplify)": false,
+                "(step t1 (cl (= (and (not (not p)) q) (and p (ite false r q)))) :rule all_simplify)": false,
+            }
+            "Constant evaluation" {
+                "(step t1 (cl (= (+ 1 2) 3)) :rule all_simplify)": true,
+                "(step t1 (cl (= (- 2 5) (- 3))) :rule all_simplify)": true,
+                "(step t1 (cl (= (= b (* 2 3)) (= b 6))) :rule all_simplify)": true,
+                "(step t1 (cl (= (ite (< 1 2) a b) a)) :rule all_simplify)": true,
+                "(step t1 (cl (= (+ 1 2) 4)) :rule all_simplify)": false,
+            }
+            "Conversions" {
+                "(step t1 (cl (= (+ (bv2nat #b101) 1) 6)) :rule all_simplify)": true,
+                "(step t1 (cl (= (sbv_to_int #b110) (- 2))) :rule all_simplify)": true,
+                "(step t1 (cl (= ((_ int2bv 3) (+ 4 5)) #b001)) :rule all_simplify)": true,
+                "(step t1 (cl (= (bv2nat ((_ int2bv 2) a)) a)) :rule all_simplify)": false,
+            }
+            "Sequences" {
+                "(step t1 (cl (= (seq.len (seq.++ (seq.unit 1) (seq.unit 2))) 2)) :rule all_simplify)": true,
+                "(step t1 (cl (= (seq.nth (seq.rev (seq.++ (seq.unit 1) (seq.unit 2))) 0) 2))
+                    :rule all_simplify)": true,
+                "(step t1 (cl (= (seq.extract (seq.unit 1) 1 1) (as seq.empty (Seq Int))))
+                    :rule all_simplify)": true,
+                "(step t1 (cl (= (seq.contains (seq.++ (seq.unit 1) (seq.unit 2)) (seq.unit 3)) false))
+                    :rule all_simplify)": true,
+                "(step t1 (cl (= (seq.len (seq.unit a)) 1)) :rule all_simplify)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn rare_rewrite() {
+        test_cases! {
+            definitions = "
+                (declare-fun p () Bool)
+                (declare-fun q () Bool)
+            ",
+            "Unknown rules" {
+                r#"(step t1 (cl (= (not (not p)) p)) :rule rare_rewrite :args ("not_simplify"))"#: false,
+                r#"(step t1 (cl (= (not (not p)) p))
+                    :rule rare_rewrite :args ("bool-double-not-elim"))"#: false,
+                "(step t1 (cl (= (not (not p)) p)) :rule rare_rewrite)": false,
+            }
+        }
+
+        // Allowed rules are only considered holes if the step uses an unknown rule, not if it fails
+        test_cases! {
+            definitions = "
+                (declare-fun p () Bool)
+                (declare-fun q () Bool)
+            ",
+            config = crate::checker::Config {
+                allowed_rules: ["all_simplify".to_owned(), "rare_rewrite".to_owned()].into(),
+                ..crate::checker::Config::new()
+            },
+            "Allowed rules" {
+                r#"(step t1 (cl (= (not (not p)) p))
+                    :rule rare_rewrite :args ("bool-double-not-elim"))"#: true,
+                "(step t1 (cl (= (not (not p)) q)) :rule all_simplify)": false,
+            }
+        }
+    }
+}
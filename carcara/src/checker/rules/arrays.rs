@@ -0,0 +1,94 @@
+use super::{assert_clause_len, assert_eq, assert_is_expected, RuleArgs, RuleResult};
+use crate::{ast::*, checker::error::CheckerError};
+
+/// Checks an instance of the array extensionality lemma:
+/// `(cl (= a b) (not (= (select a k) (select b k))))`, where `k` is the index at which the two
+/// arrays differ, if they are different. This index must be given as the choice term
+/// `(choice ((i I)) (not (= (select a i) (select b i))))`.
+pub fn ext(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 2)?;
+
+    let (a, b) = match_term_err!((= a b) = &conclusion[0])?;
+    let ((a_select, k), (b_select, k_b)) =
+        match_term_err!((not (= (select a_s k) (select b_s k_b))) = &conclusion[1])?;
+    assert_eq(a, a_select)?;
+    assert_eq(b, b_select)?;
+    assert_eq(k, k_b)?;
+
+    let Sort::Array(index_sort, _) = pool.sort(a).as_sort().cloned().unwrap() else {
+        return Err(CheckerError::TermOfWrongForm("an array", a.clone()));
+    };
+    let (bindings, inner) = match k.as_ref() {
+        Term::Binder(Binder::Choice, bindings, inner) if bindings.len() == 1 => (bindings, inner),
+        _ => return Err(CheckerError::TermOfWrongForm("a choice term", k.clone())),
+    };
+    let (name, sort) = &bindings[0];
+    assert_eq(sort, &index_sort)?;
+
+    let i = pool.add(Term::new_var(name, sort.clone()));
+    // Otherwise, the bound variable would capture a free variable with the same name and sort
+    for t in [a, b] {
+        if pool.free_vars(t).contains(&i) {
+            return Err(CheckerError::BoundVarOccursFree(name.clone(), t.clone()));
+        }
+    }
+    let expected =
+        build_term!(pool, (not (= (select {a.clone()} {i.clone()}) (select {b.clone()} {i}))));
+    assert_is_expected(inner, expected)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn ext() {
+        test_cases! {
+            definitions = "
+                (declare-const a (Array Int Real))
+                (declare-const b (Array Int Real))
+                (declare-const c (Array Int Real))
+                (declare-const j Int)
+            ",
+            "Simple working examples" {
+                "(step t1 (cl (= a b) (not (= (select a
+                    (choice ((i Int)) (not (= (select a i) (select b i)))))
+                    (select b (choice ((i Int)) (not (= (select a i) (select b i))))))))
+                :rule ext)": true,
+
+                "(step t1 (cl (= a ((as const (Array Int Real)) 0.0)) (not (= (select a
+                    (choice ((k Int)) (not (= (select a k) (select ((as const (Array Int Real)) 0.0) k)))))
+                    (select ((as const (Array Int Real)) 0.0)
+                        (choice ((k Int)) (not (= (select a k) (select ((as const (Array Int Real)) 0.0) k))))))))
+                :rule ext)": true,
+            }
+            "Index is not the difference witness" {
+                "(step t1 (cl (= a b) (not (= (select a j) (select b j)))) :rule ext)": false,
+
+                "(step t1 (cl (= a b) (not (= (select a
+                    (choice ((i Int)) (not (= (select a i) (select c i)))))
+                    (select b (choice ((i Int)) (not (= (select a i) (select c i))))))))
+                :rule ext)": false,
+
+                "(step t1 (cl (= a b) (not (= (select a
+                    (choice ((i Int)) (not (= (select b i) (select a i)))))
+                    (select b (choice ((i Int)) (not (= (select b i) (select a i))))))))
+                :rule ext)": false,
+            }
+            "Terms don't match" {
+                "(step t1 (cl (= a b) (not (= (select b
+                    (choice ((i Int)) (not (= (select a i) (select b i)))))
+                    (select a (choice ((i Int)) (not (= (select a i) (select b i))))))))
+                :rule ext)": false,
+
+                "(step t1 (cl (= a b) (not (= (select a
+                    (choice ((i Int)) (not (= (select a i) (select b i)))))
+                    (select b j)))) :rule ext)": false,
+            }
+            "Bound variable captures a free variable" {
+                "(step t1 (cl (= (store a j 1.0) b) (not (= (select (store a j 1.0)
+                    (choice ((j Int)) (not (= (select (store a j 1.0) j) (select b j)))))
+                    (select b (choice ((j Int)) (not (= (select (store a j 1.0) j) (select b j))))))))
+                :rule ext)": false,
+            }
+        }
+    }
+}
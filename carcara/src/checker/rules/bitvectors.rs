@@ -1,9 +1,10 @@
 use crate::{
-    ast::{pool::TermPool, Operator, ParamOperator, Rc, Sort, Term},
-    checker::rules::assert_clause_len,
+    ast::{pool::TermPool, Constant, Operator, ParamOperator, Rc, Sort, Term, TermLimits},
+    checker::{error::CheckerError, rules::assert_clause_len},
 };
+use rug::Integer;
 
-use super::{assert_eq, RuleArgs, RuleResult};
+use super::{assert_eq, assert_operation_len, RuleArgs, RuleResult};
 
 fn build_term_vec(term: &Rc<Term>, size: usize, pool: &mut dyn TermPool) -> Vec<Rc<Term>> {
     let term = if let Some((Operator::BvBbTerm, args_x)) = term.as_op() {
@@ -23,15 +24,185 @@ fn build_term_vec(term: &Rc<Term>, size: usize, pool: &mut dyn TermPool) -> Vec<
     term
 }
 
-pub fn ult(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
+/// Returns the width of a term of sort `BitVec`, or an error if the term is not a bitvector.
+fn bv_width(term: &Rc<Term>, pool: &mut dyn TermPool) -> Result<usize, CheckerError> {
+    match pool.sort(term).as_sort() {
+        Some(Sort::BitVec(width)) => width_to_usize(width, term),
+        _ => Err(CheckerError::TermOfWrongForm(
+            "a bitvector term",
+            term.clone(),
+        )),
+    }
+}
+
+/// Converts the width of the bitvector `term` to a `usize`. The width is given by the proof, so
+/// it may be too large to fit, in which case an error is returned.
+fn width_to_usize(width: &Integer, term: &Rc<Term>) -> Result<usize, CheckerError> {
+    width
+        .to_usize()
+        .ok_or_else(|| CheckerError::TermOfWrongForm("a bitvector term", term.clone()))
+}
+
+/// Checks that a chain of terms as long as the bitvector width would be within the term limits.
+/// The `ult` and `add` rules build such chains, and the width is given by the sort of the
+/// operands, so it may be arbitrarily large.
+fn check_chain_limits(size: usize, term_limits: TermLimits) -> RuleResult {
+    term_limits.check_len(size)?;
+    term_limits.check_nesting(size)?;
+    Ok(())
+}
+
+/// Matches a conclusion of the form `(= t (bbterm ...))`, returning `t` and the bits on the right.
+fn match_bitblast_step(conclusion: &[Rc<Term>]) -> Result<(&Rc<Term>, &[Rc<Term>]), CheckerError> {
     assert_clause_len(conclusion, 1)?;
-    let ((x, y), res) = match_term_err!((= (bvult x y) res) = &conclusion[0])?;
+    let (left, bits) = match_term_err!((= left (bbterm ...)) = &conclusion[0])?;
+    Ok((left, bits))
+}
+
+pub fn var(RuleArgs { conclusion, pool, term_limits, .. }: RuleArgs) -> RuleResult {
+    let (x, bits) = match_bitblast_step(conclusion)?;
+    let size = bv_width(x, pool)?;
+    term_limits.check_len(size)?;
+
+    let expected = build_term_vec(x, size, pool);
+    assert_operation_len(Operator::BvBbTerm, bits, size)?;
+    for (e, b) in expected.iter().zip(bits) {
+        assert_eq(e, b)?;
+    }
+    Ok(())
+}
 
-    let Sort::BitVec(size) = pool.sort(x).as_sort().cloned().unwrap() else {
-        unreachable!();
+pub fn constant(RuleArgs { conclusion, pool, term_limits, .. }: RuleArgs) -> RuleResult {
+    let (c, bits) = match_bitblast_step(conclusion)?;
+    let Term::Const(Constant::BitVec(value, width)) = c.as_ref() else {
+        return Err(CheckerError::TermOfWrongForm(
+            "a bitvector constant",
+            c.clone(),
+        ));
     };
+    let size = width_to_usize(width, c)?;
+    term_limits.check_len(size)?;
 
-    let size = size.to_usize().unwrap();
+    assert_operation_len(Operator::BvBbTerm, bits, size)?;
+    for (i, b) in bits.iter().enumerate() {
+        let expected = pool.bool_constant(value.get_bit(i as u32));
+        assert_eq(&expected, b)?;
+    }
+    Ok(())
+}
+
+pub fn not(RuleArgs { conclusion, pool, term_limits, .. }: RuleArgs) -> RuleResult {
+    let (left, bits) = match_bitblast_step(conclusion)?;
+    let x = match_term_err!((bvnot x) = left)?;
+    let size = bv_width(x, pool)?;
+    term_limits.check_len(size)?;
+
+    let x = build_term_vec(x, size, pool);
+    assert_operation_len(Operator::BvBbTerm, bits, size)?;
+    for (x_i, b) in x.into_iter().zip(bits) {
+        let expected = build_term!(pool, (not { x_i }));
+        assert_eq(&expected, b)?;
+    }
+    Ok(())
+}
+
+/// Checks the bitblasting of a bitwise operator, in which each bit of the result is obtained by
+/// folding the corresponding bits of the arguments, from left to right, with `bit_op`.
+fn bitwise(
+    RuleArgs { conclusion, pool, term_limits, .. }: RuleArgs,
+    bv_op: Operator,
+    bit_op: Operator,
+) -> RuleResult {
+    let (left, bits) = match_bitblast_step(conclusion)?;
+    let args = match left.as_op() {
+        Some((op, args)) if op == bv_op => args,
+        _ => {
+            return Err(CheckerError::TermOfWrongForm(
+                "a bitwise operation",
+                left.clone(),
+            ))
+        }
+    };
+    assert_operation_len(bv_op, args, 2..)?;
+    let size = bv_width(&args[0], pool)?;
+    term_limits.check_len(size)?;
+    term_limits.check_nesting(args.len())?;
+
+    let args: Vec<_> = args.iter().map(|a| build_term_vec(a, size, pool)).collect();
+    assert_operation_len(Operator::BvBbTerm, bits, size)?;
+    for (i, b) in bits.iter().enumerate() {
+        let mut expected = args[0][i].clone();
+        for a in &args[1..] {
+            expected = pool.add(Term::Op(bit_op, vec![expected, a[i].clone()]));
+        }
+        assert_eq(&expected, b)?;
+    }
+    Ok(())
+}
+
+pub fn and(args: RuleArgs) -> RuleResult {
+    bitwise(args, Operator::BvAnd, Operator::And)
+}
+
+pub fn or(args: RuleArgs) -> RuleResult {
+    bitwise(args, Operator::BvOr, Operator::Or)
+}
+
+pub fn xor(args: RuleArgs) -> RuleResult {
+    bitwise(args, Operator::BvXor, Operator::Xor)
+}
+
+pub fn concat(RuleArgs { conclusion, pool, term_limits, .. }: RuleArgs) -> RuleResult {
+    let (left, bits) = match_bitblast_step(conclusion)?;
+    let args = match left.as_op() {
+        Some((Operator::BvConcat, args)) => args,
+        _ => return Err(CheckerError::TermOfWrongForm("(concat ...)", left.clone())),
+    };
+
+    // The first argument of `concat` holds the most significant bits, so the bits of the result
+    // start with the bits of the last argument
+    let mut expected = Vec::new();
+    for a in args.iter().rev() {
+        let size = bv_width(a, pool)?;
+        term_limits.check_len(expected.len() + size)?;
+        expected.extend(build_term_vec(a, size, pool));
+    }
+    assert_operation_len(Operator::BvBbTerm, bits, expected.len())?;
+    for (e, b) in expected.iter().zip(bits) {
+        assert_eq(e, b)?;
+    }
+    Ok(())
+}
+
+pub fn equal(RuleArgs { conclusion, pool, term_limits, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+    let ((x, y), res) = match_term_err!((= (= x y) res) = &conclusion[0])?;
+    let size = bv_width(x, pool)?;
+    term_limits.check_len(size)?;
+
+    let x = build_term_vec(x, size, pool);
+    let y = build_term_vec(y, size, pool);
+    let mut bit_equalities: Vec<_> = x
+        .into_iter()
+        .zip(y)
+        .map(|(x_i, y_i)| build_term!(pool, (= { x_i } { y_i })))
+        .collect();
+
+    // If the bitvectors have a single bit, there is no conjunction
+    let expected = if bit_equalities.len() == 1 {
+        bit_equalities.pop().unwrap()
+    } else {
+        pool.add(Term::Op(Operator::And, bit_equalities))
+    };
+    assert_eq(&expected, res)
+}
+
+pub fn ult(RuleArgs { conclusion, pool, term_limits, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+    let ((x, y), res) = match_term_err!((= (bvult x y) res) = &conclusion[0])?;
+
+    let size = bv_width(x, pool)?;
+    check_chain_limits(size, term_limits)?;
 
     let x = build_term_vec(x, size, pool);
     let y = build_term_vec(y, size, pool);
@@ -50,15 +221,12 @@ pub fn ult(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
     assert_eq(&expected_res, res)
 }
 
-pub fn add(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
+pub fn add(RuleArgs { conclusion, pool, term_limits, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 1)?;
     let ((x, y), res) = match_term_err!((= (bvadd x y) res) = &conclusion[0])?;
 
-    let Sort::BitVec(size) = pool.sort(x).as_sort().cloned().unwrap() else {
-        unreachable!();
-    };
-
-    let size = size.to_usize().unwrap();
+    let size = bv_width(x, pool)?;
+    check_chain_limits(size, term_limits)?;
 
     let x = build_term_vec(x, size, pool);
     let y = build_term_vec(y, size, pool);
@@ -89,17 +257,20 @@ pub fn add(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
 
 pub fn extract(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 1)?;
-    let (((_, left_j), left_x), right) =
+    let (((_, j), left_x), right) =
         match_term_err!((= ((_ extract i j) x) (bbterm ...)) = &conclusion[0])?;
 
-    let left_j = left_j.as_integer().unwrap();
-    let mut index = left_j;
+    let mut index = j.as_integer().unwrap();
 
     if let Some((Operator::BvBbTerm, args)) = left_x.as_op() {
-        let mut index = index.to_usize().unwrap();
-        for arg in right {
-            assert_eq(&args[index], arg)?;
-            index += 1;
+        let start = index
+            .to_usize()
+            .filter(|&start| start <= args.len() && right.len() <= args.len() - start)
+            .ok_or_else(|| {
+                CheckerError::TermOfWrongForm("an index within the bitvector width", j.clone())
+            })?;
+        for (expected, arg) in args[start..].iter().zip(right) {
+            assert_eq(expected, arg)?;
         }
         return Ok(());
     }
@@ -118,6 +289,125 @@ pub fn extract(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
 }
 
 mod tests {
+    #[test]
+    fn var() {
+        test_cases! {
+            definitions = "
+                (declare-fun x4 () (_ BitVec 4))
+                (declare-fun y4 () (_ BitVec 4))
+            ",
+            "Simple working examples" {
+                "(step t1 (cl (= x4 (bbterm ((_ bit_of 0) x4) ((_ bit_of 1) x4) ((_ bit_of 2) x4) ((_ bit_of 3) x4)))) :rule bv_bitblast_step_var)": true,
+            }
+            "Wrong bits" {
+                "(step t1 (cl (= x4 (bbterm ((_ bit_of 0) x4) ((_ bit_of 1) y4) ((_ bit_of 2) x4) ((_ bit_of 3) x4)))) :rule bv_bitblast_step_var)": false,
+                "(step t1 (cl (= x4 (bbterm ((_ bit_of 1) x4) ((_ bit_of 0) x4) ((_ bit_of 2) x4) ((_ bit_of 3) x4)))) :rule bv_bitblast_step_var)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn constant() {
+        test_cases! {
+            definitions = "
+                (declare-fun x4 () (_ BitVec 4))
+            ",
+            "Simple working examples" {
+                "(step t1 (cl (= #b0110 (bbterm false true true false))) :rule bv_bitblast_step_const)": true,
+                "(step t1 (cl (= #b1 (bbterm true))) :rule bv_bitblast_step_const)": true,
+                "(step t1 (cl (= (_ bv1 3) (bbterm true false false))) :rule bv_bitblast_step_const)": true,
+            }
+            "Wrong bits" {
+                "(step t1 (cl (= #b0001 (bbterm false false false true))) :rule bv_bitblast_step_const)": false,
+            }
+            "Term is not a constant" {
+                "(step t1 (cl (= x4 (bbterm false false false false))) :rule bv_bitblast_step_const)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn not() {
+        test_cases! {
+            definitions = "
+                (declare-fun x2 () (_ BitVec 2))
+            ",
+            "Simple working examples" {
+                "(step t1 (cl (= (bvnot x2) (bbterm (not ((_ bit_of 0) x2)) (not ((_ bit_of 1) x2))))) :rule bv_bitblast_step_bvnot)": true,
+                "(step t1 (cl (= (bvnot (bbterm true false)) (bbterm (not true) (not false)))) :rule bv_bitblast_step_bvnot)": true,
+            }
+            "Wrong bits" {
+                "(step t1 (cl (= (bvnot x2) (bbterm (not ((_ bit_of 0) x2)) ((_ bit_of 1) x2)))) :rule bv_bitblast_step_bvnot)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn bitwise() {
+        test_cases! {
+            definitions = "
+                (declare-fun x2 () (_ BitVec 2))
+                (declare-fun y2 () (_ BitVec 2))
+                (declare-fun z2 () (_ BitVec 2))
+            ",
+            "Simple working examples" {
+                "(step t1 (cl (= (bvand x2 y2) (bbterm (and ((_ bit_of 0) x2) ((_ bit_of 0) y2)) (and ((_ bit_of 1) x2) ((_ bit_of 1) y2))))) :rule bv_bitblast_step_bvand)": true,
+                "(step t1 (cl (= (bvor x2 y2) (bbterm (or ((_ bit_of 0) x2) ((_ bit_of 0) y2)) (or ((_ bit_of 1) x2) ((_ bit_of 1) y2))))) :rule bv_bitblast_step_bvor)": true,
+                "(step t1 (cl (= (bvxor x2 y2) (bbterm (xor ((_ bit_of 0) x2) ((_ bit_of 0) y2)) (xor ((_ bit_of 1) x2) ((_ bit_of 1) y2))))) :rule bv_bitblast_step_bvxor)": true,
+            }
+            "More than two arguments" {
+                "(step t1 (cl (= (bvand x2 y2 z2) (bbterm (and (and ((_ bit_of 0) x2) ((_ bit_of 0) y2)) ((_ bit_of 0) z2)) (and (and ((_ bit_of 1) x2) ((_ bit_of 1) y2)) ((_ bit_of 1) z2))))) :rule bv_bitblast_step_bvand)": true,
+                "(step t1 (cl (= (bvand x2 y2 z2) (bbterm (and ((_ bit_of 0) x2) ((_ bit_of 0) y2) ((_ bit_of 0) z2)) (and ((_ bit_of 1) x2) ((_ bit_of 1) y2) ((_ bit_of 1) z2))))) :rule bv_bitblast_step_bvand)": false,
+            }
+            "Wrong operator" {
+                "(step t1 (cl (= (bvand x2 y2) (bbterm (or ((_ bit_of 0) x2) ((_ bit_of 0) y2)) (or ((_ bit_of 1) x2) ((_ bit_of 1) y2))))) :rule bv_bitblast_step_bvand)": false,
+                "(step t1 (cl (= (bvor x2 y2) (bbterm (or ((_ bit_of 0) x2) ((_ bit_of 0) y2)) (or ((_ bit_of 1) x2) ((_ bit_of 1) y2))))) :rule bv_bitblast_step_bvand)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn concat() {
+        test_cases! {
+            definitions = "
+                (declare-fun x1 () (_ BitVec 1))
+                (declare-fun y2 () (_ BitVec 2))
+            ",
+            "Simple working examples" {
+                "(step t1 (cl (= (concat x1 y2) (bbterm ((_ bit_of 0) y2) ((_ bit_of 1) y2) ((_ bit_of 0) x1)))) :rule bv_bitblast_step_concat)": true,
+                "(step t1 (cl (= (concat (bbterm true) (bbterm false true)) (bbterm false true true))) :rule bv_bitblast_step_concat)": true,
+            }
+            "Wrong order" {
+                "(step t1 (cl (= (concat x1 y2) (bbterm ((_ bit_of 0) x1) ((_ bit_of 0) y2) ((_ bit_of 1) y2)))) :rule bv_bitblast_step_concat)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn equal() {
+        test_cases! {
+            definitions = "
+                (declare-fun x1 () (_ BitVec 1))
+                (declare-fun y1 () (_ BitVec 1))
+                (declare-fun x2 () (_ BitVec 2))
+                (declare-fun y2 () (_ BitVec 2))
+                (declare-fun p () Bool)
+                (declare-fun q () Bool)
+            ",
+            "Simple working examples" {
+                "(step t1 (cl (= (= x2 y2) (and (= ((_ bit_of 0) x2) ((_ bit_of 0) y2)) (= ((_ bit_of 1) x2) ((_ bit_of 1) y2))))) :rule bv_bitblast_step_bvequal)": true,
+                "(step t1 (cl (= (= x1 y1) (= ((_ bit_of 0) x1) ((_ bit_of 0) y1)))) :rule bv_bitblast_step_bvequal)": true,
+            }
+            "Wrong conclusion" {
+                "(step t1 (cl (= (= x2 y2) (and (= ((_ bit_of 0) x2) ((_ bit_of 0) y2))))) :rule bv_bitblast_step_bvequal)": false,
+                "(step t1 (cl (= (= x1 y1) (and (= ((_ bit_of 0) x1) ((_ bit_of 0) y1))))) :rule bv_bitblast_step_bvequal)": false,
+            }
+            "Arguments are not bitvectors" {
+                "(step t1 (cl (= (= p q) (= p q))) :rule bv_bitblast_step_bvequal)": false,
+            }
+        }
+    }
+
     #[test]
     fn ult() {
         test_cases! {
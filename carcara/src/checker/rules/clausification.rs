@@ -5,11 +5,25 @@ use super::{
 };
 use crate::ast::*;
 use indexmap::IndexMap;
+use std::collections::HashSet;
 
 pub fn distinct_elim(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 1)?;
 
     let (distinct_args, second_term) = match_term_err!((= (distinct ...) second) = &conclusion[0])?;
+    let is_bool = pool.sort(&distinct_args[0]).as_sort().unwrap() == &Sort::Bool;
+
+    // If there are more than two boolean arguments to the `distinct` operator, or if any argument
+    // appears more than once, the `distinct` term is trivially false, and the second term may be
+    // `false`. We only look for repeated arguments in that case, to avoid doing this work in
+    // every step
+    if second_term.is_bool_false() {
+        let mut seen = HashSet::with_capacity(distinct_args.len());
+        if (is_bool && distinct_args.len() > 2) || !distinct_args.iter().all(|a| seen.insert(a)) {
+            return Ok(());
+        }
+    }
+
     match distinct_args {
         [] | [_] => unreachable!(),
         [a, b] => {
@@ -23,36 +37,28 @@ pub fn distinct_elim(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult
         }
         // If there are more than two boolean arguments to the distinct operator, the
         // second term must be `false`
-        args if pool.sort(&args[0]).as_sort().unwrap() == &Sort::Bool => {
-            if second_term.is_bool_false() {
-                Ok(())
-            } else {
-                Err(CheckerError::ExpectedBoolConstant(
-                    false,
-                    second_term.clone(),
-                ))
-            }
-        }
+        _ if is_bool => Err(CheckerError::ExpectedBoolConstant(
+            false,
+            second_term.clone(),
+        )),
 
         args => {
+            // The conjunction has one disequality for each pair of arguments, so we first check its
+            // length, before doing any work that depends on it. After that, each conjunct is only
+            // compared against the pair of arguments it should correspond to, so the check takes
+            // linear time in the size of the conclusion
             let n = args.len();
             let and_args = match_term_err!((and ...) = second_term)?;
             assert_operation_len(Operator::And, and_args, n * (n - 1) / 2)?;
 
-            let mut k = 0;
-            for i in 0..n {
-                for j in (i + 1)..n {
-                    let (a, b) = (&args[i], &args[j]);
-                    let got = match_term_err!((not (= x y)) = &and_args[k])?;
-                    if !(got == (a, b) || got == (b, a)) {
-                        let expected = build_term!(pool, (not (= {a.clone()} {b.clone()})));
-                        return Err(EqualityError::ExpectedToBe {
-                            expected,
-                            got: and_args[k].clone(),
-                        }
-                        .into());
-                    }
-                    k += 1;
+            let pairs = (0..n).flat_map(|i| ((i + 1)..n).map(move |j| (&args[i], &args[j])));
+            for ((a, b), conjunct) in pairs.zip(and_args) {
+                let got = match_term_err!((not (= x y)) = conjunct)?;
+                if !(got == (a, b) || got == (b, a)) {
+                    let expected = build_term!(pool, (not (= {a.clone()} {b.clone()})));
+                    return Err(
+                        EqualityError::ExpectedToBe { expected, got: conjunct.clone() }.into(),
+                    );
                 }
             }
             Ok(())
@@ -195,12 +201,13 @@ pub fn not_implies2(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResu
     assert_eq(phi_2, conclusion[0].remove_negation_err()?)
 }
 
-pub fn nary_elim(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
-    /// The three possible cases for n-ary operators: chainable, right associative and left
-    /// associative
+pub fn nary_elim(RuleArgs { conclusion, pool, term_limits, .. }: RuleArgs) -> RuleResult {
+    /// The possible cases for n-ary operators: chainable, pairwise, right associative and left
+    /// associative. These follow the attributes given to each operator in the SMT-LIB standard
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     enum Case {
         Chainable,
+        Pairwise,
         RightAssoc,
         LeftAssoc,
     }
@@ -229,7 +236,7 @@ pub fn nary_elim(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
         let new_args = match case {
             Case::RightAssoc => vec![head.clone(), nested],
             Case::LeftAssoc => vec![nested, head.clone()],
-            Case::Chainable => unreachable!(),
+            Case::Chainable | Case::Pairwise => unreachable!(),
         };
         pool.add(Term::Op(op, new_args))
     }
@@ -243,9 +250,27 @@ pub fn nary_elim(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
         _ => return Err(CheckerError::NotValidNaryTerm(original.clone())),
     };
 
+    // Even though `and` and `or` are also left associative in SMT-LIB, they are treated as truly
+    // n-ary operators in Alethe, so they are not eliminated by this rule
     let case = match op {
-        Operator::Equals => Case::Chainable,
-        Operator::Add | Operator::Sub | Operator::Mult => Case::LeftAssoc,
+        Operator::Equals
+        | Operator::LessThan
+        | Operator::GreaterThan
+        | Operator::LessEq
+        | Operator::GreaterEq => Case::Chainable,
+        Operator::Distinct => Case::Pairwise,
+        Operator::Add
+        | Operator::Sub
+        | Operator::Mult
+        | Operator::IntDiv
+        | Operator::RealDiv
+        | Operator::Xor
+        | Operator::BvAdd
+        | Operator::BvMul
+        | Operator::BvAnd
+        | Operator::BvOr
+        | Operator::BvXor
+        | Operator::BvConcat => Case::LeftAssoc,
         Operator::Implies => Case::RightAssoc,
         _ => return Err(CheckerError::NotValidNaryTerm(original.clone())),
     };
@@ -258,7 +283,20 @@ pub fn nary_elim(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
                 .collect();
             pool.add(Term::Op(Operator::And, and_args))
         }
-        assoc_case => expand_assoc(pool, *op, args, assoc_case),
+        Case::Pairwise => {
+            term_limits.check_len(args.len() * (args.len() - 1) / 2)?;
+            let mut and_args = Vec::new();
+            for (i, a) in args.iter().enumerate() {
+                for b in &args[i + 1..] {
+                    and_args.push(pool.add(Term::Op(*op, vec![a.clone(), b.clone()])));
+                }
+            }
+            pool.add(Term::Op(Operator::And, and_args))
+        }
+        assoc_case => {
+            term_limits.check_nesting(args.len())?;
+            expand_assoc(pool, *op, args, assoc_case)
+        }
     };
     assert_is_expected(result, expected)
 }
@@ -269,11 +307,13 @@ fn bfun_elim_first_step(
     bindigns: &[SortedVar],
     term: &Rc<Term>,
     acc: &mut Vec<Rc<Term>>,
-) -> Result<(), SubstitutionError> {
+    budget: &mut TermBudget,
+) -> Result<(), CheckerError> {
     let var = match bindigns {
         [.., var] if var.1.as_sort() == Some(&Sort::Bool) => pool.add(var.clone().into()),
-        [rest @ .., _] => return bfun_elim_first_step(pool, rest, term, acc),
+        [rest @ .., _] => return bfun_elim_first_step(pool, rest, term, acc, budget),
         [] => {
+            budget.charge(1)?;
             acc.push(term.clone());
             return Ok(());
         }
@@ -281,7 +321,7 @@ fn bfun_elim_first_step(
     for value in [pool.bool_false(), pool.bool_true()] {
         let mut substitution = Substitution::single(pool, var.clone(), value)?;
         let term = substitution.apply(pool, term);
-        bfun_elim_first_step(pool, &bindigns[..bindigns.len() - 1], &term, acc)?;
+        bfun_elim_first_step(pool, &bindigns[..bindigns.len() - 1], &term, acc, budget)?;
     }
     Ok(())
 }
@@ -293,7 +333,9 @@ fn bfun_elim_second_step(
     func: &Rc<Term>,
     args: &[Rc<Term>],
     processed: usize,
-) -> Rc<Term> {
+    budget: &mut TermBudget,
+) -> Result<Rc<Term>, CheckerError> {
+    budget.charge(1)?;
     for i in processed..args.len() {
         if pool.sort(&args[i]).as_sort().unwrap() == &Sort::Bool
             && !args[i].is_bool_false()
@@ -305,24 +347,26 @@ fn bfun_elim_second_step(
             for bool_constant in [pool.bool_true(), pool.bool_false()] {
                 let mut new_args = args.to_vec();
                 new_args[i] = bool_constant;
-                let inner_term = bfun_elim_second_step(pool, func, &new_args, i + 1);
+                let inner_term = bfun_elim_second_step(pool, func, &new_args, i + 1, budget)?;
                 ite_args.push(inner_term);
             }
-            return pool.add(Term::Op(Operator::Ite, ite_args));
+            return Ok(pool.add(Term::Op(Operator::Ite, ite_args)));
         }
     }
 
     // If there were no non-constant boolean arguments we don't need to expand the term into an ite
     // term. So we just construct the original application term and return it.
-    pool.add(Term::App(func.clone(), args.to_vec()))
+    Ok(pool.add(Term::App(func.clone(), args.to_vec())))
 }
 
-/// Applies the simplification steps for the `bfun_elim` rule.
+/// Applies the simplification steps for the `bfun_elim` rule. Since both steps may grow the term
+/// exponentially, every term they build is charged to the given budget.
 fn apply_bfun_elim(
     pool: &mut dyn TermPool,
     term: &Rc<Term>,
     cache: &mut IndexMap<Rc<Term>, Rc<Term>>,
-) -> Result<Rc<Term>, SubstitutionError> {
+    budget: &mut TermBudget,
+) -> Result<Rc<Term>, CheckerError> {
     if let Some(v) = cache.get(term) {
         return Ok(v.clone());
     }
@@ -331,14 +375,14 @@ fn apply_bfun_elim(
         Term::App(f, args) => {
             let args: Vec<_> = args
                 .iter()
-                .map(|a| apply_bfun_elim(pool, a, cache))
+                .map(|a| apply_bfun_elim(pool, a, cache, budget))
                 .collect::<Result<_, _>>()?;
-            bfun_elim_second_step(pool, f, &args, 0)
+            bfun_elim_second_step(pool, f, &args, 0, budget)?
         }
         Term::Op(op, args) => {
             let args = args
                 .iter()
-                .map(|a| apply_bfun_elim(pool, a, cache))
+                .map(|a| apply_bfun_elim(pool, a, cache, budget))
                 .collect::<Result<_, _>>()?;
             pool.add(Term::Op(*op, args))
         }
@@ -347,21 +391,21 @@ fn apply_bfun_elim(
                 Binder::Forall => Operator::And,
                 Binder::Exists => Operator::Or,
                 Binder::Choice | Binder::Lambda => {
-                    let inner = apply_bfun_elim(pool, inner, cache)?;
+                    let inner = apply_bfun_elim(pool, inner, cache, budget)?;
                     let result = pool.add(Term::Binder(*b, bindings.clone(), inner));
                     cache.insert(term.clone(), result.clone());
                     return Ok(result);
                 }
             };
-            let mut args = Vec::with_capacity(2usize.pow(bindings.len() as u32));
-            bfun_elim_first_step(pool, bindings.as_slice(), inner, &mut args)?;
+            let mut args = Vec::new();
+            bfun_elim_first_step(pool, bindings.as_slice(), inner, &mut args, budget)?;
 
             let op_term = if args.len() == 1 {
                 args.pop().unwrap()
             } else {
                 pool.add(Term::Op(op, args))
             };
-            let op_term = apply_bfun_elim(pool, &op_term, cache)?;
+            let op_term = apply_bfun_elim(pool, &op_term, cache, budget)?;
 
             let new_bindings: Vec<_> = bindings
                 .iter()
@@ -375,7 +419,7 @@ fn apply_bfun_elim(
             }
         }
         Term::Let(bindings, inner) => {
-            let inner = apply_bfun_elim(pool, inner, cache)?;
+            let inner = apply_bfun_elim(pool, inner, cache, budget)?;
             pool.add(Term::Let(bindings.clone(), inner))
         }
         _ => term.clone(),
@@ -391,6 +435,7 @@ pub fn bfun_elim(
         premises,
         pool,
         polyeq_time,
+        term_limits,
         ..
     }: RuleArgs,
 ) -> RuleResult {
@@ -399,7 +444,8 @@ pub fn bfun_elim(
 
     let psi = get_premise_term(&premises[0])?;
 
-    let expected = apply_bfun_elim(pool, psi, &mut IndexMap::new())?;
+    let mut budget = term_limits.budget();
+    let expected = apply_bfun_elim(pool, psi, &mut IndexMap::new(), &mut budget)?;
     assert_polyeq_expected(&conclusion[0], expected, polyeq_time)
 }
 
@@ -435,6 +481,25 @@ mod tests {
                     (not (= c b))
                 ))) :rule distinct_elim)": true,
             }
+            "\"distinct\" with repeated arguments may be \"false\"" {
+                "(step t1 (cl (= (distinct a b a) false)) :rule distinct_elim)": true,
+                "(step t1 (cl (= (distinct a a) false)) :rule distinct_elim)": true,
+                "(step t1 (cl (= (distinct a b c) false)) :rule distinct_elim)": false,
+                "(step t1 (cl (= (distinct p q) false)) :rule distinct_elim)": false,
+            }
+            "Conjunction has the wrong number of terms" {
+                "(step t1 (cl (= (distinct a b c) (and
+                    (not (= a b))
+                    (not (= a c))
+                ))) :rule distinct_elim)": false,
+
+                "(step t1 (cl (= (distinct a b c) (and
+                    (not (= a b))
+                    (not (= a c))
+                    (not (= b c))
+                    (not (= b c))
+                ))) :rule distinct_elim)": false,
+            }
             "Conjunction terms in wrong order" {
                 "(step t1 (cl (= (distinct a b c) (and
                     (not (= b c))
@@ -445,6 +510,10 @@ mod tests {
             "\"distinct\" on more than two booleans should be \"false\"" {
                 "(step t1 (cl (= (distinct p q r) false)) :rule distinct_elim)": true,
 
+                "(step t1 (cl (= (distinct p q r (not p) (and p q)) false)) :rule distinct_elim)": true,
+
+                "(step t1 (cl (= (distinct p q r) true)) :rule distinct_elim)": false,
+
                 "(step t1 (cl (= (distinct p q r) (and
                     (not (= p q))
                     (not (= p r))
@@ -840,6 +909,9 @@ mod tests {
                 (declare-fun b () Int)
                 (declare-fun c () Int)
                 (declare-fun d () Int)
+                (declare-fun x () (_ BitVec 4))
+                (declare-fun y () (_ BitVec 4))
+                (declare-fun z () (_ BitVec 4))
             ",
             "Chainable operators" {
                 "(step t1 (cl (= (= a b c d) (and (= a b) (= b c) (= c d)))) :rule nary_elim)": true,
@@ -853,6 +925,22 @@ mod tests {
                 "(step t1 (cl (= (- a b c d) (- a (- b (- c d))))) :rule nary_elim)": false,
                 "(step t1 (cl (= (+ a b c d) (+ (+ (+ d c) b) a))) :rule nary_elim)": false,
             }
+            "Pairwise operators" {
+                "(step t1 (cl (= (distinct a b c) (and (distinct a b) (distinct a c) (distinct b c))))
+                    :rule nary_elim)": true,
+                "(step t1 (cl (= (distinct a b c) (and (distinct a b) (distinct b c))))
+                    :rule nary_elim)": false,
+            }
+            "Other chainable and left associative operators" {
+                "(step t1 (cl (= (< a b c) (and (< a b) (< b c)))) :rule nary_elim)": true,
+                "(step t1 (cl (= (>= a b c) (and (>= a b) (>= b c)))) :rule nary_elim)": true,
+                "(step t1 (cl (= (div a b c) (div (div a b) c))) :rule nary_elim)": true,
+                "(step t1 (cl (= (xor p q r) (xor (xor p q) r))) :rule nary_elim)": true,
+                "(step t1 (cl (= (xor p q r) (xor p (xor q r)))) :rule nary_elim)": false,
+                "(step t1 (cl (= (bvadd x y z) (bvadd (bvadd x y) z))) :rule nary_elim)": true,
+                "(step t1 (cl (= (concat x y z) (concat (concat x y) z))) :rule nary_elim)": true,
+                "(step t1 (cl (= (concat x y z) (concat x (concat y z)))) :rule nary_elim)": false,
+            }
             "Right associative operators" {
                 "(step t1 (cl (= (=> p q r s) (=> p (=> q (=> r s))))) :rule nary_elim)": true,
                 "(step t1 (cl (= (=> p q) (=> p q))) :rule nary_elim)": true,
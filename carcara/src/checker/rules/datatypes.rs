@@ -0,0 +1,231 @@
+use super::{
+    assert_clause_len, assert_eq, assert_is_expected, assert_num_args, assert_num_premises,
+    get_premise_term, RuleArgs, RuleResult,
+};
+use crate::{
+    ast::*,
+    checker::error::{CheckerError, DatatypeError},
+};
+
+/// Matches a constructor application. A variable bound by an enclosing anchor is never a
+/// constructor, even if it has the same name and sort as one, since it shadows the constructor.
+fn constructor_app<'a, 't>(
+    datatypes: &'a Datatypes,
+    context: &ContextStack,
+    term: &'t Rc<Term>,
+) -> Result<(&'a Constructor, &'t [Rc<Term>]), CheckerError> {
+    datatypes
+        .as_constructor_app(term)
+        .filter(|(_, c, _)| !context.binds(&c.name))
+        .map(|(_, c, args)| (c, args))
+        .ok_or_else(|| DatatypeError::NotConstructorApp(term.clone()).into())
+}
+
+/// Builds the tester `((_ is c) x)`, where `c` is a constructor of the datatype `def`.
+fn build_tester(
+    pool: &mut dyn TermPool,
+    def: &DatatypeDef,
+    constructor: &Constructor,
+    x: &Rc<Term>,
+) -> Rc<Term> {
+    let datatype_sort = pool.add(Term::Sort(Sort::Atom(def.name.clone(), Vec::new())));
+    let constructor_sort = if constructor.selectors.is_empty() {
+        datatype_sort
+    } else {
+        let mut sorts: Vec<_> = constructor
+            .selectors
+            .iter()
+            .map(|(_, s)| s.clone())
+            .collect();
+        sorts.push(datatype_sort);
+        pool.add(Term::Sort(Sort::Function(sorts)))
+    };
+    let constructor = pool.add(Term::Var(constructor.name.clone(), constructor_sort));
+    pool.add(Term::ParamOp {
+        op: ParamOperator::DtTester,
+        op_args: vec![constructor],
+        args: vec![x.clone()],
+    })
+}
+
+/// Checks constructor injectivity: from the premise `(= (c t_1 ... t_n) (c s_1 ... s_n))`, where
+/// `c` is a constructor, and the argument `i`, concludes `(= t_i s_i)`.
+pub fn dt_unif(
+    RuleArgs {
+        conclusion,
+        premises,
+        args,
+        context,
+        datatypes,
+        ..
+    }: RuleArgs,
+) -> RuleResult {
+    assert_num_premises(premises, 1)?;
+    assert_clause_len(conclusion, 1)?;
+    assert_num_args(args, 1)?;
+
+    let i = args[0].as_usize_err()?;
+    let (t, s) = match_term_err!((= t s) = get_premise_term(&premises[0])?)?;
+    let (c_t, t_args) = constructor_app(datatypes, context, t)?;
+    let (c_s, s_args) = constructor_app(datatypes, context, s)?;
+    if c_t.name != c_s.name {
+        return Err(
+            DatatypeError::DifferentConstructors(c_t.name.clone(), c_s.name.clone()).into(),
+        );
+    }
+    let (Some(t_i), Some(s_i)) = (t_args.get(i), s_args.get(i)) else {
+        return Err(CheckerError::NoIthChildInTerm(i, t.clone()));
+    };
+
+    let (a, b) = match_term_err!((= a b) = &conclusion[0])?;
+    assert_eq(a, t_i)?;
+    assert_eq(b, s_i)
+}
+
+/// Checks constructor distinctness: concludes `(not (= (c t_1 ... t_n) (d s_1 ... s_m)))`, where `c`
+/// and `d` are different constructors.
+pub fn dt_clash(RuleArgs { conclusion, context, datatypes, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let (t, s) = match_term_err!((not (= t s)) = &conclusion[0])?;
+    let (c_t, _) = constructor_app(datatypes, context, t)?;
+    let (c_s, _) = constructor_app(datatypes, context, s)?;
+    if c_t.name == c_s.name {
+        return Err(DatatypeError::SameConstructor(c_t.name.clone()).into());
+    }
+    Ok(())
+}
+
+/// Checks constructor exhaustiveness: concludes `(cl ((_ is c_1) x) ... ((_ is c_n) x))`, where
+/// `c_1`, ..., `c_n` are all the constructors of the datatype of `x`, in the order they were
+/// declared. The conclusion may also be a single `or` term with these testers as arguments.
+pub fn dt_split(RuleArgs { conclusion, pool, datatypes, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1..)?;
+
+    let testers = match conclusion {
+        [t] => match t.as_op() {
+            Some((Operator::Or, args)) => args,
+            _ => conclusion,
+        },
+        _ => conclusion,
+    };
+    let x = match testers[0].as_ref() {
+        Term::ParamOp {
+            op: ParamOperator::DtTester, args, ..
+        } => &args[0],
+        _ => {
+            return Err(CheckerError::TermOfWrongForm(
+                "a datatype tester",
+                testers[0].clone(),
+            ))
+        }
+    };
+    let sort = pool.sort(x).as_sort().unwrap().clone();
+    let def = datatypes
+        .of_sort(&sort)
+        .ok_or(DatatypeError::NotDatatypeSort(sort))?;
+
+    assert_clause_len(testers, def.constructors.len())?;
+    for (got, constructor) in testers.iter().zip(&def.constructors) {
+        let expected = build_tester(pool, def, constructor, x);
+        assert_is_expected(got, expected)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    const DEFINITIONS: &str = "
+        (declare-datatypes ((List 0) (Tree 0)) (
+            ((nil) (cons (head Int) (tail List)))
+            ((leaf (value Int)) (node (children List)))
+        ))
+        (declare-datatype Color (red green blue))
+        (declare-const x Int)
+        (declare-const y Int)
+        (declare-const l List)
+        (declare-const m List)
+        (declare-const c Color)
+        (declare-fun f (Int) List)
+    ";
+
+    #[test]
+    fn dt_unif() {
+        test_cases! {
+            definitions = DEFINITIONS,
+            "Simple working examples" {
+                "(assume h1 (= (cons x l) (cons y m)))
+                (step t2 (cl (= x y)) :rule dt_unif :premises (h1) :args (0))": true,
+
+                "(assume h1 (= (cons x l) (cons y m)))
+                (step t2 (cl (= l m)) :rule dt_unif :premises (h1) :args (1))": true,
+
+                "(assume h1 (= (leaf x) (leaf 1)))
+                (step t2 (cl (= x 1)) :rule dt_unif :premises (h1) :args (0))": true,
+            }
+            "Wrong argument or conclusion" {
+                "(assume h1 (= (cons x l) (cons y m)))
+                (step t2 (cl (= x y)) :rule dt_unif :premises (h1) :args (1))": false,
+
+                "(assume h1 (= (cons x l) (cons y m)))
+                (step t2 (cl (= y x)) :rule dt_unif :premises (h1) :args (0))": false,
+
+                "(assume h1 (= (cons x l) (cons y m)))
+                (step t2 (cl (= x y)) :rule dt_unif :premises (h1) :args (2))": false,
+            }
+            "Terms are not applications of the same constructor" {
+                "(assume h1 (= (f x) (f y)))
+                (step t2 (cl (= x y)) :rule dt_unif :premises (h1) :args (0))": false,
+
+                "(assume h1 (= (cons x l) nil))
+                (step t2 (cl (= x y)) :rule dt_unif :premises (h1) :args (0))": false,
+            }
+        }
+    }
+
+    #[test]
+    fn dt_clash() {
+        test_cases! {
+            definitions = DEFINITIONS,
+            "Simple working examples" {
+                "(step t1 (cl (not (= (cons x l) nil))) :rule dt_clash)": true,
+                "(step t1 (cl (not (= red blue))) :rule dt_clash)": true,
+                "(step t1 (cl (not (= (leaf x) (node l)))) :rule dt_clash)": true,
+            }
+            "Failing examples" {
+                "(step t1 (cl (not (= red red))) :rule dt_clash)": false,
+                "(step t1 (cl (not (= (cons x l) (cons y m)))) :rule dt_clash)": false,
+                "(step t1 (cl (not (= (f x) nil))) :rule dt_clash)": false,
+                "(step t1 (cl (= (cons x l) nil)) :rule dt_clash)": false,
+            }
+            "Variables that shadow constructors" {
+                "(anchor :step t1 :args ((k Color)))
+                (step t1.t1 (cl (not (= red blue))) :rule dt_clash)
+                (step t1 (cl) :rule hole)": true,
+
+                "(anchor :step t1 :args ((red Color)))
+                (step t1.t1 (cl (not (= red blue))) :rule dt_clash)
+                (step t1 (cl) :rule hole)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn dt_split() {
+        test_cases! {
+            definitions = DEFINITIONS,
+            "Simple working examples" {
+                "(step t1 (cl ((_ is nil) l) ((_ is cons) l)) :rule dt_split)": true,
+                "(step t1 (cl (or ((_ is nil) l) ((_ is cons) l))) :rule dt_split)": true,
+                "(step t1 (cl ((_ is red) c) ((_ is green) c) ((_ is blue) c)) :rule dt_split)": true,
+                "(step t1 (cl ((_ is nil) (f x)) ((_ is cons) (f x))) :rule dt_split)": true,
+            }
+            "Missing, extra or reordered testers" {
+                "(step t1 (cl ((_ is red) c) ((_ is green) c)) :rule dt_split)": false,
+                "(step t1 (cl ((_ is cons) l) ((_ is nil) l)) :rule dt_split)": false,
+                "(step t1 (cl ((_ is nil) l) ((_ is cons) l) ((_ is nil) l)) :rule dt_split)": false,
+                "(step t1 (cl ((_ is nil) l) ((_ is cons) m)) :rule dt_split)": false,
+            }
+        }
+    }
+}
@@ -0,0 +1,499 @@
+use super::{assert_num_args, CheckerError, RuleArgs, RuleResult};
+use crate::{
+    ast::*,
+    checker::{error::DratError, trace::trace_value},
+};
+use indexmap::IndexMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+type Clause = Vec<i64>;
+
+/// Reads a literal or clause id from a certificate token.
+fn parse_number(token: &str, entry: usize) -> Result<i64, DratError> {
+    token.parse().map_err(|_| DratError::MalformedEntry(entry))
+}
+
+/// Reads numbers from `tokens` until a `0` is found, which is consumed but not returned.
+fn parse_until_zero<'a>(
+    tokens: &mut impl Iterator<Item = &'a str>,
+    entry: usize,
+) -> Result<Vec<i64>, DratError> {
+    let mut result = Vec::new();
+    loop {
+        match tokens.next() {
+            Some(t) => match parse_number(t, entry)? {
+                0 => return Ok(result),
+                n => result.push(n),
+            },
+            None => return Err(DratError::MalformedEntry(entry)),
+        }
+    }
+}
+
+/// Splits a textual certificate into tokens, skipping comment lines.
+fn tokenize(certificate: &str) -> impl Iterator<Item = &str> {
+    certificate
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('c'))
+        .flat_map(str::split_whitespace)
+}
+
+/// Falsifies every literal of `clause` in `assignment`, which holds the literals that are true.
+/// Returns `true` if that leads to a conflict, which happens if the clause is already satisfied.
+fn falsify(assignment: &mut HashSet<i64>, clause: &[i64]) -> bool {
+    for &l in clause {
+        if assignment.contains(&l) {
+            return true;
+        }
+        assignment.insert(-l);
+    }
+    false
+}
+
+/// The result of evaluating a clause under a partial assignment.
+enum ClauseStatus {
+    Satisfied,
+    Falsified,
+    Unit(i64),
+    Unresolved,
+}
+
+fn clause_status(assignment: &HashSet<i64>, clause: &[i64]) -> ClauseStatus {
+    let mut unassigned = None;
+    for &l in clause {
+        if assignment.contains(&l) {
+            return ClauseStatus::Satisfied;
+        }
+        if !assignment.contains(&-l) {
+            if unassigned.is_some() {
+                return ClauseStatus::Unresolved;
+            }
+            unassigned = Some(l);
+        }
+    }
+    match unassigned {
+        Some(l) => ClauseStatus::Unit(l),
+        None => ClauseStatus::Falsified,
+    }
+}
+
+/// Performs unit propagation on `clauses`, starting from `assignment`. Returns `true` if a conflict
+/// is reached.
+fn propagate<'a>(
+    clauses: impl Iterator<Item = &'a Clause> + Clone,
+    mut assignment: HashSet<i64>,
+) -> bool {
+    loop {
+        let mut changed = false;
+        for clause in clauses.clone() {
+            match clause_status(&assignment, clause) {
+                ClauseStatus::Falsified => return true,
+                ClauseStatus::Unit(l) => {
+                    assignment.insert(l);
+                    changed = true;
+                }
+                ClauseStatus::Satisfied | ClauseStatus::Unresolved => (),
+            }
+        }
+        if !changed {
+            return false;
+        }
+    }
+}
+
+/// Returns `true` if `clause` has the reverse unit propagation property with respect to `clauses`.
+fn is_rup<'a>(clauses: impl Iterator<Item = &'a Clause> + Clone, clause: &[i64]) -> bool {
+    let mut assignment = HashSet::new();
+    falsify(&mut assignment, clause) || propagate(clauses, assignment)
+}
+
+/// Returns `true` if `clause` is a resolution asymmetric tautology with respect to `clauses`, using
+/// its first literal as the pivot.
+fn is_rat<'a>(clauses: impl Iterator<Item = &'a Clause> + Clone, clause: &[i64]) -> bool {
+    let Some(&pivot) = clause.first() else {
+        return false;
+    };
+    clauses.clone().filter(|d| d.contains(&-pivot)).all(|d| {
+        let resolvent: Vec<_> = clause
+            .iter()
+            .chain(d.iter().filter(|&&l| l != -pivot))
+            .copied()
+            .collect();
+        is_rup(clauses.clone(), &resolvent)
+    })
+}
+
+/// Checks a DRAT certificate, which must derive the empty clause from `formula`. Deletions of
+/// clauses that are not in the formula are ignored.
+fn check_drat(formula: Vec<Clause>, certificate: &str) -> Result<(), DratError> {
+    let mut clauses: Vec<Option<Clause>> = formula.into_iter().map(Some).collect();
+    let mut tokens = tokenize(certificate).peekable();
+    let mut entry = 0;
+    while tokens.peek().is_some() {
+        entry += 1;
+        let is_deletion = tokens.next_if_eq(&"d").is_some();
+        let clause = parse_until_zero(&mut tokens, entry)?;
+        if is_deletion {
+            let mut sorted = clause;
+            sorted.sort_unstable();
+            let found = clauses.iter_mut().find(|c| {
+                c.as_ref().is_some_and(|c| {
+                    let mut c = c.clone();
+                    c.sort_unstable();
+                    c == sorted
+                })
+            });
+            if let Some(c) = found {
+                *c = None;
+            }
+            continue;
+        }
+
+        let active = clauses.iter().flatten();
+        if !is_rup(active.clone(), &clause) && !is_rat(active, &clause) {
+            return Err(DratError::NotRedundant(entry));
+        }
+        if clause.is_empty() {
+            return Ok(());
+        }
+        clauses.push(Some(clause));
+    }
+
+    // Some tools omit the empty clause at the end of the certificate, if it follows from unit
+    // propagation
+    if propagate(clauses.iter().flatten(), HashSet::new()) {
+        Ok(())
+    } else {
+        Err(DratError::NoEmptyClause)
+    }
+}
+
+/// Applies the hints of an LRAT entry, each of which must become unit or falsified under
+/// `assignment`. Returns `true` if a conflict is reached.
+fn apply_lrat_hints(
+    clauses: &HashMap<i64, Clause>,
+    assignment: &mut HashSet<i64>,
+    hints: &[i64],
+    entry: usize,
+) -> Result<bool, DratError> {
+    for &id in hints {
+        let clause = clauses
+            .get(&id)
+            .ok_or(DratError::InvalidClauseId(entry, id))?;
+        match clause_status(assignment, clause) {
+            ClauseStatus::Falsified => return Ok(true),
+            ClauseStatus::Unit(l) => {
+                assignment.insert(l);
+            }
+            ClauseStatus::Satisfied | ClauseStatus::Unresolved => {
+                return Err(DratError::NotRedundant(entry))
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Checks an LRAT certificate, which must derive the empty clause from `formula`. The clauses of the
+/// formula have ids starting from 1.
+fn check_lrat(formula: Vec<Clause>, certificate: &str) -> Result<(), DratError> {
+    let mut clauses: HashMap<i64, Clause> = (1..).zip(formula).collect();
+    let mut last_id = clauses.len() as i64;
+    let mut tokens = tokenize(certificate).peekable();
+    let mut entry = 0;
+    while let Some(token) = tokens.next() {
+        entry += 1;
+        let id = parse_number(token, entry)?;
+        if tokens.next_if_eq(&"d").is_some() {
+            for id in parse_until_zero(&mut tokens, entry)? {
+                clauses.remove(&id);
+            }
+            continue;
+        }
+        if id <= last_id {
+            return Err(DratError::InvalidClauseId(entry, id));
+        }
+        let clause = parse_until_zero(&mut tokens, entry)?;
+        let hints = parse_until_zero(&mut tokens, entry)?;
+
+        // The hints are a list of clauses used in unit propagation, optionally followed by groups of
+        // hints for the RAT check, each starting with the negated id of a clause that contains the
+        // negation of the pivot
+        let rat_start = hints.iter().position(|&h| h < 0).unwrap_or(hints.len());
+        let mut assignment = HashSet::new();
+        let conflict = falsify(&mut assignment, &clause)
+            || apply_lrat_hints(&clauses, &mut assignment, &hints[..rat_start], entry)?;
+        if !conflict {
+            let pivot = *clause.first().ok_or(DratError::NotRedundant(entry))?;
+            let mut groups: HashMap<i64, &[i64]> = HashMap::new();
+            let mut rest = &hints[rat_start..];
+            while let Some((&first, tail)) = rest.split_first() {
+                let len = tail.iter().position(|&h| h < 0).unwrap_or(tail.len());
+                groups.insert(-first, &tail[..len]);
+                rest = &tail[len..];
+            }
+            for (d_id, d) in &clauses {
+                if !d.contains(&-pivot) {
+                    continue;
+                }
+                let group = groups.get(d_id).ok_or(DratError::NotRedundant(entry))?;
+                let mut assignment = assignment.clone();
+                let d_rest: Vec<_> = d.iter().copied().filter(|&l| l != -pivot).collect();
+                if !falsify(&mut assignment, &d_rest)
+                    && !apply_lrat_hints(&clauses, &mut assignment, group, entry)?
+                {
+                    return Err(DratError::NotRedundant(entry));
+                }
+            }
+        }
+
+        if clause.is_empty() {
+            return Ok(());
+        }
+        clauses.insert(id, clause);
+        last_id = id;
+    }
+    Err(DratError::NoEmptyClause)
+}
+
+fn get_string<'a>(term: &'a Rc<Term>, expected: &'static str) -> Result<&'a str, CheckerError> {
+    match term.as_ref() {
+        Term::Const(Constant::String(s)) => Ok(s),
+        _ => Err(CheckerError::TermOfWrongForm(expected, term.clone())),
+    }
+}
+
+/// Returns the literal in the propositional skeleton that corresponds to `term`, numbering its atom
+/// if it wasn't seen before.
+fn literal(atoms: &mut IndexMap<Rc<Term>, i64>, term: &Rc<Term>) -> i64 {
+    let (is_negated, atom) = match term.remove_negation() {
+        Some(atom) => (true, atom),
+        None => (false, term),
+    };
+    let next = atoms.len() as i64 + 1;
+    let var = *atoms.entry(atom.clone()).or_insert(next);
+    if is_negated {
+        -var
+    } else {
+        var
+    }
+}
+
+/// Reads a certificate file, whose path is resolved against `dir`. Reading files is only allowed if
+/// the user gave such a directory, and only regular files inside it are read. Files outside of it,
+/// reached through absolute paths, `..` components or symbolic links, are rejected, and so are
+/// devices and pipes, since reading from them may never terminate.
+fn read_certificate(dir: Option<&Path>, path: &str) -> Result<String, DratError> {
+    let dir = dir.ok_or_else(|| DratError::FilesNotAllowed(path.to_owned()))?;
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let could_not_read =
+        |e: std::io::Error| DratError::CouldNotRead(path.to_owned(), e.to_string());
+    let full_path = dir.join(path).canonicalize().map_err(could_not_read)?;
+    if !full_path.starts_with(dir.canonicalize().map_err(could_not_read)?) {
+        return Err(DratError::OutsideCertificateDir(path.to_owned()));
+    }
+    if !fs::metadata(&full_path).map_err(could_not_read)?.is_file() {
+        return Err(DratError::NotARegularFile(path.to_owned()));
+    }
+    fs::read_to_string(full_path).map_err(could_not_read)
+}
+
+/// Checks a step whose conclusion follows propositionally from its premises, as justified by a DRAT
+/// or LRAT certificate. The first argument is the certificate format, either `"drat"` or `"lrat"`,
+/// and the second is the certificate itself. If the format is `"drat-file"` or `"lrat-file"`, the
+/// second argument is instead the path of a file containing the certificate, relative to the
+/// certificate directory given in the checker configuration.
+///
+/// The certificate refers to the propositional skeleton of the premises: each distinct atom is
+/// numbered from 1, in the order in which it first appears in the premises. The premise clauses are
+/// numbered from 1, in order, and are followed by unit clauses with the negation of each literal in
+/// the conclusion. The certificate must derive the empty clause from these clauses.
+pub fn drat_refutation(
+    RuleArgs {
+        conclusion,
+        premises,
+        args,
+        certificate_dir,
+        ..
+    }: RuleArgs,
+) -> RuleResult {
+    assert_num_args(args, 2)?;
+
+    let format = get_string(&args[0], "\"<format>\"")?;
+    let source = get_string(&args[1], "\"<certificate>\"")?;
+    let certificate = match format {
+        "drat" | "lrat" => source.to_owned(),
+        "drat-file" | "lrat-file" => read_certificate(certificate_dir, source)?,
+        _ => return Err(DratError::UnknownFormat(format.to_owned()).into()),
+    };
+
+    let mut atoms: IndexMap<Rc<Term>, i64> = IndexMap::new();
+    let mut formula: Vec<Clause> = premises
+        .iter()
+        .map(|p| p.clause.iter().map(|l| literal(&mut atoms, l)).collect())
+        .collect();
+    formula.extend(conclusion.iter().map(|l| vec![-literal(&mut atoms, l)]));
+
+    trace_value(|| {
+        let atoms: Vec<_> = atoms.iter().map(|(a, v)| format!("{}: {}", v, a)).collect();
+        format!("variables: {}", atoms.join(", "))
+    });
+    if format.starts_with("drat") {
+        check_drat(formula, &certificate)?;
+    } else {
+        check_lrat(formula, &certificate)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checker::error::DratError;
+
+    #[test]
+    fn drat_refutation() {
+        test_cases! {
+            definitions = "
+                (declare-fun p () Bool)
+                (declare-fun q () Bool)
+            ",
+            "DRAT certificates" {
+                "(assume h1 (or p q)) (assume h2 (or (not p) q))
+                (assume h3 (or p (not q))) (assume h4 (or (not p) (not q)))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step c2 (cl (not p) q) :rule or :premises (h2))
+                (step c3 (cl p (not q)) :rule or :premises (h3))
+                (step c4 (cl (not p) (not q)) :rule or :premises (h4))
+                (step t5 (cl) :rule drat_refutation :premises (c1 c2 c3 c4) :args (\"drat\" \"1 0 0\"))": true,
+
+                "(assume h1 (or p q)) (assume h2 (or (not p) q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step c2 (cl (not p) q) :rule or :premises (h2))
+                (step t3 (cl q) :rule drat_refutation :premises (c1 c2) :args (\"drat\" \"0\"))": true,
+
+                "(assume h1 (or p q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step t2 (cl p q) :rule drat_refutation :premises (c1)
+                    :args (\"drat\" \"c extension variable\n3 0\nd 3 0\n0\"))": true,
+
+                "(assume h1 (or p q)) (assume h2 (or (not p) q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step c2 (cl (not p) q) :rule or :premises (h2))
+                (step t3 (cl) :rule drat_refutation :premises (c1 c2) :args (\"drat\" \"2 0 0\"))": false,
+
+                "(assume h1 (or p q)) (assume h2 (or (not p) q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step c2 (cl (not p) q) :rule or :premises (h2))
+                (step t3 (cl) :rule drat_refutation :premises (c1 c2) :args (\"drat\" \"-2 0 0\"))": false,
+
+                "(assume h1 (or p q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step t2 (cl) :rule drat_refutation :premises (c1) :args (\"drat\" \"1 x 0\"))": false,
+            }
+            "LRAT certificates" {
+                "(assume h1 (or p q)) (assume h2 (or (not p) q))
+                (assume h3 (or p (not q))) (assume h4 (or (not p) (not q)))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step c2 (cl (not p) q) :rule or :premises (h2))
+                (step c3 (cl p (not q)) :rule or :premises (h3))
+                (step c4 (cl (not p) (not q)) :rule or :premises (h4))
+                (step t5 (cl) :rule drat_refutation :premises (c1 c2 c3 c4)
+                    :args (\"lrat\" \"5 1 0 1 3 0\n5 d 3 0\n6 0 5 2 4 0\"))": true,
+
+                "(assume h1 (or p q)) (assume h2 (or (not p) q))
+                (assume h3 (or p (not q))) (assume h4 (or (not p) (not q)))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step c2 (cl (not p) q) :rule or :premises (h2))
+                (step c3 (cl p (not q)) :rule or :premises (h3))
+                (step c4 (cl (not p) (not q)) :rule or :premises (h4))
+                (step t5 (cl) :rule drat_refutation :premises (c1 c2 c3 c4)
+                    :args (\"lrat\" \"5 1 0 1 0\n6 0 5 2 4 0\"))": false,
+
+                "(assume h1 (or p q)) (assume h2 (or (not p) q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step c2 (cl (not p) q) :rule or :premises (h2))
+                (step t3 (cl q) :rule drat_refutation :premises (c1 c2)
+                    :args (\"lrat\" \"4 0 3 1 2 0\"))": true,
+
+                "(assume h1 (or p q)) (assume h2 (or (not p) q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step c2 (cl (not p) q) :rule or :premises (h2))
+                (step t3 (cl q) :rule drat_refutation :premises (c1 c2)
+                    :args (\"lrat\" \"2 0 1 2 3 0\"))": false,
+
+                "(assume h1 (or p q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step t2 (cl p q) :rule drat_refutation :premises (c1)
+                    :args (\"lrat\" \"4 3 0 0\n5 0 2 3 1 0\"))": true,
+            }
+            "Invalid arguments" {
+                "(assume h1 (or p q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step t2 (cl p q) :rule drat_refutation :premises (c1) :args (\"dpr\" \"0\"))": false,
+
+                "(assume h1 (or p q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step t2 (cl p q) :rule drat_refutation :premises (c1)
+                    :args (\"drat-file\" \"/nonexistent/certificate.drat\"))": false,
+
+                "(assume h1 (or p q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step t2 (cl p q) :rule drat_refutation :premises (c1) :args (\"drat\"))": false,
+            }
+        }
+    }
+
+    #[test]
+    fn certificate_files() {
+        let dir = std::env::temp_dir().join(format!("carcara-drat-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("certificate.drat"), "0\n").unwrap();
+
+        test_cases! {
+            definitions = "
+                (declare-fun p () Bool)
+                (declare-fun q () Bool)
+            ",
+            config = crate::checker::Config::new().certificate_dir(Some(dir.clone())),
+            "Files are resolved against the certificate directory" {
+                "(assume h1 (or p q)) (assume h2 (or (not p) q))
+                (step c1 (cl p q) :rule or :premises (h1))
+                (step c2 (cl (not p) q) :rule or :premises (h2))
+                (step t3 (cl q) :rule drat_refutation :premises (c1 c2)
+                    :args (\"drat-file\" \"certificate.drat\"))": true,
+            }
+        }
+
+        // Files are not read by default, and only regular files are read
+        let path = dir.join("certificate.drat");
+        assert!(matches!(
+            super::read_certificate(None, path.to_str().unwrap()),
+            Err(DratError::FilesNotAllowed(_))
+        ));
+        assert!(matches!(
+            super::read_certificate(Some(&dir), "."),
+            Err(DratError::NotARegularFile(_))
+        ));
+
+        // Files outside of the certificate directory are never read
+        let inner = dir.join("inner");
+        std::fs::create_dir_all(&inner).unwrap();
+        assert!(matches!(
+            super::read_certificate(Some(&inner), "../certificate.drat"),
+            Err(DratError::OutsideCertificateDir(_))
+        ));
+        assert!(matches!(
+            super::read_certificate(Some(&inner), path.to_str().unwrap()),
+            Err(DratError::OutsideCertificateDir(_))
+        ));
+        assert!(super::read_certificate(Some(&dir), "inner/../certificate.drat").is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
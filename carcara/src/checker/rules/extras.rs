@@ -24,26 +24,43 @@ pub fn reordering(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult
     }
 }
 
+/// If `term` is an application of a symmetric binary predicate, returns the operator and its two
+/// arguments. Besides equalities (which include `iff`, since it is represented as an equality
+/// between booleans), this accepts `xor` and `distinct` with two arguments.
+fn match_symmetric(term: &Rc<Term>) -> Result<(Operator, &Rc<Term>, &Rc<Term>), CheckerError> {
+    match term.as_ref() {
+        Term::Op(op @ (Operator::Equals | Operator::Xor | Operator::Distinct), args)
+            if args.len() == 2 =>
+        {
+            Ok((*op, &args[0], &args[1]))
+        }
+        _ => Err(CheckerError::TermOfWrongForm("(= p q)", term.clone())),
+    }
+}
+
+fn check_symmetric(premise: &Rc<Term>, conclusion: &Rc<Term>) -> RuleResult {
+    let (op_1, p_1, q_1) = match_symmetric(premise)?;
+    let (op_2, q_2, p_2) = match_symmetric(conclusion)?;
+    if op_1 != op_2 {
+        return Err(CheckerError::TermOfWrongForm("(= q p)", conclusion.clone()));
+    }
+    assert_eq(p_1, p_2)?;
+    assert_eq(q_1, q_2)
+}
+
 pub fn symm(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
     assert_num_premises(premises, 1)?;
     assert_clause_len(conclusion, 1)?;
 
-    let premise = get_premise_term(&premises[0])?;
-    let (p_1, q_1) = match_term_err!((= p q) = premise)?;
-    let (q_2, p_2) = match_term_err!((= q p) = &conclusion[0])?;
-    assert_eq(p_1, p_2)?;
-    assert_eq(q_1, q_2)
+    check_symmetric(get_premise_term(&premises[0])?, &conclusion[0])
 }
 
 pub fn not_symm(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
     assert_num_premises(premises, 1)?;
     assert_clause_len(conclusion, 1)?;
 
-    let premise = get_premise_term(&premises[0])?;
-    let (p_1, q_1) = match_term_err!((not (= p q)) = premise)?;
-    let (q_2, p_2) = match_term_err!((not (= q p)) = &conclusion[0])?;
-    assert_eq(p_1, p_2)?;
-    assert_eq(q_1, q_2)
+    let premise = get_premise_term(&premises[0])?.remove_negation_err()?;
+    check_symmetric(premise, conclusion[0].remove_negation_err()?)
 }
 
 pub fn eq_symmetric(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
@@ -53,7 +70,24 @@ pub fn eq_symmetric(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_eq(u_1, u_2)
 }
 
+/// Checks that every literal in the premise also appears in the conclusion, in any order, and with at
+/// least the same number of occurrences.
 pub fn weakening(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
+    assert_num_premises(premises, 1)?;
+    let mut remaining: Vec<_> = conclusion.iter().collect();
+    for t in premises[0].clause {
+        match remaining.iter().position(|&u| u == t) {
+            Some(i) => {
+                remaining.swap_remove(i);
+            }
+            None => return Err(CheckerError::WeakeningMissingTerm(t.clone())),
+        }
+    }
+    Ok(())
+}
+
+/// A stricter version of `weakening` that requires the premise to be a prefix of the conclusion.
+pub fn strict_weakening(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
     assert_num_premises(premises, 1)?;
     let premise = premises[0].clause;
     assert_clause_len(conclusion, premise.len()..)?;
@@ -237,14 +271,35 @@ mod tests {
                 (declare-sort T 0)
                 (declare-fun a () T)
                 (declare-fun b () T)
+                (declare-fun p () Bool)
+                (declare-fun q () Bool)
             ",
             "Simple working examples" {
                 "(assume h1 (= a b))
                 (step t1 (cl (= b a)) :rule symm :premises (h1))": true,
             }
+            "Other symmetric predicates" {
+                "(assume h1 (= p q))
+                (step t1 (cl (= q p)) :rule symm :premises (h1))": true,
+
+                "(assume h1 (xor p q))
+                (step t1 (cl (xor q p)) :rule symm :premises (h1))": true,
+
+                "(assume h1 (distinct a b))
+                (step t1 (cl (distinct b a)) :rule symm :premises (h1))": true,
+            }
             "Failing examples" {
                 "(assume h1 (not (= a b)))
                 (step t1 (cl (not (= b a))) :rule symm :premises (h1))": false,
+
+                "(assume h1 (= p q))
+                (step t1 (cl (xor q p)) :rule symm :premises (h1))": false,
+
+                "(assume h1 (=> p q))
+                (step t1 (cl (=> q p)) :rule symm :premises (h1))": false,
+
+                "(assume h1 (distinct a b a))
+                (step t1 (cl (distinct a b a)) :rule symm :premises (h1))": false,
             }
         }
     }
@@ -256,14 +311,26 @@ mod tests {
                 (declare-sort T 0)
                 (declare-fun a () T)
                 (declare-fun b () T)
+                (declare-fun p () Bool)
+                (declare-fun q () Bool)
             ",
             "Simple working examples" {
                 "(assume h1 (not (= a b)))
                 (step t1 (cl (not (= b a))) :rule not_symm :premises (h1))": true,
             }
+            "Other symmetric predicates" {
+                "(assume h1 (not (= p q)))
+                (step t1 (cl (not (= q p))) :rule not_symm :premises (h1))": true,
+
+                "(assume h1 (not (xor p q)))
+                (step t1 (cl (not (xor q p))) :rule not_symm :premises (h1))": true,
+            }
             "Failing examples" {
                 "(assume h1 (= a b))
                 (step t1 (cl (= b a)) :rule not_symm :premises (h1))": false,
+
+                "(assume h1 (not (xor p q)))
+                (step t1 (cl (not (= q p))) :rule not_symm :premises (h1))": false,
             }
         }
     }
@@ -302,12 +369,25 @@ mod tests {
                 "(step t1 (cl) :rule hole)
                 (step t2 (cl a b) :rule weakening :premises (t1))": true,
             }
-            "Failing examples" {
+            "Premise literals in a different order" {
                 "(step t1 (cl a b) :rule hole)
-                (step t2 (cl a c b) :rule weakening :premises (t1))": false,
+                (step t2 (cl a c b) :rule weakening :premises (t1))": true,
 
+                "(step t1 (cl a b) :rule hole)
+                (step t2 (cl c b a) :rule weakening :premises (t1))": true,
+
+                "(step t1 (cl a b a) :rule hole)
+                (step t2 (cl a c b a) :rule weakening :premises (t1))": true,
+            }
+            "Failing examples" {
                 "(step t1 (cl a b c) :rule hole)
                 (step t2 (cl a b) :rule weakening :premises (t1))": false,
+
+                "(step t1 (cl a b) :rule hole)
+                (step t2 (cl a c) :rule weakening :premises (t1))": false,
+
+                "(step t1 (cl a a) :rule hole)
+                (step t2 (cl a b) :rule weakening :premises (t1))": false,
             }
         }
     }
@@ -0,0 +1,71 @@
+use super::{assert_clause_len, RuleArgs, RuleResult};
+use crate::{
+    ast::*,
+    checker::error::{CheckerError, EqualityError},
+    eval::evaluate,
+};
+
+fn is_ff_literal(term: &Rc<Term>) -> bool {
+    matches!(term.as_ref(), Term::Const(Constant::FiniteField(..)))
+}
+
+/// Checks the evaluation of a finite field operation over literals. The conclusion must be of the
+/// form `(= (op x_1 ... x_n) v)`, where each `x_i` is a finite field literal, `op` is one of
+/// `ff.add`, `ff.mul`, `ff.neg`, `ff.bitsum` or `=`, and `v` is the result of the operation.
+pub fn ff_eval(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let (t, result) = match_term_err!((= t result) = &conclusion[0])?;
+    let (op, args) = t.as_op_err()?;
+    let is_ff_op = matches!(
+        op,
+        Operator::FfAdd | Operator::FfMul | Operator::FfNeg | Operator::FfBitSum | Operator::Equals
+    );
+    if !is_ff_op || !args.iter().all(is_ff_literal) {
+        return Err(CheckerError::TermOfWrongForm(
+            "a finite field operation over literals",
+            t.clone(),
+        ));
+    }
+
+    // Since all arguments are literals of the same field, evaluation never fails
+    let expected = evaluate(t, pool).unwrap().to_term(pool);
+    if *result != expected {
+        return Err(EqualityError::ExpectedEqual(result.clone(), expected).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn ff_eval() {
+        test_cases! {
+            definitions = "
+                (declare-const x (_ FiniteField 5))
+            ",
+            "Arithmetic operations" {
+                "(step t1 (cl (= (ff.add #f3m5 #f4m5) #f2m5)) :rule ff_eval)": true,
+                "(step t1 (cl (= (ff.add #f1m5 #f1m5 #f1m5) #f3m5)) :rule ff_eval)": true,
+                "(step t1 (cl (= (ff.mul #f3m7 #f5m7) #f1m7)) :rule ff_eval)": true,
+                "(step t1 (cl (= (ff.neg #f1m5) #f4m5)) :rule ff_eval)": true,
+                "(step t1 (cl (= (ff.neg #f0m5) #f0m5)) :rule ff_eval)": true,
+                "(step t1 (cl (= (ff.bitsum #f1m7 #f1m7 #f1m7) #f0m7)) :rule ff_eval)": true,
+                "(step t1 (cl (= (ff.add #f3m5 #f4m5) #f7m5)) :rule ff_eval)": true,
+                "(step t1 (cl (= (ff.mul #f2m5 #f3m5) (as ff-4 (_ FiniteField 5)))) :rule ff_eval)": true,
+                "(step t1 (cl (= (ff.add #f3m5 #f4m5) #f3m5)) :rule ff_eval)": false,
+                "(step t1 (cl (= (ff.neg #f1m5) #f1m5)) :rule ff_eval)": false,
+            }
+            "Equalities" {
+                "(step t1 (cl (= (= #f1m5 #f6m5) true)) :rule ff_eval)": true,
+                "(step t1 (cl (= (= #f1m5 #f2m5) false)) :rule ff_eval)": true,
+                "(step t1 (cl (= (= #f1m5 #f2m5) true)) :rule ff_eval)": false,
+            }
+            "Terms that aren't operations over literals" {
+                "(step t1 (cl (= (ff.add x #f0m5) x)) :rule ff_eval)": false,
+                "(step t1 (cl (= (+ 1 2) 3)) :rule ff_eval)": false,
+                "(step t1 (cl (= #f1m5 #f1m5)) :rule ff_eval)": false,
+            }
+        }
+    }
+}
@@ -0,0 +1,566 @@
+use super::{assert_clause_len, assert_is_bool_constant, RuleArgs, RuleResult};
+use crate::{
+    ast::*,
+    checker::error::{CheckerError, EqualityError},
+};
+use rug::{Integer, Rational};
+use std::cmp::Ordering;
+
+/// The largest exponent width supported by the checker. Wider exponents would make the unbiased
+/// exponents of some values overflow the integer types used to compute them.
+const MAX_EXPONENT_WIDTH: u32 = 32;
+
+/// Returns `true` if the checker supports floating-point values with exponent width `eb` and
+/// significand width `sb`. SMT-LIB requires both widths to be greater than one.
+fn is_supported_format(eb: u32, sb: u32) -> bool {
+    (2..=MAX_EXPONENT_WIDTH).contains(&eb) && sb > 1
+}
+
+/// A floating-point value, decoded from a literal term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FpValue {
+    exponent_width: u32,
+    significand_width: u32,
+    negative: bool,
+
+    /// The biased exponent, as stored in the literal.
+    exponent: Integer,
+
+    /// The significand, without the hidden bit.
+    significand: Integer,
+}
+
+impl FpValue {
+    /// Decodes a floating-point literal, either of the form `(fp s e m)`, where `s`, `e` and `m`
+    /// are bit-vector constants, or one of the special constants `(_ +zero eb sb)`,
+    /// `(_ -zero eb sb)`, `(_ +oo eb sb)`, `(_ -oo eb sb)` or `(_ NaN eb sb)`. Returns `None` if
+    /// the literal is of an unsupported format.
+    fn from_term(term: &Rc<Term>) -> Option<Self> {
+        let value = match term.as_ref() {
+            Term::Op(Operator::Fp, args) => {
+                let [sign, exponent, significand] = args.as_slice() else {
+                    return None;
+                };
+                let [sign, exponent, significand] =
+                    [sign, exponent, significand].map(|a| match a.as_ref() {
+                        Term::Const(Constant::BitVec(v, w)) => Some((v.clone(), w.to_u32()?)),
+                        _ => None,
+                    });
+                let ((sign, _), (exponent, exponent_width), (significand, m)) =
+                    (sign?, exponent?, significand?);
+                Self {
+                    exponent_width,
+                    significand_width: m.checked_add(1)?,
+                    negative: sign == 1,
+                    exponent,
+                    significand,
+                }
+            }
+            Term::ParamOp { op, op_args, args } if args.is_empty() && op_args.len() == 2 => {
+                let exponent_width = op_args[0].as_integer()?.to_u32()?;
+                let significand_width = op_args[1].as_integer()?.to_u32()?;
+                let max_exponent = (Integer::from(1) << exponent_width) - 1u32;
+                let (negative, exponent, significand) = match op {
+                    ParamOperator::FpPosZero => (false, Integer::new(), 0),
+                    ParamOperator::FpNegZero => (true, Integer::new(), 0),
+                    ParamOperator::FpPosInfinity => (false, max_exponent, 0),
+                    ParamOperator::FpNegInfinity => (true, max_exponent, 0),
+                    ParamOperator::FpNaN => (false, max_exponent, 1),
+                    _ => return None,
+                };
+                Self {
+                    exponent_width,
+                    significand_width,
+                    negative,
+                    exponent,
+                    significand: significand.into(),
+                }
+            }
+            _ => return None,
+        };
+        is_supported_format(value.exponent_width, value.significand_width).then_some(value)
+    }
+
+    fn to_term(&self, pool: &mut dyn TermPool) -> Rc<Term> {
+        let sign = pool.add(Term::new_bv(u8::from(self.negative), 1));
+        let exponent = pool.add(Term::new_bv(self.exponent.clone(), self.exponent_width));
+        let significand = pool.add(Term::new_bv(
+            self.significand.clone(),
+            self.significand_width - 1,
+        ));
+        pool.add(Term::Op(Operator::Fp, vec![sign, exponent, significand]))
+    }
+
+    fn has_max_exponent(&self) -> bool {
+        self.exponent == (Integer::from(1) << self.exponent_width) - 1u32
+    }
+
+    fn is_nan(&self) -> bool {
+        self.has_max_exponent() && self.significand != 0
+    }
+
+    fn is_infinite(&self) -> bool {
+        self.has_max_exponent() && self.significand == 0
+    }
+
+    fn is_zero(&self) -> bool {
+        self.exponent == 0 && self.significand == 0
+    }
+
+    fn is_subnormal(&self) -> bool {
+        self.exponent == 0 && self.significand != 0
+    }
+
+    fn is_normal(&self) -> bool {
+        self.exponent != 0 && !self.has_max_exponent()
+    }
+
+    /// Returns `true` if the two values are equal according to the SMT-LIB `=` operator. All NaN
+    /// literals denote the same value, but the two zeros are different.
+    fn same_value(&self, other: &Self) -> bool {
+        if self.exponent_width != other.exponent_width
+            || self.significand_width != other.significand_width
+        {
+            return false;
+        }
+        (self.is_nan() && other.is_nan()) || self == other
+    }
+
+    /// Returns the bias of the exponent, and the minimum and maximum unbiased exponents of normal
+    /// values.
+    fn exponent_range(exponent_width: u32) -> (i64, i64, i64) {
+        let bias = (1i64 << (exponent_width - 1)) - 1;
+        (bias, 1 - bias, bias)
+    }
+
+    /// Returns the real number represented by the value, or `None` if it is infinite or NaN.
+    fn to_rational(&self) -> Option<Rational> {
+        if self.has_max_exponent() {
+            return None;
+        }
+        let (bias, min_exponent, _) = Self::exponent_range(self.exponent_width);
+        let precision = i64::from(self.significand_width);
+        let (mantissa, exponent) = if self.exponent == 0 {
+            (self.significand.clone(), min_exponent)
+        } else {
+            let hidden_bit = Integer::from(1) << (self.significand_width - 1);
+            (
+                self.significand.clone() + hidden_bit,
+                self.exponent.to_i64()? - bias,
+            )
+        };
+        let magnitude = Rational::from(mantissa) * pow2(exponent - (precision - 1));
+        Some(if self.negative { -magnitude } else { magnitude })
+    }
+
+    /// Rounds a real number to the nearest floating-point value of the given format, according to
+    /// the rounding mode `rm`.
+    fn from_rational(
+        value: &Rational,
+        rm: Operator,
+        exponent_width: u32,
+        significand_width: u32,
+    ) -> Self {
+        let negative = *value < 0;
+        let (bias, min_exponent, max_exponent) = Self::exponent_range(exponent_width);
+        let precision = i64::from(significand_width);
+        let make = |exponent: Integer, significand: Integer| Self {
+            exponent_width,
+            significand_width,
+            negative,
+            exponent,
+            significand,
+        };
+        if *value == 0 {
+            return make(Integer::new(), Integer::new());
+        }
+
+        // Values below the normal range are represented with the minimum exponent, and lose
+        // precision
+        let magnitude = value.clone().abs();
+        let exponent = floor_log2(&magnitude).max(min_exponent);
+        let scaled = magnitude / pow2(exponent - (precision - 1));
+        let mantissa = round_to_integer(scaled, rm, negative);
+
+        // Rounding up may carry into a new bit, in which case the exponent increases
+        let hidden_bit = Integer::from(1) << (significand_width - 1);
+        let (mantissa, exponent) = if mantissa == Integer::from(&hidden_bit << 1) {
+            (hidden_bit.clone(), exponent + 1)
+        } else {
+            (mantissa, exponent)
+        };
+
+        let max_biased = (Integer::from(1) << exponent_width) - 1u32;
+        if exponent > max_exponent {
+            let to_infinity = match rm {
+                Operator::RoundTowardPositive => !negative,
+                Operator::RoundTowardNegative => negative,
+                Operator::RoundTowardZero => false,
+                _ => true,
+            };
+            return if to_infinity {
+                make(max_biased, Integer::new())
+            } else {
+                make(max_biased - 1u32, hidden_bit - 1u32)
+            };
+        }
+        if mantissa < hidden_bit {
+            make(Integer::new(), mantissa)
+        } else {
+            make(Integer::from(exponent + bias), mantissa - hidden_bit)
+        }
+    }
+
+    /// Decodes a bit-vector of width `eb + sb` as a floating-point value with exponent width `eb`
+    /// and significand width `sb`.
+    fn from_bits(bits: &Integer, exponent_width: u32, significand_width: u32) -> Self {
+        let significand_mask = (Integer::from(1) << (significand_width - 1)) - 1u32;
+        let exponent_mask = (Integer::from(1) << exponent_width) - 1u32;
+        let exponent = Integer::from(bits >> (significand_width - 1)) & exponent_mask;
+        Self {
+            exponent_width,
+            significand_width,
+            negative: bits.get_bit(exponent_width + significand_width - 1),
+            exponent,
+            significand: Integer::from(bits & &significand_mask),
+        }
+    }
+
+    /// Compares the two values according to the IEEE 754 ordering. Returns `None` if either of
+    /// them is NaN. Both zeros are considered equal.
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        if self.is_nan() || other.is_nan() {
+            return None;
+        }
+        // Since the exponent is biased, the magnitude of a value is monotonic in the integer
+        // formed by concatenating its exponent and significand bits
+        let key = |v: &Self| {
+            let magnitude =
+                (v.exponent.clone() << (v.significand_width - 1)) + v.significand.clone();
+            if v.negative {
+                -magnitude
+            } else {
+                magnitude
+            }
+        };
+        Some(key(self).cmp(&key(other)))
+    }
+}
+
+/// Returns `2^exponent`.
+fn pow2(exponent: i64) -> Rational {
+    let power = Integer::from(1) << exponent.unsigned_abs() as u32;
+    if exponent >= 0 {
+        Rational::from(power)
+    } else {
+        Rational::from((Integer::from(1), power))
+    }
+}
+
+/// Returns the largest `e` such that `2^e <= x`. The value `x` must be positive.
+fn floor_log2(x: &Rational) -> i64 {
+    let bits = |i: &Integer| i64::from(i.significant_bits());
+    let e = bits(x.numer()) - bits(x.denom());
+    if *x < pow2(e) {
+        e - 1
+    } else {
+        e
+    }
+}
+
+/// Rounds a non-negative number to an integer, according to the rounding mode `rm`. Since the
+/// direction of some rounding modes depends on the sign, `negative` indicates whether the number
+/// is the magnitude of a negative value.
+fn round_to_integer(x: Rational, rm: Operator, negative: bool) -> Integer {
+    let floor = x.clone().floor().into_numer_denom().0;
+    let fraction = x - &floor;
+    let half = Rational::from((1, 2));
+    let round_up = match rm {
+        Operator::RoundNearestTiesToEven => fraction > half || (fraction == half && floor.is_odd()),
+        Operator::RoundNearestTiesToAway => fraction >= half,
+        Operator::RoundTowardPositive => fraction != 0 && !negative,
+        Operator::RoundTowardNegative => fraction != 0 && negative,
+        _ => false,
+    };
+    if round_up {
+        floor + 1u32
+    } else {
+        floor
+    }
+}
+
+fn rounding_mode(term: &Rc<Term>) -> Result<Operator, CheckerError> {
+    match term.as_op() {
+        Some((
+            op @ (Operator::RoundNearestTiesToEven
+            | Operator::RoundNearestTiesToAway
+            | Operator::RoundTowardPositive
+            | Operator::RoundTowardNegative
+            | Operator::RoundTowardZero),
+            [],
+        )) => Ok(op),
+        _ => Err(CheckerError::TermOfWrongForm(
+            "a rounding mode",
+            term.clone(),
+        )),
+    }
+}
+
+fn fp_literal(term: &Rc<Term>) -> Result<FpValue, CheckerError> {
+    FpValue::from_term(term)
+        .ok_or_else(|| CheckerError::TermOfWrongForm("a floating-point literal", term.clone()))
+}
+
+/// Checks the evaluation of a floating-point operation over literals. The conclusion must be of
+/// the form `(= (op x_1 ... x_n) v)`, where each `x_i` is a floating-point literal, and `v` is the
+/// result of the operation. Currently, the classification predicates, the comparisons, the
+/// `fp.neg` and `fp.abs` operators, and the conversions to and from reals, bit-vectors and other
+/// floating-point formats are supported.
+pub fn fp_eval(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let (t, result) = match_term_err!((= t result) = &conclusion[0])?;
+    if let Term::ParamOp { op, op_args, args } = t.as_ref() {
+        return check_conversion(pool, t, *op, op_args, args, result);
+    }
+    let (op, args) = t.as_op_err()?;
+    let values = args.iter().map(fp_literal).collect::<Result<Vec<_>, _>>()?;
+
+    if let (Operator::FpToReal, [x]) = (op, values.as_slice()) {
+        // The value of `fp.to_real` is unspecified for infinities and NaN
+        let expected = x.to_rational().ok_or_else(|| {
+            CheckerError::TermOfWrongForm("a finite floating-point literal", args[0].clone())
+        })?;
+        if result.as_fraction_err()? != expected {
+            return Err(CheckerError::ExpectedNumber(expected, result.clone()));
+        }
+        return Ok(());
+    }
+
+    let expected = match (op, values.as_slice()) {
+        (Operator::FpIsNormal, [x]) => x.is_normal(),
+        (Operator::FpIsSubnormal, [x]) => x.is_subnormal(),
+        (Operator::FpIsZero, [x]) => x.is_zero(),
+        (Operator::FpIsInfinite, [x]) => x.is_infinite(),
+        (Operator::FpIsNaN, [x]) => x.is_nan(),
+        (Operator::FpIsNegative, [x]) => !x.is_nan() && x.negative,
+        (Operator::FpIsPositive, [x]) => !x.is_nan() && !x.negative,
+        (Operator::FpEq, [x, y]) => x.compare(y) == Some(Ordering::Equal),
+        (Operator::FpLt, [x, y]) => x.compare(y) == Some(Ordering::Less),
+        (Operator::FpLeq, [x, y]) => matches!(x.compare(y), Some(Ordering::Less | Ordering::Equal)),
+        (Operator::FpGt, [x, y]) => x.compare(y) == Some(Ordering::Greater),
+        (Operator::FpGeq, [x, y]) => {
+            matches!(x.compare(y), Some(Ordering::Greater | Ordering::Equal))
+        }
+        (Operator::FpNeg | Operator::FpAbs, [x]) => {
+            let mut expected = x.clone();
+            if !x.is_nan() {
+                expected.negative = op == Operator::FpNeg && !x.negative;
+            }
+            if !fp_literal(result)?.same_value(&expected) {
+                let expected = expected.to_term(pool);
+                return Err(EqualityError::ExpectedEqual(result.clone(), expected).into());
+            }
+            return Ok(());
+        }
+        _ => {
+            return Err(CheckerError::TermOfWrongForm(
+                "a floating-point operation over literals",
+                t.clone(),
+            ))
+        }
+    };
+    assert_is_bool_constant(result, expected)
+}
+
+/// Checks the evaluation of a conversion from or to a floating-point value, which are all written
+/// as indexed operators.
+fn check_conversion(
+    pool: &mut dyn TermPool,
+    t: &Rc<Term>,
+    op: ParamOperator,
+    op_args: &[Rc<Term>],
+    args: &[Rc<Term>],
+    result: &Rc<Term>,
+) -> RuleResult {
+    let unsupported = || CheckerError::TermOfWrongForm("a floating-point conversion", t.clone());
+    let unspecified = || {
+        CheckerError::TermOfWrongForm(
+            "a floating-point conversion with a specified value",
+            t.clone(),
+        )
+    };
+    let widths = op_args
+        .iter()
+        .map(|a| a.as_integer().and_then(|i| i.to_u32()))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(unsupported)?;
+    if let (ParamOperator::ToFp | ParamOperator::ToFpUnsigned, &[eb, sb]) = (op, widths.as_slice())
+    {
+        if !is_supported_format(eb, sb) {
+            return Err(unsupported());
+        }
+    }
+
+    let expected = match (op, widths.as_slice(), args) {
+        (ParamOperator::ToFp, &[eb, sb], [bits]) => {
+            let Term::Const(Constant::BitVec(bits, _)) = bits.as_ref() else {
+                return Err(unsupported());
+            };
+            FpValue::from_bits(bits, eb, sb)
+        }
+        (ParamOperator::ToFp, &[eb, sb], [rm, x]) => {
+            let rm = rounding_mode(rm)?;
+            if let Some(x) = FpValue::from_term(x) {
+                match x.to_rational() {
+                    Some(r) if !x.is_zero() => FpValue::from_rational(&r, rm, eb, sb),
+                    // Zeros, infinities and NaN are converted to the same value in the new format
+                    _ => {
+                        let max_biased = (Integer::from(1) << eb) - 1u32;
+                        let exponent = if x.is_zero() {
+                            Integer::new()
+                        } else {
+                            max_biased
+                        };
+                        let significand = Integer::from(x.is_nan());
+                        FpValue {
+                            exponent_width: eb,
+                            significand_width: sb,
+                            negative: x.negative,
+                            exponent,
+                            significand,
+                        }
+                    }
+                }
+            } else if let Term::Const(Constant::BitVec(v, w)) = x.as_ref() {
+                // The bit-vector is interpreted as a signed integer
+                let w = w.to_u32().ok_or_else(unsupported)?;
+                let v = if w > 0 && v.get_bit(w - 1) {
+                    v.clone() - (Integer::from(1) << w)
+                } else {
+                    v.clone()
+                };
+                FpValue::from_rational(&Rational::from(v), rm, eb, sb)
+            } else {
+                let value = x.as_fraction().ok_or_else(unsupported)?;
+                FpValue::from_rational(&value, rm, eb, sb)
+            }
+        }
+        (ParamOperator::ToFpUnsigned, &[eb, sb], [rm, x]) => {
+            let rm = rounding_mode(rm)?;
+            let Term::Const(Constant::BitVec(v, _)) = x.as_ref() else {
+                return Err(unsupported());
+            };
+            FpValue::from_rational(&Rational::from(v), rm, eb, sb)
+        }
+        (ParamOperator::FpToUbv | ParamOperator::FpToSbv, &[width], [rm, x]) => {
+            let rm = rounding_mode(rm)?;
+            let value = fp_literal(x)?.to_rational().ok_or_else(unspecified)?;
+            let negative = value < 0;
+            let magnitude = round_to_integer(value.abs(), rm, negative);
+            let n = if negative { -magnitude } else { magnitude };
+            let (min, max) = if op == ParamOperator::FpToUbv {
+                (Integer::new(), Integer::from(1) << width)
+            } else {
+                let half = Integer::from(1) << (width - 1);
+                (-half.clone(), half)
+            };
+            if n < min || n >= max {
+                return Err(unspecified());
+            }
+            let n = if n < 0 {
+                n + (Integer::from(1) << width)
+            } else {
+                n
+            };
+            let expected = pool.add(Term::new_bv(n, width));
+            if *result != expected {
+                return Err(EqualityError::ExpectedEqual(result.clone(), expected).into());
+            }
+            return Ok(());
+        }
+        _ => return Err(unsupported()),
+    };
+    if !fp_literal(result)?.same_value(&expected) {
+        let expected = expected.to_term(pool);
+        return Err(EqualityError::ExpectedEqual(result.clone(), expected).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn fp_eval() {
+        test_cases! {
+            definitions = "",
+            "Classification predicates" {
+                "(step t1 (cl (= (fp.isNaN (_ NaN 2 3)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.isNaN (fp #b0 #b11 #b01)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.isInfinite (fp #b1 #b11 #b00)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.isZero (_ -zero 2 3)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.isSubnormal (fp #b0 #b00 #b10)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.isNormal (fp #b0 #b01 #b10)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.isNormal (fp #b0 #b00 #b10)) false)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.isNegative (_ -oo 2 3)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.isNegative (_ NaN 2 3)) false)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.isPositive (_ NaN 2 3)) false)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.isZero (_ +oo 2 3)) true)) :rule fp_eval)": false,
+            }
+            "Comparisons" {
+                "(step t1 (cl (= (fp.eq (_ +zero 2 3) (_ -zero 2 3)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.eq (_ NaN 2 3) (_ NaN 2 3)) false)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.lt (_ -oo 2 3) (fp #b1 #b01 #b11)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.lt (fp #b1 #b10 #b00) (fp #b1 #b01 #b11)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.leq (fp #b0 #b01 #b11) (fp #b0 #b10 #b00)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.gt (_ NaN 2 3) (_ -oo 2 3)) false)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.geq (_ +zero 2 3) (_ -zero 2 3)) true)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.gt (_ +zero 2 3) (_ -zero 2 3)) true)) :rule fp_eval)": false,
+            }
+            "Negation and absolute value" {
+                "(step t1 (cl (= (fp.neg (_ +zero 2 3)) (_ -zero 2 3))) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.neg (fp #b0 #b01 #b10)) (fp #b1 #b01 #b10))) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.abs (_ -oo 2 3)) (_ +oo 2 3))) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.neg (_ NaN 2 3)) (fp #b1 #b11 #b11))) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.neg (_ +zero 2 3)) (_ +zero 2 3))) :rule fp_eval)": false,
+                "(step t1 (cl (= (fp.abs (fp #b1 #b01 #b10)) (fp #b1 #b01 #b10))) :rule fp_eval)": false,
+            }
+            "Conversion to reals" {
+                "(step t1 (cl (= (fp.to_real (fp #b0 #b01 #b10)) 1.5)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.to_real (fp #b1 #b00 #b01)) (- 0.25))) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.to_real (fp #b0 #b10 #b11)) (/ 7.0 2.0))) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.to_real (_ -zero 2 3)) 0.0)) :rule fp_eval)": true,
+                "(step t1 (cl (= (fp.to_real (fp #b0 #b01 #b10)) 1.0)) :rule fp_eval)": false,
+                "(step t1 (cl (= (fp.to_real (_ +oo 2 3)) 0.0)) :rule fp_eval)": false,
+            }
+            "Conversion to floating-point" {
+                "(step t1 (cl (= ((_ to_fp 2 3) RNE 1.5) (fp #b0 #b01 #b10))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 2 3) RNE 1.125) (fp #b0 #b01 #b00))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 2 3) RTP 1.125) (fp #b0 #b01 #b01))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 2 3) RNA 1.125) (fp #b0 #b01 #b01))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 2 3) RTN (- 1.125)) (fp #b1 #b01 #b01))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 2 3) RNE 0.25) (fp #b0 #b00 #b01))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 2 3) RNE 100.0) (_ +oo 2 3))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 2 3) RTZ 100.0) (fp #b0 #b10 #b11))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 2 3) #b01010) (fp #b0 #b10 #b10))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 2 3) RNE #b110) (fp #b1 #b10 #b00))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp_unsigned 2 3) RNE #b110) (_ +oo 2 3))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 3 4) RNE (fp #b0 #b10 #b11)) (fp #b0 #b100 #b110))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 3 4) RNE (_ NaN 2 3)) (_ NaN 3 4))) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ to_fp 2 3) RNE 1.125) (fp #b0 #b01 #b01))) :rule fp_eval)": false,
+            }
+            "Conversion to bit-vectors" {
+                "(step t1 (cl (= ((_ fp.to_ubv 3) RNE (fp #b0 #b10 #b11)) #b100)) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ fp.to_ubv 3) RTZ (fp #b0 #b10 #b11)) #b011)) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ fp.to_sbv 3) RNE (fp #b1 #b10 #b00)) #b110)) :rule fp_eval)": true,
+                "(step t1 (cl (= ((_ fp.to_ubv 3) RNE (fp #b1 #b10 #b00)) #b110)) :rule fp_eval)": false,
+                "(step t1 (cl (= ((_ fp.to_sbv 2) RNE (fp #b0 #b10 #b11)) #b11)) :rule fp_eval)": false,
+            }
+            "Unsupported operations" {
+                "(step t1 (cl (= (fp.add RNE (_ +zero 2 3) (_ +zero 2 3)) (_ +zero 2 3))) :rule fp_eval)": false,
+                "(step t1 (cl (= ((_ to_fp 70 2) RNE 1.0) (_ +zero 70 2))) :rule fp_eval)": false,
+                "(step t1 (cl (= (fp.to_real (fp #b0 #x0000000000000001 #b1)) 1.0)) :rule fp_eval)": false,
+            }
+        }
+    }
+}
@@ -2,9 +2,10 @@ use super::{assert_clause_len, assert_eq, assert_num_args, RuleArgs, RuleResult}
 use crate::{
     ast::*,
     checker::error::{CheckerError, LinearArithmeticError},
+    linear::{as_comparison, LinearAtom, LinearComb},
 };
-use indexmap::{map::Entry, IndexMap};
-use rug::{ops::NegAssign, Integer, Rational};
+use indexmap::IndexMap;
+use rug::{Integer, Rational};
 
 pub fn la_rw_eq(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 1)?;
@@ -18,186 +19,20 @@ pub fn la_rw_eq(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_eq(u_2, u_3)
 }
 
-/// Takes a disequality term and returns its negation, represented by an operator and two linear
-/// combinations.
-/// The disequality can be:
+/// Takes a disequality term and returns its negation, in canonical linear form. The disequality
+/// can be:
 ///
 /// - An application of the `<`, `>`, `<=` or `>=` operators
 /// - The negation of an application of one of these operators
 /// - The negation of an application of the `=` operator
-fn negate_disequality(term: &Rc<Term>) -> Result<(Operator, LinearComb, LinearComb), CheckerError> {
-    use Operator::*;
-
-    fn negate_operator(op: Operator) -> Option<Operator> {
-        Some(match op {
-            LessThan => GreaterEq,
-            GreaterThan => LessEq,
-            LessEq => GreaterThan,
-            GreaterEq => LessThan,
-            _ => return None,
-        })
-    }
-
-    fn inner(term: &Rc<Term>) -> Option<(Operator, &[Rc<Term>])> {
-        if let Some(Term::Op(op, args)) = term.remove_negation().map(Rc::as_ref) {
-            if matches!(op, GreaterEq | LessEq | GreaterThan | LessThan | Equals) {
-                return Some((*op, args));
-            }
-        } else if let Term::Op(op, args) = term.as_ref() {
-            return Some((negate_operator(*op)?, args));
-        }
-        None
-    }
-
-    let (op, args) =
-        inner(term).ok_or_else(|| LinearArithmeticError::InvalidDisequalityOp(term.clone()))?;
-
-    match args {
-        [a, b] => Ok((op, LinearComb::from_term(a), LinearComb::from_term(b))),
-        _ => Err(LinearArithmeticError::TooManyArgsInDisequality(term.clone()).into()),
-    }
-}
-
-/// A linear combination, represented by a hash map from non-constant terms to their coefficients,
-/// plus a constant term. This is also used to represent a disequality, in which case the left side
-/// is the non-constant terms and their coefficients, and the right side is the constant term.
-#[derive(Debug)]
-pub struct LinearComb(pub(crate) IndexMap<Rc<Term>, Rational>, pub(crate) Rational);
-
-impl LinearComb {
-    fn new() -> Self {
-        Self(IndexMap::new(), Rational::new())
-    }
-
-    /// Flattens a term and adds it to the linear combination, multiplying by the coefficient
-    /// `coeff`. This method is only intended to be used in `LinearComb::from_term`.
-    fn add_term(&mut self, term: &Rc<Term>, coeff: &Rational) {
-        // A note on performance: this function traverses the term recursively without making use
-        // of a cache, which means sometimes it has to recompute the result for the same term more
-        // than once. However, an old implementation of this method that could use a cache showed
-        // that making use of one can actually make the performance of this function worse.
-        // Benchmarks showed that it would more than double the average time of the `la_generic`
-        // rule, which makes extensive use of `LinerComb`s. Because of that, we prefer to not use
-        // a cache here, and traverse the term naively.
-
-        match term.as_ref() {
-            Term::Op(Operator::Add, args) => {
-                for a in args {
-                    self.add_term(a, coeff);
-                }
-            }
-            Term::Op(Operator::Sub, args) if args.len() == 1 => {
-                self.add_term(&args[0], &coeff.as_neg());
-            }
-            Term::Op(Operator::Sub, args) => {
-                self.add_term(&args[0], coeff);
-                for a in &args[1..] {
-                    self.add_term(a, &coeff.as_neg());
-                }
-            }
-            Term::Op(Operator::Mult, args) if args.len() == 2 => {
-                let (var, mut inner_coeff) = match (args[0].as_fraction(), args[1].as_fraction()) {
-                    (None, Some(coeff)) => (&args[0], coeff),
-                    (Some(coeff), _) => (&args[1], coeff),
-                    (None, None) => return self.insert(term.clone(), coeff.clone()),
-                };
-                inner_coeff *= coeff;
-                self.add_term(var, &inner_coeff);
-            }
-            _ => {
-                if let Some(mut r) = term.as_fraction() {
-                    r *= coeff;
-                    self.1 += r;
-                } else {
-                    self.insert(term.clone(), coeff.clone());
-                }
-            }
-        }
-    }
-
-    /// Builds a linear combination from a term. Takes a term with nested additions, subtractions
-    /// and multiplications, and flattens it to linear combination, calculating the coefficient of
-    /// each atom.
-    fn from_term(term: &Rc<Term>) -> Self {
-        let mut result = Self::new();
-        result.add_term(term, &Rational::from(1));
-        result
-    }
-
-    fn insert(&mut self, key: Rc<Term>, value: Rational) {
-        match self.0.entry(key) {
-            Entry::Occupied(mut e) => {
-                *e.get_mut() += value;
-                if *e.get() == 0 {
-                    e.remove();
-                }
-            }
-            Entry::Vacant(e) => {
-                e.insert(value);
-            }
-        }
-    }
-
-    fn add(mut self, other: Self) -> Self {
-        for (var, coeff) in other.0 {
-            self.insert(var, coeff);
-        }
-        self.1 += other.1;
-        self
-    }
-
-    fn mul(&mut self, scalar: &Rational) {
-        if *scalar == 0 {
-            self.0.clear();
-            self.1 = Rational::new();
-            return;
-        }
-
-        if *scalar == 1 {
-            return;
-        }
-
-        for coeff in self.0.values_mut() {
-            *coeff *= scalar;
-        }
-        self.1 *= scalar;
-    }
-
-    fn neg(&mut self) {
-        for coeff in self.0.values_mut() {
-            coeff.neg_assign();
-        }
-        self.1.neg_assign();
-    }
-
-    fn sub(self, mut other: Self) -> Self {
-        other.neg();
-        self.add(other)
-    }
-
-    /// Finds the greatest common divisor of the coefficients in the linear combination. Returns
-    /// 1 if the linear combination is empty, or if any of the coefficients is not an integer.
-    fn coefficients_gcd(&self) -> Integer {
-        if !self.1.is_integer() {
-            return Integer::from(1);
-        }
-
-        let mut result = self.1.numer().clone();
-        for (_, coeff) in &self.0 {
-            if result == 1 {
-                return Integer::from(1);
-            }
-            if coeff.is_integer() {
-                result.gcd_mut(coeff.numer());
-            } else {
-                return Integer::from(1);
-            }
-        }
-
-        // If the linear combination is all zeros, the result would also be zero. In that case, we
-        // have to return one instead
-        std::cmp::max(Integer::from(1), result)
-    }
+fn negate_disequality(term: &Rc<Term>) -> Result<LinearAtom, CheckerError> {
+    let (negated, op, args) = as_comparison(term)
+        .ok_or_else(|| LinearArithmeticError::InvalidDisequalityOp(term.clone()))?;
+    let [a, b] = args else {
+        return Err(LinearArithmeticError::TooManyArgsInDisequality(term.clone()).into());
+    };
+    LinearAtom::from_literal(!negated, op, a, b)
+        .ok_or_else(|| LinearArithmeticError::InvalidDisequalityOp(term.clone()).into())
 }
 
 fn strengthen(op: Operator, disequality: &mut LinearComb, a: &Rational) -> Operator {
@@ -272,22 +107,9 @@ pub fn la_generic(RuleArgs { conclusion, args, .. }: RuleArgs) -> RuleResult {
         .iter()
         .zip(args)
         .map(|(phi, a)| -> Result<_, CheckerError> {
-            // Steps 1 and 2: Negate the disequality
-            let (mut op, s1, s2) = negate_disequality(phi)?;
-
-            // Step 3: Move all non constant terms to the left side, and the d terms to the right.
-            // We move everything to the left side by subtracting s2 from s1
-            let mut disequality = s1.sub(s2);
-            disequality.1 = -disequality.1; // We negate d to move it to the other side
-
-            // If the operator is < or <=, we flip the disequality so it is > or >=
-            if op == Operator::LessThan {
-                disequality.neg();
-                op = Operator::GreaterThan;
-            } else if op == Operator::LessEq {
-                disequality.neg();
-                op = Operator::GreaterEq;
-            }
+            // Steps 1 through 3: Negate the disequality, move all non constant terms to the left
+            // side and the d terms to the right, and flip it so the operator is > or >=
+            let LinearAtom { op, comb: mut disequality } = negate_disequality(phi)?;
 
             // Step 4: Apply strengthening rules
             let op = strengthen(op, &mut disequality, &a);
@@ -305,7 +127,7 @@ pub fn la_generic(RuleArgs { conclusion, args, .. }: RuleArgs) -> RuleResult {
             (Operator::Equals, LinearComb::new()),
             |(acc_op, acc), item| -> Result<_, CheckerError> {
                 let (op, diseq) = item?;
-                let new_acc = acc.add(diseq);
+                let new_acc = acc + diseq;
                 let new_op = match (acc_op, op) {
                     (_, Operator::GreaterEq) => Operator::GreaterEq,
                     (Operator::Equals, Operator::GreaterThan) => Operator::GreaterThan,
@@ -315,50 +137,158 @@ pub fn la_generic(RuleArgs { conclusion, args, .. }: RuleArgs) -> RuleResult {
             },
         )?;
 
-    let (op, LinearComb(left_side, right_side)) = &final_disequality;
-
-    let is_disequality_true = {
-        use std::cmp::Ordering;
-        use Operator::*;
-
-        // If the operator encompasses the actual relationship between 0 and the right side, the
-        // disequality is true
-        match Rational::new().cmp(right_side) {
-            Ordering::Less => matches!(op, LessThan | LessEq),
-            Ordering::Equal => matches!(op, LessEq | GreaterEq | Equals),
-            Ordering::Greater => matches!(op, GreaterThan | GreaterEq),
-        }
-    };
-
     // The left side must be empty (that is, equal to 0), and the final disequality must be
     // contradictory
+    let (op, comb) = final_disequality;
+    let final_disequality = LinearAtom { op, comb };
     rassert!(
-        left_side.is_empty() && !is_disequality_true,
-        LinearArithmeticError::DisequalityIsNotContradiction(*op, final_disequality.1),
+        final_disequality.is_contradiction(),
+        LinearArithmeticError::DisequalityIsNotContradiction(op, final_disequality.comb),
     );
     Ok(())
 }
 
+/// If `(<= a b)` is equivalent to `(<= t u)` by multiplying both sides by a positive rational and
+/// rearranging terms, returns that rational. This holds regardless of whether the terms are
+/// integers or reals.
+pub fn disequality_ratio(
+    (a, b): (&Rc<Term>, &Rc<Term>),
+    (t, u): (&Rc<Term>, &Rc<Term>),
+) -> Option<Rational> {
+    if a == t && b == u {
+        return Some(Rational::from(1));
+    }
+    let left = LinearAtom::new(Operator::LessEq, a, b)?;
+    let right = LinearAtom::new(Operator::LessEq, t, u)?;
+    left.ratio(&right)
+}
+
+/// Returns the disjuncts of a conclusion that may be given either as a single `or` term, or
+/// directly as a clause.
+fn disjuncts(conclusion: &[Rc<Term>]) -> &[Rc<Term>] {
+    match conclusion {
+        [t] => match_term!((or ...) = t).unwrap_or(conclusion),
+        _ => conclusion,
+    }
+}
+
 pub fn la_disequality(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
-    assert_clause_len(conclusion, 1)?;
+    let literals = disjuncts(conclusion);
+    assert_clause_len(literals, 3)?;
 
-    let ((t1_1, t2_1), (t1_2, t2_2), (t2_3, t1_3)) = match_term_err!(
-        (or (= t1 t2) (not (<= t1 t2)) (not (<= t2 t1))) = &conclusion[0]
-    )?;
-    assert_eq(t1_1, t1_2)?;
-    assert_eq(t1_2, t1_3)?;
-    assert_eq(t2_1, t2_2)?;
-    assert_eq(t2_2, t2_3)
+    let (t1, t2) = match_term_err!((= t1 t2) = &literals[0])?;
+    let (u1, u2) = match_term_err!((not (<= u1 u2)) = &literals[1])?;
+    let (v2, v1) = match_term_err!((not (<= v2 v1)) = &literals[2])?;
+
+    // The `<=` terms may be written differently from the equality, as long as they state the same
+    // disequalities, for example `(<= (* 2 t1) (* 2 t2))` or `(<= (- t1 t2) 0.0)`
+    for (a, b, t, u) in [(u1, u2, t1, t2), (v2, v1, t2, t1)] {
+        rassert!(
+            disequality_ratio((a, b), (t, u)).is_some(),
+            LinearArithmeticError::NotEquivalentDisequality(
+                literals[0].clone(),
+                a.clone(),
+                b.clone()
+            )
+        );
+    }
+    Ok(())
+}
+
+/// If the term is an upper bound, that is, states that `a < b` or `a <= b` using any of the
+/// comparison operators, possibly negated, returns whether the bound is strict, and the terms `a`
+/// and `b`.
+fn as_upper_bound(term: &Rc<Term>) -> Option<(bool, &Rc<Term>, &Rc<Term>)> {
+    use Operator::*;
+
+    let (negated, op, args) = match term.remove_negation().map(Rc::as_ref) {
+        Some(Term::Op(op, args)) => (true, *op, args),
+        Some(_) => return None,
+        None => match term.as_ref() {
+            Term::Op(op, args) => (false, *op, args),
+            _ => return None,
+        },
+    };
+    let [a, b] = args.as_slice() else {
+        return None;
+    };
+    match (negated, op) {
+        (false, LessThan) | (true, GreaterEq) => Some((true, a, b)),
+        (false, GreaterThan) | (true, LessEq) => Some((true, b, a)),
+        (false, LessEq) | (true, GreaterThan) => Some((false, a, b)),
+        (false, GreaterEq) | (true, LessThan) => Some((false, b, a)),
+        _ => None,
+    }
+}
+
+/// Checks that `literal` is a bound equivalent to `t < u`, if `strict` is true, or `t <= u`
+/// otherwise.
+fn assert_bound(literal: &Rc<Term>, strict: bool, (t, u): (&Rc<Term>, &Rc<Term>)) -> RuleResult {
+    let expected = if strict {
+        Operator::LessThan
+    } else {
+        Operator::LessEq
+    };
+    let is_equivalent = LinearAtom::from_term(literal)
+        .zip(LinearAtom::new(expected, t, u))
+        .is_some_and(|(actual, expected)| actual.is_equivalent(&expected));
+    rassert!(
+        is_equivalent,
+        LinearArithmeticError::NotEquivalentBound(literal.clone(), expected, t.clone(), u.clone())
+    );
+    Ok(())
 }
 
 pub fn la_totality(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
-    assert_clause_len(conclusion, 1)?;
+    let literals = disjuncts(conclusion);
+    assert_clause_len(literals, 2)?;
+
+    // The first literal determines the terms `t1` and `t2`, and the second literal must be
+    // equivalent to `t2 <= t1`
+    let (t1, t2) = match as_upper_bound(&literals[0]) {
+        Some((false, t1, t2)) => (t1, t2),
+        _ => return Err(LinearArithmeticError::InvalidDisequalityOp(literals[0].clone()).into()),
+    };
+    assert_bound(&literals[1], false, (t2, t1))
+}
+
+/// Checks a step of the form `(cl (< t1 t2) (= t1 t2) (< t2 t1))`, where the literals may appear in
+/// any order, and each bound may be written with any equivalent comparison.
+///
+/// This rule is a Carcara extension, and is not part of the Alethe specification. Proofs that use
+/// it can't be checked by other Alethe checkers, so the printer warns when it appears in the output.
+pub fn la_trichotomy(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
+    let literals = disjuncts(conclusion);
+    assert_clause_len(literals, 3)?;
 
-    let ((t1_1, t2_1), (t2_2, t1_2)) =
-        match_term_err!((or (<= t1 t2) (<= t2 t1)) = &conclusion[0])?;
+    // The equality may be in any position, and the two remaining literals must be equivalent to
+    // `t1 < t2` and `t2 < t1`, in any order
+    let Some(i) = literals
+        .iter()
+        .position(|l| match_term!((= t u) = l).is_some())
+    else {
+        return Err(CheckerError::TermOfWrongForm(
+            "(= t1 t2)",
+            literals[0].clone(),
+        ));
+    };
+    let (t1, t2) = match_term!((= t u) = &literals[i]).unwrap();
+    let mut bounds = literals
+        .iter()
+        .enumerate()
+        .filter(|&(j, _)| j != i)
+        .map(|(_, l)| l);
+    let (first, second) = (bounds.next().unwrap(), bounds.next().unwrap());
 
-    assert_eq(t1_1, t1_2)?;
-    assert_eq(t2_1, t2_2)
+    let first_is_less_than = as_upper_bound(first)
+        .is_some_and(|(_, a, b)| disequality_ratio((a, b), (t1, t2)).is_some());
+    let (t1, t2) = if first_is_less_than {
+        (t1, t2)
+    } else {
+        (t2, t1)
+    };
+    assert_bound(first, true, (t1, t2))?;
+    assert_bound(second, true, (t2, t1))
 }
 
 fn assert_less_than(a: &Rc<Term>, b: &Rc<Term>) -> RuleResult {
@@ -423,35 +353,610 @@ pub fn la_tautology(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     } else {
         // If the conclusion is of the first form, we apply steps 1 through 3 from `la_generic`
 
-        // Steps 1 and 2: Negate the disequality
-        let (mut op, s1, s2) = negate_disequality(&conclusion[0])?;
-
-        // Step 3: Move all non constant terms to the left side, and the d terms to the right.
-        let mut disequality = s1.sub(s2);
-        disequality.1 = -disequality.1;
-
-        // If the operator is < or <=, we flip the disequality so it is > or >=
-        if op == Operator::LessThan {
-            disequality.neg();
-            op = Operator::GreaterThan;
-        } else if op == Operator::LessEq {
-            disequality.neg();
-            op = Operator::GreaterEq;
-        }
+        // Steps 1 through 3: Negate the disequality and put it in canonical linear form
+        let negation = negate_disequality(&conclusion[0])?;
 
-        // The final disequality should be tautological
-        let is_disequality_true = disequality.0.is_empty()
-            && (disequality.1 > 0 || op == Operator::GreaterThan && disequality.1 == 0);
+        // The negation of the disequality should be contradictory, meaning the disequality itself
+        // is tautological
         rassert!(
-            is_disequality_true,
-            LinearArithmeticError::DisequalityIsNotTautology(op, disequality),
+            negation.is_contradiction(),
+            LinearArithmeticError::DisequalityIsNotTautology(negation.op, negation.comb),
         );
         Ok(())
     }
 }
 
+/// The maximum number of nodes in the branch-and-bound search done by `lia_generic`.
+const LIA_GENERIC_MAX_NODES: usize = 10_000;
+
+/// A value of the form `a + b * delta`, where `delta` is an arbitrarily small positive number.
+/// These are used by the simplex procedure to represent strict bounds: the bound `x > a` becomes
+/// `x >= a + delta`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct DeltaRational {
+    real: Rational,
+    delta: Rational,
+}
+
+impl DeltaRational {
+    fn new(real: Rational, delta: i32) -> Self {
+        Self { real, delta: Rational::from(delta) }
+    }
+
+    fn zero() -> Self {
+        Self::new(Rational::new(), 0)
+    }
+
+    fn add_mul(&mut self, other: &Self, factor: &Rational) {
+        self.real += other.real.clone() * factor;
+        self.delta += other.delta.clone() * factor;
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        Self {
+            real: self.real.clone() - &other.real,
+            delta: self.delta.clone() - &other.delta,
+        }
+    }
+
+    fn div(&self, divisor: &Rational) -> Self {
+        Self {
+            real: self.real.clone() / divisor,
+            delta: self.delta.clone() / divisor,
+        }
+    }
+
+    /// If this value is not an integer, returns the greatest integer below it.
+    fn floor_if_fractional(&self) -> Option<Rational> {
+        if !self.real.is_integer() {
+            Some(self.real.clone().floor())
+        } else if self.delta < 0 {
+            Some(self.real.clone() - 1u32)
+        } else if self.delta > 0 {
+            Some(self.real.clone())
+        } else {
+            None
+        }
+    }
+}
+
+/// A bound on a simplex variable, together with the linear form it bounds, in terms of the
+/// original variables. The linear form is used to verify the certificate of each conflict.
+#[derive(Debug, Clone)]
+struct SimplexBound {
+    value: DeltaRational,
+    form: LinearComb,
+}
+
+/// The result of checking the satisfiability of a set of bounds with the simplex procedure.
+enum SimplexResult {
+    Sat,
+
+    /// The bounds are unsatisfiable. Holds the Farkas certificate of the conflict: a list of
+    /// bounds and their coefficients. Each entry holds the bound, its coefficient, and whether it
+    /// is a lower bound.
+    Unsat(Vec<(SimplexBound, Rational, bool)>),
+}
+
+/// A general simplex solver, following the procedure described in "A Fast Linear-Arithmetic
+/// Solver for DPLL(T)", by Dutertre and de Moura. The first variables are the original variables
+/// of the problem, and each constraint is represented by an additional slack variable, that is
+/// equal to the constraint's linear form.
+#[derive(Debug, Clone)]
+struct Simplex {
+    num_original: usize,
+    is_int: Vec<bool>,
+
+    // Each row represents a basic variable as a combination of the non-basic variables
+    rows: Vec<Vec<Rational>>,
+    basic: Vec<usize>,
+    row_of: Vec<Option<usize>>,
+
+    // The linear form, over the original variables, that each variable is equal to
+    forms: Vec<LinearComb>,
+    values: Vec<DeltaRational>,
+    lower: Vec<Option<SimplexBound>>,
+    upper: Vec<Option<SimplexBound>>,
+}
+
+impl Simplex {
+    /// Builds a solver with the given original variables, and no constraints.
+    fn new(variables: &[Rc<Term>], is_int: Vec<bool>) -> Self {
+        let n = variables.len();
+        let forms = variables
+            .iter()
+            .map(|v| {
+                LinearComb(
+                    IndexMap::from([(v.clone(), Rational::from(1))]),
+                    Rational::new(),
+                )
+            })
+            .collect();
+        Self {
+            num_original: n,
+            is_int,
+            rows: Vec::new(),
+            basic: Vec::new(),
+            row_of: vec![None; n],
+            forms,
+            values: vec![DeltaRational::zero(); n],
+            lower: vec![None; n],
+            upper: vec![None; n],
+        }
+    }
+
+    /// Adds a slack variable equal to the linear form `form`, whose coefficients over the original
+    /// variables are `coeffs`.
+    fn add_slack(&mut self, form: LinearComb, coeffs: Vec<Rational>) -> usize {
+        let var = self.values.len();
+        for row in &mut self.rows {
+            row.push(Rational::new());
+        }
+        let mut row = coeffs;
+        row.resize(var + 1, Rational::new());
+        self.rows.push(row);
+        self.basic.push(var);
+        self.row_of.push(Some(self.rows.len() - 1));
+        self.forms.push(form);
+        self.values.push(DeltaRational::zero());
+        self.lower.push(None);
+        self.upper.push(None);
+        var
+    }
+
+    /// Tightens the bound on a variable. Returns the certificate of a conflict if the new bound is
+    /// incompatible with the opposite bound.
+    fn assert_bound(
+        &mut self,
+        var: usize,
+        bound: SimplexBound,
+        is_lower: bool,
+    ) -> Option<Vec<(SimplexBound, Rational, bool)>> {
+        let same = if is_lower {
+            &mut self.lower
+        } else {
+            &mut self.upper
+        };
+        let is_tighter = match &same[var] {
+            None => true,
+            Some(b) if is_lower => bound.value > b.value,
+            Some(b) => bound.value < b.value,
+        };
+        if is_tighter {
+            same[var] = Some(bound);
+        }
+        let (Some(l), Some(u)) = (&self.lower[var], &self.upper[var]) else {
+            return None;
+        };
+        (l.value > u.value).then(|| {
+            let one = Rational::from(1);
+            vec![(l.clone(), one.clone(), true), (u.clone(), one, false)]
+        })
+    }
+
+    fn update(&mut self, var: usize, value: DeltaRational) {
+        let diff = value.sub(&self.values[var]);
+        for (row, &b) in self.rows.iter().zip(&self.basic) {
+            if row[var] != 0 {
+                self.values[b].add_mul(&diff, &row[var]);
+            }
+        }
+        self.values[var] = value;
+    }
+
+    fn pivot(&mut self, r: usize, entering: usize) {
+        let leaving = self.basic[r];
+        let a = self.rows[r][entering].clone();
+
+        // The row `x_leaving = a * x_entering + ...` becomes
+        // `x_entering = (1 / a) * x_leaving - ...`
+        let mut new_row = std::mem::take(&mut self.rows[r]);
+        for c in &mut new_row {
+            *c /= &a;
+            *c = -std::mem::take(c);
+        }
+        new_row[entering] = Rational::new();
+        new_row[leaving] = Rational::from(1) / &a;
+
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            if i == r || row[entering] == 0 {
+                continue;
+            }
+            let factor = std::mem::take(&mut row[entering]);
+            for (c, n) in row.iter_mut().zip(&new_row) {
+                if *n != 0 {
+                    *c += factor.clone() * n;
+                }
+            }
+        }
+        self.rows[r] = new_row;
+        self.basic[r] = entering;
+        self.row_of[leaving] = None;
+        self.row_of[entering] = Some(r);
+    }
+
+    fn violates_lower(&self, var: usize) -> bool {
+        self.lower[var]
+            .as_ref()
+            .is_some_and(|b| self.values[var] < b.value)
+    }
+
+    fn violates_upper(&self, var: usize) -> bool {
+        self.upper[var]
+            .as_ref()
+            .is_some_and(|b| self.values[var] > b.value)
+    }
+
+    fn check(&mut self) -> SimplexResult {
+        // First, we move every non-basic variable into its bounds
+        for var in 0..self.values.len() {
+            if self.row_of[var].is_none() {
+                if self.violates_lower(var) {
+                    self.update(var, self.lower[var].as_ref().unwrap().value.clone());
+                } else if self.violates_upper(var) {
+                    self.update(var, self.upper[var].as_ref().unwrap().value.clone());
+                }
+            }
+        }
+
+        loop {
+            // To guarantee termination, we use Bland's rule: both the leaving and the entering
+            // variables are always the ones with the smallest index
+            let violating = (0..self.values.len())
+                .filter(|&v| self.row_of[v].is_some())
+                .find(|&v| self.violates_lower(v) || self.violates_upper(v));
+            let Some(var) = violating else {
+                return SimplexResult::Sat;
+            };
+            let r = self.row_of[var].unwrap();
+            let increase = self.violates_lower(var);
+
+            let entering = (0..self.values.len()).find(|&j| {
+                let a = &self.rows[r][j];
+                if *a == 0 || self.row_of[j].is_some() {
+                    return false;
+                }
+                let can_increase = self.upper[j]
+                    .as_ref()
+                    .map_or(true, |b| self.values[j] < b.value);
+                let can_decrease = self.lower[j]
+                    .as_ref()
+                    .map_or(true, |b| self.values[j] > b.value);
+                if (*a > 0) == increase {
+                    can_increase
+                } else {
+                    can_decrease
+                }
+            });
+            let Some(entering) = entering else {
+                return SimplexResult::Unsat(self.explain(r, increase));
+            };
+
+            let target = if increase {
+                &self.lower[var]
+            } else {
+                &self.upper[var]
+            };
+            let target = target.as_ref().unwrap().value.clone();
+            let theta = target.sub(&self.values[var]).div(&self.rows[r][entering]);
+            let mut new_value = self.values[entering].clone();
+            new_value.add_mul(&theta, &Rational::from(1));
+            self.update(entering, new_value);
+            self.pivot(r, entering);
+        }
+    }
+
+    /// Builds the Farkas certificate for a conflict in row `r`, where the basic variable could not
+    /// be increased to its lower bound (or decreased to its upper bound, if `increase` is false).
+    fn explain(&self, r: usize, increase: bool) -> Vec<(SimplexBound, Rational, bool)> {
+        let var = self.basic[r];
+        let own = if increase {
+            &self.lower[var]
+        } else {
+            &self.upper[var]
+        };
+        let mut result = vec![(own.clone().unwrap(), Rational::from(1), increase)];
+        for (j, a) in self.rows[r].iter().enumerate() {
+            if *a == 0 {
+                continue;
+            }
+            // If the basic variable must increase, each variable with a positive coefficient is
+            // at its upper bound, and each variable with a negative coefficient is at its lower
+            // bound. The opposite happens if the basic variable must decrease
+            let is_lower = (*a > 0) != increase;
+            let bound = if is_lower {
+                &self.lower[j]
+            } else {
+                &self.upper[j]
+            };
+            result.push((bound.clone().unwrap(), a.clone(), is_lower));
+        }
+        result
+    }
+
+    /// Returns the first integer variable whose value is not an integer, and the greatest integer
+    /// below its value.
+    fn fractional_int_var(&self) -> Option<(usize, Rational)> {
+        (0..self.num_original)
+            .filter(|&v| self.is_int[v])
+            .find_map(|v| Some((v, self.values[v].floor_if_fractional()?)))
+    }
+}
+
+/// Verifies the Farkas certificate of a simplex conflict. The certificate has the bound on a
+/// basic variable `x_b`, and the bounds on the variables `x_j` such that `x_b = sum(a_j * x_j)`.
+/// It is valid if the linear forms of the bounds actually satisfy this equation, and if the bound
+/// on `x_b` is incompatible with the bound implied by the other bounds. For the other bounds to
+/// imply a bound on `x_b` in the opposite direction, if `x_b` has a lower bound, each `x_j` with a
+/// positive coefficient must have an upper bound, and each one with a negative coefficient must
+/// have a lower bound. If `x_b` has an upper bound, the directions are flipped.
+fn verify_certificate(certificate: &[(SimplexBound, Rational, bool)]) -> bool {
+    let ((own, _, own_is_lower), rest) = certificate.split_first().unwrap();
+    let mut form = own.form.clone();
+    let mut implied = DeltaRational::zero();
+    for (bound, a, is_lower) in rest {
+        if *a == 0 || *is_lower != ((*a > 0) != *own_is_lower) {
+            return false;
+        }
+        let mut scaled = bound.form.clone();
+        scaled.mul(a);
+        form = form - scaled;
+        implied.add_mul(&bound.value, a);
+    }
+    form.coefficients().is_empty()
+        && *form.constant() == 0
+        && if *own_is_lower {
+            own.value > implied
+        } else {
+            own.value < implied
+        }
+}
+
+/// Converts a linear atom into bounds on a linear form with integer coefficients. If all the
+/// variables in the atom are integers, the bounds are also strengthened, using the fact that the
+/// linear form can only take integer values. Returns the form and its lower and upper bounds, or
+/// `None` if the atom is trivially unsatisfiable over the integers.
+fn atom_to_bounds(
+    atom: LinearAtom,
+    is_int: impl Fn(&Rc<Term>) -> bool,
+) -> Option<(LinearComb, Option<DeltaRational>, Option<DeltaRational>)> {
+    // An atom with no variables is either trivially true, in which case it imposes no bounds, or
+    // trivially false. It can't be normalized, since the form has no coefficients to divide by
+    if atom.comb.coefficients().is_empty() {
+        return (!atom.is_contradiction()).then(|| (atom.comb, None, None));
+    }
+
+    let LinearAtom { op, comb } = atom;
+    let mut form = LinearComb(comb.0, Rational::new());
+    let mut d = comb.1;
+
+    if form.coefficients().keys().all(is_int) {
+        // We scale the form so its coefficients are integers with no common divisor
+        let mut denominators = Integer::from(1);
+        let mut gcd = Integer::new();
+        for c in form.coefficients().values() {
+            denominators.lcm_mut(c.denom());
+        }
+        for c in form.coefficients().values() {
+            gcd.gcd_mut(&(c.clone() * &denominators).numer().clone());
+        }
+        let factor = Rational::from((denominators, gcd));
+        form.mul(&factor);
+        d *= &factor;
+
+        return match op {
+            Operator::Equals if !d.is_integer() => None,
+            Operator::Equals => {
+                let d = DeltaRational::new(d, 0);
+                Some((form, Some(d.clone()), Some(d)))
+            }
+            Operator::GreaterEq => Some((form, Some(DeltaRational::new(d.ceil(), 0)), None)),
+            _ => Some((form, Some(DeltaRational::new(d.floor() + 1u32, 0)), None)),
+        };
+    }
+    let bounds = match op {
+        Operator::Equals => {
+            let d = DeltaRational::new(d, 0);
+            (Some(d.clone()), Some(d))
+        }
+        Operator::GreaterEq => (Some(DeltaRational::new(d, 0)), None),
+        _ => (Some(DeltaRational::new(d, 1)), None),
+    };
+    Some((form, bounds.0, bounds.1))
+}
+
+/// Searches for an integer solution to the constraints in the solver, using branch and bound.
+/// Returns `Ok(true)` if the constraints are unsatisfiable, and `Ok(false)` if a solution was
+/// found. Every conflict found during the search has its certificate verified.
+fn branch_and_bound(simplex: Simplex, nodes: &mut usize) -> Result<bool, CheckerError> {
+    let mut stack = vec![simplex];
+    while let Some(mut simplex) = stack.pop() {
+        *nodes += 1;
+        if *nodes > LIA_GENERIC_MAX_NODES {
+            return Err(LinearArithmeticError::LiaSearchLimit(LIA_GENERIC_MAX_NODES).into());
+        }
+        match simplex.check() {
+            SimplexResult::Unsat(certificate) => {
+                rassert!(
+                    verify_certificate(&certificate),
+                    LinearArithmeticError::InvalidLiaCertificate
+                );
+            }
+            SimplexResult::Sat => {
+                let Some((var, floor)) = simplex.fractional_int_var() else {
+                    return Ok(false);
+                };
+
+                // We split on `x <= floor` or `x >= floor + 1`
+                let form = &simplex.forms[var];
+                for (value, is_lower) in [(floor.clone() + 1u32, true), (floor, false)] {
+                    let mut branch = simplex.clone();
+                    let bound = SimplexBound {
+                        value: DeltaRational::new(value, 0),
+                        form: form.clone(),
+                    };
+                    match branch.assert_bound(var, bound, is_lower) {
+                        Some(certificate) => rassert!(
+                            verify_certificate(&certificate),
+                            LinearArithmeticError::InvalidLiaCertificate
+                        ),
+                        None => stack.push(branch),
+                    }
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Checks a `lia_generic` step without calling an external solver. The step is valid if the
+/// negation of its conclusion is unsatisfiable in linear arithmetic, where variables of sort `Int`
+/// must take integer values. This is decided using the simplex procedure, together with branch and
+/// bound for the integer variables.
+pub fn lia_generic(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
+    let mut nodes = 0;
+    for atoms in negation_cases(conclusion)? {
+        if !lia_case_is_unsat(pool, atoms, &mut nodes)? {
+            return Err(LinearArithmeticError::LiaClauseNotValid.into());
+        }
+    }
+    Ok(())
+}
+
+/// Negates a clause of arithmetic literals, returning the cases of the negation as conjunctions of
+/// linear atoms. The clause is valid if and only if each case is unsatisfiable. Literals that are
+/// equalities are negated into a disjunction of two strict disequalities, so there are `2^n` cases,
+/// where `n` is the number of such literals.
+pub(super) fn negation_cases(
+    conclusion: &[Rc<Term>],
+) -> Result<Vec<Vec<LinearAtom>>, CheckerError> {
+    let mut atoms = Vec::new();
+    let mut disequalities = Vec::new();
+    for literal in conclusion {
+        match negate_disequality(literal) {
+            Ok(atom) => atoms.push(atom),
+            Err(e) => {
+                let Some((false, Operator::Equals, [a, b])) = as_comparison(literal) else {
+                    return Err(e);
+                };
+                disequalities.push((a, b));
+            }
+        }
+    }
+
+    // Each disequality `a != b` is split into `a < b` or `a > b`
+    let num_cases = 1usize
+        .checked_shl(disequalities.len() as u32)
+        .filter(|&n| n <= LIA_GENERIC_MAX_NODES)
+        .ok_or(LinearArithmeticError::LiaSearchLimit(LIA_GENERIC_MAX_NODES))?;
+    let cases = (0..num_cases).map(|case| {
+        let mut case_atoms = atoms.clone();
+        for (i, (a, b)) in disequalities.iter().enumerate() {
+            let op = if case & (1 << i) == 0 {
+                Operator::LessThan
+            } else {
+                Operator::GreaterThan
+            };
+            case_atoms.push(LinearAtom::new(op, a, b).unwrap());
+        }
+        case_atoms
+    });
+    Ok(cases.collect())
+}
+
+/// Returns `true` if the conjunction of the given atoms is unsatisfiable. Every term that is not a
+/// sum, subtraction or multiplication by a constant is considered a variable, and must take integer
+/// values if its sort is `Int`.
+pub(super) fn lia_case_is_unsat(
+    pool: &mut dyn TermPool,
+    atoms: Vec<LinearAtom>,
+    nodes: &mut usize,
+) -> Result<bool, CheckerError> {
+    let is_int = |t: &Rc<Term>| pool.sort(t).as_sort() == Some(&Sort::Int);
+    let mut variables: IndexMap<Rc<Term>, bool> = IndexMap::new();
+    let mut constraints = Vec::new();
+    for atom in atoms {
+        for v in atom.comb.coefficients().keys() {
+            if !variables.contains_key(v) {
+                let int = is_int(v);
+                variables.insert(v.clone(), int);
+            }
+        }
+        let Some(constraint) = atom_to_bounds(atom, |v| variables[v]) else {
+            return Ok(true);
+        };
+        constraints.push(constraint);
+    }
+
+    let (vars, is_int): (Vec<_>, Vec<_>) = variables.into_iter().unzip();
+    let mut simplex = Simplex::new(&vars, is_int);
+    for (form, lower, upper) in constraints {
+        // Constraints with no variables are trivially true, since trivially false ones were
+        // already found by `atom_to_bounds`
+        if form.coefficients().is_empty() {
+            continue;
+        }
+        let coeffs = vars
+            .iter()
+            .map(|v| form.coefficients().get(v).cloned().unwrap_or_default())
+            .collect();
+        let slack = simplex.add_slack(form.clone(), coeffs);
+        for (value, is_lower) in [(lower, true), (upper, false)] {
+            let Some(value) = value else { continue };
+            let bound = SimplexBound { value, form: form.clone() };
+            if let Some(certificate) = simplex.assert_bound(slack, bound, is_lower) {
+                rassert!(
+                    verify_certificate(&certificate),
+                    LinearArithmeticError::InvalidLiaCertificate
+                );
+                return Ok(true);
+            }
+        }
+    }
+    branch_and_bound(simplex, nodes)
+}
+
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn certificate_directions() {
+        use super::*;
+
+        let mut pool = PrimitivePool::new();
+        let int = pool.add(Term::Sort(Sort::Int));
+        let [x, y] = ["x", "y"].map(|name| pool.add(Term::new_var(name, int.clone())));
+
+        // The bounds `x + y >= 3`, `x <= 1` and `y <= 1` are unsatisfiable
+        let mut simplex = Simplex::new(&[x.clone(), y.clone()], vec![true, true]);
+        let one = Rational::from(1);
+        let bound = |form: LinearComb, value: i32| SimplexBound {
+            value: DeltaRational::new(Rational::from(value), 0),
+            form,
+        };
+        let form = |vars: &[&Rc<Term>]| {
+            let coefficients = vars.iter().map(|&v| (v.clone(), one.clone())).collect();
+            LinearComb(coefficients, Rational::new())
+        };
+        let sum = simplex.add_slack(form(&[&x, &y]), vec![one.clone(), one.clone()]);
+        assert!(simplex
+            .assert_bound(sum, bound(form(&[&x, &y]), 3), true)
+            .is_none());
+        assert!(simplex
+            .assert_bound(0, bound(form(&[&x]), 1), false)
+            .is_none());
+        assert!(simplex
+            .assert_bound(1, bound(form(&[&y]), 1), false)
+            .is_none());
+        let SimplexResult::Unsat(mut certificate) = simplex.check() else {
+            panic!("bounds should be unsatisfiable");
+        };
+        assert!(verify_certificate(&certificate));
+
+        // A lower bound can't be used where an upper bound is needed, even if the values match
+        certificate[2].2 = !certificate[2].2;
+        assert!(!verify_certificate(&certificate));
+    }
+
     #[test]
     fn la_rw_eq() {
         test_cases! {
@@ -539,6 +1044,8 @@ mod tests {
                     :rule la_disequality)": true,
                 "(step t1 (cl (or (= x y) (not (<= x y)) (not (<= y x))))
                     :rule la_disequality)": true,
+                "(step t1 (cl (= (* 0 a) b) (not (<= (+ (* 0 a) 0) b)) (not (<= b (* 0 a))))
+                    :rule la_disequality)": true,
             }
             "Clause term is not of the correct form" {
                 "(step t1 (cl (or (= b a) (not (<= a b)) (not (<= b a))))
@@ -546,6 +1053,24 @@ mod tests {
                 "(step t1 (cl (or (= x y) (not (<= y x)) (not (<= y x))))
                     :rule la_disequality)": false,
             }
+            "Conclusion given as a clause" {
+                "(step t1 (cl (= a b) (not (<= a b)) (not (<= b a))) :rule la_disequality)": true,
+                "(step t1 (cl (= a b) (not (<= a b))) :rule la_disequality)": false,
+            }
+            "Equivalent disequalities" {
+                "(step t1 (cl (or (= a (+ b 1)) (not (<= (- a b) 1)) (not (<= (+ b 1) a))))
+                    :rule la_disequality)": true,
+                "(step t1 (cl (or (= x 1.0) (not (<= (* 2.0 x) 2.0)) (not (<= 0.5 (* 0.5 x)))))
+                    :rule la_disequality)": true,
+                "(step t1 (cl (= (* 3 a) b) (not (<= (* 6 a) (* 2 b))) (not (<= (- (* 3 a)) (- b))))
+                    :rule la_disequality)": true,
+                "(step t1 (cl (or (= a b) (not (<= (* (- 2) a) (* (- 2) b))) (not (<= b a))))
+                    :rule la_disequality)": false,
+                "(step t1 (cl (or (= a b) (not (<= a (+ b 1))) (not (<= b a))))
+                    :rule la_disequality)": false,
+                "(step t1 (cl (or (= x y) (not (<= x y)) (not (<= (* 0.0 y) (* 0.0 x)))))
+                    :rule la_disequality)": false,
+            }
         }
     }
 
@@ -566,6 +1091,50 @@ mod tests {
                 "(step t1 (cl (or (<= a b) (<= a b))) :rule la_totality)": false,
                 "(step t1 (cl (<= x y) (<= x y)) :rule la_totality)": false,
                 "(step t1 (cl (<= 0 1) (<= 0.0 1.0)) :rule la_totality)": false,
+                "(step t1 (cl (or (< a b) (<= b a))) :rule la_totality)": false,
+                "(step t1 (cl (or (<= a b) (<= b a) (<= a b))) :rule la_totality)": false,
+            }
+            "Conclusion given as a clause" {
+                "(step t1 (cl (<= a b) (<= b a)) :rule la_totality)": true,
+                "(step t1 (cl (<= x y) (>= x y)) :rule la_totality)": true,
+            }
+            "Equivalent bounds" {
+                "(step t1 (cl (or (>= b a) (not (> b a)))) :rule la_totality)": true,
+                "(step t1 (cl (or (<= (- a b) 0) (<= (* 2 b) (* 2 a)))) :rule la_totality)": true,
+                "(step t1 (cl (or (<= x 1.5) (<= (* 2.0 (- x)) (- 3.0)))) :rule la_totality)": true,
+                "(step t1 (cl (or (<= x 1.5) (<= 3.0 (* 2.0 x)))) :rule la_totality)": true,
+                "(step t1 (cl (or (<= x 1.5) (<= 1.0 x))) :rule la_totality)": false,
+                "(step t1 (cl (or (<= a b) (<= (* (- 1) b) (* (- 1) a)))) :rule la_totality)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn la_trichotomy() {
+        test_cases! {
+            definitions = "
+                (declare-fun a () Int)
+                (declare-fun b () Int)
+                (declare-fun x () Real)
+                (declare-fun y () Real)
+            ",
+            "Simple working examples" {
+                "(step t1 (cl (or (< a b) (= a b) (< b a))) :rule la_trichotomy)": true,
+                "(step t1 (cl (< x y) (= x y) (> x y)) :rule la_trichotomy)": true,
+                "(step t1 (cl (= a b) (> a b) (> b a)) :rule la_trichotomy)": true,
+                "(step t1 (cl (> a b) (< a b) (= b a)) :rule la_trichotomy)": true,
+            }
+            "Negated and equivalent bounds" {
+                "(step t1 (cl (not (>= a b)) (= a b) (not (<= a b))) :rule la_trichotomy)": true,
+                "(step t1 (cl (< (- x y) 0.0) (= x y) (< 0.0 (- x y))) :rule la_trichotomy)": true,
+                "(step t1 (cl (< (* 2 a) (* 2 b)) (= a b) (< b a)) :rule la_trichotomy)": true,
+            }
+            "Clause term is not of the correct form" {
+                "(step t1 (cl (< a b) (= a b)) :rule la_trichotomy)": false,
+                "(step t1 (cl (< a b) (< a b) (< b a)) :rule la_trichotomy)": false,
+                "(step t1 (cl (< a b) (= a b) (< a b)) :rule la_trichotomy)": false,
+                "(step t1 (cl (<= a b) (= a b) (< b a)) :rule la_trichotomy)": false,
+                "(step t1 (cl (< x y) (= x y) (< y (+ x 1.0))) :rule la_trichotomy)": false,
             }
         }
     }
@@ -583,6 +1152,10 @@ mod tests {
                 "(step t1 (cl (not (<= n (- n 1)))) :rule la_tautology)": true,
                 "(step t1 (cl (< 0 (- (+ 1 n) n))) :rule la_tautology)": true,
                 "(step t1 (cl (not (<= (+ 1 n) (- (+ 1 n) 1)))) :rule la_tautology)": true,
+                "(step t1 (cl (not (= (+ n 1) n))) :rule la_tautology)": true,
+                "(step t1 (cl (not (= n (+ n 1)))) :rule la_tautology)": true,
+                "(step t1 (cl (not (= n n))) :rule la_tautology)": false,
+                "(step t1 (cl (<= n (- n 1))) :rule la_tautology)": false,
             }
             "Second form" {
                 "(step t1 (cl (or (not (<= x 5.0)) (<= x 6.0))) :rule la_tautology)": true,
@@ -600,4 +1173,39 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn lia_generic() {
+        test_cases! {
+            definitions = "
+                (declare-fun x () Int)
+                (declare-fun y () Int)
+                (declare-fun r () Real)
+            ",
+            config = crate::checker::Config::new().native_lia_generic(true),
+            "Simple working examples" {
+                "(step t1 (cl (not (< x 0)) (not (> x (- 1)))) :rule lia_generic)": true,
+                "(step t1 (cl (not (= (* 2 x) 1))) :rule lia_generic)": true,
+                "(step t1 (cl (not (>= (* 3 x) 1)) (not (<= (* 3 x) 2))) :rule lia_generic)": true,
+                "(step t1 (cl (not (<= x y)) (not (<= y x)) (= x y)) :rule lia_generic)": true,
+                "(step t1 (cl (< x 0) (= x 0) (> x 0)) :rule lia_generic)": true,
+            }
+            "Requires branching" {
+                "(step t1 (cl (not (= (+ (* 2 x) (* 3 y)) 1)) (< x 0) (> x 1) (< y 0) (> y 1))
+                    :rule lia_generic)": true,
+                "(step t1 (cl (not (= (+ (* 2 x) (* 3 y)) 1))) :rule lia_generic)": false,
+            }
+            "Clause is not valid" {
+                "(step t1 (cl (not (< x 1)) (not (> x (- 1)))) :rule lia_generic)": false,
+                "(step t1 (cl (not (< r 1.0)) (not (> r 0.0))) :rule lia_generic)": false,
+                "(step t1 (cl (= x 0)) :rule lia_generic)": false,
+                "(step t1 (cl (< x y) (> x y)) :rule lia_generic)": false,
+            }
+            "Atoms with no variables" {
+                "(step t1 (cl (<= (* 0 x) 5)) :rule lia_generic)": true,
+                "(step t1 (cl (not (<= (* 0 x) 5))) :rule lia_generic)": false,
+                "(step t1 (cl (not (<= (* 0 x) 5)) (< x 0) (>= x 0)) :rule lia_generic)": true,
+            }
+        }
+    }
 }
@@ -4,9 +4,10 @@ use super::{
 };
 use crate::{
     ast::*,
+    rewriting::RuleDatabase,
     utils::{Range, TypeName},
 };
-use std::time::Duration;
+use std::{path::Path, time::Duration};
 
 pub type RuleResult = Result<(), CheckerError>;
 
@@ -16,6 +17,10 @@ pub struct RuleArgs<'a> {
     pub(super) conclusion: &'a [Rc<Term>],
     pub(super) premises: &'a [Premise<'a>],
     pub(super) args: &'a [Rc<Term>],
+
+    /// The variables assigned by the arguments that are assignments, if any. See
+    /// [`ProofStep::arg_vars`].
+    pub(super) arg_vars: &'a [Option<SortedVar>],
     pub(super) pool: &'a mut dyn TermPool,
     pub(super) context: &'a mut ContextStack,
 
@@ -26,6 +31,20 @@ pub struct RuleArgs<'a> {
     pub(super) discharge: &'a [&'a ProofCommand],
 
     pub(super) polyeq_time: &'a mut Duration,
+
+    /// Limits on the terms built by the rule. Rules that may build terms whose size is not bounded
+    /// by the size of the step itself should check them.
+    pub(super) term_limits: TermLimits,
+
+    /// The datatypes declared in the problem.
+    pub(super) datatypes: &'a Datatypes,
+
+    /// The rewrites used by umbrella rules like `all_simplify`, including the RARE rules loaded by
+    /// the user, if any.
+    pub(super) rewrites: &'a RuleDatabase,
+
+    /// The directory against which certificate files are resolved, if reading them is allowed.
+    pub(super) certificate_dir: Option<&'a Path>,
 }
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
@@ -156,7 +175,12 @@ fn assert_is_bool_constant(got: &Rc<Term>, expected: bool) -> RuleResult {
 }
 
 #[cfg(test)]
-fn run_tests(test_name: &str, definitions: &str, cases: &[(&str, bool)]) {
+fn run_tests(
+    test_name: &str,
+    definitions: &str,
+    cases: &[(&str, bool)],
+    config: &crate::checker::Config,
+) {
     use crate::{checker, parser};
     use std::io::Cursor;
 
@@ -189,10 +213,13 @@ fn run_tests(test_name: &str, definitions: &str, cases: &[(&str, bool)]) {
             rule: "hole".into(),
             premises: Vec::new(),
             args: Vec::new(),
+            arg_vars: Vec::new(),
             discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
         }));
 
-        let mut checker = checker::ProofChecker::new(&mut pool, checker::Config::new());
+        let mut checker = checker::ProofChecker::new(&mut pool, config.clone());
         let got = checker.check(&problem, &proof).is_ok();
         assert_eq!(
             *expected, got,
@@ -207,28 +234,50 @@ macro_rules! test_cases {
     (
         definitions = $defs:expr,
         $($name:literal { $($proof:literal: $exp:literal,)* } )*
+    ) => {
+        test_cases! {
+            definitions = $defs,
+            config = $crate::checker::Config::new(),
+            $($name { $($proof: $exp,)* } )*
+        }
+    };
+    (
+        definitions = $defs:expr,
+        config = $config:expr,
+        $($name:literal { $($proof:literal: $exp:literal,)* } )*
     ) => {{
         let definitions: &str = $defs;
+        let config: $crate::checker::Config = $config;
         $({
             let name: &str = $name;
             let cases = [ $(($proof, $exp),)* ];
-            $crate::checker::rules::run_tests(name, definitions, &cases);
+            $crate::checker::rules::run_tests(name, definitions, &cases, &config);
         })*
     }};
 }
 
 // Since the rule submodules use the `test_cases` macro, we have to declare them here, after the
 // macro is declared
+pub(super) mod all_simplify;
+pub(super) mod arrays;
 pub(super) mod bitvectors;
 pub(super) mod clausification;
 pub(super) mod congruence;
+pub(super) mod datatypes;
+pub(super) mod drat;
 pub(super) mod extras;
+pub(super) mod finite_field;
+pub(super) mod floating_point;
 pub(super) mod linear_arithmetic;
+pub(super) mod nonlinear_arithmetic;
 pub(super) mod quantifier;
+pub(super) mod rare;
 pub(super) mod reflexivity;
 pub(super) mod resolution;
+pub(super) mod sets;
 pub(super) mod simplification;
 pub(super) mod strings;
 pub(super) mod subproof;
 pub(super) mod tautology;
+pub(super) mod transcendental;
 pub(super) mod transitivity;
@@ -0,0 +1,335 @@
+use super::{
+    linear_arithmetic::{lia_case_is_unsat, negation_cases},
+    RuleArgs, RuleResult,
+};
+use crate::{
+    ast::*,
+    checker::error::LinearArithmeticError,
+    linear::{LinearAtom, LinearComb},
+};
+use indexmap::IndexMap;
+use rug::Rational;
+use std::cmp::Ordering;
+
+/// An endpoint of an interval. Finite endpoints may be strict, in which case the value itself is
+/// not in the interval.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Endpoint {
+    NegInfinity,
+    Finite(Rational, bool),
+    PosInfinity,
+}
+
+impl Endpoint {
+    fn sign(&self) -> Ordering {
+        match self {
+            Endpoint::NegInfinity => Ordering::Less,
+            Endpoint::Finite(v, _) => v.cmp0(),
+            Endpoint::PosInfinity => Ordering::Greater,
+        }
+    }
+
+    /// Multiplies two endpoints. A product of zero and infinity is considered to be zero, which
+    /// is the correct value when computing the extremes of the product of two intervals.
+    fn mul(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Endpoint::Finite(a, s), Endpoint::Finite(b, t)) => {
+                let strict = (*s && *b != 0) || (*t && *a != 0) || (*s && *t);
+                Endpoint::Finite(a.clone() * b, strict)
+            }
+            (Endpoint::Finite(a, s), _) | (_, Endpoint::Finite(a, s)) if *a == 0 => {
+                Endpoint::Finite(Rational::new(), *s)
+            }
+            (a, b) if a.sign() == b.sign() => Endpoint::PosInfinity,
+            _ => Endpoint::NegInfinity,
+        }
+    }
+
+    /// Compares two endpoints by value. Finite endpoints with the same value are ordered by their
+    /// strictness: if `strict_is_greater` is `true`, a strict endpoint is considered greater than
+    /// a non-strict one, and otherwise it is considered smaller.
+    fn cmp_by(&self, other: &Self, strict_is_greater: bool) -> Ordering {
+        match (self, other) {
+            (Endpoint::Finite(a, s), Endpoint::Finite(b, t)) => a.cmp(b).then_with(|| {
+                if strict_is_greater {
+                    s.cmp(t)
+                } else {
+                    t.cmp(s)
+                }
+            }),
+            (a, b) => a.rank().cmp(&b.rank()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Endpoint::NegInfinity => 0,
+            Endpoint::Finite(..) => 1,
+            Endpoint::PosInfinity => 2,
+        }
+    }
+}
+
+/// An interval of possible values for a term.
+#[derive(Debug, Clone)]
+struct Interval {
+    lower: Endpoint,
+    upper: Endpoint,
+}
+
+impl Interval {
+    fn unbounded() -> Self {
+        Self {
+            lower: Endpoint::NegInfinity,
+            upper: Endpoint::PosInfinity,
+        }
+    }
+
+    fn point(value: Rational) -> Self {
+        Self {
+            lower: Endpoint::Finite(value.clone(), false),
+            upper: Endpoint::Finite(value, false),
+        }
+    }
+
+    /// Multiplies two independent intervals. The extremes of the product are always among the
+    /// products of the endpoints. When several products have the same value, a non-strict bound
+    /// is preferred, since that value may be reached by the product.
+    fn mul(&self, other: &Self) -> Self {
+        let corners = [
+            self.lower.mul(&other.lower),
+            self.lower.mul(&other.upper),
+            self.upper.mul(&other.lower),
+            self.upper.mul(&other.upper),
+        ];
+        let lower = corners.iter().min_by(|a, b| a.cmp_by(b, true)).unwrap();
+        let upper = corners.iter().max_by(|a, b| a.cmp_by(b, false)).unwrap();
+        Self {
+            lower: lower.clone(),
+            upper: upper.clone(),
+        }
+    }
+
+    /// Returns the interval of the absolute value of a term in this interval.
+    fn abs(&self) -> Self {
+        let neg = |e: &Endpoint| match e {
+            Endpoint::NegInfinity => Endpoint::PosInfinity,
+            Endpoint::Finite(v, s) => Endpoint::Finite(-v.clone(), *s),
+            Endpoint::PosInfinity => Endpoint::NegInfinity,
+        };
+        if self.lower.sign() != Ordering::Less {
+            self.clone()
+        } else if self.upper.sign() != Ordering::Greater {
+            Self {
+                lower: neg(&self.upper),
+                upper: neg(&self.lower),
+            }
+        } else {
+            let neg_lower = neg(&self.lower);
+            let upper = [neg_lower, self.upper.clone()]
+                .into_iter()
+                .max_by(|a, b| a.cmp_by(b, false))
+                .unwrap();
+            Self {
+                lower: Endpoint::Finite(Rational::new(), false),
+                upper,
+            }
+        }
+    }
+
+    /// Returns the interval of `x^n`, where `x` is a term in this interval. Unlike multiplying the
+    /// interval by itself, this accounts for the fact that the factors are the same term, so even
+    /// powers are always non-negative.
+    fn pow(&self, n: usize) -> Self {
+        let abs = self.abs();
+        let mut result = Interval::point(Rational::from(1));
+        for _ in 0..n / 2 * 2 {
+            result = result.mul(&abs);
+        }
+        if n % 2 == 1 {
+            result = result.mul(self);
+        }
+        result
+    }
+
+    /// Tightens this interval with the bound `op d`, where `op` is one of `=`, `>=`, `>`, `<=` or
+    /// `<`.
+    fn tighten(&mut self, op: Operator, d: Rational) {
+        let (lower, upper) = match op {
+            Operator::Equals => (
+                Some(Endpoint::Finite(d.clone(), false)),
+                Some(Endpoint::Finite(d, false)),
+            ),
+            Operator::GreaterEq => (Some(Endpoint::Finite(d, false)), None),
+            Operator::GreaterThan => (Some(Endpoint::Finite(d, true)), None),
+            Operator::LessEq => (None, Some(Endpoint::Finite(d, false))),
+            _ => (None, Some(Endpoint::Finite(d, true))),
+        };
+        if let Some(l) = lower {
+            if l.cmp_by(&self.lower, true) == Ordering::Greater {
+                self.lower = l;
+            }
+        }
+        if let Some(u) = upper {
+            if u.cmp_by(&self.upper, false) == Ordering::Less {
+                self.upper = u;
+            }
+        }
+    }
+}
+
+/// If the term is a nonlinear monomial, returns its constant coefficient and its non-constant
+/// factors, each with its multiplicity.
+fn as_monomial(term: &Rc<Term>) -> Option<(Rational, IndexMap<&Rc<Term>, usize>)> {
+    let Term::Op(Operator::Mult, args) = term.as_ref() else {
+        return None;
+    };
+    let mut coeff = Rational::from(1);
+    let mut factors = IndexMap::new();
+    for a in args {
+        match a.as_fraction() {
+            Some(c) => coeff *= c,
+            None => *factors.entry(a).or_insert(0) += 1,
+        }
+    }
+    let degree: usize = factors.values().sum();
+    (degree >= 2).then_some((coeff, factors))
+}
+
+/// Derives bounds on the nonlinear monomials that appear in the atoms. Bounds on the other terms
+/// are collected from the atoms that have a single term, and the bounds on each monomial are
+/// computed by multiplying the intervals of its factors.
+fn monomial_bounds(pool: &mut dyn TermPool, atoms: &[LinearAtom]) -> Vec<LinearAtom> {
+    let mut intervals: IndexMap<&Rc<Term>, Interval> = IndexMap::new();
+    for atom in atoms {
+        let coefficients = atom.comb.coefficients();
+        if coefficients.len() != 1 {
+            continue;
+        }
+        let (term, c) = coefficients.first().unwrap();
+        if *c == 0 {
+            continue;
+        }
+        let d = atom.comb.constant().clone() / c;
+        let op = match atom.op {
+            Operator::GreaterEq if *c < 0 => Operator::LessEq,
+            Operator::GreaterThan if *c < 0 => Operator::LessThan,
+            op => op,
+        };
+        let interval = intervals.entry(term).or_insert_with(Interval::unbounded);
+        interval.tighten(op, d);
+
+        // Integer terms can have their strict bounds strengthened
+        if pool.sort(term).as_sort() == Some(&Sort::Int) {
+            if let Endpoint::Finite(v, true) = &interval.lower {
+                interval.lower = Endpoint::Finite(v.clone().floor() + 1u32, false);
+            }
+            if let Endpoint::Finite(v, true) = &interval.upper {
+                interval.upper = Endpoint::Finite(v.clone().ceil() - 1u32, false);
+            }
+        }
+    }
+
+    let monomials = atoms
+        .iter()
+        .flat_map(|atom| atom.comb.coefficients().keys())
+        .filter_map(|t| Some((t, as_monomial(t)?)));
+    let mut result = Vec::new();
+    for (term, (coeff, factors)) in monomials {
+        let mut interval = Interval::point(coeff);
+        for (factor, n) in factors {
+            let factor_interval = intervals
+                .get(factor)
+                .cloned()
+                .unwrap_or_else(Interval::unbounded);
+            interval = interval.mul(&factor_interval.pow(n));
+        }
+        let single = |c: i32, d: Rational| {
+            LinearComb(IndexMap::from([(term.clone(), Rational::from(c))]), d)
+        };
+        if let Endpoint::Finite(v, strict) = interval.lower {
+            let op = if strict {
+                Operator::GreaterThan
+            } else {
+                Operator::GreaterEq
+            };
+            result.push(LinearAtom { op, comb: single(1, v) });
+        }
+        if let Endpoint::Finite(v, strict) = interval.upper {
+            let op = if strict {
+                Operator::GreaterThan
+            } else {
+                Operator::GreaterEq
+            };
+            result.push(LinearAtom { op, comb: single(-1, -v) });
+        }
+    }
+    result
+}
+
+/// Checks a `nla_generic` step. Like `lia_generic`, the step is valid if the negation of its
+/// conclusion is unsatisfiable, but the literals may contain products of non-constant terms. Each
+/// such product is treated as a variable, bounded by the interval obtained by multiplying the
+/// intervals of its factors. This captures reasoning about the signs of products, as well as the
+/// fact that even powers are non-negative. This procedure is incomplete, so a valid step may still
+/// be rejected.
+pub fn nla_generic(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
+    let mut nodes = 0;
+    for mut atoms in negation_cases(conclusion)? {
+        let bounds = monomial_bounds(pool, &atoms);
+        atoms.extend(bounds);
+        if !lia_case_is_unsat(pool, atoms, &mut nodes)? {
+            return Err(LinearArithmeticError::NlaClauseNotProven.into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn nla_generic() {
+        test_cases! {
+            definitions = "
+                (declare-fun x () Real)
+                (declare-fun y () Real)
+                (declare-fun z () Real)
+                (declare-fun a () Int)
+                (declare-fun b () Int)
+            ",
+            "Sign reasoning" {
+                "(step t1 (cl (>= (* x x) 0.0)) :rule nla_generic)": true,
+                "(step t1 (cl (not (> x 0.0)) (not (> y 0.0)) (> (* x y) 0.0)) :rule nla_generic)": true,
+                "(step t1 (cl (not (< x 0.0)) (not (> y 0.0)) (< (* x y) 0.0)) :rule nla_generic)": true,
+                "(step t1 (cl (not (< x 0.0)) (not (< y 0.0)) (> (* x y) 0.0)) :rule nla_generic)": true,
+                "(step t1 (cl (not (< x 0.0)) (< (* x x x) 0.0)) :rule nla_generic)": true,
+                "(step t1 (cl (= x 0.0) (> (* x x) 0.0)) :rule nla_generic)": true,
+            }
+            "Interval evaluation" {
+                "(step t1 (cl (not (>= x 2.0)) (not (>= y 3.0)) (>= (* x y) 6.0)) :rule nla_generic)": true,
+                "(step t1 (cl (not (<= 1.0 x)) (not (<= x 2.0)) (<= (* x x x) 8.0))
+                    :rule nla_generic)": true,
+                "(step t1 (cl (not (<= (- 3.0) x)) (not (<= x 2.0)) (<= (* 2.0 x x) 18.0))
+                    :rule nla_generic)": true,
+                "(step t1 (cl (not (> a 0)) (not (> b 0)) (>= (* a b) 1)) :rule nla_generic)": true,
+            }
+            "Combined with linear reasoning" {
+                "(step t1 (cl (not (= z (* x x))) (>= (+ z 1.0) 0.0)) :rule nla_generic)": true,
+                "(step t1 (cl (not (> x 0.0)) (not (> y 0.0)) (not (< (+ (* x y) z) z)))
+                    :rule nla_generic)": true,
+            }
+            "Terms multiplied by zero" {
+                "(step t1 (cl (not (> (* 0.0 x) 1.0)) (> (* x x) 0.0)) :rule nla_generic)": true,
+                "(step t1 (cl (not (>= (* 0.0 x) 0.0)) (> (* x x) 0.0)) :rule nla_generic)": false,
+            }
+            "Clause is not proven" {
+                "(step t1 (cl (> (* x y) 0.0)) :rule nla_generic)": false,
+                "(step t1 (cl (not (> x 0.0)) (>= (* x y) 0.0)) :rule nla_generic)": false,
+                "(step t1 (cl (> (* x x) 0.0)) :rule nla_generic)": false,
+                "(step t1 (cl (not (<= 1.0 x)) (not (<= x 2.0)) (< (* x x x) 8.0))
+                    :rule nla_generic)": false,
+                "(step t1 (cl (not (>= x 2.0)) (not (>= y 3.0)) (>= (* x y) 7.0)) :rule nla_generic)": false,
+            }
+        }
+    }
+}
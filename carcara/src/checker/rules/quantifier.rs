@@ -7,7 +7,12 @@ use indexmap::{IndexMap, IndexSet};
 
 pub fn forall_inst(
     RuleArgs {
-        conclusion, args, pool, polyeq_time, ..
+        conclusion,
+        args,
+        arg_vars,
+        pool,
+        polyeq_time,
+        ..
     }: RuleArgs,
 ) -> RuleResult {
     assert_clause_len(conclusion, 1)?;
@@ -15,12 +20,69 @@ pub fn forall_inst(
     let ((bindings, original), substituted) =
         match_term_err!((or (not (forall ... original)) result) = &conclusion[0])?;
 
+    // If no arguments are given, but the quantifier body has `:pattern` annotations, we try to
+    // recover the instantiation by matching the patterns against the terms in the result
+    let patterns = if args.is_empty() && !bindings.is_empty() {
+        pool.patterns(original)
+    } else {
+        Vec::new()
+    };
+    if !patterns.is_empty() {
+        let vars: Vec<_> = bindings
+            .iter()
+            .map(|(name, sort)| pool.add(Term::new_var(name, sort.clone())))
+            .collect();
+        let candidates = ground_subterms(pool, substituted, &vars);
+        let mut is_valid = |assignment: &Assignment| {
+            let mut substitution = match Substitution::new(pool, assignment.clone()) {
+                Ok(s) => s,
+                Err(_) => return false,
+            };
+            let expected = substitution.apply(pool, original);
+            assert_alpha_equiv_expected(substituted, expected, polyeq_time).is_ok()
+        };
+        for pattern in &patterns {
+            let mut assignment = IndexMap::new();
+            if match_multi_pattern(pattern, &candidates, &vars, &mut assignment, &mut is_valid) {
+                return Ok(());
+            }
+        }
+        return Err(QuantifierError::NoPatternMatches(original.clone()).into());
+    }
+
     assert_num_args(args, bindings.len())?;
 
+    // If the arguments are given as assignments, they are matched to the bindings by name, instead
+    // of by position
+    let args: Vec<_> = if arg_vars.is_empty() {
+        args.to_vec()
+    } else {
+        let mut assigned = IndexMap::new();
+        for (var, value) in arg_vars.iter().zip(args) {
+            let name = match var {
+                Some((name, _)) if bindings.iter().any(|(b, _)| b == name) => name,
+                Some((name, _)) => {
+                    return Err(QuantifierError::NoBindingMatchesArg(name.clone()).into())
+                }
+                None => return Err(QuantifierError::NoBindingMatchesArg(value.to_string()).into()),
+            };
+            assigned.insert(name.as_str(), value.clone());
+        }
+        bindings
+            .iter()
+            .map(|(name, _)| {
+                assigned
+                    .get(name.as_str())
+                    .cloned()
+                    .ok_or_else(|| QuantifierError::NoArgGivenForBinding(name.clone()))
+            })
+            .collect::<Result<_, _>>()?
+    };
+
     // iterate over the bindings and arguments simultaneously, building the substitution
     let substitution: IndexMap<_, _> = bindings
         .iter()
-        .zip(args)
+        .zip(&args)
         .map(|((var_name, sort), value)| {
             assert_eq(sort, &pool.sort(value))?;
             let var = pool.add(Term::new_var(var_name, sort.clone()));
@@ -35,6 +97,107 @@ pub fn forall_inst(
     assert_alpha_equiv_expected(substituted, expected, polyeq_time)
 }
 
+type Assignment = IndexMap<Rc<Term>, Rc<Term>>;
+
+/// Collects all subterms of `term` that could be matched by a pattern, that is, all applications
+/// whose free variables do not include any of `vars`.
+fn ground_subterms(pool: &mut dyn TermPool, term: &Rc<Term>, vars: &[Rc<Term>]) -> Vec<Rc<Term>> {
+    let mut seen = IndexSet::new();
+    let mut stack = vec![term.clone()];
+    while let Some(t) = stack.pop() {
+        if !seen.insert(t.clone()) {
+            continue;
+        }
+        match t.as_ref() {
+            Term::App(_, args) | Term::Op(_, args) | Term::ParamOp { args, .. } => {
+                stack.extend(args.iter().cloned());
+            }
+            Term::Binder(_, _, inner) | Term::Let(_, inner) => stack.push(inner.clone()),
+            _ => (),
+        }
+    }
+    seen.into_iter()
+        .filter(|t| {
+            matches!(
+                t.as_ref(),
+                Term::App(..) | Term::Op(..) | Term::ParamOp { .. }
+            )
+        })
+        .filter(|t| !pool.free_vars(t).iter().any(|v| vars.contains(v)))
+        .collect()
+}
+
+/// Tries to match every term in the multi-pattern `pattern` against one of the `candidates`,
+/// backtracking on failure. Each complete assignment of the variables in `vars` is passed to
+/// `is_valid`, and the search stops as soon as it accepts one.
+fn match_multi_pattern(
+    pattern: &[Rc<Term>],
+    candidates: &[Rc<Term>],
+    vars: &[Rc<Term>],
+    assignment: &mut Assignment,
+    is_valid: &mut dyn FnMut(&Assignment) -> bool,
+) -> bool {
+    let Some((first, rest)) = pattern.split_first() else {
+        // Patterns that don't mention every variable can't determine the whole instantiation
+        return assignment.len() == vars.len() && is_valid(assignment);
+    };
+    for candidate in candidates {
+        let mut extended = assignment.clone();
+        if match_pattern(first, candidate, vars, &mut extended)
+            && match_multi_pattern(rest, candidates, vars, &mut extended, is_valid)
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Matches a single pattern term against `term`, extending `assignment` with the values of the
+/// variables in `vars`.
+fn match_pattern(
+    pattern: &Rc<Term>,
+    term: &Rc<Term>,
+    vars: &[Rc<Term>],
+    assignment: &mut Assignment,
+) -> bool {
+    if vars.contains(pattern) {
+        return match assignment.get(pattern) {
+            Some(value) => value == term,
+            None => {
+                assignment.insert(pattern.clone(), term.clone());
+                true
+            }
+        };
+    }
+    let match_all = |a: &[Rc<Term>], b: &[Rc<Term>], assignment: &mut _| {
+        a.len() == b.len()
+            && a.iter()
+                .zip(b)
+                .all(|(p, t)| match_pattern(p, t, vars, assignment))
+    };
+    match (pattern.as_ref(), term.as_ref()) {
+        (Term::App(f, p_args), Term::App(g, t_args)) => {
+            f == g && match_all(p_args, t_args, assignment)
+        }
+        (Term::Op(p_op, p_args), Term::Op(t_op, t_args)) => {
+            p_op == t_op && match_all(p_args, t_args, assignment)
+        }
+        (
+            Term::ParamOp {
+                op: p_op,
+                op_args: p_op_args,
+                args: p_args,
+            },
+            Term::ParamOp {
+                op: t_op,
+                op_args: t_op_args,
+                args: t_args,
+            },
+        ) => p_op == t_op && p_op_args == t_op_args && match_all(p_args, t_args, assignment),
+        _ => pattern == term,
+    }
+}
+
 pub fn qnt_join(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 1)?;
 
@@ -238,34 +401,44 @@ fn conjunctive_normal_form(term: &Rc<Term>) -> CnfFormula {
     }
 }
 
+/// Checks the clausification of a quantified formula. The conclusion has the form
+/// `(cl (or (not (forall xs phi)) (forall ys phi')))`, where `phi'` is one of the clauses of the
+/// conjunctive normal form of `phi`, after it is converted into negation normal form and its
+/// universal quantifiers are moved to the prefix. The variables `ys` are the variables of the
+/// prefix that appear in `phi'`.
+///
+/// veriT may also give the conclusion as the two literals of the disjunction, and may reorder or
+/// remove repeated literals in `phi'`, so clauses are compared as sets of literals. If no variables
+/// appear in `phi'`, it is given without a quantifier.
 pub fn qnt_cnf(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
-    assert_clause_len(conclusion, 1)?;
+    assert_clause_len(conclusion, 1..3)?;
 
     let (l_bindings, phi, r_bindings, phi_prime) = {
-        let (l, r) = match_term_err!((or (not l) r) = &conclusion[0])?;
+        let (l, r) = match conclusion {
+            [c] => match_term_err!((or (not l) r) = c)?,
+            [l, r] => (match_term_err!((not l) = l)?, r),
+            _ => unreachable!(),
+        };
         let (l_q, l_b, phi) = l.as_quant_err()?;
-        let (r_q, r_b, phi_prime) = r.as_quant_err()?;
 
         // We expect both quantifiers to be `forall`
         assert_is_expected(&l_q, Binder::Forall)?;
-        assert_is_expected(&r_q, Binder::Forall)?;
+        let (r_b, phi_prime) = match r.as_quant() {
+            Some((r_q, r_b, phi_prime)) => {
+                assert_is_expected(&r_q, Binder::Forall)?;
+                (r_b.iter().cloned().collect(), phi_prime)
+            }
+            None => (IndexSet::new(), r),
+        };
 
         (l_b, phi, r_b, phi_prime)
     };
 
-    let r_bindings = r_bindings.iter().cloned().collect::<IndexSet<_>>();
     let mut new_bindings = l_bindings.iter().cloned().collect::<IndexSet<_>>();
     let clauses: Vec<_> = {
         let nnf = negation_normal_form(pool, phi, true, &mut IndexMap::new());
         let prenexed = prenex_forall(pool, &mut new_bindings, &nnf);
-        let cnf = conjunctive_normal_form(&prenexed);
-        cnf.into_iter()
-            .map(|c| match c.as_slice() {
-                [] => unreachable!(),
-                [term] => term.clone(),
-                _ => pool.add(Term::Op(Operator::Or, c)),
-            })
-            .collect()
+        conjunctive_normal_form(&prenexed)
     };
 
     // `new_bindings` contains all bindings that existed in the original term, plus all bindings
@@ -276,13 +449,18 @@ pub fn qnt_cnf(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
         ));
     }
 
-    let selected_clause = clauses
+    let literals: IndexSet<_> = match phi_prime.as_ref() {
+        Term::Op(Operator::Or, args) => args.iter().collect(),
+        _ => std::iter::once(phi_prime).collect(),
+    };
+    if !clauses
         .iter()
-        .find(|&clause| clause == phi_prime)
-        .ok_or_else(|| QuantifierError::ClauseDoesntAppearInCnf(phi_prime.clone()))?;
+        .any(|clause| clause.iter().collect::<IndexSet<_>>() == literals)
+    {
+        return Err(QuantifierError::ClauseDoesntAppearInCnf(phi_prime.clone()).into());
+    }
 
-    // Cloning here may be unnecessary
-    let free_vars = pool.free_vars(selected_clause);
+    let free_vars = pool.free_vars(phi_prime);
 
     // While all bindings in `r_bindings` must also be in `new_bindings`, the same is not true in
     // the opposite direction. That is because some variables from the set may be omitted in the
@@ -309,6 +487,9 @@ mod tests {
                 (declare-fun a () Real)
                 (declare-fun b () Real)
                 (declare-fun x () Real)
+                (declare-fun f (Real) Real)
+                (declare-fun g (Real Real) Real)
+                (declare-fun P (Real) Bool)
             ",
             "Simple working examples" {
                 "(step t1 (cl (or (not (forall ((p Bool)) p)) q))
@@ -354,6 +535,44 @@ mod tests {
                 "(step t1 (cl (or (not (forall ((x Real) (y Real)) (= x y))) (= a b)))
                     :rule forall_inst :args ((= x a) b))": false,
             }
+            "Arguments given as assignments" {
+                "(step t1 (cl (or (not (forall ((x Real) (y Real)) (< x y))) (< a b)))
+                    :rule forall_inst :args ((:= (x Real) a) (:= (y Real) b)))": true,
+                "(step t1 (cl (or (not (forall ((x Real) (y Real)) (< x y))) (< a b)))
+                    :rule forall_inst :args ((:= (y Real) b) (:= (x Real) a)))": true,
+                "(step t1 (cl (or (not (forall ((x Real) (y Real)) (< x y))) (< a b)))
+                    :rule forall_inst :args ((:= (y Real) a) (:= (x Real) b)))": false,
+                "(step t1 (cl (or (not (forall ((x Real) (y Real)) (< x y))) (< a b)))
+                    :rule forall_inst :args ((:= (x Real) a) (:= (z Real) b)))": false,
+                "(step t1 (cl (or (not (forall ((x Real) (y Real)) (< x y))) (< a b)))
+                    :rule forall_inst :args ((:= (x Real) a) (:= (x Real) b)))": false,
+                "(step t1 (cl (or (not (forall ((x Real) (y Real)) (< x y))) (< a b)))
+                    :rule forall_inst :args ((:= (x Real) a) b))": false,
+            }
+            "Instantiation recovered from patterns" {
+                "(step t1 (cl (or (not (forall ((x Real)) (! (P (f x)) :pattern ((f x)))))
+                    (P (f a)))) :rule forall_inst)": true,
+                "(step t1 (cl (or (not (forall ((x Real) (y Real))
+                    (! (= (g x y) (g y x)) :pattern ((g x y)))))
+                    (= (g a b) (g b a)))) :rule forall_inst)": true,
+                "(step t1 (cl (or (not (forall ((x Real) (y Real))
+                    (! (< (f x) (f y)) :pattern ((f x) (f y)))))
+                    (< (f b) (f a)))) :rule forall_inst)": true,
+                "(step t1 (cl (or (not (forall ((x Real)) (! (P (f x)) :pattern ((f x)))))
+                    (P (f a)))) :rule forall_inst :args (a))": true,
+                "(step t1 (cl (or (not (forall ((x Real))
+                    (! (or (P (f x)) (forall ((x Real)) (P (f x)))) :pattern ((f x)))))
+                    (or (P (f a)) (forall ((x Real)) (P (f x)))))) :rule forall_inst)": true,
+            }
+            "No instantiation from patterns matches" {
+                "(step t1 (cl (or (not (forall ((x Real)) (! (P (f x)) :pattern ((f x)))))
+                    (P a))) :rule forall_inst)": false,
+                "(step t1 (cl (or (not (forall ((x Real) (y Real))
+                    (! (< (f x) y) :pattern ((f x)))))
+                    (< (f a) b))) :rule forall_inst)": false,
+                "(step t1 (cl (or (not (forall ((x Real)) (P (f x)))) (P (f a))))
+                    :rule forall_inst)": false,
+            }
         }
     }
 
@@ -465,20 +684,8 @@ mod tests {
             let mut bindings = Vec::new();
             let prenexed = prenex_forall(pool, &mut bindings, &nnf);
             let cnf = conjunctive_normal_form(&prenexed);
-            let mut clauses: Vec<_> = cnf
-                .into_iter()
-                .map(|c| match c.as_slice() {
-                    [] => unreachable!(),
-                    [term] => term.clone(),
-                    _ => pool.add(Term::Op(Operator::Or, c)),
-                })
-                .collect();
-
-            let conjunctions = if clauses.len() == 1 {
-                clauses.pop().unwrap()
-            } else {
-                pool.add(Term::Op(Operator::And, clauses))
-            };
+            let clauses: Vec<_> = cnf.into_iter().map(|c| pool.mk_or(c)).collect();
+            let conjunctions = pool.mk_and(clauses);
 
             if bindings.is_empty() {
                 conjunctions
@@ -600,6 +807,44 @@ mod tests {
                     (forall ((p Bool)) (or p false))
                 )) :rule qnt_cnf)": true,
             }
+            "Literals reordered or repeated" {
+                "(step t1 (cl (or
+                    (not (forall ((p Bool) (q Bool)) (not (and p q))))
+                    (forall ((p Bool) (q Bool)) (or (not q) (not p)))
+                )) :rule qnt_cnf)": true,
+
+                "(step t1 (cl (or
+                    (not (forall ((p Bool) (q Bool)) (or p q p)))
+                    (forall ((p Bool) (q Bool)) (or q p))
+                )) :rule qnt_cnf)": true,
+
+                "(step t1 (cl (or
+                    (not (forall ((p Bool) (q Bool)) (or p q)))
+                    (forall ((p Bool) (q Bool)) (or q p (not p)))
+                )) :rule qnt_cnf)": false,
+            }
+            "Conclusion given as two literals" {
+                "(step t1 (cl
+                    (not (forall ((p Bool) (q Bool)) (not (and p q))))
+                    (forall ((p Bool) (q Bool)) (or (not p) (not q)))
+                ) :rule qnt_cnf)": true,
+
+                "(step t1 (cl
+                    (forall ((p Bool) (q Bool)) (not (and p q)))
+                    (forall ((p Bool) (q Bool)) (or (not p) (not q)))
+                ) :rule qnt_cnf)": false,
+            }
+            "Clause without quantified variables" {
+                "(step t1 (cl (or
+                    (not (forall ((p Bool)) (and (or p (not p)) (or true false))))
+                    (or true false)
+                )) :rule qnt_cnf)": true,
+
+                "(step t1 (cl (or
+                    (not (forall ((p Bool)) (and p (or true false))))
+                    (or true true)
+                )) :rule qnt_cnf)": false,
+            }
         }
     }
 }
@@ -0,0 +1,111 @@
+//! The checking of `rare_rewrite` steps, which reference rewrite rules written in RARE, the
+//! language cvc5 uses to describe its rewrites. The rules themselves are loaded into the
+//! [`RuleDatabase`](crate::rewriting::RuleDatabase) used by the checker.
+
+use super::{assert_clause_len, assert_is_expected, assert_num_args, RuleResult};
+use crate::{ast::*, checker::trace::trace_value, rewriting::RareRule};
+
+/// Checks a `rare_rewrite` step that uses `rule`, by instantiating the rule with the step
+/// arguments and comparing the result with the conclusion. The first argument is the name of the
+/// rule, and the remaining ones give the instantiation of each parameter, in order.
+pub(super) fn check_rare_rewrite(
+    rule: &RareRule,
+    conclusion: &[Rc<Term>],
+    args: &[Rc<Term>],
+    pool: &mut dyn TermPool,
+) -> RuleResult {
+    assert_num_args(args, rule.num_params() + 1)?;
+    assert_clause_len(conclusion, 1)?;
+
+    let (lhs, rhs) = rule.instantiate(&args[1..], pool)?;
+    trace_value(|| format!("instantiated rule: {} -> {}", lhs, rhs));
+
+    let (left, right) = match_term_err!((= l r) = &conclusion[0])?;
+    assert_is_expected(left, lhs)?;
+    assert_is_expected(right, rhs)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rewriting::RuleDatabase;
+    use std::sync::Arc;
+
+    const RULES: &str = "
+        (define-rule bool-double-not-elim ((t Bool)) (not (not t)) t)
+        (define-rule bool-and-true ((xs Bool :list) (ys Bool :list))
+            (and xs true ys)
+            (and xs ys))
+        (define-rule* bool-or-flatten ((xs Bool :list) (b Bool) (ys Bool :list) (zs Bool :list))
+            (or xs (or b ys) zs)
+            (or xs b ys zs))
+        (define-cond-rule arith-div-by-one ((t Int) (c Int)) (= c 1) (div t c) t)
+        (define-rule bv-extract-whole ((x ?BitVec) (n Int))
+            (def (m (- n 1)))
+            ((_ extract m 0) x)
+            x)
+        (define-rule uf-apply ((f (-> Int Int)) (x Int)) (f x) (f x))
+        (define-rule bool-eq-refl ((t Bool)) (= t t) true)
+    ";
+
+    #[test]
+    fn rare_rewrite() {
+        let rules = RuleDatabase::parse_rare(RULES.as_bytes()).unwrap();
+        test_cases! {
+            definitions = "
+                (declare-fun p () Bool)
+                (declare-fun q () Bool)
+                (declare-fun r () Bool)
+                (declare-fun a () Int)
+                (declare-fun x () Real)
+                (declare-fun f (Int) Int)
+                (declare-fun g (Real) Real)
+                (declare-fun z () (_ BitVec 4))
+            ",
+            config = crate::checker::Config::new().rare_rules(Some(Arc::new(rules))),
+            "Simple rules" {
+                r#"(step t1 (cl (= (not (not p)) p))
+                    :rule rare_rewrite :args ("bool-double-not-elim" p))"#: true,
+                r#"(step t1 (cl (= (not (not p)) q))
+                    :rule rare_rewrite :args ("bool-double-not-elim" p))"#: false,
+                r#"(step t1 (cl (= (not (not p)) p))
+                    :rule rare_rewrite :args ("bool-double-not-elim" q))"#: false,
+                r#"(step t1 (cl (= (not (not p)) p))
+                    :rule rare_rewrite :args ("bool-double-not-elim"))"#: false,
+                r#"(step t1 (cl (= (f a) (f a))) :rule rare_rewrite :args ("uf-apply" f a))"#: true,
+            }
+            "List parameters" {
+                r#"(step t1 (cl (= (and p true q r) (and p q r)))
+                    :rule rare_rewrite :args ("bool-and-true" p (rare-list q r)))"#: true,
+                r#"(step t1 (cl (= (and p q true) (and p q)))
+                    :rule rare_rewrite :args ("bool-and-true" (rare-list p q) r))"#: false,
+                r#"(step t1 (cl (= (and p true q) (and p q)))
+                    :rule rare_rewrite :args ("bool-and-true" p q))"#: true,
+                r#"(step t1 (cl (= (or p (or q r) q) (or p q r q)))
+                    :rule rare_rewrite :args ("bool-or-flatten" p q r q))"#: true,
+                r#"(step t1 (cl (= (or (or q r p) p) (or q r p p)))
+                    :rule rare_rewrite :args ("bool-or-flatten" (rare-list q) q (rare-list r p) p))"#: false,
+            }
+            "Definitions and indexed operators" {
+                r#"(step t1 (cl (= ((_ extract 3 0) z) z))
+                    :rule rare_rewrite :args ("bv-extract-whole" z 4))"#: true,
+                r#"(step t1 (cl (= ((_ extract 3 0) z) z))
+                    :rule rare_rewrite :args ("bv-extract-whole" z 3))"#: false,
+            }
+            "Conditions" {
+                r#"(step t1 (cl (= (div a 1) a))
+                    :rule rare_rewrite :args ("arith-div-by-one" a 1))"#: true,
+                r#"(step t1 (cl (= (div a 2) a))
+                    :rule rare_rewrite :args ("arith-div-by-one" a 2))"#: false,
+            }
+            "Parameter sorts" {
+                r#"(step t1 (cl (= (= p p) true)) :rule rare_rewrite :args ("bool-eq-refl" p))"#: true,
+                r#"(step t1 (cl (= (= a a) true)) :rule rare_rewrite :args ("bool-eq-refl" a))"#: false,
+                r#"(step t1 (cl (= (g x) (g x))) :rule rare_rewrite :args ("uf-apply" g x))"#: false,
+            }
+            "Rules are looked up by their RARE names" {
+                r#"(step t1 (cl (= (not (not p)) p)) :rule rare_rewrite :args ("not_simplify"))"#: false,
+                r#"(step t1 (cl (= (not (not p)) p)) :rule rare_rewrite :args ("bool-not-elim"))"#: false,
+            }
+        }
+    }
+}
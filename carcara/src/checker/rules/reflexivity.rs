@@ -20,9 +20,15 @@ pub fn refl(
 
     let (left, right) = match_term_err!((= l r) = &conclusion[0])?;
 
-    // If the two terms are directly identical, we don't need to do any more work. We make sure to
-    // do this check before we try to get the context substitution, because `refl` can be used
-    // outside of any subproof
+    // Most `refl` steps are between identical terms, so we check that first, since it doesn't
+    // require constructing a polyequality comparator, which allocates
+    if left == right {
+        return Ok(());
+    }
+
+    // If the two terms are alpha equivalent, we don't need to do any more work. We make sure to do
+    // this check before we try to get the context substitution, because `refl` can be used outside
+    // of any subproof
     if alpha_equiv(left, right, polyeq_time) {
         return Ok(());
     }
@@ -2,7 +2,7 @@ use super::{
     assert_clause_len, assert_eq, assert_is_bool_constant, assert_num_args, assert_num_premises,
     CheckerError, Premise, RuleArgs, RuleResult,
 };
-use crate::{ast::*, resolution::*};
+use crate::{ast::*, checker::trace, resolution::*};
 use indexmap::IndexSet;
 
 pub fn resolution(rule_args: RuleArgs) -> RuleResult {
@@ -27,9 +27,18 @@ pub fn resolution(rule_args: RuleArgs) -> RuleResult {
 
     let premise_clauses: Vec<_> = premises.iter().map(|p| p.clause).collect();
 
-    greedy_resolution(conclusion, &premise_clauses, pool, false)
-        .map(|_| ())
+    greedy_resolution(conclusion, &premise_clauses, pool, trace::is_enabled())
+        .map(|ResolutionTrace { pivot_trace, .. }| {
+            trace::trace_value(|| {
+                let pivots: Vec<_> = pivot_trace
+                    .iter()
+                    .map(|(pivot, polarity)| format!("{} {}", pivot, polarity))
+                    .collect();
+                format!("pivots: {}", pivots.join(", "))
+            });
+        })
         .or_else(|greedy_error| {
+            trace::trace_value(|| format!("greedy resolution failed: {}", greedy_error));
             if rup_resolution(conclusion, premises) {
                 Ok(())
             } else {
@@ -207,29 +216,29 @@ pub fn tautology(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult
     assert_clause_len(conclusion, 1)?;
     assert_is_bool_constant(&conclusion[0], true)?;
 
-    let premise = premises[0].clause;
-    let mut seen = IndexSet::with_capacity(premise.len());
-    let with_negations_removed = premise.iter().map(Rc::remove_all_negations_with_polarity);
-    for (polarity, term) in with_negations_removed {
-        if seen.contains(&(!polarity, term)) {
-            return Ok(());
-        }
-        seen.insert((polarity, term));
-    }
-    Err(ResolutionError::TautologyFailed.into())
+    rassert!(
+        Clause::from(premises[0].clause).is_tautology(),
+        ResolutionError::TautologyFailed
+    );
+    Ok(())
 }
 
 pub fn contraction(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
     assert_num_premises(premises, 1)?;
 
-    let premise_set: IndexSet<_> = premises[0].clause.iter().collect();
+    let premise = Clause::from(premises[0].clause);
+    let conclusion = Clause::from(conclusion);
+    if premise.same_literals(&conclusion) {
+        return Ok(());
+    }
+
+    let premise_set: IndexSet<_> = premise.iter().collect();
     let conclusion_set: IndexSet<_> = conclusion.iter().collect();
     if let Some(&t) = premise_set.difference(&conclusion_set).next() {
         Err(CheckerError::ContractionMissingTerm(t.clone()))
-    } else if let Some(&t) = conclusion_set.difference(&premise_set).next() {
-        Err(CheckerError::ContractionExtraTerm(t.clone()))
     } else {
-        Ok(())
+        let t = conclusion_set.difference(&premise_set).next().unwrap();
+        Err(CheckerError::ContractionExtraTerm((*t).clone()))
     }
 }
 
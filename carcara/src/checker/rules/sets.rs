@@ -0,0 +1,212 @@
+use super::{
+    assert_clause_len, assert_eq, assert_is_expected, assert_num_premises, get_premise_term,
+    RuleArgs, RuleResult,
+};
+use crate::{ast::*, checker::error::CheckerError};
+
+/// Checks an instance of the set extensionality lemma:
+/// `(cl (= a b) (not (= (set.member k a) (set.member k b))))`, where `k` is an element that
+/// belongs to exactly one of the sets, if they are different. This element must be given as the
+/// choice term `(choice ((x T)) (not (= (set.member x a) (set.member x b))))`.
+pub fn sets_ext(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 2)?;
+
+    let (a, b) = match_term_err!((= a b) = &conclusion[0])?;
+    let ((k, a_member), (k_b, b_member)) =
+        match_term_err!((not (= (setmember k a_m) (setmember k_b b_m))) = &conclusion[1])?;
+    assert_eq(a, a_member)?;
+    assert_eq(b, b_member)?;
+    assert_eq(k, k_b)?;
+
+    let Sort::Set(element_sort) = pool.sort(a).as_sort().cloned().unwrap() else {
+        return Err(CheckerError::TermOfWrongForm("a set", a.clone()));
+    };
+    let (bindings, inner) = match k.as_ref() {
+        Term::Binder(Binder::Choice, bindings, inner) if bindings.len() == 1 => (bindings, inner),
+        _ => return Err(CheckerError::TermOfWrongForm("a choice term", k.clone())),
+    };
+    let (name, sort) = &bindings[0];
+    assert_eq(sort, &element_sort)?;
+
+    let x = pool.add(Term::new_var(name, sort.clone()));
+    // Otherwise, the bound variable would capture a free variable with the same name and sort
+    for t in [a, b] {
+        if pool.free_vars(t).contains(&x) {
+            return Err(CheckerError::BoundVarOccursFree(name.clone(), t.clone()));
+        }
+    }
+    let expected = build_term!(
+        pool,
+        (not (= (setmember {x.clone()} {a.clone()}) (setmember {x} {b.clone()})))
+    );
+    assert_is_expected(inner, expected)
+}
+
+/// Checks the injectivity of singleton sets: from the premise
+/// `(= (set.singleton a) (set.singleton b))`, concludes `(= a b)`.
+pub fn sets_singleton_inj(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
+    assert_num_premises(premises, 1)?;
+    assert_clause_len(conclusion, 1)?;
+
+    let (a, b) =
+        match_term_err!((= (setsingleton a) (setsingleton b)) = get_premise_term(&premises[0])?)?;
+    let (got_a, got_b) = match_term_err!((= a b) = &conclusion[0])?;
+    assert_eq(got_a, a)?;
+    assert_eq(got_b, b)
+}
+
+/// Checks the unfolding of a membership test in a set built by some set operator, for example
+/// `(= (set.member x (set.union a b)) (or (set.member x a) (set.member x b)))`. The set may be a
+/// union, intersection, difference, complement, singleton, insertion, or the empty or universe
+/// set.
+pub fn sets_member(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let ((x, set), got) = match_term_err!((= (setmember x set) got) = &conclusion[0])?;
+    let member = |pool: &mut dyn TermPool, s: &Rc<Term>| {
+        pool.add(Term::Op(Operator::SetMember, vec![x.clone(), s.clone()]))
+    };
+    let expected = match set.as_ref() {
+        Term::Op(Operator::SetUnion, args) => {
+            let args = args.iter().map(|s| member(pool, s)).collect();
+            pool.add(Term::Op(Operator::Or, args))
+        }
+        Term::Op(Operator::SetInter, args) => {
+            let args = args.iter().map(|s| member(pool, s)).collect();
+            pool.add(Term::Op(Operator::And, args))
+        }
+        Term::Op(Operator::SetMinus, args) => {
+            let a = member(pool, &args[0]);
+            let not_b = build_term!(pool, (not {member(pool, &args[1])}));
+            pool.add(Term::Op(Operator::And, vec![a, not_b]))
+        }
+        Term::Op(Operator::SetComplement, args) => {
+            let a = member(pool, &args[0]);
+            build_term!(pool, (not { a }))
+        }
+        Term::Op(Operator::SetSingleton, args) => {
+            build_term!(pool, (= {x.clone()} {args[0].clone()}))
+        }
+        Term::Op(Operator::SetInsert, args) => {
+            let (inner, elements) = args.split_last().unwrap();
+            let mut disjuncts: Vec<_> = elements
+                .iter()
+                .map(|e| build_term!(pool, (= {x.clone()} {e.clone()})))
+                .collect();
+            disjuncts.push(member(pool, inner));
+            pool.add(Term::Op(Operator::Or, disjuncts))
+        }
+        Term::ParamOp { op: ParamOperator::SetEmpty, .. } => pool.bool_false(),
+        Term::ParamOp { op: ParamOperator::SetUniverse, .. } => pool.bool_true(),
+        _ => {
+            return Err(CheckerError::TermOfWrongForm(
+                "a set operation",
+                set.clone(),
+            ))
+        }
+    };
+    assert_is_expected(got, expected)
+}
+
+#[cfg(test)]
+mod tests {
+    const DEFINITIONS: &str = "
+        (declare-const a (Set Int))
+        (declare-const b (Set Int))
+        (declare-const c (Set Int))
+        (declare-const x Int)
+        (declare-const y Int)
+    ";
+
+    #[test]
+    fn sets_ext() {
+        test_cases! {
+            definitions = DEFINITIONS,
+            "Simple working examples" {
+                "(step t1 (cl (= a b) (not (= (set.member
+                    (choice ((i Int)) (not (= (set.member i a) (set.member i b)))) a)
+                    (set.member (choice ((i Int)) (not (= (set.member i a) (set.member i b)))) b))))
+                :rule sets_ext)": true,
+            }
+            "Element is not the difference witness" {
+                "(step t1 (cl (= a b) (not (= (set.member x a) (set.member x b))))
+                :rule sets_ext)": false,
+
+                "(step t1 (cl (= a b) (not (= (set.member
+                    (choice ((i Int)) (not (= (set.member i a) (set.member i c)))) a)
+                    (set.member (choice ((i Int)) (not (= (set.member i a) (set.member i c)))) b))))
+                :rule sets_ext)": false,
+            }
+            "Terms don't match" {
+                "(step t1 (cl (= a b) (not (= (set.member
+                    (choice ((i Int)) (not (= (set.member i a) (set.member i b)))) b)
+                    (set.member (choice ((i Int)) (not (= (set.member i a) (set.member i b)))) a))))
+                :rule sets_ext)": false,
+            }
+            "Bound variable captures a free variable" {
+                "(step t1 (cl (= (set.insert x a) b) (not (= (set.member
+                    (choice ((x Int)) (not (= (set.member x (set.insert x a)) (set.member x b))))
+                        (set.insert x a))
+                    (set.member
+                        (choice ((x Int)) (not (= (set.member x (set.insert x a)) (set.member x b))))
+                        b))))
+                :rule sets_ext)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn sets_singleton_inj() {
+        test_cases! {
+            definitions = DEFINITIONS,
+            "Simple working examples" {
+                "(assume h1 (= (set.singleton x) (set.singleton y)))
+                (step t2 (cl (= x y)) :rule sets_singleton_inj :premises (h1))": true,
+            }
+            "Conclusion doesn't match premise" {
+                "(assume h1 (= (set.singleton x) (set.singleton y)))
+                (step t2 (cl (= y x)) :rule sets_singleton_inj :premises (h1))": false,
+
+                "(assume h1 (= (set.singleton x) (set.singleton (+ y 1))))
+                (step t2 (cl (= x y)) :rule sets_singleton_inj :premises (h1))": false,
+            }
+            "Premise is not an equality of singletons" {
+                "(assume h1 (= a (set.singleton y)))
+                (step t2 (cl (= x y)) :rule sets_singleton_inj :premises (h1))": false,
+            }
+        }
+    }
+
+    #[test]
+    fn sets_member() {
+        test_cases! {
+            definitions = DEFINITIONS,
+            "Simple working examples" {
+                "(step t1 (cl (= (set.member x (set.union a b)) (or (set.member x a) (set.member x b))))
+                    :rule sets_member)": true,
+                "(step t1 (cl (= (set.member x (set.inter a b)) (and (set.member x a) (set.member x b))))
+                    :rule sets_member)": true,
+                "(step t1 (cl (= (set.member x (set.minus a b))
+                    (and (set.member x a) (not (set.member x b))))) :rule sets_member)": true,
+                "(step t1 (cl (= (set.member x (set.complement a)) (not (set.member x a))))
+                    :rule sets_member)": true,
+                "(step t1 (cl (= (set.member x (set.singleton y)) (= x y))) :rule sets_member)": true,
+                "(step t1 (cl (= (set.member x (set.insert 1 y a)) (or (= x 1) (= x y) (set.member x a))))
+                    :rule sets_member)": true,
+                "(step t1 (cl (= (set.member x (as set.empty (Set Int))) false)) :rule sets_member)": true,
+                "(step t1 (cl (= (set.member x (as set.universe (Set Int))) true)) :rule sets_member)": true,
+            }
+            "Wrong unfolding" {
+                "(step t1 (cl (= (set.member x (set.union a b)) (and (set.member x a) (set.member x b))))
+                    :rule sets_member)": false,
+                "(step t1 (cl (= (set.member x (set.minus a b))
+                    (and (set.member x b) (not (set.member x a))))) :rule sets_member)": false,
+                "(step t1 (cl (= (set.member x (set.singleton y)) (= y x))) :rule sets_member)": false,
+                "(step t1 (cl (= (set.member x (as set.empty (Set Int))) true)) :rule sets_member)": false,
+            }
+            "Set is not an operation" {
+                "(step t1 (cl (= (set.member x a) (set.member x a))) :rule sets_member)": false,
+            }
+        }
+    }
+}
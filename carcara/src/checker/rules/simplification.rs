@@ -38,10 +38,23 @@ macro_rules! simplify {
     };
 }
 
+type SimplifyFunction = fn(&Term, &mut dyn TermPool) -> Option<Rc<Term>>;
+
+/// The transformations of the simplification rules that rewrite a term into an equivalent one,
+/// with the name of each rule. These are used by the rewriting engine to check `all_simplify` steps.
+pub const SIMPLIFICATION_REWRITES: &[(&str, SimplifyFunction)] = &[
+    ("ite_simplify", ite_simplify_rewrite),
+    ("eq_simplify", eq_simplify_rewrite),
+    ("not_simplify", not_simplify_rewrite),
+    ("implies_simplify", implies_simplify_rewrite),
+    ("equiv_simplify", equiv_simplify_rewrite),
+    ("bool_simplify", bool_simplify_rewrite),
+];
+
 fn generic_simplify_rule(
     conclusion: &[Rc<Term>],
     pool: &mut dyn TermPool,
-    simplify_function: fn(&Term, &mut dyn TermPool) -> Option<Rc<Term>>,
+    simplify_function: SimplifyFunction,
 ) -> RuleResult {
     assert_clause_len(conclusion, 1)?;
 
@@ -82,81 +95,87 @@ fn generic_simplify_rule(
     Ok(())
 }
 
-pub fn ite_simplify(args: RuleArgs) -> RuleResult {
-    generic_simplify_rule(args.conclusion, args.pool, |term, pool| {
-        simplify!(term {
-            // ite true t_1 t_2 => t_1
-            (ite true t_1 t_2): (_, t_1, _) => t_1.clone(),
+/// Applies a single `ite_simplify` transformation to the root of `term`, if any applies.
+fn ite_simplify_rewrite(term: &Term, pool: &mut dyn TermPool) -> Option<Rc<Term>> {
+    simplify!(term {
+        // ite true t_1 t_2 => t_1
+        (ite true t_1 t_2): (_, t_1, _) => t_1.clone(),
 
-            // ite false t_1 t_2 => t_2
-            (ite false t_1 t_2): (_, _, t_2) => t_2.clone(),
+        // ite false t_1 t_2 => t_2
+        (ite false t_1 t_2): (_, _, t_2) => t_2.clone(),
 
-            // ite phi t t => t
-            (ite phi t t): (_, t_1, t_2) if t_1 == t_2 => t_1.clone(),
+        // ite phi t t => t
+        (ite phi t t): (_, t_1, t_2) if t_1 == t_2 => t_1.clone(),
 
-            // ite psi true false => psi
-            (ite psi true false): (psi, _, _) => psi.clone(),
+        // ite psi true false => psi
+        (ite psi true false): (psi, _, _) => psi.clone(),
 
-            // ite psi false true => ¬psi
-            (ite psi false true): (psi, _, _) => build_term!(pool, (not {psi.clone()})),
+        // ite psi false true => ¬psi
+        (ite psi false true): (psi, _, _) => build_term!(pool, (not {psi.clone()})),
 
-            // ite ¬phi t_1 t_2 => ite phi t_2 t_1
-            (ite (not phi) t_1 t_2): (phi, t_1, t_2) => {
-                build_term!(pool, (ite {phi.clone()} {t_2.clone()} {t_1.clone()}))
-            },
+        // ite ¬phi t_1 t_2 => ite phi t_2 t_1
+        (ite (not phi) t_1 t_2): (phi, t_1, t_2) => {
+            build_term!(pool, (ite {phi.clone()} {t_2.clone()} {t_1.clone()}))
+        },
 
-            // ite phi (ite phi t_1 t_2) t_3 => ite phi t_1 t_3
-            (ite phi (ite phi t_1 t_2) t_3): (phi_1, (phi_2, t_1, _), t_3) if phi_1 == phi_2 => {
-                build_term!(pool, (ite {phi_1.clone()} {t_1.clone()} {t_3.clone()}))
-            },
+        // ite phi (ite phi t_1 t_2) t_3 => ite phi t_1 t_3
+        (ite phi (ite phi t_1 t_2) t_3): (phi_1, (phi_2, t_1, _), t_3) if phi_1 == phi_2 => {
+            build_term!(pool, (ite {phi_1.clone()} {t_1.clone()} {t_3.clone()}))
+        },
 
-            // ite phi t_1 (ite phi t_2 t_3) => ite phi t_1 t_3
-            (ite phi t_1 (ite phi t_2 t_3)): (phi_1, t_1, (phi_2, _, t_3)) if phi_1 == phi_2 => {
-                build_term!(pool, (ite {phi_1.clone()} {t_1.clone()} {t_3.clone()}))
-            },
+        // ite phi t_1 (ite phi t_2 t_3) => ite phi t_1 t_3
+        (ite phi t_1 (ite phi t_2 t_3)): (phi_1, t_1, (phi_2, _, t_3)) if phi_1 == phi_2 => {
+            build_term!(pool, (ite {phi_1.clone()} {t_1.clone()} {t_3.clone()}))
+        },
 
-            // ite psi true phi => psi v phi
-            (ite psi true phi): (psi, _, phi) => {
-                build_term!(pool, (or {psi.clone()} {phi.clone()}))
-            },
+        // ite psi true phi => psi v phi
+        (ite psi true phi): (psi, _, phi) => {
+            build_term!(pool, (or {psi.clone()} {phi.clone()}))
+        },
 
-            // ite psi phi false => psi ^ phi
-            (ite psi phi false): (psi, phi, _) => {
-                build_term!(pool, (and {psi.clone()} {phi.clone()}))
-            },
+        // ite psi phi false => psi ^ phi
+        (ite psi phi false): (psi, phi, _) => {
+            build_term!(pool, (and {psi.clone()} {phi.clone()}))
+        },
 
-            // ite psi false phi => ¬psi ^ phi
-            (ite psi false phi): (psi, _, phi) => {
-                build_term!(pool, (and (not {psi.clone()}) {phi.clone()}))
-            },
+        // ite psi false phi => ¬psi ^ phi
+        (ite psi false phi): (psi, _, phi) => {
+            build_term!(pool, (and (not {psi.clone()}) {phi.clone()}))
+        },
 
-            // ite psi phi true => ¬psi v phi
-            (ite psi phi true): (psi, phi, _) => {
-                build_term!(pool, (or (not {psi.clone()}) {phi.clone()}))
-            },
-        })
+        // ite psi phi true => ¬psi v phi
+        (ite psi phi true): (psi, phi, _) => {
+            build_term!(pool, (or (not {psi.clone()}) {phi.clone()}))
+        },
     })
 }
 
-pub fn eq_simplify(args: RuleArgs) -> RuleResult {
-    generic_simplify_rule(args.conclusion, args.pool, |term, pool| {
-        simplify!(term {
-            // t = t => true
-            (= t t): (t1, t2) if t1 == t2 => pool.bool_true(),
-
-            // t_1 = t_2 => false, if t_1 and t_2 are different numerical constants
-            (= t t): (t1, t2) if {
-                let t1 = t1.as_signed_number();
-                let t2 = t2.as_signed_number();
-                t1.is_some() && t2.is_some() && t1 != t2
-            } => pool.bool_false(),
-
-            // ¬(t = t) => false, if t is a numerical constant
-            (not (= t t)): (t1, t2) if t1 == t2 && t1.is_signed_number() => pool.bool_false(),
-        })
+pub fn ite_simplify(args: RuleArgs) -> RuleResult {
+    generic_simplify_rule(args.conclusion, args.pool, ite_simplify_rewrite)
+}
+
+/// Applies a single `eq_simplify` transformation to the root of `term`, if any applies.
+fn eq_simplify_rewrite(term: &Term, pool: &mut dyn TermPool) -> Option<Rc<Term>> {
+    simplify!(term {
+        // t = t => true
+        (= t t): (t1, t2) if t1 == t2 => pool.bool_true(),
+
+        // t_1 = t_2 => false, if t_1 and t_2 are different numerical constants
+        (= t t): (t1, t2) if {
+            let t1 = t1.as_signed_number();
+            let t2 = t2.as_signed_number();
+            t1.is_some() && t2.is_some() && t1 != t2
+        } => pool.bool_false(),
+
+        // ¬(t = t) => false, if t is a numerical constant
+        (not (= t t)): (t1, t2) if t1 == t2 && t1.is_signed_number() => pool.bool_false(),
     })
 }
 
+pub fn eq_simplify(args: RuleArgs) -> RuleResult {
+    generic_simplify_rule(args.conclusion, args.pool, eq_simplify_rewrite)
+}
+
 /// Used for both the `and_simplify` and `or_simplify` rules, depending on `rule_kind`. `rule_kind`
 /// has to be either `Operator::And` or `Operator::Or`.
 fn generic_and_or_simplify(
@@ -267,131 +286,143 @@ pub fn or_simplify(RuleArgs { conclusion, pool, .. }: RuleArgs) -> RuleResult {
     generic_and_or_simplify(pool, conclusion, Operator::Or)
 }
 
-pub fn not_simplify(args: RuleArgs) -> RuleResult {
-    generic_simplify_rule(args.conclusion, args.pool, |term, pool| {
-        simplify!(term {
-            // ¬(¬phi) => phi
-            (not (not phi)): phi => phi.clone(),
+/// Applies a single `not_simplify` transformation to the root of `term`, if any applies.
+fn not_simplify_rewrite(term: &Term, pool: &mut dyn TermPool) -> Option<Rc<Term>> {
+    simplify!(term {
+        // ¬(¬phi) => phi
+        (not (not phi)): phi => phi.clone(),
 
-            // ¬false => true
-            (not false): _ => pool.bool_true(),
+        // ¬false => true
+        (not false): _ => pool.bool_true(),
 
-            // ¬true => false
-            (not true): _ => pool.bool_false(),
-        })
+        // ¬true => false
+        (not true): _ => pool.bool_false(),
     })
 }
 
-pub fn implies_simplify(args: RuleArgs) -> RuleResult {
-    generic_simplify_rule(args.conclusion, args.pool, |term, pool| {
-        simplify!(term {
-            // ¬phi_1 -> ¬phi_2 => phi_2 -> phi_1
-            (=> (not phi_1) (not phi_2)): (phi_1, phi_2) => {
-                build_term!(pool, (=> {phi_2.clone()} {phi_1.clone()}))
-            },
+pub fn not_simplify(args: RuleArgs) -> RuleResult {
+    generic_simplify_rule(args.conclusion, args.pool, not_simplify_rewrite)
+}
 
-            // false -> phi => true
-            (=> false phi): _ => pool.bool_true(),
+/// Applies a single `implies_simplify` transformation to the root of `term`, if any applies.
+fn implies_simplify_rewrite(term: &Term, pool: &mut dyn TermPool) -> Option<Rc<Term>> {
+    simplify!(term {
+        // ¬phi_1 -> ¬phi_2 => phi_2 -> phi_1
+        (=> (not phi_1) (not phi_2)): (phi_1, phi_2) => {
+            build_term!(pool, (=> {phi_2.clone()} {phi_1.clone()}))
+        },
 
-            // phi -> true => true
-            (=> phi true): _ => pool.bool_true(),
+        // false -> phi => true
+        (=> false phi): _ => pool.bool_true(),
 
-            // true -> phi => phi
-            (=> true phi): (_, phi) => phi.clone(),
+        // phi -> true => true
+        (=> phi true): _ => pool.bool_true(),
 
-            // phi -> false => ¬phi
-            (=> phi false): (phi, _) => build_term!(pool, (not {phi.clone()})),
+        // true -> phi => phi
+        (=> true phi): (_, phi) => phi.clone(),
 
-            // phi -> phi => true
-            (=> phi phi): (phi_1, phi_2) if phi_1 == phi_2 => pool.bool_true(),
+        // phi -> false => ¬phi
+        (=> phi false): (phi, _) => build_term!(pool, (not {phi.clone()})),
 
-            // ¬phi -> phi => phi
-            // phi -> ¬phi => ¬phi
-            (=> phi_1 phi_2): (phi_1, phi_2) if {
-                phi_1.remove_negation() == Some(phi_2) || phi_2.remove_negation() == Some(phi_1)
-            } => phi_2.clone(),
+        // phi -> phi => true
+        (=> phi phi): (phi_1, phi_2) if phi_1 == phi_2 => pool.bool_true(),
 
-            // (phi_1 -> phi_2) -> phi_2 => phi_1 v phi_2
-            (=> (=> phi_1 phi_2) phi_3): ((phi_1, phi_2), phi_3) if phi_2 == phi_3 => {
-                build_term!(pool, (or {phi_1.clone()} {phi_2.clone()}))
-            },
-        })
+        // ¬phi -> phi => phi
+        // phi -> ¬phi => ¬phi
+        (=> phi_1 phi_2): (phi_1, phi_2) if {
+            phi_1.remove_negation() == Some(phi_2) || phi_2.remove_negation() == Some(phi_1)
+        } => phi_2.clone(),
+
+        // (phi_1 -> phi_2) -> phi_2 => phi_1 v phi_2
+        (=> (=> phi_1 phi_2) phi_3): ((phi_1, phi_2), phi_3) if phi_2 == phi_3 => {
+            build_term!(pool, (or {phi_1.clone()} {phi_2.clone()}))
+        },
     })
 }
 
-pub fn equiv_simplify(args: RuleArgs) -> RuleResult {
-    generic_simplify_rule(args.conclusion, args.pool, |term, pool| {
-        simplify!(term {
-            // ¬phi_1 = ¬phi_2 => phi_1 = phi_2
-            (= (not phi_1) (not phi_2)): (phi_1, phi_2) => {
-                build_term!(pool, (= {phi_1.clone()} {phi_2.clone()}))
-            },
+pub fn implies_simplify(args: RuleArgs) -> RuleResult {
+    generic_simplify_rule(args.conclusion, args.pool, implies_simplify_rewrite)
+}
 
-            // phi = phi => true
-            (= phi_1 phi_2): (phi_1, phi_2) if phi_1 == phi_2 => pool.bool_true(),
+/// Applies a single `equiv_simplify` transformation to the root of `term`, if any applies.
+fn equiv_simplify_rewrite(term: &Term, pool: &mut dyn TermPool) -> Option<Rc<Term>> {
+    simplify!(term {
+        // ¬phi_1 = ¬phi_2 => phi_1 = phi_2
+        (= (not phi_1) (not phi_2)): (phi_1, phi_2) => {
+            build_term!(pool, (= {phi_1.clone()} {phi_2.clone()}))
+        },
 
-            // phi = ¬phi => false
-            (= phi_1 (not phi_2)): (phi_1, phi_2) if phi_1 == phi_2 => pool.bool_false(),
+        // phi = phi => true
+        (= phi_1 phi_2): (phi_1, phi_2) if phi_1 == phi_2 => pool.bool_true(),
 
-            // ¬phi = phi => false
-            (= (not phi_1) phi_2): (phi_1, phi_2) if phi_1 == phi_2 => pool.bool_false(),
+        // phi = ¬phi => false
+        (= phi_1 (not phi_2)): (phi_1, phi_2) if phi_1 == phi_2 => pool.bool_false(),
 
-            // true = phi => phi
-            (= true phi_1): (_, phi_1) => phi_1.clone(),
+        // ¬phi = phi => false
+        (= (not phi_1) phi_2): (phi_1, phi_2) if phi_1 == phi_2 => pool.bool_false(),
 
-            // phi = true => phi
-            (= phi_1 true): (phi_1, _) => phi_1.clone(),
+        // true = phi => phi
+        (= true phi_1): (_, phi_1) => phi_1.clone(),
 
-            // false = phi => ¬phi
-            (= false phi_1): (_, phi_1) => build_term!(pool, (not {phi_1.clone()})),
+        // phi = true => phi
+        (= phi_1 true): (phi_1, _) => phi_1.clone(),
 
-            // phi = false => ¬phi
-            (= phi_1 false): (phi_1, _) => build_term!(pool, (not {phi_1.clone()})),
-        })
+        // false = phi => ¬phi
+        (= false phi_1): (_, phi_1) => build_term!(pool, (not {phi_1.clone()})),
+
+        // phi = false => ¬phi
+        (= phi_1 false): (phi_1, _) => build_term!(pool, (not {phi_1.clone()})),
     })
 }
 
-pub fn bool_simplify(args: RuleArgs) -> RuleResult {
-    generic_simplify_rule(args.conclusion, args.pool, |term, pool| {
-        simplify!(term {
-            // ¬(phi_1 -> phi_2) => (phi_1 ^ ¬phi_2)
-            (not (=> phi_1 phi_2)): (phi_1, phi_2) => {
-                build_term!(pool, (and {phi_1.clone()} (not {phi_2.clone()})))
-            },
+pub fn equiv_simplify(args: RuleArgs) -> RuleResult {
+    generic_simplify_rule(args.conclusion, args.pool, equiv_simplify_rewrite)
+}
 
-            // ¬(phi_1 v phi_2) => (¬phi_1 ^ ¬phi_2)
-            (not (or phi_1 phi_2)): (phi_1, phi_2) => {
-                build_term!(pool, (and (not {phi_1.clone()}) (not {phi_2.clone()})))
-            },
+/// Applies a single `bool_simplify` transformation to the root of `term`, if any applies.
+fn bool_simplify_rewrite(term: &Term, pool: &mut dyn TermPool) -> Option<Rc<Term>> {
+    simplify!(term {
+        // ¬(phi_1 -> phi_2) => (phi_1 ^ ¬phi_2)
+        (not (=> phi_1 phi_2)): (phi_1, phi_2) => {
+            build_term!(pool, (and {phi_1.clone()} (not {phi_2.clone()})))
+        },
 
-            // ¬(phi_1 ^ phi_2) => (¬phi_1 v ¬phi_2)
-            (not (and phi_1 phi_2)): (phi_1, phi_2) => {
-                build_term!(pool, (or (not {phi_1.clone()}) (not {phi_2.clone()})))
-            },
+        // ¬(phi_1 v phi_2) => (¬phi_1 ^ ¬phi_2)
+        (not (or phi_1 phi_2)): (phi_1, phi_2) => {
+            build_term!(pool, (and (not {phi_1.clone()}) (not {phi_2.clone()})))
+        },
 
-            // (phi_1 -> (phi_2 -> phi_3)) => ((phi_1 ^ phi_2) -> phi_3)
-            (=> phi_1 (=> phi_2 phi_3)): (phi_1, (phi_2, phi_3)) => {
-                build_term!(pool, (=> (and {phi_1.clone()} {phi_2.clone()}) {phi_3.clone()}))
-            },
+        // ¬(phi_1 ^ phi_2) => (¬phi_1 v ¬phi_2)
+        (not (and phi_1 phi_2)): (phi_1, phi_2) => {
+            build_term!(pool, (or (not {phi_1.clone()}) (not {phi_2.clone()})))
+        },
 
-            // ((phi_1 -> phi_2) -> phi_2) => (phi_1 v phi_2)
-            (=> (=> phi_1 phi_2) phi_3): ((phi_1, phi_2), phi_3) if phi_2 == phi_3 => {
-                build_term!(pool, (or {phi_1.clone()} {phi_2.clone()}))
-            },
+        // (phi_1 -> (phi_2 -> phi_3)) => ((phi_1 ^ phi_2) -> phi_3)
+        (=> phi_1 (=> phi_2 phi_3)): (phi_1, (phi_2, phi_3)) => {
+            build_term!(pool, (=> (and {phi_1.clone()} {phi_2.clone()}) {phi_3.clone()}))
+        },
 
-            // (phi_1 ^ (phi_1 -> phi_2)) => (phi_1 ^ phi_2)
-            (and phi_1 (=> phi_2 phi_3)): (phi_1, (phi_2, phi_3)) if phi_1 == phi_2 => {
-                build_term!(pool, (and {phi_1.clone()} {phi_3.clone()}))
-            },
+        // ((phi_1 -> phi_2) -> phi_2) => (phi_1 v phi_2)
+        (=> (=> phi_1 phi_2) phi_3): ((phi_1, phi_2), phi_3) if phi_2 == phi_3 => {
+            build_term!(pool, (or {phi_1.clone()} {phi_2.clone()}))
+        },
 
-            // ((phi_1 -> phi_2) ^ phi_1) => (phi_1 ^ phi_2)
-            (and (=> phi_1 phi_2) phi_3): ((phi_1, phi_2), phi_3) if phi_1 == phi_3 => {
-                build_term!(pool, (and {phi_1.clone()} {phi_2.clone()}))
-            },
-        })
+        // (phi_1 ^ (phi_1 -> phi_2)) => (phi_1 ^ phi_2)
+        (and phi_1 (=> phi_2 phi_3)): (phi_1, (phi_2, phi_3)) if phi_1 == phi_2 => {
+            build_term!(pool, (and {phi_1.clone()} {phi_3.clone()}))
+        },
+
+        // ((phi_1 -> phi_2) ^ phi_1) => (phi_1 ^ phi_2)
+        (and (=> phi_1 phi_2) phi_3): ((phi_1, phi_2), phi_3) if phi_1 == phi_3 => {
+            build_term!(pool, (and {phi_1.clone()} {phi_2.clone()}))
+        },
     })
 }
 
+pub fn bool_simplify(args: RuleArgs) -> RuleResult {
+    generic_simplify_rule(args.conclusion, args.pool, bool_simplify_rewrite)
+}
+
 pub fn qnt_simplify(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 1)?;
     let (left, right) = match_term_err!((= l r) = &conclusion[0])?;
@@ -1,12 +1,12 @@
 use super::{
-    assert_clause_len, assert_eq, assert_num_args, assert_num_premises, assert_polyeq_expected,
-    get_premise_term, RuleArgs, RuleResult,
+    assert_clause_len, assert_eq, assert_is_bool_constant, assert_num_args, assert_num_premises,
+    assert_polyeq_expected, get_premise_term, RuleArgs, RuleResult,
 };
 use crate::{
     ast::*,
     checker::{error::CheckerError, rules::assert_polyeq},
 };
-use std::{cmp, time::Duration};
+use std::{cmp, collections::BTreeSet, time::Duration};
 
 /// A function that takes an `Rc<Term>` and returns a vector corresponding to
 /// the flat form of that term.
@@ -1025,6 +1025,29 @@ pub fn string_length_non_empty(
     Ok(())
 }
 
+pub fn string_code_inj(RuleArgs { args, conclusion, polyeq_time, .. }: RuleArgs) -> RuleResult {
+    assert_num_args(args, 2)?;
+    assert_clause_len(conclusion, 1)?;
+
+    let (t, s) = (&args[0], &args[1]);
+    let ((t_1, minus_one), (t_2, s_1), (t_3, s_2)) = match_term_err!(
+        (or
+            (= (strtocode t) m)
+            (not (= (strtocode t) (strtocode s)))
+            (= t s)
+        ) = &conclusion[0]
+    )?;
+
+    if minus_one.as_signed_integer() != Some(rug::Integer::from(-1)) {
+        return Err(CheckerError::TermOfWrongForm("(- 1)", minus_one.clone()));
+    }
+    for (got, expected) in [(t_1, t), (t_2, t), (t_3, t), (s_1, s), (s_2, s)] {
+        assert_polyeq(got, expected, polyeq_time)?;
+    }
+
+    Ok(())
+}
+
 pub fn re_inter(
     RuleArgs {
         premises, conclusion, polyeq_time, ..
@@ -1224,6 +1247,179 @@ pub fn re_unfold_neg_concat_fixed_suffix(
     assert_eq(&conclusion[0], &expanded)
 }
 
+/// Returns the set of positions `j` such that `s[i..j]` is in the language of the regular
+/// expression `r`. Returns `None` if `r` is not a ground regular expression.
+fn re_match_ends(r: &Rc<Term>, s: &[char], i: usize) -> Option<BTreeSet<usize>> {
+    // Returns the end positions of matching `r` starting from any of the positions in `starts`
+    fn extend(r: &Rc<Term>, s: &[char], starts: &BTreeSet<usize>) -> Option<BTreeSet<usize>> {
+        let mut result = BTreeSet::new();
+        for &p in starts {
+            result.extend(re_match_ends(r, s, p)?);
+        }
+        Some(result)
+    }
+
+    // Returns the end positions of matching `r` exactly `n` times. Since positions never decrease,
+    // a match with more than `s.len()` repetitions must repeat some empty match, so the result is
+    // the same for every `n` greater than `s.len()`
+    fn power(r: &Rc<Term>, s: &[char], i: usize, n: usize) -> Option<BTreeSet<usize>> {
+        let mut ends = BTreeSet::from([i]);
+        for _ in 0..n.min(s.len() + 1) {
+            let next = extend(r, s, &ends)?;
+            if next == ends {
+                break;
+            }
+            ends = next;
+        }
+        Some(ends)
+    }
+
+    // Returns the end positions of matching `r` zero or more times
+    fn star(r: &Rc<Term>, s: &[char], i: usize) -> Option<BTreeSet<usize>> {
+        let mut reached = BTreeSet::from([i]);
+        let mut todo = vec![i];
+        while let Some(p) = todo.pop() {
+            for e in re_match_ends(r, s, p)? {
+                if reached.insert(e) {
+                    todo.push(e);
+                }
+            }
+        }
+        Some(reached)
+    }
+
+    let all = || (i..=s.len()).collect::<BTreeSet<_>>();
+    let single_char = |t: &Rc<Term>| match t.as_ref() {
+        Term::Const(Constant::String(c)) if c.chars().count() == 1 => c.chars().next(),
+        _ => None,
+    };
+
+    let result = match r.as_ref() {
+        Term::Op(op, args) => match op {
+            Operator::StrToRe => {
+                let Term::Const(Constant::String(c)) = args[0].as_ref() else {
+                    return None;
+                };
+                let c: Vec<char> = c.chars().collect();
+                let end = i + c.len();
+                if s.get(i..end) == Some(c.as_slice()) {
+                    BTreeSet::from([end])
+                } else {
+                    BTreeSet::new()
+                }
+            }
+            Operator::ReNone => BTreeSet::new(),
+            Operator::ReAll => all(),
+            Operator::ReAllChar if i < s.len() => BTreeSet::from([i + 1]),
+            Operator::ReAllChar => BTreeSet::new(),
+            Operator::ReRange => {
+                let (low, high) = (single_char(&args[0]), single_char(&args[1]));
+                match (low, high, s.get(i)) {
+                    (Some(low), Some(high), Some(c)) if (low..=high).contains(c) => {
+                        BTreeSet::from([i + 1])
+                    }
+                    // The language is empty if either bound is not a single character
+                    _ => BTreeSet::new(),
+                }
+            }
+            Operator::ReConcat => {
+                let mut ends = BTreeSet::from([i]);
+                for arg in args {
+                    ends = extend(arg, s, &ends)?;
+                }
+                ends
+            }
+            Operator::ReUnion => {
+                let mut ends = BTreeSet::new();
+                for arg in args {
+                    ends.extend(re_match_ends(arg, s, i)?);
+                }
+                ends
+            }
+            Operator::ReIntersection => {
+                let mut ends = all();
+                for arg in args {
+                    let other = re_match_ends(arg, s, i)?;
+                    ends.retain(|e| other.contains(e));
+                }
+                ends
+            }
+            Operator::ReKleeneClosure => star(&args[0], s, i)?,
+            Operator::ReKleeneCross => extend(&args[0], s, &star(&args[0], s, i)?)?,
+            Operator::ReOption => {
+                let mut ends = re_match_ends(&args[0], s, i)?;
+                ends.insert(i);
+                ends
+            }
+            Operator::ReComplement => {
+                let excluded = re_match_ends(&args[0], s, i)?;
+                all().difference(&excluded).copied().collect()
+            }
+            Operator::ReDiff => {
+                let excluded = re_match_ends(&args[1], s, i)?;
+                let ends = re_match_ends(&args[0], s, i)?;
+                ends.difference(&excluded).copied().collect()
+            }
+            _ => return None,
+        },
+        Term::ParamOp {
+            op: ParamOperator::RePower,
+            op_args,
+            args,
+        } => {
+            let n = op_args[0].as_integer()?.to_usize()?;
+            power(&args[0], s, i, n)?
+        }
+        Term::ParamOp {
+            op: ParamOperator::ReLoop,
+            op_args,
+            args,
+        } => {
+            let low = op_args[0].as_integer()?.to_usize()?;
+            let high = op_args[1].as_integer()?.to_usize()?;
+            if low > high {
+                return Some(BTreeSet::new());
+            }
+
+            // Repeating more than `s.len() + 1` times doesn't reach any new positions (see `power`)
+            let (low, high) = (low.min(s.len() + 1), high.min(s.len() + 1));
+            let mut current = power(&args[0], s, i, low)?;
+            let mut ends = current.clone();
+            for _ in low..high {
+                let next = extend(&args[0], s, &current)?;
+                if next == current {
+                    break;
+                }
+                ends.extend(&next);
+                current = next;
+            }
+            ends
+        }
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Checks a step of the form `(= (str.in_re s r) b)`, where `s` is a string constant, `r` is a
+/// ground regular expression and `b` is `true` if `s` is in the language of `r`, or `false`
+/// otherwise.
+pub fn str_in_re_eval(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let ((s, r), result) = match_term_err!((= (strinre s r) result) = &conclusion[0])?;
+    let Term::Const(Constant::String(s)) = s.as_ref() else {
+        return Err(CheckerError::TermOfWrongForm(
+            "a string constant",
+            s.clone(),
+        ));
+    };
+    let s: Vec<char> = s.chars().collect();
+    let ends = re_match_ends(r, &s, 0)
+        .ok_or_else(|| CheckerError::TermOfWrongForm("a ground regular expression", r.clone()))?;
+
+    assert_is_bool_constant(result, ends.contains(&s.len()))
+}
+
 mod tests {
     #[test]
     fn concat_eq() {
@@ -2340,6 +2536,76 @@ mod tests {
         }
     }
 
+    #[test]
+    fn string_code_inj() {
+        test_cases! {
+            definitions = "
+                (declare-fun a () String)
+                (declare-fun b () String)
+            ",
+            "Simple working examples" {
+                r#"(step t1 (cl (or (= (str.to_code a) (- 1)) (not (= (str.to_code a) (str.to_code b))) (= a b))) :rule string_code_inj :args (a b))"#: true,
+                r#"(step t1 (cl (or (= (str.to_code "x") (- 1)) (not (= (str.to_code "x") (str.to_code b))) (= "x" b))) :rule string_code_inj :args ("x" b))"#: true,
+            }
+            "Terms don't match the arguments" {
+                r#"(step t1 (cl (or (= (str.to_code b) (- 1)) (not (= (str.to_code a) (str.to_code b))) (= a b))) :rule string_code_inj :args (a b))"#: false,
+                r#"(step t1 (cl (or (= (str.to_code a) (- 1)) (not (= (str.to_code a) (str.to_code b))) (= b a))) :rule string_code_inj :args (a b))"#: false,
+            }
+            "Wrong constant" {
+                r#"(step t1 (cl (or (= (str.to_code a) 1) (not (= (str.to_code a) (str.to_code b))) (= a b))) :rule string_code_inj :args (a b))"#: false,
+            }
+        }
+    }
+
+    #[test]
+    fn str_in_re_eval() {
+        test_cases! {
+            definitions = "
+                (declare-fun a () String)
+                (declare-fun r () RegLan)
+            ",
+            "Simple working examples" {
+                r#"(step t1 (cl (= (str.in_re "ab" (str.to_re "ab")) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "ab" (str.to_re "a")) false)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "" (re.* (str.to_re "a"))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "aaa" (re.+ (str.to_re "a"))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "" (re.+ (str.to_re "a"))) false)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "abab" (re.* (re.++ (str.to_re "a") (re.opt (str.to_re "b"))))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "x7" (re.++ re.allchar (re.range "0" "9"))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "xy" (re.++ re.allchar (re.range "0" "9"))) false)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "5" (re.range "0" "99")) false)) :rule str_in_re_eval)"#: true,
+            }
+            "Set operations" {
+                r#"(step t1 (cl (= (str.in_re "ab" (re.union (str.to_re "a") (str.to_re "ab"))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "ab" (re.inter re.all (re.++ re.allchar re.allchar))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "ab" (re.comp (str.to_re "ab"))) false)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "abc" (re.comp (str.to_re "ab"))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "b" (re.diff re.allchar (str.to_re "a"))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "a" (re.diff re.allchar (str.to_re "a"))) false)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "" re.none) false)) :rule str_in_re_eval)"#: true,
+            }
+            "Indexed operators" {
+                r#"(step t1 (cl (= (str.in_re "aaa" ((_ re.^ 3) (str.to_re "a"))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "aa" ((_ re.^ 3) (str.to_re "a"))) false)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "aa" ((_ re.loop 1 3) (str.to_re "a"))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "aaaa" ((_ re.loop 1 3) (str.to_re "a"))) false)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "bb" ((_ re.loop 0 1000000000) (str.to_re "b"))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "" ((_ re.loop 5 1000000000) (re.opt (str.to_re "b")))) true)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "b" ((_ re.loop 1000000000 5) (str.to_re "b"))) false)) :rule str_in_re_eval)"#: true,
+                r#"(step t1 (cl (= (str.in_re "b" ((_ re.^ 1000000000) (re.opt (str.to_re "b")))) true)) :rule str_in_re_eval)"#: true,
+            }
+            "Wrong result" {
+                r#"(step t1 (cl (= (str.in_re "ab" (str.to_re "ab")) false)) :rule str_in_re_eval)"#: false,
+                r#"(step t1 (cl (= (str.in_re "b" (re.* (str.to_re "a"))) true)) :rule str_in_re_eval)"#: false,
+            }
+            "Terms are not ground" {
+                r#"(step t1 (cl (= (str.in_re a (str.to_re "ab")) true)) :rule str_in_re_eval)"#: false,
+                r#"(step t1 (cl (= (str.in_re "ab" (re.++ r (str.to_re "ab"))) true)) :rule str_in_re_eval)"#: false,
+                r#"(step t1 (cl (= (str.in_re "ab" (str.to_re a)) true)) :rule str_in_re_eval)"#: false,
+            }
+        }
+    }
+
     #[test]
     fn re_inter() {
         test_cases! {
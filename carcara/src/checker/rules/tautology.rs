@@ -1,8 +1,10 @@
 use super::{
-    assert_clause_len, assert_eq, assert_num_args, assert_num_premises, assert_polyeq,
-    get_premise_term, CheckerError, RuleArgs, RuleResult,
+    assert_clause_len, assert_eq, assert_num_args, assert_num_premises, get_premise_term,
+    CheckerError, RuleArgs, RuleResult,
 };
 use crate::{ast::*, checker::rules::assert_operation_len};
+use indexmap::IndexSet;
+use std::collections::HashSet;
 
 pub fn r#true(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 1)?;
@@ -85,31 +87,63 @@ pub fn or_neg(RuleArgs { conclusion, args, .. }: RuleArgs) -> RuleResult {
     assert_eq(other, &or_contents[i])
 }
 
+/// Views an `xor` term with any number of arguments as a binary `xor`. Since `xor` is
+/// left-associative, `(xor p_1 ... p_n)` is equivalent to `(xor (xor p_1 ... p_n-1) p_n)`, so this
+/// returns the arguments `p_1, ..., p_n-1` that make up the first operand, and the last argument
+/// `p_n`. The first operand should be checked with `assert_xor_operand`.
+fn as_binary_xor(term: &Rc<Term>) -> Result<(&[Rc<Term>], &Rc<Term>), CheckerError> {
+    match match_term!((xor ...) = term) {
+        Some([init @ .., last]) if !init.is_empty() => Ok((init, last)),
+        _ => Err(CheckerError::TermOfWrongForm(
+            "(xor phi_1 phi_2)",
+            term.clone(),
+        )),
+    }
+}
+
+/// Checks that `term` is the first operand of an `xor` term, given the arguments returned by
+/// `as_binary_xor`. If there is more than one argument, the term must be either an `xor` with
+/// exactly those arguments, or their left-associative nesting.
+fn assert_xor_operand(args: &[Rc<Term>], term: &Rc<Term>) -> RuleResult {
+    let [init @ .., last] = args else {
+        unreachable!()
+    };
+    if init.is_empty() {
+        return assert_eq(last, term);
+    }
+    if match_term!((xor ...) = term) == Some(args) {
+        return Ok(());
+    }
+    let (phi_1, phi_2) = match_term_err!((xor phi_1 phi_2) = term)?;
+    assert_xor_operand(init, phi_1)?;
+    assert_eq(last, phi_2)
+}
+
 pub fn xor_pos1(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 3)?;
-    let (phi_1, phi_2) = match_term_err!((not (xor phi_1 phi_2)) = &conclusion[0])?;
-    assert_eq(phi_1, &conclusion[1])?;
+    let (phi_1, phi_2) = as_binary_xor(conclusion[0].remove_negation_err()?)?;
+    assert_xor_operand(phi_1, &conclusion[1])?;
     assert_eq(phi_2, &conclusion[2])
 }
 
 pub fn xor_pos2(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 3)?;
-    let (phi_1, phi_2) = match_term_err!((not (xor phi_1 phi_2)) = &conclusion[0])?;
-    assert_eq(phi_1, conclusion[1].remove_negation_err()?)?;
+    let (phi_1, phi_2) = as_binary_xor(conclusion[0].remove_negation_err()?)?;
+    assert_xor_operand(phi_1, conclusion[1].remove_negation_err()?)?;
     assert_eq(phi_2, conclusion[2].remove_negation_err()?)
 }
 
 pub fn xor_neg1(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 3)?;
-    let (phi_1, phi_2) = match_term_err!((xor phi_1 phi_2) = &conclusion[0])?;
-    assert_eq(phi_1, &conclusion[1])?;
+    let (phi_1, phi_2) = as_binary_xor(&conclusion[0])?;
+    assert_xor_operand(phi_1, &conclusion[1])?;
     assert_eq(phi_2, conclusion[2].remove_negation_err()?)
 }
 
 pub fn xor_neg2(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 3)?;
-    let (phi_1, phi_2) = match_term_err!((xor phi_1 phi_2) = &conclusion[0])?;
-    assert_eq(phi_1, conclusion[1].remove_negation_err()?)?;
+    let (phi_1, phi_2) = as_binary_xor(&conclusion[0])?;
+    assert_xor_operand(phi_1, conclusion[1].remove_negation_err()?)?;
     assert_eq(phi_2, &conclusion[2])
 }
 
@@ -132,32 +166,90 @@ pub fn implies_neg2(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_eq(phi_2, conclusion[1].remove_negation_err()?)
 }
 
+/// Views a term as an equivalence chain over Booleans. `(= p_1 ... p_n)` states that all of its
+/// arguments are equivalent, and `(distinct p_1 p_2)` is seen as the negation of `(= p_1 p_2)`.
+/// Returns the arguments of the chain if its polarity is `polarity`, that is, if it is asserted
+/// positively or negatively as expected.
+fn as_equiv_chain(term: &Rc<Term>, polarity: bool) -> Result<&[Rc<Term>], CheckerError> {
+    let (is_positive, inner) = match term.remove_negation() {
+        Some(inner) => (false, inner),
+        None => (true, term),
+    };
+    let chain = match_term!((= ...) = inner)
+        .map(|args| (is_positive, args))
+        .or_else(|| match match_term!((distinct ...) = inner) {
+            Some(args @ [_, _]) => Some((!is_positive, args)),
+            _ => None,
+        });
+    match chain {
+        Some((p, args)) if p == polarity => Ok(args),
+        _ if polarity => Err(CheckerError::TermOfWrongForm(
+            "(= phi_1 phi_2)",
+            term.clone(),
+        )),
+        _ => Err(CheckerError::TermOfWrongForm(
+            "(not (= phi_1 phi_2))",
+            term.clone(),
+        )),
+    }
+}
+
+/// Checks that `phi_1` and `phi_2` are arguments of an equivalence chain, with `phi_1` appearing
+/// before `phi_2`. If the chain is binary, they must be exactly its two arguments.
+fn assert_equiv_pair(chain: &[Rc<Term>], phi_1: &Rc<Term>, phi_2: &Rc<Term>) -> RuleResult {
+    if let [a, b] = chain {
+        assert_eq(a, phi_1)?;
+        return assert_eq(b, phi_2);
+    }
+    let Some(i) = chain.iter().position(|a| a == phi_1) else {
+        return Err(CheckerError::TermDoesntApperInOp(
+            Operator::Equals,
+            phi_1.clone(),
+        ));
+    };
+    rassert!(
+        chain[i + 1..].contains(phi_2),
+        CheckerError::TermDoesntApperInOp(Operator::Equals, phi_2.clone())
+    );
+    Ok(())
+}
+
+/// Checks that the given literals are exactly the arguments of an equivalence chain, each of them
+/// negated if `negated` is true.
+fn assert_equiv_literals(chain: &[Rc<Term>], literals: &[Rc<Term>], negated: bool) -> RuleResult {
+    assert_clause_len(literals, chain.len())?;
+    for (phi, literal) in chain.iter().zip(literals) {
+        if negated {
+            assert_eq(phi, literal.remove_negation_err()?)?;
+        } else {
+            assert_eq(phi, literal)?;
+        }
+    }
+    Ok(())
+}
+
 pub fn equiv_pos1(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 3)?;
-    let (phi_1, phi_2) = match_term_err!((not (= phi_1 phi_2)) = &conclusion[0])?;
-    assert_eq(phi_1, &conclusion[1])?;
-    assert_eq(phi_2, conclusion[2].remove_negation_err()?)
+    let chain = as_equiv_chain(&conclusion[0], false)?;
+    assert_equiv_pair(chain, &conclusion[1], conclusion[2].remove_negation_err()?)
 }
 
 pub fn equiv_pos2(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 3)?;
-    let (phi_1, phi_2) = match_term_err!((not (= phi_1 phi_2)) = &conclusion[0])?;
-    assert_eq(phi_1, conclusion[1].remove_negation_err()?)?;
-    assert_eq(phi_2, &conclusion[2])
+    let chain = as_equiv_chain(&conclusion[0], false)?;
+    assert_equiv_pair(chain, conclusion[1].remove_negation_err()?, &conclusion[2])
 }
 
 pub fn equiv_neg1(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
-    assert_clause_len(conclusion, 3)?;
-    let (phi_1, phi_2) = match_term_err!((= phi_1 phi_2) = &conclusion[0])?;
-    assert_eq(phi_1, conclusion[1].remove_negation_err()?)?;
-    assert_eq(phi_2, conclusion[2].remove_negation_err()?)
+    assert_clause_len(conclusion, 3..)?;
+    let chain = as_equiv_chain(&conclusion[0], true)?;
+    assert_equiv_literals(chain, &conclusion[1..], true)
 }
 
 pub fn equiv_neg2(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
-    assert_clause_len(conclusion, 3)?;
-    let (phi_1, phi_2) = match_term_err!((= phi_1 phi_2) = &conclusion[0])?;
-    assert_eq(phi_1, &conclusion[1])?;
-    assert_eq(phi_2, &conclusion[2])
+    assert_clause_len(conclusion, 3..)?;
+    let chain = as_equiv_chain(&conclusion[0], true)?;
+    assert_equiv_literals(chain, &conclusion[1..], false)
 }
 
 pub fn ite_pos1(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
@@ -191,37 +283,27 @@ pub fn ite_neg2(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
 pub fn equiv1(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
     assert_num_premises(premises, 1)?;
     assert_clause_len(conclusion, 2)?;
-    let premise_term = get_premise_term(&premises[0])?;
-    let (phi_1, phi_2) = match_term_err!((= phi_1 phi_2) = premise_term)?;
-    assert_eq(phi_1, conclusion[0].remove_negation_err()?)?;
-    assert_eq(phi_2, &conclusion[1])
+    let chain = as_equiv_chain(get_premise_term(&premises[0])?, true)?;
+    assert_equiv_pair(chain, conclusion[0].remove_negation_err()?, &conclusion[1])
 }
 
 pub fn equiv2(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
     assert_num_premises(premises, 1)?;
     assert_clause_len(conclusion, 2)?;
-    let premise_term = get_premise_term(&premises[0])?;
-    let (phi_1, phi_2) = match_term_err!((= phi_1 phi_2) = premise_term)?;
-    assert_eq(phi_1, &conclusion[0])?;
-    assert_eq(phi_2, conclusion[1].remove_negation_err()?)
+    let chain = as_equiv_chain(get_premise_term(&premises[0])?, true)?;
+    assert_equiv_pair(chain, &conclusion[0], conclusion[1].remove_negation_err()?)
 }
 
 pub fn not_equiv1(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
     assert_num_premises(premises, 1)?;
-    assert_clause_len(conclusion, 2)?;
-    let premise_term = get_premise_term(&premises[0])?;
-    let (phi_1, phi_2) = match_term_err!((not (= phi_1 phi_2)) = premise_term)?;
-    assert_eq(phi_1, &conclusion[0])?;
-    assert_eq(phi_2, &conclusion[1])
+    let chain = as_equiv_chain(get_premise_term(&premises[0])?, false)?;
+    assert_equiv_literals(chain, conclusion, false)
 }
 
 pub fn not_equiv2(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
     assert_num_premises(premises, 1)?;
-    assert_clause_len(conclusion, 2)?;
-    let premise_term = get_premise_term(&premises[0])?;
-    let (phi_1, phi_2) = match_term_err!((not (= phi_1 phi_2)) = premise_term)?;
-    assert_eq(phi_1, conclusion[0].remove_negation_err()?)?;
-    assert_eq(phi_2, conclusion[1].remove_negation_err()?)
+    let chain = as_equiv_chain(get_premise_term(&premises[0])?, false)?;
+    assert_equiv_literals(chain, conclusion, true)
 }
 
 pub fn ite1(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
@@ -285,31 +367,36 @@ pub fn ite_intro(RuleArgs { conclusion, polyeq_time, .. }: RuleArgs) -> RuleResu
     }
     let us = match_term_err!((and ...) = right_side)?;
 
-    // `us` must be a conjunction where the first term is the root term
-    assert_polyeq(&us[0], root_term, polyeq_time)?;
-
-    // The remaining terms in `us` should be of the correct form
-    for u_i in &us[1..] {
+    // One of the terms in `us` must be the root term. Usually, it is the first term, but some
+    // solvers may place it elsewhere in the conjunction
+    let root_index = if polyeq(&us[0], root_term, polyeq_time) {
+        0
+    } else {
+        us.iter()
+            .position(|u| polyeq(u, root_term, polyeq_time))
+            .ok_or_else(|| CheckerError::TermOfWrongForm("(and t ...)", right_side.clone()))?
+    };
+
+    // The remaining terms in `us` must be definitions of `ite` subterms of the root term. These
+    // may be in any order, and an `ite` term that is shared by different parts of the root term
+    // may have more than one definition
+    let ite_subterms = collect_ite_subterms(root_term);
+    for (i, u_i) in us.iter().enumerate() {
+        if i == root_index {
+            continue;
+        }
         let (cond, (a, b), (c, d)) = match_term_err!((ite cond (= a b) (= c d)) = u_i)?;
 
-        let mut is_valid = |r_1, s_1, r_2, s_2| {
-            // s_1 == s_2 == (ite cond r_1 r_2)
-            if polyeq(s_1, s_2, polyeq_time) {
-                if let Some((a, b, c)) = match_term!((ite a b c) = s_1) {
-                    return polyeq(a, cond, polyeq_time)
-                        && polyeq(b, r_1, polyeq_time)
-                        && polyeq(c, r_2, polyeq_time);
-                }
-            }
-            false
-        };
         // Since the (= r_1 s_1) and (= r_2 s_2) equalities may be flipped, we have to check all
         // four possibilities: neither are flipped, either one is flipped, or both are flipped
-        let is_valid = is_valid(a, b, c, d)
-            || is_valid(b, a, c, d)
-            || is_valid(a, b, d, c)
-            || is_valid(b, a, d, c);
-
+        let introduced = [(a, b, c, d), (b, a, c, d), (a, b, d, c), (b, a, d, c)]
+            .into_iter()
+            .find_map(|(r_1, s_1, r_2, s_2)| {
+                is_ite_definition(cond, r_1, s_1, r_2, s_2, polyeq_time).then_some(s_1)
+            });
+        let is_valid = introduced.is_some_and(|s| {
+            ite_subterms.contains(s) || ite_subterms.iter().any(|t| polyeq(t, s, polyeq_time))
+        });
         if !is_valid {
             return Err(CheckerError::IsNotValidIteIntro(u_i.clone()));
         }
@@ -317,17 +404,81 @@ pub fn ite_intro(RuleArgs { conclusion, polyeq_time, .. }: RuleArgs) -> RuleResu
     Ok(())
 }
 
+/// Returns `true` if `s_1` and `s_2` are both the term `(ite cond r_1 r_2)`, or the term `(ite (not
+/// cond) r_2 r_1)`.
+fn is_ite_definition(
+    cond: &Rc<Term>,
+    r_1: &Rc<Term>,
+    s_1: &Rc<Term>,
+    r_2: &Rc<Term>,
+    s_2: &Rc<Term>,
+    polyeq_time: &mut std::time::Duration,
+) -> bool {
+    if !polyeq(s_1, s_2, polyeq_time) {
+        return false;
+    }
+    let Some((a, b, c)) = match_term!((ite a b c) = s_1) else {
+        return false;
+    };
+    if polyeq(a, cond, polyeq_time) {
+        return polyeq(b, r_1, polyeq_time) && polyeq(c, r_2, polyeq_time);
+    }
+
+    // Some solvers introduce the `ite` term with its condition negated, and its branches swapped
+    match a.remove_negation() {
+        Some(a) => {
+            polyeq(a, cond, polyeq_time)
+                && polyeq(b, r_2, polyeq_time)
+                && polyeq(c, r_1, polyeq_time)
+        }
+        None => false,
+    }
+}
+
+/// Collects all `ite` subterms of a term, including nested ones. Since terms are shared, each
+/// distinct subterm is only visited once.
+fn collect_ite_subterms(term: &Rc<Term>) -> IndexSet<&Rc<Term>> {
+    let mut visited = HashSet::new();
+    let mut result = IndexSet::new();
+    let mut stack = vec![term];
+    while let Some(current) = stack.pop() {
+        if !visited.insert(current) {
+            continue;
+        }
+        match current.as_ref() {
+            Term::Op(op, args) => {
+                if *op == Operator::Ite {
+                    result.insert(current);
+                }
+                stack.extend(args);
+            }
+            Term::App(f, args) => {
+                stack.push(f);
+                stack.extend(args);
+            }
+            Term::ParamOp { args, .. } => stack.extend(args),
+            Term::Binder(_, _, inner) => stack.push(inner),
+            Term::Let(bindings, inner) => {
+                stack.extend(bindings.iter().map(|(_, value)| value));
+                stack.push(inner);
+            }
+            Term::Const(_) | Term::Var(..) | Term::Sort(_) => (),
+        }
+    }
+    result
+}
+
 pub fn connective_def(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
     assert_clause_len(conclusion, 1)?;
 
     let (first, second) = match_term_err!((= f s) = &conclusion[0])?;
 
-    if let Some((phi_1, phi_2)) = match_term!((xor phi_1 phi_2) = first) {
+    if let Ok((phi_1, phi_2)) = as_binary_xor(first) {
         // phi_1 xor phi_2 <-> (¬phi_1 ^ phi_2) v (phi_1 ^ ¬phi_2)
         let ((a, b), (c, d)) = match_term_err!((or (and (not a) b) (and c (not d))) = second)?;
-        assert_eq(a, phi_1)?;
+        assert_xor_operand(phi_1, a)?;
         assert_eq(b, phi_2)?;
-        assert_eq(c, phi_1)?;
+        assert_xor_operand(phi_1, c)?;
         assert_eq(d, phi_2)
     } else if let Some((phi_1, phi_2)) = match_term!((= phi_1 phi_2) = first) {
         // (phi_1 <-> phi_2) <-> (phi_1 -> phi_2) ^ (phi_2 -> phi_1)
@@ -412,6 +563,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn not_not_generated() {
+        use crate::ast::{PrimitivePool, Sort, TermGenerator};
+
+        let definitions = "
+            (declare-fun p () Bool)
+            (declare-fun q () Bool)
+        ";
+        let mut pool = PrimitivePool::new();
+        let terms = TermGenerator::boolean(&mut pool, &["p", "q"])
+            .limit(100)
+            .generate(&mut pool, &Sort::Bool, 2);
+        let cases: Vec<_> = terms
+            .iter()
+            .flat_map(|t| {
+                [
+                    (
+                        format!("(step t1 (cl (not (not (not {t}))) {t}) :rule not_not)"),
+                        true,
+                    ),
+                    (
+                        format!("(step t1 (cl (not (not {t})) {t}) :rule not_not)"),
+                        false,
+                    ),
+                ]
+            })
+            .collect();
+        let cases: Vec<_> = cases.iter().map(|(c, e)| (c.as_str(), *e)).collect();
+        let config = crate::checker::Config::new();
+        super::super::run_tests("Generated terms", definitions, &cases, &config);
+    }
+
     #[test]
     fn and_pos() {
         test_cases! {
@@ -524,6 +707,7 @@ mod tests {
             definitions = "
                 (declare-fun p () Bool)
                 (declare-fun q () Bool)
+                (declare-fun r () Bool)
             ",
             "Simple working examples" {
                 "(step t1 (cl (not (xor p q)) p q) :rule xor_pos1)": true,
@@ -537,6 +721,13 @@ mod tests {
                 "(step t1 (cl (not (xor p q)) q p) :rule xor_pos1)": false,
                 "(step t1 (cl (not (xor (not p) q)) p (not q)) :rule xor_pos1)": false,
             }
+            "N-ary \"xor\" terms" {
+                "(step t1 (cl (not (xor p q r)) (xor p q) r) :rule xor_pos1)": true,
+                "(step t1 (cl (not (xor p q r)) (xor (xor p q) r) r) :rule xor_pos1)": false,
+                "(step t1 (cl (not (xor (xor p q) r)) (xor p q) r) :rule xor_pos1)": true,
+                "(step t1 (cl (not (xor p q r)) p (xor q r)) :rule xor_pos1)": false,
+                "(step t1 (cl (not (xor p q r)) p r) :rule xor_pos1)": false,
+            }
         }
     }
 
@@ -592,6 +783,7 @@ mod tests {
             definitions = "
                 (declare-fun p () Bool)
                 (declare-fun q () Bool)
+                (declare-fun r () Bool)
             ",
             "Simple working examples" {
                 "(step t1 (cl (xor p q) (not p) q) :rule xor_neg2)": true,
@@ -604,6 +796,10 @@ mod tests {
                 "(step t1 (cl (xor p q) (not q) p) :rule xor_neg2)": false,
                 "(step t1 (cl (xor p q) (not p) p) :rule xor_neg2)": false,
             }
+            "N-ary \"xor\" terms" {
+                "(step t1 (cl (xor p q r) (not (xor p q)) r) :rule xor_neg2)": true,
+                "(step t1 (cl (xor p q r) (not (xor p q)) (not r)) :rule xor_neg2)": false,
+            }
         }
     }
 
@@ -684,6 +880,7 @@ mod tests {
             definitions = "
                 (declare-fun p () Bool)
                 (declare-fun q () Bool)
+                (declare-fun r () Bool)
             ",
             "Simple working examples" {
                 "(step t1 (cl (not (= p q)) p (not q)) :rule equiv_pos1)": true,
@@ -704,6 +901,13 @@ mod tests {
                 "(step t1 (cl (not (= p q)) p (not p)) :rule equiv_pos1)": false,
                 "(step t1 (cl (not (= (not p) q)) p (not q)) :rule equiv_pos1)": false,
             }
+            "N-ary \"=\" and \"distinct\" terms" {
+                "(step t1 (cl (not (= p q r)) p (not r)) :rule equiv_pos1)": true,
+                "(step t1 (cl (not (= p q r)) q (not r)) :rule equiv_pos1)": true,
+                "(step t1 (cl (not (= p q r)) r (not p)) :rule equiv_pos1)": false,
+                "(step t1 (cl (distinct p q) p (not q)) :rule equiv_pos1)": true,
+                "(step t1 (cl (not (distinct p q)) p (not q)) :rule equiv_pos1)": false,
+            }
         }
     }
 
@@ -742,6 +946,7 @@ mod tests {
             definitions = "
                 (declare-fun p () Bool)
                 (declare-fun q () Bool)
+                (declare-fun r () Bool)
             ",
             "Simple working examples" {
                 "(step t1 (cl (= p q) (not p) (not q)) :rule equiv_neg1)": true,
@@ -755,6 +960,12 @@ mod tests {
                 "(step t1 (cl (= p q) (not q) (not p)) :rule equiv_neg1)": false,
                 "(step t1 (cl (= p q) (not p) (not p)) :rule equiv_neg1)": false,
             }
+            "N-ary \"=\" and \"distinct\" terms" {
+                "(step t1 (cl (= p q r) (not p) (not q) (not r)) :rule equiv_neg1)": true,
+                "(step t1 (cl (= p q r) (not p) (not q)) :rule equiv_neg1)": false,
+                "(step t1 (cl (= p q r) (not p) (not r) (not q)) :rule equiv_neg1)": false,
+                "(step t1 (cl (not (distinct p q)) (not p) (not q)) :rule equiv_neg1)": true,
+            }
         }
     }
 
@@ -882,6 +1093,7 @@ mod tests {
             definitions = "
                 (declare-fun p () Bool)
                 (declare-fun q () Bool)
+                (declare-fun r () Bool)
             ",
             "Simple working examples" {
                 "(assume h1 (= p q))
@@ -897,6 +1109,14 @@ mod tests {
                 "(assume h1 (= p q))
                 (step t2 (cl (not p) (not q)) :rule equiv1 :premises (h1))": false,
             }
+            "N-ary \"=\" and \"distinct\" terms" {
+                "(assume h1 (= p q r))
+                (step t2 (cl (not p) r) :rule equiv1 :premises (h1))": true,
+                "(assume h1 (= p q r))
+                (step t2 (cl (not r) q) :rule equiv1 :premises (h1))": false,
+                "(assume h1 (not (distinct p q)))
+                (step t2 (cl (not p) q) :rule equiv1 :premises (h1))": true,
+            }
         }
     }
 
@@ -930,6 +1150,7 @@ mod tests {
             definitions = "
                 (declare-fun p () Bool)
                 (declare-fun q () Bool)
+                (declare-fun r () Bool)
             ",
             "Simple working examples" {
                 "(assume h1 (not (= p q)))
@@ -942,6 +1163,14 @@ mod tests {
                 "(assume h1 (not (= p q)))
                 (step t2 (cl p) :rule not_equiv1 :premises (h1))": false,
             }
+            "N-ary \"=\" and \"distinct\" terms" {
+                "(assume h1 (not (= p q r)))
+                (step t2 (cl p q r) :rule not_equiv1 :premises (h1))": true,
+                "(assume h1 (not (= p q r)))
+                (step t2 (cl p q) :rule not_equiv1 :premises (h1))": false,
+                "(assume h1 (distinct p q))
+                (step t2 (cl p q) :rule not_equiv1 :premises (h1))": true,
+            }
         }
     }
 
@@ -1121,6 +1350,76 @@ mod tests {
                     )
                 )) :rule ite_intro)": true,
             }
+            "Condition is negated and branches are swapped" {
+                "(step t1 (cl (=
+                    (ite (not p) a b)
+                    (and (ite (not p) a b) (ite p (= b (ite (not p) a b)) (= a (ite (not p) a b))))
+                )) :rule ite_intro)": true,
+
+                "(step t1 (cl (=
+                    (not (ite (not p) a b))
+                    (and
+                        (not (ite (not p) a b))
+                        (ite p (= (ite (not p) a b) b) (= a (ite (not p) a b)))
+                    )
+                )) :rule ite_intro)": true,
+
+                "(step t1 (cl (=
+                    (ite (not p) a b)
+                    (and (ite (not p) a b) (ite p (= a (ite (not p) a b)) (= b (ite (not p) a b))))
+                )) :rule ite_intro)": false,
+
+                "(step t1 (cl (=
+                    (ite (not q) a b)
+                    (and (ite (not q) a b) (ite p (= b (ite (not q) a b)) (= a (ite (not q) a b))))
+                )) :rule ite_intro)": false,
+            }
+            "Shared and nested \"ite\" subterms" {
+                "(step t1 (cl (=
+                    (and (ite p a b) (or (ite p a b) c))
+                    (and
+                        (and (ite p a b) (or (ite p a b) c))
+                        (ite p (= a (ite p a b)) (= b (ite p a b)))
+                    )
+                )) :rule ite_intro)": true,
+
+                "(step t1 (cl (=
+                    (ite p (ite q c d) b)
+                    (and
+                        (ite p (ite q c d) b)
+                        (ite q (= c (ite q c d)) (= d (ite q c d)))
+                        (ite p (= (ite q c d) (ite p (ite q c d) b)) (= b (ite p (ite q c d) b)))
+                    )
+                )) :rule ite_intro)": true,
+
+                "(step t1 (cl (=
+                    (or (ite p a b) (ite p a b))
+                    (and
+                        (or (ite p a b) (ite p a b))
+                        (ite p (= a (ite p a b)) (= b (ite p a b)))
+                        (ite p (= (ite p a b) a) (= b (ite p a b)))
+                    )
+                )) :rule ite_intro)": true,
+            }
+            "Definitions in a different order" {
+                "(step t1 (cl (=
+                    (or (ite p a b) (ite q c d))
+                    (and
+                        (ite q (= c (ite q c d)) (= d (ite q c d)))
+                        (or (ite p a b) (ite q c d))
+                        (ite p (= a (ite p a b)) (= b (ite p a b)))
+                    )
+                )) :rule ite_intro)": true,
+            }
+            "Definition of term that is not a subterm" {
+                "(step t1 (cl (=
+                    (or (ite p a b) c)
+                    (and
+                        (or (ite p a b) c)
+                        (ite q (= c (ite q c d)) (= d (ite q c d)))
+                    )
+                )) :rule ite_intro)": false,
+            }
             "Clause term is not an equality" {
                 "(step t1 (cl) :rule ite_intro)": false,
                 "(step t1 (cl (not (= p q))) :rule ite_intro)": false,
@@ -1210,6 +1509,14 @@ mod tests {
                 "(step t1 (cl (= (exists ((x Real)) p) (not (forall ((x Real)) (not p)))))
                     :rule connective_def)": false,
             }
+            "N-ary \"xor\" terms" {
+                "(step t1 (cl (= (xor p q r)
+                    (or (and (not (xor p q)) r) (and (xor p q) (not r)))
+                )) :rule connective_def)": true,
+                "(step t1 (cl (= (xor p q r)
+                    (or (and (not p) (xor q r)) (and p (not (xor q r))))
+                )) :rule connective_def)": false,
+            }
         }
     }
 }
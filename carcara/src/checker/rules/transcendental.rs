@@ -0,0 +1,567 @@
+use super::{assert_clause_len, assert_eq, RuleArgs, RuleResult};
+use crate::{
+    ast::*,
+    checker::error::{CheckerError, LinearArithmeticError},
+};
+use rug::{Integer, Rational};
+
+/// The precisions, in bits, used when computing enclosures. If a bound can't be proven with some
+/// precision, the next one is tried.
+const PRECISIONS: [u32; 5] = [32, 64, 128, 256, 512];
+
+/// The largest magnitude of an argument to `exp`, `sin` or `cos` for which an enclosure is
+/// computed. Larger arguments would require impractically large numbers.
+const MAX_ARGUMENT: u32 = 1 << 12;
+
+/// A closed interval with rational endpoints that is known to contain the value of some term.
+#[derive(Debug, Clone)]
+struct Enclosure {
+    lo: Rational,
+    hi: Rational,
+}
+
+impl Enclosure {
+    fn new(lo: Rational, hi: Rational) -> Self {
+        Self { lo, hi }
+    }
+
+    fn point(value: Rational) -> Self {
+        Self::new(value.clone(), value)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Self::new(
+            Rational::from(&self.lo + &other.lo),
+            Rational::from(&self.hi + &other.hi),
+        )
+    }
+
+    fn neg(&self) -> Self {
+        Self::new(-self.hi.clone(), -self.lo.clone())
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let corners = [
+            Rational::from(&self.lo * &other.lo),
+            Rational::from(&self.lo * &other.hi),
+            Rational::from(&self.hi * &other.lo),
+            Rational::from(&self.hi * &other.hi),
+        ];
+        let lo = corners.iter().min().unwrap().clone();
+        let hi = corners.iter().max().unwrap().clone();
+        Self::new(lo, hi)
+    }
+
+    /// Divides by another enclosure. Returns `None` if the divisor may be zero.
+    fn div(&self, other: &Self) -> Option<Self> {
+        if other.lo <= 0 && other.hi >= 0 {
+            return None;
+        }
+        let inverse = Self::new(other.hi.clone().recip(), other.lo.clone().recip());
+        Some(self.mul(&inverse))
+    }
+
+    /// Returns the enclosure of the square of a term in this enclosure. Unlike multiplying the
+    /// enclosure by itself, this accounts for the fact that squares are non-negative.
+    fn square(&self) -> Self {
+        let product = self.mul(self);
+        if self.lo <= 0 && self.hi >= 0 {
+            Self::new(Rational::new(), product.hi)
+        } else {
+            product
+        }
+    }
+
+    /// Returns the largest absolute value of a term in this enclosure.
+    fn magnitude(&self) -> Rational {
+        std::cmp::max(self.lo.clone().abs(), self.hi.clone().abs())
+    }
+
+    /// Widens the enclosure by `error` in both directions.
+    fn widen(&self, error: &Rational) -> Self {
+        Self::new(
+            Rational::from(&self.lo - error),
+            Rational::from(&self.hi + error),
+        )
+    }
+
+    /// Widens the enclosure to the nearest endpoints with denominator `2^precision`, to keep the
+    /// size of the endpoints bounded.
+    fn round(&self, precision: u32) -> Self {
+        let scale = Integer::from(1) << precision;
+        let to_scale = |x: Rational| Rational::from((x.into_numer_denom().0, scale.clone()));
+        let lo = Rational::from(&self.lo * &scale).floor();
+        let hi = Rational::from(&self.hi * &scale).ceil();
+        Self::new(to_scale(lo), to_scale(hi))
+    }
+
+    /// Intersects the enclosure with the interval `[-1, 1]`, which contains every value of `sin`
+    /// and `cos`.
+    fn clamp_unit(self) -> Self {
+        let one = Rational::from(1);
+        Self::new(
+            std::cmp::max(self.lo, -one.clone()),
+            std::cmp::min(self.hi, one),
+        )
+    }
+}
+
+/// Returns `2^-precision`, the error allowed when computing a series with the given precision.
+fn epsilon(precision: u32) -> Rational {
+    Rational::from((1, Integer::from(1) << precision))
+}
+
+/// Computes an enclosure of `arctan(x)`, for `0 < x < 1`. The series for `arctan` is alternating,
+/// and its terms are decreasing, so the value always lies between two consecutive partial sums.
+fn arctan_small(x: &Rational, precision: u32) -> Enclosure {
+    let eps = epsilon(precision);
+    let x_squared = Rational::from(x * x);
+    let mut power = x.clone();
+    let mut sum = Rational::new();
+    let mut k = 0u32;
+    loop {
+        let term = Rational::from(&power / (2 * k + 1));
+        let next = if k % 2 == 0 {
+            Rational::from(&sum + &term)
+        } else {
+            Rational::from(&sum - &term)
+        };
+        if term < eps {
+            let (lo, hi) = if sum < next { (sum, next) } else { (next, sum) };
+            return Enclosure::new(lo, hi);
+        }
+        sum = next;
+        power *= &x_squared;
+        k += 1;
+    }
+}
+
+/// Computes an enclosure of `pi`, using Machin's formula `pi = 16 arctan(1/5) - 4 arctan(1/239)`.
+fn pi(precision: u32) -> Enclosure {
+    let a = arctan_small(&Rational::from((1, 5)), precision + 5);
+    let b = arctan_small(&Rational::from((1, 239)), precision + 5);
+    let pi = a
+        .mul(&Enclosure::point(16.into()))
+        .sub(&b.mul(&Enclosure::point(4.into())));
+    pi.round(precision)
+}
+
+/// Computes an enclosure of `exp(x)`. The argument is first halved until it is at most `1/2` in
+/// absolute value, and the result of the Taylor series is then squared back.
+fn exp_point(x: &Rational, precision: u32) -> Option<Enclosure> {
+    if x.clone().abs() > MAX_ARGUMENT {
+        return None;
+    }
+    let half = Rational::from((1, 2));
+    let mut y = x.clone();
+    let mut halvings = 0;
+    while y.clone().abs() > half {
+        y /= 2;
+        halvings += 1;
+    }
+
+    // Since `|y| <= 1/2`, each term of the series is at most half of the previous one, so the
+    // remainder after some term is bounded by twice that term
+    let working_precision = precision + halvings + 8;
+    let eps = epsilon(working_precision);
+    let mut sum = Rational::new();
+    let mut term = Rational::from(1);
+    let mut i = 1u32;
+    while term.clone().abs() >= eps {
+        sum += &term;
+        term = term * &y / i;
+        i += 1;
+    }
+    let error = term.abs() * 2;
+    let mut result = Enclosure::point(sum).widen(&error).round(working_precision);
+    for _ in 0..halvings {
+        result = result.mul(&result).round(working_precision);
+    }
+    Some(result)
+}
+
+fn exp(x: &Enclosure, precision: u32) -> Option<Enclosure> {
+    // `exp` is increasing, so it's enough to compute it at the endpoints
+    let lo = exp_point(&x.lo, precision)?.lo;
+    let hi = exp_point(&x.hi, precision)?.hi;
+    Some(Enclosure::new(lo, hi))
+}
+
+/// Computes an enclosure of `sin(x)` (if `is_sin` is `true`) or `cos(x)`. The argument is first
+/// shifted by a multiple of `2 pi` to be close to zero, and the Taylor series is then computed using
+/// interval arithmetic, with a bound on its remainder.
+fn sin_or_cos(x: &Enclosure, precision: u32, is_sin: bool) -> Option<Enclosure> {
+    if x.magnitude() > MAX_ARGUMENT {
+        return None;
+    }
+    let working_precision = precision + 8;
+    let mut x = x.clone();
+    if x.magnitude() > 4 {
+        let two_pi = pi(working_precision + 16).mul(&Enclosure::point(2.into()));
+        let mid = Rational::from(&x.lo + &x.hi) / 2;
+        let k = Rational::from(&mid / &two_pi.lo).round();
+        x = x.sub(&two_pi.mul(&Enclosure::point(k)));
+    }
+
+    let eps = epsilon(working_precision);
+    let m = x.magnitude();
+    let m_squared = Rational::from(&m * &m);
+    let x_squared = x.square();
+    let (mut term, mut bound, mut n) = if is_sin {
+        (x.clone(), m.clone(), 1u32)
+    } else {
+        (Enclosure::point(1.into()), Rational::from(1), 0u32)
+    };
+    let mut sum = Enclosure::point(Rational::new());
+    let mut negate = false;
+
+    // `term` encloses `x^n / n!`, and `bound` is an upper bound for its absolute value. The
+    // remainder of the series after some term is bounded by that term, once `n` exceeds `|x|`
+    while bound >= eps || m >= n {
+        let signed = if negate { term.neg() } else { term.clone() };
+        sum = sum.add(&signed);
+        let divisor = Rational::from((n + 1) * (n + 2));
+        term = term
+            .mul(&x_squared)
+            .mul(&Enclosure::point(divisor.clone().recip()))
+            .round(working_precision + 16);
+        bound = bound * &m_squared / divisor;
+        negate = !negate;
+        n += 2;
+    }
+    let error = Rational::from(&bound + &epsilon(working_precision + 8));
+    Some(sum.widen(&error).round(working_precision).clamp_unit())
+}
+
+fn sqrt(x: &Enclosure, precision: u32) -> Option<Enclosure> {
+    if x.lo < 0 {
+        return None;
+    }
+    let scale = Rational::from(Integer::from(1) << (2 * precision));
+    let unscale = Integer::from(1) << precision;
+    let lo = Rational::from(&x.lo * &scale)
+        .floor()
+        .into_numer_denom()
+        .0
+        .sqrt();
+    let hi = Rational::from(&x.hi * &scale)
+        .ceil()
+        .into_numer_denom()
+        .0
+        .sqrt()
+        + 1;
+    Some(Enclosure::new(
+        Rational::from((lo, unscale.clone())),
+        Rational::from((hi, unscale)),
+    ))
+}
+
+/// Computes an enclosure of the value of a ground term built from numerical constants, the
+/// arithmetic operators, `real.pi`, `exp`, `sin`, `cos` and `sqrt`. Returns `None` if the term uses
+/// other operators, or if its value is undefined or too large.
+fn enclose(term: &Rc<Term>, precision: u32) -> Option<Enclosure> {
+    if let Some(r) = term.as_number() {
+        return Some(Enclosure::point(r));
+    }
+    let Term::Op(op, args) = term.as_ref() else {
+        return None;
+    };
+    let args: Vec<_> = args
+        .iter()
+        .map(|a| enclose(a, precision))
+        .collect::<Option<_>>()?;
+    let result = match op {
+        Operator::Add => args[1..].iter().fold(args[0].clone(), |acc, x| acc.add(x)),
+        Operator::Sub if args.len() == 1 => args[0].neg(),
+        Operator::Sub => args[1..].iter().fold(args[0].clone(), |acc, x| acc.sub(x)),
+        Operator::Mult => args[1..].iter().fold(args[0].clone(), |acc, x| acc.mul(x)),
+        Operator::RealDiv => {
+            let mut result = args[0].clone();
+            for x in &args[1..] {
+                result = result.div(x)?;
+            }
+            result
+        }
+        Operator::ToReal => args[0].clone(),
+        Operator::Pi => pi(precision),
+        Operator::Exp => exp(&args[0], precision)?,
+        Operator::Sin => sin_or_cos(&args[0], precision, true)?,
+        Operator::Cos => sin_or_cos(&args[0], precision, false)?,
+        Operator::Sqrt => sqrt(&args[0], precision)?,
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Tries to decide the value of a literal using enclosures computed with the given precision. The
+/// literal may be a comparison between ground terms, or a negation or conjunction of such
+/// literals. Returns `Ok(None)` if the enclosures are not precise enough to decide its value, and
+/// an error if some term can't be enclosed.
+fn decide(literal: &Rc<Term>, precision: u32) -> Result<Option<bool>, CheckerError> {
+    if let Some(inner) = match_term!((not l) = literal) {
+        return Ok(decide(inner, precision)?.map(|b| !b));
+    }
+    match literal.as_ref() {
+        Term::Op(Operator::And, args) => {
+            let mut result = Some(true);
+            for a in args {
+                match decide(a, precision)? {
+                    Some(false) => return Ok(Some(false)),
+                    Some(true) => (),
+                    None => result = None,
+                }
+            }
+            Ok(result)
+        }
+        Term::Op(
+            op @ (Operator::LessThan
+            | Operator::LessEq
+            | Operator::GreaterThan
+            | Operator::GreaterEq),
+            args,
+        ) if args.len() == 2 => {
+            let [a, b] = [&args[0], &args[1]].map(|t| {
+                enclose(t, precision).ok_or_else(|| LinearArithmeticError::CannotEnclose(t.clone()))
+            });
+            let (a, b) = (a?, b?);
+            let (x, y) = match op {
+                Operator::LessThan | Operator::LessEq => (a, b),
+                _ => (b, a),
+            };
+            let strict = matches!(op, Operator::LessThan | Operator::GreaterThan);
+
+            // We are deciding `x < y` or `x <= y`, where `x` and `y` are in the given enclosures
+            let result = if x.hi < y.lo || (!strict && x.hi == y.lo) {
+                Some(true)
+            } else if x.lo > y.hi || (strict && x.lo == y.hi) {
+                Some(false)
+            } else {
+                None
+            };
+            Ok(result)
+        }
+        _ => Err(CheckerError::TermOfWrongForm(
+            "a bound on a ground term",
+            literal.clone(),
+        )),
+    }
+}
+
+/// Tries to prove that some literal in the clause holds, using increasingly precise enclosures.
+fn prove_with_enclosures(clause: &[Rc<Term>]) -> RuleResult {
+    for precision in PRECISIONS {
+        for literal in clause {
+            if decide(literal, precision)? == Some(true) {
+                return Ok(());
+            }
+        }
+    }
+    Err(LinearArithmeticError::BoundNotProven.into())
+}
+
+/// Checks that a rational constant `n` is equal to the given integer.
+fn assert_constant(term: &Rc<Term>, expected: i32) -> RuleResult {
+    match term.as_signed_number() {
+        Some(n) if n == expected => Ok(()),
+        _ => Err(CheckerError::ExpectedNumber(expected.into(), term.clone())),
+    }
+}
+
+/// Checks a clause of bounds on ground terms that use transcendental functions, such as
+/// `(cl (< (exp 1.0) 2.72))`. Some literal of the clause must be proven true by interval
+/// arithmetic. The literals may be comparisons between terms built from numerical constants, the
+/// arithmetic operators, `real.pi`, `exp`, `sin`, `cos` and `sqrt`, as well as negations and
+/// conjunctions of such comparisons.
+pub fn arith_trans_bound(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1..)?;
+    prove_with_enclosures(conclusion)
+}
+
+/// Checks a bound on the value of `pi`, of the form `(and (>= real.pi l) (<= real.pi u))`, where
+/// `l` and `u` are numerical constants.
+pub fn arith_trans_pi(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let ((pi, l), (pi_2, u)) = match_term_err!((and (>= pi l) (<= pi_2 u)) = &conclusion[0])?;
+    for p in [pi, pi_2] {
+        if !matches!(p.as_ref(), Term::Op(Operator::Pi, _)) {
+            return Err(CheckerError::TermOfWrongForm("real.pi", p.clone()));
+        }
+    }
+    for bound in [l, u] {
+        if bound.as_fraction().is_none() {
+            return Err(CheckerError::ExpectedAnyNumber(bound.clone()));
+        }
+    }
+    prove_with_enclosures(conclusion)
+}
+
+/// Checks that the exponential is always positive: `(cl (> (exp t) 0))`.
+pub fn arith_trans_exp_positivity(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let (_, zero) = match_term_err!((> (exp t) zero) = &conclusion[0])?;
+    assert_constant(zero, 0)
+}
+
+/// Checks that the exponential of a term is less than one exactly when the term is negative:
+/// `(cl (= (< t 0) (< (exp t) 1)))`.
+pub fn arith_trans_exp_neg(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let ((t, zero), (t_2, one)) =
+        match_term_err!((= (< t zero) (< (exp t_2) one)) = &conclusion[0])?;
+    assert_eq(t, t_2)?;
+    assert_constant(zero, 0)?;
+    assert_constant(one, 1)
+}
+
+/// Checks that the exponential of a term is one exactly when the term is zero:
+/// `(cl (= (= t 0) (= (exp t) 1)))`.
+pub fn arith_trans_exp_zero(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let ((t, zero), (t_2, one)) =
+        match_term_err!((= (= t zero) (= (exp t_2) one)) = &conclusion[0])?;
+    assert_eq(t, t_2)?;
+    assert_constant(zero, 0)?;
+    assert_constant(one, 1)
+}
+
+/// Checks that the exponential grows faster than a linear function for positive arguments:
+/// `(cl (or (<= t 0) (> (exp t) (+ t 1))))`.
+pub fn arith_trans_exp_super_lin(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let ((t, zero), (t_2, (t_3, one))) =
+        match_term_err!((or (<= t zero) (> (exp t_2) (+ t_3 one))) = &conclusion[0])?;
+    assert_eq(t, t_2)?;
+    assert_eq(t, t_3)?;
+    assert_constant(zero, 0)?;
+    assert_constant(one, 1)
+}
+
+/// Checks that the sine is bounded by one: `(cl (and (<= (sin t) 1) (>= (sin t) (- 1))))`.
+pub fn arith_trans_sine_bounds(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let ((t, one), (t_2, minus_one)) =
+        match_term_err!((and (<= (sin t) one) (>= (sin t_2) minus_one)) = &conclusion[0])?;
+    assert_eq(t, t_2)?;
+    assert_constant(one, 1)?;
+    assert_constant(minus_one, -1)
+}
+
+/// Checks that the sine is an odd function: `(cl (= (+ (sin t) (sin (* (- 1) t))) 0))`.
+pub fn arith_trans_sine_symmetry(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
+    assert_clause_len(conclusion, 1)?;
+
+    let ((t, (minus_one, t_2)), zero) =
+        match_term_err!((= (+ (sin t) (sin (* minus_one t_2))) zero) = &conclusion[0])?;
+    assert_eq(t, t_2)?;
+    assert_constant(minus_one, -1)?;
+    assert_constant(zero, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    const DEFINITIONS: &str = "
+        (set-logic QF_NRAT)
+        (declare-const x Real)
+        (declare-const y Real)
+    ";
+
+    #[test]
+    fn arith_trans_bound() {
+        test_cases! {
+            definitions = DEFINITIONS,
+            "Simple working examples" {
+                "(step t1 (cl (< (exp 1.0) 2.72)) :rule arith_trans_bound)": true,
+                "(step t1 (cl (> (exp 1.0) 2.71)) :rule arith_trans_bound)": true,
+                "(step t1 (cl (< (exp (- 10.0)) 0.0001)) :rule arith_trans_bound)": true,
+                "(step t1 (cl (and (< 0.84 (sin 1.0)) (< (sin 1.0) 0.85))) :rule arith_trans_bound)": true,
+                "(step t1 (cl (< (cos 3.0) (- 0.98))) :rule arith_trans_bound)": true,
+                "(step t1 (cl (> (sin 100.0) (- 0.51))) :rule arith_trans_bound)": true,
+                "(step t1 (cl (<= (sqrt 2.0) 1.4143)) :rule arith_trans_bound)": true,
+                "(step t1 (cl (not (>= (* 2.0 real.pi) 6.3))) :rule arith_trans_bound)": true,
+            }
+            "Some literal must hold" {
+                "(step t1 (cl (> (exp 1.0) 3.0) (< (sin real.pi) 0.001)) :rule arith_trans_bound)": true,
+                "(step t1 (cl (> (exp 1.0) 3.0) (< (cos 0.0) 1.0)) :rule arith_trans_bound)": false,
+            }
+            "Bound does not hold" {
+                "(step t1 (cl (< (exp 1.0) 2.71)) :rule arith_trans_bound)": false,
+                "(step t1 (cl (> (sin 1.0) 0.85)) :rule arith_trans_bound)": false,
+                "(step t1 (cl (< (sqrt 2.0) 1.4142)) :rule arith_trans_bound)": false,
+            }
+            "Terms are not ground" {
+                "(step t1 (cl (< (exp x) 2.72)) :rule arith_trans_bound)": false,
+                "(step t1 (cl (= (sin 0.0) 0.0)) :rule arith_trans_bound)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn arith_trans_pi() {
+        test_cases! {
+            definitions = DEFINITIONS,
+            "Simple working examples" {
+                "(step t1 (cl (and (>= real.pi 3.14) (<= real.pi 3.15))) :rule arith_trans_pi)": true,
+                "(step t1 (cl (and (>= real.pi (/ 314159.0 100000.0)) (<= real.pi (/ 314160.0 100000.0))))
+                    :rule arith_trans_pi)": true,
+            }
+            "Bounds are wrong" {
+                "(step t1 (cl (and (>= real.pi 3.15) (<= real.pi 3.16))) :rule arith_trans_pi)": false,
+                "(step t1 (cl (and (>= real.pi 3.14159) (<= real.pi 3.14159))) :rule arith_trans_pi)": false,
+            }
+            "Term is not a bound on pi" {
+                "(step t1 (cl (and (>= (exp 1.0) 2.0) (<= (exp 1.0) 3.0))) :rule arith_trans_pi)": false,
+                "(step t1 (cl (and (>= real.pi x) (<= real.pi 4.0))) :rule arith_trans_pi)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn arith_trans_exp() {
+        test_cases! {
+            definitions = DEFINITIONS,
+            "Positivity" {
+                "(step t1 (cl (> (exp x) 0.0)) :rule arith_trans_exp_positivity)": true,
+                "(step t1 (cl (> (exp x) 1.0)) :rule arith_trans_exp_positivity)": false,
+            }
+            "Negative arguments" {
+                "(step t1 (cl (= (< x 0.0) (< (exp x) 1.0))) :rule arith_trans_exp_neg)": true,
+                "(step t1 (cl (= (< x 0.0) (< (exp y) 1.0))) :rule arith_trans_exp_neg)": false,
+            }
+            "Zero" {
+                "(step t1 (cl (= (= x 0.0) (= (exp x) 1.0))) :rule arith_trans_exp_zero)": true,
+                "(step t1 (cl (= (= x 1.0) (= (exp x) 1.0))) :rule arith_trans_exp_zero)": false,
+            }
+            "Super-linearity" {
+                "(step t1 (cl (or (<= x 0.0) (> (exp x) (+ x 1.0)))) :rule arith_trans_exp_super_lin)": true,
+                "(step t1 (cl (or (<= x 0.0) (> (exp x) (+ y 1.0)))) :rule arith_trans_exp_super_lin)": false,
+                "(step t1 (cl (or (<= x 0.0) (> (exp x) (+ x 2.0)))) :rule arith_trans_exp_super_lin)": false,
+            }
+        }
+    }
+
+    #[test]
+    fn arith_trans_sine() {
+        test_cases! {
+            definitions = DEFINITIONS,
+            "Bounds" {
+                "(step t1 (cl (and (<= (sin x) 1.0) (>= (sin x) (- 1.0)))) :rule arith_trans_sine_bounds)": true,
+                "(step t1 (cl (and (<= (sin x) 1.0) (>= (sin y) (- 1.0)))) :rule arith_trans_sine_bounds)": false,
+                "(step t1 (cl (and (<= (sin x) 1.0) (>= (sin x) 0.0))) :rule arith_trans_sine_bounds)": false,
+            }
+            "Symmetry" {
+                "(step t1 (cl (= (+ (sin x) (sin (* (- 1.0) x))) 0.0)) :rule arith_trans_sine_symmetry)": true,
+                "(step t1 (cl (= (+ (sin x) (sin (* 1.0 x))) 0.0)) :rule arith_trans_sine_symmetry)": false,
+            }
+        }
+    }
+}
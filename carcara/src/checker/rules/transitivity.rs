@@ -1,5 +1,21 @@
 use super::{assert_clause_len, get_premise_term, CheckerError, RuleArgs, RuleResult};
-use crate::ast::*;
+use crate::{ast::*, checker::trace::trace_value};
+
+/// Finds a transitive chain, and records the chain order found when the step is being traced.
+fn find_chain_traced(
+    conclusion: (&Rc<Term>, &Rc<Term>),
+    premises: &mut [(&Rc<Term>, &Rc<Term>)],
+) -> RuleResult {
+    find_chain(conclusion, premises)?;
+    trace_value(|| {
+        let chain: Vec<_> = premises
+            .iter()
+            .map(|(t, u)| format!("(= {} {})", t, u))
+            .collect();
+        format!("chain order: {}", chain.join(", "))
+    });
+    Ok(())
+}
 
 /// Function to find a transitive chain given a conclusion equality and a series of premise
 /// equalities.
@@ -56,7 +72,7 @@ pub fn eq_transitive(RuleArgs { conclusion, .. }: RuleArgs) -> RuleResult {
         .map(|term| match_term_err!((not (= t u)) = term))
         .collect::<Result<_, _>>()?;
 
-    find_chain(chain_conclusion, &mut premises)
+    find_chain_traced(chain_conclusion, &mut premises)
 }
 
 pub fn trans(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
@@ -68,7 +84,7 @@ pub fn trans(RuleArgs { conclusion, premises, .. }: RuleArgs) -> RuleResult {
         .map(|premise| match_term_err!((= t u) = get_premise_term(premise)?))
         .collect::<Result<_, _>>()?;
 
-    find_chain(conclusion, &mut premises)
+    find_chain_traced(conclusion, &mut premises)
 }
 
 #[cfg(test)]
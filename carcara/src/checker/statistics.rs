@@ -0,0 +1,73 @@
+use super::CheckerStatistics;
+use crate::benchmarking::CollectResults;
+use std::time::Duration;
+
+/// A sink for the events emitted while checking or elaborating a proof.
+///
+/// All methods have empty default implementations, so implementors only need to override the
+/// events they are interested in. This can be used to export metrics to an external system, or to
+/// aggregate them in ways not supported by the benchmarking module, without having to modify the
+/// checker itself. [`CheckerStatistics`] is itself implemented in terms of this trait.
+pub trait StatisticsCollector {
+    /// Called right before a step is checked. Every call is matched by a later call to
+    /// `step_finished` with the same id and rule, unless checking the step fails.
+    fn step_started(&mut self, _id: &str, _rule: &str) {}
+
+    /// Called after a step was successfully checked, or skipped because its rule is ignored.
+    /// `polyeq_time` is the part of `time` that was spent comparing terms for equality modulo
+    /// reordering.
+    ///
+    /// Opening a subproof is also reported as a step, whose rule is `anchor(<rule>)`, where
+    /// `<rule>` is the rule of the subproof's last step.
+    fn step_finished(&mut self, _id: &str, _rule: &str, _time: Duration, _polyeq_time: Duration) {}
+
+    /// Called after an `assume` command was successfully matched with a problem premise. If
+    /// `is_easy` is true, the term was found syntactically in the premises. Otherwise, `core_time`
+    /// is the time spent comparing it with the matching premise, and `polyeq_time` is the total
+    /// time spent comparing it with all premises.
+    fn assume_checked(
+        &mut self,
+        _id: &str,
+        _is_easy: bool,
+        _time: Duration,
+        _core_time: Duration,
+        _polyeq_time: Duration,
+    ) {
+    }
+
+    /// Called with the maximum depth reached in each comparison made when checking an `assume`.
+    fn polyeq_depth(&mut self, _depth: usize) {}
+
+    /// Called whenever the term pool has grown, with its new size.
+    fn pool_grew(&mut self, _num_terms: usize) {}
+
+    /// Called after each pass of the elaboration pipeline is done.
+    fn elaboration_pass(&mut self, _pass: &str, _time: Duration) {}
+}
+
+impl<CR: CollectResults + Send + Default> StatisticsCollector for CheckerStatistics<'_, CR> {
+    fn step_finished(&mut self, id: &str, rule: &str, time: Duration, polyeq_time: Duration) {
+        self.results
+            .add_step_measurement(self.file_name, id, rule, time);
+        self.polyeq_time += polyeq_time;
+    }
+
+    fn assume_checked(
+        &mut self,
+        id: &str,
+        is_easy: bool,
+        time: Duration,
+        core_time: Duration,
+        polyeq_time: Duration,
+    ) {
+        self.assume_time += time;
+        self.assume_core_time += core_time;
+        self.polyeq_time += polyeq_time;
+        self.results
+            .add_assume_measurement(self.file_name, id, is_easy, time);
+    }
+
+    fn polyeq_depth(&mut self, depth: usize) {
+        self.results.add_polyeq_depth(depth);
+    }
+}
@@ -0,0 +1,113 @@
+//! Tracing of individual steps while they are checked.
+//!
+//! When a step is traced, the checker prints its premises, arguments, conclusion and the context
+//! substitution in effect, as well as any intermediate values recorded by the rule through
+//! `trace_value`. This is useful when debugging both proofs and rule implementations.
+
+use std::{cell::RefCell, convert::Infallible, str::FromStr};
+
+thread_local! {
+    /// The values recorded while checking the current step, or `None` if it is not being traced.
+    static TRACE_BUFFER: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Returns `true` if the step currently being checked is being traced.
+pub(super) fn is_enabled() -> bool {
+    TRACE_BUFFER.with(|b| b.borrow().is_some())
+}
+
+/// Records an intermediate value computed by a rule. The message is only built if the step is
+/// being traced.
+pub(super) fn trace_value(message: impl FnOnce() -> String) {
+    TRACE_BUFFER.with(|b| {
+        if let Some(buffer) = b.borrow_mut().as_mut() {
+            buffer.push(message());
+        }
+    });
+}
+
+pub(super) fn begin() {
+    TRACE_BUFFER.with(|b| *b.borrow_mut() = Some(Vec::new()));
+}
+
+/// Stops tracing, returning the recorded values.
+pub(super) fn end() -> Vec<String> {
+    TRACE_BUFFER.with(|b| b.borrow_mut().take().unwrap_or_default())
+}
+
+/// The steps that should be traced. This is either a single step, or an inclusive range of steps
+/// in the order they appear in the proof, written as `<first>..<last>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceSelection {
+    first: String,
+    last: String,
+}
+
+impl TraceSelection {
+    pub fn new(first: impl Into<String>, last: impl Into<String>) -> Self {
+        Self {
+            first: first.into(),
+            last: last.into(),
+        }
+    }
+}
+
+impl FromStr for TraceSelection {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.split_once("..") {
+            Some((first, last)) => Self::new(first, last),
+            None => Self::new(s, s),
+        })
+    }
+}
+
+/// Keeps track of whether the steps being checked are inside the selected range.
+#[derive(Debug, Default)]
+pub(super) struct TraceState {
+    selection: Option<TraceSelection>,
+    active: bool,
+}
+
+impl TraceState {
+    pub fn new(selection: Option<TraceSelection>) -> Self {
+        Self { selection, active: false }
+    }
+
+    /// Returns `true` if the step with the given id should be traced. This must be called once for
+    /// every step, in the order they are checked.
+    pub fn should_trace(&mut self, id: &str) -> bool {
+        let Some(selection) = &self.selection else {
+            return false;
+        };
+        if id == selection.first {
+            self.active = true;
+        }
+        let result = self.active;
+        if id == selection.last {
+            self.active = false;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_selection() {
+        let mut state = TraceState::new(Some("t2..t3.t1".parse().unwrap()));
+        let got: Vec<_> = ["t1", "t2", "t3.t1", "t3", "t4"]
+            .into_iter()
+            .filter(|id| state.should_trace(id))
+            .collect();
+        assert_eq!(got, ["t2", "t3.t1"]);
+
+        let mut state = TraceState::new(Some("t3".parse().unwrap()));
+        assert!(!state.should_trace("t1"));
+        assert!(state.should_trace("t3"));
+        assert!(!state.should_trace("t4"));
+    }
+}
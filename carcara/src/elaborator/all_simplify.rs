@@ -0,0 +1,81 @@
+use super::{transitivity::build_equality_chain, IdHelper};
+use crate::{
+    ast::*,
+    checker::error::CheckerError,
+    rewriting::{subterm_at, RewriteStep, Rewriter, RuleDatabase},
+};
+
+/// Builds a step that proves the equality between `term` and the result of applying `rewrite` to
+/// it. The rewrite itself is justified by a step using the rewrite's rule, which is then lifted to
+/// the whole term using `cong` steps.
+fn lift_rewrite(
+    pool: &mut dyn TermPool,
+    ids: &mut IdHelper,
+    depth: usize,
+    term: &Rc<Term>,
+    rewrite: &RewriteStep,
+) -> Rc<ProofNode> {
+    let (redex, contractum) = (rewrite.redex.clone(), rewrite.contractum.clone());
+    let mut node = Rc::new(ProofNode::Step(StepNode {
+        id: ids.next_id(),
+        depth,
+        clause: vec![pool.mk_eq(redex, contractum)],
+        rule: rewrite.rule.clone(),
+        ..Default::default()
+    }));
+    for k in (0..rewrite.position.len()).rev() {
+        let before = subterm_at(term, &rewrite.position[..k]).clone();
+        let after = subterm_at(&rewrite.result, &rewrite.position[..k]).clone();
+        node = Rc::new(ProofNode::Step(StepNode {
+            id: ids.next_id(),
+            depth,
+            clause: vec![pool.mk_eq(before, after)],
+            rule: "cong".to_owned(),
+            premises: vec![node],
+            ..Default::default()
+        }));
+    }
+    node
+}
+
+/// Elaborates an `all_simplify` step into the sequence of fine-grained rewrites that justifies it,
+/// lifting each rewrite to the whole term with `cong` steps, and chaining them with a `trans` step.
+/// If no such sequence is found, or if the step is a single constant evaluation, the step is left
+/// unchanged.
+pub fn all_simplify(
+    pool: &mut PrimitivePool,
+    _: &mut ContextStack,
+    step: &StepNode,
+) -> Result<Rc<ProofNode>, CheckerError> {
+    assert_eq!(step.clause.len(), 1);
+
+    let (left, right) = match_term_err!((= l r) = &step.clause[0])?;
+    let database = RuleDatabase::simplification();
+    let Some(sequence) = Rewriter::new(&database).find_sequence(pool, left, right) else {
+        return Ok(Rc::new(ProofNode::Step(step.clone())));
+    };
+
+    if let [s] = sequence.as_slice() {
+        if s.rule == "all_simplify" && s.position.is_empty() {
+            return Ok(Rc::new(ProofNode::Step(step.clone())));
+        }
+    }
+
+    let mut ids = IdHelper::new(&step.id);
+    let mut current = left.clone();
+    let mut links = Vec::with_capacity(sequence.len());
+    for rewrite in &sequence {
+        links.push(lift_rewrite(pool, &mut ids, step.depth, &current, rewrite));
+        current = rewrite.result.clone();
+    }
+
+    // The last step in the chain must have the same id as the original step
+    let broken_chain = || CheckerError::BrokenTransitivityChain(left.clone(), right.clone());
+    let chain = build_equality_chain(pool, &mut ids, step.depth, &links, left, right)
+        .ok_or_else(broken_chain)?;
+    let chain = chain.as_step().ok_or_else(broken_chain)?;
+    Ok(Rc::new(ProofNode::Step(StepNode {
+        id: step.id.clone(),
+        ..chain.clone()
+    })))
+}
@@ -0,0 +1,196 @@
+use super::*;
+use std::collections::hash_map::Entry;
+
+/// The parts of a step that determine whether two steps are duplicates.
+#[derive(PartialEq, Eq, Hash)]
+struct StepKey {
+    rule: String,
+    clause: Clause,
+    premises: Vec<Rc<ProofNode>>,
+    args: Vec<Rc<Term>>,
+    discharge: Vec<Rc<ProofNode>>,
+}
+
+/// Merges steps that have the same rule, conclusion, premises, arguments and discharged
+/// assumptions, redirecting every reference to a duplicate step to its first occurrence. Returns
+/// the new proof and the number of steps that were removed.
+///
+/// Only steps in the root proof are merged. Steps in different subproofs can't reference each
+/// other, so merging them could produce invalid premise references.
+pub fn remove_duplicate_steps(root: &Rc<ProofNode>) -> (Rc<ProofNode>, usize) {
+    let mut seen: HashMap<StepKey, Rc<ProofNode>> = HashMap::new();
+    let mut removed = 0;
+
+    let result = mutate(root, |_, node| {
+        let Some(step) = node.as_step() else {
+            return node.clone();
+        };
+        if step.depth > 0 {
+            return node.clone();
+        }
+
+        // Since the premises are mutated before the step itself, any duplicate premises were
+        // already replaced by their first occurrence, so comparing them by reference is enough
+        let key = StepKey {
+            rule: step.rule.clone(),
+            clause: step.clause.as_slice().into(),
+            premises: step.premises.clone(),
+            args: step.args.clone(),
+            discharge: step.discharge.clone(),
+        };
+        match seen.get(&key) {
+            Some(first) => {
+                removed += 1;
+                first.clone()
+            }
+            None => {
+                seen.insert(key, node.clone());
+                node.clone()
+            }
+        }
+    });
+    (result, removed)
+}
+
+/// Redirects every reference to a step to the first command in the proof that concludes the same
+/// clause, regardless of how that clause was derived. Returns the new proof and the number of steps
+/// that were replaced.
+///
+/// This is a more aggressive form of [`remove_duplicate_steps`]. Since the proof is traversed in
+/// postorder, the first command to conclude a clause never depends on later commands that
+/// conclude it, so redirecting references to it can't introduce cycles. Like in
+/// `remove_duplicate_steps`, only commands in the root proof are considered.
+///
+/// A step is never replaced by a `hole` step, since that would make the proof holey. Instead, the
+/// first command that is not a `hole` step becomes the representative of its clause, and later
+/// references are redirected to it.
+pub fn merge_equal_conclusions(root: &Rc<ProofNode>) -> (Rc<ProofNode>, usize) {
+    let is_hole = |node: &Rc<ProofNode>| node.as_step().is_some_and(|s| s.rule == "hole");
+    let mut representatives: HashMap<Clause, Rc<ProofNode>> = HashMap::new();
+    let mut replaced = 0;
+
+    let result = mutate(root, |_, node| {
+        if node.depth() > 0 || matches!(node.as_ref(), ProofNode::Subproof(_)) {
+            return node.clone();
+        }
+        match representatives.entry(node.clause().into()) {
+            Entry::Occupied(mut e) if is_hole(e.get()) && !is_hole(node) => {
+                e.insert(node.clone());
+                node.clone()
+            }
+            Entry::Occupied(e) if node.as_step().is_some() => {
+                replaced += 1;
+                e.get().clone()
+            }
+            Entry::Occupied(_) => node.clone(),
+            Entry::Vacant(e) => {
+                e.insert(node.clone());
+                node.clone()
+            }
+        }
+    });
+    (result, replaced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn test_remove_duplicate_steps() {
+        let problem: &[u8] = b"
+            (declare-const a Bool)
+            (declare-const b Bool)
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (or a b))
+            (assume h2 (not a))
+            (step t1 (cl a b) :rule or :premises (h1))
+            (step t2 (cl a b) :rule or :premises (h1))
+            (step t3 (cl b) :rule resolution :premises (t1 h2))
+            (step t4 (cl b) :rule resolution :premises (t2 h2))
+            (step t5 (cl a b) :rule or :premises (h1) :args (1))
+            (anchor :step t6)
+            (step t6.t1 (cl (= b b)) :rule refl)
+            (step t6.t2 (cl (= b b)) :rule refl)
+            (step t6 (cl (= b b)) :rule bind)
+            (step t7 (cl) :rule hole :premises (t3 t4 t5 t6))
+        ";
+        let (_, proof, _) = parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let node = ProofNode::from_commands(proof.commands);
+        let (deduplicated, removed) = remove_duplicate_steps(&node);
+
+        // `t2` is a duplicate of `t1`, and once it is merged, `t4` becomes a duplicate of `t3`.
+        // `t5` has different arguments, and the steps in the subproof are not merged
+        assert_eq!(removed, 2);
+        let ids: Vec<_> = deduplicated
+            .into_commands()
+            .iter()
+            .map(|c| c.id().to_owned())
+            .collect();
+        assert_eq!(ids, ["h1", "t1", "h2", "t3", "t5", "t6", "t7"]);
+    }
+
+    #[test]
+    fn test_merge_equal_conclusions() {
+        let problem: &[u8] = b"
+            (declare-const a Bool)
+            (declare-const b Bool)
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (or a b))
+            (assume h2 (not a))
+            (step t1 (cl a b) :rule or :premises (h1))
+            (step t2 (cl a b) :rule hole)
+            (step t3 (cl b) :rule resolution :premises (t2 h2))
+            (step t4 (cl (not a)) :rule hole)
+            (step t5 (cl b a) :rule hole)
+            (step t6 (cl) :rule hole :premises (t1 t3 t4 t5))
+        ";
+        let (_, proof, _) = parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let node = ProofNode::from_commands(proof.commands);
+        let (merged, replaced) = merge_equal_conclusions(&node);
+
+        // `t2` concludes the same clause as `t1`, and `t4` the same as `h2`. `t5` has the same
+        // literals as `t1`, but in a different order
+        assert_eq!(replaced, 2);
+        let commands = merged.into_commands();
+        let ids: Vec<_> = commands.iter().map(|c| c.id().to_owned()).collect();
+        assert_eq!(ids, ["h1", "t1", "h2", "t3", "t5", "t6"]);
+        let ProofCommand::Step(t3) = &commands[3] else {
+            panic!();
+        };
+        assert_eq!(t3.premises, [(0, 1), (0, 2)]);
+    }
+
+    #[test]
+    fn test_merge_equal_conclusions_prefers_non_holes() {
+        let problem: &[u8] = b"
+            (declare-const a Bool)
+            (declare-const b Bool)
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (or a b))
+            (step t1 (cl a b) :rule hole)
+            (step t2 (cl b a) :rule hole :premises (t1))
+            (step t3 (cl a b) :rule or :premises (h1))
+            (step t4 (cl a b) :rule hole)
+            (step t5 (cl) :rule hole :premises (t2 t3 t4))
+        ";
+        let (_, proof, _) = parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let node = ProofNode::from_commands(proof.commands);
+        let (merged, replaced) = merge_equal_conclusions(&node);
+
+        // `t3` is not replaced by the `hole` step `t1`, and becomes the representative of its
+        // clause, so `t4` is replaced by it. `t2` was already visited, so it still uses `t1`
+        assert_eq!(replaced, 1);
+        let commands = merged.into_commands();
+        let ids: Vec<_> = commands.iter().map(|c| c.id().to_owned()).collect();
+        assert_eq!(ids, ["t1", "t2", "h1", "t3", "t5"]);
+        let ProofCommand::Step(t5) = &commands[4] else {
+            panic!();
+        };
+        assert_eq!(t5.premises, [(0, 1), (0, 3), (0, 3)]);
+    }
+}
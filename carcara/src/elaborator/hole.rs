@@ -150,6 +150,10 @@ fn parse_and_check_solver_proof(
         allow_int_real_subtyping: true,
         strict: false,
         parse_hole_args: false,
+        strings_profile: parser::StringsProfile::Cvc5,
+        alethe_version: None,
+        rename_duplicate_step_ids: false,
+        detect_mismatch: false,
     };
 
     let (problem, proof) = parser::parse_instance_with_pool(problem, proof, config, pool)?;
@@ -207,8 +211,11 @@ fn insert_solver_proof(
         rule: "subproof".to_owned(),
         premises: Vec::new(),
         args: Vec::new(),
+        arg_vars: Vec::new(),
         discharge: subproof_assumptions,
         previous_step: Some(proof),
+        origin: None,
+        attributes: Vec::new(),
     }));
 
     let subproof = Rc::new(ProofNode::Subproof(SubproofNode {
@@ -0,0 +1,131 @@
+use super::*;
+use indexmap::IndexMap;
+
+/// Collects the ids of all `assume` commands and steps in the proof, including the ones inside
+/// subproofs.
+pub(super) fn collect_ids(root: &Rc<ProofNode>) -> HashSet<String> {
+    let mut ids = HashSet::new();
+    root.traverse(|node| match node.as_ref() {
+        ProofNode::Assume { id, .. } => {
+            ids.insert(id.clone());
+        }
+        ProofNode::Step(s) => {
+            ids.insert(s.id.clone());
+        }
+        ProofNode::Subproof(_) => (),
+    });
+    ids
+}
+
+/// Returns the original id from which `id` was derived. New steps introduced when elaborating a
+/// step are given ids of the form `<id>.t<n>`, possibly nested, so this is the longest prefix of
+/// `id` that is an original id.
+pub(super) fn origin_of<'a>(id: &str, original_ids: &'a HashSet<String>) -> Option<&'a str> {
+    let mut prefix = id;
+    loop {
+        if let Some(original) = original_ids.get(prefix) {
+            return Some(original);
+        }
+        prefix = &prefix[..prefix.rfind('.')?];
+    }
+}
+
+/// Gives back the original ids to steps that lost them during elaboration.
+///
+/// Some elaboration passes replace a step with a new one, derived from it, that has a new id. For
+/// example, when a `reordering` step is removed, the step that was its premise takes its place.
+/// For each original id that no longer appears in the proof, if exactly one of the steps derived
+/// from it is not used as a premise by any of the others, that step is renamed to the original id.
+pub(super) fn restore_original_ids(
+    original: &Rc<ProofNode>,
+    elaborated: &Rc<ProofNode>,
+) -> Rc<ProofNode> {
+    let original_ids = collect_ids(original);
+
+    let mut present = HashSet::new();
+    let mut derived: IndexMap<&str, Vec<Rc<ProofNode>>> = IndexMap::new();
+    elaborated.traverse(|node| match node.as_ref() {
+        ProofNode::Assume { id, .. } => {
+            present.insert(id.clone());
+        }
+        ProofNode::Step(s) if original_ids.contains(&s.id) => {
+            present.insert(s.id.clone());
+        }
+        ProofNode::Step(s) => {
+            if let Some(origin) = origin_of(&s.id, &original_ids) {
+                derived.entry(origin).or_default().push(node.clone());
+            }
+        }
+        ProofNode::Subproof(_) => (),
+    });
+
+    let mut renames: HashMap<String, String> = HashMap::new();
+    for (origin, nodes) in derived {
+        if present.contains(origin) {
+            continue;
+        }
+        let used: HashSet<_> = nodes
+            .iter()
+            .flat_map(|n| &n.as_step().unwrap().premises)
+            .collect();
+        let unused: Vec<_> = nodes.iter().filter(|n| !used.contains(n)).collect();
+        if let [top] = unused[..] {
+            renames.insert(top.id().to_owned(), origin.to_owned());
+        }
+    }
+    if renames.is_empty() {
+        return elaborated.clone();
+    }
+
+    mutate(elaborated, |_, node| match node.as_ref() {
+        ProofNode::Step(s) => match renames.get(&s.id) {
+            Some(id) => Rc::new(ProofNode::Step(StepNode { id: id.clone(), ..s.clone() })),
+            None => node.clone(),
+        },
+        _ => node.clone(),
+    })
+}
+
+/// Records the provenance of the steps introduced by an elaboration pass, which transformed
+/// `before` into `after`. Every step in `after` whose id is not in `before` is annotated with the
+/// step from which it was derived and the name of the pass. Steps that already have an origin are
+/// left unchanged.
+pub(super) fn annotate_origins(
+    before: &Rc<ProofNode>,
+    after: &Rc<ProofNode>,
+    pass: &str,
+) -> Rc<ProofNode> {
+    let original_ids = collect_ids(before);
+    mutate(after, |_, node| match node.as_ref() {
+        ProofNode::Step(s) if s.origin.is_none() && !original_ids.contains(&s.id) => {
+            match origin_of(&s.id, &original_ids) {
+                Some(step) => Rc::new(ProofNode::Step(StepNode {
+                    origin: Some(StepOrigin {
+                        step: step.to_owned(),
+                        pass: pass.to_owned(),
+                    }),
+                    ..s.clone()
+                })),
+                None => node.clone(),
+            }
+        }
+        _ => node.clone(),
+    })
+}
+
+/// Maps the id of each `assume` command and step in the elaborated proof to the id of the command
+/// in the original proof from which it was derived. Commands that were kept from the original
+/// proof are mapped to their own ids.
+pub fn id_map(original: &Rc<ProofNode>, elaborated: &Rc<ProofNode>) -> IndexMap<String, String> {
+    let original_ids = collect_ids(original);
+    let mut result = IndexMap::new();
+    elaborated.traverse(|node| {
+        if let ProofNode::Subproof(_) = node.as_ref() {
+            return;
+        }
+        if let Some(origin) = origin_of(node.id(), &original_ids) {
+            result.insert(node.id().to_owned(), origin.to_owned());
+        }
+    });
+    result
+}
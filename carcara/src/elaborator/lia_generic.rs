@@ -1,8 +1,11 @@
 use super::*;
-use crate::{checker, parser, CarcaraResult};
+use crate::{checker, parser};
 use std::{
-    io::{self, BufRead, Write},
-    process::{Command, Stdio},
+    io::{self, BufRead, Read, Write},
+    process::{Child, ChildStderr, ChildStdin, ChildStdout, Command, Stdio},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
 };
 use thiserror::Error;
 
@@ -27,13 +30,20 @@ pub enum LiaGenericError {
     SolverTimeout,
 
     #[error(
-        "solver returned non-zero exit code: {}",
-        if let Some(i) = .0 { format!("{}", i) } else { "none".to_owned() }
+        "solver returned non-zero exit code: {}{}",
+        if let Some(i) = .0 { format!("{}", i) } else { "none".to_owned() },
+        if .1.is_empty() { String::new() } else { format!(", stderr: {}", .1.trim_end()) },
     )]
-    NonZeroExitCode(Option<i32>),
+    NonZeroExitCode(Option<i32>, String),
 
     #[error("error in inner proof: {0}")]
     InnerProofError(Box<crate::Error>),
+
+    #[error("inner proof has `lia_generic` step '{0}' nested beyond the depth limit")]
+    DepthLimitExceeded(String),
+
+    #[error("inner proof has unchecked `hole` step '{0}'")]
+    InnerProofIsHoley(String),
 }
 
 fn get_problem_string(
@@ -61,7 +71,8 @@ fn get_problem_string(
 pub fn lia_generic(elaborator: &mut Elaborator, step: &StepNode) -> Option<Rc<ProofNode>> {
     let problem = get_problem_string(elaborator.pool, &elaborator.problem.prelude, &step.clause);
     let options = elaborator.config.lia_options.as_ref().unwrap();
-    let commands = match get_solver_proof(elaborator.pool, problem, options) {
+    let stats = &mut elaborator.lia_stats;
+    let proof = match get_solver_proof(elaborator.pool, problem, options, 0, stats) {
         Ok(c) => c,
         Err(e) => {
             log::warn!("failed to elaborate `lia_generic` step: {}", e);
@@ -71,47 +82,44 @@ pub fn lia_generic(elaborator: &mut Elaborator, step: &StepNode) -> Option<Rc<Pr
 
     Some(insert_solver_proof(
         elaborator.pool,
-        commands,
+        proof,
         &step.clause,
         &step.id,
         step.depth,
     ))
 }
 
+/// Runs the solver on the given problem and returns its proof, after checking it. The `depth` is
+/// the level of nesting of the problem, that is, how many solver proofs were needed to get to it.
 fn get_solver_proof(
     pool: &mut PrimitivePool,
     problem: String,
     options: &LiaGenericOptions,
-) -> Result<Vec<ProofCommand>, LiaGenericError> {
-    let mut process = Command::new(options.solver.as_ref())
-        .args(options.arguments.iter().map(AsRef::as_ref))
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(LiaGenericError::FailedSpawnSolver)?;
-
-    process
-        .stdin
-        .take()
-        .expect("failed to open solver stdin")
-        .write_all(problem.as_bytes())
-        .map_err(LiaGenericError::FailedWriteToSolverStdin)?;
-
-    let output = process
-        .wait_with_output()
-        .map_err(LiaGenericError::FailedWaitForSolver)?;
-
-    if !output.status.success() {
-        if let Ok(s) = std::str::from_utf8(&output.stderr) {
-            if s.contains("interrupted by timeout.") {
-                return Err(LiaGenericError::SolverTimeout);
+    depth: usize,
+    stats: &mut LiaGenericStatistics,
+) -> Result<Rc<ProofNode>, LiaGenericError> {
+    let mut attempt = 0;
+    let stdout = loop {
+        // Each retry may use a different random seed, which may make the solver take a different
+        // path and succeed
+        let seed = (attempt > 0).then_some(attempt);
+        let time = Instant::now();
+        let result = run_solver(&problem, options, seed);
+        stats.solver_runs += 1;
+        stats.solver_time += time.elapsed();
+        match result {
+            Ok(stdout) => break stdout,
+            Err(e @ (LiaGenericError::SolverTimeout | LiaGenericError::NonZeroExitCode(..)))
+                if attempt < options.retries =>
+            {
+                attempt += 1;
+                log::info!("retrying `lia_generic` solver with seed {}: {}", attempt, e);
             }
+            Err(e) => return Err(e),
         }
-        return Err(LiaGenericError::NonZeroExitCode(output.status.code()));
-    }
+    };
 
-    let mut proof = output.stdout.as_slice();
+    let mut proof = stdout.as_slice();
     let mut first_line = String::new();
 
     proof
@@ -122,27 +130,227 @@ fn get_solver_proof(
         return Err(LiaGenericError::OutputNotUnsat);
     }
 
-    parse_and_check_solver_proof(pool, problem.as_bytes(), proof)
-        .map_err(|e| LiaGenericError::InnerProofError(Box::new(e)))
+    parse_and_check_solver_proof(pool, problem.as_bytes(), proof, options, depth, stats)
 }
 
+/// Runs the solver on the given problem, returning its standard output. The solver is run in its
+/// own process group, so that if the timeout is exceeded, it can be killed together with any
+/// processes it started, instead of leaving them behind.
+///
+/// Writing the problem to the solver and reading its output happen in a separate thread, so the
+/// timeout also applies if the solver stops reading its input or never closes its output.
+fn run_solver(
+    problem: &str,
+    options: &LiaGenericOptions,
+    seed: Option<usize>,
+) -> Result<Vec<u8>, LiaGenericError> {
+    let seed_option = seed.zip(options.seed_option.as_deref());
+    let mut command = Command::new(options.solver.as_ref());
+    command
+        .args(options.arguments.iter().map(AsRef::as_ref))
+        .args(seed_option.map(|(seed, option)| format!("{}={}", option, seed)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let deadline = options.timeout.map(|t| Instant::now() + t);
+    let mut process = command
+        .spawn()
+        .map_err(LiaGenericError::FailedSpawnSolver)?;
+
+    let (sender, receiver) = mpsc::channel();
+    let pipes = (
+        process.stdin.take().expect("failed to open solver stdin"),
+        process.stdout.take().expect("failed to open solver stdout"),
+        process.stderr.take().expect("failed to open solver stderr"),
+    );
+    let problem = problem.to_owned();
+    thread::spawn(move || {
+        let _ = sender.send(communicate(pipes, &problem));
+    });
+    let received = match deadline {
+        Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+        None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+    let (stdout, stderr) = match received {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            kill_solver(&mut process);
+            return Err(LiaGenericError::FailedWriteToSolverStdin(e));
+        }
+        Err(RecvTimeoutError::Timeout) => {
+            kill_solver(&mut process);
+            return Err(LiaGenericError::SolverTimeout);
+        }
+        Err(RecvTimeoutError::Disconnected) => {
+            kill_solver(&mut process);
+            let e = io::Error::new(io::ErrorKind::Other, "solver communication thread panicked");
+            return Err(LiaGenericError::FailedWaitForSolver(e));
+        }
+    };
+
+    let status = match wait_with_timeout(&mut process, deadline) {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            kill_solver(&mut process);
+            return Err(LiaGenericError::SolverTimeout);
+        }
+        Err(e) => {
+            kill_solver(&mut process);
+            return Err(LiaGenericError::FailedWaitForSolver(e));
+        }
+    };
+    let stderr = String::from_utf8_lossy(&stderr).into_owned();
+
+    if !status.success() {
+        if stderr.contains("interrupted by timeout.") {
+            return Err(LiaGenericError::SolverTimeout);
+        }
+        return Err(LiaGenericError::NonZeroExitCode(status.code(), stderr));
+    }
+    Ok(stdout)
+}
+
+/// Writes the problem to the solver's stdin, and reads its stdout and stderr until they are
+/// closed. The output pipes are read in separate threads, so the solver never blocks on a full pipe
+/// while the problem is being written.
+fn communicate(
+    (mut stdin, stdout, stderr): (ChildStdin, ChildStdout, ChildStderr),
+    problem: &str,
+) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    fn read_pipe(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = pipe.read_to_end(&mut buf);
+            buf
+        })
+    }
+
+    let stdout = read_pipe(stdout);
+    let stderr = read_pipe(stderr);
+    let written = stdin.write_all(problem.as_bytes());
+    drop(stdin);
+    let output = (
+        stdout.join().unwrap_or_default(),
+        stderr.join().unwrap_or_default(),
+    );
+    written.map(|()| output)
+}
+
+/// Waits for the process to exit, returning `None` if it doesn't exit before the deadline.
+fn wait_with_timeout(
+    process: &mut Child,
+    deadline: Option<Instant>,
+) -> io::Result<Option<std::process::ExitStatus>> {
+    let Some(deadline) = deadline else {
+        return process.wait().map(Some);
+    };
+    loop {
+        if let Some(status) = process.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(10)));
+    }
+}
+
+/// Kills the solver process and every process in its process group, and waits for it to exit so
+/// it doesn't become a zombie process.
+fn kill_solver(process: &mut Child) {
+    #[cfg(unix)]
+    {
+        // Since the solver is the leader of its process group, the group id is the same as its
+        // process id. A negative process id means the signal is sent to the whole group
+        if let Ok(pid) = libc::pid_t::try_from(process.id()) {
+            // SAFETY: `kill` has no memory safety preconditions
+            unsafe { libc::kill(-pid, libc::SIGKILL) };
+        }
+    }
+    let _ = process.kill();
+    let _ = process.wait();
+}
+
+/// Parses and checks the proof returned by the solver. The `lia_generic` steps in the solver proof
+/// are validated recursively, and the proofs of these nested steps are inserted in their place. In
+/// strict mode, unknown rules are not ignored and no holes are allowed, so that accepting the proof
+/// never depends on trusting the solver.
 fn parse_and_check_solver_proof(
     pool: &mut PrimitivePool,
     problem: &[u8],
     proof: &[u8],
-) -> CarcaraResult<Vec<ProofCommand>> {
+    options: &LiaGenericOptions,
+    depth: usize,
+    stats: &mut LiaGenericStatistics,
+) -> Result<Rc<ProofNode>, LiaGenericError> {
+    let inner_error = |e: crate::Error| LiaGenericError::InnerProofError(Box::new(e));
+
+    let time = Instant::now();
     let config = parser::Config {
         apply_function_defs: false,
         expand_lets: true,
         allow_int_real_subtyping: true,
         strict: false,
         parse_hole_args: false,
+        strings_profile: parser::StringsProfile::Cvc5,
+        alethe_version: None,
+        rename_duplicate_step_ids: false,
+        detect_mismatch: false,
     };
-    let (problem, proof) = parser::parse_instance_with_pool(problem, proof, config, pool)?;
+    let (problem, proof) =
+        parser::parse_instance_with_pool(problem, proof, config, pool).map_err(inner_error)?;
+
+    let checker_config = checker::Config::new().ignore_unknown_rules(!options.strict);
+    let is_holey = checker::ProofChecker::new(pool, checker_config)
+        .check(&problem, &proof)
+        .map_err(inner_error)?;
+    stats.checked_proofs += 1;
+    stats.max_depth = stats.max_depth.max(depth);
+    stats.checking_time += time.elapsed();
+
+    let root = ProofNode::from_commands(proof.commands);
+    if !is_holey {
+        return Ok(root);
+    }
 
-    let config = checker::Config::new().ignore_unknown_rules(true);
-    checker::ProofChecker::new(pool, config).check(&problem, &proof)?;
-    Ok(proof.commands)
+    // The nested `lia_generic` steps are discharged while the proof is being rebuilt, so each step
+    // node is handled on its own, even if the solver proof repeats step ids
+    let mut error = None;
+    let root = mutate(&root, |_, node| {
+        let ProofNode::Step(s) = node.as_ref() else {
+            return node.clone();
+        };
+        if error.is_some() {
+            return node.clone();
+        }
+        match s.rule.as_str() {
+            "lia_generic" if depth < options.max_depth => {
+                let inner_problem = get_problem_string(pool, &problem.prelude, &s.clause);
+                match get_solver_proof(pool, inner_problem, options, depth + 1, stats) {
+                    Ok(inner) => {
+                        return insert_solver_proof(pool, inner, &s.clause, &s.id, s.depth)
+                    }
+                    Err(e) => error = Some(e),
+                }
+            }
+            "lia_generic" if options.strict => {
+                error = Some(LiaGenericError::DepthLimitExceeded(s.id.clone()));
+            }
+            "hole" if options.strict => {
+                error = Some(LiaGenericError::InnerProofIsHoley(s.id.clone()));
+            }
+            _ => (),
+        }
+        node.clone()
+    });
+    match error {
+        Some(e) => Err(e),
+        None => Ok(root),
+    }
 }
 
 fn increase_subproof_depth(proof: &Rc<ProofNode>, delta: usize, prefix: &str) -> Rc<ProofNode> {
@@ -166,13 +374,11 @@ fn increase_subproof_depth(proof: &Rc<ProofNode>, delta: usize, prefix: &str) ->
 
 fn insert_solver_proof(
     pool: &mut PrimitivePool,
-    commands: Vec<ProofCommand>,
+    proof: Rc<ProofNode>,
     conclusion: &[Rc<Term>],
     root_id: &str,
     depth: usize,
 ) -> Rc<ProofNode> {
-    let proof = ProofNode::from_commands(commands.clone());
-
     let mut ids = IdHelper::new(root_id);
     let subproof_id = ids.next_id();
 
@@ -233,8 +439,11 @@ fn insert_solver_proof(
         rule: "subproof".to_owned(),
         premises: Vec::new(),
         args: Vec::new(),
+        arg_vars: Vec::new(),
         discharge: subproof_assumptions,
         previous_step: Some(proof),
+        origin: None,
+        attributes: Vec::new(),
     }));
 
     let subproof = Rc::new(ProofNode::Subproof(SubproofNode {
@@ -287,3 +496,274 @@ fn insert_solver_proof(
         ..Default::default()
     }))
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A temporary directory that is removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+            std::fs::create_dir(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn shell_solver(script: &str, timeout: Option<Duration>, retries: usize) -> LiaGenericOptions {
+        LiaGenericOptions {
+            solver: "sh".into(),
+            arguments: vec!["-c".into(), script.into()],
+            timeout,
+            retries,
+            seed_option: Some("--seed".into()),
+            max_depth: 0,
+            strict: false,
+        }
+    }
+
+    #[test]
+    fn test_solver_timeout() {
+        // The solver starts a child process that would keep running after the solver is killed, if
+        // the whole process group were not killed
+        let options = shell_solver("sleep 30 & sleep 30", Some(Duration::from_millis(100)), 0);
+        let start = Instant::now();
+        let result = run_solver("", &options, None);
+        assert!(matches!(result, Err(LiaGenericError::SolverTimeout)));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_solver_timeout_without_reading_input() {
+        // The problem is larger than the pipe buffer, so writing it would block forever if the
+        // timeout did not apply to it
+        let options = shell_solver("sleep 30", Some(Duration::from_millis(100)), 0);
+        let problem = "(assert true)\n".repeat(100_000);
+        let start = Instant::now();
+        let result = run_solver(&problem, &options, None);
+        assert!(matches!(result, Err(LiaGenericError::SolverTimeout)));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_solver_seed_option() {
+        let script = r#"cat > /dev/null; echo "$0""#;
+        let options = LiaGenericOptions {
+            seed_option: Some("--random-seed".into()),
+            ..shell_solver(script, None, 0)
+        };
+        let output = run_solver("", &options, Some(3)).unwrap();
+        assert_eq!(output, b"--random-seed=3\n");
+
+        let options = LiaGenericOptions {
+            seed_option: None,
+            ..shell_solver(script, None, 0)
+        };
+        let output = run_solver("", &options, Some(3)).unwrap();
+        assert_eq!(output, b"sh\n");
+    }
+
+    #[test]
+    fn test_solver_stderr_and_retries() {
+        // The solver only succeeds when given the seed of the second retry
+        let script =
+            r#"cat > /dev/null; [ "$0" = --seed=2 ] || { echo failed >&2; exit 3; }; echo sat"#;
+        let mut pool = PrimitivePool::new();
+
+        let mut stats = LiaGenericStatistics::default();
+
+        let options = shell_solver(script, None, 1);
+        let result = get_solver_proof(&mut pool, String::new(), &options, 0, &mut stats);
+        assert!(matches!(
+            result,
+            Err(LiaGenericError::NonZeroExitCode(Some(3), stderr)) if stderr == "failed\n"
+        ));
+
+        let options = shell_solver(script, None, 2);
+        let result = get_solver_proof(&mut pool, String::new(), &options, 0, &mut stats);
+        assert!(matches!(result, Err(LiaGenericError::OutputNotUnsat)));
+        assert_eq!(stats.solver_runs, 5);
+    }
+
+    #[test]
+    fn test_nested_solver_proofs() {
+        let run = |problem: &str, proof: &str, max_depth: usize| {
+            let mut pool = PrimitivePool::new();
+            let mut stats = LiaGenericStatistics::default();
+            let script = format!("cat > /dev/null; echo unsat; echo '{}'", proof);
+            let options = LiaGenericOptions {
+                max_depth,
+                strict: true,
+                ..shell_solver(&script, None, 0)
+            };
+            let result = get_solver_proof(&mut pool, problem.to_owned(), &options, 0, &mut stats);
+            (result, stats)
+        };
+
+        let (result, stats) = run(
+            "(assert false)",
+            "(assume h1 false) (step t1 (cl (not false)) :rule false) \
+             (step t2 (cl) :rule resolution :premises (h1 t1))",
+            0,
+        );
+        assert!(result.is_ok());
+        assert_eq!((stats.solver_runs, stats.checked_proofs), (1, 1));
+
+        // The solver always answers with a `lia_generic` step, so it is called again for each
+        // level of nesting, until the limit is reached
+        let (result, stats) = run("", "(step t1 (cl) :rule lia_generic)", 0);
+        assert!(matches!(result, Err(LiaGenericError::DepthLimitExceeded(id)) if id == "t1"));
+        assert_eq!((stats.solver_runs, stats.max_depth), (1, 0));
+
+        let (result, stats) = run("", "(step t1 (cl) :rule lia_generic)", 2);
+        assert!(matches!(
+            result,
+            Err(LiaGenericError::DepthLimitExceeded(_))
+        ));
+        assert_eq!((stats.solver_runs, stats.max_depth), (3, 2));
+
+        let (result, _) = run("", "(step t1 (cl) :rule hole)", 2);
+        assert!(matches!(result, Err(LiaGenericError::InnerProofIsHoley(id)) if id == "t1"));
+
+        let (result, _) = run("", "(step t1 (cl) :rule trust_me)", 2);
+        assert!(matches!(result, Err(LiaGenericError::InnerProofError(_))));
+    }
+
+    #[test]
+    fn test_non_strict_solver_proofs() {
+        let run = |proof: &str| {
+            let mut pool = PrimitivePool::new();
+            let mut stats = LiaGenericStatistics::default();
+            let script = format!("cat > /dev/null; echo unsat; echo '{}'", proof);
+            let options = LiaGenericOptions {
+                max_depth: 1,
+                ..shell_solver(&script, None, 0)
+            };
+            let result = get_solver_proof(&mut pool, String::new(), &options, 0, &mut stats);
+            (result, stats)
+        };
+
+        // Nested `lia_generic` steps are still validated up to the depth limit, but the ones past
+        // it are kept, as are holes and steps with unknown rules
+        let (result, stats) = run("(step t1 (cl) :rule lia_generic)");
+        assert!(result.is_ok());
+        assert_eq!((stats.solver_runs, stats.max_depth), (2, 1));
+
+        let (result, _) = run("(step t1 (cl) :rule hole)");
+        assert!(result.is_ok());
+
+        let (result, _) = run("(step t1 (cl) :rule trust_me)");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_nested_solver_proofs_with_repeated_ids() {
+        // The solver proof has two `lia_generic` steps with the same id, in sibling subproofs. Each
+        // must be replaced by the proof of its own conclusion
+        let nested = |n: u32| {
+            format!(
+                "(assume h1 (not (= {0} {0}))) (step t1 (cl (= {0} {0})) :rule refl) \
+                 (step t2 (cl) :rule resolution :premises (h1 t1))",
+                n
+            )
+        };
+        let outer = "(assume h1 (not true)) \
+            (anchor :step t3) (assume t3.a p) (step s (cl (= 1 1)) :rule lia_generic) \
+            (step t3 (cl (not p) (= 1 1)) :rule subproof :discharge (t3.a)) \
+            (anchor :step t4) (assume t4.a p) (step s (cl (= 2 2)) :rule lia_generic) \
+            (step t4 (cl (not p) (= 2 2)) :rule subproof :discharge (t4.a)) \
+            (step t5 (cl true) :rule hole :premises (t3 t4)) \
+            (step t6 (cl) :rule resolution :premises (h1 t5))";
+        let script = format!(
+            "input=$(cat); echo unsat; case \"$input\" in \
+             *'1 1'*) echo '{}' ;; *'2 2'*) echo '{}' ;; *) echo '{}' ;; esac",
+            nested(1),
+            nested(2),
+            outer,
+        );
+        let problem = "(declare-const p Bool) (assert (not true))";
+        let options = LiaGenericOptions {
+            max_depth: 1,
+            ..shell_solver(&script, None, 0)
+        };
+
+        let mut pool = PrimitivePool::new();
+        let mut stats = LiaGenericStatistics::default();
+        let root =
+            get_solver_proof(&mut pool, problem.to_owned(), &options, 0, &mut stats).unwrap();
+        let (problem, _) = parser::parse_instance_with_pool(
+            problem.as_bytes(),
+            &b""[..],
+            parser::Config::new(),
+            &mut pool,
+        )
+        .unwrap();
+        assert_eq!(stats.solver_runs, 3);
+
+        let proof = Proof {
+            constant_definitions: Vec::new(),
+            commands: root.into_commands(),
+        };
+        let result =
+            checker::ProofChecker::new(&mut pool, checker::Config::new()).check(&problem, &proof);
+        assert!(matches!(result, Ok(true)));
+    }
+
+    #[test]
+    fn test_nested_solver_proofs_are_inlined() {
+        // The first run of the solver answers with a `lia_generic` step, which is discharged by the
+        // proof given in the second run
+        let dir = TempDir::new("carcara-lia-inlined");
+        let counter = dir.0.join("counter");
+        let script = format!(
+            "cat > /dev/null; echo unsat; echo '(assume h1 (not true))'; \
+             if [ -e {0} ]; then echo '(step t1 (cl true) :rule true)'; \
+             else touch {0}; echo '(step t1 (cl true) :rule lia_generic)'; fi; \
+             echo '(step t2 (cl) :rule resolution :premises (h1 t1))'",
+            counter.display(),
+        );
+        let problem: &[u8] = b"(assert (not true))";
+        let proof: &[u8] = b"
+            (assume h1 (not true))
+            (step t1 (cl true) :rule lia_generic)
+            (step t2 (cl) :rule resolution :premises (h1 t1))
+        ";
+        let (problem, proof, mut pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let config = Config {
+            lia_options: Some(LiaGenericOptions {
+                max_depth: 1,
+                ..shell_solver(&script, None, 0)
+            }),
+            ..Config::new()
+        };
+        let node = ProofNode::from_commands(proof.commands);
+        let mut elaborator = Elaborator::new(&mut pool, &problem, config);
+        let elaborated = elaborator
+            .elaborate(&node, vec![ElaborationStep::LiaGeneric])
+            .unwrap();
+        let stats = elaborator.lia_generic_stats().clone();
+        assert_eq!((stats.solver_runs, stats.max_depth), (2, 1));
+
+        let elaborated = Proof {
+            constant_definitions: Vec::new(),
+            commands: elaborated.into_commands(),
+        };
+        assert!(elaborated.iter().all(|c| match c {
+            ProofCommand::Step(s) => s.rule != "lia_generic",
+            _ => true,
+        }));
+        let result = checker::ProofChecker::new(&mut pool, checker::Config::new())
+            .check(&problem, &elaborated);
+        assert!(matches!(result, Ok(false)));
+    }
+}
@@ -0,0 +1,176 @@
+use super::IdHelper;
+use crate::{
+    ast::*,
+    checker::{disequality_ratio, error::CheckerError},
+};
+
+/// Elaborates an `la_disequality` step that is not in the standard form, that is, whose conclusion
+/// is given as a clause instead of a single `or` term, or whose `<=` terms are not written exactly
+/// in terms of the sides of the equality. The step is replaced by an `la_disequality` step in the
+/// standard form, and each `<=` term that is written differently is justified by an `la_generic`
+/// step and replaced using resolution. If the step is already in the standard form, or is invalid,
+/// it is left unchanged.
+#[allow(clippy::unnecessary_wraps)] // All elaboration functions must have the same signature
+pub fn la_disequality(
+    pool: &mut PrimitivePool,
+    _: &mut ContextStack,
+    step: &StepNode,
+) -> Result<Rc<ProofNode>, CheckerError> {
+    let unchanged = || Ok(Rc::new(ProofNode::Step(step.clone())));
+
+    let (is_or_term, literals) = match step.clause.as_slice() {
+        [t] => match match_term!((or ...) = t) {
+            Some(args) => (true, args),
+            None => return unchanged(),
+        },
+        clause => (false, clause),
+    };
+    let [eq, first, second] = literals else {
+        return unchanged();
+    };
+    let (Some((t1, t2)), Some((u1, u2)), Some((v2, v1))) = (
+        match_term!((= t1 t2) = eq),
+        match_term!((not (<= u1 u2)) = first),
+        match_term!((not (<= v2 v1)) = second),
+    ) else {
+        return unchanged();
+    };
+
+    // Each `<=` term that is not written in terms of the sides of the equality must be replaced,
+    // using the ratio between it and the corresponding standard term
+    let mut replacements = Vec::new();
+    for (a, b, t, u) in [(u1, u2, t1, t2), (v2, v1, t2, t1)] {
+        if a == t && b == u {
+            continue;
+        }
+        let Some(ratio) = disequality_ratio((a, b), (t, u)) else {
+            return unchanged();
+        };
+        let standard = build_term!(pool, (<= {t.clone()} {u.clone()}));
+        let original = build_term!(pool, (<= {a.clone()} {b.clone()}));
+        replacements.push((standard, original, ratio));
+    }
+    if is_or_term && replacements.is_empty() {
+        return unchanged();
+    }
+
+    let mut ids = IdHelper::new(&step.id);
+    let new_step = |id, clause, rule: &str, premises, args| {
+        Rc::new(ProofNode::Step(StepNode {
+            id,
+            depth: step.depth,
+            clause,
+            rule: rule.to_owned(),
+            premises,
+            args,
+            ..Default::default()
+        }))
+    };
+
+    let standard_literals = {
+        let t1_le_t2 = build_term!(pool, (not (<= {t1.clone()} {t2.clone()})));
+        let t2_le_t1 = build_term!(pool, (not (<= {t2.clone()} {t1.clone()})));
+        vec![eq.clone(), t1_le_t2, t2_le_t1]
+    };
+    let standard_or = pool.add(Term::Op(Operator::Or, standard_literals.clone()));
+    let la_step = new_step(
+        ids.next_id(),
+        vec![standard_or],
+        "la_disequality",
+        Vec::new(),
+        Vec::new(),
+    );
+    let is_last = !is_or_term && replacements.is_empty();
+    let or_id = if is_last {
+        step.id.clone()
+    } else {
+        ids.next_id()
+    };
+    let mut current = new_step(or_id, standard_literals, "or", vec![la_step], Vec::new());
+    if is_last {
+        return Ok(current);
+    }
+
+    // Each `la_generic` step concludes `(cl (<= t u) (not (<= a b)))`. Since `(<= a b)` is `(<= t u)`
+    // multiplied by the ratio, the coefficients are the numerator and denominator of the ratio
+    let mut clause = current.clause().to_vec();
+    let mut premises = vec![current.clone()];
+    let mut pivots = Vec::new();
+    for (standard, original, ratio) in replacements {
+        let negated_standard = build_term!(pool, (not {standard.clone()}));
+        let negated_original = build_term!(pool, (not { original }));
+        let (numer, denom) = ratio.into_numer_denom();
+        let args = vec![
+            pool.add(Term::new_int(numer)),
+            pool.add(Term::new_int(denom)),
+        ];
+        premises.push(new_step(
+            ids.next_id(),
+            vec![standard.clone(), negated_original.clone()],
+            "la_generic",
+            Vec::new(),
+            args,
+        ));
+
+        clause.retain(|l| *l != negated_standard);
+        clause.push(negated_original);
+        pivots.push(standard);
+        pivots.push(pool.bool_false());
+    }
+    let is_last = !is_or_term && clause == step.clause;
+    current = new_step(
+        if is_last {
+            step.id.clone()
+        } else {
+            ids.next_id()
+        },
+        clause,
+        "resolution",
+        premises,
+        pivots,
+    );
+    if is_last {
+        return Ok(current);
+    }
+    if !is_or_term {
+        return Ok(new_step(
+            step.id.clone(),
+            step.clause.clone(),
+            "reordering",
+            vec![current],
+            Vec::new(),
+        ));
+    }
+
+    // If the conclusion is a single `or` term, we introduce it with `or_neg` steps, and resolve
+    // them against the clause
+    let or_term = step.clause[0].clone();
+    let mut premises = vec![current.clone()];
+    let mut pivots = Vec::new();
+    for literal in current.clause() {
+        let original_index = literals.iter().position(|l| l == literal).unwrap();
+        premises.push(new_step(
+            ids.next_id(),
+            vec![or_term.clone(), build_term!(pool, (not {literal.clone()}))],
+            "or_neg",
+            Vec::new(),
+            vec![pool.add(Term::new_int(original_index))],
+        ));
+        pivots.push(literal.clone());
+        pivots.push(pool.bool_true());
+    }
+    let resolved = new_step(
+        ids.next_id(),
+        vec![or_term.clone(); literals.len()],
+        "resolution",
+        premises,
+        pivots,
+    );
+    Ok(new_step(
+        step.id.clone(),
+        vec![or_term],
+        "contraction",
+        vec![resolved],
+        Vec::new(),
+    ))
+}
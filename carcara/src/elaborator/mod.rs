@@ -1,21 +1,30 @@
+mod all_simplify;
+mod dedup;
 mod hole;
+mod ids;
 mod lia_generic;
+mod linear_arithmetic;
+mod not_not;
 mod polyeq;
 mod reflexivity;
 mod reordering;
 mod resolution;
+mod sanity;
 mod transitivity;
 mod uncrowding;
+mod weakening;
 
-use crate::{ast::*, CheckerError};
+use crate::{ast::*, checker::StatisticsCollector, CarcaraResult, CheckerError, Error};
+pub use ids::id_map;
 use indexmap::IndexSet;
 use polyeq::PolyeqElaborator;
 use std::{
     collections::{HashMap, HashSet},
+    fmt,
     time::{Duration, Instant},
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct Config {
     /// If `Some`, enables the elaboration of `lia_generic` steps using an external solver. When
     /// checking a proof, this means calling the solver to solve the linear integer arithmetic
@@ -28,6 +37,37 @@ pub struct Config {
     pub uncrowd_rotation: bool,
 
     pub hole_options: Option<HoleOptions>,
+
+    /// If `Some`, limits how much each elaboration pass can grow a single step. See
+    /// [`GrowthLimit`] for more details.
+    pub growth_limit: Option<GrowthLimit>,
+
+    /// Limits on the size and depth of the terms in the steps introduced by each elaboration pass.
+    /// If a pass introduces a step whose conclusion exceeds them, the elaboration fails.
+    pub term_limits: TermLimits,
+
+    /// If `true`, steps that were replaced during elaboration by a new step with a different id
+    /// are given back their original id whenever possible. The ids of new steps are always derived
+    /// deterministically from the id of the original step. See [`id_map`] to map the ids in the
+    /// elaborated proof back to the original ones.
+    pub preserve_ids: bool,
+
+    /// If `true`, each step introduced during elaboration is annotated with the original step from
+    /// which it was derived and the pass that introduced it. These annotations are printed using
+    /// the `:origin` attribute.
+    pub annotate_origins: bool,
+
+    /// If `Some`, every step introduced by an elaboration pass is checked by evaluating it under
+    /// random interpretations of its free symbols, and the elaboration fails if some step is
+    /// falsified. This is slow, and is only meant for debugging the elaborator. See
+    /// [`SanityCheckOptions`] for more details.
+    pub sanity_check: Option<SanityCheckOptions>,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -38,6 +78,26 @@ pub enum ElaborationStep {
     Uncrowd,
     Reordering,
     Hole,
+    Dedup,
+    MergeConclusions,
+    NotNot,
+}
+
+impl ElaborationStep {
+    /// Returns the name of the elaboration pass, as used in `:origin` annotations.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ElaborationStep::Polyeq => "polyeq",
+            ElaborationStep::LiaGeneric => "lia-generic",
+            ElaborationStep::Local => "local",
+            ElaborationStep::Uncrowd => "uncrowd",
+            ElaborationStep::Reordering => "reordering",
+            ElaborationStep::Hole => "hole",
+            ElaborationStep::Dedup => "dedup",
+            ElaborationStep::MergeConclusions => "merge-conclusions",
+            ElaborationStep::NotNot => "not-not",
+        }
+    }
 }
 
 /// The options that control how `lia_generic` steps are elaborated using an external solver.
@@ -49,6 +109,94 @@ pub struct LiaGenericOptions {
 
     /// The arguments to pass to the solver.
     pub arguments: Vec<Box<str>>,
+
+    /// The wall-clock time limit for each solver run. When it is exceeded, the solver and any
+    /// processes it started are killed. If this is `None`, the solver may run indefinitely.
+    pub timeout: Option<Duration>,
+
+    /// How many times to retry running the solver if it times out or fails. If `seed_option` is
+    /// set, each retry gives the solver a different random seed.
+    pub retries: usize,
+
+    /// The command-line option used to give the solver a different random seed on each retry. The
+    /// seed is passed as `<option>=<seed>`, so for cvc5 this should be `--seed`. If this is `None`,
+    /// retries run the solver with the same arguments.
+    pub seed_option: Option<Box<str>>,
+
+    /// How deeply the proofs returned by the solver are validated. The solver proof is always
+    /// checked, but it may itself contain `lia_generic` steps. These are validated recursively, by
+    /// calling the solver again and checking its proof, up to this many levels of nesting.
+    pub max_depth: usize,
+
+    /// If `true`, the solver proofs are checked strictly: unknown rules are not ignored, and a
+    /// solver proof that still has `lia_generic` steps past the depth limit, or that has any other
+    /// holes, is rejected.
+    pub strict: bool,
+}
+
+/// Statistics about the external solver calls made while elaborating `lia_generic` steps.
+#[derive(Debug, Clone, Default)]
+pub struct LiaGenericStatistics {
+    /// The number of times the solver was run, including retries and nested calls.
+    pub solver_runs: usize,
+
+    /// The number of solver proofs that were parsed and checked, including nested ones.
+    pub checked_proofs: usize,
+
+    /// The deepest level of nesting at which a solver proof was checked. Proofs returned for the
+    /// steps in the original proof are at level zero.
+    pub max_depth: usize,
+
+    /// The total time spent running the solver.
+    pub solver_time: Duration,
+
+    /// The total time spent parsing and checking the solver proofs.
+    pub checking_time: Duration,
+}
+
+impl fmt::Display for LiaGenericStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "lia_generic solver runs:   {}", self.solver_runs)?;
+        writeln!(f, "    solver time:          {:?}", self.solver_time)?;
+        writeln!(f, "checked solver proofs:     {}", self.checked_proofs)?;
+        writeln!(f, "    checking time:        {:?}", self.checking_time)?;
+        writeln!(f, "    max. nesting depth:   {}", self.max_depth)
+    }
+}
+
+/// A limit on the number of steps that an elaboration pass can introduce when elaborating a single
+/// step. Steps that are already in the proof, like the premises of the elaborated step, are not
+/// counted.
+#[derive(Debug, Clone, Copy)]
+pub struct GrowthLimit {
+    /// The maximum number of steps that a single step can be elaborated into.
+    pub max_steps: usize,
+
+    /// What to do when the elaboration of a step exceeds the limit.
+    pub policy: GrowthPolicy,
+}
+
+/// What to do when the elaboration of a step exceeds the [`GrowthLimit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrowthPolicy {
+    /// Abort the elaboration with an error.
+    Fail,
+
+    /// Keep the step unelaborated by the offending pass. Later passes may still elaborate it.
+    Skip,
+
+    /// Like `Skip`, but also log a warning.
+    Warn,
+}
+
+/// The options that control the sanity check of the steps introduced during elaboration.
+#[derive(Debug, Clone, Copy)]
+pub struct SanityCheckOptions {
+    /// How many random interpretations are tried for each new step.
+    pub samples: usize,
+
+    /// The seed used to generate the interpretations.
+    pub seed: u64,
 }
 
 /// The options that control how `hole` steps are elaborated using an external solver.
@@ -66,16 +214,35 @@ pub struct Elaborator<'e> {
     pool: &'e mut PrimitivePool,
     problem: &'e Problem,
     config: Config,
+    lia_stats: LiaGenericStatistics,
 }
 
 impl<'e> Elaborator<'e> {
     pub fn new(pool: &'e mut PrimitivePool, problem: &'e Problem, config: Config) -> Self {
-        Self { pool, problem, config }
+        Self {
+            pool,
+            problem,
+            config,
+            lia_stats: LiaGenericStatistics::default(),
+        }
     }
 
-    pub fn elaborate_with_default_pipeline(&mut self, root: &Rc<ProofNode>) -> Rc<ProofNode> {
-        use ElaborationStep::*;
-        let pipeline = vec![Polyeq, LiaGeneric, Local, Uncrowd, Reordering];
+    /// Returns the statistics about the solver calls made so far to elaborate `lia_generic` steps.
+    pub fn lia_generic_stats(&self) -> &LiaGenericStatistics {
+        &self.lia_stats
+    }
+
+    pub fn elaborate_with_default_pipeline(
+        &mut self,
+        root: &Rc<ProofNode>,
+    ) -> CarcaraResult<Rc<ProofNode>> {
+        let pipeline = vec![
+            ElaborationStep::Polyeq,
+            ElaborationStep::LiaGeneric,
+            ElaborationStep::Local,
+            ElaborationStep::Uncrowd,
+            ElaborationStep::Reordering,
+        ];
         self.elaborate(root, pipeline)
     }
 
@@ -83,63 +250,139 @@ impl<'e> Elaborator<'e> {
         &mut self,
         root: &Rc<ProofNode>,
         pipeline: Vec<ElaborationStep>,
-    ) -> Rc<ProofNode> {
-        self.elaborate_with_stats(root, pipeline).0
+    ) -> CarcaraResult<Rc<ProofNode>> {
+        Ok(self.elaborate_with_stats(root, pipeline)?.0)
     }
 
     pub fn elaborate_with_stats(
         &mut self,
         root: &Rc<ProofNode>,
         pipeline: Vec<ElaborationStep>,
-    ) -> (Rc<ProofNode>, Vec<Duration>) {
+    ) -> CarcaraResult<(Rc<ProofNode>, Vec<Duration>)> {
+        let result = self.elaborate_impl(root, pipeline, None);
+        // Like the checker, we don't let the shared deep-equality cache outlive the elaboration
+        clear_shared_cache();
+        result
+    }
+
+    /// Elaborates the proof, reporting the time taken by each pass, as well as the growth of the
+    /// term pool, to the given statistics collector.
+    pub fn elaborate_with_collector(
+        &mut self,
+        root: &Rc<ProofNode>,
+        pipeline: Vec<ElaborationStep>,
+        collector: &mut dyn StatisticsCollector,
+    ) -> CarcaraResult<Rc<ProofNode>> {
+        let result = self.elaborate_impl(root, pipeline, Some(collector));
+        clear_shared_cache();
+        Ok(result?.0)
+    }
+
+    fn elaborate_impl(
+        &mut self,
+        root: &Rc<ProofNode>,
+        pipeline: Vec<ElaborationStep>,
+        mut collector: Option<&mut dyn StatisticsCollector>,
+    ) -> CarcaraResult<(Rc<ProofNode>, Vec<Duration>)> {
         let mut durations = Vec::new();
         let mut current = root.clone();
+        let limit = (self.config.growth_limit, self.config.term_limits);
         for step in pipeline {
             let time = Instant::now();
+            let pool_size = self.pool.num_terms();
+            let before = current.clone();
             current = match step {
-                ElaborationStep::Polyeq => self.elaborate_polyeq(&current),
+                ElaborationStep::Polyeq => self.elaborate_polyeq(&current, limit)?,
                 ElaborationStep::LiaGeneric if self.config.lia_options.is_some() => {
-                    mutate(&current, |_, node| match node.as_ref() {
+                    mutate_limited(&current, step, limit, |_, node| match node.as_ref() {
                         ProofNode::Step(s) if s.rule == "lia_generic" => {
                             lia_generic::lia_generic(self, s).unwrap_or_else(|| node.clone())
                         }
                         _ => node.clone(),
-                    })
+                    })?
                 }
                 ElaborationStep::LiaGeneric => current.clone(),
-                ElaborationStep::Local => self.elaborate_local(&current),
-                ElaborationStep::Uncrowd => mutate(&current, |_, node| match node.as_ref() {
-                    ProofNode::Step(s)
-                        if (s.rule == "resolution" || s.rule == "th_resolution")
-                            && !s.args.is_empty() =>
-                    {
-                        uncrowding::uncrowd_resolution(self.pool, s, self.config.uncrowd_rotation)
-                    }
-                    _ => node.clone(),
-                }),
+                ElaborationStep::Local => self.elaborate_local(&current, limit)?,
+                ElaborationStep::Uncrowd => {
+                    mutate_limited(&current, step, limit, |_, node| match node.as_ref() {
+                        ProofNode::Step(s)
+                            if (s.rule == "resolution" || s.rule == "th_resolution")
+                                && !s.args.is_empty() =>
+                        {
+                            uncrowding::uncrowd_resolution(
+                                self.pool,
+                                s,
+                                self.config.uncrowd_rotation,
+                            )
+                        }
+                        _ => node.clone(),
+                    })?
+                }
                 ElaborationStep::Reordering => reordering::remove_reorderings(&current),
+                ElaborationStep::Dedup => {
+                    let (result, removed) = dedup::remove_duplicate_steps(&current);
+                    log::info!("removed {} duplicate steps", removed);
+                    result
+                }
+                ElaborationStep::MergeConclusions => {
+                    let (result, replaced) = dedup::merge_equal_conclusions(&current);
+                    log::info!("replaced {} steps with equal conclusions", replaced);
+                    result
+                }
+                ElaborationStep::NotNot => {
+                    let before = not_not::count_steps(&current);
+                    let (result, chains) = not_not::remove_not_not_chains(&current);
+                    let removed = before - not_not::count_steps(&result);
+                    log::info!(
+                        "removed {} double negation chains, with {} steps in total",
+                        chains,
+                        removed
+                    );
+                    result
+                }
                 ElaborationStep::Hole => {
                     if self.config.hole_options.is_none() {
                         current.clone()
                     } else {
-                        mutate(&current, |_, node| match node.as_ref() {
+                        mutate_limited(&current, step, limit, |_, node| match node.as_ref() {
                             ProofNode::Step(s)
                                 if (s.rule == "all_simplify" || s.rule == "rare_rewrite") =>
                             {
                                 hole::hole(self, s).unwrap_or_else(|| node.clone())
                             }
                             _ => node.clone(),
-                        })
+                        })?
                     }
                 }
             };
-            durations.push(time.elapsed());
+            let elapsed = time.elapsed();
+            if let Some(c) = &mut collector {
+                c.elaboration_pass(step.name(), elapsed);
+                let new_size = self.pool.num_terms();
+                if new_size > pool_size {
+                    c.pool_grew(new_size);
+                }
+            }
+            durations.push(elapsed);
+            if let Some(options) = &self.config.sanity_check {
+                sanity::check_new_steps(self.pool, &before, &current, step.name(), options)?;
+            }
+            if self.config.annotate_origins {
+                current = ids::annotate_origins(&before, &current, step.name());
+            }
+        }
+        if self.config.preserve_ids {
+            current = ids::restore_original_ids(root, &current);
         }
-        (current, durations)
+        Ok((current, durations))
     }
 
-    fn elaborate_polyeq(&mut self, root: &Rc<ProofNode>) -> Rc<ProofNode> {
-        mutate(root, |context, node| {
+    fn elaborate_polyeq(
+        &mut self,
+        root: &Rc<ProofNode>,
+        limit: (Option<GrowthLimit>, TermLimits),
+    ) -> CarcaraResult<Rc<ProofNode>> {
+        mutate_limited(root, ElaborationStep::Polyeq, limit, |context, node| {
             match node.as_ref() {
                 ProofNode::Assume { id, depth, term }
                     if context.is_empty() && !self.problem.premises.contains(term) =>
@@ -154,27 +397,48 @@ impl<'e> Elaborator<'e> {
         })
     }
 
-    fn elaborate_local(&mut self, root: &Rc<ProofNode>) -> Rc<ProofNode> {
+    fn elaborate_local(
+        &mut self,
+        root: &Rc<ProofNode>,
+        limit: (Option<GrowthLimit>, TermLimits),
+    ) -> CarcaraResult<Rc<ProofNode>> {
         fn get_elaboration_function(rule: &str) -> Option<ElaborationFunc> {
             Some(match rule {
                 "eq_transitive" => transitivity::eq_transitive,
-                "trans" => transitivity::trans,
                 "resolution" | "th_resolution" => resolution::resolution,
+                "all_simplify" => all_simplify::all_simplify,
+                "weakening" => weakening::weakening,
+                "la_disequality" => linear_arithmetic::la_disequality,
                 _ => return None,
             })
         }
 
-        mutate(root, |context, node| {
-            match node.as_ref() {
-                ProofNode::Step(s) => {
-                    if let Some(func) = get_elaboration_function(&s.rule) {
-                        return func(self.pool, context, s).unwrap(); // TODO: add proper error handling
-                    }
+        // The equalities concluded by the steps in the root proof that were already visited. Since
+        // the proof is traversed in postorder, these steps can't depend on the current step, so
+        // they can be safely reused when elaborating `trans` steps
+        let mut known_equalities: HashMap<Rc<Term>, Rc<ProofNode>> = HashMap::new();
+
+        mutate_limited(root, ElaborationStep::Local, limit, |context, node| {
+            let result = match node.as_ref() {
+                ProofNode::Step(s) if s.rule == "trans" => {
+                    transitivity::trans(self.pool, s, &mut known_equalities).unwrap()
+                    // TODO: add proper error handling
                 }
+                ProofNode::Step(s) => match get_elaboration_function(&s.rule) {
+                    Some(func) => func(self.pool, context, s).unwrap(), // TODO: add proper error handling
+                    None => node.clone(),
+                },
                 ProofNode::Subproof(_) => unreachable!(),
-                ProofNode::Assume { .. } => (),
+                ProofNode::Assume { .. } => node.clone(),
+            };
+            if let [conclusion] = result.clause() {
+                if result.depth() == 0 && match_term!((= a b) = conclusion).is_some() {
+                    known_equalities
+                        .entry(conclusion.clone())
+                        .or_insert_with(|| result.clone());
+                }
             }
-            node.clone()
+            result
         })
     }
 
@@ -239,18 +503,151 @@ pub fn add_refl_step(
     Rc::new(ProofNode::Step(StepNode {
         id,
         depth,
-        clause: vec![build_term!(pool, (= {a} {b}))],
+        clause: vec![pool.mk_eq(a, b)],
         rule: "refl".to_owned(),
         premises: Vec::new(),
         args: Vec::new(),
+        arg_vars: Vec::new(),
         discharge: Vec::new(),
         previous_step: None,
+        origin: None,
+        attributes: Vec::new(),
     }))
 }
 
 type ElaborationFunc =
     fn(&mut PrimitivePool, &mut ContextStack, &StepNode) -> Result<Rc<ProofNode>, CheckerError>;
 
+/// Like `mutate`, but checks that each mutated node respects the growth limit, if one is given.
+/// Nodes that don't are handled according to the limit's policy. The steps introduced by the
+/// mutation must also respect the term limits, otherwise the elaboration fails.
+fn mutate_limited<F>(
+    root: &Rc<ProofNode>,
+    pass: ElaborationStep,
+    (limit, term_limits): (Option<GrowthLimit>, TermLimits),
+    mut mutate_func: F,
+) -> CarcaraResult<Rc<ProofNode>>
+where
+    F: FnMut(&mut ContextStack, &Rc<ProofNode>) -> Rc<ProofNode>,
+{
+    if limit.is_none() && term_limits.is_unlimited() {
+        return Ok(mutate(root, mutate_func));
+    }
+    let mut error = None;
+    let result = mutate(root, |context, node| {
+        let mutated = mutate_func(context, node);
+        if error.is_some() || mutated == *node {
+            return mutated;
+        }
+        if let Some(e) = check_added_terms(node, &mutated, term_limits) {
+            error = Some(e);
+            return node.clone();
+        }
+        let Some(limit) = limit else {
+            return mutated;
+        };
+        let added = count_added_steps(node, &mutated);
+        if added <= limit.max_steps {
+            return mutated;
+        }
+        let (id, rule) = match node.as_ref() {
+            ProofNode::Step(s) => (s.id.as_str(), s.rule.as_str()),
+            _ => (node.id(), "assume"),
+        };
+        match limit.policy {
+            GrowthPolicy::Fail => {
+                error = Some(Error::ElaborationGrowth {
+                    step: id.to_owned(),
+                    pass: format!("{:?}", pass),
+                    steps: added,
+                    limit: limit.max_steps,
+                });
+            }
+            GrowthPolicy::Skip => log::info!(
+                "skipping elaboration of step '{}' ({}) in pass {:?}: {} steps added",
+                id,
+                rule,
+                pass,
+                added
+            ),
+            GrowthPolicy::Warn => log::warn!(
+                "skipping elaboration of step '{}' ({}) in pass {:?}: {} steps added, but the \
+                limit is {}",
+                id,
+                rule,
+                pass,
+                added,
+                limit.max_steps
+            ),
+        }
+        node.clone()
+    });
+    error.map_or(Ok(result), Err)
+}
+
+/// Counts the steps introduced when `original` was mutated into `mutated`.
+fn count_added_steps(original: &Rc<ProofNode>, mutated: &Rc<ProofNode>) -> usize {
+    added_steps(original, mutated).len()
+}
+
+/// Checks that the conclusions of the steps introduced when `original` was mutated into `mutated`
+/// respect the term limits, returning an error for the first step that doesn't.
+fn check_added_terms(
+    original: &Rc<ProofNode>,
+    mutated: &Rc<ProofNode>,
+    limits: TermLimits,
+) -> Option<Error> {
+    if limits.is_unlimited() {
+        return None;
+    }
+    added_steps(original, mutated).into_iter().find_map(|s| {
+        let inner = s.clause.iter().find_map(|t| limits.check(t).err())?;
+        Some(Error::Checker {
+            inner: inner.into(),
+            rule: s.rule.clone(),
+            step: s.id.clone(),
+        })
+    })
+}
+
+/// Returns the steps introduced when `original` was mutated into `mutated`. The premises of
+/// `original` already exist in the proof, so they and their own premises are not included.
+fn added_steps<'a>(original: &'a Rc<ProofNode>, mutated: &'a Rc<ProofNode>) -> Vec<&'a StepNode> {
+    let mut seen: HashSet<&Rc<ProofNode>> = HashSet::new();
+    if let ProofNode::Step(s) = original.as_ref() {
+        seen.extend(
+            s.premises
+                .iter()
+                .chain(&s.discharge)
+                .chain(&s.previous_step),
+        );
+    }
+    let mut result = Vec::new();
+    let mut todo = vec![mutated];
+    while let Some(node) = todo.pop() {
+        if !seen.insert(node) {
+            continue;
+        }
+        match node.as_ref() {
+            ProofNode::Assume { .. } => (),
+            ProofNode::Step(s) => {
+                result.push(s);
+                todo.extend(
+                    s.premises
+                        .iter()
+                        .chain(&s.discharge)
+                        .chain(&s.previous_step),
+                );
+            }
+            ProofNode::Subproof(s) => {
+                todo.push(&s.last_step);
+                todo.extend(&s.outbound_premises);
+            }
+        }
+    }
+    result
+}
+
 fn mutate<F>(root: &Rc<ProofNode>, mut mutate_func: F) -> Rc<ProofNode>
 where
     F: FnMut(&mut ContextStack, &Rc<ProofNode>) -> Rc<ProofNode>,
@@ -370,3 +767,484 @@ impl IdHelper {
         self.stack.pop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{checker, parser};
+
+    /// Parses the problem and proof, and elaborates the proof with the given configuration and
+    /// pipeline. Returns the parsed problem, the elaborated commands, and the term pool.
+    fn elaborate_instance(
+        problem: &[u8],
+        proof: &[u8],
+        config: Config,
+        pipeline: Vec<ElaborationStep>,
+    ) -> (Problem, Vec<ProofCommand>, PrimitivePool) {
+        let (problem, proof, mut pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let node = ProofNode::from_commands(proof.commands);
+        let elaborated = Elaborator::new(&mut pool, &problem, config)
+            .elaborate(&node, pipeline)
+            .unwrap()
+            .into_commands();
+        (problem, elaborated, pool)
+    }
+
+    /// Checks an elaborated proof with the given checker configuration.
+    fn check_elaborated(
+        pool: &mut PrimitivePool,
+        problem: &Problem,
+        commands: Vec<ProofCommand>,
+        config: checker::Config,
+    ) -> CarcaraResult<bool> {
+        let proof = Proof {
+            constant_definitions: Vec::new(),
+            commands,
+        };
+        checker::ProofChecker::new(pool, config).check(problem, &proof)
+    }
+
+    /// Returns the id and rule of every step in `commands`.
+    fn step_rules(commands: &[ProofCommand]) -> Vec<(&str, &str)> {
+        commands
+            .iter()
+            .filter_map(|c| match c {
+                ProofCommand::Step(s) => Some((s.id.as_str(), s.rule.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_growth_limit() {
+        let problem: &[u8] = b"
+            (declare-sort U 0)
+            (declare-const a U)
+            (declare-const b U)
+            (declare-const c U)
+            (assert (= b a))
+            (assert (= b c))
+            (assert (not (= a c)))
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (= b a))
+            (assume h2 (= b c))
+            (assume h3 (not (= a c)))
+            (step t4 (cl (= a c)) :rule trans :premises (h1 h2))
+            (step t5 (cl) :rule resolution :premises (t4 h3))
+        ";
+        let (problem, proof, mut pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let node = ProofNode::from_commands(proof.commands);
+
+        let mut elaborate_with = |max_steps, policy, term_limits| {
+            let config = Config {
+                growth_limit: Some(GrowthLimit { max_steps, policy }),
+                term_limits,
+                ..Config::new()
+            };
+            Elaborator::new(&mut pool, &problem, config)
+                .elaborate(&node, vec![ElaborationStep::Local])
+                .map(|n| n.into_commands())
+        };
+        let mut elaborate =
+            |max_steps, policy| elaborate_with(max_steps, policy, TermLimits::new());
+        let has_symm = |commands: &[ProofCommand]| {
+            commands
+                .iter()
+                .any(|c| matches!(c, ProofCommand::Step(s) if s.rule == "symm"))
+        };
+
+        // Elaborating the `trans` step introduces a `symm` step, so it grows into two steps
+        let elaborated = elaborate(2, GrowthPolicy::Fail).unwrap();
+        assert!(has_symm(&elaborated));
+
+        let skipped = elaborate(1, GrowthPolicy::Skip).unwrap();
+        assert!(!has_symm(&skipped));
+
+        let failed = elaborate(1, GrowthPolicy::Fail);
+        assert!(matches!(
+            failed,
+            Err(Error::ElaborationGrowth { step, steps: 2, .. }) if step == "t4",
+        ));
+
+        // The introduced steps conclude equalities between constants, which have depth 1
+        let limits = TermLimits::new().max_depth(Some(1));
+        assert!(elaborate_with(2, GrowthPolicy::Fail, limits).is_ok());
+        let limits = TermLimits::new().max_depth(Some(0));
+        assert!(matches!(
+            elaborate_with(2, GrowthPolicy::Fail, limits),
+            Err(Error::Checker {
+                inner: CheckerError::TermLimit(_),
+                ..
+            }),
+        ));
+    }
+
+    #[test]
+    fn test_strict_resolution_elaboration() {
+        let problem: &[u8] = b"
+            (declare-const a Bool)
+            (declare-const b Bool)
+            (declare-const c Bool)
+        ";
+        let proof: &[u8] = b"
+            (step t1 (cl a b c) :rule hole)
+            (step t2 (cl (not a) c b) :rule hole)
+            (step t3 (cl (not b) c) :rule hole)
+            (step t4 (cl c b) :rule resolution :premises (t1 t2))
+            (step t5 (cl c) :rule resolution :premises (t4 t3))
+            (step t6 (cl (not c)) :rule hole)
+            (step t7 (cl) :rule resolution :premises (t5 t6))
+        ";
+        let checker_config = checker::Config::new()
+            .strict_resolution(true)
+            .ignore_unknown_rules(true);
+
+        // The resolution steps implicitly reorder and contract their conclusions, so they are not
+        // valid under the strict semantics
+        let (parsed_problem, parsed_proof, mut pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let result = checker::ProofChecker::new(&mut pool, checker_config.clone())
+            .check(&parsed_problem, &parsed_proof);
+        assert!(result.is_err());
+
+        let pipeline = vec![
+            ElaborationStep::Polyeq,
+            ElaborationStep::LiaGeneric,
+            ElaborationStep::Local,
+            ElaborationStep::Uncrowd,
+            ElaborationStep::Reordering,
+        ];
+        let (problem, elaborated, mut pool) =
+            elaborate_instance(problem, proof, Config::new(), pipeline);
+        let result = check_elaborated(&mut pool, &problem, elaborated, checker_config);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_trans_reuses_flipped_equalities() {
+        let problem: &[u8] = b"
+            (declare-sort U 0)
+            (declare-const a U)
+            (declare-const b U)
+            (declare-const c U)
+            (declare-const d U)
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (= b a))
+            (assume h2 (= b c))
+            (assume h3 (= c d))
+            (step t4 (cl (= d c)) :rule symm :premises (h3))
+            (step t5 (cl (= a c)) :rule trans :premises (h1 h2))
+            (step t6 (cl (= a d)) :rule trans :premises (h1 h2 h3))
+            (step t7 (cl (= d b)) :rule trans :premises (h3 h2))
+            (step t8 (cl) :rule hole :premises (t4 t5 t6 t7))
+        ";
+        let (_, elaborated, _) =
+            elaborate_instance(problem, proof, Config::new(), vec![ElaborationStep::Local]);
+
+        // `t5` and `t6` both need `(= a b)`, which should only be derived once. `t7` needs
+        // `(= d c)`, which is already concluded by `t4`, and `(= c b)`
+        let symm_steps: Vec<_> = step_rules(&elaborated)
+            .into_iter()
+            .filter_map(|(id, rule)| (rule == "symm").then_some(id))
+            .collect();
+        assert_eq!(symm_steps.len(), 3);
+        assert!(symm_steps.contains(&"t4"));
+    }
+
+    #[test]
+    fn test_refl_elaboration_with_context_on_both_sides() {
+        // The context must be applied to both sides of the `refl` step, and the results are only
+        // equal modulo renaming of bound variables
+        let problem: &[u8] = b"";
+        let proof: &[u8] = b"
+            (anchor :step t1 :args ((y Int) (:= (x Int) y) (:= (u Int) y)))
+            (step t1.t1 (cl (= (forall ((z Int)) (> z x)) (forall ((w Int)) (> w u)))) :rule refl)
+            (step t1 (cl (= (forall ((z Int)) (> z x)) (forall ((w Int)) (> w u)))) :rule hole)
+            (step t2 (cl) :rule hole :premises (t1))
+        ";
+        let (problem, elaborated, mut pool) =
+            elaborate_instance(problem, proof, Config::new(), vec![ElaborationStep::Polyeq]);
+        let checker_config = checker::Config::new().elaborated(true);
+        let result = check_elaborated(&mut pool, &problem, elaborated, checker_config);
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn test_all_simplify_elaboration() {
+        let problem: &[u8] = b"
+            (declare-const p Bool)
+            (declare-const q Bool)
+            (declare-const r Bool)
+        ";
+        let proof: &[u8] = b"
+            (step t1 (cl (= (and (not (not p)) (ite true q r)) (and p q))) :rule all_simplify)
+            (step t2 (cl (= (+ 1 2) 3)) :rule all_simplify)
+            (step t3 (cl) :rule hole :premises (t1 t2))
+        ";
+        let (problem, elaborated, mut pool) =
+            elaborate_instance(problem, proof, Config::new(), vec![ElaborationStep::Local]);
+
+        let rules = step_rules(&elaborated);
+        assert!(rules.contains(&("t1", "trans")));
+        assert!(rules.contains(&("t2", "all_simplify")));
+        assert!(rules.iter().any(|&(_, rule)| rule == "not_simplify"));
+        assert!(rules.iter().any(|&(_, rule)| rule == "ite_simplify"));
+        assert!(rules.iter().any(|&(_, rule)| rule == "cong"));
+
+        let checker_config = checker::Config::new().elaborated(true);
+        let result = check_elaborated(&mut pool, &problem, elaborated, checker_config);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_weakening_elaboration() {
+        let problem: &[u8] = b"
+            (declare-const a Bool)
+            (declare-const b Bool)
+            (declare-const c Bool)
+        ";
+        let proof: &[u8] = b"
+            (step t1 (cl a b) :rule hole)
+            (step t2 (cl a b c) :rule weakening :premises (t1))
+            (step t3 (cl c b a) :rule weakening :premises (t1))
+            (step t4 (cl) :rule hole :premises (t2 t3))
+        ";
+        let (problem, elaborated, mut pool) =
+            elaborate_instance(problem, proof, Config::new(), vec![ElaborationStep::Local]);
+
+        let rules = step_rules(&elaborated);
+        assert!(rules.contains(&("t2", "weakening")));
+        assert!(rules.contains(&("t3", "reordering")));
+
+        let checker_config = checker::Config::new().elaborated(true);
+        let result = check_elaborated(&mut pool, &problem, elaborated, checker_config);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_la_disequality_elaboration() {
+        let problem: &[u8] = b"
+            (declare-const a Int)
+            (declare-const b Int)
+            (declare-const x Real)
+        ";
+        let proof: &[u8] = b"
+            (step t1 (cl (or (= a b) (not (<= a b)) (not (<= b a)))) :rule la_disequality)
+            (step t2 (cl (= a b) (not (<= a b)) (not (<= b a))) :rule la_disequality)
+            (step t3 (cl (= a (+ b 1)) (not (<= (- a b) 1)) (not (<= (+ b 1) a)))
+                :rule la_disequality)
+            (step t4 (cl (= a b) (not (<= a b)) (not (<= (* 2 b) (* 2 a))))
+                :rule la_disequality)
+            (step t5 (cl (or (= x 1.0) (not (<= (* 2.0 x) 2.0)) (not (<= 0.5 (* 0.5 x)))))
+                :rule la_disequality)
+            (step t6 (cl) :rule hole :premises (t1 t2 t3 t4 t5))
+        ";
+        let (problem, elaborated, mut pool) =
+            elaborate_instance(problem, proof, Config::new(), vec![ElaborationStep::Local]);
+
+        let rules = step_rules(&elaborated);
+        assert!(rules.contains(&("t1", "la_disequality")));
+        assert!(rules.contains(&("t2", "or")));
+        assert!(rules.contains(&("t3", "reordering")));
+        assert!(rules.contains(&("t4", "resolution")));
+        assert!(rules.contains(&("t5", "contraction")));
+        assert!(rules.contains(&("t3.t3", "la_generic")));
+
+        let checker_config = checker::Config::new()
+            .elaborated(true)
+            .strict_resolution(true);
+        check_elaborated(&mut pool, &problem, elaborated, checker_config).unwrap();
+    }
+
+    #[test]
+    fn test_build_equality_chain() {
+        use transitivity::build_equality_chain;
+
+        let mut pool = PrimitivePool::new();
+        let [a, b, c, d, e] = parser::tests::parse_terms(
+            &mut pool,
+            "(declare-sort U 0)
+            (declare-const a U) (declare-const b U) (declare-const c U)
+            (declare-const d U) (declare-const e U)",
+            ["a", "b", "c", "d", "e"],
+        );
+        let links: Vec<_> = [(&a, &b), (&c, &e), (&c, &b), (&a, &e), (&c, &d)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (x, y))| {
+                Rc::new(ProofNode::Step(StepNode {
+                    id: format!("h{i}"),
+                    clause: vec![pool.mk_eq(x.clone(), y.clone())],
+                    rule: "hole".to_owned(),
+                    ..Default::default()
+                }))
+            })
+            .collect();
+        let mut ids = IdHelper::new("t1");
+        let mut chain = |from, to| {
+            let node = build_equality_chain(&mut pool, &mut ids, 0, &links, from, to)?;
+            let step = node.as_step().unwrap();
+            let premises: Vec<_> = step
+                .premises
+                .iter()
+                .map(|p| p.as_step().unwrap().rule.clone())
+                .collect();
+            Some((step.rule.clone(), premises))
+        };
+
+        // There are two shortest chains from `a` to `d`, going either through `b` and `c`, or
+        // through `e` and `c`. The first uses `(= c b)` backwards
+        assert_eq!(
+            chain(&a, &d),
+            Some((
+                "trans".to_owned(),
+                vec!["hole".into(), "symm".into(), "hole".into()]
+            ))
+        );
+        assert_eq!(
+            chain(&b, &a),
+            Some(("symm".to_owned(), vec!["hole".into()]))
+        );
+        assert_eq!(chain(&a, &b), Some(("hole".to_owned(), vec![])));
+        assert_eq!(chain(&a, &a), Some(("refl".to_owned(), vec![])));
+
+        let [f] =
+            parser::tests::parse_terms(&mut pool, "(declare-sort U 0) (declare-const f U)", ["f"]);
+        let mut ids = IdHelper::new("t2");
+        assert!(build_equality_chain(&mut pool, &mut ids, 0, &links, &a, &f).is_none());
+    }
+
+    #[test]
+    fn test_preserve_ids() {
+        let problem: &[u8] = b"
+            (declare-const a Bool)
+            (declare-const b Bool)
+            (declare-const c Bool)
+        ";
+        let proof: &[u8] = b"
+            (step t1 (cl a b c) :rule hole)
+            (step t2 (cl (not a) c b) :rule hole)
+            (step t3 (cl (not b) c) :rule hole)
+            (step t4 (cl c b) :rule resolution :premises (t1 t2))
+            (step t5 (cl c) :rule resolution :premises (t4 t3))
+        ";
+        let (problem, proof, mut pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let node = ProofNode::from_commands(proof.commands);
+
+        let mut elaborate = |preserve_ids| {
+            let config = Config { preserve_ids, ..Config::new() };
+            Elaborator::new(&mut pool, &problem, config)
+                .elaborate_with_default_pipeline(&node)
+                .unwrap()
+        };
+        let has_id =
+            |node: &Rc<ProofNode>, id: &str| node.into_commands().iter().any(|c| c.id() == id);
+
+        // When the `reordering` step that concludes `t4` is removed, the step that replaces it has
+        // a new id
+        let elaborated = elaborate(false);
+        assert!(!has_id(&elaborated, "t4"));
+
+        let elaborated = elaborate(true);
+        assert!(has_id(&elaborated, "t4"));
+        let map = id_map(&node, &elaborated);
+        assert_eq!(map["t4"], "t4");
+        assert_eq!(map["t4.t1"], "t4");
+        assert_eq!(map["t5"], "t5");
+    }
+
+    #[test]
+    fn test_annotate_origins() {
+        let problem: &[u8] = b"
+            (declare-sort U 0)
+            (declare-const a U)
+            (declare-const b U)
+            (declare-const c U)
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (= b a))
+            (assume h2 (= b c))
+            (step t3 (cl (= a c)) :rule trans :premises (h1 h2))
+        ";
+        let config = Config {
+            annotate_origins: true,
+            ..Config::new()
+        };
+        let (_, elaborated, _) =
+            elaborate_instance(problem, proof, config, vec![ElaborationStep::Local]);
+
+        let origins: Vec<_> = elaborated
+            .iter()
+            .filter_map(|c| match c {
+                ProofCommand::Step(s) => Some((s.id.as_str(), s.origin.clone())),
+                _ => None,
+            })
+            .collect();
+        let expected = StepOrigin {
+            step: "t3".to_owned(),
+            pass: "local".to_owned(),
+        };
+        assert_eq!(origins, [("t3.t1", Some(expected)), ("t3", None)]);
+    }
+
+    #[test]
+    fn test_sanity_check() {
+        let problem: &[u8] = b"
+            (declare-sort U 0)
+            (declare-const a U)
+            (declare-const b U)
+            (declare-const c U)
+            (declare-const x Int)
+            (assert (= b a))
+            (assert (= b c))
+            (assert (= x 1))
+        ";
+        let parse = |proof: &[u8]| {
+            let (_, proof, pool) =
+                parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+            (ProofNode::from_commands(proof.commands), pool)
+        };
+        let options = SanityCheckOptions { samples: 100, seed: 0 };
+
+        let (before, _) = parse(
+            b"(assume h1 (= x 1))
+            (step t2 (cl (= x 1)) :rule hole :premises (h1))",
+        );
+        let (sound, _) = parse(
+            b"(assume h1 (= x 1))
+            (step t2.t1 (cl (= (+ x 1) 2)) :rule hole :premises (h1))
+            (step t2 (cl (= x 1)) :rule hole :premises (t2.t1))",
+        );
+        let (unsound, mut pool) = parse(
+            b"(assume h1 (= x 1))
+            (step t2.t1 (cl (= x 2)) :rule hole :premises (h1))
+            (step t2 (cl (= x 1)) :rule hole :premises (t2.t1))",
+        );
+        assert!(sanity::check_new_steps(&mut pool, &before, &sound, "local", &options).is_ok());
+        match sanity::check_new_steps(&mut pool, &before, &unsound, "local", &options) {
+            Err(crate::Error::ElaborationUnsound { step, origin, pass, assignment }) => {
+                assert_eq!(step, "t2.t1");
+                assert_eq!(origin, "t2");
+                assert_eq!(pass, "local");
+                assert_eq!(assignment, "x = 1");
+            }
+            other => panic!("expected sanity check to fail, got {:?}", other),
+        }
+
+        // Elaborating a sound proof with sanity checks enabled should succeed
+        let proof = b"(assume h1 (= b a))
+            (assume h2 (= b c))
+            (step t3 (cl (= a c)) :rule trans :premises (h1 h2))";
+        let config = Config {
+            sanity_check: Some(options),
+            ..Config::new()
+        };
+        let pipeline = vec![ElaborationStep::Local, ElaborationStep::Polyeq];
+        elaborate_instance(problem, proof, config, pipeline);
+    }
+}
@@ -0,0 +1,114 @@
+use super::*;
+
+/// Returns `true` if the node is a `not_not` step, that is, a step concluding a clause of the form
+/// `(cl (not (not (not phi))) phi)`.
+fn is_not_not_step(node: &ProofNode) -> bool {
+    matches!(node.as_step(), Some(s) if s.rule == "not_not" && s.premises.is_empty())
+}
+
+/// If `step` is a resolution step in which every premise but one is a `not_not` step, returns the
+/// remaining premise. Such a step only adds or removes double negations from the literals of that
+/// premise.
+fn double_negation_base(step: &StepNode) -> Option<&Rc<ProofNode>> {
+    if !matches!(
+        step.rule.as_str(),
+        "resolution" | "th_resolution" | "strict_resolution"
+    ) {
+        return None;
+    }
+    let mut others = step.premises.iter().filter(|p| !is_not_not_step(p));
+    let base = others.next()?;
+    (others.next().is_none() && step.premises.len() > 1).then_some(base)
+}
+
+/// Removes chains of resolution steps that only add and then remove double negations, using
+/// `not_not` steps. If such a chain concludes exactly the same clause as the step where it started,
+/// every reference to the last step of the chain is redirected to that step, and the `not_not`
+/// steps become unused. Returns the new proof and the number of chains that were removed.
+///
+/// Since the conclusion must be exactly the same, including the order of the literals, the steps
+/// that use the chain remain valid. Like in [`super::dedup::remove_duplicate_steps`], only steps in
+/// the root proof are considered.
+pub fn remove_not_not_chains(root: &Rc<ProofNode>) -> (Rc<ProofNode>, usize) {
+    let mut removed = 0;
+    let result = mutate(root, |_, node| {
+        let Some(step) = node.as_step() else {
+            return node.clone();
+        };
+        if step.depth > 0 {
+            return node.clone();
+        }
+
+        // Since the premises are mutated before the step itself, any inner chain that cancelled
+        // out was already removed, so we only need to find the first step in the chain that
+        // concludes the same clause as this one
+        let mut current = double_negation_base(step);
+        while let Some(base) = current {
+            if base.depth() == 0 && base.clause() == step.clause.as_slice() {
+                removed += 1;
+                return base.clone();
+            }
+            current = base.as_step().and_then(double_negation_base);
+        }
+        node.clone()
+    });
+    (result, removed)
+}
+
+/// Returns the number of steps in a proof, not counting `assume` commands or subproofs.
+pub fn count_steps(root: &Rc<ProofNode>) -> usize {
+    let mut count = 0;
+    root.traverse(|node| count += usize::from(node.is_step()));
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{checker, parser};
+
+    #[test]
+    fn test_remove_not_not_chains() {
+        let problem: &[u8] = b"
+            (declare-const a Bool)
+            (declare-const b Bool)
+            (assert (or a (not b)))
+            (assert b)
+            (assert (not a))
+        ";
+        let proof: &[u8] = b"
+            (assume h1 (or a (not b)))
+            (assume h2 b)
+            (assume h3 (not a))
+            (step t1 (cl a (not b)) :rule or :premises (h1))
+            (step t2 (cl (not (not (not b))) b) :rule not_not)
+            (step t3 (cl a (not (not (not b)))) :rule resolution :premises (t1 t2))
+            (step t4 (cl (not (not (not (not b)))) (not b)) :rule not_not)
+            (step t5 (cl a (not b)) :rule resolution :premises (t3 t4))
+            (step t6 (cl a) :rule resolution :premises (t5 h2))
+            (step t7 (cl a (not b)) :rule resolution :premises (t1 t2 t4))
+            (step t8 (cl a) :rule resolution :premises (t7 h2))
+            (step t9 (cl) :rule hole :premises (t6 t8 h3))
+        ";
+        let (problem, proof, mut pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let node = ProofNode::from_commands(proof.commands);
+
+        // Both `t5` and `t7` conclude the same clause as `t1`, by adding and then removing a double
+        // negation
+        let (result, removed) = remove_not_not_chains(&node);
+        assert_eq!(removed, 2);
+        assert_eq!(count_steps(&node) - count_steps(&result), 5);
+
+        let commands = result.into_commands();
+        let ids: Vec<_> = commands.iter().map(ProofCommand::id).collect();
+        assert_eq!(ids, ["h1", "t1", "h2", "t6", "t8", "h3", "t9"]);
+
+        let proof = Proof {
+            constant_definitions: Vec::new(),
+            commands,
+        };
+        let mut checker = checker::ProofChecker::new(&mut pool, checker::Config::new());
+        assert!(checker.check(&problem, &proof).is_ok());
+    }
+}
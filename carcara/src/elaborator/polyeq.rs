@@ -135,9 +135,10 @@ impl<'a> PolyeqElaborator<'a> {
                 let last_step = StepNode {
                     id: String::new(), // this will be overwritten later
                     depth: self.depth(),
-                    clause: vec![build_term!(pool, (= {a.clone()} {b.clone()}))],
+                    clause: vec![pool.mk_eq(a.clone(), b.clone())],
                     rule: "bind".to_owned(),
                     previous_step: Some(previous),
+                    origin: None,
                     ..Default::default()
                 };
                 self.close_subproof(args, last_step)
@@ -172,12 +173,15 @@ impl<'a> PolyeqElaborator<'a> {
                 let last_step = StepNode {
                     id: String::new(), // this will be overwritten later
                     depth: self.depth(),
-                    clause: vec![build_term!(pool, (= {a.clone()} {b.clone()}))],
+                    clause: vec![pool.mk_eq(a.clone(), b.clone())],
                     rule: "bind_let".to_owned(),
                     premises,
                     args: Vec::new(),
+                    arg_vars: Vec::new(),
                     discharge: Vec::new(),
                     previous_step: Some(previous),
+                    origin: None,
+                    attributes: Vec::new(),
                 };
                 self.close_subproof(args, last_step)
             }
@@ -219,7 +223,7 @@ impl<'a> PolyeqElaborator<'a> {
         (a, b): (&Rc<Term>, &Rc<Term>),
         (a_args, b_args): (&[Rc<Term>], &[Rc<Term>]),
     ) -> Rc<ProofNode> {
-        let clause = vec![build_term!(pool, (= {a.clone()} {b.clone()}))];
+        let clause = vec![pool.mk_eq(a.clone(), b.clone())];
         let premises = a_args
             .iter()
             .zip(b_args)
@@ -287,7 +291,7 @@ impl<'a> PolyeqElaborator<'a> {
             return Rc::new(ProofNode::Step(StepNode {
                 id: self.ids.next_id(),
                 depth: self.depth(),
-                clause: vec![build_term!(pool, (= {a} {b}))],
+                clause: vec![pool.mk_eq(a, b)],
                 rule: "eq_symmetric".to_owned(),
                 ..Default::default()
             }));
@@ -296,7 +300,7 @@ impl<'a> PolyeqElaborator<'a> {
         // To create the `cong` step that derives `(= (= x y) (= x' y'))`, we use the `build_cong`
         // method. This method also creates the steps that prove `(= x x')` and `(= y y')`, if
         // needed.
-        let flipped_b = build_term!(pool, (= {b_right.clone()} {b_left.clone()}));
+        let flipped_b = pool.mk_eq(b_right.clone(), b_left.clone());
         let cong_step = self.build_cong(
             pool,
             (&a, &flipped_b),
@@ -306,7 +310,7 @@ impl<'a> PolyeqElaborator<'a> {
         let equiv_step = Rc::new(ProofNode::Step(StepNode {
             id: self.ids.next_id(),
             depth: self.depth(),
-            clause: vec![build_term!(pool, (= {flipped_b} {b.clone()}))],
+            clause: vec![pool.mk_eq(flipped_b, b.clone())],
             rule: "eq_symmetric".to_owned(),
             ..Default::default()
         }));
@@ -314,7 +318,7 @@ impl<'a> PolyeqElaborator<'a> {
         Rc::new(ProofNode::Step(StepNode {
             id: self.ids.next_id(),
             depth: self.depth(),
-            clause: vec![build_term!(pool, (= {a} {b}))],
+            clause: vec![pool.mk_eq(a, b)],
             rule: "trans".to_owned(),
             premises: vec![cong_step, equiv_step],
             ..Default::default()
@@ -357,7 +361,7 @@ impl<'a> PolyeqElaborator<'a> {
         if inner_eq.as_step().is_some_and(|s| s.depth == self.depth()) {
             inner_eq
         } else {
-            let clause = vec![build_term!(pool, (= {a} {b}))];
+            let clause = vec![pool.mk_eq(a, b)];
             Rc::new(ProofNode::Step(StepNode {
                 id: self.ids.next_id(),
                 depth: self.depth(),
@@ -1,4 +1,4 @@
-use super::*;
+use super::{transitivity::build_equality_chain, *};
 use crate::{ast::*, checker::error::CheckerError};
 
 fn polyeq(a: &Rc<Term>, b: &Rc<Term>) -> bool {
@@ -60,33 +60,18 @@ pub fn refl(
     } else {
         let first_step = add_refl_step(pool, left.clone(), new_left.clone(), ids.next_id(), depth);
 
-        if alpha_equiv(&new_left, right) {
+        let links = if alpha_equiv(&new_left, right) {
             let second_step = elaborate_equality(pool, &new_left, right, &mut ids, depth);
-
-            Ok(Rc::new(ProofNode::Step(StepNode {
-                id: ids.next_id(),
-                depth,
-                clause: step.clause.clone(),
-                rule: "trans".to_owned(),
-                premises: vec![first_step, second_step],
-                ..Default::default()
-            })))
+            vec![first_step, second_step]
         } else if alpha_equiv(&new_left, &new_right) {
-            let second_step = elaborate_equality(pool, &new_left, right, &mut ids, depth);
-
+            let second_step = elaborate_equality(pool, &new_left, &new_right, &mut ids, depth);
             let third_step =
                 add_refl_step(pool, new_right.clone(), right.clone(), ids.next_id(), depth);
-
-            Ok(Rc::new(ProofNode::Step(StepNode {
-                id: ids.next_id(),
-                depth,
-                clause: step.clause.clone(),
-                rule: "trans".to_owned(),
-                premises: vec![first_step, second_step, third_step],
-                ..Default::default()
-            })))
+            vec![first_step, second_step, third_step]
         } else {
-            Err(CheckerError::ReflexivityFailed(left.clone(), right.clone()))
-        }
+            return Err(CheckerError::ReflexivityFailed(left.clone(), right.clone()));
+        };
+        build_equality_chain(pool, &mut ids, depth, &links, left, right)
+            .ok_or_else(|| CheckerError::BrokenTransitivityChain(left.clone(), right.clone()))
     }
 }
@@ -45,10 +45,9 @@ fn get_recomputation_func(rule: &str) -> Option<RecomputationFunc> {
 }
 
 fn recompute_weakening(step: &StepNode) -> Vec<Rc<Term>> {
-    let mut new = step.clause.clone();
-    let premise = step.premises[0].clause();
-    new[..premise.len()].clone_from_slice(premise);
-    new
+    Clause::from(step.premises[0].clause())
+        .weaken_to(&step.clause)
+        .unwrap()
 }
 
 fn recompute_contraction(step: &StepNode) -> Vec<Rc<Term>> {
@@ -0,0 +1,201 @@
+use super::*;
+use crate::eval::{Evaluator, Value};
+use indexmap::IndexMap;
+
+/// The number of abstract values given to each uninterpreted sort when sampling.
+const ABSTRACT_VALUES_PER_SORT: usize = 3;
+
+/// A small pseudo-random number generator (`SplitMix64`). Sanity checks use a fixed seed, so their
+/// results are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns an integer in the range `[-n, n]`.
+    fn small_int(&mut self, n: i64) -> i64 {
+        (self.next() % (2 * n as u64 + 1)) as i64 - n
+    }
+}
+
+/// An interpretation of the free symbols of a step, mapping each symbol to its value.
+struct Assignment {
+    definitions: IndexMap<String, Rc<Term>>,
+    abstract_values: IndexMap<Rc<Term>, Vec<Rc<Term>>>,
+}
+
+impl fmt::Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (name, value)) in self.definitions.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} = {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks every step introduced by an elaboration pass, which transformed `before` into `after`, by
+/// evaluating it under random interpretations of its free symbols. If, under some interpretation,
+/// all premises of a new step are true but its conclusion is false, the pass produced an unsound
+/// step, and an error is returned.
+///
+/// Only steps outside of subproofs are checked, since the steps inside a subproof may depend on its
+/// context. Steps whose terms can't be evaluated, for example because they use symbols of sorts
+/// that can't be sampled, are skipped.
+pub(super) fn check_new_steps(
+    pool: &mut PrimitivePool,
+    before: &Rc<ProofNode>,
+    after: &Rc<ProofNode>,
+    pass: &str,
+    options: &SanityCheckOptions,
+) -> CarcaraResult<()> {
+    let original_ids = ids::collect_ids(before);
+    let mut rng = Rng(options.seed);
+    let mut result = Ok(());
+    after.traverse(|node| {
+        let ProofNode::Step(s) = node.as_ref() else {
+            return;
+        };
+        if result.is_err()
+            || original_ids.contains(&s.id)
+            || s.depth > 0
+            || s.previous_step.is_some()
+            || !s.discharge.is_empty()
+        {
+            return;
+        }
+        if let Some(assignment) = find_counterexample(pool, s, options.samples, &mut rng) {
+            result = Err(Error::ElaborationUnsound {
+                step: s.id.clone(),
+                origin: ids::origin_of(&s.id, &original_ids)
+                    .unwrap_or(&s.id)
+                    .to_owned(),
+                pass: pass.to_owned(),
+                assignment: assignment.to_string(),
+            });
+        }
+    });
+    result
+}
+
+/// Tries to find an interpretation under which all premises of the step are true, but its
+/// conclusion is false, by sampling up to `samples` random interpretations.
+fn find_counterexample(
+    pool: &mut PrimitivePool,
+    step: &StepNode,
+    samples: usize,
+    rng: &mut Rng,
+) -> Option<Assignment> {
+    let premises: Vec<&[Rc<Term>]> = step
+        .premises
+        .iter()
+        .map(|p| match p.as_ref() {
+            ProofNode::Assume { term, .. } => std::slice::from_ref(term),
+            ProofNode::Step(s) => s.clause.as_slice(),
+            ProofNode::Subproof(s) => s.last_step.clause(),
+        })
+        .collect();
+
+    let mut symbols = IndexSet::new();
+    for t in premises.iter().copied().flatten().chain(&step.clause) {
+        symbols.extend(pool.free_vars(t));
+    }
+
+    (0..samples).find_map(|_| {
+        let assignment = sample_assignment(pool, &symbols, rng)?;
+        let mut evaluator = Evaluator::new(&assignment.definitions, &assignment.abstract_values);
+        let mut eval_clause = |clause: &[Rc<Term>]| -> Option<bool> {
+            let mut result = false;
+            for t in clause {
+                result |= matches!(evaluator.eval(t).ok()?, Value::Bool(true));
+            }
+            Some(result)
+        };
+        let premises_hold = premises
+            .iter()
+            .map(|p| eval_clause(p))
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .all(|b| b);
+        let falsified = premises_hold && !eval_clause(&step.clause)?;
+        falsified.then_some(assignment)
+    })
+}
+
+/// Samples a random interpretation for the given symbols. Functions are interpreted as constant
+/// functions. Returns `None` if some symbol has a sort that can't be sampled.
+fn sample_assignment(
+    pool: &mut PrimitivePool,
+    symbols: &IndexSet<Rc<Term>>,
+    rng: &mut Rng,
+) -> Option<Assignment> {
+    let mut assignment = Assignment {
+        definitions: IndexMap::new(),
+        abstract_values: IndexMap::new(),
+    };
+    for symbol in symbols {
+        let Term::Var(name, sort) = symbol.as_ref() else {
+            unreachable!("free variables are always `Term::Var`s")
+        };
+        let value = match sort.as_sort()? {
+            Sort::Function(sorts) => {
+                let (result_sort, param_sorts) = sorts.split_last().unwrap();
+                let params = param_sorts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| (format!("@x{}", i), s.clone()))
+                    .collect();
+                let body = sample_value(pool, &mut assignment, result_sort, rng)?;
+                pool.add(Term::Binder(Binder::Lambda, BindingList(params), body))
+            }
+            _ => sample_value(pool, &mut assignment, sort, rng)?,
+        };
+        assignment.definitions.insert(name.clone(), value);
+    }
+    Some(assignment)
+}
+
+/// Samples a random value of the given sort. Integers and reals are sampled from a small range, to
+/// make collisions between values more likely.
+fn sample_value(
+    pool: &mut PrimitivePool,
+    assignment: &mut Assignment,
+    sort: &Rc<Term>,
+    rng: &mut Rng,
+) -> Option<Rc<Term>> {
+    let value = match sort.as_sort()? {
+        Sort::Bool => Value::Bool(rng.next() % 2 == 0),
+        Sort::Int => Value::Int(rng.small_int(3).into()),
+        Sort::Real => {
+            let numerator = rng.small_int(3);
+            let denominator = (rng.next() % 2 + 1) as i64;
+            Value::Real(rug::Rational::from((numerator, denominator)))
+        }
+        Sort::FiniteField(p) => {
+            let value = rug::Integer::from(rng.next()).modulo(p);
+            Value::FiniteField(value, p.clone())
+        }
+        Sort::Atom(name, args) if args.is_empty() => {
+            let values = assignment
+                .abstract_values
+                .entry(sort.clone())
+                .or_insert_with(|| {
+                    (0..ABSTRACT_VALUES_PER_SORT)
+                        .map(|i| pool.add(Term::new_var(format!("@{}!{}", name, i), sort.clone())))
+                        .collect()
+                });
+            let i = rng.next() as usize % values.len();
+            Value::Abstract(values[i].clone())
+        }
+        _ => return None,
+    };
+    Some(value.to_term(pool))
+}
@@ -1,10 +1,11 @@
-use super::IdHelper;
+use super::{add_refl_step, IdHelper};
 use crate::{ast::*, checker::error::CheckerError};
+use std::collections::{HashMap, VecDeque};
 
-fn add_symm_step(pool: &mut PrimitivePool, node: &Rc<ProofNode>, id: String) -> Rc<ProofNode> {
+fn add_symm_step(pool: &mut dyn TermPool, node: &Rc<ProofNode>, id: String) -> Rc<ProofNode> {
     assert_eq!(node.clause().len(), 1);
     let (a, b) = match_term!((= a b) = node.clause()[0]).unwrap();
-    let clause = vec![build_term!(pool, (= {b.clone()} {a.clone()}))];
+    let clause = vec![pool.mk_eq(b.clone(), a.clone())];
     Rc::new(ProofNode::Step(StepNode {
         id,
         depth: node.depth(),
@@ -12,11 +13,101 @@ fn add_symm_step(pool: &mut PrimitivePool, node: &Rc<ProofNode>, id: String) ->
         rule: "symm".into(),
         premises: vec![node.clone()],
         args: Vec::new(),
+        arg_vars: Vec::new(),
         discharge: Vec::new(),
         previous_step: None,
+        origin: None,
+        attributes: Vec::new(),
     }))
 }
 
+/// Builds a proof of `(= from to)`, using the equalities concluded by the `links` steps to form a
+/// transitivity chain. The links may be given in any order, and each one may be used in either
+/// direction, in which case a `symm` step is introduced to flip it. Links that are not needed are
+/// ignored, and the chain found uses the smallest possible number of links.
+///
+/// If the chain has a single link, the step concluding it (or the `symm` step that flips it) is
+/// returned directly. If `from` and `to` are the same term, a `refl` step is returned. If no chain
+/// connects the two terms, this returns `None`.
+pub fn build_equality_chain(
+    pool: &mut dyn TermPool,
+    ids: &mut IdHelper,
+    depth: usize,
+    links: &[Rc<ProofNode>],
+    from: &Rc<Term>,
+    to: &Rc<Term>,
+) -> Option<Rc<ProofNode>> {
+    if from == to {
+        return Some(add_refl_step(
+            pool,
+            from.clone(),
+            to.clone(),
+            ids.next_id(),
+            depth,
+        ));
+    }
+
+    // For each term, the links that have it on either side, together with the term on the other
+    // side, and whether the link must be flipped to go from the first term to the second
+    let mut adjacent = HashMap::new();
+    for (i, link) in links.iter().enumerate() {
+        let Some((a, b)) = link.clause().first().and_then(|t| match_term!((= a b) = t)) else {
+            continue;
+        };
+        adjacent
+            .entry(a)
+            .or_insert_with(Vec::new)
+            .push((i, b, false));
+        adjacent
+            .entry(b)
+            .or_insert_with(Vec::new)
+            .push((i, a, true));
+    }
+
+    // Since all links have the same weight, a breadth-first search finds the shortest chain
+    let mut previous = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(current) = queue.pop_front() {
+        if current == to {
+            break;
+        }
+        for &(i, next, flipped) in adjacent.get(current).into_iter().flatten() {
+            if next != from && !previous.contains_key(next) {
+                previous.insert(next, (i, current, flipped));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let mut chain = Vec::new();
+    let mut current = to;
+    while current != from {
+        let &(i, prev, flipped) = previous.get(current)?;
+        chain.push((i, flipped));
+        current = prev;
+    }
+    chain.reverse();
+
+    let mut premises: Vec<_> = chain
+        .into_iter()
+        .map(|(i, flipped)| match flipped {
+            true => add_symm_step(pool, &links[i], ids.next_id()),
+            false => links[i].clone(),
+        })
+        .collect();
+    if premises.len() == 1 {
+        return premises.pop();
+    }
+    Some(Rc::new(ProofNode::Step(StepNode {
+        id: ids.next_id(),
+        depth,
+        clause: vec![pool.mk_eq(from.clone(), to.clone())],
+        rule: "trans".to_owned(),
+        premises,
+        ..Default::default()
+    })))
+}
+
 /// Similar to `find_chain`, but reorders a premises vector to match the found chain. In `trans`,
 /// this is used to reorder the step premises vector; in `eq_transitive`, it is used to reorder the
 /// clause. This returns a boolean indicating whether any reordering was needed, a `usize`
@@ -63,10 +154,15 @@ fn find_and_trace_chain<'a, T>(
     }
 }
 
+/// Elaborates a `trans` step, reordering its premises and flipping them as needed.
+///
+/// `known_equalities` maps equalities to steps in the root proof that conclude them. These steps
+/// must not depend on `step`. When a premise needs to be flipped, a step from this map is reused if
+/// possible, and any `symm` steps added in the root proof are inserted into it.
 pub fn trans(
     pool: &mut PrimitivePool,
-    _: &mut ContextStack,
     step: &StepNode,
+    known_equalities: &mut HashMap<Rc<Term>, Rc<ProofNode>>,
 ) -> Result<Rc<ProofNode>, CheckerError> {
     assert_eq!(step.clause.len(), 1);
 
@@ -92,10 +188,22 @@ pub fn trans(
     new_premises.truncate(num_needed);
 
     // If there are any premises that need flipping, we need to introduce `symm` steps to flip the
-    // needed equalities
+    // needed equalities. If the flipped equality is already concluded by some other step, we use
+    // that step instead
     let mut ids = IdHelper::new(&step.id);
     for i in should_flip {
-        new_premises[i] = add_symm_step(pool, &new_premises[i], ids.next_id());
+        let (a, b) = match_term!((= a b) = new_premises[i].clause()[0]).unwrap();
+        let flipped = pool.mk_eq(b.clone(), a.clone());
+        new_premises[i] = match known_equalities.get(&flipped) {
+            Some(node) => node.clone(),
+            None => {
+                let symm_step = add_symm_step(pool, &new_premises[i], ids.next_id());
+                if symm_step.depth() == 0 {
+                    known_equalities.insert(flipped, symm_step.clone());
+                }
+                symm_step
+            }
+        };
     }
 
     Ok(Rc::new(ProofNode::Step(StepNode {
@@ -160,8 +268,11 @@ pub fn eq_transitive(
         rule: "eq_transitive".to_owned(),
         premises: Vec::new(),
         args: Vec::new(),
+        arg_vars: Vec::new(),
         discharge: Vec::new(),
         previous_step: None,
+        origin: None,
+        attributes: Vec::new(),
     }));
 
     let mut latest_step = new_eq_transitive_step.clone();
@@ -187,8 +298,11 @@ pub fn eq_transitive(
             rule: "weakening".to_owned(),
             premises: vec![latest_step],
             args: Vec::new(),
+            arg_vars: Vec::new(),
             discharge: Vec::new(),
             previous_step: None,
+            origin: None,
+            attributes: Vec::new(),
         }));
     }
 
@@ -199,8 +313,11 @@ pub fn eq_transitive(
         rule: "reordering".to_owned(),
         premises: vec![latest_step],
         args: Vec::new(),
+        arg_vars: Vec::new(),
         discharge: Vec::new(),
         previous_step: None,
+        origin: None,
+        attributes: step.attributes.clone(),
     })))
 }
 
@@ -217,12 +334,12 @@ fn flip_eq_transitive_premises(
         .map(|&i| {
             let (a, b) = match_term!((not (= a b)) = new_clause[i]).unwrap();
 
-            let a_eq_b = build_term!(pool, (= {a.clone()} {b.clone()}));
-            let b_eq_a = build_term!(pool, (= {b.clone()} {a.clone()}));
+            let a_eq_b = pool.mk_eq(a.clone(), b.clone());
+            let b_eq_a = pool.mk_eq(b.clone(), a.clone());
             let eq_symm_step = Rc::new(ProofNode::Step(StepNode {
                 id: ids.next_id(),
                 depth,
-                clause: vec![build_term!(pool, (= {a_eq_b.clone()} {b_eq_a.clone()}))],
+                clause: vec![pool.mk_eq(a_eq_b.clone(), b_eq_a.clone())],
                 rule: "eq_symmetric".to_owned(),
                 ..StepNode::default()
             }));
@@ -276,7 +393,10 @@ fn flip_eq_transitive_premises(
         rule: "resolution".to_owned(),
         premises,
         args,
+        arg_vars: Vec::new(),
         discharge: Vec::new(),
         previous_step: None,
+        origin: None,
+        attributes: Vec::new(),
     }))
 }
@@ -1,6 +1,6 @@
 use super::IdHelper;
 use crate::{ast::*, resolution::*, utils::DedupIterator};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 fn literals_to_clause(pool: &mut dyn TermPool, clause: &[Literal]) -> Vec<Rc<Term>> {
     clause.iter().map(|l| literal_to_term(pool, *l)).collect()
@@ -30,10 +30,61 @@ impl<'a> ResolutionPremise<'a> {
     }
 }
 
+/// A clause that is built incrementally during naive resolution. Besides the literals themselves,
+/// it keeps an index from each literal to the positions where it occurs, so that finding and
+/// removing a pivot takes constant time instead of requiring a search through the whole clause.
+/// Removed literals are left as tombstones, which preserves the relative order of the remaining
+/// ones.
+struct IndexedClause<'a> {
+    literals: Vec<Option<Literal<'a>>>,
+    positions: HashMap<Literal<'a>, VecDeque<usize>>,
+}
+
+impl<'a> IndexedClause<'a> {
+    fn new(clause: &[Literal<'a>]) -> Self {
+        let mut result = Self {
+            literals: Vec::with_capacity(clause.len()),
+            positions: HashMap::with_capacity(clause.len()),
+        };
+        for &l in clause {
+            result.push(l);
+        }
+        result
+    }
+
+    fn push(&mut self, literal: Literal<'a>) {
+        self.positions
+            .entry(literal)
+            .or_default()
+            .push_back(self.literals.len());
+        self.literals.push(Some(literal));
+    }
+
+    /// Removes the first occurrence of `literal` in the clause, returning `false` if the literal
+    /// does not occur in it.
+    fn remove_first(&mut self, literal: &Literal<'a>) -> bool {
+        match self
+            .positions
+            .get_mut(literal)
+            .and_then(VecDeque::pop_front)
+        {
+            Some(pos) => {
+                self.literals[pos] = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn into_vec(self) -> Vec<Literal<'a>> {
+        self.literals.into_iter().flatten().collect()
+    }
+}
+
 fn apply_naive_resolution<'a>(premises: &[ResolutionPremise<'a>]) -> Vec<Literal<'a>> {
     assert!(premises.len() >= 2);
 
-    let mut current = premises[0].clause.clone();
+    let mut current = IndexedClause::new(&premises[0].clause);
 
     for ResolutionPremise { clause, pivot, .. } in &premises[1..] {
         let (pivot, polarity) = pivot.unwrap();
@@ -45,8 +96,7 @@ fn apply_naive_resolution<'a>(premises: &[ResolutionPremise<'a>]) -> Vec<Literal
             (negated_pivot, pivot)
         };
 
-        let pos = current.iter().position(|x| x == &pivot_in_current).unwrap();
-        current.remove(pos);
+        assert!(current.remove_first(&pivot_in_current));
 
         let mut found = false;
         for &t in clause {
@@ -59,7 +109,7 @@ fn apply_naive_resolution<'a>(premises: &[ResolutionPremise<'a>]) -> Vec<Literal
         assert!(found);
     }
 
-    current
+    current.into_vec()
 }
 
 pub fn uncrowd_resolution(
@@ -106,7 +156,9 @@ pub fn uncrowd_resolution(
     let mut final_step = premises[previous_cut].node.as_step().unwrap().clone();
 
     if final_step.clause.len() != step.clause.len() {
-        let clause = get_weakening_clause(&final_step.clause, &step.clause);
+        let clause = Clause::from(final_step.clause.as_slice())
+            .weaken_to(&step.clause)
+            .expect("current clause is not a subset of target clause!");
         final_step = StepNode {
             id: ids.next_id(),
             depth: step.depth,
@@ -163,8 +215,11 @@ fn add_partial_resolution_step<'a>(
         rule: "resolution".to_owned(),
         premises: premises.iter().map(|p| p.node.clone()).collect(),
         args,
+        arg_vars: Vec::new(),
         discharge: Vec::new(),
         previous_step: None,
+        origin: None,
+        attributes: Vec::new(),
     }));
 
     if resolution_step.clause() == final_target {
@@ -180,8 +235,11 @@ fn add_partial_resolution_step<'a>(
             rule: "contraction".to_owned(),
             premises: vec![resolution_step],
             args: Vec::new(),
+            arg_vars: Vec::new(),
             discharge: Vec::new(),
             previous_step: None,
+            origin: None,
+            attributes: Vec::new(),
         }));
         (contraction_step, contracted_conclusion)
     } else {
@@ -189,30 +247,6 @@ fn add_partial_resolution_step<'a>(
     }
 }
 
-fn get_weakening_clause(current: &[Rc<Term>], target: &[Rc<Term>]) -> Vec<Rc<Term>> {
-    let mut missing: HashMap<&Rc<Term>, usize> = HashMap::new();
-    for term in target {
-        *missing.entry(term).or_default() += 1;
-    }
-    for term in current {
-        match missing.get_mut(term) {
-            Some(0) | None => panic!("current clause is not a subset of target clause!"),
-            Some(1) => {
-                missing.remove(term);
-            }
-            Some(count) => *count -= 1,
-        }
-    }
-
-    let mut result = current.to_vec();
-    for (term, n) in missing {
-        for _ in 0..n {
-            result.push(term.clone());
-        }
-    }
-    result
-}
-
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 struct LiteralInfo {
     is_crowding: bool,
@@ -373,6 +407,24 @@ mod tests {
     use super::*;
     use crate::parser::{self, parse_instance, parse_instance_with_pool};
 
+    #[test]
+    fn test_indexed_clause() {
+        let mut pool = PrimitivePool::new();
+        let bool_sort = pool.add(Term::Sort(Sort::Bool));
+        let [a, b, c] =
+            ["a", "b", "c"].map(|name| pool.add(Term::new_var(name, bool_sort.clone())));
+        let (a, b, c) = ((0, &a), (0, &b), (1, &c));
+
+        let mut clause = IndexedClause::new(&[a, b, a, c]);
+        assert!(clause.remove_first(&a));
+        clause.push(b);
+        assert!(clause.remove_first(&b));
+        assert!(!clause.remove_first(&(1, a.1)));
+        assert!(clause.remove_first(&c));
+        clause.push(a);
+        assert_eq!(clause.into_vec(), [a, b, a]);
+    }
+
     #[test]
     fn test_uncrowd_resolution() {
         let problem: &[u8] = b"
@@ -0,0 +1,40 @@
+use super::IdHelper;
+use crate::{ast::*, checker::error::CheckerError};
+
+/// Elaborates a `weakening` step in which the premise is not a prefix of the conclusion into a
+/// `weakening` step that only adds the missing literals at the end of the clause, followed by a
+/// `reordering` step. If the premise is already a prefix of the conclusion, or if the step is
+/// invalid, it is left unchanged.
+#[allow(clippy::unnecessary_wraps)] // All elaboration functions must have the same signature
+pub fn weakening(
+    _: &mut PrimitivePool,
+    _: &mut ContextStack,
+    step: &StepNode,
+) -> Result<Rc<ProofNode>, CheckerError> {
+    let premise = step.premises[0].clone();
+    if step.clause.starts_with(premise.clause()) {
+        return Ok(Rc::new(ProofNode::Step(step.clone())));
+    }
+
+    let Some(clause) = Clause::from(premise.clause()).weaken_to(&step.clause) else {
+        return Ok(Rc::new(ProofNode::Step(step.clone())));
+    };
+
+    let mut ids = IdHelper::new(&step.id);
+    let weakening_step = Rc::new(ProofNode::Step(StepNode {
+        id: ids.next_id(),
+        depth: step.depth,
+        clause,
+        rule: "weakening".to_owned(),
+        premises: vec![premise],
+        ..Default::default()
+    }));
+    Ok(Rc::new(ProofNode::Step(StepNode {
+        id: step.id.clone(),
+        depth: step.depth,
+        clause: step.clause.clone(),
+        rule: "reordering".to_owned(),
+        premises: vec![weakening_step],
+        ..Default::default()
+    })))
+}
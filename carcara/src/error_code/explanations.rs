@@ -0,0 +1,468 @@
+use super::{ErrorCode, Explanation};
+
+const fn x(
+    number: u16,
+    description: &'static str,
+    rules: &'static [&'static str],
+    mistakes: &'static [&'static str],
+) -> (ErrorCode, Explanation) {
+    let explanation = Explanation { description, rules, mistakes };
+    (ErrorCode(number), explanation)
+}
+
+/// The long explanations of error codes, in order. Not every code has an explanation.
+pub(super) const EXPLANATIONS: &[(ErrorCode, Explanation)] = &[
+    x(
+        2,
+        "A proof of unsatisfiability must derive the empty clause. Carcara requires that some step \
+        outside of any subproof concludes `(cl)`, and reports this error if the proof is otherwise \
+        valid but never does.",
+        &[],
+        &[
+            "The proof was truncated, for example because the solver ran out of time while \
+            printing it.",
+            "The last step concludes `(cl false)` instead of `(cl)`. An extra `resolution` step \
+            with `false` is needed to reach the empty clause.",
+        ],
+    ),
+    x(
+        3,
+        "A step or command id given to Carcara, for example to `slice` or `context`, does not \
+        appear in the proof.",
+        &[],
+        &["Step ids inside subproofs are written with their full prefix, such as `t5.t2`."],
+    ),
+    x(
+        4,
+        "The elaborated proof grew past the configured limit relative to the size of the original \
+        proof, so elaboration was stopped.",
+        &[],
+        &["The growth limit is too small for proofs with many `lia_generic` steps."],
+    ),
+    x(
+        109,
+        "The parser found a token that is not allowed at this point of the input. This usually \
+        means the file is not valid SMT-LIB or Alethe syntax.",
+        &[],
+        &[
+            "Unbalanced parentheses, often caused by a truncated proof.",
+            "A problem file was given where a proof was expected, or vice versa.",
+        ],
+    ),
+    x(
+        111,
+        "A term does not have the sort required by the context in which it is used, for example an \
+        integer term used as an argument of `and`.",
+        &[],
+        &[
+            "Mixing `Int` and `Real` terms without `to_real`. Carcara accepts this only when \
+            `--allow-int-real-subtyping` is given.",
+            "Using a symbol that was declared with a different sort in the problem.",
+        ],
+    ),
+    x(
+        115,
+        "An identifier was used without being declared, either in the problem or in the proof.",
+        &[],
+        &[
+            "The proof uses a symbol introduced by the solver, such as a Skolem constant, without \
+            defining it with `define-fun`.",
+            "The proof is checked against the wrong problem file.",
+        ],
+    ),
+    x(
+        117,
+        "A step refers to a premise whose id does not exist, or is not visible from the step.",
+        &[],
+        &[
+            "Referring to a step inside a subproof from outside of it. Only the last step of a \
+            subproof is visible after the subproof ends.",
+            "The proof was sliced or reordered and a premise was left behind.",
+        ],
+    ),
+    x(
+        121,
+        "Two commands in the same proof have the same id. Ids must be unique, including across \
+        subproofs.",
+        &[],
+        &["Concatenating proofs from different solver calls without renaming their steps."],
+    ),
+    x(
+        125,
+        "An `anchor` command opened a subproof that was never closed by a step.",
+        &[],
+        &["The proof was truncated in the middle of a subproof."],
+    ),
+    x(
+        139,
+        "The proof uses functions or sorts that are not declared in the problem. This is only \
+        checked when mismatch detection is enabled, in which case every undeclared symbol is \
+        reported together, with its arity and number of uses.",
+        &[],
+        &[
+            "The proof is checked against the wrong problem file, for example after the files \
+            of a benchmark set were renamed.",
+            "The problem was preprocessed or simplified after the proof was produced.",
+        ],
+    ),
+    x(
+        200,
+        "The rule failed, but its checker did not produce a more specific error. The step's \
+        conclusion is not justified by its premises and arguments.",
+        &[],
+        &[],
+    ),
+    x(
+        201,
+        "An `assume` command outside of any subproof must introduce a term that appears as an \
+        assertion in the original problem. The term must be equal to the assertion, modulo the \
+        reordering of the arguments of equalities.",
+        &["assume"],
+        &[
+            "The solver preprocessed the assertion, for example by removing `let` terms or \
+            expanding definitions, before printing it.",
+            "The proof is checked against the wrong problem file.",
+        ],
+    ),
+    x(
+        202,
+        "The step uses a rule that is not implemented by Carcara. Unless `--ignore-unknown-rules` \
+        is given, this makes the proof invalid.",
+        &[],
+        &[
+            "A typo in the rule name.",
+            "A solver-specific rule that is not part of the Alethe specification. Such steps can \
+            be accepted as holes with `--ignore-unknown-rules`.",
+        ],
+    ),
+    x(
+        203,
+        "The premises of a transitivity step must form a chain of equalities connecting the two \
+        sides of the conclusion. That is, from `(= t_1 t_2)`, `(= t_2 t_3)`, ..., `(= t_{n-1} \
+        t_n)` the rule concludes `(= t_1 t_n)`. Carcara accepts the premises in any order, and \
+        each equality may be flipped, but every premise must be used and the chain must not be \
+        broken.",
+        &["trans", "eq_transitive"],
+        &[
+            "One equality in the chain is missing, so two consecutive links don't share a term.",
+            "Two links share a term only modulo some rewriting, such as a reordering of \
+            arguments or an implicit `let` expansion. The terms must be syntactically equal.",
+        ],
+    ),
+    x(
+        204,
+        "A `refl` step concludes `(= t u)`, where `t` and `u` must be equal after applying the \
+        substitution introduced by the enclosing subproof contexts. Outside of any subproof, \
+        `t` and `u` must be alpha-equivalent.",
+        &["refl"],
+        &[
+            "The substitution from an outer context is needed, but the step is in a subproof that \
+            does not inherit it.",
+            "The two sides are only equal after some simplification, which `refl` doesn't do.",
+        ],
+    ),
+    x(
+        205,
+        "A simplification rule concludes `(= t u)`, where `u` must be the result of applying the \
+        rule's rewrites to `t`. Carcara applied the rewrites to `t` and got a different result.",
+        &[
+            "and_simplify",
+            "or_simplify",
+            "not_simplify",
+            "implies_simplify",
+            "equiv_simplify",
+            "bool_simplify",
+            "ite_simplify",
+            "eq_simplify",
+            "div_simplify",
+            "prod_simplify",
+            "minus_simplify",
+            "sum_simplify",
+            "comp_simplify",
+        ],
+        &[
+            "The conclusion applies rewrites of a different simplification rule as well. Each \
+            step may only use the rewrites of its own rule.",
+            "The conclusion only applies some of the possible rewrites, and the result is not one \
+            of the intermediate terms Carcara considers.",
+        ],
+    ),
+    x(
+        206,
+        "While simplifying a term, Carcara reached a term it had already seen, so the \
+        simplification would never terminate.",
+        &[
+            "ite_simplify",
+            "eq_simplify",
+            "not_simplify",
+            "implies_simplify",
+            "equiv_simplify",
+            "bool_simplify",
+        ],
+        &[],
+    ),
+    x(
+        211,
+        "A `contraction` step removes duplicate literals from its premise. Every literal of the \
+        premise must appear in the conclusion.",
+        &["contraction"],
+        &["A literal was dropped from the clause, not just duplicated copies of it."],
+    ),
+    x(
+        212,
+        "A `contraction` step removes duplicate literals from its premise. The conclusion may not \
+        contain literals that are not in the premise.",
+        &["contraction"],
+        &["The conclusion also reorders or rewrites the literals, which `contraction` doesn't do."],
+    ),
+    x(
+        216,
+        "The step has a different number of premises than its rule requires.",
+        &[],
+        &[
+            "A premise that the rule needs was omitted because it appeared earlier in the proof.",
+            "The rule takes no premises, but the step gives some anyway.",
+        ],
+    ),
+    x(
+        217,
+        "The conclusion clause has a different number of literals than the rule requires. Many \
+        rules conclude a specific number of literals, such as `(cl (not (and a b)) a)` for \
+        `and_pos`.",
+        &[],
+        &[
+            "Writing a single-literal clause as the literal itself, without `cl`.",
+            "Duplicate literals were removed from the conclusion, which requires a separate \
+            `contraction` step.",
+        ],
+    ),
+    x(
+        218,
+        "The step has a different number of arguments than its rule requires.",
+        &["forall_inst", "la_generic", "lia_generic"],
+        &["`la_generic` needs one coefficient for each literal in the conclusion."],
+    ),
+    x(
+        222,
+        "A term in the step does not have the form required by the rule. The message shows the \
+        pattern Carcara expected and the term it found.",
+        &[],
+        &[
+            "A negated literal was written as the negation of the expected term, or vice versa.",
+            "An n-ary operator was written with nested binary applications.",
+        ],
+    ),
+    x(
+        239,
+        "This rule may only be used in the last step of a subproof, since it relies on the \
+        subproof's context or assumptions.",
+        &["subproof", "bind", "sko_ex", "sko_forall", "onepoint", "let"],
+        &["Forgetting the `anchor` command that opens the subproof."],
+    ),
+    x(
+        240,
+        "An arithmetic rule evaluated a division or modulo whose divisor is zero.",
+        &["div_simplify", "la_generic"],
+        &[],
+    ),
+    x(
+        241,
+        "Checking the step required building a term larger than the limit given with \
+        `--max-term-size`, or elaborating it introduced such a term. Some rules, like `nary_elim` \
+        and `bfun_elim`, build terms that may be much larger than the step itself. The size of a \
+        term is its number of distinct subterms.",
+        &["nary_elim", "bfun_elim", "bitblast_ult", "bitblast_bvadd"],
+        &["The limit is too small for the proof. It is meant to reject adversarial inputs, and \
+        should be set well above the size of the terms in the problem."],
+    ),
+    x(
+        242,
+        "Checking the step required building a term deeper than the limit given with \
+        `--max-term-depth`, or elaborating it introduced such a term.",
+        &["nary_elim", "bitblast_ult", "bitblast_bvadd"],
+        &[],
+    ),
+    x(
+        250,
+        "Two terms that the rule requires to be equal are different. Depending on the rule, \
+        Carcara compares terms syntactically, modulo the reordering of the arguments of \
+        equalities, or modulo alpha-equivalence.",
+        &[],
+        &[
+            "The terms are only equal modulo the symmetry of `=`, but the rule requires them to \
+            be syntactically equal.",
+            "The terms are only equal after expanding a `let` or a `define-fun`.",
+        ],
+    ),
+    x(
+        300,
+        "A tautology step must conclude a clause that contains both a literal and its negation, \
+        or the literal `true`.",
+        &["tautology"],
+        &[],
+    ),
+    x(
+        301,
+        "Resolution removes each pivot from the clauses it resolves. A pivot was eliminated \
+        from the premises, but still appears in the conclusion.",
+        &["resolution", "th_resolution"],
+        &[
+            "The pivot also appears in a premise with the same polarity as in the clause it was \
+            resolved with, so it is not removed from the result.",
+        ],
+    ),
+    x(
+        302,
+        "The conclusion of a resolution step contains a literal that is not in the result of \
+        resolving the premises.",
+        &["resolution", "th_resolution"],
+        &[
+            "A double negation was removed from a literal. Carcara treats `(not (not p))` as \
+            different from `p` in clauses, unless the double negation is a pivot.",
+            "The order of the premises is different from the order used to resolve them, and \
+            some intermediate pivots don't cancel. Give explicit pivots as arguments to make the \
+            intended order unambiguous.",
+        ],
+    ),
+    x(
+        303,
+        "A literal in the result of resolving the premises does not appear in the conclusion of \
+        the resolution step.",
+        &["resolution", "th_resolution"],
+        &["A pivot was resolved away in the solver, but the premise that contains its negation \
+        was not listed in the step."],
+    ),
+    x(
+        304,
+        "An explicit pivot given as an argument to a resolution step does not appear in the \
+        clauses it should be resolved with.",
+        &["resolution"],
+        &["The polarity argument of the pivot is wrong: `true` means the pivot appears \
+        positively in the left clause and negated in the right one."],
+    ),
+    x(
+        400,
+        "A congruence step has more premises than there are arguments in the function \
+        application.",
+        &["cong", "ho_cong"],
+        &["Premises for arguments that are already equal were included. These are redundant."],
+    ),
+    x(
+        401,
+        "In a congruence step concluding `(= (f t_1 ... t_n) (f u_1 ... u_n))`, each pair of \
+        arguments `t_i` and `u_i` must either be equal or be justified by a premise `(= t_i u_i)`. \
+        The premises must be in the same order as the arguments.",
+        &["cong", "ho_cong"],
+        &[
+            "A premise justifying one of the argument pairs is missing.",
+            "The premises are not in the same order as the arguments they justify.",
+        ],
+    ),
+    x(
+        403,
+        "Both sides of the equality concluded by a congruence step must be applications of the \
+        same function.",
+        &["cong", "ho_cong"],
+        &[],
+    ),
+    x(
+        500,
+        "A `forall_inst` step instantiates the quantified variables with the terms given as \
+        arguments. Each argument `(:= x t)` must name one of the variables bound by the \
+        quantifier.",
+        &["forall_inst"],
+        &["The argument refers to a variable renamed by the solver after printing the term."],
+    ),
+    x(
+        501,
+        "A `forall_inst` step must give an instantiation for every variable bound by the \
+        quantifier.",
+        &["forall_inst"],
+        &[],
+    ),
+    x(
+        505,
+        "A `qnt_cnf` step concludes the negation of a quantified formula, or a clause of its \
+        clausal normal form. The clause in the conclusion does not appear in the CNF Carcara \
+        computed.",
+        &["qnt_cnf"],
+        &[],
+    ),
+    x(
+        506,
+        "A `forall_inst` step with no arguments, whose quantifier body has `:pattern` \
+        annotations, is checked by matching each pattern against the terms of the instantiated \
+        formula. None of the instantiations found this way gives the formula in the conclusion.",
+        &["forall_inst"],
+        &["A pattern does not mention every variable bound by the quantifier."],
+    ),
+    x(
+        600,
+        "An `la_tautology` step must conclude a clause that is a tautology of linear \
+        arithmetic, in one of the specific forms allowed by the rule.",
+        &["la_tautology"],
+        &[],
+    ),
+    x(
+        603,
+        "An `la_generic` or `lia_generic` step concludes a clause whose negation is an \
+        unsatisfiable set of linear (in)equalities. For `la_generic`, the arguments are \
+        coefficients of a linear combination of the negated literals, which must produce a \
+        contradiction of the form `0 < 0`, `0 <= c` with `c < 0`, or similar.",
+        &["la_generic", "la_disequality"],
+        &[
+            "The coefficients are for the literals, not for their negations.",
+            "The coefficients are in a different order than the literals.",
+            "A strict inequality was strengthened over the integers, which `la_generic` doesn't \
+            do. Use `lia_generic` or give the strengthened literal directly.",
+        ],
+    ),
+    x(
+        700,
+        "The `:discharge` argument of a `subproof` step may only refer to `assume` commands that \
+        are inside the subproof.",
+        &["subproof"],
+        &[],
+    ),
+    x(
+        701,
+        "Every `assume` command inside a subproof must be discharged by the `subproof` step that \
+        ends it. Otherwise, the conclusion of the subproof would depend on a local assumption.",
+        &["subproof"],
+        &["The `:discharge` attribute lists only some of the local assumptions."],
+    ),
+    x(
+        702,
+        "Only the `subproof` rule may end a subproof that contains `assume` commands, since it \
+        is the only rule that discharges them.",
+        &["subproof"],
+        &[],
+    ),
+    x(
+        703,
+        "In a `bind` step concluding `(= (Q (x_1 ... x_n) phi) (Q (y_1 ... y_n) psi))`, the \
+        variables `y_i` must not appear free in `phi`.",
+        &["bind"],
+        &["The solver reused the name of a variable when renaming the bound variables."],
+    ),
+    x(
+        706,
+        "A binding in the conclusion was not introduced by the anchor of the subproof, so it is \
+        not in the context of the step.",
+        &["bind", "sko_ex", "sko_forall", "onepoint"],
+        &["The anchor does not list all the variables that are renamed in the subproof."],
+    ),
+    x(
+        800,
+        "A substitution may only replace variables. The left-hand side of a `(:= x t)` argument \
+        is a term that is not a variable.",
+        &[],
+        &[],
+    ),
+    x(
+        801,
+        "In a substitution `(:= x t)`, the variable `x` and the term `t` must have the same sort.",
+        &[],
+        &["An integer term was substituted for a real variable, or vice versa."],
+    ),
+];
@@ -0,0 +1,338 @@
+//! Stable, machine-readable codes for every kind of error.
+//!
+//! Each variant of [`Error`](crate::Error), [`ParserError`](crate::parser::ParserError),
+//! [`CheckerError`](crate::checker::error::CheckerError) and of the errors nested in them has a
+//! code, such as `E0203` for a broken transitivity chain. Unlike the error messages, the codes don't
+//! change across versions, so tools can use them to classify failures. Codes are never reused: if a
+//! kind of error is removed, its code is retired.
+//!
+//! The codes are grouped by the kind of error:
+//!
+//! - `E00xx`: general errors
+//! - `E01xx`: parser errors
+//! - `E02xx`: checker errors that are common to many rules
+//! - `E03xx`: resolution errors
+//! - `E04xx`: congruence errors
+//! - `E05xx`: quantifier errors
+//! - `E06xx`: linear arithmetic errors
+//! - `E07xx`: subproof errors
+//! - `E08xx`: substitution errors
+//! - `E09xx`: datatype errors
+//! - `E10xx`: RARE rewrite errors
+//! - `E11xx`: DRAT and LRAT certificate errors
+//!
+//! Some codes also have a longer [`Explanation`], describing what the rules involved require and
+//! the mistakes that commonly cause the error.
+
+mod explanations;
+
+use explanations::EXPLANATIONS;
+use std::{fmt, str::FromStr};
+
+/// A stable code identifying a kind of error. It is displayed as `E` followed by four digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ErrorCode(u16);
+
+impl ErrorCode {
+    pub(crate) const fn new(number: u16) -> Self {
+        Self(number)
+    }
+
+    pub fn number(self) -> u16 {
+        self.0
+    }
+
+    /// Returns a short description of the kind of error.
+    pub fn summary(self) -> &'static str {
+        let index = ERROR_CODES
+            .binary_search_by_key(&self, |&(code, _)| code)
+            .expect("every error code should be in the table");
+        ERROR_CODES[index].1
+    }
+
+    /// Returns the long explanation of the kind of error, if there is one.
+    pub fn explanation(self) -> Option<&'static Explanation> {
+        let index = EXPLANATIONS
+            .binary_search_by_key(&self, |&(code, _)| code)
+            .ok()?;
+        Some(&EXPLANATIONS[index].1)
+    }
+}
+
+/// A longer description of a kind of error.
+#[derive(Debug)]
+pub struct Explanation {
+    /// What is required for the error not to happen, usually taken from the specification of the
+    /// rules involved.
+    pub description: &'static str,
+
+    /// The rules that may produce the error. This is empty if the error is not specific to a few
+    /// rules.
+    pub rules: &'static [&'static str],
+
+    /// Common mistakes made by proof producers that lead to the error.
+    pub mistakes: &'static [&'static str],
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "E{:04}", self.0)
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = ();
+
+    /// Parses an error code, with or without the leading `E`. Only codes that exist are accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix(['E', 'e']).unwrap_or(s);
+        let code = Self(digits.parse().map_err(|_| ())?);
+        match ERROR_CODES.binary_search_by_key(&code, |&(code, _)| code) {
+            Ok(_) => Ok(code),
+            Err(_) => Err(()),
+        }
+    }
+}
+
+const fn e(number: u16, summary: &'static str) -> (ErrorCode, &'static str) {
+    (ErrorCode(number), summary)
+}
+
+/// All error codes, in order, with a short description of each.
+pub const ERROR_CODES: &[(ErrorCode, &str)] = &[
+    e(1, "IO error"),
+    e(2, "proof does not conclude empty clause"),
+    e(3, "step does not exist"),
+    e(4, "elaboration exceeded growth limit"),
+    e(5, "final step does not conclude empty clause"),
+    e(6, "final step does not conclude goal"),
+    e(7, "elaborated step is falsified"),
+    e(100, "unexpected character"),
+    e(101, "leading zero in numeral"),
+    e(102, "division by zero in numerical literal"),
+    e(103, "backslash in quoted symbol"),
+    e(104, "unexpected end of input in quoted symbol"),
+    e(105, "unexpected end of input in string literal"),
+    e(106, "invalid unicode value"),
+    e(107, "empty bitvector literal"),
+    e(108, "bitvector literal is too large"),
+    e(109, "unexpected token"),
+    e(110, "expected non-empty sequence"),
+    e(111, "sort error"),
+    e(112, "expected bitvector sort"),
+    e(113, "expected integer constant"),
+    e(114, "not a function"),
+    e(115, "undefined identifier"),
+    e(116, "undefined sort"),
+    e(117, "undefined step id"),
+    e(118, "wrong number of arguments"),
+    e(119, "argument value out of range"),
+    e(120, "invalid extract arguments"),
+    e(121, "repeated step id"),
+    e(122, "invalid sort arity"),
+    e(123, "empty subproof"),
+    e(124, "last command in subproof is not a step"),
+    e(125, "unclosed subproof"),
+    e(126, "missing conclusion"),
+    e(127, "invalid indexed operator"),
+    e(128, "invalid qualified operator"),
+    e(129, "conflicting inferred sorts"),
+    e(130, "inconsistent arity"),
+    e(131, "unknown Alethe version"),
+    e(132, "expected floating-point sort"),
+    e(133, "parametric datatype"),
+    e(134, "not a datatype constructor"),
+    e(135, "invalid `input` step"),
+    e(136, "premise in closed subproof"),
+    e(137, "discharged command is not local"),
+    e(138, "repeated RARE rule"),
+    e(139, "proof does not match problem"),
+    e(140, "invalid finite field literal"),
+    e(141, "expected finite field sort"),
+    e(142, "non-prime finite field order"),
+    e(200, "unspecified error"),
+    e(201, "assumption not in problem"),
+    e(202, "unknown rule"),
+    e(203, "broken transitivity chain"),
+    e(204, "reflexivity failed"),
+    e(205, "simplification failed"),
+    e(206, "cycle in simplification"),
+    e(207, "rewrite sequence not found"),
+    e(208, "invalid sum or product simplification"),
+    e(209, "term is not a connective"),
+    e(210, "invalid `ite_intro` term"),
+    e(211, "term missing in contraction"),
+    e(212, "extra term in contraction"),
+    e(213, "invalid n-ary term"),
+    e(214, "length cannot be evaluated"),
+    e(215, "missing child in term"),
+    e(216, "wrong number of premises"),
+    e(217, "wrong number of terms in clause"),
+    e(218, "wrong number of arguments"),
+    e(219, "wrong number of terms in operation"),
+    e(220, "term missing in operation"),
+    e(221, "wrong number of terms in premise clause"),
+    e(222, "term of wrong form"),
+    e(223, "expected specific boolean constant"),
+    e(224, "expected boolean constant"),
+    e(225, "expected string constant of length one"),
+    e(226, "expected different constant prefixes"),
+    e(227, "expected specific numerical constant"),
+    e(228, "expected specific integer constant"),
+    e(229, "expected numerical constant"),
+    e(230, "expected integer constant"),
+    e(231, "expected non-negative integer constant"),
+    e(232, "expected operation term"),
+    e(233, "expected quantifier term"),
+    e(234, "expected binder term"),
+    e(235, "expected `let` term"),
+    e(236, "expected prefix"),
+    e(237, "expected suffix"),
+    e(238, "expected non-empty term"),
+    e(239, "rule must end a subproof"),
+    e(240, "division or modulo by zero"),
+    e(241, "term exceeds size limit"),
+    e(242, "term exceeds depth limit"),
+    e(243, "premise is not in scope"),
+    e(244, "term missing in weakening"),
+    e(245, "bound variable occurs free"),
+    e(250, "terms are not equal"),
+    e(251, "term is not the expected one"),
+    e(252, "quantifiers are not equal"),
+    e(253, "quantifier is not the expected one"),
+    e(254, "binding lists are not equal"),
+    e(255, "binding list is not the expected one"),
+    e(300, "resolution tautology not found"),
+    e(301, "pivot was not eliminated"),
+    e(302, "extra term in resolution conclusion"),
+    e(303, "term missing in resolution conclusion"),
+    e(304, "pivot not found"),
+    e(400, "too many premises for congruence"),
+    e(401, "missing congruence premise"),
+    e(402, "premise doesn't justify congruence arguments"),
+    e(403, "different functions in congruence"),
+    e(404, "different operators in congruence"),
+    e(405, "different number of arguments in congruence"),
+    e(406, "term is not an application or operation"),
+    e(407, "different indexed operators in congruence"),
+    e(500, "argument doesn't match any binding"),
+    e(501, "no argument for binding"),
+    e(502, "union of bindings failed"),
+    e(503, "new binding introduced in CNF"),
+    e(504, "binding missing in CNF"),
+    e(505, "clause doesn't appear in CNF"),
+    e(506, "no pattern instantiation matches"),
+    e(600, "not a valid tautology case"),
+    e(601, "invalid disequality operation"),
+    e(602, "too many arguments in disequality"),
+    e(603, "disequality is not contradictory"),
+    e(604, "disequality is not tautological"),
+    e(605, "expected less than"),
+    e(606, "expected less than or equal"),
+    e(607, "disequality doesn't match equality"),
+    e(608, "bound is not equivalent"),
+    e(609, "LIA clause is not valid"),
+    e(610, "LIA search limit reached"),
+    e(611, "invalid simplex certificate"),
+    e(612, "NLA clause not proven"),
+    e(613, "term cannot be enclosed"),
+    e(614, "bound not proven by interval arithmetic"),
+    e(700, "discharge must be assumption"),
+    e(701, "local assumption not discharged"),
+    e(702, "discharge in wrong rule"),
+    e(703, "binding is free in body"),
+    e(704, "unexpected anchor argument"),
+    e(705, "different number of bindings"),
+    e(706, "binding not in context"),
+    e(707, "wrong number of `let` bindings"),
+    e(708, "premise doesn't justify `let` substitution"),
+    e(709, "no point for substitution"),
+    e(710, "wrong left bindings in `onepoint`"),
+    e(711, "wrong right bindings in `onepoint`"),
+    e(712, "discharged command is not local"),
+    e(800, "substituted term is not a variable"),
+    e(801, "substitution with different sorts"),
+    e(900, "not a constructor application"),
+    e(901, "different constructors"),
+    e(902, "same constructor"),
+    e(903, "not a datatype sort"),
+    e(1000, "unknown symbol in RARE rule"),
+    e(1001, "RARE list parameter outside of application"),
+    e(1002, "empty application in RARE rule"),
+    e(1003, "RARE rule condition not satisfied"),
+    e(1004, "invalid index in RARE rule"),
+    e(1005, "wrong sort for RARE parameter"),
+    e(1100, "unknown certificate format"),
+    e(1101, "could not read certificate"),
+    e(1102, "malformed certificate entry"),
+    e(1103, "invalid clause id in certificate"),
+    e(1104, "certificate clause is not redundant"),
+    e(1105, "certificate does not derive empty clause"),
+    e(1106, "certificate files not allowed"),
+    e(1107, "certificate file is not a regular file"),
+    e(1108, "certificate file outside of certificate directory"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        assert!(ERROR_CODES.windows(2).all(|w| w[0].0 < w[1].0));
+
+        let code: ErrorCode = "E0203".parse().unwrap();
+        assert_eq!(code.to_string(), "E0203");
+        assert_eq!(code.summary(), "broken transitivity chain");
+        assert_eq!("e0203".parse(), Ok(code));
+        assert_eq!("203".parse(), Ok(code));
+        assert_eq!("E0299".parse::<ErrorCode>(), Err(()));
+        assert_eq!("E02x3".parse::<ErrorCode>(), Err(()));
+    }
+
+    #[test]
+    fn test_explanations() {
+        use crate::checker::IMPLEMENTED_RULES;
+
+        assert!(EXPLANATIONS.windows(2).all(|w| w[0].0 < w[1].0));
+        for (code, explanation) in EXPLANATIONS {
+            assert!(
+                ERROR_CODES.binary_search_by_key(code, |&(c, _)| c).is_ok(),
+                "explanation for unknown error code {}",
+                code
+            );
+            for rule in explanation.rules {
+                assert!(
+                    *rule == "assume" || IMPLEMENTED_RULES.contains(rule),
+                    "unknown rule '{}' in explanation for {}",
+                    rule,
+                    code
+                );
+            }
+        }
+        let code: ErrorCode = "E0203".parse().unwrap();
+        assert!(code.explanation().unwrap().rules.contains(&"trans"));
+        assert!(ErrorCode::new(1).explanation().is_none());
+    }
+
+    #[test]
+    fn test_error_variant_codes() {
+        use crate::checker::error::{CheckerError, SubproofError};
+        use crate::parser::ParserError;
+
+        let cases = [
+            (crate::Error::DoesNotReachEmptyClause.code(), "E0002"),
+            (ParserError::EmptySequence.code(), "E0110"),
+            (CheckerError::UnknownRule.code(), "E0202"),
+            (CheckerError::DivOrModByZero.code(), "E0240"),
+            (
+                CheckerError::from(SubproofError::DischargeInWrongRule).code(),
+                "E0702",
+            ),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(code.to_string(), expected);
+            assert!(!code.summary().is_empty());
+        }
+    }
+}
@@ -0,0 +1,1042 @@
+//! An evaluator for ground SMT-LIB terms.
+//!
+//! The evaluator reduces a term to a concrete value, using a set of function definitions (for
+//! example, the ones given by a model) to interpret the free symbols in the term. Closed terms
+//! can be evaluated directly with [`evaluate`].
+
+use crate::ast::*;
+use indexmap::IndexMap;
+use rug::{Integer, Rational};
+use std::{collections::HashMap, fmt};
+use thiserror::Error;
+
+/// The result of evaluating a term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Bool(bool),
+    Int(Integer),
+    Real(Rational),
+    String(String),
+
+    /// A bit-vector, given by its unsigned value and its width.
+    BitVec(Integer, Integer),
+
+    /// A finite field element, given by its value in the range `[0, p)` and the field order `p`.
+    FiniteField(Integer, Integer),
+
+    /// A sequence, given by the sort of its elements and its elements.
+    Seq(Sort, Vec<Value>),
+
+    /// An element of an uninterpreted sort, like the abstract values declared in a model. Two
+    /// abstract values are equal if and only if they are the same term.
+    Abstract(Rc<Term>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Real(r) => write!(f, "{}", r),
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::BitVec(v, w) => write!(f, "(_ bv{} {})", v, w),
+            Value::FiniteField(v, p) => write!(f, "#f{}m{}", v, p),
+            Value::Seq(sort, elements) => match elements.as_slice() {
+                [] => write!(f, "(as seq.empty (Seq {}))", sort),
+                [x] => write!(f, "(seq.unit {})", x),
+                _ => {
+                    write!(f, "(seq.++")?;
+                    for x in elements {
+                        write!(f, " (seq.unit {})", x)?;
+                    }
+                    write!(f, ")")
+                }
+            },
+            Value::Abstract(t) => write!(f, "{}", t),
+        }
+    }
+}
+
+/// An error encountered while evaluating a term.
+#[derive(Debug, Error)]
+pub enum EvalError {
+    /// The term contains a free symbol that has no definition.
+    #[error("symbol '{0}' has no value")]
+    UnknownSymbol(String),
+
+    /// The term uses an operator or construct that the evaluator doesn't support, like bit-vector
+    /// operations or `choice` terms.
+    #[error("cannot evaluate term '{0}'")]
+    Unsupported(Rc<Term>),
+
+    /// The term is an application whose value is left unspecified by SMT-LIB, like a division by
+    /// zero.
+    #[error("the value of term '{0}' is unspecified by SMT-LIB")]
+    Unspecified(Rc<Term>),
+
+    /// A quantifier binds a variable of a sort for which no bound was given.
+    #[error("no bound was given for quantified variables of sort '{0}'")]
+    NoQuantifierBound(Rc<Term>),
+
+    /// The bounded enumeration of a quantifier didn't determine its value.
+    #[error("quantifier bounds are insufficient to evaluate '{0}'")]
+    BoundsInsufficient(Rc<Term>),
+
+    /// An operator received a value of the wrong kind.
+    #[error("expected {expected} value, got '{got}'")]
+    WrongValue { expected: &'static str, got: Value },
+}
+
+pub type EvalResult<T> = Result<T, EvalError>;
+
+/// Evaluates a closed term, that is, a term with no free symbols.
+///
+/// This supports Boolean connectives, `ite`, equalities, integer and real arithmetic, most string
+/// operations, sequence operations, and conversions between integers and bit-vectors. Quantifiers are
+/// only evaluated if they range over `Bool`. To evaluate terms
+/// that refer to defined symbols, or to evaluate quantifiers by bounded enumeration, use an
+/// [`Evaluator`] directly.
+///
+/// # Examples
+///
+/// ```
+/// # use carcara::{ast::*, eval::{evaluate, Value}, parser};
+/// let mut pool = PrimitivePool::new();
+/// let mut parser = parser::Parser::new(&mut pool, parser::Config::new(), "".as_bytes()).unwrap();
+/// parser.reset("(ite (< 1 2) (str.len \"abc\") 0)".as_bytes()).unwrap();
+/// let term = parser.parse_term().unwrap();
+/// assert_eq!(evaluate(&term, &mut pool).unwrap(), Value::Int(3.into()));
+/// ```
+pub fn evaluate(term: &Rc<Term>, pool: &mut dyn TermPool) -> EvalResult<Value> {
+    if let Some(var) = pool.free_vars(term).first() {
+        let name = var.as_var().unwrap_or_default();
+        return Err(EvalError::UnknownSymbol(name.to_owned()));
+    }
+    let (definitions, abstract_values) = (IndexMap::new(), IndexMap::new());
+    Evaluator::new(&definitions, &abstract_values).eval(term)
+}
+
+impl Value {
+    /// Converts the value back into a constant term. For abstract values, this is the term that
+    /// represents the value.
+    pub fn to_term(&self, pool: &mut dyn TermPool) -> Rc<Term> {
+        match self {
+            Value::Bool(b) => pool.bool_constant(*b),
+            Value::Int(i) => pool.add(Term::new_int(i.clone())),
+            Value::Real(r) => pool.add(Term::new_real(r.clone())),
+            Value::String(s) => pool.add(Term::new_string(s.clone())),
+            Value::BitVec(v, w) => pool.add(Term::new_bv(v.clone(), w.clone())),
+            Value::FiniteField(v, p) => pool.add(Term::new_ff(v.clone(), p.clone())),
+            Value::Seq(sort, elements) => {
+                let mut units: Vec<_> = elements
+                    .iter()
+                    .map(|x| {
+                        let x = x.to_term(pool);
+                        pool.add(Term::Op(Operator::SeqUnit, vec![x]))
+                    })
+                    .collect();
+                match units.len() {
+                    0 => {
+                        let element = pool.add(Term::Sort(sort.clone()));
+                        let sort = pool.add(Term::Sort(Sort::Seq(element)));
+                        pool.add(Term::ParamOp {
+                            op: ParamOperator::SeqEmpty,
+                            op_args: vec![sort],
+                            args: Vec::new(),
+                        })
+                    }
+                    1 => units.pop().unwrap(),
+                    _ => pool.add(Term::Op(Operator::SeqConcat, units)),
+                }
+            }
+            Value::Abstract(t) => t.clone(),
+        }
+    }
+
+    /// Returns the sort of the value, if it can be represented without a term pool. This is not
+    /// the case for sequences, whose sort refers to the sort of their elements.
+    fn sort(&self) -> Option<Sort> {
+        let sort = match self {
+            Value::Bool(_) => Sort::Bool,
+            Value::Int(_) => Sort::Int,
+            Value::Real(_) => Sort::Real,
+            Value::String(_) => Sort::String,
+            Value::BitVec(_, w) => Sort::BitVec(w.clone()),
+            Value::FiniteField(_, p) => Sort::FiniteField(p.clone()),
+            Value::Abstract(t) => match t.as_ref() {
+                Term::Var(_, sort) => sort.as_sort()?.clone(),
+                _ => return None,
+            },
+            Value::Seq(..) => return None,
+        };
+        Some(sort)
+    }
+
+    fn as_bool(&self) -> EvalResult<bool> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            other => Err(EvalError::WrongValue {
+                expected: "boolean",
+                got: other.clone(),
+            }),
+        }
+    }
+
+    fn as_integer(&self) -> EvalResult<&Integer> {
+        match self {
+            Value::Int(i) => Ok(i),
+            other => Err(EvalError::WrongValue {
+                expected: "integer",
+                got: other.clone(),
+            }),
+        }
+    }
+
+    /// Returns the numeric value, converting integers to rationals.
+    fn as_rational(&self) -> EvalResult<Rational> {
+        match self {
+            Value::Int(i) => Ok(Rational::from(i)),
+            Value::Real(r) => Ok(r.clone()),
+            other => Err(EvalError::WrongValue {
+                expected: "numeric",
+                got: other.clone(),
+            }),
+        }
+    }
+
+    /// Returns the unsigned value and the width of a bit-vector.
+    fn as_bitvec(&self) -> EvalResult<(&Integer, &Integer)> {
+        match self {
+            Value::BitVec(v, w) => Ok((v, w)),
+            other => Err(EvalError::WrongValue {
+                expected: "bit-vector",
+                got: other.clone(),
+            }),
+        }
+    }
+
+    /// Returns the value and the order of a finite field element.
+    fn as_ff(&self) -> EvalResult<(&Integer, &Integer)> {
+        match self {
+            Value::FiniteField(v, p) => Ok((v, p)),
+            other => Err(EvalError::WrongValue {
+                expected: "finite field element",
+                got: other.clone(),
+            }),
+        }
+    }
+
+    /// Returns the sort of the elements and the elements of a sequence.
+    fn as_seq(&self) -> EvalResult<(&Sort, &[Value])> {
+        match self {
+            Value::Seq(sort, elements) => Ok((sort, elements)),
+            other => Err(EvalError::WrongValue {
+                expected: "sequence",
+                got: other.clone(),
+            }),
+        }
+    }
+
+    fn as_str(&self) -> EvalResult<&str> {
+        match self {
+            Value::String(s) => Ok(s),
+            other => Err(EvalError::WrongValue {
+                expected: "string",
+                got: other.clone(),
+            }),
+        }
+    }
+
+    /// Compares two values for equality. Integer and real values are compared by their numeric
+    /// value, to support `Int`/`Real` subtyping.
+    fn equals(&self, other: &Value) -> EvalResult<bool> {
+        match (self, other) {
+            (Value::Int(_) | Value::Real(_), Value::Int(_) | Value::Real(_)) => {
+                Ok(self.as_rational()? == other.as_rational()?)
+            }
+            _ => Ok(self == other),
+        }
+    }
+}
+
+/// Evaluates ground terms, using a set of function definitions to interpret free symbols.
+///
+/// Each definition maps a symbol name to its value. Symbols that take arguments are defined as a
+/// `lambda` term.
+pub struct Evaluator<'a> {
+    definitions: &'a IndexMap<String, Rc<Term>>,
+    abstract_values: &'a IndexMap<Rc<Term>, Vec<Rc<Term>>>,
+    bounds: IndexMap<String, usize>,
+    scopes: Vec<HashMap<String, Value>>,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Constructs a new evaluator. `abstract_values` contains, for each uninterpreted sort, the
+    /// constants that represent its elements.
+    pub fn new(
+        definitions: &'a IndexMap<String, Rc<Term>>,
+        abstract_values: &'a IndexMap<Rc<Term>, Vec<Rc<Term>>>,
+    ) -> Self {
+        Self {
+            definitions,
+            abstract_values,
+            bounds: IndexMap::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    /// Sets the bounds used to evaluate quantifiers, by sort name. Quantified variables of sort
+    /// `Int` or `Real` with bound `n` range over the integers from `-n` to `n`. For uninterpreted
+    /// sorts, the abstract values of the sort are used, and the bound limits how many of them are
+    /// considered. Variables of sort `Bool` always range over both boolean values.
+    pub fn with_quantifier_bounds(mut self, bounds: IndexMap<String, usize>) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Value> {
+        self.scopes.iter().rev().find_map(|s| s.get(name))
+    }
+
+    fn with_scope<T>(
+        &mut self,
+        scope: HashMap<String, Value>,
+        f: impl FnOnce(&mut Self) -> EvalResult<T>,
+    ) -> EvalResult<T> {
+        self.scopes.push(scope);
+        let result = f(self);
+        self.scopes.pop();
+        result
+    }
+
+    /// Evaluates the body of a definition, given the values of its parameters. The body can only
+    /// refer to its own parameters, so the values of variables bound outside of it are hidden.
+    fn eval_definition(
+        &mut self,
+        body: &Rc<Term>,
+        params: HashMap<String, Value>,
+    ) -> EvalResult<Value> {
+        let outer_scopes = std::mem::replace(&mut self.scopes, vec![params]);
+        let result = self.eval(body);
+        self.scopes = outer_scopes;
+        result
+    }
+
+    pub fn eval(&mut self, term: &Rc<Term>) -> EvalResult<Value> {
+        match term.as_ref() {
+            Term::Const(c) => match c {
+                Constant::Integer(i) => Ok(Value::Int(i.clone())),
+                Constant::Real(r) => Ok(Value::Real(r.clone())),
+                Constant::String(s) => Ok(Value::String(s.clone())),
+                Constant::BitVec(v, w) => Ok(Value::BitVec(v.clone(), w.clone())),
+                Constant::FiniteField(v, p) => Ok(Value::FiniteField(v.clone(), p.clone())),
+            },
+            Term::Var(name, sort) => {
+                if let Some(v) = self.lookup(name) {
+                    return Ok(v.clone());
+                }
+                if let Some(def) = self.definitions.get(name) {
+                    return self.eval_definition(&def.clone(), HashMap::new());
+                }
+                let is_abstract_value = self
+                    .abstract_values
+                    .get(sort)
+                    .is_some_and(|values| values.contains(term));
+                if is_abstract_value {
+                    Ok(Value::Abstract(term.clone()))
+                } else {
+                    Err(EvalError::UnknownSymbol(name.clone()))
+                }
+            }
+            Term::App(func, args) => {
+                let func = match func.as_ref() {
+                    Term::Var(name, _) => match self.definitions.get(name) {
+                        Some(def) => def.clone(),
+                        None => return Err(EvalError::UnknownSymbol(name.clone())),
+                    },
+                    _ => func.clone(),
+                };
+                let Some((Binder::Lambda, params, body)) = func.as_binder() else {
+                    return Err(EvalError::Unsupported(term.clone()));
+                };
+                let scope = params
+                    .iter()
+                    .zip(args)
+                    .map(|((name, _), arg)| Ok((name.clone(), self.eval(arg)?)))
+                    .collect::<EvalResult<_>>()?;
+                self.eval_definition(body, scope)
+            }
+            Term::Op(op, args) => self.eval_op(term, *op, args),
+            Term::Let(bindings, inner) => {
+                let scope = bindings
+                    .iter()
+                    .map(|(name, value)| Ok((name.clone(), self.eval(value)?)))
+                    .collect::<EvalResult<_>>()?;
+                self.with_scope(scope, |this| this.eval(inner))
+            }
+            Term::Binder(binder @ (Binder::Forall | Binder::Exists), bindings, inner) => {
+                self.eval_quantifier(term, *binder, bindings, inner)
+            }
+            Term::ParamOp {
+                op: ParamOperator::Divisible,
+                op_args,
+                args,
+            } => {
+                let n = op_args[0].as_integer().unwrap();
+                let value = self.eval(&args[0])?;
+                Ok(Value::Bool(value.as_integer()?.is_divisible(&n)))
+            }
+            Term::ParamOp {
+                op: ParamOperator::Int2Bv | ParamOperator::IntToBv,
+                op_args,
+                args,
+            } => {
+                let width = op_args[0].as_integer().unwrap();
+                let modulus = Integer::from(1) << width.to_u32().unwrap();
+                let value = self.eval(&args[0])?.as_integer()?.clone();
+                Ok(Value::BitVec(value.modulo(&modulus), width))
+            }
+            Term::ParamOp {
+                op: ParamOperator::SeqEmpty,
+                op_args,
+                ..
+            } => match op_args[0].as_sort() {
+                Some(Sort::Seq(element)) => {
+                    Ok(Value::Seq(element.as_sort().unwrap().clone(), Vec::new()))
+                }
+                _ => Err(EvalError::Unsupported(term.clone())),
+            },
+            Term::Sort(_) | Term::Binder(..) | Term::ParamOp { .. } => {
+                Err(EvalError::Unsupported(term.clone()))
+            }
+        }
+    }
+
+    /// Evaluates a disjunction (if `is_or` is `true`) or a conjunction of terms, each of which may
+    /// be negated. If some term determines the result, it is returned even if other terms can't be
+    /// evaluated.
+    fn eval_junction<'t>(
+        &mut self,
+        terms: impl IntoIterator<Item = (&'t Rc<Term>, bool)>,
+        is_or: bool,
+    ) -> EvalResult<bool> {
+        let mut error = None;
+        for (t, negated) in terms {
+            match self.eval(t).and_then(|v| v.as_bool()) {
+                Ok(b) if (b != negated) == is_or => return Ok(is_or),
+                Ok(_) => (),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        error.map_or(Ok(!is_or), Err)
+    }
+
+    /// Returns the values that quantified variables of the given sort range over, and whether these
+    /// are all the elements of the sort.
+    fn domain(&self, sort: &Rc<Term>) -> EvalResult<(Vec<Value>, bool)> {
+        let bound = self.bounds.get(&sort.to_string()).copied();
+        let result = match sort.as_sort() {
+            Some(Sort::Bool) => (vec![Value::Bool(false), Value::Bool(true)], true),
+            Some(Sort::Atom(..)) if self.abstract_values.contains_key(sort) => {
+                let values = &self.abstract_values[sort];
+                let n = bound.unwrap_or(values.len()).min(values.len());
+                let domain = values[..n].iter().cloned().map(Value::Abstract).collect();
+                (domain, n == values.len())
+            }
+            Some(Sort::Int | Sort::Real) if bound.is_some() => {
+                let n = bound.unwrap() as i64;
+                let is_int = sort.as_sort() == Some(&Sort::Int);
+                let domain = (-n..=n)
+                    .map(|i| {
+                        if is_int {
+                            Value::Int(i.into())
+                        } else {
+                            Value::Real(i.into())
+                        }
+                    })
+                    .collect();
+                (domain, false)
+            }
+            _ => return Err(EvalError::NoQuantifierBound(sort.clone())),
+        };
+        Ok(result)
+    }
+
+    /// Evaluates a quantifier by enumerating all combinations of values for its bound variables.
+    /// If the domain of some variable is incomplete and no instance determines the result, an
+    /// error is returned.
+    fn eval_quantifier(
+        &mut self,
+        term: &Rc<Term>,
+        binder: Binder,
+        bindings: &BindingList,
+        inner: &Rc<Term>,
+    ) -> EvalResult<Value> {
+        let is_exists = binder == Binder::Exists;
+        let mut domains = Vec::with_capacity(bindings.len());
+        let mut is_complete = true;
+        for (_, sort) in bindings {
+            let (domain, complete) = self.domain(sort)?;
+            is_complete &= complete;
+            domains.push(domain);
+        }
+        if domains.iter().any(Vec::is_empty) {
+            return if is_complete {
+                Ok(Value::Bool(!is_exists))
+            } else {
+                Err(EvalError::BoundsInsufficient(term.clone()))
+            };
+        }
+
+        // We enumerate the instances like an odometer, where each digit is an index into the
+        // domain of one of the variables
+        let mut indices = vec![0; domains.len()];
+        let mut error = None;
+        loop {
+            let scope = bindings
+                .iter()
+                .zip(&indices)
+                .zip(&domains)
+                .map(|(((name, _), &i), domain)| (name.clone(), domain[i].clone()))
+                .collect();
+            match self.with_scope(scope, |this| this.eval(inner)?.as_bool()) {
+                Ok(b) if b == is_exists => return Ok(Value::Bool(is_exists)),
+                Ok(_) => (),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+
+            let Some(pos) = (0..indices.len()).find(|&k| indices[k] + 1 < domains[k].len()) else {
+                break;
+            };
+            indices[pos] += 1;
+            indices[..pos].fill(0);
+        }
+        match error {
+            Some(e) => Err(e),
+            None if is_complete => Ok(Value::Bool(!is_exists)),
+            None => Err(EvalError::BoundsInsufficient(term.clone())),
+        }
+    }
+
+    fn eval_all(&mut self, args: &[Rc<Term>]) -> EvalResult<Vec<Value>> {
+        args.iter().map(|a| self.eval(a)).collect()
+    }
+
+    fn eval_op(&mut self, term: &Rc<Term>, op: Operator, args: &[Rc<Term>]) -> EvalResult<Value> {
+        // The boolean connectives and `ite` are evaluated lazily, so that we don't fail to evaluate
+        // a term because of a branch that doesn't affect its value
+        match op {
+            Operator::True => return Ok(Value::Bool(true)),
+            Operator::False => return Ok(Value::Bool(false)),
+            Operator::Not => return Ok(Value::Bool(!self.eval(&args[0])?.as_bool()?)),
+            Operator::And | Operator::Or => {
+                let is_or = op == Operator::Or;
+                let result = self.eval_junction(args.iter().map(|a| (a, false)), is_or)?;
+                return Ok(Value::Bool(result));
+            }
+            Operator::Implies => {
+                let (conclusion, antecedents) = args.split_last().unwrap();
+                let terms = antecedents
+                    .iter()
+                    .map(|a| (a, true))
+                    .chain(std::iter::once((conclusion, false)));
+                return Ok(Value::Bool(self.eval_junction(terms, true)?));
+            }
+            Operator::Ite => {
+                let branch = if self.eval(&args[0])?.as_bool()? {
+                    &args[1]
+                } else {
+                    &args[2]
+                };
+                return self.eval(branch);
+            }
+            _ => (),
+        }
+
+        let values = self.eval_all(args)?;
+        let result = match op {
+            Operator::Xor => {
+                let mut result = false;
+                for v in &values {
+                    result ^= v.as_bool()?;
+                }
+                Value::Bool(result)
+            }
+            Operator::Equals => {
+                let mut result = true;
+                for w in values.windows(2) {
+                    result &= w[0].equals(&w[1])?;
+                }
+                Value::Bool(result)
+            }
+            Operator::Distinct => {
+                for (i, a) in values.iter().enumerate() {
+                    for b in &values[i + 1..] {
+                        if a.equals(b)? {
+                            return Ok(Value::Bool(false));
+                        }
+                    }
+                }
+                Value::Bool(true)
+            }
+
+            Operator::Add | Operator::Sub | Operator::Mult => {
+                let is_int = values.iter().all(|v| matches!(v, Value::Int(_)));
+                let mut numbers = values
+                    .iter()
+                    .map(Value::as_rational)
+                    .collect::<EvalResult<Vec<_>>>()?;
+                let result = if op == Operator::Sub && numbers.len() == 1 {
+                    -numbers.pop().unwrap()
+                } else {
+                    let mut iter = numbers.into_iter();
+                    let first = iter.next().unwrap();
+                    iter.fold(first, |acc, x| match op {
+                        Operator::Add => acc + x,
+                        Operator::Sub => acc - x,
+                        _ => acc * x,
+                    })
+                };
+                if is_int {
+                    Value::Int(result.into_numer_denom().0)
+                } else {
+                    Value::Real(result)
+                }
+            }
+            Operator::IntDiv | Operator::Mod => {
+                let mut result = values[0].as_integer()?.clone();
+                for v in &values[1..] {
+                    let divisor = v.as_integer()?;
+                    if *divisor == 0 {
+                        return Err(EvalError::Unspecified(term.clone()));
+                    }
+                    // SMT-LIB uses euclidean division, in which the remainder is always
+                    // non-negative
+                    let (q, r) = result.div_rem_euc(divisor.clone());
+                    result = if op == Operator::IntDiv { q } else { r };
+                }
+                Value::Int(result)
+            }
+            Operator::RealDiv => {
+                let mut result = values[0].as_rational()?;
+                for v in &values[1..] {
+                    let divisor = v.as_rational()?;
+                    if divisor == 0 {
+                        return Err(EvalError::Unspecified(term.clone()));
+                    }
+                    result /= divisor;
+                }
+                Value::Real(result)
+            }
+            Operator::Abs => match &values[0] {
+                Value::Int(i) => Value::Int(i.clone().abs()),
+                other => Value::Real(other.as_rational()?.abs()),
+            },
+            Operator::LessThan | Operator::GreaterThan | Operator::LessEq | Operator::GreaterEq => {
+                let numbers = values
+                    .iter()
+                    .map(Value::as_rational)
+                    .collect::<EvalResult<Vec<_>>>()?;
+                let result = numbers.windows(2).all(|w| match op {
+                    Operator::LessThan => w[0] < w[1],
+                    Operator::GreaterThan => w[0] > w[1],
+                    Operator::LessEq => w[0] <= w[1],
+                    _ => w[0] >= w[1],
+                });
+                Value::Bool(result)
+            }
+            Operator::ToReal => Value::Real(values[0].as_rational()?),
+            Operator::ToInt => Value::Int(values[0].as_rational()?.floor().into_numer_denom().0),
+            Operator::IsInt => Value::Bool(values[0].as_rational()?.is_integer()),
+
+            Operator::StrConcat => {
+                let mut result = String::new();
+                for v in &values {
+                    result.push_str(v.as_str()?);
+                }
+                Value::String(result)
+            }
+            Operator::StrLen => Value::Int(values[0].as_str()?.chars().count().into()),
+            Operator::StrLessThan | Operator::StrLessEq => {
+                let (a, b) = (values[0].as_str()?, values[1].as_str()?);
+                Value::Bool(if op == Operator::StrLessThan {
+                    a < b
+                } else {
+                    a <= b
+                })
+            }
+            Operator::CharAt => {
+                let s = values[0].as_str()?;
+                let result = values[1]
+                    .as_integer()?
+                    .to_usize()
+                    .and_then(|i| s.chars().nth(i))
+                    .map(String::from)
+                    .unwrap_or_default();
+                Value::String(result)
+            }
+            Operator::Substring => {
+                let s: Vec<char> = values[0].as_str()?.chars().collect();
+                let (start, len) = (values[1].as_integer()?, values[2].as_integer()?);
+                let result = match (start.to_usize(), len.to_usize()) {
+                    (Some(start), Some(len)) if start < s.len() => s
+                        [start..s.len().min(start.saturating_add(len))]
+                        .iter()
+                        .collect(),
+                    _ => String::new(),
+                };
+                Value::String(result)
+            }
+            Operator::PrefixOf => Value::Bool(values[1].as_str()?.starts_with(values[0].as_str()?)),
+            Operator::SuffixOf => Value::Bool(values[1].as_str()?.ends_with(values[0].as_str()?)),
+            Operator::Contains => Value::Bool(values[0].as_str()?.contains(values[1].as_str()?)),
+            Operator::IndexOf => {
+                let s: Vec<char> = values[0].as_str()?.chars().collect();
+                let t: Vec<char> = values[1].as_str()?.chars().collect();
+                let result = values[2]
+                    .as_integer()?
+                    .to_usize()
+                    .filter(|&start| start <= s.len())
+                    .and_then(|start| {
+                        (start..=s.len().saturating_sub(t.len())).find(|&i| s[i..].starts_with(&t))
+                    })
+                    .map_or(Integer::from(-1), Integer::from);
+                Value::Int(result)
+            }
+            Operator::Replace => {
+                let (s, t, u) = (
+                    values[0].as_str()?,
+                    values[1].as_str()?,
+                    values[2].as_str()?,
+                );
+                Value::String(s.replacen(t, u, 1))
+            }
+            Operator::ReplaceAll => {
+                let (s, t, u) = (
+                    values[0].as_str()?,
+                    values[1].as_str()?,
+                    values[2].as_str()?,
+                );
+                let result = if t.is_empty() {
+                    s.to_owned()
+                } else {
+                    s.replace(t, u)
+                };
+                Value::String(result)
+            }
+            Operator::StrIsDigit => {
+                let s = values[0].as_str()?;
+                Value::Bool(s.chars().count() == 1 && s.chars().all(|c| c.is_ascii_digit()))
+            }
+            Operator::StrToCode => {
+                let s = values[0].as_str()?;
+                let mut chars = s.chars();
+                let result = match (chars.next(), chars.next()) {
+                    (Some(c), None) => Integer::from(u32::from(c)),
+                    _ => Integer::from(-1),
+                };
+                Value::Int(result)
+            }
+            Operator::StrFromCode => {
+                let result = values[0]
+                    .as_integer()?
+                    .to_u32()
+                    .and_then(char::from_u32)
+                    .map(String::from)
+                    .unwrap_or_default();
+                Value::String(result)
+            }
+            Operator::StrToInt => {
+                let s = values[0].as_str()?;
+                let result = if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+                    s.parse().unwrap()
+                } else {
+                    Integer::from(-1)
+                };
+                Value::Int(result)
+            }
+            Operator::StrFromInt => {
+                let i = values[0].as_integer()?;
+                let result = if *i < 0 { String::new() } else { i.to_string() };
+                Value::String(result)
+            }
+            Operator::StrUpdate => {
+                let mut s: Vec<char> = values[0].as_str()?.chars().collect();
+                let t = values[2].as_str()?;
+                if let Some(i) = values[1].as_integer()?.to_usize().filter(|&i| i < s.len()) {
+                    for (c, new) in s[i..].iter_mut().zip(t.chars()) {
+                        *c = new;
+                    }
+                }
+                Value::String(s.into_iter().collect())
+            }
+            Operator::StrRev => Value::String(values[0].as_str()?.chars().rev().collect()),
+            Operator::StrToLower => Value::String(values[0].as_str()?.to_ascii_lowercase()),
+            Operator::StrToUpper => Value::String(values[0].as_str()?.to_ascii_uppercase()),
+
+            Operator::SeqUnit => match values[0].sort() {
+                Some(sort) => Value::Seq(sort, values),
+                // Sequences of sequences are not supported
+                None => return Err(EvalError::Unsupported(term.clone())),
+            },
+            Operator::SeqConcat => {
+                let (sort, _) = values[0].as_seq()?;
+                let mut result = Vec::new();
+                for v in &values {
+                    result.extend_from_slice(v.as_seq()?.1);
+                }
+                Value::Seq(sort.clone(), result)
+            }
+            Operator::SeqLen => Value::Int(values[0].as_seq()?.1.len().into()),
+            Operator::SeqExtract | Operator::SeqAt => {
+                let (sort, s) = values[0].as_seq()?;
+                let start = values[1].as_integer()?;
+                let len = match op {
+                    Operator::SeqAt => Integer::from(1),
+                    _ => values[2].as_integer()?.clone(),
+                };
+                let result = match (start.to_usize(), len.to_usize()) {
+                    (Some(start), Some(len)) if start < s.len() => {
+                        s[start..s.len().min(start.saturating_add(len))].to_vec()
+                    }
+                    _ => Vec::new(),
+                };
+                Value::Seq(sort.clone(), result)
+            }
+            Operator::SeqNth => {
+                let s = values[0].as_seq()?.1;
+                match values[1].as_integer()?.to_usize().and_then(|i| s.get(i)) {
+                    Some(x) => x.clone(),
+                    None => return Err(EvalError::Unspecified(term.clone())),
+                }
+            }
+            Operator::SeqUpdate => {
+                let (sort, s) = values[0].as_seq()?;
+                let mut s = s.to_vec();
+                let t = values[2].as_seq()?.1;
+                if let Some(i) = values[1].as_integer()?.to_usize().filter(|&i| i < s.len()) {
+                    for (x, new) in s[i..].iter_mut().zip(t) {
+                        *x = new.clone();
+                    }
+                }
+                Value::Seq(sort.clone(), s)
+            }
+            Operator::SeqPrefixOf => {
+                Value::Bool(values[1].as_seq()?.1.starts_with(values[0].as_seq()?.1))
+            }
+            Operator::SeqSuffixOf => {
+                Value::Bool(values[1].as_seq()?.1.ends_with(values[0].as_seq()?.1))
+            }
+            Operator::SeqContains => {
+                let (s, t) = (values[0].as_seq()?.1, values[1].as_seq()?.1);
+                Value::Bool(t.is_empty() || s.windows(t.len()).any(|w| w == t))
+            }
+            Operator::SeqIndexOf => {
+                let (s, t) = (values[0].as_seq()?.1, values[1].as_seq()?.1);
+                let result = values[2]
+                    .as_integer()?
+                    .to_usize()
+                    .filter(|&start| start <= s.len())
+                    .and_then(|start| {
+                        (start..=s.len().saturating_sub(t.len())).find(|&i| s[i..].starts_with(t))
+                    })
+                    .map_or(Integer::from(-1), Integer::from);
+                Value::Int(result)
+            }
+            Operator::SeqReplace | Operator::SeqReplaceAll => {
+                let (sort, s) = values[0].as_seq()?;
+                let (t, u) = (values[1].as_seq()?.1, values[2].as_seq()?.1);
+                let mut result = Vec::new();
+                let mut i = 0;
+                let mut replaced = false;
+                while i < s.len() {
+                    let can_replace = !(replaced && op == Operator::SeqReplace);
+                    if can_replace && !t.is_empty() && s[i..].starts_with(t) {
+                        result.extend_from_slice(u);
+                        i += t.len();
+                        replaced = true;
+                    } else {
+                        result.push(s[i].clone());
+                        i += 1;
+                    }
+                }
+                // Like `str.replace`, replacing the empty sequence prepends `u` to the sequence
+                if t.is_empty() && op == Operator::SeqReplace {
+                    result.splice(0..0, u.iter().cloned());
+                }
+                Value::Seq(sort.clone(), result)
+            }
+            Operator::SeqRev => {
+                let (sort, s) = values[0].as_seq()?;
+                Value::Seq(sort.clone(), s.iter().rev().cloned().collect())
+            }
+
+            Operator::Bv2Nat | Operator::UbvToInt => Value::Int(values[0].as_bitvec()?.0.clone()),
+            Operator::SbvToInt => {
+                let (v, w) = values[0].as_bitvec()?;
+                let w = w.to_u32().unwrap();
+                if w > 0 && v.get_bit(w - 1) {
+                    Value::Int(v.clone() - (Integer::from(1) << w))
+                } else {
+                    Value::Int(v.clone())
+                }
+            }
+
+            Operator::FfAdd | Operator::FfMul | Operator::FfNeg | Operator::FfBitSum => {
+                let p = values[0].as_ff()?.1.clone();
+                let mut result = Integer::from(u8::from(op == Operator::FfMul));
+                for (i, v) in values.iter().enumerate() {
+                    let v = v.as_ff()?.0;
+                    match op {
+                        Operator::FfAdd => result += v,
+                        Operator::FfMul => result *= v,
+                        Operator::FfNeg => result -= v,
+                        _ => result += Integer::from(v << i as u32),
+                    }
+                }
+                Value::FiniteField(result.modulo(&p), p)
+            }
+            _ => return Err(EvalError::Unsupported(term.clone())),
+        };
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::tests::parse_terms;
+
+    #[test]
+    fn test_eval() {
+        let definitions = IndexMap::new();
+        let abstract_values = IndexMap::new();
+        let cases = [
+            ("(+ 1 2 3)", Value::Int(6.into())),
+            ("(- 5)", Value::Int((-5).into())),
+            ("(div (- 7) 2)", Value::Int((-4).into())),
+            ("(mod (- 7) 2)", Value::Int(1.into())),
+            ("(/ 1.0 4.0)", Value::Real(Rational::from((1, 4)))),
+            ("(< 1 2 3)", Value::Bool(true)),
+            ("(= (/ 4.0 2.0) 2.0)", Value::Bool(true)),
+            ("(distinct 1 2 1)", Value::Bool(false)),
+            ("(ite (> 1 2) 1 (abs (- 3)))", Value::Int(3.into())),
+            ("(=> false (= (div 1 0) 0))", Value::Bool(true)),
+            ("(xor true true true)", Value::Bool(true)),
+            ("(to_int (- 1.5))", Value::Int((-2).into())),
+            ("(str.++ \"ab\" \"c\")", Value::String("abc".to_owned())),
+            ("(str.len \"abc\")", Value::Int(3.into())),
+            ("(str.substr \"abcde\" 1 2)", Value::String("bc".to_owned())),
+            ("(str.indexof \"abcabc\" \"c\" 3)", Value::Int(5.into())),
+            ("(str.to_int \"012\")", Value::Int(12.into())),
+            ("(let ((x 2)) (* x x))", Value::Int(4.into())),
+            ("((_ divisible 3) (- 6))", Value::Bool(true)),
+            ("((_ divisible 4) (+ 6 1))", Value::Bool(false)),
+            ("(bv2nat #b101)", Value::Int(5.into())),
+            ("(sbv_to_int #b101)", Value::Int((-3).into())),
+            ("(ubv_to_int (_ bv12 4))", Value::Int(12.into())),
+            ("((_ int2bv 3) 13)", Value::BitVec(5.into(), 3.into())),
+            (
+                "((_ int_to_bv 4) (- 3))",
+                Value::BitVec(13.into(), 4.into()),
+            ),
+            ("(= ((_ int2bv 2) 7) #b11)", Value::Bool(true)),
+            ("(seq.len (seq.++ (seq.unit 1) (seq.unit 2)))", Value::Int(2.into())),
+            ("(seq.nth (seq.rev (seq.++ (seq.unit 1) (seq.unit 2))) 0)", Value::Int(2.into())),
+            (
+                "(seq.extract (seq.++ (seq.unit 1) (seq.unit 2) (seq.unit 3)) 1 5)",
+                Value::Seq(Sort::Int, vec![Value::Int(2.into()), Value::Int(3.into())]),
+            ),
+            (
+                "(seq.update (seq.++ (seq.unit 1) (seq.unit 2)) 1 (seq.++ (seq.unit 3) (seq.unit 4)))",
+                Value::Seq(Sort::Int, vec![Value::Int(1.into()), Value::Int(3.into())]),
+            ),
+            (
+                "(seq.indexof (seq.++ (seq.unit 1) (seq.unit 2) (seq.unit 1)) (seq.unit 1) 1)",
+                Value::Int(2.into()),
+            ),
+            (
+                "(seq.replace_all (seq.++ (seq.unit 1) (seq.unit 2) (seq.unit 1)) (seq.unit 1) \
+                    (as seq.empty (Seq Int)))",
+                Value::Seq(Sort::Int, vec![Value::Int(2.into())]),
+            ),
+            (
+                "(seq.prefixof (seq.unit true) (seq.++ (seq.unit true) (seq.unit false)))",
+                Value::Bool(true),
+            ),
+            ("(seq.at (as seq.empty (Seq Int)) 0)", Value::Seq(Sort::Int, Vec::new())),
+            ("(ff.add #f3m5 #f4m5)", Value::FiniteField(2.into(), 5.into())),
+            ("(ff.mul #f3m7 #f4m7 #f2m7)", Value::FiniteField(3.into(), 7.into())),
+            ("(ff.neg #f2m5)", Value::FiniteField(3.into(), 5.into())),
+            ("(ff.neg #f0m5)", Value::FiniteField(0.into(), 5.into())),
+            ("(ff.bitsum #f1m7 #f0m7 #f1m7)", Value::FiniteField(5.into(), 7.into())),
+            ("(= (ff.add #f4m5 #f1m5) (as ff0 (_ FiniteField 5)))", Value::Bool(true)),
+        ];
+        for (term, expected) in cases {
+            let mut pool = PrimitivePool::new();
+            let [term] = parse_terms(&mut pool, "", [term]);
+            let got = Evaluator::new(&definitions, &abstract_values).eval(&term);
+            assert_eq!(expected, got.unwrap(), "evaluating '{}'", term);
+        }
+
+        let mut pool = PrimitivePool::new();
+        let [term, nth] = parse_terms(&mut pool, "", ["(div 1 0)", "(seq.nth (seq.unit 1) 1)"]);
+        let got = Evaluator::new(&definitions, &abstract_values).eval(&term);
+        assert!(matches!(got, Err(EvalError::Unspecified(_))));
+        let got = Evaluator::new(&definitions, &abstract_values).eval(&nth);
+        assert!(matches!(got, Err(EvalError::Unspecified(_))));
+    }
+
+    #[test]
+    fn test_eval_cvc5_strings() {
+        let config = crate::parser::Config {
+            strings_profile: crate::parser::StringsProfile::Cvc5,
+            ..crate::parser::Config::new()
+        };
+        let cases = [
+            ("(str.update \"abcd\" 1 \"xy\")", "axyd"),
+            ("(str.update \"abcd\" 3 \"xy\")", "abcx"),
+            ("(str.update \"abcd\" 4 \"xy\")", "abcd"),
+            ("(str.update \"abcd\" (- 1) \"xy\")", "abcd"),
+            ("(str.rev \"abc\")", "cba"),
+            ("(str.to_lower \"aBc1\")", "abc1"),
+            ("(str.to_upper \"aBc1\")", "ABC1"),
+        ];
+        for (term, expected) in cases {
+            let mut pool = PrimitivePool::new();
+            let mut parser =
+                crate::parser::Parser::new(&mut pool, config, term.as_bytes()).unwrap();
+            let term = parser.parse_term().unwrap();
+            let got = evaluate(&term, &mut pool).unwrap();
+            assert_eq!(
+                Value::String(expected.to_owned()),
+                got,
+                "evaluating '{}'",
+                term
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut pool = PrimitivePool::new();
+        let [closed, open] = parse_terms(
+            &mut pool,
+            "(declare-const x Int)",
+            ["(str.++ \"a\" (str.from_int (* 2 21)))", "(+ x 1)"],
+        );
+        let value = evaluate(&closed, &mut pool).unwrap();
+        assert_eq!(value, Value::String("a42".to_owned()));
+        let [expected] = parse_terms(&mut pool, "", ["\"a42\""]);
+        assert_eq!(value.to_term(&mut pool), expected);
+
+        assert!(matches!(
+            evaluate(&open, &mut pool),
+            Err(EvalError::UnknownSymbol(x)) if x == "x",
+        ));
+    }
+}
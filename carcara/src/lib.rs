@@ -39,12 +39,23 @@ pub mod ast;
 pub mod benchmarking;
 pub mod checker;
 pub mod elaborator;
+pub mod error_code;
+pub mod eval;
+pub mod linear;
+pub mod model;
 pub mod parser;
 mod resolution;
+pub mod rewriting;
+pub mod shrink;
 mod utils;
 
-use crate::benchmarking::{CollectResults, OnlineBenchmarkResults, RunMeasurement};
+use crate::benchmarking::{
+    CollectResults, JsonString, LiteralStatistics, MetricDeltas, OnlineBenchmarkResults,
+    ProofMetrics, RunMeasurement,
+};
 use checker::{error::CheckerError, CheckerStatistics};
+use error_code::ErrorCode;
+use indexmap::IndexMap;
 use parser::{ParserError, Position};
 use std::io;
 use std::time::{Duration, Instant};
@@ -80,6 +91,104 @@ pub enum Error {
     // checker errors, so we model it as a different variant
     #[error("checker error: proof does not conclude empty clause")]
     DoesNotReachEmptyClause,
+
+    // The proof may have an empty clause elsewhere, but the checker was configured to require the
+    // last step of the proof to conclude it
+    #[error(
+        "checker error: every step is valid, but the last step '{0}' does not conclude the empty \
+        clause"
+    )]
+    FinalStepIsNotEmptyClause(String),
+
+    #[error(
+        "checker error: every step is valid, but the last step '{step}' does not conclude the goal \
+        '{goal}'"
+    )]
+    FinalStepIsNotGoal { step: String, goal: String },
+
+    #[error("step '{0}' does not exist in the proof")]
+    StepNotFound(String),
+
+    #[error(
+        "elaboration of step '{step}' in pass {pass} introduced {steps} steps, exceeding the limit \
+        of {limit}"
+    )]
+    ElaborationGrowth {
+        step: String,
+        pass: String,
+        steps: usize,
+        limit: usize,
+    },
+
+    #[error(
+        "step '{step}', introduced by pass {pass} when elaborating '{origin}', is falsified by the \
+        interpretation {assignment}"
+    )]
+    ElaborationUnsound {
+        step: String,
+        origin: String,
+        pass: String,
+        assignment: String,
+    },
+}
+
+impl Error {
+    /// Returns the stable code identifying this kind of error. See [`error_code`] for details.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::Io(_) => ErrorCode::new(1),
+            Error::Parser(e, _) => e.code(),
+            Error::Checker { inner, .. } => inner.code(),
+            Error::DoesNotReachEmptyClause => ErrorCode::new(2),
+            Error::StepNotFound(_) => ErrorCode::new(3),
+            Error::ElaborationGrowth { .. } => ErrorCode::new(4),
+            Error::FinalStepIsNotEmptyClause(_) => ErrorCode::new(5),
+            Error::FinalStepIsNotGoal { .. } => ErrorCode::new(6),
+            Error::ElaborationUnsound { .. } => ErrorCode::new(7),
+        }
+    }
+
+    /// Writes the error as a JSON object, with its code and message. For parser errors, the
+    /// object also contains the line and column where the error happened, and for checker errors,
+    /// the id and rule of the step that failed.
+    pub fn write_json(&self, dest: &mut dyn io::Write) -> io::Result<()> {
+        write!(
+            dest,
+            "{{\"code\": \"{}\", \"message\": {}",
+            self.code(),
+            JsonString(&self.to_string())
+        )?;
+        match self {
+            Error::Parser(_, (line, column)) => {
+                write!(dest, ", \"line\": {}, \"column\": {}", line, column)?;
+            }
+            Error::Checker { rule, step, .. } => {
+                write!(
+                    dest,
+                    ", \"step\": {}, \"rule\": {}",
+                    JsonString(step),
+                    JsonString(rule)
+                )?;
+            }
+            _ => (),
+        }
+        write!(dest, "}}")
+    }
+}
+
+/// Prints the statistics collected while checking a proof, which are shown with `--stats`.
+fn print_stats(
+    results: &OnlineBenchmarkResults,
+    literal_stats: Option<&LiteralStatistics>,
+    polyeq_cache: &ast::PolyeqCacheStats,
+) {
+    results.print(false);
+    if let Some(literal_stats) = literal_stats {
+        print!("{}", literal_stats);
+    }
+    if polyeq_cache.hits + polyeq_cache.misses > 0 {
+        print!("{}", polyeq_cache);
+    }
 }
 
 pub fn check<T: io::BufRead>(
@@ -95,6 +204,7 @@ pub fn check<T: io::BufRead>(
     let total = Instant::now();
     let (problem, proof, mut pool) = parser::parse_instance(problem, proof, parser_config)?;
     run_measures.parsing = total.elapsed();
+    let literal_stats = collect_stats.then(|| LiteralStatistics::from_proof(&pool, &proof));
 
     // Checking
     let checking = Instant::now();
@@ -105,29 +215,25 @@ pub fn check<T: io::BufRead>(
             polyeq_time: Duration::ZERO,
             assume_time: Duration::ZERO,
             assume_core_time: Duration::ZERO,
+            polyeq_cache: Default::default(),
             results: OnlineBenchmarkResults::new(),
         };
         let res = checker.check_with_stats(&problem, &proof, &mut checker_stats);
 
         run_measures.checking = checking.elapsed();
         run_measures.total = total.elapsed();
-
-        checker_stats.results.add_run_measurement(
-            &("this".to_owned(), 0),
-            RunMeasurement {
-                parsing: run_measures.parsing,
-                checking: run_measures.checking,
-                elaboration: run_measures.elaboration,
-                scheduling: run_measures.scheduling,
-                total: run_measures.total,
-                polyeq: checker_stats.polyeq_time,
-                assume: checker_stats.assume_time,
-                assume_core: checker_stats.assume_core_time,
-                elaboration_pipeline: Vec::new(),
-            },
+        run_measures.polyeq = checker_stats.polyeq_time;
+        run_measures.assume = checker_stats.assume_time;
+        run_measures.assume_core = checker_stats.assume_core_time;
+
+        checker_stats
+            .results
+            .add_run_measurement(&("this".to_owned(), 0), run_measures);
+        print_stats(
+            &checker_stats.results,
+            literal_stats.as_ref(),
+            &checker_stats.polyeq_cache,
         );
-        // Print the statistics
-        checker_stats.results.print(false);
 
         res
     } else {
@@ -152,6 +258,7 @@ pub fn check_parallel<T: io::BufRead>(
     let total = Instant::now();
     let (problem, proof, pool) = parser::parse_instance(problem, proof, parser_config)?;
     run_measures.parsing = total.elapsed();
+    let literal_stats = collect_stats.then(|| LiteralStatistics::from_proof(&pool, &proof));
 
     // Checking
     let checking = Instant::now();
@@ -171,29 +278,25 @@ pub fn check_parallel<T: io::BufRead>(
             polyeq_time: Duration::ZERO,
             assume_time: Duration::ZERO,
             assume_core_time: Duration::ZERO,
+            polyeq_cache: Default::default(),
             results: OnlineBenchmarkResults::new(),
         };
         let res = checker.check_with_stats(&problem, &proof, &scheduler, &mut checker_stats);
 
         run_measures.checking = checking.elapsed();
         run_measures.total = total.elapsed();
-
-        checker_stats.results.add_run_measurement(
-            &("this".to_owned(), 0),
-            RunMeasurement {
-                parsing: run_measures.parsing,
-                checking: run_measures.checking,
-                elaboration: run_measures.elaboration,
-                scheduling: run_measures.scheduling,
-                total: run_measures.total,
-                polyeq: checker_stats.polyeq_time,
-                assume: checker_stats.assume_time,
-                assume_core: checker_stats.assume_core_time,
-                elaboration_pipeline: Vec::new(),
-            },
+        run_measures.polyeq = checker_stats.polyeq_time;
+        run_measures.assume = checker_stats.assume_time;
+        run_measures.assume_core = checker_stats.assume_core_time;
+
+        checker_stats
+            .results
+            .add_run_measurement(&("this".to_owned(), 0), run_measures);
+        print_stats(
+            &checker_stats.results,
+            literal_stats.as_ref(),
+            &checker_stats.polyeq_cache,
         );
-        // Print the statistics
-        checker_stats.results.print(false);
 
         res
     } else {
@@ -201,6 +304,144 @@ pub fn check_parallel<T: io::BufRead>(
     }
 }
 
+/// Checks several proofs of the same problem. The problem is only parsed once, and all proofs share
+/// the same term pool (see [`parser::parse_problem_and_proofs`]).
+///
+/// If `num_threads` is greater than one, up to that many proofs are checked at the same time, each
+/// one in a single thread. This returns an error only if the problem fails to parse. Otherwise, it
+/// returns the result of checking each proof, in the order they were given.
+pub fn check_multiple<T: io::BufRead>(
+    problem: T,
+    proofs: Vec<T>,
+    parser_config: parser::Config,
+    checker_config: checker::Config,
+    num_threads: usize,
+    stack_size: usize,
+) -> Result<Vec<Result<bool, Error>>, Error> {
+    use crate::checker::Scheduler;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use std::thread;
+
+    let (problem, proofs, mut pool) =
+        parser::parse_problem_and_proofs(problem, proofs, parser_config)?;
+
+    if num_threads <= 1 {
+        let results = proofs
+            .into_iter()
+            .map(|proof| {
+                let mut checker = checker::ProofChecker::new(&mut pool, checker_config.clone());
+                checker.check(&problem, &proof?)
+            })
+            .collect();
+        return Ok(results);
+    }
+
+    // Proofs that failed to parse are not sent to the worker threads
+    let mut results: Vec<_> = Vec::with_capacity(proofs.len());
+    let mut parsed = Vec::new();
+    for (i, proof) in proofs.into_iter().enumerate() {
+        match proof {
+            Ok(proof) => {
+                parsed.push((i, proof));
+                results.push(None);
+            }
+            Err(e) => results.push(Some(Err(e))),
+        }
+    }
+
+    let pool = Arc::new(pool);
+    let next = AtomicUsize::new(0);
+    let (problem, parsed, next) = (&problem, &parsed, &next);
+    let checked: Vec<_> = thread::scope(|s| {
+        let workers: Vec<_> = (0..num_threads.min(parsed.len()))
+            .map(|_| {
+                let (pool, checker_config) = (pool.clone(), checker_config.clone());
+                s.spawn(move || {
+                    let mut results = Vec::new();
+                    while let Some((i, proof)) = parsed.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let (scheduler, context_usage) = Scheduler::new(1, proof);
+                        let result = checker::ParallelProofChecker::new(
+                            pool.clone(),
+                            checker_config.clone(),
+                            &problem.prelude,
+                            &context_usage,
+                            stack_size,
+                        )
+                        .check(problem, proof, &scheduler);
+                        results.push((*i, result));
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().unwrap())
+            .collect()
+    });
+    for (i, result) in checked {
+        results[i] = Some(result);
+    }
+    Ok(results.into_iter().map(Option::unwrap).collect())
+}
+
+/// The result of `check_refutations`: the id of each refutation in the proof, and the result of
+/// checking it.
+pub type RefutationResults = Vec<(String, Result<bool, Error>)>;
+
+/// Checks each refutation in a proof separately (see [`ast::find_refutations`]). This is useful
+/// for proofs that concatenate the refutations of several goals, where one of them failing should
+/// not prevent the others from being checked. Steps that are not used by any refutation are not
+/// checked.
+///
+/// This returns an error if the proof fails to parse, or if it has no step concluding the empty
+/// clause. Otherwise, it returns the id of each refutation and the result of checking it, in the
+/// order they appear in the proof.
+pub fn check_refutations<T: io::BufRead>(
+    problem: T,
+    proof: T,
+    parser_config: parser::Config,
+    checker_config: checker::Config,
+) -> Result<RefutationResults, Error> {
+    let (problem, proof, mut pool) = parser::parse_instance(problem, proof, parser_config)?;
+
+    let refutations = ast::find_refutations(&proof);
+    if refutations.is_empty() {
+        return Err(Error::DoesNotReachEmptyClause);
+    }
+    let results = refutations
+        .into_iter()
+        .map(|refutation| {
+            if !refutation.shared_steps.is_empty() {
+                log::warn!(
+                    "refutation '{}' shares {} steps with other refutations, starting with '{}'",
+                    refutation.id,
+                    refutation.shared_steps.len(),
+                    refutation.shared_steps[0],
+                );
+            }
+            let mut checker = checker::ProofChecker::new(&mut pool, checker_config.clone());
+            let result = checker.check(&problem, &refutation.proof);
+            (refutation.id, result)
+        })
+        .collect();
+    Ok(results)
+}
+
+/// The result of `check_and_elaborate`: whether the proof is holey, the problem, the elaborated
+/// proof, the term pool, and the map from ids in the elaborated proof to the original ids (see
+/// [`elaborator::id_map`]).
+pub type ElaborationResult = (
+    bool,
+    ast::Problem,
+    ast::Proof,
+    ast::PrimitivePool,
+    IndexMap<String, String>,
+);
+
 pub fn check_and_elaborate<T: io::BufRead>(
     problem: T,
     proof: T,
@@ -209,15 +450,19 @@ pub fn check_and_elaborate<T: io::BufRead>(
     elaborator_config: elaborator::Config,
     pipeline: Vec<elaborator::ElaborationStep>,
     collect_stats: bool,
-) -> Result<(bool, ast::Problem, ast::Proof, ast::PrimitivePool), Error> {
+) -> Result<ElaborationResult, Error> {
     let mut run: RunMeasurement = RunMeasurement::default();
 
     // Parsing
     let total = Instant::now();
     let (problem, proof, mut pool) = parser::parse_instance(problem, proof, parser_config)?;
     run.parsing = total.elapsed();
+    let literal_stats = collect_stats.then(|| LiteralStatistics::from_proof(&pool, &proof));
 
+    let input_metrics =
+        collect_stats.then(|| ProofMetrics::new(&mut pool, &problem.prelude, &proof));
     let mut stats = OnlineBenchmarkResults::new();
+    let mut polyeq_cache = ast::PolyeqCacheStats::default();
 
     // Checking
     let checking = Instant::now();
@@ -228,6 +473,7 @@ pub fn check_and_elaborate<T: io::BufRead>(
             polyeq_time: Duration::ZERO,
             assume_time: Duration::ZERO,
             assume_core_time: Duration::ZERO,
+            polyeq_cache: Default::default(),
             results: std::mem::take(&mut stats),
         };
 
@@ -236,6 +482,7 @@ pub fn check_and_elaborate<T: io::BufRead>(
         run.polyeq = checker_stats.polyeq_time;
         run.assume = checker_stats.assume_time;
         run.assume_core = checker_stats.assume_core_time;
+        polyeq_cache = checker_stats.polyeq_cache;
 
         stats = checker_stats.results;
         res
@@ -247,9 +494,10 @@ pub fn check_and_elaborate<T: io::BufRead>(
     let elaboration = Instant::now();
 
     let node = ast::ProofNode::from_commands(proof.commands);
-    let (elaborated, pipeline_durations) =
-        elaborator::Elaborator::new(&mut pool, &problem, elaborator_config)
-            .elaborate_with_stats(&node, pipeline);
+    let mut elaborator = elaborator::Elaborator::new(&mut pool, &problem, elaborator_config);
+    let (elaborated, pipeline_durations) = elaborator.elaborate_with_stats(&node, pipeline)?;
+    let lia_stats = elaborator.lia_generic_stats().clone();
+    let id_map = elaborator::id_map(&node, &elaborated);
     let elaborated = ast::Proof {
         commands: elaborated.into_commands(),
         ..proof
@@ -262,10 +510,65 @@ pub fn check_and_elaborate<T: io::BufRead>(
 
         stats.add_run_measurement(&("this".to_owned(), 0), run);
 
-        stats.print(false);
+        print_stats(&stats, literal_stats.as_ref(), &polyeq_cache);
+        if lia_stats.solver_runs > 0 {
+            print!("{}", lia_stats);
+        }
+        if let Some(before) = input_metrics {
+            let after = ProofMetrics::new(&mut pool, &problem.prelude, &elaborated);
+            print!("{}", MetricDeltas { before, after });
+        }
+    }
+
+    Ok((checking_result, problem, elaborated, pool, id_map))
+}
+
+/// Extracts the conclusions of the steps in a proof as an SMT-LIB script, which declares the
+/// problem's sorts and functions and asserts each conclusion. A conclusion with more than one
+/// literal is asserted as a disjunction. Each distinct lemma is only asserted once, and the empty
+/// clause is not included.
+///
+/// If `rules` is not empty, only the conclusions of steps that use one of these rules are
+/// extracted. Steps inside subproofs are never included, since their conclusions may refer to
+/// variables bound by the subproof.
+pub fn extract_lemmas<T: io::BufRead>(
+    problem: T,
+    proof: T,
+    config: parser::Config,
+    rules: &[String],
+    use_sharing: bool,
+) -> Result<String, Error> {
+    use ast::TermPool;
+    use indexmap::IndexSet;
+
+    let (problem, proof, mut pool) = parser::parse_instance(problem, proof, config)?;
+
+    let mut lemmas = IndexSet::new();
+    for command in &proof.commands {
+        let ast::ProofCommand::Step(step) = command else {
+            continue;
+        };
+        if !rules.is_empty() && !rules.contains(&step.rule) {
+            continue;
+        }
+        let lemma = match step.clause.as_slice() {
+            [] => continue,
+            [literal] => literal.clone(),
+            clause => pool.add(ast::Term::Op(ast::Operator::Or, clause.to_vec())),
+        };
+        lemmas.insert(lemma);
     }
 
-    Ok((checking_result, problem, elaborated, pool))
+    let mut bytes = format!("{}", problem.prelude).into_bytes();
+    let lemmas: Vec<_> = lemmas.into_iter().collect();
+    ast::printer::write_smt_assertions(
+        &mut pool,
+        &problem.prelude,
+        &mut bytes,
+        &lemmas,
+        use_sharing,
+    )?;
+    Ok(String::from_utf8(bytes).unwrap())
 }
 
 pub fn generate_lia_smt_instances<T: io::BufRead>(
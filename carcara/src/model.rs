@@ -0,0 +1,144 @@
+//! Checking models produced by SMT solvers.
+//!
+//! When a solver answers `sat`, it can usually produce a model that witnesses the satisfiability
+//! of the problem. This module checks such a model by evaluating every assertion in the problem
+//! under it, which complements checking refutation proofs of `unsat` answers.
+
+use crate::{
+    ast::*,
+    eval::{EvalError, Evaluator, Value},
+};
+use indexmap::IndexMap;
+
+/// A model, as produced by the `get-model` SMT-LIB command.
+#[derive(Debug, Clone, Default)]
+pub struct Model {
+    /// The value of each symbol defined by the model. Symbols that take arguments are defined as
+    /// a `lambda` term.
+    pub definitions: IndexMap<String, Rc<Term>>,
+
+    /// The abstract values declared by the model for each uninterpreted sort. Each abstract value
+    /// is a constant term that represents a distinct element of the sort.
+    pub abstract_values: IndexMap<Rc<Term>, Vec<Rc<Term>>>,
+}
+
+/// The result of evaluating an assertion under a model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssertionStatus {
+    /// The assertion evaluates to `true`.
+    Satisfied,
+
+    /// The assertion evaluates to `false`.
+    Falsified,
+
+    /// The assertion could not be evaluated. The string describes the reason.
+    Unknown(String),
+}
+
+/// Evaluates every assertion in `problem` under `model`, returning the status of each assertion.
+///
+/// Quantified assertions are evaluated by enumerating the values of their bound variables, using
+/// the given bounds for each sort name. Variables of sort `Int` or `Real` with bound `n` range
+/// over the integers from `-n` to `n`, and variables of uninterpreted sorts range over the
+/// abstract values declared in the model (up to the bound, if one is given). If the bounds are not
+/// enough to determine the value of an assertion, its status is `AssertionStatus::Unknown`.
+///
+/// Function definitions in the problem should be expanded when parsing it (see
+/// `parser::Config::apply_function_defs`), since the model usually doesn't include them.
+pub fn check_model(
+    problem: &Problem,
+    model: &Model,
+    quantifier_bounds: IndexMap<String, usize>,
+) -> Vec<(Rc<Term>, AssertionStatus)> {
+    let mut evaluator = Evaluator::new(&model.definitions, &model.abstract_values)
+        .with_quantifier_bounds(quantifier_bounds);
+    problem
+        .premises
+        .iter()
+        .map(|assertion| {
+            let status = match evaluator.eval(assertion) {
+                Ok(Value::Bool(true)) => AssertionStatus::Satisfied,
+                Ok(Value::Bool(false)) => AssertionStatus::Falsified,
+                Ok(other) => AssertionStatus::Unknown(
+                    EvalError::WrongValue { expected: "boolean", got: other }.to_string(),
+                ),
+                Err(e) => AssertionStatus::Unknown(e.to_string()),
+            };
+            (assertion.clone(), status)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn test_check_model() {
+        let problem: &[u8] = b"
+            (declare-sort U 0)
+            (declare-fun f (Int) Int)
+            (declare-const x Int)
+            (declare-const s String)
+            (declare-const u U)
+            (declare-const v U)
+            (define-fun g ((a Int)) Int (+ a 1))
+            (assert (= (f x) (g 2)))
+            (assert (> (str.len s) x))
+            (assert (not (= u v)))
+            (assert (= x 1))
+            (assert (forall ((y Int)) (> (f y) 0)))
+            (assert (forall ((y Int)) (< (f y) 4)))
+            (assert (forall ((w U)) (or (= w u) (= w v))))
+            (assert (exists ((w U) (b Bool)) (and b (not (= w u)))))
+        ";
+        let model: &[u8] = b"
+            sat
+            (
+              (define-fun x () Int 2)
+              (define-fun s () String \"abc\")
+              (declare-fun U!val!0 () U)
+              (declare-fun U!val!1 () U)
+              (define-fun u () U U!val!0)
+              (define-fun v () U U!val!1)
+              (define-fun f ((x!0 Int)) Int (ite (= x!0 2) 3 (- 1)))
+            )
+        ";
+        let config = parser::Config {
+            apply_function_defs: true,
+            ..parser::Config::new()
+        };
+        let (problem, model, _) = parser::parse_problem_and_model(problem, model, config).unwrap();
+
+        let got: Vec<_> = check_model(&problem, &model, IndexMap::new())
+            .into_iter()
+            .map(|(_, status)| status)
+            .collect();
+        assert_eq!(
+            got[..4],
+            [
+                AssertionStatus::Satisfied,
+                AssertionStatus::Satisfied,
+                AssertionStatus::Satisfied,
+                AssertionStatus::Falsified,
+            ]
+        );
+        assert!(matches!(got[4], AssertionStatus::Unknown(_)));
+        assert!(matches!(got[5], AssertionStatus::Unknown(_)));
+        assert_eq!(
+            got[6..],
+            [AssertionStatus::Satisfied, AssertionStatus::Satisfied]
+        );
+
+        // With bounds, a counterexample can be found for the first quantified assertion, but the
+        // second one is still unknown, since no finite bound can show that it holds
+        let bounds = [("Int".to_owned(), 3)].into_iter().collect();
+        let got: Vec<_> = check_model(&problem, &model, bounds)
+            .into_iter()
+            .map(|(_, status)| status)
+            .collect();
+        assert_eq!(got[4], AssertionStatus::Falsified);
+        assert!(matches!(&got[5], AssertionStatus::Unknown(r) if r.contains("insufficient")));
+    }
+}
@@ -2,10 +2,11 @@
 
 use crate::{
     ast::{Constant, PrimitivePool, Rc, Sort, Term, TermPool},
-    parser::Token,
+    error_code::ErrorCode,
+    parser::{Position, Token, UndeclaredSymbols},
     utils::Range,
 };
-use rug::Integer;
+use rug::{integer::IsPrime, Integer};
 use std::fmt;
 use thiserror::Error;
 
@@ -49,6 +50,10 @@ pub enum ParserError {
     #[error("bitvector literal is too large")]
     TooLargeBitvector,
 
+    /// The lexer encountered a malformed finite field literal, e.g. `#f3` or `#fm7`.
+    #[error("invalid finite field literal: '{0}'")]
+    InvalidFiniteFieldLiteral(String),
+
     /// The parser encountered an unexpected token.
     #[error("unexpected token: '{0}'")]
     UnexpectedToken(Token),
@@ -85,6 +90,15 @@ pub enum ParserError {
     #[error("step id '{0}' is not defined")]
     UndefinedStepId(String),
 
+    /// A premise references a command inside a subproof that was already closed. Only the last
+    /// step of a subproof can be referenced from outside it.
+    #[error("step '{0}' is inside the subproof closed by step '{1}', and can't be referenced")]
+    PremiseInClosedSubproof(String, String),
+
+    /// A step discharges a command that is not local to the current subproof.
+    #[error("discharged command '{0}' is not in the current subproof")]
+    NonLocalDischarge(String),
+
     /// The wrong number of arguments was given to a function, operator or sort.
     #[error("expected {0} arguments, got {1}")]
     WrongNumberOfArgs(Range, usize),
@@ -96,9 +110,10 @@ pub enum ParserError {
     #[error("extract arguments do not follow restrictions. Expected: {2} > {0} and {0} >= {1} and {1} >= 0")]
     InvalidExtractArgs(usize, usize, usize),
 
-    /// A step id was used in more than one step.
-    #[error("step id '{0}' was repeated")]
-    RepeatedStepId(String),
+    /// A step id was used in more than one step. The line and column where the id was first
+    /// defined are also included.
+    #[error("step id '{0}' was repeated (first defined on line {1}, column {2})")]
+    RepeatedStepId(String, usize, usize),
 
     /// The number given as the arity in a `declare-sort` command is too large. This only happens
     /// if the number is too big to fit in a `usize`, so it almost never happens.
@@ -117,6 +132,10 @@ pub enum ParserError {
     #[error("subproof '{0}' was not closed")]
     UnclosedSubproof(String),
 
+    /// A command in a legacy veriT proof has no `:conclusion` attribute.
+    #[error("command '{0}' has no conclusion")]
+    MissingConclusion(String),
+
     /// The parser encountered an unknown indexed operator.
     #[error("not a valid indexed operator: '{0}'")]
     InvalidIndexedOp(String),
@@ -124,6 +143,105 @@ pub enum ParserError {
     /// The parser encountered an unknown qualified operator.
     #[error("not a valid qualified operator: '{0}'")]
     InvalidQualifiedOp(String),
+
+    /// When inferring declarations from a proof, a term was used with two conflicting sorts.
+    #[error("conflicting sorts inferred for {0}: expected '{1}', got '{2}'")]
+    InferredSortConflict(String, String, String),
+
+    /// When inferring declarations from a proof, a function or sort was used with different
+    /// numbers of arguments.
+    #[error("'{0}' is used with {1} arguments, but was previously used with {2}")]
+    InconsistentArity(String, usize, usize),
+
+    /// A proof declares a version of the Alethe format that is not supported.
+    #[error("unknown Alethe version: '{0}'")]
+    UnknownAletheVersion(String),
+
+    /// Expected a floating-point sort.
+    #[error("expected floating-point sort, got '{0}'")]
+    ExpectedFpSort(Sort),
+
+    /// Expected a finite field sort.
+    #[error("expected finite field sort, got '{0}'")]
+    ExpectedFfSort(Sort),
+
+    /// The order of a finite field is not a prime number.
+    #[error("finite field order must be prime, got {0}")]
+    NonPrimeFieldOrder(Integer),
+
+    /// A datatype declaration declares a parametric datatype, which is not supported.
+    #[error("parametric datatypes are not supported: '{0}'")]
+    ParametricDatatype(String),
+
+    /// The argument of a datatype tester is not a constructor.
+    #[error("'{0}' is not a datatype constructor")]
+    NotAConstructor(String),
+
+    /// A step using the `input` rule, which is an alias for `assume`, has premises, arguments,
+    /// discharged assumptions or an empty conclusion.
+    #[error("step '{0}' uses the `input` rule, but is not a valid assumption")]
+    InvalidInputStep(String),
+
+    /// The proof uses functions or sorts that are not declared in the problem. This usually means
+    /// that the proof is being checked against the wrong problem.
+    #[error("proof references {0} not declared in problem")]
+    ProblemMismatch(UndeclaredSymbols),
+
+    /// A RARE rule file defines the same rule more than once.
+    #[error("RARE rule '{0}' was defined more than once")]
+    RepeatedRareRule(String),
+}
+
+impl ParserError {
+    /// Returns the stable code identifying this kind of error.
+    pub fn code(&self) -> ErrorCode {
+        let number = match self {
+            Self::UnexpectedChar(..) => 100,
+            Self::LeadingZero(..) => 101,
+            Self::DivisionByZeroInLiteral(..) => 102,
+            Self::BackslashInQuotedSymbol => 103,
+            Self::EofInQuotedSymbol => 104,
+            Self::EofInString => 105,
+            Self::InvalidUnicode(..) => 106,
+            Self::EmptyBitvector => 107,
+            Self::TooLargeBitvector => 108,
+            Self::UnexpectedToken(..) => 109,
+            Self::EmptySequence => 110,
+            Self::SortError(..) => 111,
+            Self::ExpectedBvSort(..) => 112,
+            Self::ExpectedIntegerConstant(..) => 113,
+            Self::NotAFunction(..) => 114,
+            Self::UndefinedIden(..) => 115,
+            Self::UndefinedSort(..) => 116,
+            Self::UndefinedStepId(..) => 117,
+            Self::WrongNumberOfArgs(..) => 118,
+            Self::WrongValueOfArgs(..) => 119,
+            Self::InvalidExtractArgs(..) => 120,
+            Self::RepeatedStepId(..) => 121,
+            Self::InvalidSortArity(..) => 122,
+            Self::EmptySubproof(..) => 123,
+            Self::LastSubproofStepIsNotStep(..) => 124,
+            Self::UnclosedSubproof(..) => 125,
+            Self::MissingConclusion(..) => 126,
+            Self::InvalidIndexedOp(..) => 127,
+            Self::InvalidQualifiedOp(..) => 128,
+            Self::InferredSortConflict(..) => 129,
+            Self::InconsistentArity(..) => 130,
+            Self::UnknownAletheVersion(..) => 131,
+            Self::ExpectedFpSort(..) => 132,
+            Self::ParametricDatatype(..) => 133,
+            Self::NotAConstructor(..) => 134,
+            Self::InvalidInputStep(..) => 135,
+            Self::PremiseInClosedSubproof(..) => 136,
+            Self::NonLocalDischarge(..) => 137,
+            Self::RepeatedRareRule(..) => 138,
+            Self::ProblemMismatch(..) => 139,
+            Self::InvalidFiniteFieldLiteral(..) => 140,
+            Self::ExpectedFfSort(..) => 141,
+            Self::NonPrimeFieldOrder(..) => 142,
+        };
+        ErrorCode::new(number)
+    }
 }
 
 /// Returns an error if the length of `sequence` is not in the `expected` range.
@@ -155,6 +273,35 @@ where
     Ok(())
 }
 
+/// Returns an error if the sorts in `sequence` are not all the same floating-point sort.
+pub fn assert_fp_sorts(sequence: &[&Sort]) -> Result<(), ParserError> {
+    for s in sequence {
+        if !matches!(s, Sort::FloatingPoint(..)) {
+            return Err(ParserError::ExpectedFpSort((*s).clone()));
+        }
+    }
+    Ok(SortError::assert_all_eq(sequence)?)
+}
+
+/// Returns an error if `order` is not a prime number, and therefore not a valid finite field order.
+pub fn assert_prime_order(order: &Integer) -> Result<(), ParserError> {
+    if order.is_probably_prime(30) == IsPrime::No {
+        return Err(ParserError::NonPrimeFieldOrder(order.clone()));
+    }
+    Ok(())
+}
+
+/// Returns an error if any sort in `sequence` is not a finite field sort, or if they are not all
+/// the same sort.
+pub fn assert_ff_sorts(sequence: &[&Sort]) -> Result<(), ParserError> {
+    for s in sequence {
+        if !matches!(s, Sort::FiniteField(_)) {
+            return Err(ParserError::ExpectedFfSort((*s).clone()));
+        }
+    }
+    Ok(SortError::assert_all_eq(sequence)?)
+}
+
 /// An error in sort checking.
 #[derive(Debug, Error)]
 pub struct SortError {
@@ -163,10 +310,32 @@ pub struct SortError {
 
     /// The sort we got.
     pub got: Sort,
+
+    /// If the error happened when sort checking the arguments of an operator or function
+    /// application, the index of the argument whose sort was wrong.
+    pub argument: Option<usize>,
+
+    /// The operator or function being applied, and where the argument is in the source.
+    pub context: Option<Box<SortErrorContext>>,
+}
+
+/// The application in which a sort error happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortErrorContext {
+    /// The operator or function being applied.
+    pub function: String,
+
+    /// The positions of the first and last tokens of the argument whose sort was wrong.
+    pub span: (Position, Position),
 }
 
 impl fmt::Display for SortError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.context, self.argument) {
+            (Some(c), Some(i)) => write!(f, "in argument {} of '{}': ", i + 1, c.function)?,
+            (None, Some(i)) => write!(f, "in argument {}: ", i + 1)?,
+            _ => (),
+        }
         match self.expected.as_slice() {
             [] => unreachable!(),
             [p] => write!(f, "expected '{}', got '{}'", p, self.got),
@@ -182,22 +351,36 @@ impl fmt::Display for SortError {
 }
 
 impl SortError {
+    fn new(expected: Vec<Sort>, got: &Sort) -> Self {
+        Self {
+            expected,
+            got: got.clone(),
+            argument: None,
+            context: None,
+        }
+    }
+
+    /// Records that this error happened when checking the argument with index `i`. If the index
+    /// of the argument was already recorded, it is kept.
+    pub(crate) fn at(mut self, i: usize) -> Self {
+        self.argument.get_or_insert(i);
+        self
+    }
+
     /// Returns a sort error if `got` does not equal `expected`.
     pub(crate) fn assert_eq(expected: &Sort, got: &Sort) -> Result<(), Self> {
         if expected == got {
             Ok(())
         } else {
-            Err(Self {
-                expected: vec![expected.clone()],
-                got: got.clone(),
-            })
+            Err(Self::new(vec![expected.clone()], got))
         }
     }
 
-    /// Makes sure all terms in `sequence` are equal to each other, otherwise returns an error.
+    /// Makes sure all terms in `sequence` are equal to each other, otherwise returns an error. The
+    /// error points to the first term that differs from the one before it.
     pub(crate) fn assert_all_eq(sequence: &[&Sort]) -> Result<(), Self> {
         for i in 1..sequence.len() {
-            Self::assert_eq(sequence[i - 1], sequence[i])?;
+            Self::assert_eq(sequence[i - 1], sequence[i]).map_err(|e| e.at(i))?;
         }
         Ok(())
     }
@@ -207,10 +390,7 @@ impl SortError {
         if possibilities.contains(got) {
             Ok(())
         } else {
-            Err(Self {
-                expected: possibilities.to_vec(),
-                got: got.clone(),
-            })
+            Err(Self::new(possibilities.to_vec(), got))
         }
     }
 
@@ -228,13 +408,55 @@ impl SortError {
             vec![Sort::Array(key, value)]
         };
         let Sort::Array(got_key, got_value) = got else {
-            return Err(Self { expected, got: got.clone() });
+            return Err(Self::new(expected, got));
         };
         if key.is_some_and(|k| got_key.as_sort().unwrap() != k)
             || value.is_some_and(|v| got_value.as_sort().unwrap() != v)
         {
-            return Err(Self { expected, got: got.clone() });
+            return Err(Self::new(expected, got));
         }
         Ok(())
     }
+
+    /// Returns a sort error if `got` is not a set sort. If `element` is given, the elements of the
+    /// set must also be of that sort.
+    pub(crate) fn assert_set_sort(
+        pool: &mut PrimitivePool,
+        element: Option<&Sort>,
+        got: &Sort,
+    ) -> Result<(), Self> {
+        let any = Sort::Atom("?".to_owned(), Vec::new());
+        let expected = vec![Sort::Set(
+            pool.add(Term::Sort(element.cloned().unwrap_or(any))),
+        )];
+        match got {
+            Sort::Set(got_element)
+                if element.map_or(true, |e| got_element.as_sort().unwrap() == e) =>
+            {
+                Ok(())
+            }
+            _ => Err(Self::new(expected, got)),
+        }
+    }
+
+    /// Returns a sort error if `got` is not a sequence sort. If `element` is given, the elements of
+    /// the sequence must also be of that sort.
+    pub(crate) fn assert_seq_sort(
+        pool: &mut PrimitivePool,
+        element: Option<&Sort>,
+        got: &Sort,
+    ) -> Result<(), Self> {
+        let any = Sort::Atom("?".to_owned(), Vec::new());
+        let expected = vec![Sort::Seq(
+            pool.add(Term::Sort(element.cloned().unwrap_or(any))),
+        )];
+        match got {
+            Sort::Seq(got_element)
+                if element.map_or(true, |e| got_element.as_sort().unwrap() == e) =>
+            {
+                Ok(())
+            }
+            _ => Err(Self::new(expected, got)),
+        }
+    }
 }
@@ -0,0 +1,891 @@
+//! Synthesis of a problem prelude from the terms of a proof.
+//!
+//! When the original problem of a proof is not available, the sorts and functions used in the
+//! proof are not declared anywhere. This module infers these declarations by reading the proof as a
+//! sequence of s-expressions, and running a simple unification-based sort inference on its terms:
+//! each undeclared function gets a sort variable for each of its arguments and for its result, and
+//! these are constrained by the operators and functions that use them. Sort variables that are not
+//! constrained at all are assigned a fresh uninterpreted sort.
+//!
+//! The constraints imposed by operators are not described here. Instead, each operator application
+//! is sort checked by the parser itself, with placeholder sorts standing in for the sort variables.
+//! Whenever the check fails because of a placeholder, the sort the parser expected is unified with
+//! it, and the check is tried again.
+
+use super::{Config, Lexer, Parser, ParserError, Position, Reserved, Token};
+use crate::{
+    ast::{Operator, PrimitivePool, Rc, Sort, Term, TermPool},
+    CarcaraResult, Error,
+};
+use indexmap::IndexMap;
+use rug::Integer;
+use std::{collections::HashMap, fmt, io::BufRead, str::FromStr};
+
+/// The name of the uninterpreted sort given to unconstrained sort variables. If this name is
+/// already in use, a numeric suffix is added to it.
+const DEFAULT_SORT_NAME: &str = "InferredSort";
+
+/// The prefix of the names of the placeholder sorts that stand for sort variables when an operator
+/// application is sort checked by the parser. Since it contains a space, it can't clash with the
+/// name of a declared sort.
+const PLACEHOLDER_PREFIX: &str = "?sort ";
+
+/// The name the parser uses for the unconstrained parts of an expected sort, like the element sort
+/// in `(Array Int ?)`.
+const ANY_SORT_NAME: &str = "?";
+
+/// The maximum number of times an operator application is sort checked while inferring the sorts
+/// of its arguments. Each failed check binds at least one sort variable, so this is only reached
+/// when the parser keeps expecting sorts with unconstrained parts.
+const MAX_SORT_CHECKS: usize = 16;
+
+/// An s-expression, annotated with the position of its first token.
+pub(super) enum SExpr {
+    Atom(Token, Position),
+    List(Vec<SExpr>, Position),
+}
+
+impl SExpr {
+    fn position(&self) -> Position {
+        match self {
+            SExpr::Atom(_, pos) | SExpr::List(_, pos) => *pos,
+        }
+    }
+
+    pub(super) fn as_symbol(&self) -> Option<&str> {
+        match self {
+            SExpr::Atom(Token::Symbol(s), _) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// Reads all s-expressions from the input.
+pub(super) fn read_all<R: BufRead>(input: R) -> CarcaraResult<Vec<SExpr>> {
+    let mut lexer = Lexer::new(input)?;
+    let mut stack: Vec<(Vec<SExpr>, Position)> = vec![(Vec::new(), (0, 0))];
+    loop {
+        match lexer.next_token()? {
+            (Token::Eof, pos) => {
+                if stack.len() > 1 {
+                    return Err(Error::Parser(ParserError::UnexpectedToken(Token::Eof), pos));
+                }
+                return Ok(stack.pop().unwrap().0);
+            }
+            (Token::OpenParen, pos) => stack.push((Vec::new(), pos)),
+            (Token::CloseParen, pos) => {
+                if stack.len() == 1 {
+                    return Err(Error::Parser(
+                        ParserError::UnexpectedToken(Token::CloseParen),
+                        pos,
+                    ));
+                }
+                let (list, pos) = stack.pop().unwrap();
+                stack.last_mut().unwrap().0.push(SExpr::List(list, pos));
+            }
+            (token, pos) => stack.last_mut().unwrap().0.push(SExpr::Atom(token, pos)),
+        }
+    }
+}
+
+/// A sort that may contain sort variables.
+#[derive(Debug, Clone)]
+enum Ty {
+    Var(usize),
+    Con(String, Vec<Ty>),
+}
+
+impl Ty {
+    fn atom(name: &str) -> Self {
+        Ty::Con(name.to_owned(), Vec::new())
+    }
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Ty::Var(_) => write!(f, "?"),
+            Ty::Con(name, args) if args.is_empty() => write!(f, "{}", name),
+            Ty::Con(name, args) => {
+                write!(f, "({}", name)?;
+                for a in args {
+                    write!(f, " {}", a)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// A function symbol, either declared or defined in the proof, or inferred from its uses.
+struct Symbol {
+    params: Vec<Ty>,
+    result: Ty,
+    inferred: bool,
+}
+
+struct Inference<'a> {
+    /// The parser used to sort check operator applications. It never reads any input.
+    parser: Parser<'a, &'a [u8]>,
+
+    /// The binding of each sort variable, if any.
+    bindings: Vec<Option<Ty>>,
+
+    symbols: IndexMap<String, Symbol>,
+    sorts: IndexMap<String, (usize, bool)>,
+    scopes: Vec<HashMap<String, Ty>>,
+}
+
+impl<'a> Inference<'a> {
+    fn new(parser: Parser<'a, &'a [u8]>) -> Self {
+        Self {
+            parser,
+            bindings: Vec::new(),
+            symbols: IndexMap::new(),
+            sorts: IndexMap::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    fn fresh(&mut self) -> Ty {
+        self.bindings.push(None);
+        Ty::Var(self.bindings.len() - 1)
+    }
+
+    /// Follows the bindings of a sort variable until reaching an unbound variable or a sort
+    /// constructor.
+    fn shallow_resolve(&self, ty: &Ty) -> Ty {
+        let mut ty = ty.clone();
+        while let Ty::Var(v) = ty {
+            match &self.bindings[v] {
+                Some(bound) => ty = bound.clone(),
+                None => break,
+            }
+        }
+        ty
+    }
+
+    fn resolve(&self, ty: &Ty) -> Ty {
+        match self.shallow_resolve(ty) {
+            Ty::Con(name, args) => Ty::Con(name, args.iter().map(|a| self.resolve(a)).collect()),
+            var @ Ty::Var(_) => var,
+        }
+    }
+
+    fn occurs(&self, v: usize, ty: &Ty) -> bool {
+        match self.shallow_resolve(ty) {
+            Ty::Var(w) => v == w,
+            Ty::Con(_, args) => args.iter().any(|a| self.occurs(v, a)),
+        }
+    }
+
+    fn unify(&mut self, a: &Ty, b: &Ty) -> bool {
+        match (self.shallow_resolve(a), self.shallow_resolve(b)) {
+            (Ty::Var(v), Ty::Var(w)) if v == w => true,
+            (Ty::Var(v), other) | (other, Ty::Var(v)) => {
+                if self.occurs(v, &other) {
+                    return false;
+                }
+                self.bindings[v] = Some(other);
+                true
+            }
+            (Ty::Con(f, xs), Ty::Con(g, ys)) => {
+                f == g && xs.len() == ys.len() && xs.iter().zip(&ys).all(|(x, y)| self.unify(x, y))
+            }
+        }
+    }
+
+    /// Unifies the sort of a term with the sort it is expected to have, returning a clear error if
+    /// they conflict.
+    fn expect(
+        &mut self,
+        what: &dyn Fn() -> String,
+        got: &Ty,
+        expected: &Ty,
+        pos: Position,
+    ) -> CarcaraResult<()> {
+        if self.unify(got, expected) {
+            return Ok(());
+        }
+        Err(Error::Parser(
+            ParserError::InferredSortConflict(
+                what(),
+                self.resolve(expected).to_string(),
+                self.resolve(got).to_string(),
+            ),
+            pos,
+        ))
+    }
+
+    fn lookup_var(&self, name: &str) -> Option<Ty> {
+        self.scopes.iter().rev().find_map(|s| s.get(name).cloned())
+    }
+
+    fn parse_sort(&mut self, sort: &SExpr) -> CarcaraResult<Ty> {
+        let (name, args) = match sort {
+            SExpr::Atom(Token::Symbol(s), _) => (s.as_str(), Vec::new()),
+            SExpr::List(items, _) => match items.as_slice() {
+                [SExpr::Atom(Token::ReservedWord(Reserved::Underscore), _), rest @ ..] => {
+                    let text: Vec<_> = rest.iter().map(sexpr_to_string).collect();
+                    return Ok(Ty::atom(&format!("(_ {})", text.join(" "))));
+                }
+                [head, args @ ..] if head.as_symbol().is_some() => {
+                    let args = args
+                        .iter()
+                        .map(|a| self.parse_sort(a))
+                        .collect::<CarcaraResult<_>>()?;
+                    (head.as_symbol().unwrap(), args)
+                }
+                _ => return Err(unexpected(sort)),
+            },
+            SExpr::Atom(..) => return Err(unexpected(sort)),
+        };
+        let builtin_arity = match name {
+            "Bool" | "Int" | "Real" | "String" | "RegLan" => Some(0),
+            "Array" => Some(2),
+            "Set" | "Seq" => Some(1),
+            _ => None,
+        };
+        if builtin_arity.is_none() && !self.sorts.contains_key(name) {
+            self.sorts.insert(name.to_owned(), (args.len(), true));
+        }
+        let arity = builtin_arity.unwrap_or_else(|| self.sorts[name].0);
+        if arity != args.len() {
+            return Err(Error::Parser(
+                ParserError::InconsistentArity(name.to_owned(), args.len(), arity),
+                sort.position(),
+            ));
+        }
+        Ok(Ty::Con(name.to_owned(), args))
+    }
+
+    /// Parses a list of sorted variables, of the form `((<symbol> <sort>)*)`.
+    fn parse_sorted_vars(&mut self, vars: &SExpr) -> CarcaraResult<Vec<(String, Ty)>> {
+        let SExpr::List(vars, _) = vars else {
+            return Err(unexpected(vars));
+        };
+        vars.iter()
+            .map(|var| match var {
+                SExpr::List(items, _) if items.len() == 2 && items[0].as_symbol().is_some() => {
+                    let name = items[0].as_symbol().unwrap().to_owned();
+                    Ok((name, self.parse_sort(&items[1])?))
+                }
+                _ => Err(unexpected(var)),
+            })
+            .collect()
+    }
+
+    fn declare(
+        &mut self,
+        name: &str,
+        params: Vec<Ty>,
+        result: Ty,
+        pos: Position,
+    ) -> CarcaraResult<()> {
+        if let Some(existing) = self.symbols.get(name) {
+            // If the symbol was used before being declared, its inferred sort must agree with the
+            // declaration
+            let (old_params, old_result) = (existing.params.clone(), existing.result.clone());
+            if old_params.len() != params.len() {
+                return Err(Error::Parser(
+                    ParserError::InconsistentArity(name.to_owned(), params.len(), old_params.len()),
+                    pos,
+                ));
+            }
+            let what = || format!("'{}'", name);
+            for (old, new) in old_params.iter().zip(&params) {
+                self.expect(&what, old, new, pos)?;
+            }
+            self.expect(&what, &old_result, &result, pos)?;
+        }
+        let symbol = Symbol { params, result, inferred: false };
+        self.symbols.insert(name.to_owned(), symbol);
+        Ok(())
+    }
+
+    fn command(&mut self, command: &SExpr, subproof_ends: &mut Vec<String>) -> CarcaraResult<()> {
+        let SExpr::List(items, pos) = command else {
+            // Some solvers print "unsat" before the proof
+            return Ok(());
+        };
+        let pos = *pos;
+        let Some(SExpr::Atom(Token::ReservedWord(head), _)) = items.first() else {
+            return Ok(());
+        };
+        match (head, &items[1..]) {
+            (Reserved::Assume, [_, term]) => {
+                let ty = self.term(term)?;
+                self.expect(
+                    &|| "assumed term".to_owned(),
+                    &ty,
+                    &Ty::atom("Bool"),
+                    term.position(),
+                )?;
+            }
+            (Reserved::Step, [id, rest @ ..]) => {
+                for (keyword, value) in attributes(rest) {
+                    match (keyword, value) {
+                        (None, SExpr::List(clause, _)) => {
+                            for literal in clause.iter().skip(1) {
+                                let ty = self.term(literal)?;
+                                let what = || "clause literal".to_owned();
+                                self.expect(&what, &ty, &Ty::atom("Bool"), literal.position())?;
+                            }
+                        }
+                        (Some("args"), SExpr::List(args, _)) => {
+                            for arg in args {
+                                self.term(arg)?;
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                // If this step ends a subproof, the variables bound by its anchor go out of scope
+                if let (Some(id), Some(end)) = (id.as_symbol(), subproof_ends.last()) {
+                    if id == end {
+                        subproof_ends.pop();
+                        self.scopes.pop();
+                    }
+                }
+            }
+            (Reserved::Anchor, rest) => {
+                let mut scope = HashMap::new();
+                let mut end = None;
+                for (keyword, value) in attributes(rest) {
+                    match (keyword, value) {
+                        (Some("step"), id) => end = id.as_symbol().map(str::to_owned),
+                        (Some("args"), SExpr::List(args, _)) => {
+                            self.scopes.push(HashMap::new());
+                            for arg in args {
+                                let (name, ty) = self.anchor_arg(arg)?;
+                                self.scopes
+                                    .last_mut()
+                                    .unwrap()
+                                    .insert(name.clone(), ty.clone());
+                                scope.insert(name, ty);
+                            }
+                            self.scopes.pop();
+                        }
+                        _ => (),
+                    }
+                }
+                let Some(end) = end else {
+                    return Err(unexpected(command));
+                };
+                self.scopes.push(scope);
+                subproof_ends.push(end);
+            }
+            (Reserved::DefineFun, [name, params, sort, body]) => {
+                let Some(name) = name.as_symbol() else {
+                    return Err(unexpected(command));
+                };
+                let params = self.parse_sorted_vars(params)?;
+                let result = self.parse_sort(sort)?;
+                self.scopes.push(params.iter().cloned().collect());
+                let ty = self.term(body)?;
+                self.scopes.pop();
+                let what = || format!("body of '{}'", name);
+                self.expect(&what, &ty, &result, body.position())?;
+                let params = params.into_iter().map(|(_, ty)| ty).collect();
+                self.declare(name, params, result, pos)?;
+            }
+            (Reserved::DeclareFun, [name, SExpr::List(params, _), sort]) => {
+                let Some(name) = name.as_symbol() else {
+                    return Err(unexpected(command));
+                };
+                let params = params
+                    .iter()
+                    .map(|p| self.parse_sort(p))
+                    .collect::<CarcaraResult<_>>()?;
+                let result = self.parse_sort(sort)?;
+                self.declare(name, params, result, pos)?;
+            }
+            (Reserved::DeclareConst, [name, sort]) => {
+                let Some(name) = name.as_symbol() else {
+                    return Err(unexpected(command));
+                };
+                let result = self.parse_sort(sort)?;
+                self.declare(name, Vec::new(), result, pos)?;
+            }
+            (Reserved::DeclareSort, [name, SExpr::Atom(Token::Numeral(arity), _)]) => {
+                let (Some(name), Some(arity)) = (name.as_symbol(), arity.to_usize()) else {
+                    return Err(unexpected(command));
+                };
+                if let Some((existing, _)) = self.sorts.get(name) {
+                    if *existing != arity {
+                        return Err(Error::Parser(
+                            ParserError::InconsistentArity(name.to_owned(), arity, *existing),
+                            pos,
+                        ));
+                    }
+                }
+                self.sorts.insert(name.to_owned(), (arity, false));
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Infers an anchor argument, of the form `(<symbol> <sort>)`, `(:= (<symbol> <sort>) <term>)`
+    /// or `(:= <symbol> <term>)`.
+    fn anchor_arg(&mut self, arg: &SExpr) -> CarcaraResult<(String, Ty)> {
+        let SExpr::List(items, _) = arg else {
+            return Err(unexpected(arg));
+        };
+        match items.as_slice() {
+            [SExpr::Atom(Token::Keyword(k), _), var, value] if k == "=" => {
+                let value_ty = self.term(value)?;
+                if let Some(name) = var.as_symbol() {
+                    return Ok((name.to_owned(), value_ty));
+                }
+                let SExpr::List(var, _) = var else {
+                    return Err(unexpected(arg));
+                };
+                let [name, sort] = var.as_slice() else {
+                    return Err(unexpected(arg));
+                };
+                let Some(name) = name.as_symbol() else {
+                    return Err(unexpected(arg));
+                };
+                let ty = self.parse_sort(sort)?;
+                let what = || format!("value assigned to '{}'", name);
+                self.expect(&what, &value_ty, &ty, value.position())?;
+                Ok((name.to_owned(), ty))
+            }
+            [name, sort] if name.as_symbol().is_some() => {
+                Ok((name.as_symbol().unwrap().to_owned(), self.parse_sort(sort)?))
+            }
+            _ => Err(unexpected(arg)),
+        }
+    }
+
+    fn term(&mut self, term: &SExpr) -> CarcaraResult<Ty> {
+        let (items, pos) = match term {
+            SExpr::Atom(token, pos) => return self.atom(token, *pos),
+            SExpr::List(items, pos) => (items, *pos),
+        };
+        let Some((head, args)) = items.split_first() else {
+            return Err(unexpected(term));
+        };
+        match head {
+            SExpr::Atom(Token::ReservedWord(reserved), _) => {
+                self.special_form(*reserved, term, args)
+            }
+            SExpr::Atom(Token::Symbol(f), _) => {
+                let arg_tys = args
+                    .iter()
+                    .map(|a| self.term(a))
+                    .collect::<CarcaraResult<Vec<_>>>()?;
+                self.application(f, args, &arg_tys, pos)
+            }
+            // Indexed and qualified operators, like `((_ extract 3 0) x)`, are not constrained
+            _ => {
+                for a in args {
+                    self.term(a)?;
+                }
+                Ok(self.fresh())
+            }
+        }
+    }
+
+    fn atom(&mut self, token: &Token, pos: Position) -> CarcaraResult<Ty> {
+        Ok(match token {
+            Token::Numeral(_) => Ty::atom("Int"),
+            Token::Decimal(_) => Ty::atom("Real"),
+            Token::String(_) => Ty::atom("String"),
+            Token::Bitvector { width, .. } => Ty::atom(&format!("(_ BitVec {})", width)),
+            Token::FiniteField { order, .. } => Ty::atom(&format!("(_ FiniteField {})", order)),
+            Token::Symbol(s) => {
+                if let Some(ty) = self.lookup_var(s) {
+                    return Ok(ty);
+                }
+                return self.application(s, &[], &[], pos);
+            }
+            _ => {
+                return Err(Error::Parser(
+                    ParserError::UnexpectedToken(token.clone()),
+                    pos,
+                ))
+            }
+        })
+    }
+
+    fn special_form(
+        &mut self,
+        reserved: Reserved,
+        term: &SExpr,
+        args: &[SExpr],
+    ) -> CarcaraResult<Ty> {
+        let bool_sort = Ty::atom("Bool");
+        match (reserved, args) {
+            (Reserved::Bang, [inner, attrs @ ..]) => {
+                let ty = self.term(inner)?;
+                for (keyword, value) in attributes(attrs) {
+                    if let (Some("named"), Some(name)) = (keyword, value.as_symbol()) {
+                        self.declare(name, Vec::new(), ty.clone(), value.position())?;
+                    }
+                }
+                Ok(ty)
+            }
+            (Reserved::Let, [SExpr::List(bindings, _), body]) => {
+                let mut scope = HashMap::new();
+                for binding in bindings {
+                    match binding {
+                        SExpr::List(b, _) if b.len() == 2 && b[0].as_symbol().is_some() => {
+                            let ty = self.term(&b[1])?;
+                            scope.insert(b[0].as_symbol().unwrap().to_owned(), ty);
+                        }
+                        _ => return Err(unexpected(binding)),
+                    }
+                }
+                self.scopes.push(scope);
+                let ty = self.term(body);
+                self.scopes.pop();
+                ty
+            }
+            (
+                Reserved::Forall | Reserved::Exists | Reserved::Choice | Reserved::Lambda,
+                [vars, body],
+            ) => {
+                let vars = self.parse_sorted_vars(vars)?;
+                let first = vars.first().map(|(_, ty)| ty.clone());
+                self.scopes.push(vars.into_iter().collect());
+                let body_ty = self.term(body);
+                self.scopes.pop();
+                let body_ty = body_ty?;
+                match reserved {
+                    Reserved::Lambda => Ok(self.fresh()),
+                    _ => {
+                        let what = || "quantifier body".to_owned();
+                        self.expect(&what, &body_ty, &bool_sort, body.position())?;
+                        match (reserved, first) {
+                            (Reserved::Choice, Some(ty)) => Ok(ty),
+                            _ => Ok(bool_sort),
+                        }
+                    }
+                }
+            }
+            (Reserved::As, [inner, sort]) => {
+                let is_ff_literal = inner
+                    .as_symbol()
+                    .and_then(super::ff_literal_value)
+                    .is_some();
+                if !matches!(inner.as_symbol(), Some("const")) && !is_ff_literal {
+                    self.term(inner)?;
+                }
+                self.parse_sort(sort)
+            }
+            (
+                Reserved::Underscore,
+                [SExpr::Atom(Token::Symbol(s), _), SExpr::Atom(Token::Numeral(w), _)],
+            ) if s.starts_with("bv") && s[2..].chars().all(|c| c.is_ascii_digit()) => {
+                Ok(Ty::atom(&format!("(_ BitVec {})", w)))
+            }
+            (Reserved::Underscore | Reserved::Match, _) => Ok(self.fresh()),
+            _ => Err(unexpected(term)),
+        }
+    }
+
+    fn application(
+        &mut self,
+        f: &str,
+        args: &[SExpr],
+        arg_tys: &[Ty],
+        pos: Position,
+    ) -> CarcaraResult<Ty> {
+        if let Some(symbol) = self.symbols.get(f) {
+            let (params, result) = (symbol.params.clone(), symbol.result.clone());
+            if params.len() != arg_tys.len() {
+                return Err(Error::Parser(
+                    ParserError::InconsistentArity(f.to_owned(), arg_tys.len(), params.len()),
+                    pos,
+                ));
+            }
+            for (i, (param, arg)) in params.iter().zip(arg_tys).enumerate() {
+                let what = || format!("argument {} of '{}'", i + 1, f);
+                self.expect(&what, arg, param, args[i].position())?;
+            }
+            return Ok(result);
+        }
+        if let Some(ty) = self.lookup_var(f) {
+            // A bound variable applied to arguments, as in higher-order terms
+            return Ok(if arg_tys.is_empty() { ty } else { self.fresh() });
+        }
+        if let Ok(op) = Operator::from_str(f) {
+            return self.operator(op, args, arg_tys, pos);
+        }
+
+        let params: Vec<_> = arg_tys.to_vec();
+        let result = self.fresh();
+        let symbol = Symbol {
+            params,
+            result: result.clone(),
+            inferred: true,
+        };
+        self.symbols.insert(f.to_owned(), symbol);
+        Ok(result)
+    }
+
+    /// Infers the sort of an operator application, constraining the sorts of its arguments so the
+    /// application passes the parser's sort checks.
+    fn operator(
+        &mut self,
+        op: Operator,
+        args: &[SExpr],
+        arg_tys: &[Ty],
+        pos: Position,
+    ) -> CarcaraResult<Ty> {
+        for _ in 0..MAX_SORT_CHECKS {
+            let terms = arg_tys
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| {
+                    let sort = self.sort_of_ty(ty);
+                    self.parser.pool.add(Term::Var(format!("arg{}", i), sort))
+                })
+                .collect();
+            let error = match self.parser.make_op(op, terms) {
+                Ok(term) => {
+                    let sort = self.parser.pool.sort(&term);
+                    return Ok(self.ty_of_sort(sort.as_sort().unwrap()));
+                }
+                Err(e) => e,
+            };
+            match error {
+                ParserError::SortError(e) => {
+                    let i = e.argument.unwrap_or(0);
+                    let got = self.ty_of_sort(&e.got);
+                    let expected = match e.expected.as_slice() {
+                        [expected] => self.ty_of_sort(expected),
+                        // When one of several sorts is expected, as with arithmetic operators, a
+                        // sort variable takes the sort of another argument if possible
+                        possibilities if matches!(self.shallow_resolve(&got), Ty::Var(_)) => {
+                            let chosen = arg_tys
+                                .iter()
+                                .map(|ty| self.sort_of_ty(ty))
+                                .find_map(|s| possibilities.iter().find(|p| s.as_sort() == Some(p)))
+                                .unwrap_or(&possibilities[0]);
+                            self.ty_of_sort(chosen)
+                        }
+                        _ => return Err(Error::Parser(ParserError::SortError(e), pos)),
+                    };
+                    let what = || format!("argument {} of '{}'", i + 1, op);
+                    let arg_pos = args.get(i).map_or(pos, SExpr::position);
+                    self.expect(&what, &got, &expected, arg_pos)?;
+                }
+                // These errors don't say which sort was expected, so a sort variable is unified with
+                // the sort of another argument of the same kind. If there is none, the result is
+                // left unconstrained
+                ParserError::ExpectedBvSort(ref got)
+                | ParserError::ExpectedFpSort(ref got)
+                | ParserError::ExpectedFfSort(ref got) => {
+                    let got = self.ty_of_sort(got);
+                    if !matches!(self.shallow_resolve(&got), Ty::Var(_)) {
+                        return Err(Error::Parser(error, pos));
+                    }
+                    let same_kind = |s: &Sort| match error {
+                        ParserError::ExpectedBvSort(_) => matches!(s, Sort::BitVec(_)),
+                        ParserError::ExpectedFpSort(_) => matches!(s, Sort::FloatingPoint(..)),
+                        _ => matches!(s, Sort::FiniteField(_)),
+                    };
+                    let Some(other) = arg_tys
+                        .iter()
+                        .find(|ty| same_kind(self.sort_of_ty(ty).as_sort().unwrap()))
+                    else {
+                        return Ok(self.fresh());
+                    };
+                    self.unify(&got, other);
+                }
+                e => return Err(Error::Parser(e, pos)),
+            }
+        }
+        Ok(self.fresh())
+    }
+
+    /// Converts a sort to the representation used by the parser, replacing each unbound sort
+    /// variable with a placeholder sort.
+    fn sort_of_ty(&mut self, ty: &Ty) -> Rc<Term> {
+        let sort = match self.shallow_resolve(ty) {
+            Ty::Var(v) => Sort::Atom(format!("{}{}", PLACEHOLDER_PREFIX, v), Vec::new()),
+            Ty::Con(name, args) => {
+                let mut args: Vec<_> = args.iter().map(|a| self.sort_of_ty(a)).collect();
+                match (name.as_str(), args.len()) {
+                    ("Bool", 0) => Sort::Bool,
+                    ("Int", 0) => Sort::Int,
+                    ("Real", 0) => Sort::Real,
+                    ("String", 0) => Sort::String,
+                    ("RegLan", 0) => Sort::RegLan,
+                    ("RoundingMode", 0) => Sort::RoundingMode,
+                    ("Array", 2) => {
+                        let y = args.pop().unwrap();
+                        Sort::Array(args.pop().unwrap(), y)
+                    }
+                    ("Set", 1) => Sort::Set(args.pop().unwrap()),
+                    ("Seq", 1) => Sort::Seq(args.pop().unwrap()),
+                    _ => indexed_sort(&name).unwrap_or(Sort::Atom(name, args)),
+                }
+            }
+        };
+        self.parser.pool.add(Term::Sort(sort))
+    }
+
+    /// Converts a sort in the representation used by the parser back to a `Ty`. Placeholder sorts
+    /// become their sort variables, and unconstrained parts of expected sorts become fresh ones.
+    fn ty_of_sort(&mut self, sort: &Sort) -> Ty {
+        let mut convert = |s: &Rc<Term>| self.ty_of_sort(s.as_sort().unwrap());
+        match sort {
+            Sort::Atom(name, _) if name == ANY_SORT_NAME => self.fresh(),
+            Sort::Atom(name, args) => match name.strip_prefix(PLACEHOLDER_PREFIX) {
+                Some(v) => Ty::Var(v.parse().unwrap()),
+                None => Ty::Con(name.clone(), args.iter().map(convert).collect()),
+            },
+            Sort::Array(x, y) => Ty::Con("Array".to_owned(), vec![convert(x), convert(y)]),
+            Sort::Set(x) => Ty::Con("Set".to_owned(), vec![convert(x)]),
+            Sort::Seq(x) => Ty::Con("Seq".to_owned(), vec![convert(x)]),
+            Sort::Function(_) | Sort::RareList | Sort::Type => self.fresh(),
+            Sort::Bool
+            | Sort::Int
+            | Sort::Real
+            | Sort::String
+            | Sort::RegLan
+            | Sort::BitVec(_)
+            | Sort::FloatingPoint(..)
+            | Sort::RoundingMode
+            | Sort::FiniteField(_) => Ty::atom(&sort.to_string()),
+        }
+    }
+
+    /// Assigns a sort to every unconstrained sort variable in the inferred declarations, and
+    /// returns them as SMT-LIB commands.
+    fn finish(mut self) -> String {
+        let default_sort = (0..)
+            .map(|i| match i {
+                0 => DEFAULT_SORT_NAME.to_owned(),
+                i => format!("{}{}", DEFAULT_SORT_NAME, i),
+            })
+            .find(|name| !self.sorts.contains_key(name) && !self.symbols.contains_key(name))
+            .unwrap();
+        let mut uses_default_sort = false;
+        for v in 0..self.bindings.len() {
+            if let Ty::Var(v) = self.shallow_resolve(&Ty::Var(v)) {
+                uses_default_sort = true;
+                self.bindings[v] = Some(Ty::atom(&default_sort));
+            }
+        }
+
+        let mut result = String::new();
+        if uses_default_sort {
+            result.push_str(&format!("(declare-sort {} 0)\n", default_sort));
+        }
+        for (name, (arity, inferred)) in &self.sorts {
+            if *inferred {
+                result.push_str(&format!("(declare-sort {} {})\n", quote(name), arity));
+            }
+        }
+        for (name, symbol) in &self.symbols {
+            if !symbol.inferred {
+                continue;
+            }
+            let params: Vec<_> = symbol
+                .params
+                .iter()
+                .map(|p| self.resolve(p).to_string())
+                .collect();
+            let result_sort = self.resolve(&symbol.result);
+            result.push_str(&format!(
+                "(declare-fun {} ({}) {})\n",
+                quote(name),
+                params.join(" "),
+                result_sort
+            ));
+        }
+        result
+    }
+}
+
+/// Splits a sequence of attributes, of the form `:<keyword> <value>`, into pairs. Values that are
+/// not preceded by a keyword are returned with `None` as their keyword.
+pub(super) fn attributes(items: &[SExpr]) -> Vec<(Option<&str>, &SExpr)> {
+    let mut result = Vec::new();
+    let mut iter = items.iter().peekable();
+    while let Some(item) = iter.next() {
+        match item {
+            SExpr::Atom(Token::Keyword(k), _) => {
+                if let Some(value) =
+                    iter.next_if(|v| !matches!(v, SExpr::Atom(Token::Keyword(_), _)))
+                {
+                    result.push((Some(k.as_str()), value));
+                }
+            }
+            value => result.push((None, value)),
+        }
+    }
+    result
+}
+
+/// Parses the name of an indexed sort, like `(_ BitVec 32)`, as it is built by
+/// `Inference::parse_sort`.
+fn indexed_sort(name: &str) -> Option<Sort> {
+    let inner = name.strip_prefix("(_ ")?.strip_suffix(')')?;
+    let indices: Vec<_> = inner.split(' ').collect();
+    let index = |i: usize| indices.get(i)?.parse::<Integer>().ok();
+    match indices[0] {
+        "BitVec" if indices.len() == 2 => Some(Sort::BitVec(index(1)?)),
+        "FloatingPoint" if indices.len() == 3 => Some(Sort::FloatingPoint(index(1)?, index(2)?)),
+        "FiniteField" if indices.len() == 2 => Some(Sort::FiniteField(index(1)?)),
+        _ => None,
+    }
+}
+
+fn quote(symbol: &str) -> String {
+    if !symbol.is_empty() && symbol.chars().all(crate::utils::is_symbol_character) {
+        symbol.to_owned()
+    } else {
+        format!("|{}|", symbol)
+    }
+}
+
+fn sexpr_to_string(sexpr: &SExpr) -> String {
+    match sexpr {
+        SExpr::Atom(Token::Symbol(s), _) => s.clone(),
+        SExpr::Atom(Token::Numeral(n), _) => n.to_string(),
+        SExpr::Atom(token, _) => token.to_string(),
+        SExpr::List(items, _) => {
+            let items: Vec<_> = items.iter().map(sexpr_to_string).collect();
+            format!("({})", items.join(" "))
+        }
+    }
+}
+
+fn unexpected(sexpr: &SExpr) -> Error {
+    Error::Parser(
+        ParserError::UnexpectedToken(match sexpr {
+            SExpr::Atom(token, _) => token.clone(),
+            SExpr::List(..) => Token::OpenParen,
+        }),
+        sexpr.position(),
+    )
+}
+
+/// Infers the declarations of all sorts and functions that are used in a proof, but are not
+/// declared or defined in it. Returns the inferred declarations as a sequence of SMT-LIB
+/// `declare-sort` and `declare-fun` commands.
+///
+/// Since the declarations are inferred only from how the symbols are used, they may be more
+/// general than the original ones. For example, if a function is only ever compared with itself,
+/// its sort is not constrained at all, and it is given an uninterpreted sort. If the uses of a
+/// symbol are not consistent with each other, this returns an error.
+///
+/// Operator applications are sort checked as the parser would check them with the given `config`,
+/// so, for example, `allow_int_real_subtyping` lets arithmetic operators mix `Int` and `Real`
+/// arguments.
+pub fn synthesize_prelude<R: BufRead>(proof: R, config: Config) -> CarcaraResult<String> {
+    let commands = read_all(proof)?;
+    let mut pool = PrimitivePool::new();
+    let mut inference = Inference::new(Parser::new(&mut pool, config, &[][..])?);
+    let mut subproof_ends = Vec::new();
+    for command in &commands {
+        inference.command(command, &mut subproof_ends)?;
+    }
+    Ok(inference.finish())
+}
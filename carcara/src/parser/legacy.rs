@@ -0,0 +1,136 @@
+//! Parsing of the proof-trace format used by veriT before the Alethe format was introduced.
+//!
+//! In this format, each command has the form `(set <id> (<rule> <attribute>*))`, where the
+//! attributes are `:clauses`, which lists the premises, `:conclusion`, which gives the conclusion
+//! as a list of literals, and, for some rules, `:args` and `:iargs`. Input formulas are introduced
+//! by the `input` rule. For example:
+//!
+//! ```text
+//! (set .c1 (input :conclusion ((not (= a a)))))
+//! (set .c2 (eq_reflexive :conclusion ((= a a))))
+//! (set .c3 (resolution :clauses (.c1 .c2) :conclusion ()))
+//! ```
+//!
+//! The format has no explicit subproof boundaries, so every command is imported into the root
+//! proof. Steps that in Alethe must end a subproof (like `bind`) will not check, but the remaining
+//! steps can still be checked and compared against newer proofs.
+
+use super::*;
+
+impl<'a, R: BufRead> Parser<'a, R> {
+    /// Parses a proof in veriT's legacy proof-trace format, converting it to an Alethe proof.
+    ///
+    /// Commands that use the `input` rule become `assume` commands, and all other commands become
+    /// steps with the same rule name. The `:iargs` attribute, which holds the indices of literals
+    /// used by some rules, is ignored, since the corresponding Alethe rules don't need it.
+    pub fn parse_legacy_proof(&mut self) -> CarcaraResult<Proof> {
+        let mut commands = Vec::new();
+        self.renamed_step_ids.clear();
+
+        if self.current_token == Token::Symbol("unsat".into()) {
+            self.next_token()?;
+        }
+
+        while self.current_token != Token::Eof {
+            self.expect_token(Token::OpenParen)?;
+            match self.next_token()? {
+                (Token::Symbol(s), _) if s == "set" => (),
+                (other, pos) => {
+                    return Err(Error::Parser(ParserError::UnexpectedToken(other), pos))
+                }
+            }
+            let position = self.current_position;
+            let command = self.parse_legacy_command()?;
+            self.expect_token(Token::CloseParen)?;
+
+            let id = command.id().to_owned();
+            let (id, command, renamed_id) = self.check_step_id(id, command, position)?;
+            if let Some(renamed_id) = renamed_id {
+                let entry = (commands.len(), position);
+                self.state
+                    .step_ids
+                    .insert(HashCache::new(renamed_id), entry);
+            }
+            let entry = (commands.len(), position);
+            self.state.step_ids.insert(HashCache::new(id), entry);
+            commands.push(command);
+        }
+        Ok(Proof {
+            constant_definitions: Vec::new(),
+            commands,
+        })
+    }
+
+    /// Parses the id and body of a legacy `set` command. This method assumes that the `(` and
+    /// `set` tokens were already consumed.
+    fn parse_legacy_command(&mut self) -> CarcaraResult<ProofCommand> {
+        let id = self.expect_symbol()?;
+        self.expect_token(Token::OpenParen)?;
+        let rule = self.expect_symbol()?;
+
+        let (mut premises, mut args, mut clause) = (Vec::new(), Vec::new(), None);
+        while let Token::Keyword(keyword) = &self.current_token {
+            match keyword.as_str() {
+                "clauses" => {
+                    self.next_token()?;
+                    self.expect_token(Token::OpenParen)?;
+                    premises = self.parse_sequence(Self::parse_step_premise, false)?;
+                }
+                "args" => {
+                    self.next_token()?;
+                    self.expect_token(Token::OpenParen)?;
+                    args = self.parse_sequence(Self::parse_term, false)?;
+                }
+                "conclusion" => {
+                    self.next_token()?;
+                    self.expect_token(Token::OpenParen)?;
+                    clause = Some(
+                        self.parse_sequence(|p| p.parse_term_expecting_sort(&Sort::Bool), false)?,
+                    );
+                }
+                // Other attributes, like `:iargs`, are skipped together with their values
+                _ => {
+                    self.next_token()?;
+                    match self.current_token {
+                        Token::OpenParen => {
+                            self.next_token()?;
+                            self.ignore_until_close_parens()?;
+                        }
+                        Token::Keyword(_) | Token::CloseParen => (),
+                        _ => {
+                            self.next_token()?;
+                        }
+                    }
+                }
+            }
+        }
+        self.expect_token(Token::CloseParen)?;
+
+        let Some(clause) = clause else {
+            return Err(Error::Parser(
+                ParserError::MissingConclusion(id),
+                self.current_position,
+            ));
+        };
+        if rule == "input" {
+            // An empty clause is the same as `false`, which can't be represented as an `or` term
+            let term = match <[_; 1]>::try_from(clause) {
+                Ok([term]) => term,
+                Err(literals) if literals.is_empty() => self.pool.bool_false(),
+                Err(literals) => self.pool.add(Term::Op(Operator::Or, literals)),
+            };
+            return Ok(ProofCommand::Assume { id, term });
+        }
+        Ok(ProofCommand::Step(ProofStep {
+            id,
+            clause,
+            rule,
+            premises,
+            args,
+            arg_vars: Vec::new(),
+            discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
+        }))
+    }
+}
@@ -8,7 +8,7 @@ use std::{
 };
 
 /// A token in the SMT-LIB and Alethe formats.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Token {
     /// The `(` token.
     OpenParen,
@@ -35,6 +35,10 @@ pub enum Token {
     /// A bitvector literal.
     Bitvector { value: Integer, width: u64 },
 
+    /// A finite field literal, of the form `#f<value>m<order>`. The value is not reduced modulo the
+    /// order.
+    FiniteField { value: Integer, order: Integer },
+
     /// A string literal.
     String(String),
 
@@ -108,6 +112,12 @@ pub enum Reserved {
     /// The `define-sort` reserved word.
     DefineSort,
 
+    /// The `declare-datatype` reserved word.
+    DeclareDatatype,
+
+    /// The `declare-datatypes` reserved word.
+    DeclareDatatypes,
+
     /// The `assert` reserved word.
     Assert,
 
@@ -139,6 +149,8 @@ impl_str_conversion_traits!(Reserved {
     DefineFunRec: "define-fun-rec",
     DefineFunsRec: "define-funs-rec",
     DefineSort: "define-sort",
+    DeclareDatatype: "declare-datatype",
+    DeclareDatatypes: "declare-datatypes",
     Assert: "assert",
     CheckSatAssuming: "check-sat-assuming",
     SetLogic: "set-logic",
@@ -272,7 +284,7 @@ impl<R: BufRead> Lexer<R> {
             Some('"') => self.read_string(),
             Some('|') => self.read_quoted_symbol(),
             Some(':') => self.read_keyword(),
-            Some('#') => self.read_bitvector(),
+            Some('#') => self.read_hash_literal(),
             Some('-') => {
                 // If we encounter the '-' character, the token can either be a GMP-style numerical
                 // literal (e.g. '-5'), or a symbol that starts with '-' (e.g. the '-' operator
@@ -333,15 +345,17 @@ impl<R: BufRead> Lexer<R> {
         Ok(Token::Keyword(symbol))
     }
 
-    /// Reads a binary or hexadecimal bitvector literal, e.g. `#b0110` or `#x01Ab`.
+    /// Reads a literal that starts with `#`. This is either a binary or hexadecimal bitvector
+    /// literal, e.g. `#b0110` or `#x01Ab`, or a finite field literal, e.g. `#f3m7`.
     ///
-    /// Returns an error if any character other than `b` or `x` is encountered after the `#`, or if
-    /// no digits are provided.
-    fn read_bitvector(&mut self) -> CarcaraResult<Token> {
+    /// Returns an error if any character other than `b`, `x` or `f` is encountered after the `#`,
+    /// or if no digits are provided.
+    fn read_hash_literal(&mut self) -> CarcaraResult<Token> {
         self.next_char()?; // Consume `#`
         let (base, bits_per_char) = match self.next_char()? {
             Some('b') => (2, 1),
             Some('x') => (16, 4),
+            Some('f') => return self.read_finite_field(),
             None => return Err(Error::Parser(ParserError::EmptyBitvector, self.position)),
             Some(other) => {
                 return Err(Error::Parser(
@@ -360,6 +374,29 @@ impl<R: BufRead> Lexer<R> {
         Ok(Token::Bitvector { value, width })
     }
 
+    /// Reads the rest of a finite field literal, after the `#f` prefix.
+    fn read_finite_field(&mut self) -> CarcaraResult<Token> {
+        let value = self.read_chars_while(|c| c.is_ascii_digit())?;
+        let separator = self.next_char()?;
+        let order = self.read_chars_while(|c| c.is_ascii_digit())?;
+        if value.is_empty() || separator != Some('m') || order.is_empty() {
+            let text = format!(
+                "#f{}{}{}",
+                value,
+                separator.map(String::from).unwrap_or_default(),
+                order
+            );
+            return Err(Error::Parser(
+                ParserError::InvalidFiniteFieldLiteral(text),
+                self.position,
+            ));
+        }
+        Ok(Token::FiniteField {
+            value: value.parse().unwrap(),
+            order: order.parse().unwrap(),
+        })
+    }
+
     /// Reads an integer or decimal numerical literal.
     fn read_number(&mut self, negated: bool) -> CarcaraResult<Token> {
         let first_part = self.read_chars_while(|c| c.is_ascii_digit())?;
@@ -642,6 +679,24 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_finite_fields() {
+        let input = "#f3m7 #f0m2 #f12m5";
+        let expected = vec![
+            Token::FiniteField { value: 3.into(), order: 7.into() },
+            Token::FiniteField { value: 0.into(), order: 2.into() },
+            Token::FiniteField { value: 12.into(), order: 5.into() },
+        ];
+        assert_eq!(expected, lex_all(input));
+
+        for input in ["#f", "#f3", "#fm7", "#f3m", "#f3x7"] {
+            assert!(matches!(
+                lex_one(input),
+                Err(Error::Parser(ParserError::InvalidFiniteFieldLiteral(_), _)),
+            ));
+        }
+    }
+
     #[test]
     fn test_strings() {
         let input = r#" "string" "escaped quote: """ """" """""" "\u0061" "\u{0061}" "#;
@@ -0,0 +1,398 @@
+//! Detection of proofs that are paired with the wrong problem.
+//!
+//! When a proof is checked against the wrong problem, the parser usually only notices when it
+//! reaches the first term that uses a symbol the problem doesn't declare, which may be deep into
+//! the proof. This module instead reads the whole proof as a sequence of s-expressions before
+//! parsing it, and collects every function and sort that it uses but that is declared neither in
+//! the problem nor in the proof itself. This is done without any sort checking, so that all
+//! missing symbols can be reported at once.
+
+use super::{
+    inference::{attributes, read_all, SExpr},
+    Position, Reserved, Token,
+};
+use crate::CarcaraResult;
+use indexmap::IndexMap;
+use std::{collections::HashSet, fmt, io::BufRead};
+
+/// A function or sort used in a proof that is not declared in its problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndeclaredSymbol {
+    /// The name of the function or sort.
+    pub name: String,
+
+    /// The number of arguments it was used with.
+    pub arity: usize,
+
+    /// Whether this is a sort, instead of a function.
+    pub is_sort: bool,
+
+    /// How many times it was used in the proof.
+    pub uses: usize,
+
+    /// The position in the proof where it was first used.
+    pub first_use: Position,
+}
+
+impl fmt::Display for UndeclaredSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = if self.is_sort { "sort" } else { "symbol" };
+        let plural = if self.uses == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} {}/{} ({} use{})",
+            kind, self.name, self.arity, self.uses, plural
+        )
+    }
+}
+
+/// A list of undeclared symbols, displayed separated by commas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndeclaredSymbols(pub Vec<UndeclaredSymbol>);
+
+impl fmt::Display for UndeclaredSymbols {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, symbol) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+/// The sorts that don't need to be declared. Indexed sorts, like `(_ BitVec 32)`, are not
+/// included, since they are never reported.
+const BUILTIN_SORTS: &[(&str, usize)] = &[
+    ("Bool", 0),
+    ("Int", 0),
+    ("Real", 0),
+    ("String", 0),
+    ("RegLan", 0),
+    ("RoundingMode", 0),
+    ("Float16", 0),
+    ("Float32", 0),
+    ("Float64", 0),
+    ("Float128", 0),
+    ("Array", 2),
+    ("Set", 1),
+    ("Seq", 1),
+];
+
+/// The declarations of the problem, as seen by the scanner.
+pub(super) trait Declarations {
+    /// Returns `true` if a function with this name can be applied to `arity` arguments.
+    fn has_function(&self, name: &str, arity: usize) -> bool;
+
+    /// Returns `true` if a sort with this name and arity was declared or defined.
+    fn has_sort(&self, name: &str, arity: usize) -> bool;
+}
+
+struct Scanner<'a> {
+    declarations: &'a dyn Declarations,
+
+    /// The functions defined in the proof, with `define-fun` or the `:named` attribute.
+    defined: HashSet<String>,
+
+    /// The variables bound by binders, `let` terms and anchors.
+    scopes: Vec<HashSet<String>>,
+
+    /// The undeclared symbols found so far, indexed by whether they are sorts, their names and
+    /// their arities.
+    found: IndexMap<(bool, String, usize), UndeclaredSymbol>,
+    parse_hole_args: bool,
+}
+
+impl Scanner<'_> {
+    fn record(&mut self, is_sort: bool, name: &str, arity: usize, pos: Position) {
+        self.found
+            .entry((is_sort, name.to_owned(), arity))
+            .or_insert_with(|| UndeclaredSymbol {
+                name: name.to_owned(),
+                arity,
+                is_sort,
+                uses: 0,
+                first_use: pos,
+            })
+            .uses += 1;
+    }
+
+    fn is_bound(&self, name: &str) -> bool {
+        self.defined.contains(name) || self.scopes.iter().any(|s| s.contains(name))
+    }
+
+    fn use_function(&mut self, name: &str, arity: usize, pos: Position) {
+        if !self.is_bound(name) && !self.declarations.has_function(name, arity) {
+            self.record(false, name, arity, pos);
+        }
+    }
+
+    fn sort(&mut self, sort: &SExpr) {
+        match sort {
+            SExpr::Atom(Token::Symbol(name), pos) => self.use_sort(name, 0, *pos),
+            SExpr::List(items, _) => match items.as_slice() {
+                [SExpr::Atom(Token::ReservedWord(Reserved::Underscore), _), ..] => (),
+                [SExpr::Atom(Token::Symbol(name), pos), args @ ..] => {
+                    self.use_sort(name, args.len(), *pos);
+                    for a in args {
+                        self.sort(a);
+                    }
+                }
+                _ => (),
+            },
+            SExpr::Atom(..) => (),
+        }
+    }
+
+    fn use_sort(&mut self, name: &str, arity: usize, pos: Position) {
+        let is_builtin = BUILTIN_SORTS.contains(&(name, arity));
+        if !is_builtin && !self.declarations.has_sort(name, arity) {
+            self.record(true, name, arity, pos);
+        }
+    }
+
+    /// Scans a list of sorted variables, of the form `((<symbol> <sort>)*)`, and returns the names
+    /// of the variables.
+    fn sorted_vars(&mut self, vars: &SExpr) -> HashSet<String> {
+        let SExpr::List(vars, _) = vars else {
+            return HashSet::new();
+        };
+        let mut names = HashSet::new();
+        for var in vars {
+            if let SExpr::List(items, _) = var {
+                if let [name, sort] = items.as_slice() {
+                    names.extend(name.as_symbol().map(str::to_owned));
+                    self.sort(sort);
+                }
+            }
+        }
+        names
+    }
+
+    fn term(&mut self, term: &SExpr) {
+        match term {
+            SExpr::Atom(Token::Symbol(s), pos) => self.use_function(s, 0, *pos),
+            SExpr::Atom(..) => (),
+            SExpr::List(items, _) => match items.as_slice() {
+                [SExpr::Atom(Token::ReservedWord(r), _), args @ ..] => self.special_form(*r, args),
+                [SExpr::Atom(Token::Symbol(f), pos), args @ ..] => {
+                    self.use_function(f, args.len(), *pos);
+                    for a in args {
+                        self.term(a);
+                    }
+                }
+                [head, args @ ..] => {
+                    self.term(head);
+                    for a in args {
+                        self.term(a);
+                    }
+                }
+                [] => (),
+            },
+        }
+    }
+
+    fn special_form(&mut self, reserved: Reserved, args: &[SExpr]) {
+        match (reserved, args) {
+            (Reserved::Bang, [inner, attrs @ ..]) => {
+                self.term(inner);
+                for (keyword, value) in attributes(attrs) {
+                    match (keyword, value) {
+                        (Some("named"), name) => {
+                            self.defined.extend(name.as_symbol().map(str::to_owned));
+                        }
+                        (Some("pattern"), SExpr::List(terms, _)) => {
+                            for t in terms {
+                                self.term(t);
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            (Reserved::Let, [SExpr::List(bindings, _), body]) => {
+                let mut scope = HashSet::new();
+                for binding in bindings {
+                    if let SExpr::List(b, _) = binding {
+                        if let [name, value] = b.as_slice() {
+                            self.term(value);
+                            scope.extend(name.as_symbol().map(str::to_owned));
+                        }
+                    }
+                }
+                self.scopes.push(scope);
+                self.term(body);
+                self.scopes.pop();
+            }
+            (
+                Reserved::Forall | Reserved::Exists | Reserved::Choice | Reserved::Lambda,
+                [vars, body],
+            ) => {
+                let scope = self.sorted_vars(vars);
+                self.scopes.push(scope);
+                self.term(body);
+                self.scopes.pop();
+            }
+            (Reserved::Match, [scrutinee, SExpr::List(cases, _)]) => {
+                self.term(scrutinee);
+                for case in cases {
+                    let SExpr::List(case, _) = case else { continue };
+                    let [pattern, body] = case.as_slice() else {
+                        continue;
+                    };
+                    // The pattern's constructor is also added to the scope, but this is harmless
+                    let scope = match pattern {
+                        SExpr::List(items, _) => items
+                            .iter()
+                            .filter_map(|i| i.as_symbol().map(str::to_owned))
+                            .collect(),
+                        atom @ SExpr::Atom(..) => {
+                            atom.as_symbol().map(str::to_owned).into_iter().collect()
+                        }
+                    };
+                    self.scopes.push(scope);
+                    self.term(body);
+                    self.scopes.pop();
+                }
+            }
+            (Reserved::As, [inner, sort]) => {
+                if inner.as_symbol() != Some("const") {
+                    self.term(inner);
+                }
+                self.sort(sort);
+            }
+            _ => (),
+        }
+    }
+
+    /// Scans an assignment, of the form `(:= (<symbol> <sort>) <term>)` or `(:= <symbol> <term>)`,
+    /// or a sorted variable, of the form `(<symbol> <sort>)`, and returns the variable name.
+    fn assignment_or_var(&mut self, arg: &SExpr) -> Option<String> {
+        let SExpr::List(items, _) = arg else {
+            return None;
+        };
+        match items.as_slice() {
+            [SExpr::Atom(Token::Keyword(k), _), var, value] if k == "=" => {
+                self.term(value);
+                match var {
+                    SExpr::List(var, _) => match var.as_slice() {
+                        [name, sort] => {
+                            self.sort(sort);
+                            name.as_symbol().map(str::to_owned)
+                        }
+                        _ => None,
+                    },
+                    atom @ SExpr::Atom(..) => atom.as_symbol().map(str::to_owned),
+                }
+            }
+            [name, sort] if name.as_symbol().is_some() => {
+                self.sort(sort);
+                name.as_symbol().map(str::to_owned)
+            }
+            _ => None,
+        }
+    }
+
+    fn command(&mut self, command: &SExpr, subproof_ends: &mut Vec<String>) {
+        let SExpr::List(items, _) = command else {
+            return;
+        };
+        let Some(SExpr::Atom(Token::ReservedWord(head), _)) = items.first() else {
+            return;
+        };
+        match (head, &items[1..]) {
+            (Reserved::Assume, [_, term]) => self.term(term),
+            (Reserved::Step, [id, rest @ ..]) => {
+                let attributes = attributes(rest);
+                let rule = attributes.iter().find_map(|(k, v)| match k {
+                    Some("rule") => v.as_symbol(),
+                    _ => None,
+                });
+                let skip_args = rule == Some("hole") && !self.parse_hole_args;
+                for (keyword, value) in attributes {
+                    match (keyword, value) {
+                        (None, SExpr::List(clause, _)) => {
+                            for literal in clause.iter().skip(1) {
+                                self.term(literal);
+                            }
+                        }
+                        (Some("args"), SExpr::List(args, _)) if !skip_args => {
+                            for arg in args {
+                                match arg {
+                                    SExpr::List(items, _) if matches!(items.first(), Some(SExpr::Atom(Token::Keyword(k), _)) if k == "=") =>
+                                    {
+                                        self.assignment_or_var(arg);
+                                    }
+                                    _ => self.term(arg),
+                                }
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                if let (Some(id), Some(end)) = (id.as_symbol(), subproof_ends.last()) {
+                    if id == end {
+                        subproof_ends.pop();
+                        self.scopes.pop();
+                    }
+                }
+            }
+            (Reserved::Anchor, rest) => {
+                let mut scope = HashSet::new();
+                let mut end = None;
+                for (keyword, value) in attributes(rest) {
+                    match (keyword, value) {
+                        (Some("step"), id) => end = id.as_symbol().map(str::to_owned),
+                        (Some("args"), SExpr::List(args, _)) => {
+                            for arg in args {
+                                // Each argument may refer to the variables introduced before it
+                                self.scopes.push(scope);
+                                let name = self.assignment_or_var(arg);
+                                scope = self.scopes.pop().unwrap();
+                                scope.extend(name);
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                if let Some(end) = end {
+                    self.scopes.push(scope);
+                    subproof_ends.push(end);
+                }
+            }
+            (Reserved::DefineFun, [name, params, sort, body]) => {
+                let scope = self.sorted_vars(params);
+                self.sort(sort);
+                self.scopes.push(scope);
+                self.term(body);
+                self.scopes.pop();
+                self.defined.extend(name.as_symbol().map(str::to_owned));
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Reads a proof and returns all functions and sorts that it uses, but that are declared neither
+/// in `declarations` nor in the proof itself. These are returned in the order in which they are
+/// first used.
+pub(super) fn find_undeclared_symbols<R: BufRead>(
+    declarations: &dyn Declarations,
+    proof: R,
+    parse_hole_args: bool,
+) -> CarcaraResult<Vec<UndeclaredSymbol>> {
+    let commands = read_all(proof)?;
+    let mut scanner = Scanner {
+        declarations,
+        defined: HashSet::new(),
+        scopes: Vec::new(),
+        found: IndexMap::new(),
+        parse_hole_args,
+    };
+    let mut subproof_ends = Vec::new();
+    for command in &commands {
+        scanner.command(command, &mut subproof_ends);
+    }
+    Ok(scanner.found.into_values().collect())
+}
@@ -1,25 +1,40 @@
 //! A parser for the Alethe proof format.
 
 mod error;
+mod inference;
+mod legacy;
 mod lexer;
+mod mismatch;
+mod profile;
 pub(crate) mod tests;
+mod version;
 
 use std::iter::Iterator;
 
-pub use error::{ParserError, SortError};
+pub use error::{ParserError, SortError, SortErrorContext};
+pub use inference::synthesize_prelude;
 pub use lexer::{Lexer, Position, Reserved, Token};
+pub use mismatch::{UndeclaredSymbol, UndeclaredSymbols};
+pub use profile::StringsProfile;
+pub use version::AletheVersion;
+
+/// The positions of the first and last tokens of a term in the source.
+type Span = (Position, Position);
 
 use crate::{
     ast::*,
+    model::Model,
     utils::{HashCache, HashMapStack},
     CarcaraResult, Error,
 };
 use error::assert_num_args;
 use indexmap::{IndexMap, IndexSet};
 use rug::{Integer, Rational};
-use std::{io::BufRead, str::FromStr};
+use std::{collections::HashMap, io::BufRead, str::FromStr, sync::Arc};
 
-use self::error::assert_indexed_op_args_value;
+use self::error::{
+    assert_ff_sorts, assert_fp_sorts, assert_indexed_op_args_value, assert_prime_order,
+};
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Config {
@@ -49,6 +64,29 @@ pub struct Config {
     /// If `true`, the parser will parse arguments to the `hole` rule, expecting them to be valid
     /// terms.
     pub parse_hole_args: bool,
+
+    /// Selects which string operators are recognized. By default, only the operators from the
+    /// SMT-LIB standard are recognized, and the names of cvc5 extensions like `str.update` are
+    /// treated as ordinary identifiers.
+    pub strings_profile: StringsProfile,
+
+    /// The version of the Alethe format in which the proof is written. If this is `None`, the
+    /// version is taken from the proof's `(set-info :alethe-version ...)` command, if it exists,
+    /// or inferred from the rules used in the proof. Proofs in older versions are normalized to
+    /// the current version.
+    pub alethe_version: Option<AletheVersion>,
+
+    /// If `true`, a step id that was already used is not an error. Instead, the repeated command
+    /// is given a fresh id, and later references to the original id refer to the most recent
+    /// command that used it. The renamed ids can be retrieved with
+    /// [`Parser::renamed_step_ids`].
+    pub rename_duplicate_step_ids: bool,
+
+    /// If `true`, the proof is read once before being parsed, to check that every function and
+    /// sort it uses is declared in the problem. If not, a single error reporting all of the
+    /// undeclared symbols is returned. This catches proofs paired with the wrong problem early,
+    /// but requires the whole proof to be kept in memory while parsing.
+    pub detect_mismatch: bool,
 }
 
 impl Config {
@@ -79,13 +117,276 @@ pub fn parse_instance_with_pool<T: BufRead>(
     pool: &mut PrimitivePool,
 ) -> CarcaraResult<(Problem, Proof)> {
     let mut parser = Parser::new(pool, config, problem)?;
-    let problem = parser.parse_problem()?;
-    parser.reset(proof)?;
+    let mut problem = parser.parse_problem()?;
+    if !config.detect_mismatch {
+        parser.reset(proof)?;
+        let proof = parser.parse_proof()?;
+        problem.parsing_context = Some(parser.parsing_context());
+        return Ok((problem, proof));
+    }
+
+    let mut proof_bytes = Vec::new();
+    let mut proof = proof;
+    proof.read_to_end(&mut proof_bytes)?;
+    parser.check_proof_symbols(proof_bytes.as_slice())?;
+    let mut parser = parser.with_input(proof_bytes.as_slice())?;
     let proof = parser.parse_proof()?;
+    problem.parsing_context = Some(parser.parsing_context());
     Ok((problem, proof))
 }
 
+/// Parses a clause, written as `(cl <term>*)`, in which the terms may use the sorts and functions
+/// declared in `prelude`.
+pub fn parse_clause_with_prelude(
+    clause: &str,
+    prelude: &ProblemPrelude,
+    config: Config,
+    pool: &mut PrimitivePool,
+) -> CarcaraResult<Vec<Rc<Term>>> {
+    let declarations = prelude.to_string();
+    let mut parser = Parser::new(pool, config, declarations.as_bytes())?;
+    parser.parse_problem()?;
+    parser.reset(clause.as_bytes())?;
+    parser.parse_single_clause()
+}
+
+/// Parses a clause, written as `(cl <term>*)`, in the context in which a problem and its proof
+/// were parsed. That is, the terms may use any sort or function declared or defined in them, and
+/// are parsed using the same configuration.
+pub fn parse_clause_in_context(
+    clause: &str,
+    context: &ParsingContext,
+    pool: &mut PrimitivePool,
+) -> CarcaraResult<Vec<Rc<Term>>> {
+    let mut parser = Parser::new(pool, context.config, clause.as_bytes())?;
+    parser.state = context.state.clone();
+    parser.is_real_only_logic = context.is_real_only_logic;
+    parser.is_transcendental_logic = context.is_transcendental_logic;
+    parser.parse_single_clause()
+}
+
+/// Parses a proof without its original problem.
+///
+/// Since there is no problem, any sorts and functions used in the proof must be declared in the
+/// proof itself, using `declare-sort`, `declare-fun` or `declare-const` commands. These are
+/// collected in the prelude of the returned problem. The premises of the returned problem are the
+/// terms of all `assume` commands outside of subproofs, so checking the proof against it trusts
+/// every assumption.
+pub fn parse_proof_without_problem<T: BufRead>(
+    proof: T,
+    config: Config,
+) -> CarcaraResult<(Problem, Proof, PrimitivePool)> {
+    let mut pool = PrimitivePool::new();
+    let mut parser = Parser::new(&mut pool, config, proof)?;
+    parser.proof_declarations = Some(ProblemPrelude::new());
+    let proof = parser.parse_proof()?;
+    let prelude = parser.proof_declarations.take().unwrap();
+    let mut problem = standalone_problem(prelude, &proof);
+    problem.parsing_context = Some(parser.parsing_context());
+    Ok((problem, proof, pool))
+}
+
+/// Parses a proof without its original problem, like [`parse_proof_without_problem`], but first
+/// infers the declarations of all sorts and functions used in the proof that are not declared in it
+/// (see [`synthesize_prelude`]). These inferred declarations are included in the prelude of the
+/// returned problem.
+pub fn parse_proof_with_synthesized_prelude<T: BufRead>(
+    mut proof: T,
+    config: Config,
+) -> CarcaraResult<(Problem, Proof, PrimitivePool)> {
+    let mut proof_bytes = Vec::new();
+    proof.read_to_end(&mut proof_bytes)?;
+    let declarations = synthesize_prelude(proof_bytes.as_slice(), config)?;
+
+    let mut pool = PrimitivePool::new();
+    let mut parser = Parser::new(&mut pool, config, declarations.as_bytes())?;
+    parser.proof_declarations = Some(ProblemPrelude::new());
+    parser.parse_proof()?;
+    parser.reset(proof_bytes.as_slice())?;
+    let proof = parser.parse_proof()?;
+    let prelude = parser.proof_declarations.take().unwrap();
+    let mut problem = standalone_problem(prelude, &proof);
+    problem.parsing_context = Some(parser.parsing_context());
+    Ok((problem, proof, pool))
+}
+
+/// Parses a proof file in which the solver also printed the problem, so no separate problem file is
+/// needed.
+///
+/// The file starts with the SMT-LIB commands of the problem, which are parsed as usual, and is
+/// followed by the proof, optionally preceded by the `unsat` response. Declarations may also
+/// appear among the proof commands, and are included in the prelude of the returned problem. If
+/// the file contains the `assert` commands of the problem, the `assume` commands of the proof are
+/// checked against them. Otherwise, the premises of the returned problem are the terms of all
+/// `assume` commands outside of subproofs, so checking the proof trusts every assumption.
+pub fn parse_embedded_instance<T: BufRead>(
+    proof: T,
+    config: Config,
+) -> CarcaraResult<(Problem, Proof, PrimitivePool)> {
+    let mut pool = PrimitivePool::new();
+    let mut parser = Parser::new(&mut pool, config, proof)?;
+    let mut problem = parser.parse_embedded_problem()?;
+    parser.proof_declarations = Some(ProblemPrelude::new());
+    let proof = parser.parse_proof()?;
+    let declarations = parser.proof_declarations.take().unwrap();
+    problem.parsing_context = Some(parser.parsing_context());
+
+    let prelude = &mut problem.prelude;
+    prelude
+        .sort_declarations
+        .extend(declarations.sort_declarations);
+    prelude
+        .function_declarations
+        .extend(declarations.function_declarations);
+    if problem.premises.is_empty() {
+        let standalone = standalone_problem(ProblemPrelude::new(), &proof);
+        if !standalone.premises.is_empty() {
+            log::warn!(
+                "the proof file contains no assertions, so {} assumption(s) were trusted",
+                standalone.premises.len()
+            );
+        }
+        problem.premises = standalone.premises;
+    }
+    Ok((problem, proof, pool))
+}
+
+/// Returns `true` if `op` is one of the transcendental functions, which are only available in logics
+/// that include them.
+fn is_transcendental(op: Operator) -> bool {
+    matches!(
+        op,
+        Operator::Exp
+            | Operator::Sin
+            | Operator::Cos
+            | Operator::Tan
+            | Operator::Csc
+            | Operator::Sec
+            | Operator::Cot
+            | Operator::ArcSin
+            | Operator::ArcCos
+            | Operator::ArcTan
+            | Operator::ArcCsc
+            | Operator::ArcSec
+            | Operator::ArcCot
+            | Operator::Sqrt
+            | Operator::Pi
+    )
+}
+
+/// Returns the value of a finite field literal written as a qualified symbol, like the `ff3` in
+/// `(as ff3 (_ FiniteField 7))`. The value may be negative, as in `ff-1`.
+fn ff_literal_value(symbol: &str) -> Option<Integer> {
+    let value = symbol.strip_prefix("ff")?;
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// Builds the problem of a proof parsed without its original problem, in which the premises are
+/// the terms of all `assume` commands outside of subproofs.
+fn standalone_problem(prelude: ProblemPrelude, proof: &Proof) -> Problem {
+    let premises = proof
+        .commands
+        .iter()
+        .filter_map(|c| match c {
+            ProofCommand::Assume { term, .. } => Some(term.clone()),
+            _ => None,
+        })
+        .collect();
+    Problem {
+        prelude,
+        premises,
+        assumptions: IndexSet::new(),
+        parsing_context: None,
+    }
+}
+
+/// Parses an SMT problem instance and several proofs of it.
+///
+/// The problem is only parsed once, and all proofs share the same `TermPool`. Each proof is parsed
+/// independently of the others, so the constants defined and the step ids used in one proof are not
+/// visible when parsing the next ones. If a proof fails to parse, the remaining proofs are still
+/// parsed, so this returns one result for each proof.
+pub fn parse_problem_and_proofs<T: BufRead>(
+    problem: T,
+    proofs: Vec<T>,
+    config: Config,
+) -> CarcaraResult<(Problem, Vec<CarcaraResult<Proof>>, PrimitivePool)> {
+    let mut pool = PrimitivePool::new();
+    let mut parser = Parser::new(&mut pool, config, problem)?;
+    let mut problem = parser.parse_problem()?;
+    problem.parsing_context = Some(parser.parsing_context());
+    let state = parser.state.clone();
+    let proofs = proofs
+        .into_iter()
+        .map(|proof| {
+            parser.state = state.clone();
+            parser.reset(proof)?;
+            parser.parse_proof()
+        })
+        .collect();
+    Ok((problem, proofs, pool))
+}
+
+/// Parses an SMT problem instance (in the SMT-LIB format) and its associated proof, in the
+/// proof-trace format used by veriT before the Alethe format. The proof is converted to an Alethe
+/// proof. See [`Parser::parse_legacy_proof`] for details.
+pub fn parse_legacy_instance<T: BufRead>(
+    problem: T,
+    proof: T,
+    config: Config,
+) -> CarcaraResult<(Problem, Proof, PrimitivePool)> {
+    let mut pool = PrimitivePool::new();
+    let mut parser = Parser::new(&mut pool, config, problem)?;
+    let mut problem = parser.parse_problem()?;
+    parser.reset(proof)?;
+    let proof = parser.parse_legacy_proof()?;
+    problem.parsing_context = Some(parser.parsing_context());
+    Ok((problem, proof, pool))
+}
+
+/// Parses an SMT problem instance (in the SMT-LIB format) and a model for it, in the format produced
+/// by the `get-model` command.
+pub fn parse_problem_and_model<T: BufRead>(
+    problem: T,
+    model: T,
+    config: Config,
+) -> CarcaraResult<(Problem, Model, PrimitivePool)> {
+    let mut pool = PrimitivePool::new();
+    let mut parser = Parser::new(&mut pool, config, problem)?;
+    let problem = parser.parse_problem()?;
+    parser.reset(model)?;
+    let model = parser.parse_model()?;
+    Ok((problem, model, pool))
+}
+
+/// Wraps an error that happened when constructing an application of `function`. If it is a sort
+/// error in one of the arguments, the application is added as the context of the error, and the
+/// error points to the argument. Otherwise, the error points to the start of the application.
+fn application_error(
+    err: ParserError,
+    function: &dyn std::fmt::Display,
+    spans: &[Span],
+    head_pos: Position,
+) -> Error {
+    match err {
+        ParserError::SortError(mut e) if e.argument.is_some_and(|i| i < spans.len()) => {
+            let span = spans[e.argument.unwrap()];
+            e.context = Some(Box::new(SortErrorContext {
+                function: function.to_string(),
+                span,
+            }));
+            Error::Parser(e.into(), span.0)
+        }
+        other => Error::Parser(other, head_pos),
+    }
+}
+
 /// A function definition, from a `define-fun` command.
+#[derive(Clone)]
 struct FunctionDef {
     params: Vec<SortedVar>,
     body: Rc<Term>,
@@ -98,8 +399,9 @@ impl FunctionDef {
             return Ok(self.body.clone());
         }
 
-        for (arg, param) in args.iter().zip(self.params.iter()) {
-            SortError::assert_eq(param.1.as_sort().unwrap(), p.sort(arg).as_sort().unwrap())?;
+        for (i, (arg, param)) in args.iter().zip(self.params.iter()).enumerate() {
+            SortError::assert_eq(param.1.as_sort().unwrap(), p.sort(arg).as_sort().unwrap())
+                .map_err(|e| e.at(i))?;
         }
 
         // Build a hash map of all the parameter names and the values they will
@@ -121,22 +423,72 @@ impl FunctionDef {
 }
 
 /// A sort definition, from a `define-sort` command.
+#[derive(Clone)]
 struct SortDef {
     params: Vec<String>,
     body: Rc<Term>,
 }
 
+impl<R: BufRead> mismatch::Declarations for Parser<'_, R> {
+    fn has_function(&self, name: &str, arity: usize) -> bool {
+        if self.lookup_operator(name).is_some() {
+            return true;
+        }
+        if let Some(def) = self.state.function_defs.get(name) {
+            return def.params.len() == arity;
+        }
+        let name = HashCache::new(name.to_owned());
+        match self.state.symbol_table.get(&name).map(|s| s.as_sort()) {
+            // Functions may also be used as values, in higher-order terms
+            Some(Some(Sort::Function(sorts))) => arity == 0 || arity == sorts.len() - 1,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn has_sort(&self, name: &str, arity: usize) -> bool {
+        if let Some(def) = self.state.sort_defs.get(name) {
+            return def.params.len() == arity;
+        }
+        self.state.sort_declarations.get(name) == Some(&arity)
+    }
+}
+
 /// The state of the parser.
 ///
 /// This holds all the function, constant or sort declarations and definitions, as well as the term
 /// pool used by the parser.
-#[derive(Default)]
+#[derive(Default, Clone)]
 struct ParserState {
     symbol_table: HashMapStack<HashCache<String>, Rc<Term>>,
     function_defs: IndexMap<String, FunctionDef>,
     sort_declarations: HashMapStack<String, usize>,
     sort_defs: IndexMap<String, SortDef>,
-    step_ids: HashMapStack<HashCache<String>, usize>,
+    datatypes: Datatypes,
+    // Each step id is mapped to the index of its command, and the position where it was defined
+    step_ids: HashMapStack<HashCache<String>, (usize, Position)>,
+    // The ids of the commands inside subproofs that were already closed, each mapped to the id of
+    // the step that closed its subproof. These commands can no longer be referenced
+    closed_step_ids: HashMap<HashCache<String>, String>,
+}
+
+/// The declarations and definitions visible at the end of parsing a problem and its proof, and
+/// the configuration used to parse them. This allows parsing other terms in the same context, like
+/// the goal that the proof is expected to conclude. See [`parse_clause_in_context`].
+#[derive(Clone)]
+pub struct ParsingContext {
+    config: Config,
+    state: ParserState,
+    is_real_only_logic: bool,
+    is_transcendental_logic: bool,
+}
+
+impl std::fmt::Debug for ParsingContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ParsingContext")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
 }
 
 /// A parser for the Alethe proof format.
@@ -146,9 +498,38 @@ pub struct Parser<'a, R> {
     lexer: Lexer<R>,
     current_token: Token,
     current_position: Position,
+    previous_position: Position,
     state: ParserState,
     is_real_only_logic: bool,
+
+    /// Whether the problem's logic includes transcendental functions, like `exp` and `sin`. These
+    /// are only recognized as operators in such logics, since their names are otherwise free for
+    /// user declarations.
+    is_transcendental_logic: bool,
+
     problem: Option<Problem>,
+
+    /// When parsing a proof without its problem, this collects the sort and function declarations
+    /// that appear in the proof. Otherwise, this is `None`, and declarations in the proof are
+    /// rejected.
+    proof_declarations: Option<ProblemPrelude>,
+
+    /// The version of the Alethe format of the proof being parsed, if it is known.
+    alethe_version: Option<AletheVersion>,
+
+    /// In strict mode, assignment arguments without a sort hint are accepted provisionally while
+    /// the Alethe version is not yet known, since the proof may later turn out to be in the legacy
+    /// version. This holds the first such argument, and where it appeared, so it can be rejected
+    /// once the proof is parsed if the version does not allow it.
+    first_unsorted_assign: Option<(String, Position)>,
+
+    /// Whether the opening parenthesis of the first proof command was already consumed. This
+    /// happens when the problem is embedded in the proof file, since the parser only knows the
+    /// problem has ended once it reads the first proof command.
+    in_proof_command: bool,
+
+    /// The step ids that were renamed because they were repeated, each paired with its new id.
+    renamed_step_ids: Vec<(String, String)>,
 }
 
 impl<'a, R: BufRead> Parser<'a, R> {
@@ -164,9 +545,16 @@ impl<'a, R: BufRead> Parser<'a, R> {
             lexer,
             current_token,
             current_position,
+            previous_position: current_position,
             state: ParserState::default(),
             is_real_only_logic: false,
+            is_transcendental_logic: false,
             problem: None,
+            proof_declarations: None,
+            alethe_version: config.alethe_version,
+            first_unsorted_assign: None,
+            renamed_step_ids: Vec::new(),
+            in_proof_command: false,
         })
     }
 
@@ -178,9 +566,50 @@ impl<'a, R: BufRead> Parser<'a, R> {
         self.lexer = lexer;
         self.current_token = current_token;
         self.current_position = current_position;
+        self.previous_position = current_position;
         Ok(())
     }
 
+    /// Consumes the parser, returning a new parser that reads from `input`, and keeps the parser
+    /// state. This is like [`Parser::reset`], but allows the input to have a different type.
+    fn with_input<S: BufRead>(self, input: S) -> CarcaraResult<Parser<'a, S>> {
+        let mut lexer = Lexer::new(input)?;
+        let (current_token, current_position) = lexer.next_token()?;
+        Ok(Parser {
+            pool: self.pool,
+            config: self.config,
+            lexer,
+            current_token,
+            current_position,
+            previous_position: current_position,
+            state: self.state,
+            is_real_only_logic: self.is_real_only_logic,
+            is_transcendental_logic: self.is_transcendental_logic,
+            problem: self.problem,
+            proof_declarations: self.proof_declarations,
+            alethe_version: self.alethe_version,
+            first_unsorted_assign: self.first_unsorted_assign,
+            renamed_step_ids: self.renamed_step_ids,
+            in_proof_command: self.in_proof_command,
+        })
+    }
+
+    /// Reads a proof without parsing it, and returns an error if it uses any functions or sorts
+    /// that are not declared in the problem. All declarations from the problem should already be
+    /// in the parser state.
+    pub fn check_proof_symbols<S: BufRead>(&self, proof: S) -> CarcaraResult<()> {
+        let undeclared =
+            mismatch::find_undeclared_symbols(self, proof, self.config.parse_hole_args)?;
+        match undeclared.first() {
+            None => Ok(()),
+            Some(first) => {
+                let position = first.first_use;
+                let err = ParserError::ProblemMismatch(UndeclaredSymbols(undeclared));
+                Err(Error::Parser(err, position))
+            }
+        }
+    }
+
     /// Advances the parser one token, and returns the previous `current_token`.
     fn next_token(&mut self) -> CarcaraResult<(Token, Position)> {
         use std::mem::replace;
@@ -188,6 +617,7 @@ impl<'a, R: BufRead> Parser<'a, R> {
         let (new_token, new_position) = self.lexer.next_token()?;
         let old_token = replace(&mut self.current_token, new_token);
         let old_position = replace(&mut self.current_position, new_position);
+        self.previous_position = old_position;
         Ok((old_token, old_position))
     }
 
@@ -232,20 +662,20 @@ impl<'a, R: BufRead> Parser<'a, R> {
             Operator::True | Operator::False => assert_num_args(&args, 0)?,
             Operator::Not => {
                 assert_num_args(&args, 1)?;
-                SortError::assert_eq(&Sort::Bool, sorts[0])?;
+                SortError::assert_eq(&Sort::Bool, sorts[0]).map_err(|e| e.at(0))?;
             }
             Operator::Implies => {
                 assert_num_args(&args, 2..)?;
-                for s in sorts {
-                    SortError::assert_eq(&Sort::Bool, s)?;
+                for (i, s) in sorts.into_iter().enumerate() {
+                    SortError::assert_eq(&Sort::Bool, s).map_err(|e| e.at(i))?;
                 }
             }
             Operator::Or | Operator::And | Operator::Xor => {
                 // If we are not in "strict" parsing mode, we allow these operators to be called
                 // with just one argument
                 assert_num_args(&args, if self.config.strict { 2.. } else { 1.. })?;
-                for s in sorts {
-                    SortError::assert_eq(&Sort::Bool, s)?;
+                for (i, s) in sorts.into_iter().enumerate() {
+                    SortError::assert_eq(&Sort::Bool, s).map_err(|e| e.at(i))?;
                 }
             }
             Operator::Equals | Operator::Distinct => {
@@ -254,8 +684,8 @@ impl<'a, R: BufRead> Parser<'a, R> {
             }
             Operator::Ite => {
                 assert_num_args(&args, 3)?;
-                SortError::assert_eq(&Sort::Bool, sorts[0])?;
-                SortError::assert_eq(sorts[1], sorts[2])?;
+                SortError::assert_eq(&Sort::Bool, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(sorts[1], sorts[2]).map_err(|e| e.at(2))?;
             }
             Operator::Add | Operator::Sub | Operator::Mult => {
                 // The `-` operator, in particular, can be called with only one argument, in which
@@ -269,17 +699,19 @@ impl<'a, R: BufRead> Parser<'a, R> {
                 // All the arguments must be either Int or Real. Also, if we are not allowing
                 // Int/Real subtyping, all arguments must have the same sort
                 if self.config.allow_int_real_subtyping {
-                    for s in sorts {
-                        SortError::assert_one_of(&[Sort::Int, Sort::Real], s)?;
+                    for (i, s) in sorts.into_iter().enumerate() {
+                        SortError::assert_one_of(&[Sort::Int, Sort::Real], s)
+                            .map_err(|e| e.at(i))?;
                     }
                 } else {
-                    SortError::assert_one_of(&[Sort::Int, Sort::Real], sorts[0])?;
+                    SortError::assert_one_of(&[Sort::Int, Sort::Real], sorts[0])
+                        .map_err(|e| e.at(0))?;
                     SortError::assert_all_eq(&sorts)?;
                 }
             }
             Operator::IntDiv => {
                 assert_num_args(&args, 2..)?;
-                SortError::assert_eq(&Sort::Int, sorts[0])?;
+                SortError::assert_eq(&Sort::Int, sorts[0]).map_err(|e| e.at(0))?;
                 SortError::assert_all_eq(&sorts)?;
             }
             Operator::RealDiv => {
@@ -288,11 +720,12 @@ impl<'a, R: BufRead> Parser<'a, R> {
                 // Normally, the `/` operator may only receive Real arguments, but if we are
                 // allowing Int/Real subtyping, it may also receive Ints
                 if self.config.allow_int_real_subtyping {
-                    for s in sorts {
-                        SortError::assert_one_of(&[Sort::Int, Sort::Real], s)?;
+                    for (i, s) in sorts.into_iter().enumerate() {
+                        SortError::assert_one_of(&[Sort::Int, Sort::Real], s)
+                            .map_err(|e| e.at(i))?;
                     }
                 } else {
-                    SortError::assert_eq(&Sort::Real, sorts[0])?;
+                    SortError::assert_eq(&Sort::Real, sorts[0]).map_err(|e| e.at(0))?;
                     SortError::assert_all_eq(&sorts)?;
                 }
 
@@ -302,46 +735,136 @@ impl<'a, R: BufRead> Parser<'a, R> {
             }
             Operator::Mod => {
                 assert_num_args(&args, 2)?;
-                SortError::assert_eq(&Sort::Int, sorts[0])?;
-                SortError::assert_eq(&Sort::Int, sorts[1])?;
+                SortError::assert_eq(&Sort::Int, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::Int, sorts[1]).map_err(|e| e.at(1))?;
             }
             Operator::Abs => {
                 assert_num_args(&args, 1)?;
-                SortError::assert_eq(&Sort::Int, sorts[0])?;
+                SortError::assert_eq(&Sort::Int, sorts[0]).map_err(|e| e.at(0))?;
             }
             Operator::LessThan | Operator::GreaterThan | Operator::LessEq | Operator::GreaterEq => {
                 assert_num_args(&args, 2..)?;
                 // All the arguments must be either Int or Real sorted, but they don't need to all
                 // have the same sort
-                for s in sorts {
-                    SortError::assert_one_of(&[Sort::Int, Sort::Real], s)?;
+                for (i, s) in sorts.into_iter().enumerate() {
+                    SortError::assert_one_of(&[Sort::Int, Sort::Real], s).map_err(|e| e.at(i))?;
                 }
             }
             Operator::ToReal => {
                 assert_num_args(&args, 1)?;
-                SortError::assert_eq(&Sort::Int, sorts[0])?;
+                SortError::assert_eq(&Sort::Int, sorts[0]).map_err(|e| e.at(0))?;
+            }
+            Operator::Pi => assert_num_args(&args, 0)?,
+            Operator::Exp
+            | Operator::Sin
+            | Operator::Cos
+            | Operator::Tan
+            | Operator::Csc
+            | Operator::Sec
+            | Operator::Cot
+            | Operator::ArcSin
+            | Operator::ArcCos
+            | Operator::ArcTan
+            | Operator::ArcCsc
+            | Operator::ArcSec
+            | Operator::ArcCot
+            | Operator::Sqrt => {
+                assert_num_args(&args, 1)?;
+                SortError::assert_one_of(&[Sort::Int, Sort::Real], sorts[0])
+                    .map_err(|e| e.at(0))?;
             }
             Operator::ToInt | Operator::IsInt => {
                 assert_num_args(&args, 1)?;
-                SortError::assert_eq(&Sort::Real, sorts[0])?;
+                SortError::assert_eq(&Sort::Real, sorts[0]).map_err(|e| e.at(0))?;
             }
             Operator::Select => {
                 assert_num_args(&args, 2)?;
-                SortError::assert_array_sort(self.pool, Some(sorts[1]), None, sorts[0])?;
+                SortError::assert_array_sort(self.pool, Some(sorts[1]), None, sorts[0])
+                    .map_err(|e| e.at(0))?;
             }
             Operator::Store => {
                 assert_num_args(&args, 3)?;
-                SortError::assert_array_sort(self.pool, Some(sorts[1]), Some(sorts[2]), sorts[0])?;
+                SortError::assert_array_sort(self.pool, Some(sorts[1]), Some(sorts[2]), sorts[0])
+                    .map_err(|e| e.at(0))?;
+            }
+            Operator::SetUnion | Operator::SetInter | Operator::SetMinus | Operator::SetSubset => {
+                assert_num_args(&args, 2)?;
+                SortError::assert_set_sort(self.pool, None, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(sorts[0], sorts[1]).map_err(|e| e.at(1))?;
+            }
+            Operator::SetComplement | Operator::SetCard => {
+                assert_num_args(&args, 1)?;
+                SortError::assert_set_sort(self.pool, None, sorts[0]).map_err(|e| e.at(0))?;
+            }
+            Operator::SetMember => {
+                assert_num_args(&args, 2)?;
+                SortError::assert_set_sort(self.pool, Some(sorts[0]), sorts[1])
+                    .map_err(|e| e.at(1))?;
+            }
+            Operator::SetSingleton => assert_num_args(&args, 1)?,
+            Operator::SetInsert => {
+                assert_num_args(&args, 2..)?;
+                let n = sorts.len() - 1;
+                for (i, s) in sorts[..n].iter().enumerate() {
+                    SortError::assert_set_sort(self.pool, Some(s), sorts[n])
+                        .map_err(|e| e.at(i))?;
+                }
+            }
+            Operator::SeqConcat => {
+                assert_num_args(&args, 2..)?;
+                SortError::assert_seq_sort(self.pool, None, sorts[0]).map_err(|e| e.at(0))?;
+                for (i, s) in sorts.iter().enumerate().skip(1) {
+                    SortError::assert_eq(sorts[0], s).map_err(|e| e.at(i))?;
+                }
+            }
+            Operator::SeqLen | Operator::SeqRev => {
+                assert_num_args(&args, 1)?;
+                SortError::assert_seq_sort(self.pool, None, sorts[0]).map_err(|e| e.at(0))?;
+            }
+            Operator::SeqUnit => assert_num_args(&args, 1)?,
+            Operator::SeqAt | Operator::SeqNth => {
+                assert_num_args(&args, 2)?;
+                SortError::assert_seq_sort(self.pool, None, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::Int, sorts[1]).map_err(|e| e.at(1))?;
+            }
+            Operator::SeqExtract => {
+                assert_num_args(&args, 3)?;
+                SortError::assert_seq_sort(self.pool, None, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::Int, sorts[1]).map_err(|e| e.at(1))?;
+                SortError::assert_eq(&Sort::Int, sorts[2]).map_err(|e| e.at(2))?;
+            }
+            Operator::SeqUpdate => {
+                assert_num_args(&args, 3)?;
+                SortError::assert_seq_sort(self.pool, None, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::Int, sorts[1]).map_err(|e| e.at(1))?;
+                SortError::assert_eq(sorts[0], sorts[2]).map_err(|e| e.at(2))?;
+            }
+            Operator::SeqContains | Operator::SeqPrefixOf | Operator::SeqSuffixOf => {
+                assert_num_args(&args, 2)?;
+                SortError::assert_seq_sort(self.pool, None, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(sorts[0], sorts[1]).map_err(|e| e.at(1))?;
+            }
+            Operator::SeqIndexOf => {
+                assert_num_args(&args, 3)?;
+                SortError::assert_seq_sort(self.pool, None, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(sorts[0], sorts[1]).map_err(|e| e.at(1))?;
+                SortError::assert_eq(&Sort::Int, sorts[2]).map_err(|e| e.at(2))?;
+            }
+            Operator::SeqReplace | Operator::SeqReplaceAll => {
+                assert_num_args(&args, 3)?;
+                SortError::assert_seq_sort(self.pool, None, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(sorts[0], sorts[1]).map_err(|e| e.at(1))?;
+                SortError::assert_eq(sorts[0], sorts[2]).map_err(|e| e.at(2))?;
             }
             Operator::StrConcat => {
                 assert_num_args(&args, 2..)?;
-                for s in sorts {
-                    SortError::assert_eq(&Sort::String, s)?;
+                for (i, s) in sorts.into_iter().enumerate() {
+                    SortError::assert_eq(&Sort::String, s).map_err(|e| e.at(i))?;
                 }
             }
             Operator::StrLen | Operator::StrIsDigit | Operator::StrToCode | Operator::StrToInt => {
                 assert_num_args(&args, 1)?;
-                SortError::assert_eq(&Sort::String, sorts[0])?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
             }
             Operator::StrLessThan
             | Operator::StrLessEq
@@ -350,44 +873,60 @@ impl<'a, R: BufRead> Parser<'a, R> {
             | Operator::Contains
             | Operator::ReRange => {
                 assert_num_args(&args, 2)?;
-                SortError::assert_eq(&Sort::String, sorts[0])?;
-                SortError::assert_eq(&Sort::String, sorts[1])?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::String, sorts[1]).map_err(|e| e.at(1))?;
             }
             Operator::CharAt => {
                 assert_num_args(&args, 2)?;
-                SortError::assert_eq(&Sort::String, sorts[0])?;
-                SortError::assert_eq(&Sort::Int, sorts[1])?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::Int, sorts[1]).map_err(|e| e.at(1))?;
             }
             Operator::Substring => {
                 assert_num_args(&args, 3)?;
-                SortError::assert_eq(&Sort::String, sorts[0])?;
-                SortError::assert_eq(&Sort::Int, sorts[1])?;
-                SortError::assert_eq(&Sort::Int, sorts[2])?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::Int, sorts[1]).map_err(|e| e.at(1))?;
+                SortError::assert_eq(&Sort::Int, sorts[2]).map_err(|e| e.at(2))?;
             }
             Operator::IndexOf => {
                 assert_num_args(&args, 3)?;
-                SortError::assert_eq(&Sort::String, sorts[0])?;
-                SortError::assert_eq(&Sort::String, sorts[1])?;
-                SortError::assert_eq(&Sort::Int, sorts[2])?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::String, sorts[1]).map_err(|e| e.at(1))?;
+                SortError::assert_eq(&Sort::Int, sorts[2]).map_err(|e| e.at(2))?;
             }
             Operator::Replace | Operator::ReplaceAll => {
                 assert_num_args(&args, 3)?;
-                SortError::assert_eq(&Sort::String, sorts[0])?;
-                SortError::assert_eq(&Sort::String, sorts[1])?;
-                SortError::assert_eq(&Sort::String, sorts[2])?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::String, sorts[1]).map_err(|e| e.at(1))?;
+                SortError::assert_eq(&Sort::String, sorts[2]).map_err(|e| e.at(2))?;
             }
             Operator::StrFromCode | Operator::StrFromInt => {
                 assert_num_args(&args, 1)?;
-                SortError::assert_eq(&Sort::Int, sorts[0])?;
+                SortError::assert_eq(&Sort::Int, sorts[0]).map_err(|e| e.at(0))?;
+            }
+            Operator::StrUpdate => {
+                assert_num_args(&args, 3)?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::Int, sorts[1]).map_err(|e| e.at(1))?;
+                SortError::assert_eq(&Sort::String, sorts[2]).map_err(|e| e.at(2))?;
+            }
+            Operator::StrRev | Operator::StrToLower | Operator::StrToUpper => {
+                assert_num_args(&args, 1)?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
+            }
+            Operator::IndexOfRe => {
+                assert_num_args(&args, 3)?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::RegLan, sorts[1]).map_err(|e| e.at(1))?;
+                SortError::assert_eq(&Sort::Int, sorts[2]).map_err(|e| e.at(2))?;
             }
             Operator::StrToRe => {
                 assert_num_args(&args, 1)?;
-                SortError::assert_eq(&Sort::String, sorts[0])?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
             }
             Operator::StrInRe => {
                 assert_num_args(&args, 2)?;
-                SortError::assert_eq(&Sort::String, sorts[0])?;
-                SortError::assert_eq(&Sort::RegLan, sorts[1])?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::RegLan, sorts[1]).map_err(|e| e.at(1))?;
             }
             Operator::ReNone | Operator::ReAll | Operator::ReAllChar => {
                 assert_num_args(&args, 0)?;
@@ -397,8 +936,8 @@ impl<'a, R: BufRead> Parser<'a, R> {
             | Operator::ReIntersection
             | Operator::ReDiff => {
                 assert_num_args(&args, 2..)?;
-                for s in sorts {
-                    SortError::assert_eq(&Sort::RegLan, s)?;
+                for (i, s) in sorts.into_iter().enumerate() {
+                    SortError::assert_eq(&Sort::RegLan, s).map_err(|e| e.at(i))?;
                 }
             }
             Operator::ReKleeneClosure
@@ -406,13 +945,13 @@ impl<'a, R: BufRead> Parser<'a, R> {
             | Operator::ReKleeneCross
             | Operator::ReOption => {
                 assert_num_args(&args, 1)?;
-                SortError::assert_eq(&Sort::RegLan, sorts[0])?;
+                SortError::assert_eq(&Sort::RegLan, sorts[0]).map_err(|e| e.at(0))?;
             }
             Operator::ReplaceRe | Operator::ReplaceReAll => {
                 assert_num_args(&args, 3)?;
-                SortError::assert_eq(&Sort::String, sorts[0])?;
-                SortError::assert_eq(&Sort::RegLan, sorts[1])?;
-                SortError::assert_eq(&Sort::String, sorts[2])?;
+                SortError::assert_eq(&Sort::String, sorts[0]).map_err(|e| e.at(0))?;
+                SortError::assert_eq(&Sort::RegLan, sorts[1]).map_err(|e| e.at(1))?;
+                SortError::assert_eq(&Sort::String, sorts[2]).map_err(|e| e.at(2))?;
             }
             Operator::BvNot | Operator::BvNeg => {
                 assert_num_args(&args, 1)?;
@@ -422,9 +961,15 @@ impl<'a, R: BufRead> Parser<'a, R> {
                     }
                 }
             }
+            Operator::Bv2Nat | Operator::UbvToInt | Operator::SbvToInt => {
+                assert_num_args(&args, 1)?;
+                if !matches!(sorts[0], Sort::BitVec(_)) {
+                    return Err(ParserError::ExpectedBvSort(sorts[0].clone()));
+                }
+            }
             Operator::BvBbTerm => {
                 assert_num_args(&args, 1..)?;
-                SortError::assert_eq(&Sort::Bool, sorts[0])?;
+                SortError::assert_eq(&Sort::Bool, sorts[0]).map_err(|e| e.at(0))?;
                 SortError::assert_all_eq(&sorts)?;
             }
             Operator::BvConcat => {
@@ -473,6 +1018,74 @@ impl<'a, R: BufRead> Parser<'a, R> {
                 }
                 SortError::assert_all_eq(&sorts)?;
             }
+            Operator::RoundNearestTiesToEven
+            | Operator::RoundNearestTiesToAway
+            | Operator::RoundTowardPositive
+            | Operator::RoundTowardNegative
+            | Operator::RoundTowardZero => assert_num_args(&args, 0)?,
+            Operator::Fp => {
+                assert_num_args(&args, 3)?;
+                for s in &sorts {
+                    if !matches!(s, Sort::BitVec(_)) {
+                        return Err(ParserError::ExpectedBvSort((*s).clone()));
+                    }
+                }
+                SortError::assert_eq(&Sort::BitVec(Integer::ONE.into()), sorts[0])
+                    .map_err(|e| e.at(0))?;
+            }
+            Operator::FpAbs
+            | Operator::FpNeg
+            | Operator::FpIsNormal
+            | Operator::FpIsSubnormal
+            | Operator::FpIsZero
+            | Operator::FpIsInfinite
+            | Operator::FpIsNaN
+            | Operator::FpIsNegative
+            | Operator::FpIsPositive
+            | Operator::FpToReal => {
+                assert_num_args(&args, 1)?;
+                assert_fp_sorts(&sorts)?;
+            }
+            Operator::FpRem | Operator::FpMin | Operator::FpMax => {
+                assert_num_args(&args, 2)?;
+                assert_fp_sorts(&sorts)?;
+            }
+            Operator::FpLeq
+            | Operator::FpLt
+            | Operator::FpGeq
+            | Operator::FpGt
+            | Operator::FpEq => {
+                assert_num_args(&args, 2..)?;
+                assert_fp_sorts(&sorts)?;
+            }
+            Operator::FpAdd
+            | Operator::FpSub
+            | Operator::FpMul
+            | Operator::FpDiv
+            | Operator::FpFma
+            | Operator::FpSqrt
+            | Operator::FpRoundToIntegral => {
+                let num_args = match op {
+                    Operator::FpFma => 4,
+                    Operator::FpSqrt | Operator::FpRoundToIntegral => 2,
+                    _ => 3,
+                };
+                assert_num_args(&args, num_args)?;
+                SortError::assert_eq(&Sort::RoundingMode, sorts[0]).map_err(|e| e.at(0))?;
+                assert_fp_sorts(&sorts[1..])?;
+            }
+            Operator::FfAdd | Operator::FfMul => {
+                assert_num_args(&args, 2..)?;
+                assert_ff_sorts(&sorts)?;
+            }
+            Operator::FfNeg => {
+                assert_num_args(&args, 1)?;
+                assert_ff_sorts(&sorts)?;
+            }
+            Operator::FfBitSum => {
+                assert_num_args(&args, 1..)?;
+                assert_ff_sorts(&sorts)?;
+            }
             Operator::RareList => SortError::assert_all_eq(&sorts)?,
         }
         Ok(self.pool.add(Term::Op(op, args)))
@@ -520,7 +1133,8 @@ impl<'a, R: BufRead> Parser<'a, R> {
             SortError::assert_eq(
                 sorts[i].as_sort().unwrap(),
                 self.pool.sort(&args[i]).as_sort().unwrap(),
-            )?;
+            )
+            .map_err(|e| e.at(i))?;
         }
         Ok(self.pool.add(Term::App(function, args)))
     }
@@ -585,6 +1199,28 @@ impl<'a, R: BufRead> Parser<'a, R> {
         }
     }
 
+    /// Parses the arguments of an application, until a closing parenthesis is reached. Returns the
+    /// arguments, together with the span of each argument, given by the positions of its first and
+    /// last tokens. Like `parse_sequence`, this consumes the ending `)` token, and returns an error
+    /// if there are no arguments.
+    fn parse_arguments(&mut self) -> CarcaraResult<(Vec<Rc<Term>>, Vec<Span>)> {
+        let mut args = Vec::new();
+        let mut spans = Vec::new();
+        while self.current_token != Token::CloseParen {
+            let start = self.current_position;
+            args.push(self.parse_term()?);
+            spans.push((start, self.previous_position));
+        }
+        if args.is_empty() {
+            return Err(Error::Parser(
+                ParserError::EmptySequence,
+                self.current_position,
+            ));
+        }
+        self.next_token()?; // Consume `)` token
+        Ok((args, spans))
+    }
+
     /// Reads tokens until the matching closing parenthesis is reached.
     fn read_until_close_parens(&mut self) -> CarcaraResult<Vec<Token>> {
         let mut result = Vec::new();
@@ -610,6 +1246,31 @@ impl<'a, R: BufRead> Parser<'a, R> {
         Ok(())
     }
 
+    /// Parses attributes and their values until a closing parenthesis is reached. The values are
+    /// not interpreted, and are kept as text.
+    fn parse_remaining_attributes(&mut self) -> CarcaraResult<Vec<StepAttribute>> {
+        let mut result = Vec::new();
+        while let Token::Keyword(keyword) = &self.current_token {
+            let keyword = keyword.clone();
+            self.next_token()?;
+            let value = match self.current_token {
+                // If there is no value for this attribute, we may reach the closing parenthesis or
+                // the next attribute, which we must not consume
+                Token::CloseParen | Token::Eof | Token::Keyword(_) => None,
+
+                Token::OpenParen => {
+                    let (open, _) = self.next_token()?;
+                    let mut tokens = vec![open];
+                    tokens.extend(self.read_until_close_parens()?);
+                    Some(tokens_to_string(&tokens))
+                }
+                _ => Some(self.next_token()?.0.to_string()),
+            };
+            result.push(StepAttribute { keyword, value });
+        }
+        Ok(result)
+    }
+
     /// Consumes and ignores attributes and their values until a closing parenthesis is reached.
     fn ignore_remaining_attributes(&mut self) -> CarcaraResult<()> {
         while let Token::Keyword(_) = self.current_token {
@@ -627,6 +1288,7 @@ impl<'a, R: BufRead> Parser<'a, R> {
                 | Token::Numeral(_)
                 | Token::Decimal(_)
                 | Token::Bitvector { .. }
+                | Token::FiniteField { .. }
                 | Token::String(_)
                 | Token::ReservedWord(_) => {
                     self.next_token()?;
@@ -653,16 +1315,41 @@ impl<'a, R: BufRead> Parser<'a, R> {
     /// - `declare-const`
     /// - `declare-fun`
     /// - `declare-sort`
+    /// - `declare-datatype`
+    /// - `declare-datatypes`
     /// - `define-fun`
     /// - `set-logic`
     ///
     /// All other commands are ignored. This method returns a hash set containing the premises
     /// introduced in `assert` commands.
     pub fn parse_problem(&mut self) -> CarcaraResult<Problem> {
+        self.parse_problem_commands(false)
+    }
+
+    /// Parses the SMT-LIB commands at the start of a proof file in which the solver also printed
+    /// the problem. This stops at the first proof command, or at the `unsat` response of the
+    /// solver, after which the rest of the file can be parsed with [`Parser::parse_proof`].
+    pub fn parse_embedded_problem(&mut self) -> CarcaraResult<Problem> {
+        self.parse_problem_commands(true)
+    }
+
+    fn parse_problem_commands(&mut self, embedded: bool) -> CarcaraResult<Problem> {
         self.problem = Some(Problem::new());
 
         while self.current_token != Token::Eof {
+            if embedded && self.current_token == Token::Symbol("unsat".into()) {
+                break;
+            }
             self.expect_token(Token::OpenParen)?;
+            if embedded
+                && matches!(
+                    self.current_token,
+                    Token::ReservedWord(Reserved::Assume | Reserved::Step | Reserved::Anchor)
+                )
+            {
+                self.in_proof_command = true;
+                break;
+            }
             match self.next_token()?.0 {
                 Token::ReservedWord(Reserved::DeclareFun) => {
                     let (name, sort) = self.parse_declare_fun()?;
@@ -685,6 +1372,26 @@ impl<'a, R: BufRead> Parser<'a, R> {
                     // argument which is a string terminal representing the sort name.
                     self.state.sort_declarations.insert(name, arity);
                 }
+                Token::ReservedWord(Reserved::DeclareDatatype) => {
+                    let name = self.expect_symbol()?;
+                    self.parse_datatype_declarations(vec![(name, 0)])?;
+                }
+                Token::ReservedWord(Reserved::DeclareDatatypes) => {
+                    self.expect_token(Token::OpenParen)?;
+                    let sorts = self.parse_sequence(
+                        |p| {
+                            p.expect_token(Token::OpenParen)?;
+                            let name = p.expect_symbol()?;
+                            let arity = p.expect_numeral()?;
+                            p.expect_token(Token::CloseParen)?;
+                            Ok((name, arity.to_usize().unwrap_or(usize::MAX)))
+                        },
+                        true,
+                    )?;
+                    self.expect_token(Token::OpenParen)?;
+                    self.parse_datatype_declarations(sorts)?;
+                    self.expect_token(Token::CloseParen)?;
+                }
                 Token::ReservedWord(Reserved::DefineFun) => {
                     let (name, func_def) = self.parse_define_fun()?;
 
@@ -727,7 +1434,8 @@ impl<'a, R: BufRead> Parser<'a, R> {
                     self.expect_token(Token::OpenParen)?;
                     let terms = self.parse_sequence(Self::parse_term, true)?;
                     self.expect_token(Token::CloseParen)?;
-                    self.premises().extend(terms);
+                    self.premises().extend(terms.iter().cloned());
+                    self.problem.as_mut().unwrap().assumptions.extend(terms);
                 }
                 Token::ReservedWord(Reserved::SetLogic) => {
                     let logic = self.expect_symbol()?;
@@ -741,6 +1449,10 @@ impl<'a, R: BufRead> Parser<'a, R> {
                     self.is_real_only_logic =
                         (logic.contains("LRA") || logic.contains("NRA") || logic.contains("RDL"))
                             && !logic.contains('I');
+
+                    // Logics with transcendental functions, like "QF_NRAT" or "NIRAT", extend the
+                    // nonlinear arithmetic logics with a "T". The "ALL" logic also includes them.
+                    self.is_transcendental_logic = logic.ends_with("RAT") || logic == "ALL";
                 }
                 _ => {
                     // If the command is not one of the commands we care about, we just ignore it.
@@ -752,6 +1464,57 @@ impl<'a, R: BufRead> Parser<'a, R> {
         Ok(self.problem.take().unwrap())
     }
 
+    /// Parses a model, in the format produced by the `get-model` SMT-LIB command. All function,
+    /// constant and sort declarations needed should already be in the parser state.
+    ///
+    /// The model may be preceded by the `sat` response of the solver, and may start with the
+    /// `model` symbol, as printed by some solvers. Inside the model, `define-fun` commands give the
+    /// value of each symbol, and `declare-fun` commands declare the abstract values of
+    /// uninterpreted sorts. All other commands are ignored.
+    pub fn parse_model(&mut self) -> CarcaraResult<Model> {
+        if self.current_token == Token::Symbol("sat".to_owned()) {
+            self.next_token()?;
+        }
+        self.expect_token(Token::OpenParen)?;
+        if self.current_token == Token::Symbol("model".to_owned()) {
+            self.next_token()?;
+        }
+
+        // In logics where integer literals should be parsed as reals, the same is true for the
+        // values in the model, so we act as if we were parsing the problem
+        self.problem = Some(Problem::new());
+
+        let mut model = Model::default();
+        while self.current_token != Token::CloseParen {
+            self.expect_token(Token::OpenParen)?;
+            match self.next_token()?.0 {
+                Token::ReservedWord(Reserved::DefineFun) => {
+                    let (name, func_def) = self.parse_define_fun()?;
+                    let value = if func_def.params.is_empty() {
+                        func_def.body
+                    } else {
+                        self.pool.add(Term::Binder(
+                            Binder::Lambda,
+                            BindingList(func_def.params),
+                            func_def.body,
+                        ))
+                    };
+                    model.definitions.insert(name, value);
+                }
+                Token::ReservedWord(Reserved::DeclareFun) => {
+                    let (name, sort) = self.parse_declare_fun()?;
+                    self.insert_sorted_var((name.clone(), sort.clone()));
+                    let value = self.pool.add(Term::Var(name, sort.clone()));
+                    model.abstract_values.entry(sort).or_default().push(value);
+                }
+                _ => self.ignore_until_close_parens()?,
+            }
+        }
+        self.next_token()?; // Consume `)` token
+        self.problem = None;
+        Ok(model)
+    }
+
     /// Parses a proof in the Alethe format. All function, constant and sort declarations needed
     /// should already be in the parser state. Note that the `premises` field in the proof will not
     /// be set.
@@ -770,14 +1533,20 @@ impl<'a, R: BufRead> Parser<'a, R> {
 
         let mut constant_definitions = Vec::new();
 
+        self.alethe_version = self.config.alethe_version;
+        self.first_unsorted_assign = None;
+        self.renamed_step_ids.clear();
+
         // Some solvers print the satisfiability result (unsat) together with the proof. To save the
         // user from having to remove this, we consume this first "unsat" token if it exists
         if self.current_token == Token::Symbol("unsat".into()) {
             self.next_token()?;
         }
 
-        while self.current_token != Token::Eof {
-            self.expect_token(Token::OpenParen)?;
+        while self.in_proof_command || self.current_token != Token::Eof {
+            if !std::mem::take(&mut self.in_proof_command) {
+                self.expect_token(Token::OpenParen)?;
+            }
             let (token, position) = self.next_token()?;
             let (id, command) = match token {
                 Token::ReservedWord(Reserved::Assume) => {
@@ -788,9 +1557,46 @@ impl<'a, R: BufRead> Parser<'a, R> {
                     (id.clone(), ProofCommand::Assume { id, term })
                 }
                 Token::ReservedWord(Reserved::Step) => {
-                    finished_assumes = true;
-                    let step = self.parse_step_command()?;
-                    (step.id.clone(), ProofCommand::Step(step))
+                    let mut step = self.parse_step_command()?;
+                    let allows_input_steps = self
+                        .alethe_version
+                        .map_or(true, AletheVersion::allows_input_steps);
+                    if step.rule == "input" && allows_input_steps {
+                        let term = self.input_step_assumption(&step, position)?;
+                        if stack.len() == 1 && finished_assumes {
+                            log::warn!("`input` step '{}' appears after `step` commands", &step.id);
+                        }
+                        if let [_] = step.clause.as_slice() {
+                            (step.id.clone(), ProofCommand::Assume { id: step.id, term })
+                        } else {
+                            // An assumption is a single term, so to keep the shape of the clause,
+                            // we assume the disjunction of its literals with a fresh id, and
+                            // derive the clause from it using the `or` rule
+                            let assume_id = (0..)
+                                .map(|i| match i {
+                                    0 => format!("{}.input", step.id),
+                                    i => format!("{}.input'{}", step.id, i),
+                                })
+                                .find(|s| {
+                                    let s = HashCache::new(s.clone());
+                                    self.state.step_ids.get(&s).is_none()
+                                })
+                                .unwrap();
+                            let (top_subproof, _) = stack.last_mut().unwrap();
+                            let assume = ProofCommand::Assume { id: assume_id.clone(), term };
+                            top_subproof.commands.push(assume);
+                            let index = top_subproof.commands.len() - 1;
+                            self.state
+                                .step_ids
+                                .insert(HashCache::new(assume_id), (index, position));
+                            step.rule = "or".to_owned();
+                            step.premises = vec![(stack.len() - 1, index)];
+                            (step.id.clone(), ProofCommand::Step(step))
+                        }
+                    } else {
+                        finished_assumes = true;
+                        (step.id.clone(), ProofCommand::Step(step))
+                    }
                 }
                 Token::ReservedWord(Reserved::DefineFun) => {
                     let (name, func_def) = self.parse_define_fun()?;
@@ -800,6 +1606,16 @@ impl<'a, R: BufRead> Parser<'a, R> {
                     self.state.function_defs.insert(name, func_def);
                     continue;
                 }
+                Token::ReservedWord(
+                    r @ (Reserved::DeclareFun | Reserved::DeclareConst | Reserved::DeclareSort),
+                ) if self.proof_declarations.is_some() => {
+                    self.parse_proof_declaration(r)?;
+                    continue;
+                }
+                Token::Symbol(s) if s == "set-info" => {
+                    self.parse_proof_set_info()?;
+                    continue;
+                }
                 Token::ReservedWord(Reserved::Anchor) => {
                     let (end_step_id, args) = self.parse_anchor_command()?;
 
@@ -823,13 +1639,8 @@ impl<'a, R: BufRead> Parser<'a, R> {
                     return Err(Error::Parser(ParserError::UnexpectedToken(token), position));
                 }
             };
+            let (id, command, renamed_id) = self.check_step_id(id, command, position)?;
             let id = HashCache::new(id);
-            if self.state.step_ids.get(&id).is_some() {
-                return Err(Error::Parser(
-                    ParserError::RepeatedStepId(id.unwrap()),
-                    position,
-                ));
-            }
 
             let (top_subproof, top_end_step) = stack.last_mut().unwrap();
             top_subproof.commands.push(command);
@@ -837,7 +1648,10 @@ impl<'a, R: BufRead> Parser<'a, R> {
                 // If this is the last step in a subproof, we need to pop all the subproof data off
                 // of the stacks and build the subproof command with it
                 self.state.symbol_table.pop_scope();
-                self.state.step_ids.pop_scope();
+                let closed = self.state.step_ids.pop_scope();
+                self.state
+                    .closed_step_ids
+                    .extend(closed.into_keys().map(|k| (k, id.as_ref().clone())));
                 let (subproof, _) = stack.pop().unwrap();
 
                 // The subproof must contain at least two commands: the end step and the previous
@@ -861,7 +1675,12 @@ impl<'a, R: BufRead> Parser<'a, R> {
                 outer.commands.push(ProofCommand::Subproof(subproof));
             }
             let index = stack.last().unwrap().0.commands.len() - 1;
-            self.state.step_ids.insert(id, index);
+            if let Some(renamed_id) = renamed_id {
+                self.state
+                    .step_ids
+                    .insert(HashCache::new(renamed_id), (index, position));
+            }
+            self.state.step_ids.insert(id, (index, position));
         }
         let commands = match stack.len() {
             0 => unreachable!(),
@@ -876,7 +1695,102 @@ impl<'a, R: BufRead> Parser<'a, R> {
                 ))
             }
         };
-        Ok(Proof { constant_definitions, commands })
+        let mut proof = Proof { constant_definitions, commands };
+
+        let version = self
+            .alethe_version
+            .or_else(|| AletheVersion::detect(&proof))
+            .unwrap_or_default();
+        if let Some((var, position)) = self.first_unsorted_assign.take() {
+            if !version.allows_unsorted_assign_args() {
+                let token = Token::Symbol(var);
+                return Err(Error::Parser(ParserError::UnexpectedToken(token), position));
+            }
+        }
+        log::info!("parsed proof as Alethe version '{}'", version);
+        version.normalize(&mut proof);
+        Ok(proof)
+    }
+
+    /// Returns the step ids that were renamed while parsing the last proof because they were
+    /// repeated, each paired with its new id. This is always empty unless the
+    /// `rename_duplicate_step_ids` option is enabled.
+    pub fn renamed_step_ids(&self) -> &[(String, String)] {
+        &self.renamed_step_ids
+    }
+
+    /// Checks that the id of a newly parsed command was not used before. If it was, and the
+    /// `rename_duplicate_step_ids` option is enabled, the command is given a fresh id. In that
+    /// case, this returns the original id, the renamed command, and the new id. Otherwise, this
+    /// returns the id and the command unchanged.
+    fn check_step_id(
+        &mut self,
+        id: String,
+        mut command: ProofCommand,
+        position: Position,
+    ) -> CarcaraResult<(String, ProofCommand, Option<String>)> {
+        let Some(&(_, first)) = self.state.step_ids.get(&HashCache::new(id.clone())) else {
+            return Ok((id, command, None));
+        };
+        if !self.config.rename_duplicate_step_ids {
+            return Err(Error::Parser(
+                ParserError::RepeatedStepId(id, first.0, first.1),
+                position,
+            ));
+        }
+
+        let new_id = (1..)
+            .map(|i| format!("{}'{}", id, i))
+            .find(|s| {
+                self.state
+                    .step_ids
+                    .get(&HashCache::new(s.clone()))
+                    .is_none()
+            })
+            .unwrap();
+        log::warn!(
+            "step id '{}' was repeated (first defined on line {}, column {}), renaming it to '{}'",
+            id,
+            first.0,
+            first.1,
+            new_id,
+        );
+        match &mut command {
+            ProofCommand::Assume { id, .. } => *id = new_id.clone(),
+            ProofCommand::Step(s) => s.id = new_id.clone(),
+            ProofCommand::Subproof(_) => unreachable!(),
+        }
+        self.renamed_step_ids.push((id.clone(), new_id.clone()));
+        Ok((id, command, Some(new_id)))
+    }
+
+    /// Parses a `set-info` command in a proof. This method assumes that the `(` and `set-info`
+    /// tokens were already consumed. The only attribute that is used is `:alethe-version`, which
+    /// declares the version of the Alethe format of the proof. Other attributes are ignored.
+    fn parse_proof_set_info(&mut self) -> CarcaraResult<()> {
+        let (token, position) = self.next_token()?;
+        if token != Token::Keyword("alethe-version".into()) {
+            return self.ignore_until_close_parens();
+        }
+        let version = match self.next_token()? {
+            (Token::String(s) | Token::Symbol(s), _) => s,
+            (other, pos) => return Err(Error::Parser(ParserError::UnexpectedToken(other), pos)),
+        };
+        self.expect_token(Token::CloseParen)?;
+
+        let version = AletheVersion::from_str(&version).map_err(|_| {
+            Error::Parser(ParserError::UnknownAletheVersion(version.clone()), position)
+        })?;
+        match self.config.alethe_version {
+            Some(configured) if configured != version => log::warn!(
+                "proof declares Alethe version '{}', but version '{}' was configured",
+                version,
+                configured,
+            ),
+            Some(_) => (),
+            None => self.alethe_version = Some(version),
+        }
+        Ok(())
     }
 
     /// Parses an `assume` proof command. This method assumes that the `(` and `assume` tokens were
@@ -889,6 +1803,31 @@ impl<'a, R: BufRead> Parser<'a, R> {
         Ok((id, term))
     }
 
+    /// Returns the term assumed by a step that uses the `input` rule, which some solvers emit
+    /// instead of an `assume` command. The step must have no premises, arguments or discharged
+    /// assumptions. If its conclusion has more than one literal, the assumed term is their
+    /// disjunction.
+    fn input_step_assumption(
+        &mut self,
+        step: &ProofStep,
+        position: Position,
+    ) -> CarcaraResult<Rc<Term>> {
+        if !step.premises.is_empty()
+            || !step.args.is_empty()
+            || !step.discharge.is_empty()
+            || step.clause.is_empty()
+        {
+            return Err(Error::Parser(
+                ParserError::InvalidInputStep(step.id.clone()),
+                position,
+            ));
+        }
+        Ok(match step.clause.as_slice() {
+            [term] => term.clone(),
+            literals => self.pool.add(Term::Op(Operator::Or, literals.to_vec())),
+        })
+    }
+
     /// Parses a `step` proof command. This method assumes that the `(` and `step` tokens were
     /// already consumed.
     fn parse_step_command(&mut self) -> CarcaraResult<ProofStep> {
@@ -903,7 +1842,10 @@ impl<'a, R: BufRead> Parser<'a, R> {
             }
         };
 
-        let premises = if self.current_token == Token::Keyword("premises".into()) {
+        // Older versions of veriT list the premises using the `:clauses` keyword
+        let is_premises_keyword =
+            matches!(&self.current_token, Token::Keyword(k) if k == "premises" || k == "clauses");
+        let premises = if is_premises_keyword {
             self.next_token()?;
             self.expect_token(Token::OpenParen)?;
             self.parse_sequence(Self::parse_step_premise, true)?
@@ -922,11 +1864,17 @@ impl<'a, R: BufRead> Parser<'a, R> {
                 self.ignore_until_close_parens()?;
                 Vec::new()
             } else {
-                self.parse_sequence(Self::parse_term, true)?
+                self.parse_sequence(Self::parse_step_argument, true)?
             }
         } else {
             Vec::new()
         };
+        let (arg_vars, args): (Vec<_>, Vec<_>) = args.into_iter().unzip();
+        let arg_vars = if arg_vars.iter().any(Option::is_some) {
+            arg_vars
+        } else {
+            Vec::new()
+        };
 
         // For some rules (notably the `subproof` rule), there is also a `:discharge` attribute that
         // takes a series of command ids, in addition to the regular premises
@@ -938,7 +1886,20 @@ impl<'a, R: BufRead> Parser<'a, R> {
             Vec::new()
         };
 
-        self.ignore_remaining_attributes()?;
+        // Steps introduced during elaboration may have an `:origin` attribute, recording the
+        // original step and the elaboration pass that introduced them
+        let origin = if self.current_token == Token::Keyword("origin".into()) {
+            self.next_token()?;
+            self.expect_token(Token::OpenParen)?;
+            let step = self.expect_symbol()?;
+            let pass = self.expect_symbol()?;
+            self.expect_token(Token::CloseParen)?;
+            Some(StepOrigin { step, pass })
+        } else {
+            None
+        };
+
+        let attributes = self.parse_remaining_attributes()?;
         self.expect_token(Token::CloseParen)?;
 
         Ok(ProofStep {
@@ -947,10 +1908,61 @@ impl<'a, R: BufRead> Parser<'a, R> {
             rule,
             premises,
             args,
+            arg_vars,
             discharge,
+            origin,
+            attributes,
         })
     }
 
+    /// Parses an argument for a `step` command. This is usually a term, but can also be an
+    /// assignment, of the form `(:= (<symbol> <sort>) <term>)`, in which case the assigned variable
+    /// is also returned.
+    fn parse_step_argument(&mut self) -> CarcaraResult<(Option<SortedVar>, Rc<Term>)> {
+        if self.current_token != Token::OpenParen {
+            return Ok((None, self.parse_term()?));
+        }
+        self.next_token()?;
+        if self.current_token != Token::Keyword("=".into()) {
+            return Ok((None, self.parse_application()?));
+        }
+        self.next_token()?;
+        let (var, value) = self.parse_assignment()?;
+        self.expect_token(Token::CloseParen)?;
+        Ok((Some(var), value))
+    }
+
+    /// Parses the variable and value of an assignment argument. This assumes that the `(` and `:=`
+    /// tokens were already consumed, and does not consume the closing `)`.
+    fn parse_assignment(&mut self) -> CarcaraResult<(SortedVar, Rc<Term>)> {
+        // To make Carcara more robust to recent changes in the Alethe format, we support parsing
+        // the two versions of assignment arguments:
+        // - the old version, without the sort hint: `(:= <symbol> <term>)`
+        // - and the new version, with the sort hint: `(:= (<symbol> <sort>) <term>)`
+        // However, if "strict" parsing is enabled, we only allow the old version if the proof is in
+        // the legacy version. If the version is not yet known, we accept it provisionally, and
+        // check it once the whole proof is parsed and the version is detected
+        let allow_unsorted = !self.config.strict
+            || self
+                .alethe_version
+                .map_or(true, AletheVersion::allows_unsorted_assign_args);
+        if allow_unsorted && matches!(self.current_token, Token::Symbol(_)) {
+            let position = self.current_position;
+            let var = self.expect_symbol()?;
+            if self.config.strict && self.alethe_version.is_none() {
+                self.first_unsorted_assign
+                    .get_or_insert_with(|| (var.clone(), position));
+            }
+            let value = self.parse_term()?;
+            let sort = self.pool.sort(&value);
+            Ok(((var, sort), value))
+        } else {
+            let (var, sort) = self.parse_sorted_var()?;
+            let value = self.parse_term_expecting_sort(sort.as_sort().unwrap())?;
+            Ok(((var, sort), value))
+        }
+    }
+
     /// Parses a premise for a `step` command. This already converts it into the depth and command
     /// index used to reference commands in the AST.
     fn parse_step_premise(&mut self) -> CarcaraResult<(usize, usize)> {
@@ -959,8 +1971,18 @@ impl<'a, R: BufRead> Parser<'a, R> {
         self.state
             .step_ids
             .get_with_depth(&id)
-            .map(|(d, &i)| (d, i))
-            .ok_or_else(|| Error::Parser(ParserError::UndefinedStepId(id.unwrap()), position))
+            .map(|(d, &(i, _))| (d, i))
+            .ok_or_else(|| Error::Parser(self.undefined_step_id_error(id), position))
+    }
+
+    /// Returns the error for a reference to a step id that is not in scope. This distinguishes
+    /// between ids that were never defined and ids of commands inside subproofs that were already
+    /// closed.
+    fn undefined_step_id_error(&self, id: HashCache<String>) -> ParserError {
+        match self.state.closed_step_ids.get(&id) {
+            Some(end_step) => ParserError::PremiseInClosedSubproof(id.unwrap(), end_step.clone()),
+            None => ParserError::UndefinedStepId(id.unwrap()),
+        }
     }
 
     /// Parses an argument for the `:discharge` attribute.
@@ -975,12 +1997,20 @@ impl<'a, R: BufRead> Parser<'a, R> {
         let absolute_id = format!("{}.{}", root_id, &id);
         let id = HashCache::new(id);
         let absolute_id = HashCache::new(absolute_id);
-        self.state
+        let (depth, index) = self
+            .state
             .step_ids
             .get_with_depth(&absolute_id)
             .or_else(|| self.state.step_ids.get_with_depth(&id))
-            .map(|(d, &i)| (d, i))
-            .ok_or_else(|| Error::Parser(ParserError::UndefinedStepId(id.unwrap()), position))
+            .map(|(d, &(i, _))| (d, i))
+            .ok_or_else(|| Error::Parser(self.undefined_step_id_error(id.clone()), position))?;
+
+        // Only the assumptions local to the current subproof can be discharged
+        if depth != self.state.step_ids.height() - 1 {
+            let err = ParserError::NonLocalDischarge(id.unwrap());
+            return Err(Error::Parser(err, position));
+        }
+        Ok((depth, index))
     }
 
     /// Parses an `anchor` proof command. This method assumes that the `(` and `anchor` tokens were
@@ -1014,23 +2044,7 @@ impl<'a, R: BufRead> Parser<'a, R> {
         self.expect_token(Token::OpenParen)?;
         Ok(if self.current_token == Token::Keyword("=".into()) {
             self.next_token()?;
-
-            // To make Carcara more robust to recent changes in the Alethe format, we support
-            // parsing the two versions of assign-style anchor arguments:
-            // - the old version, without the sort hint: `(:= <symbol> <term>)`
-            // - and the new version, with the sort hint: `(:= (<symbol> <sort>) <term>)`
-            // However, if "strict" parsing is enabled, we only allow the new version
-            let (var, value, sort) =
-                if !self.config.strict && matches!(self.current_token, Token::Symbol(_)) {
-                    let var = self.expect_symbol()?;
-                    let value = self.parse_term()?;
-                    let sort = self.pool.sort(&value);
-                    (var, value, sort)
-                } else {
-                    let (var, sort) = self.parse_sorted_var()?;
-                    let value = self.parse_term_expecting_sort(sort.as_sort().unwrap())?;
-                    (var, value, sort)
-                };
+            let ((var, sort), value) = self.parse_assignment()?;
             self.insert_sorted_var((var.clone(), sort.clone()));
             self.expect_token(Token::CloseParen)?;
             AnchorArg::Assign((var, sort), value)
@@ -1075,6 +2089,100 @@ impl<'a, R: BufRead> Parser<'a, R> {
         Ok((name, arity))
     }
 
+    /// Parses a `declare-fun`, `declare-const` or `declare-sort` command that appears in a proof
+    /// being parsed without its problem, and adds it to the collected proof declarations.
+    fn parse_proof_declaration(&mut self, command: Reserved) -> CarcaraResult<()> {
+        if command == Reserved::DeclareSort {
+            let (name, arity) = self.parse_declare_sort()?;
+            self.state.sort_declarations.insert(name.clone(), arity);
+            let declarations = self.proof_declarations.as_mut().unwrap();
+            declarations.sort_declarations.push((name, arity));
+            return Ok(());
+        }
+        let (name, sort) = if command == Reserved::DeclareFun {
+            self.parse_declare_fun()?
+        } else {
+            let name = self.expect_symbol()?;
+            let sort = self.parse_sort()?;
+            self.expect_token(Token::CloseParen)?;
+            (name, sort)
+        };
+        self.insert_sorted_var((name.clone(), sort.clone()));
+        let declarations = self.proof_declarations.as_mut().unwrap();
+        declarations.function_declarations.push((name, sort));
+        Ok(())
+    }
+
+    /// Parses the datatype declarations of a `declare-datatype` or `declare-datatypes` command,
+    /// given the names and arities of the datatypes being declared. For each datatype, this parses
+    /// its list of constructor declarations, and then consumes one `)` token. For
+    /// `declare-datatype`, that is the end of the command. For `declare-datatypes`, that is the end
+    /// of the list of datatype declarations, whose opening `(` must already have been consumed.
+    ///
+    /// Each datatype is declared as a sort, and its constructors and selectors are declared as
+    /// functions.
+    fn parse_datatype_declarations(&mut self, sorts: Vec<(String, usize)>) -> CarcaraResult<()> {
+        // The datatypes must be declared before their constructors are parsed, since they may be
+        // recursive or mutually recursive
+        for (name, arity) in &sorts {
+            if *arity != 0 {
+                return Err(Error::Parser(
+                    ParserError::ParametricDatatype(name.clone()),
+                    self.current_position,
+                ));
+            }
+            self.state.sort_declarations.insert(name.clone(), 0);
+        }
+
+        for (name, _) in sorts {
+            let datatype_sort = self
+                .pool
+                .add(Term::Sort(Sort::Atom(name.clone(), Vec::new())));
+            self.expect_token(Token::OpenParen)?;
+            if self.current_token == Token::Symbol("par".into()) {
+                return Err(Error::Parser(
+                    ParserError::ParametricDatatype(name),
+                    self.current_position,
+                ));
+            }
+            let constructors = self.parse_sequence(
+                |p| {
+                    // Nullary constructors may be written without parentheses
+                    if let Token::Symbol(_) = p.current_token {
+                        let name = p.expect_symbol()?;
+                        return Ok(Constructor { name, selectors: Vec::new() });
+                    }
+                    p.expect_token(Token::OpenParen)?;
+                    let name = p.expect_symbol()?;
+                    let selectors = p.parse_sequence(Self::parse_sorted_var, false)?;
+                    Ok(Constructor { name, selectors })
+                },
+                true,
+            )?;
+
+            for c in &constructors {
+                let constructor_sort = if c.selectors.is_empty() {
+                    datatype_sort.clone()
+                } else {
+                    let mut sorts: Vec<_> = c.selectors.iter().map(|(_, s)| s.clone()).collect();
+                    sorts.push(datatype_sort.clone());
+                    self.pool.add(Term::Sort(Sort::Function(sorts)))
+                };
+                self.insert_sorted_var((c.name.clone(), constructor_sort));
+                for (selector, field_sort) in &c.selectors {
+                    let sorts = vec![datatype_sort.clone(), field_sort.clone()];
+                    let selector_sort = self.pool.add(Term::Sort(Sort::Function(sorts)));
+                    self.insert_sorted_var((selector.clone(), selector_sort));
+                }
+            }
+            let def = DatatypeDef { name, constructors };
+            self.state.datatypes.insert(def.clone());
+            self.prelude().datatypes.insert(def);
+        }
+        self.expect_token(Token::CloseParen)?;
+        Ok(())
+    }
+
     /// Parses a function declaration, of the form `(<symbol> (<sorted var>*) <sort>)`. If the
     /// parameter `consume_parens` is `false`, the opening and closing parentheses are not consumed
     fn parse_function_dec(
@@ -1206,6 +2314,27 @@ impl<'a, R: BufRead> Parser<'a, R> {
         Ok((name, SortDef { params, body }))
     }
 
+    /// Returns the context in which the parser is currently parsing terms. See
+    /// [`ParsingContext`].
+    fn parsing_context(&self) -> Arc<ParsingContext> {
+        Arc::new(ParsingContext {
+            config: self.config,
+            state: self.state.clone(),
+            is_real_only_logic: self.is_real_only_logic,
+            is_transcendental_logic: self.is_transcendental_logic,
+        })
+    }
+
+    /// Parses a clause, and checks that nothing else follows it in the input.
+    fn parse_single_clause(&mut self) -> CarcaraResult<Vec<Rc<Term>>> {
+        let clause = self.parse_clause()?;
+        if self.current_token != Token::Eof {
+            let err = ParserError::UnexpectedToken(self.current_token.clone());
+            return Err(Error::Parser(err, self.current_position));
+        }
+        Ok(clause)
+    }
+
     /// Parses a clause of the form `(cl <term>*)`.
     fn parse_clause(&mut self) -> CarcaraResult<Vec<Rc<Term>>> {
         self.expect_token(Token::OpenParen)?;
@@ -1222,10 +2351,32 @@ impl<'a, R: BufRead> Parser<'a, R> {
         Ok((symbol, sort))
     }
 
+    /// Returns the operator with the given name, if it is recognized in the current strings
+    /// profile.
+    fn lookup_operator(&self, name: &str) -> Option<Operator> {
+        // The rounding modes can also be written using their long names
+        let name = match name {
+            "roundNearestTiesToEven" => "RNE",
+            "roundNearestTiesToAway" => "RNA",
+            "roundTowardPositive" => "RTP",
+            "roundTowardNegative" => "RTN",
+            "roundTowardZero" => "RTZ",
+            other => other,
+        };
+        Operator::from_str(name)
+            .ok()
+            .filter(|&op| self.config.strings_profile.supports(op))
+            .filter(|&op| self.is_transcendental_logic || !is_transcendental(op))
+    }
+
     /// Parses a term.
     pub fn parse_term(&mut self) -> CarcaraResult<Rc<Term>> {
         let term = match self.next_token()? {
             (Token::Bitvector { value, width }, _) => Term::new_bv(value, width),
+            (Token::FiniteField { value, order }, pos) => {
+                assert_prime_order(&order).map_err(|err| Error::Parser(err, pos))?;
+                Term::new_ff(value, order)
+            }
             (Token::Numeral(n), _) if self.interpret_ints_as_reals() => Term::new_real(n),
             (Token::Numeral(n), _) => Term::new_int(n),
             (Token::Decimal(r), _) => Term::new_real(r),
@@ -1235,7 +2386,7 @@ impl<'a, R: BufRead> Parser<'a, R> {
                 return if let Some(func) = self.state.function_defs.get(&s) {
                     func.apply(self.pool, Vec::new())
                         .map_err(|err| Error::Parser(err, pos))
-                } else if let Ok(op) = Operator::from_str(&s) {
+                } else if let Some(op) = self.lookup_operator(&s) {
                     let args = Vec::new();
 
                     self.make_op(op, args)
@@ -1255,6 +2406,10 @@ impl<'a, R: BufRead> Parser<'a, R> {
     pub fn parse_constant(&mut self) -> CarcaraResult<Constant> {
         let constant = match self.next_token()? {
             (Token::Bitvector { value, width }, _) => Constant::BitVec(value, width.into()),
+            (Token::FiniteField { value, order }, pos) => {
+                assert_prime_order(&order).map_err(|err| Error::Parser(err, pos))?;
+                Constant::FiniteField(value.modulo(&order), order)
+            }
             (Token::Numeral(n), _) if self.interpret_ints_as_reals() => Constant::Real(n.into()),
             (Token::Numeral(n), _) => Constant::Integer(n),
             (Token::Decimal(r), _) => Constant::Real(r),
@@ -1354,8 +2509,9 @@ impl<'a, R: BufRead> Parser<'a, R> {
     /// Parses an annotated term, of the form `(! <term> <attribute>+)`. This method assumes that
     /// the `(` and `!` tokens were already consumed.
     ///
-    /// The two supported attributes are `:named` and `:pattern`, though the latter is ignored. If
-    /// any other attribute is present, an error will be returned.
+    /// The two supported attributes are `:named` and `:pattern`. The terms of each `:pattern`
+    /// attribute are recorded in the term pool as an instantiation pattern for the inner term (see
+    /// [`TermPool::patterns`]). Any other attribute is ignored.
     fn parse_annotated_term(&mut self) -> CarcaraResult<Rc<Term>> {
         let inner = self.parse_term()?;
         self.parse_sequence(
@@ -1373,6 +2529,12 @@ impl<'a, R: BufRead> Parser<'a, R> {
                         p.state.function_defs.insert(name, func_def);
                         Ok(())
                     }
+                    "pattern" => {
+                        p.expect_token(Token::OpenParen)?;
+                        let pattern = p.parse_sequence(Self::parse_term, true)?;
+                        p.pool.add_pattern(&inner, pattern);
+                        Ok(())
+                    }
 
                     // We allow unknown attributes, and just ignore them
                     _ => match p.current_token {
@@ -1440,6 +2602,40 @@ impl<'a, R: BufRead> Parser<'a, R> {
         Ok((op, constant_args))
     }
 
+    /// Parses the constructor of a datatype tester, of the form `(_ is C)`. This method assumes that
+    /// the `(` and `_` tokens were already consumed.
+    fn parse_tester(&mut self) -> CarcaraResult<Rc<Term>> {
+        self.expect_symbol()?; // The `is` symbol
+        let pos = self.current_position;
+        let name = self.expect_symbol()?;
+        self.expect_token(Token::CloseParen)?;
+        let constructor = self
+            .make_var(name.clone())
+            .map_err(|e| Error::Parser(e, pos))?;
+        if self.state.datatypes.as_constructor(&constructor).is_none() {
+            return Err(Error::Parser(ParserError::NotAConstructor(name), pos));
+        }
+        Ok(constructor)
+    }
+
+    /// Constructs and sort checks a datatype tester application.
+    fn make_tester(
+        &mut self,
+        constructor: Rc<Term>,
+        args: Vec<Rc<Term>>,
+    ) -> Result<Rc<Term>, ParserError> {
+        assert_num_args(&args, 1)?;
+        let (def, _) = self.state.datatypes.as_constructor(&constructor).unwrap();
+        let expected = Sort::Atom(def.name.clone(), Vec::new());
+        SortError::assert_eq(&expected, self.pool.sort(&args[0]).as_sort().unwrap())
+            .map_err(|e| e.at(0))?;
+        Ok(self.pool.add(Term::ParamOp {
+            op: ParamOperator::DtTester,
+            op_args: vec![constructor],
+            args,
+        }))
+    }
+
     fn parse_qualified_operator(&mut self) -> CarcaraResult<(ParamOperator, Rc<Term>)> {
         let op_symbol = self.expect_symbol()?;
         let op = ParamOperator::from_str(op_symbol.as_str()).map_err(|_| {
@@ -1503,7 +2699,11 @@ impl<'a, R: BufRead> Parser<'a, R> {
                     ));
                 }
             }
-            ParamOperator::BvBitOf | ParamOperator::ZeroExtend | ParamOperator::SignExtend => {
+            ParamOperator::BvBitOf
+            | ParamOperator::ZeroExtend
+            | ParamOperator::SignExtend
+            | ParamOperator::BvRotateLeft
+            | ParamOperator::BvRotateRight => {
                 assert_num_args(&op_args, 1)?;
                 assert_num_args(&args, 1)?;
                 SortError::assert_eq(&Sort::Int, &op_args[0].sort())?;
@@ -1512,11 +2712,34 @@ impl<'a, R: BufRead> Parser<'a, R> {
                 }
                 assert_indexed_op_args_value(&op_args, 0..)?;
             }
+            ParamOperator::BvRepeat => {
+                assert_num_args(&op_args, 1)?;
+                assert_num_args(&args, 1)?;
+                SortError::assert_eq(&Sort::Int, &op_args[0].sort())?;
+                if !matches!(sorts[0], Sort::BitVec(_)) {
+                    return Err(ParserError::ExpectedBvSort(sorts[0].clone()));
+                }
+                assert_indexed_op_args_value(&op_args, 1..)?;
+            }
+            ParamOperator::Int2Bv | ParamOperator::IntToBv => {
+                assert_num_args(&op_args, 1)?;
+                assert_num_args(&args, 1)?;
+                SortError::assert_eq(&Sort::Int, &op_args[0].sort())?;
+                SortError::assert_eq(&Sort::Int, sorts[0]).map_err(|e| e.at(0))?;
+                assert_indexed_op_args_value(&op_args, 1..)?;
+            }
+            ParamOperator::Divisible => {
+                assert_num_args(&op_args, 1)?;
+                assert_num_args(&args, 1)?;
+                SortError::assert_eq(&Sort::Int, &op_args[0].sort())?;
+                SortError::assert_eq(&Sort::Int, sorts[0]).map_err(|e| e.at(0))?;
+                assert_indexed_op_args_value(&op_args, 1..)?;
+            }
             ParamOperator::RePower => {
                 assert_num_args(&op_args, 1)?;
                 assert_num_args(&args, 1)?;
                 SortError::assert_eq(&Sort::Int, &op_args[0].sort())?;
-                SortError::assert_eq(&Sort::RegLan, sorts[0])?;
+                SortError::assert_eq(&Sort::RegLan, sorts[0]).map_err(|e| e.at(0))?;
                 assert_indexed_op_args_value(&op_args, 0..)?;
             }
             ParamOperator::ReLoop => {
@@ -1525,10 +2748,74 @@ impl<'a, R: BufRead> Parser<'a, R> {
                 for arg in &op_args {
                     SortError::assert_eq(&Sort::Int, &arg.sort())?;
                 }
-                SortError::assert_eq(&Sort::RegLan, sorts[0])?;
+                SortError::assert_eq(&Sort::RegLan, sorts[0]).map_err(|e| e.at(0))?;
                 assert_indexed_op_args_value(&op_args, 0..)?;
             }
-            ParamOperator::ArrayConst => return Err(ParserError::InvalidIndexedOp(op.to_string())),
+            ParamOperator::FpPosZero
+            | ParamOperator::FpNegZero
+            | ParamOperator::FpPosInfinity
+            | ParamOperator::FpNegInfinity
+            | ParamOperator::FpNaN => {
+                assert_num_args(&op_args, 2)?;
+                assert_num_args(&args, 0)?;
+                for arg in &op_args {
+                    SortError::assert_eq(&Sort::Int, &arg.sort())?;
+                }
+                assert_indexed_op_args_value(&op_args, 2..)?;
+            }
+            ParamOperator::ToFp => {
+                /*
+                ((_ to_fp eb sb) (_ BitVec m) (_ FloatingPoint eb sb))
+                ((_ to_fp eb sb) RoundingMode (_ FloatingPoint mb nb) (_ FloatingPoint eb sb))
+                ((_ to_fp eb sb) RoundingMode Real (_ FloatingPoint eb sb))
+                ((_ to_fp eb sb) RoundingMode (_ BitVec m) (_ FloatingPoint eb sb))
+
+                where, in the first case, m = eb + sb
+                 */
+                assert_num_args(&op_args, 2)?;
+                assert_num_args(&args, 1..3)?;
+                for arg in &op_args {
+                    SortError::assert_eq(&Sort::Int, &arg.sort())?;
+                }
+                assert_indexed_op_args_value(&op_args, 2..)?;
+                if let [sort] = sorts.as_slice() {
+                    let width = op_args[0].as_integer().unwrap() + op_args[1].as_integer().unwrap();
+                    SortError::assert_eq(&Sort::BitVec(width), sort).map_err(|e| e.at(0))?;
+                } else {
+                    SortError::assert_eq(&Sort::RoundingMode, sorts[0]).map_err(|e| e.at(0))?;
+                    if !matches!(
+                        sorts[1],
+                        Sort::FloatingPoint(..) | Sort::Real | Sort::BitVec(_)
+                    ) {
+                        return Err(ParserError::ExpectedFpSort(sorts[1].clone()));
+                    }
+                }
+            }
+            ParamOperator::ToFpUnsigned => {
+                assert_num_args(&op_args, 2)?;
+                assert_num_args(&args, 2)?;
+                for arg in &op_args {
+                    SortError::assert_eq(&Sort::Int, &arg.sort())?;
+                }
+                assert_indexed_op_args_value(&op_args, 2..)?;
+                SortError::assert_eq(&Sort::RoundingMode, sorts[0]).map_err(|e| e.at(0))?;
+                if !matches!(sorts[1], Sort::BitVec(_)) {
+                    return Err(ParserError::ExpectedBvSort(sorts[1].clone()));
+                }
+            }
+            ParamOperator::FpToUbv | ParamOperator::FpToSbv => {
+                assert_num_args(&op_args, 1)?;
+                assert_num_args(&args, 2)?;
+                SortError::assert_eq(&Sort::Int, &op_args[0].sort())?;
+                assert_indexed_op_args_value(&op_args, 1..)?;
+                SortError::assert_eq(&Sort::RoundingMode, sorts[0]).map_err(|e| e.at(0))?;
+                assert_fp_sorts(&sorts[1..])?;
+            }
+            ParamOperator::DtTester
+            | ParamOperator::ArrayConst
+            | ParamOperator::SetEmpty
+            | ParamOperator::SetUniverse
+            | ParamOperator::SeqEmpty => return Err(ParserError::InvalidIndexedOp(op.to_string())),
         }
         let op_args = op_args
             .into_iter()
@@ -1554,7 +2841,16 @@ impl<'a, R: BufRead> Parser<'a, R> {
                     None,
                     Some(sorts[0]),
                     op_sort.as_sort().unwrap(),
-                )?;
+                )
+                .map_err(|e| e.at(0))?;
+            }
+            ParamOperator::SetEmpty | ParamOperator::SetUniverse => {
+                assert_num_args(&args, 0)?;
+                SortError::assert_set_sort(self.pool, None, op_sort.as_sort().unwrap())?;
+            }
+            ParamOperator::SeqEmpty => {
+                assert_num_args(&args, 0)?;
+                SortError::assert_seq_sort(self.pool, None, op_sort.as_sort().unwrap())?;
             }
             _ => return Err(ParserError::InvalidQualifiedOp(op.to_string())),
         }
@@ -1576,6 +2872,23 @@ impl<'a, R: BufRead> Parser<'a, R> {
                             .map_err(|err| Error::Parser(err, head_pos))
                     }
                     Reserved::As => {
+                        // Finite field literals can also be written as qualified symbols, like
+                        // `(as ff3 (_ FiniteField 7))`
+                        let ff_value = match &self.current_token {
+                            Token::Symbol(s) => ff_literal_value(s),
+                            _ => None,
+                        };
+                        if let Some(value) = ff_value {
+                            self.next_token()?;
+                            let sort = self.parse_sort()?;
+                            self.expect_token(Token::CloseParen)?;
+                            let Sort::FiniteField(order) = sort.as_sort().unwrap() else {
+                                let err =
+                                    ParserError::ExpectedFfSort(sort.as_sort().unwrap().clone());
+                                return Err(Error::Parser(err, head_pos));
+                            };
+                            return Ok(self.pool.add(Term::new_ff(value, order.clone())));
+                        }
                         let (op, sort) = self.parse_qualified_operator()?;
                         self.make_qualified_op(op, sort, Vec::new())
                             .map_err(|err| Error::Parser(err, head_pos))
@@ -1594,63 +2907,74 @@ impl<'a, R: BufRead> Parser<'a, R> {
             }
             // Here, I would like to use an `if let` guard, like:
             //
-            //     Token::Symbol(s) if let Ok(operator) = Operator::from_str(s) => { ... }
+            //     Token::Symbol(s) if let Some(operator) = self.lookup_operator(s) => { ... }
             //
             // However, `if let` guards are still nightly only. For more info, see:
             // https://github.com/rust-lang/rust/issues/51114
-            Token::Symbol(s) if Operator::from_str(s).is_ok() => {
-                let operator = Operator::from_str(s).unwrap();
+            Token::Symbol(s) if self.lookup_operator(s).is_some() => {
+                let operator = self.lookup_operator(s).unwrap();
                 self.next_token()?;
-                let args = self.parse_sequence(Self::parse_term, true)?;
+                let (args, spans) = self.parse_arguments()?;
                 self.make_op(operator, args)
-                    .map_err(|err| Error::Parser(err, head_pos))
+                    .map_err(|err| application_error(err, &operator, &spans, head_pos))
             }
             Token::Symbol(s) if self.state.function_defs.get(s).is_some() => {
                 let head_pos = self.current_position;
                 let func_name = self.expect_symbol()?;
-                let args = self.parse_sequence(Self::parse_term, true)?;
+                let (args, spans) = self.parse_arguments()?;
                 let func = self.state.function_defs.get(&func_name).unwrap();
 
                 func.apply(self.pool, args)
-                    .map_err(|err| Error::Parser(err, head_pos))
+                    .map_err(|err| application_error(err, &func_name, &spans, head_pos))
             }
             Token::OpenParen => {
                 self.next_token()?;
                 match self.current_token {
                     Token::ReservedWord(Reserved::Underscore) => {
                         self.next_token()?;
+                        if self.current_token == Token::Symbol("is".into()) {
+                            let constructor = self.parse_tester()?;
+                            let (args, spans) = self.parse_arguments()?;
+                            let op = ParamOperator::DtTester;
+                            return self
+                                .make_tester(constructor, args)
+                                .map_err(|err| application_error(err, &op, &spans, head_pos));
+                        }
                         let (op, op_args) = self.parse_indexed_operator()?;
-                        let args = self.parse_sequence(Self::parse_term, true)?;
+                        let (args, spans) = self.parse_arguments()?;
                         self.make_indexed_op(op, op_args, args)
-                            .map_err(|err| Error::Parser(err, head_pos))
+                            .map_err(|err| application_error(err, &op, &spans, head_pos))
                     }
                     Token::ReservedWord(Reserved::As) => {
                         self.next_token()?;
                         let (op, op_sort) = self.parse_qualified_operator()?;
-                        let args = self.parse_sequence(Self::parse_term, true)?;
+                        let (args, spans) = self.parse_arguments()?;
                         self.make_qualified_op(op, op_sort, args)
-                            .map_err(|err| Error::Parser(err, head_pos))
+                            .map_err(|err| application_error(err, &op, &spans, head_pos))
                     }
                     _ => {
                         let func = self.parse_application()?;
-                        let args = self.parse_sequence(Self::parse_term, true)?;
-                        self.make_app(func, args)
-                            .map_err(|err| Error::Parser(err, head_pos))
+                        let (args, spans) = self.parse_arguments()?;
+                        self.make_app(func.clone(), args)
+                            .map_err(|err| application_error(err, &func, &spans, head_pos))
                     }
                 }
             }
             _ => {
                 let func = self.parse_term()?;
-                let args = self.parse_sequence(Self::parse_term, true)?;
-                self.make_app(func, args)
-                    .map_err(|err| Error::Parser(err, head_pos))
+                let (args, spans) = self.parse_arguments()?;
+                self.make_app(func.clone(), args)
+                    .map_err(|err| application_error(err, &func, &spans, head_pos))
             }
         }
     }
 
     fn make_sort(&mut self, name: String, args: Vec<Rc<Term>>) -> Result<Rc<Term>, ParserError> {
         let sort = match name.as_str() {
-            "Bool" | "Int" | "Real" | "String" | "RegLan" if !args.is_empty() => {
+            "Bool" | "Int" | "Real" | "String" | "RegLan" | "RoundingMode" | "Float16"
+            | "Float32" | "Float64" | "Float128"
+                if !args.is_empty() =>
+            {
                 Err(ParserError::WrongNumberOfArgs(0.into(), args.len()))
             }
             "Bool" => Ok(Sort::Bool),
@@ -1658,10 +2982,23 @@ impl<'a, R: BufRead> Parser<'a, R> {
             "Real" => Ok(Sort::Real),
             "String" => Ok(Sort::String),
             "RegLan" => Ok(Sort::RegLan),
+            "RoundingMode" => Ok(Sort::RoundingMode),
+            "Float16" => Ok(Sort::FloatingPoint(5.into(), 11.into())),
+            "Float32" => Ok(Sort::FloatingPoint(8.into(), 24.into())),
+            "Float64" => Ok(Sort::FloatingPoint(11.into(), 53.into())),
+            "Float128" => Ok(Sort::FloatingPoint(15.into(), 113.into())),
             "Array" => match args.as_slice() {
                 [x, y] => Ok(Sort::Array(x.clone(), y.clone())),
                 _ => Err(ParserError::WrongNumberOfArgs(2.into(), args.len())),
             },
+            "Set" => match args.as_slice() {
+                [x] => Ok(Sort::Set(x.clone())),
+                _ => Err(ParserError::WrongNumberOfArgs(1.into(), args.len())),
+            },
+            "Seq" => match args.as_slice() {
+                [x] => Ok(Sort::Seq(x.clone())),
+                _ => Err(ParserError::WrongNumberOfArgs(1.into(), args.len())),
+            },
             other if self.state.sort_defs.get(other).is_some() => {
                 let def = self.state.sort_defs.get(other).unwrap();
                 return if def.params.len() != args.len() {
@@ -1711,6 +3048,26 @@ impl<'a, R: BufRead> Parser<'a, R> {
                     Err(ParserError::ExpectedIntegerConstant(args[0].clone()))
                 }
             }
+            "FiniteField" => {
+                if args.len() != 1 {
+                    return Err(ParserError::WrongNumberOfArgs(1.into(), args.len()));
+                }
+                let Some(order) = args[0].as_integer() else {
+                    return Err(ParserError::ExpectedIntegerConstant(args[0].clone()));
+                };
+                assert_prime_order(&order)?;
+                Ok(self.pool.add(Term::Sort(Sort::FiniteField(order))))
+            }
+            "FloatingPoint" => {
+                if args.len() != 2 {
+                    return Err(ParserError::WrongNumberOfArgs(2.into(), args.len()));
+                }
+                let [e, s] = [&args[0], &args[1]].map(|a| match a.as_integer() {
+                    Some(i) if i > 1 => Ok(i),
+                    _ => Err(ParserError::ExpectedIntegerConstant(a.clone())),
+                });
+                Ok(self.pool.add(Term::Sort(Sort::FloatingPoint(e?, s?))))
+            }
             _ => Err(ParserError::UndefinedSort(name)),
         }
     }
@@ -1741,3 +3098,16 @@ impl<'a, R: BufRead> Parser<'a, R> {
             .map_err(|e| Error::Parser(e, pos))
     }
 }
+
+/// Writes a sequence of tokens back as text, separating them with spaces except around
+/// parentheses.
+fn tokens_to_string(tokens: &[Token]) -> String {
+    let mut result = String::new();
+    for token in tokens {
+        if !result.is_empty() && !result.ends_with('(') && *token != Token::CloseParen {
+            result.push(' ');
+        }
+        result.push_str(&token.to_string());
+    }
+    result
+}
@@ -0,0 +1,64 @@
+//! Profiles that select which string operators are recognized by the parser.
+//!
+//! The SMT-LIB theory of strings is extended by cvc5 with several operators that are not part of
+//! the standard. Since these operators use names that are otherwise free for user declarations,
+//! the parser only recognizes them if the cvc5 profile is selected.
+
+use crate::ast::Operator;
+use std::{fmt, str::FromStr};
+
+/// The string operators accepted by the parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StringsProfile {
+    /// Only the operators defined in the SMT-LIB standard theory of strings. This is the default.
+    #[default]
+    Standard,
+
+    /// The standard operators, plus the extensions supported by cvc5, such as `str.update`,
+    /// `str.rev` and `str.indexof_re`.
+    Cvc5,
+}
+
+/// Operators that are only available in the cvc5 profile.
+const CVC5_EXTENSIONS: &[Operator] = &[
+    Operator::StrUpdate,
+    Operator::StrRev,
+    Operator::StrToLower,
+    Operator::StrToUpper,
+    Operator::IndexOfRe,
+];
+
+impl StringsProfile {
+    /// All the available profiles.
+    pub const ALL: [StringsProfile; 2] = [Self::Standard, Self::Cvc5];
+
+    /// Returns the name of the profile, as accepted by its `FromStr` implementation.
+    pub fn name(self) -> &'static str {
+        match self {
+            StringsProfile::Standard => "standard",
+            StringsProfile::Cvc5 => "cvc5",
+        }
+    }
+
+    /// Returns `true` if `op` is recognized as an operator in this profile.
+    pub fn supports(self, op: Operator) -> bool {
+        match self {
+            StringsProfile::Standard => !CVC5_EXTENSIONS.contains(&op),
+            StringsProfile::Cvc5 => true,
+        }
+    }
+}
+
+impl fmt::Display for StringsProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for StringsProfile {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|p| p.name() == s).ok_or(())
+    }
+}
@@ -14,6 +14,10 @@ const TEST_CONFIG: Config = Config {
     allow_int_real_subtyping: false,
     strict: false,
     parse_hole_args: false,
+    strings_profile: StringsProfile::Standard,
+    alethe_version: None,
+    rename_duplicate_step_ids: false,
+    detect_mismatch: false,
 };
 
 pub fn parse_terms<const N: usize>(
@@ -216,6 +220,42 @@ fn test_logic_ops() {
     ));
 }
 
+#[test]
+fn test_sort_error_context() {
+    let err = parse_term_err("(and true\n     (+ 1 2))");
+    let Error::Parser(ParserError::SortError(e), pos) = err else {
+        panic!("expected sort error, got {:?}", err);
+    };
+    assert_eq!(e.expected, [Sort::Bool]);
+    assert_eq!(e.got, Sort::Int);
+    assert_eq!(e.argument, Some(1));
+    let context = e.context.as_ref().unwrap();
+    assert_eq!(context.function, "and");
+    assert_eq!(context.span, ((2, 6), (2, 12)));
+    assert_eq!(pos, (2, 6));
+    assert_eq!(
+        e.to_string(),
+        "in argument 2 of 'and': expected 'Bool', got 'Int'"
+    );
+
+    // When checking that all arguments have the same sort, the error points to the first argument
+    // that differs from the previous one
+    let err = parse_term_err("(= 1 2 (f 3) 4.0)");
+    assert!(matches!(
+        err,
+        Error::Parser(ParserError::UndefinedIden(_), _)
+    ));
+    let err = parse_term_err("(= 1 2 3.0 4.0)");
+    let Error::Parser(ParserError::SortError(e), _) = err else {
+        panic!("expected sort error, got {:?}", err);
+    };
+    assert_eq!(
+        (e.expected.as_slice(), &e.got),
+        (&[Sort::Int][..], &Sort::Real)
+    );
+    assert_eq!(e.argument, Some(2));
+}
+
 #[test]
 fn test_ite() {
     let mut p = PrimitivePool::new();
@@ -403,6 +443,26 @@ fn test_annotated_terms() {
         parse_term_err("(! true :named 1 2 3)"),
         Error::Parser(ParserError::UnexpectedToken(_), _),
     ));
+    assert!(matches!(
+        parse_term_err("(! true :pattern (1 2)"),
+        Error::Parser(ParserError::UnexpectedToken(_), _),
+    ));
+
+    // Patterns are recorded in the pool, and may mention the variables of an enclosing binder
+    let term = parse_term(
+        &mut p,
+        "(forall ((x Int)) (! (> x 0) :pattern ((+ x 1)) :pattern ((- x) x) :pattern ((+ x 1))))",
+    );
+    let (_, _, body) = term.as_quant().unwrap();
+    let int_sort = p.add(Term::Sort(Sort::Int));
+    let x = p.add(Term::new_var("x", int_sort));
+    let one = p.add(Term::new_int(1));
+    let expected = vec![
+        vec![p.add(Term::Op(Operator::Add, vec![x.clone(), one]))],
+        vec![p.add(Term::Op(Operator::Sub, vec![x.clone()])), x],
+    ];
+    assert_eq!(p.patterns(body), expected);
+    assert!(p.patterns(&term).is_empty());
 }
 
 #[test]
@@ -573,6 +633,59 @@ fn test_assume() {
     );
 }
 
+#[test]
+fn test_input_steps() {
+    let mut p = PrimitivePool::new();
+    let input = "
+        (step h1 (cl true) :rule input)
+        (step h2 (cl true false) :rule input)
+        (step t3 (cl true) :rule hole)
+    ";
+    let proof = parse_proof(&mut p, input);
+    assert_eq!(
+        &proof.commands[0],
+        &ProofCommand::Assume {
+            id: "h1".into(),
+            term: p.bool_true(),
+        }
+    );
+
+    // Multi-literal steps keep the shape of their clause, which is derived from an assumption of
+    // their disjunction
+    assert_eq!(
+        &proof.commands[1],
+        &ProofCommand::Assume {
+            id: "h2.input".into(),
+            term: parse_term(&mut p, "(or true false)"),
+        }
+    );
+    let ProofCommand::Step(step) = &proof.commands[2] else {
+        panic!("command is not a step");
+    };
+    assert_eq!(step.id, "h2");
+    assert_eq!(step.rule, "or");
+    assert_eq!(step.clause, [p.bool_true(), p.bool_false()]);
+    assert_eq!(step.premises, [(0, 1)]);
+    assert!(proof.commands[3].is_step());
+
+    // Proofs in newer versions of the format can't use `input` steps as assumptions
+    let input = "(set-info :alethe-version \"1.2\") (step h1 (cl true) :rule input)";
+    let proof = parse_proof(&mut p, input);
+    assert!(matches!(&proof.commands[0], ProofCommand::Step(s) if s.rule == "input"));
+
+    for input in [
+        "(step h1 (cl) :rule input)",
+        "(step h1 (cl true) :rule input :args (true))",
+        "(assume h1 true) (step h2 (cl true) :rule input :premises (h1))",
+    ] {
+        let mut parser = Parser::new(&mut p, TEST_CONFIG, input.as_bytes()).expect(ERROR_MESSAGE);
+        assert!(matches!(
+            parser.parse_proof(),
+            Err(Error::Parser(ParserError::InvalidInputStep(_), _))
+        ));
+    }
+}
+
 #[test]
 fn test_step() {
     let mut p = PrimitivePool::new();
@@ -582,9 +695,10 @@ fn test_step() {
         (step t3 (cl) :rule rule-name :args (1 2.0 \"three\"))
         (step t4 (cl) :rule rule-name :premises (t1 t2 t3) :args (42)
             :ignore_this :and_this (blah blah 0 1))
+        (step t5 (cl) :rule rule-name :premises (t4) :origin (t4 uncrowd) :and_this 0)
     ";
     let proof = parse_proof(&mut p, input);
-    assert_eq!(proof.commands.len(), 4);
+    assert_eq!(proof.commands.len(), 5);
 
     assert_eq!(
         &proof.commands[0],
@@ -594,7 +708,10 @@ fn test_step() {
             rule: "rule-name".into(),
             premises: Vec::new(),
             args: Vec::new(),
+            arg_vars: Vec::new(),
             discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
         })
     );
 
@@ -606,7 +723,10 @@ fn test_step() {
             rule: "rule-name".into(),
             premises: vec![(0, 0)],
             args: Vec::new(),
+            arg_vars: Vec::new(),
             discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
         })
     );
 
@@ -627,7 +747,10 @@ fn test_step() {
                 .map(|term| p.add(term))
                 .collect()
             },
+            arg_vars: Vec::new(),
             discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
         })
     );
 
@@ -639,9 +762,199 @@ fn test_step() {
             rule: "rule-name".into(),
             premises: vec![(0, 0), (0, 1), (0, 2)],
             args: vec![p.add(Term::new_int(42))],
+            arg_vars: Vec::new(),
+            discharge: Vec::new(),
+            origin: None,
+            attributes: vec![
+                StepAttribute {
+                    keyword: "ignore_this".into(),
+                    value: None,
+                },
+                StepAttribute {
+                    keyword: "and_this".into(),
+                    value: Some("(blah blah 0 1)".into()),
+                },
+            ],
+        })
+    );
+
+    assert_eq!(
+        &proof.commands[4],
+        &ProofCommand::Step(ProofStep {
+            id: "t5".into(),
+            clause: Vec::new(),
+            rule: "rule-name".into(),
+            premises: vec![(0, 3)],
+            args: Vec::new(),
+            arg_vars: Vec::new(),
             discharge: Vec::new(),
+            origin: Some(StepOrigin {
+                step: "t4".into(),
+                pass: "uncrowd".into(),
+            }),
+            attributes: vec![StepAttribute {
+                keyword: "and_this".into(),
+                value: Some("0".into()),
+            }],
         })
     );
+
+    // Older versions of veriT use `:clauses` instead of `:premises`
+    let proof = parse_proof(
+        &mut p,
+        "(step t1 (cl) :rule r) (step t2 (cl) :rule r :clauses (t1))",
+    );
+    let ProofCommand::Step(t2) = &proof.commands[1] else {
+        panic!("expected step");
+    };
+    assert_eq!(t2.premises, vec![(0, 0)]);
+}
+
+#[test]
+fn test_step_assignment_args() {
+    let mut p = PrimitivePool::new();
+    let input = "
+        (step t1 (cl) :rule rule-name :args ((:= (x Int) 1) (+ 2 3) (:= y 4.0)))
+        (step t2 (cl) :rule rule-name :args ((+ 2 3) 1))
+    ";
+    let proof = parse_proof(&mut p, input);
+    let [ProofCommand::Step(t1), ProofCommand::Step(t2)] = proof.commands.as_slice() else {
+        panic!("expected two steps");
+    };
+
+    let [one, five, four] = ["1", "(+ 2 3)", "4.0"].map(|t| parse_term(&mut p, t));
+    assert_eq!(t1.args, [one.clone(), five.clone(), four]);
+    assert_eq!(
+        t1.arg_vars,
+        [
+            Some(("x".to_owned(), p.add(Term::Sort(Sort::Int)))),
+            None,
+            Some(("y".to_owned(), p.add(Term::Sort(Sort::Real)))),
+        ]
+    );
+
+    // If no argument is an assignment, no variables are stored
+    assert_eq!(t2.args, [five, one]);
+    assert!(t2.arg_vars.is_empty());
+
+    // In strict mode, the sort of the assigned variable must be given
+    let config = Config { strict: true, ..TEST_CONFIG };
+    let input = "(step t1 (cl) :rule rule-name :args ((:= (y Real) 4.0)))";
+    let mut parser = Parser::new(&mut p, config, input.as_bytes()).expect(ERROR_MESSAGE);
+    assert!(parser.parse_proof().is_ok());
+    let input = "(step t1 (cl) :rule rule-name :args ((:= y 4.0)))";
+    let mut parser = Parser::new(&mut p, config, input.as_bytes()).expect(ERROR_MESSAGE);
+    assert!(parser.parse_proof().is_err());
+}
+
+#[test]
+fn test_duplicate_step_ids() {
+    let input = "
+        (assume h1 true)
+        (step t2 (cl) :rule hole :premises (h1))
+        (anchor :step t3)
+        (assume h1 false)
+        (step t2 (cl) :rule hole :premises (h1))
+        (step t3 (cl) :rule hole :premises (t2))
+        (step t4 (cl) :rule hole :premises (h1 t2))
+    ";
+    let mut p = PrimitivePool::new();
+
+    // By default, the repeated id is an error that points to its first definition
+    let mut parser = Parser::new(&mut p, TEST_CONFIG, input.as_bytes()).expect(ERROR_MESSAGE);
+    assert!(matches!(
+        parser.parse_proof(),
+        Err(Error::Parser(ParserError::RepeatedStepId(id, 2, 10), (5, 10))) if id == "h1"
+    ));
+
+    let config = Config {
+        rename_duplicate_step_ids: true,
+        ..TEST_CONFIG
+    };
+    let mut parser = Parser::new(&mut p, config, input.as_bytes()).expect(ERROR_MESSAGE);
+    let proof = parser.parse_proof().unwrap();
+    assert_eq!(
+        parser.renamed_step_ids(),
+        [("h1".into(), "h1'1".into()), ("t2".into(), "t2'1".into())]
+    );
+
+    // Inside the subproof, the ids refer to the renamed commands, but outside of it they refer to
+    // the original ones again
+    let ProofCommand::Subproof(subproof) = &proof.commands[2] else {
+        panic!("command is not a subproof");
+    };
+    assert_eq!(subproof.commands[0].id(), "h1'1");
+    let ProofCommand::Step(s) = &subproof.commands[1] else {
+        panic!("command is not a step");
+    };
+    assert_eq!(
+        (s.id.as_str(), s.premises.as_slice()),
+        ("t2'1", [(1, 0)].as_slice())
+    );
+    let ProofCommand::Step(s) = &subproof.commands[2] else {
+        panic!("command is not a step");
+    };
+    assert_eq!(s.premises, [(1, 1)]);
+    let ProofCommand::Step(s) = &proof.commands[3] else {
+        panic!("command is not a step");
+    };
+    assert_eq!(s.premises, [(0, 0), (0, 1)]);
+}
+
+#[test]
+fn test_detect_mismatch() {
+    let problem = "
+        (declare-sort U 0)
+        (declare-fun f (U) U)
+        (declare-const a U)
+        (declare-datatypes ((D 0)) (((c (sel U)))))
+    ";
+    let config = Config {
+        detect_mismatch: true,
+        ..TEST_CONFIG
+    };
+    let parse = |proof: &str| {
+        parse_instance(problem.as_bytes(), proof.as_bytes(), config).map(|(_, proof, _)| proof)
+    };
+
+    // Bound variables, `let` bindings, anchor arguments, named terms and functions defined in the
+    // proof are not reported
+    let proof = "
+        (assume h1 (forall ((x U)) (= (f x) (let ((y (f a))) y))))
+        (anchor :step t2 :args ((:= (z U) a)))
+        (step t2.t1 (cl (= (! (f z) :named n) (sel (c n)))) :rule hole)
+        (step t2 (cl (= (f a) (f a))) :rule bind)
+        (define-fun sk () U (choice ((w U)) (= w a)))
+        (step t3 (cl (= sk (f sk))) :rule hole :args (anything))
+    ";
+    assert!(parse(proof).is_ok());
+
+    let proof = "
+        (assume h1 (= (f a) (g a b)))
+        (assume h2 (forall ((x V)) (= (g x x) (f a))))
+        (step t3 (cl (= (f a a) (g b b))) :rule hole)
+    ";
+    let Err(Error::Parser(ParserError::ProblemMismatch(undeclared), (2, 30))) = parse(proof) else {
+        panic!("expected mismatch error");
+    };
+    let summary: Vec<_> = undeclared
+        .0
+        .iter()
+        .map(|s| (s.name.as_str(), s.arity, s.is_sort, s.uses))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("g", 2, false, 3),
+            ("b", 0, false, 3),
+            ("V", 0, true, 1),
+            ("f", 2, false, 1),
+        ]
+    );
+    assert_eq!(
+        undeclared.to_string(),
+        "symbol g/2 (3 uses), symbol b/0 (3 uses), sort V/0 (1 use), symbol f/2 (1 use)"
+    );
 }
 
 #[test]
@@ -670,7 +983,10 @@ fn test_premises_in_subproofs() {
             rule: "rule-name".into(),
             premises: vec![(0, 0), (0, 1)],
             args: Vec::new(),
+            arg_vars: Vec::new(),
             discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
         })
     );
     assert_eq!(
@@ -681,7 +997,10 @@ fn test_premises_in_subproofs() {
             rule: "rule-name".into(),
             premises: vec![(1, 0), (0, 0), (0, 1)],
             args: Vec::new(),
+            arg_vars: Vec::new(),
             discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
         })
     );
     assert_eq!(
@@ -692,11 +1011,48 @@ fn test_premises_in_subproofs() {
             rule: "rule-name".into(),
             premises: vec![(0, 0), (1, 0), (0, 1), (1, 1)],
             args: Vec::new(),
+            arg_vars: Vec::new(),
             discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
         })
     );
 }
 
+#[test]
+fn test_premise_scopes() {
+    let mut p = PrimitivePool::new();
+    let parse = |p: &mut PrimitivePool, input: &str| {
+        Parser::new(p, TEST_CONFIG, input.as_bytes()).and_then(|mut parser| parser.parse_proof())
+    };
+
+    // Steps inside a closed subproof can't be referenced, except for its last step
+    let input = "
+        (anchor :step t2)
+        (assume t2.h1 true)
+        (step t2.t1 (cl) :rule hole)
+        (step t2 (cl (not true)) :rule subproof :discharge (t2.h1))
+        (step t3 (cl) :rule hole :premises (t2 t2.t1))
+    ";
+    assert!(matches!(
+        parse(&mut p, input),
+        Err(Error::Parser(ParserError::PremiseInClosedSubproof(id, end), _))
+            if id == "t2.t1" && end == "t2"
+    ));
+
+    // Steps can only discharge assumptions that are local to the current subproof
+    let input = "
+        (assume h1 true)
+        (anchor :step t2)
+        (step t2.t1 (cl) :rule hole)
+        (step t2 (cl (not true)) :rule subproof :discharge (h1))
+    ";
+    assert!(matches!(
+        parse(&mut p, input),
+        Err(Error::Parser(ParserError::NonLocalDischarge(id), _)) if id == "h1"
+    ));
+}
+
 #[test]
 fn test_bitvectors() {
     let mut p = PrimitivePool::new();
@@ -727,6 +1083,121 @@ fn test_bitvectors() {
     }
 }
 
+#[test]
+fn test_floating_point() {
+    let mut p = PrimitivePool::new();
+    let definitions = "
+        (declare-const x Float32)
+        (declare-const y (_ FloatingPoint 8 24))
+        (declare-const r RoundingMode)
+    ";
+    let [x, y, sum, long_name, literal, to_fp, to_ubv] = parse_terms(
+        &mut p,
+        definitions,
+        [
+            "x",
+            "y",
+            "(fp.add r x y)",
+            "roundTowardZero",
+            "(fp #b0 #b10000000 #b00000000000000000000000)",
+            "((_ to_fp 8 24) RNE 0.5)",
+            "((_ fp.to_ubv 8) RTZ y)",
+        ],
+    );
+    let float32 = Sort::FloatingPoint(8.into(), 24.into());
+    assert_eq!(p.sort(&x).as_sort(), Some(&float32));
+    assert_eq!(p.sort(&y).as_sort(), Some(&float32));
+    assert_eq!(p.sort(&sum).as_sort(), Some(&float32));
+    assert_eq!(*long_name, Term::Op(Operator::RoundTowardZero, Vec::new()));
+    assert_eq!(p.sort(&literal).as_sort(), Some(&float32));
+    assert_eq!(p.sort(&to_fp).as_sort(), Some(&float32));
+    assert_eq!(p.sort(&to_ubv).as_sort(), Some(&Sort::BitVec(8.into())));
+
+    let special = parse_term(&mut p, "(fp.isNaN (_ NaN 11 53))");
+    assert_eq!(special.as_op().unwrap().0, Operator::FpIsNaN);
+
+    assert!(matches!(
+        parse_term_err("(fp.abs 1)"),
+        Error::Parser(ParserError::ExpectedFpSort(Sort::Int), _),
+    ));
+    assert!(matches!(
+        parse_term_err("(fp.add (_ +zero 2 3) (_ +zero 2 3) (_ +zero 2 3))"),
+        Error::Parser(ParserError::SortError(_), _),
+    ));
+    assert!(matches!(
+        parse_term_err("(_ +oo 1 3)"),
+        Error::Parser(ParserError::WrongValueOfArgs(_, _), _),
+    ));
+}
+
+#[test]
+fn test_datatypes() {
+    let mut p = PrimitivePool::new();
+    let definitions = "
+        (declare-datatypes ((List 0)) (((nil) (cons (head Int) (tail List)))))
+        (declare-datatype Color (red green))
+        (declare-const l List)
+    ";
+    let [app, selector, nullary, tester] = parse_terms(
+        &mut p,
+        definitions,
+        [
+            "(cons 1 l)",
+            "(tail (cons 1 nil))",
+            "green",
+            "((_ is cons) l)",
+        ],
+    );
+    let list = Sort::Atom("List".into(), Vec::new());
+    assert_eq!(p.sort(&app).as_sort(), Some(&list));
+    assert_eq!(p.sort(&selector).as_sort(), Some(&list));
+    assert_eq!(
+        p.sort(&nullary).as_sort(),
+        Some(&Sort::Atom("Color".into(), Vec::new()))
+    );
+    assert_eq!(p.sort(&tester).as_sort(), Some(&Sort::Bool));
+
+    let problem = Parser::new(&mut p, TEST_CONFIG, definitions.as_bytes())
+        .and_then(|mut parser| parser.parse_problem())
+        .unwrap();
+    let datatypes = problem.prelude.datatypes();
+    assert_eq!(datatypes.iter().count(), 2);
+    assert_eq!(
+        datatypes.get("List").unwrap().constructors[1]
+            .selectors
+            .len(),
+        2
+    );
+    assert!(problem
+        .prelude
+        .to_string()
+        .contains("(declare-datatypes ((List 0) (Color 0)) (((nil) (cons (head Int) (tail List))) ((red) (green))))"));
+
+    let mut pool = PrimitivePool::new();
+    let result =
+        Parser::new(&mut pool, TEST_CONFIG, definitions.as_bytes()).and_then(|mut parser| {
+            parser.parse_problem()?;
+            parser.reset("((_ is head) l)".as_bytes())?;
+            parser.parse_term()
+        });
+    assert!(matches!(
+        result,
+        Err(Error::Parser(ParserError::NotAConstructor(_), _))
+    ));
+
+    let mut pool = PrimitivePool::new();
+    let result = Parser::new(
+        &mut pool,
+        TEST_CONFIG,
+        "(declare-datatypes ((Pair 2)) ((par (X Y) ((pair (first X) (second Y))))))".as_bytes(),
+    )
+    .and_then(|mut parser| parser.parse_problem());
+    assert!(matches!(
+        result,
+        Err(Error::Parser(ParserError::ParametricDatatype(_), _))
+    ));
+}
+
 #[test]
 fn test_indexed_operators() {
     let mut p = PrimitivePool::new();
@@ -752,6 +1223,23 @@ fn test_indexed_operators() {
                 term: parse_term(&mut p, "(= ((_ zero_extend 2) (_ bv1 4)) (_ bv1 6))"),
             },
         ),
+        (
+            "(assume a0 (= ((_ repeat 2) #b10) #b1010))",
+            ProofCommand::Assume {
+                id: "a0".into(),
+                term: parse_term(&mut p, "(= ((_ repeat 2) #b10) #b1010)"),
+            },
+        ),
+        (
+            "(assume a0 (= ((_ rotate_left 1) #b100) ((_ rotate_right 2) #b100)))",
+            ProofCommand::Assume {
+                id: "a0".into(),
+                term: parse_term(
+                    &mut p,
+                    "(= ((_ rotate_left 1) #b100) ((_ rotate_right 2) #b100))",
+                ),
+            },
+        ),
     ];
 
     for (input, expected_value) in cases {
@@ -759,6 +1247,45 @@ fn test_indexed_operators() {
         assert_eq!(proof.commands.len(), 1);
         assert_eq!(&proof.commands[0], &expected_value);
     }
+
+    assert!(matches!(
+        parse_term_err("((_ repeat 0) #b10)"),
+        Error::Parser(ParserError::WrongValueOfArgs(_, _), _),
+    ));
+
+    let divisible = parse_term(&mut p, "((_ divisible 3) (+ 1 2))");
+    let sum = parse_term(&mut p, "(+ 1 2)");
+    let three = p.add(Term::new_int(3));
+    assert_eq!(
+        *divisible,
+        Term::ParamOp {
+            op: ParamOperator::Divisible,
+            op_args: vec![three],
+            args: vec![sum],
+        }
+    );
+    assert_eq!(p.sort(&divisible).as_sort(), Some(&Sort::Bool));
+    assert!(matches!(
+        parse_term_err("((_ divisible 0) 4)"),
+        Error::Parser(ParserError::WrongValueOfArgs(_, _), _),
+    ));
+    assert!(matches!(
+        parse_term_err("((_ divisible 2) 4.0)"),
+        Error::Parser(ParserError::SortError(_), _),
+    ));
+
+    let int2bv = parse_term(&mut p, "((_ int2bv 4) (+ 1 2))");
+    assert_eq!(p.sort(&int2bv).as_sort(), Some(&Sort::BitVec(4.into())));
+    let bv2nat = parse_term(&mut p, "(bv2nat #b101)");
+    assert_eq!(p.sort(&bv2nat).as_sort(), Some(&Sort::Int));
+    assert!(matches!(
+        parse_term_err("((_ int_to_bv 0) 4)"),
+        Error::Parser(ParserError::WrongValueOfArgs(_, _), _),
+    ));
+    assert!(matches!(
+        parse_term_err("(sbv_to_int 4)"),
+        Error::Parser(ParserError::ExpectedBvSort(_), _),
+    ));
 }
 
 #[test]
@@ -783,4 +1310,492 @@ fn test_qualified_operators() {
         parse_term_err("((as undefined (Array Int Int)) 1)"),
         Error::Parser(ParserError::InvalidQualifiedOp(_), _),
     ));
+
+    let empty = parse_term(&mut p, "(as set.empty (Set Int))");
+    let int = p.add(Term::Sort(Sort::Int));
+    assert_eq!(p.sort(&empty).as_sort(), Some(&Sort::Set(int.clone())));
+    assert!(matches!(
+        parse_term_err("(as set.empty Int)"),
+        Error::Parser(ParserError::SortError(_), _),
+    ));
+    assert!(matches!(
+        parse_term_err("(set.member 1.0 (set.singleton 1))"),
+        Error::Parser(ParserError::SortError(_), _),
+    ));
+
+    let empty = parse_term(&mut p, "(as seq.empty (Seq Int))");
+    let seq = parse_term(&mut p, "(seq.++ (seq.unit 1) (as seq.empty (Seq Int)))");
+    assert_eq!(p.sort(&empty).as_sort(), Some(&Sort::Seq(int.clone())));
+    assert_eq!(p.sort(&seq), p.sort(&empty));
+    let nth = parse_term(&mut p, "(seq.nth (seq.unit 1) 0)");
+    assert_eq!(p.sort(&nth), int);
+    assert!(matches!(
+        parse_term_err("(as seq.empty (Set Int))"),
+        Error::Parser(ParserError::SortError(_), _),
+    ));
+    assert!(matches!(
+        parse_term_err("(seq.++ (seq.unit 1) (seq.unit 1.0))"),
+        Error::Parser(ParserError::SortError(_), _),
+    ));
+    assert!(matches!(
+        parse_term_err("(seq.len \"abc\")"),
+        Error::Parser(ParserError::SortError(_), _),
+    ));
+}
+
+#[test]
+fn test_transcendental_operators() {
+    let mut p = PrimitivePool::new();
+    let [exp, pi, sqrt] = parse_terms(
+        &mut p,
+        "(set-logic QF_NRAT)",
+        ["(exp 1.0)", "(* 2.0 real.pi)", "(sqrt (sin (cos 0.5)))"],
+    );
+    for term in [exp, pi, sqrt] {
+        assert_eq!(p.sort(&term).as_sort(), Some(&Sort::Real));
+    }
+
+    // Outside of transcendental logics, the names of these operators are free for user declarations
+    let [exp] = parse_terms(
+        &mut p,
+        "(set-logic QF_NRA) (declare-fun exp (Real) Bool)",
+        ["(exp 1.0)"],
+    );
+    assert_eq!(p.sort(&exp).as_sort(), Some(&Sort::Bool));
+
+    let mut parser = Parser::new(&mut p, TEST_CONFIG, "(set-logic NRAT)".as_bytes()).unwrap();
+    parser.parse_problem().unwrap();
+    parser.reset("(sin true)".as_bytes()).unwrap();
+    assert!(matches!(
+        parser.parse_term(),
+        Err(Error::Parser(ParserError::SortError(_), _)),
+    ));
+}
+
+#[test]
+fn test_finite_fields() {
+    let mut p = PrimitivePool::new();
+    let [sum, literal, bitsum] = parse_terms(
+        &mut p,
+        "(declare-const x (_ FiniteField 7))",
+        [
+            "(ff.add x (ff.mul x #f3m7) (ff.neg x))",
+            "(as ff-1 (_ FiniteField 7))",
+            "(ff.bitsum x x #f1m7)",
+        ],
+    );
+    let field = Sort::FiniteField(7.into());
+    for term in [&sum, &literal, &bitsum] {
+        assert_eq!(p.sort(term).as_sort(), Some(&field));
+    }
+
+    // Literals are reduced modulo the field order, so both ways of writing a literal give the same
+    // term
+    let [a, b] = parse_terms(&mut p, "", ["#f13m7", "(as ff6 (_ FiniteField 7))"]);
+    assert_eq!(a, literal);
+    assert_eq!(b, literal);
+    assert_eq!(literal.to_string(), "#f6m7");
+
+    assert!(matches!(
+        parse_term_err("#f1m4"),
+        Error::Parser(ParserError::NonPrimeFieldOrder(_), _),
+    ));
+    assert!(matches!(
+        parse_term_err("(as ff1 Int)"),
+        Error::Parser(ParserError::ExpectedFfSort(_), _),
+    ));
+    assert!(matches!(
+        parse_term_err("(ff.add #f1m5 #f1m7)"),
+        Error::Parser(ParserError::SortError(_), _),
+    ));
+    assert!(matches!(
+        parse_term_err("(ff.neg 1)"),
+        Error::Parser(ParserError::ExpectedFfSort(_), _),
+    ));
+}
+
+#[test]
+fn test_strings_profile() {
+    let cvc5_config = Config {
+        strings_profile: StringsProfile::Cvc5,
+        ..TEST_CONFIG
+    };
+    let parse_cvc5 = |pool: &mut PrimitivePool, input: &str| {
+        let mut parser = Parser::new(pool, cvc5_config, input.as_bytes()).expect(ERROR_MESSAGE);
+        parser.parse_term().expect(ERROR_MESSAGE)
+    };
+
+    let mut p = PrimitivePool::new();
+    let cases = [
+        ("(str.update \"abc\" 1 \"x\")", Sort::String),
+        ("(str.rev \"abc\")", Sort::String),
+        ("(str.to_lower \"ABC\")", Sort::String),
+        ("(str.to_upper \"abc\")", Sort::String),
+        ("(str.indexof_re \"abc\" (str.to_re \"b\") 0)", Sort::Int),
+    ];
+    for (input, expected) in cases {
+        let term = parse_cvc5(&mut p, input);
+        assert_eq!(p.sort(&term).as_sort(), Some(&expected));
+    }
+
+    // Standard operators are available in both profiles
+    assert_eq!(
+        parse_cvc5(&mut p, "(str.indexof \"abc\" \"b\" 0)"),
+        parse_term(&mut p, "(str.indexof \"abc\" \"b\" 0)"),
+    );
+
+    assert!(matches!(
+        parse_term_err("(str.update \"abc\" 1 \"x\")"),
+        Error::Parser(ParserError::UndefinedIden(_), _),
+    ));
+    assert!(matches!(
+        parse_term_err("(str.rev \"abc\")"),
+        Error::Parser(ParserError::UndefinedIden(_), _),
+    ));
+
+    // In the standard profile, the names of cvc5 extensions are free for user declarations
+    let [term] = parse_terms(
+        &mut p,
+        "(declare-fun str.rev (String) String)",
+        ["(str.rev \"abc\")"],
+    );
+    assert!(matches!(term.as_ref(), Term::App(..)));
+}
+
+#[test]
+fn test_legacy_proof() {
+    let problem: &[u8] = b"(declare-fun a () Int) (declare-fun p () Bool)";
+    let proof: &[u8] = b"
+        unsat
+        (set .c1 (input :conclusion ((not (= a a)))))
+        (set .c2 (input :conclusion (p (not p))))
+        (set .c3 (eq_reflexive :conclusion ((= a a))))
+        (set .c4 (and :clauses (.c1) :iargs (0) :conclusion ((not (= a a)))))
+        (set .c5 (resolution :clauses (.c4 .c3) :conclusion ()))
+    ";
+    let (_, proof, mut pool) = parse_legacy_instance(problem, proof, Config::new()).unwrap();
+    let [a_eq_a, p] = parse_terms(
+        &mut pool,
+        "(declare-fun a () Int) (declare-fun p () Bool)",
+        ["(= a a)", "p"],
+    );
+    let not_a_eq_a = pool.add(Term::Op(Operator::Not, vec![a_eq_a.clone()]));
+    let not_p = pool.add(Term::Op(Operator::Not, vec![p.clone()]));
+    let p_or_not_p = pool.add(Term::Op(Operator::Or, vec![p, not_p]));
+
+    let expected = [
+        ProofCommand::Assume {
+            id: ".c1".into(),
+            term: not_a_eq_a.clone(),
+        },
+        ProofCommand::Assume { id: ".c2".into(), term: p_or_not_p },
+        ProofCommand::Step(ProofStep {
+            id: ".c3".into(),
+            clause: vec![a_eq_a],
+            rule: "eq_reflexive".into(),
+            premises: Vec::new(),
+            args: Vec::new(),
+            arg_vars: Vec::new(),
+            discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
+        }),
+        ProofCommand::Step(ProofStep {
+            id: ".c4".into(),
+            clause: vec![not_a_eq_a],
+            rule: "and".into(),
+            premises: vec![(0, 0)],
+            args: Vec::new(),
+            arg_vars: Vec::new(),
+            discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
+        }),
+        ProofCommand::Step(ProofStep {
+            id: ".c5".into(),
+            clause: Vec::new(),
+            rule: "resolution".into(),
+            premises: vec![(0, 3), (0, 2)],
+            args: Vec::new(),
+            arg_vars: Vec::new(),
+            discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
+        }),
+    ];
+    assert_eq!(proof.commands, expected);
+
+    let empty_input: &[u8] = b"(set .c1 (input :conclusion ()))";
+    let (_, proof, mut pool) = parse_legacy_instance(problem, empty_input, Config::new()).unwrap();
+    let expected = ProofCommand::Assume {
+        id: ".c1".into(),
+        term: pool.bool_false(),
+    };
+    assert_eq!(proof.commands, [expected]);
+
+    let missing_conclusion: &[u8] = b"(set .c1 (eq_reflexive :clauses ()))";
+    assert!(matches!(
+        parse_legacy_instance(problem, missing_conclusion, Config::new()),
+        Err(Error::Parser(ParserError::MissingConclusion(_), _)),
+    ));
+}
+
+#[test]
+fn test_parse_problem_and_proofs() {
+    let problem: &[u8] = b"(declare-fun a () Bool) (assert a)";
+    let first: &[u8] = b"
+        (define-fun c () Bool (not a))
+        (assume h1 a)
+        (step t2 (cl c) :rule hole)
+    ";
+    let second: &[u8] = b"
+        (define-fun c () Bool (and a a))
+        (assume h1 a)
+        (step t2 (cl c) :rule hole)
+    ";
+    let third: &[u8] = b"(step t1 (cl b) :rule hole)";
+    let fourth: &[u8] = b"(step t1 (cl (not a)) :rule hole)";
+
+    let (problem, proofs, mut pool) =
+        parse_problem_and_proofs(problem, vec![first, second, third, fourth], Config::new())
+            .unwrap();
+    assert_eq!(problem.premises.len(), 1);
+
+    // Definitions and step ids from one proof must not leak into the others
+    let [first, second, third, fourth]: [_; 4] = proofs.try_into().unwrap();
+    let [not_a, a_and_a] = parse_terms(
+        &mut pool,
+        "(declare-fun a () Bool)",
+        ["(not a)", "(and a a)"],
+    );
+    assert_eq!(
+        first.unwrap().commands[1].clause(),
+        std::slice::from_ref(&not_a)
+    );
+    assert_eq!(second.unwrap().commands[1].clause(), [a_and_a]);
+    assert!(matches!(
+        third,
+        Err(Error::Parser(ParserError::UndefinedIden(_), _)),
+    ));
+    assert_eq!(fourth.unwrap().commands[0].clause(), [not_a]);
+}
+
+#[test]
+fn test_parse_proof_without_problem() {
+    let proof: &[u8] = b"
+        (declare-sort U 0)
+        (declare-fun f (U) U)
+        (declare-const x U)
+        (assume h1 (= (f x) x))
+        (anchor :step t2)
+            (assume t2.h1 (= x (f x)))
+            (step t2 (cl) :rule hole)
+        (step t3 (cl) :rule hole)
+    ";
+    let (problem, proof, mut pool) = parse_proof_without_problem(proof, Config::new()).unwrap();
+
+    let prelude = &problem.prelude;
+    assert_eq!(prelude.sort_declarations, [("U".to_owned(), 0)]);
+    let names: Vec<_> = prelude
+        .function_declarations
+        .iter()
+        .map(|(n, _)| n)
+        .collect();
+    assert_eq!(names, ["f", "x"]);
+
+    // Only the assumptions outside of subproofs become premises
+    let [premise] = parse_terms(
+        &mut pool,
+        "(declare-sort U 0) (declare-fun f (U) U) (declare-const x U)",
+        ["(= (f x) x)"],
+    );
+    assert_eq!(problem.premises.into_iter().collect::<Vec<_>>(), [premise]);
+    assert_eq!(proof.commands.len(), 3);
+
+    // Declarations are only allowed in proofs parsed without a problem
+    let problem: &[u8] = b"";
+    let proof: &[u8] = b"(declare-const x Int) (step t1 (cl (= x x)) :rule hole)";
+    assert!(matches!(
+        parse_instance(problem, proof, Config::new()),
+        Err(Error::Parser(ParserError::UnexpectedToken(_), _)),
+    ));
+}
+
+#[test]
+fn test_parse_embedded_instance() {
+    let proof: &[u8] = b"
+        (set-logic QF_UF)
+        (declare-sort U 0)
+        (declare-fun f (U) U)
+        (declare-const x U)
+        (assert (= (f x) x))
+        (check-sat)
+        unsat
+        (assume h1 (= (f x) x))
+        (declare-const y U)
+        (step t2 (cl (= y y)) :rule refl)
+        (step t3 (cl) :rule hole)
+    ";
+    let (problem, proof, mut pool) = parse_embedded_instance(proof, Config::new()).unwrap();
+    let prelude = &problem.prelude;
+    assert_eq!(prelude.logic.as_deref(), Some("QF_UF"));
+    assert_eq!(prelude.sort_declarations, [("U".to_owned(), 0)]);
+    assert_eq!(prelude.function_declarations.len(), 3);
+    let [premise] = parse_terms(
+        &mut pool,
+        "(declare-sort U 0) (declare-fun f (U) U) (declare-const x U)",
+        ["(= (f x) x)"],
+    );
+    assert_eq!(problem.premises.into_iter().collect::<Vec<_>>(), [premise]);
+    assert_eq!(proof.commands.len(), 3);
+
+    // Without assertions, the assumptions become the premises
+    let proof: &[u8] = b"
+        (declare-const p Bool)
+        (assume h1 p)
+        (assume h2 (not p))
+        (step t3 (cl) :rule resolution :premises (h1 h2))
+    ";
+    let (problem, proof, _) = parse_embedded_instance(proof, Config::new()).unwrap();
+    assert_eq!(problem.premises.len(), 2);
+    assert_eq!(proof.commands.len(), 3);
+}
+
+#[test]
+fn test_synthesize_prelude() {
+    let proof: &[u8] = b"
+        (declare-fun c () Int)
+        (assume h1 (= (f a) b))
+        (assume h2 (forall ((x U)) (p (g x c))))
+        (anchor :step t3 :args ((y Int) (:= (z Int) y)))
+            (step t3.t1 (cl (= z y)) :rule refl)
+            (step t3 (cl (= (q z) (q y))) :rule hole)
+        (step t4 (cl (< (+ k 1.5) 2.0) (= (select m 1) b)) :rule hole)
+        (step t5 (cl (! (= e e) :named n)) :rule hole)
+        (step t6 (cl n) :rule hole)
+    ";
+    let expected = "\
+        (declare-sort InferredSort 0)\n\
+        (declare-sort U 0)\n\
+        (declare-fun a () InferredSort)\n\
+        (declare-fun f (InferredSort) InferredSort)\n\
+        (declare-fun b () InferredSort)\n\
+        (declare-fun g (U Int) InferredSort)\n\
+        (declare-fun p (InferredSort) Bool)\n\
+        (declare-fun q (Int) InferredSort)\n\
+        (declare-fun k () Real)\n\
+        (declare-fun m () (Array Int InferredSort))\n\
+        (declare-fun e () InferredSort)\n\
+    ";
+    assert_eq!(synthesize_prelude(proof, Config::new()).unwrap(), expected);
+
+    let (problem, proof, _) = parse_proof_with_synthesized_prelude(proof, Config::new()).unwrap();
+    assert_eq!(problem.prelude.sort_declarations.len(), 2);
+    assert_eq!(problem.prelude.function_declarations.len(), 10);
+    assert_eq!(problem.premises.len(), 2);
+    assert_eq!(proof.commands.len(), 6);
+
+    let conflicting: &[u8] = b"(assume h1 (p a)) (assume h2 (p 1)) (assume h3 (p 2.0))";
+    assert!(matches!(
+        synthesize_prelude(conflicting, Config::new()),
+        Err(Error::Parser(
+            ParserError::InferredSortConflict(..),
+            (1, 51)
+        )),
+    ));
+    let wrong_arity: &[u8] = b"(assume h1 (p a)) (assume h2 (p a a))";
+    assert!(matches!(
+        synthesize_prelude(wrong_arity, Config::new()),
+        Err(Error::Parser(ParserError::InconsistentArity(..), _)),
+    ));
+
+    // Operators are sort checked like the parser does, so mixing `Int` and `Real` arguments is only
+    // allowed with Int/Real subtyping
+    let mixed: &[u8] = b"(assume h1 (< (+ x 1 2.5) 4)) (assume h2 (= (bvadd y #b0001) z))";
+    let config = Config {
+        allow_int_real_subtyping: true,
+        ..Config::new()
+    };
+    let expected = "\
+        (declare-fun x () Int)\n\
+        (declare-fun y () (_ BitVec 4))\n\
+        (declare-fun z () (_ BitVec 4))\n\
+    ";
+    assert_eq!(synthesize_prelude(mixed, config).unwrap(), expected);
+    assert!(matches!(
+        synthesize_prelude(mixed, Config::new()),
+        Err(Error::Parser(ParserError::InferredSortConflict(..), _)),
+    ));
+}
+
+#[test]
+fn test_alethe_version() {
+    let parse = |config: Config, input: &str| {
+        let mut pool = PrimitivePool::new();
+        let mut parser = Parser::new(&mut pool, config, input.as_bytes()).expect(ERROR_MESSAGE);
+        parser.parse_proof()
+    };
+    let rule_of = |command: &ProofCommand| match command {
+        ProofCommand::Step(s) => s.rule.clone(),
+        _ => panic!("command is not a step"),
+    };
+    let strict = Config { strict: true, ..TEST_CONFIG };
+    let legacy_rule = "(step t1 (cl) :rule tmp_AC_simp)";
+    let unsorted_anchor = "
+        (anchor :step t1 :args ((:= x 0)))
+        (step t1.t1 (cl) :rule hole)
+        (step t1 (cl) :rule hole)
+    ";
+
+    // Legacy rule names are detected and normalized, unless another version is configured
+    let proof = parse(TEST_CONFIG, legacy_rule).unwrap();
+    assert_eq!(rule_of(&proof.commands[0]), "ac_simp");
+    let config = Config {
+        alethe_version: Some(AletheVersion::V1_2),
+        ..TEST_CONFIG
+    };
+    let proof = parse(config, legacy_rule).unwrap();
+    assert_eq!(rule_of(&proof.commands[0]), "tmp_AC_simp");
+
+    // Rules inside subproofs are also normalized
+    let input = "
+        (anchor :step t1)
+        (step t1.t1 (cl) :rule tmp_nary_elim)
+        (step t1 (cl) :rule hole)
+    ";
+    let proof = parse(TEST_CONFIG, input).unwrap();
+    let ProofCommand::Subproof(s) = &proof.commands[0] else {
+        panic!("command is not a subproof");
+    };
+    assert_eq!(rule_of(&s.commands[0]), "nary_elim");
+
+    // In strict mode, anchor arguments without sort hints are only allowed in the legacy version
+    assert!(parse(strict, unsorted_anchor).is_err());
+    let input = format!("(set-info :alethe-version \"legacy\") {unsorted_anchor}");
+    assert!(parse(strict, &input).is_ok());
+    let input = format!("(set-info :alethe-version \"1.2\") {unsorted_anchor}");
+    assert!(parse(strict, &input).is_err());
+    let config = Config {
+        alethe_version: Some(AletheVersion::Legacy),
+        ..strict
+    };
+    assert!(parse(config, unsorted_anchor).is_ok());
+
+    // The same applies when the legacy version is detected from the rule names
+    let input = format!("{unsorted_anchor} (step t2 (cl) :rule tmp_AC_simp)");
+    assert!(parse(strict, &input).is_ok());
+    let input = format!("{unsorted_anchor} (step t2 (cl) :rule ac_simp)");
+    assert!(parse(strict, &input).is_err());
+
+    // Other `set-info` attributes are ignored
+    let input = format!("(set-info :source |some solver|) {legacy_rule}");
+    assert_eq!(
+        rule_of(&parse(TEST_CONFIG, &input).unwrap().commands[0]),
+        "ac_simp"
+    );
+
+    assert!(matches!(
+        parse(TEST_CONFIG, "(set-info :alethe-version \"0.1\")"),
+        Err(Error::Parser(ParserError::UnknownAletheVersion(_), _)),
+    ));
 }
@@ -0,0 +1,98 @@
+//! Versions of the Alethe format, and their detection.
+//!
+//! The Alethe format has changed over time: some rules were renamed, and the syntax of
+//! assign-style anchor arguments gained a sort hint. To support both archived proofs and the
+//! output of current solvers, the parser determines the version of each proof, and normalizes
+//! proofs written in older versions to the current one, so the checker only needs to handle a
+//! single version.
+//!
+//! The version can be given explicitly in the parser configuration, or declared in the proof
+//! itself with a `(set-info :alethe-version "<version>")` command. If neither is present, the
+//! version is inferred from the rule names used in the proof.
+
+use crate::ast::{Proof, ProofCommand, VERIT_LEGACY_RULE_NAMES};
+use std::{fmt, str::FromStr};
+
+/// A version of the Alethe format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AletheVersion {
+    /// The format produced by veriT before the Alethe specification was stabilized. In this
+    /// version, some rules have different names (like `tmp_AC_simp` instead of `ac_simp`), and
+    /// assign-style anchor arguments have no sort hint.
+    Legacy,
+
+    /// Version 1.2 of the Alethe specification. This is the default.
+    #[default]
+    V1_2,
+}
+
+impl AletheVersion {
+    /// All the available versions.
+    pub const ALL: [AletheVersion; 2] = [Self::Legacy, Self::V1_2];
+
+    /// Returns the name of the version, as accepted by its `FromStr` implementation.
+    pub fn name(self) -> &'static str {
+        match self {
+            AletheVersion::Legacy => "legacy",
+            AletheVersion::V1_2 => "1.2",
+        }
+    }
+
+    /// Infers the version of a proof from the rules it uses. Returns `None` if the proof has no
+    /// evidence of any particular version.
+    pub fn detect(proof: &Proof) -> Option<Self> {
+        let uses_legacy_rule = proof.iter().any(|c| match c {
+            ProofCommand::Step(s) => VERIT_LEGACY_RULE_NAMES.iter().any(|(_, l)| *l == s.rule),
+            _ => false,
+        });
+        uses_legacy_rule.then_some(AletheVersion::Legacy)
+    }
+
+    /// Returns `true` if assign-style anchor arguments may omit the sort hint, even when strict
+    /// parsing is enabled.
+    pub(super) fn allows_unsorted_assign_args(self) -> bool {
+        self == AletheVersion::Legacy
+    }
+
+    /// Returns `true` if steps that use the `input` rule are read as assumptions. Older versions of
+    /// veriT introduce input formulas this way, instead of using `assume` commands.
+    pub(super) fn allows_input_steps(self) -> bool {
+        self == AletheVersion::Legacy
+    }
+
+    /// Rewrites a proof written in this version so it uses the current names for all rules.
+    pub(super) fn normalize(self, proof: &mut Proof) {
+        fn rename(commands: &mut [ProofCommand]) {
+            for command in commands {
+                match command {
+                    ProofCommand::Assume { .. } => (),
+                    ProofCommand::Step(s) => {
+                        let new_name = VERIT_LEGACY_RULE_NAMES.iter().find(|(_, l)| *l == s.rule);
+                        if let Some((new_name, _)) = new_name {
+                            s.rule = (*new_name).to_owned();
+                        }
+                    }
+                    ProofCommand::Subproof(s) => rename(&mut s.commands),
+                }
+            }
+        }
+
+        if self == AletheVersion::Legacy {
+            rename(&mut proof.commands);
+        }
+    }
+}
+
+impl fmt::Display for AletheVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for AletheVersion {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|v| v.name() == s).ok_or(())
+    }
+}
@@ -1,4 +1,4 @@
-use crate::ast::*;
+use crate::{ast::*, error_code::ErrorCode};
 use indexmap::{map::Entry, IndexMap, IndexSet};
 use thiserror::Error;
 
@@ -20,6 +20,20 @@ pub enum ResolutionError {
     PivotNotFound(Rc<Term>),
 }
 
+impl ResolutionError {
+    /// Returns the stable code identifying this kind of error.
+    pub fn code(&self) -> ErrorCode {
+        let number = match self {
+            Self::TautologyFailed => 300,
+            Self::RemainingPivot(..) => 301,
+            Self::ExtraTermInConclusion(..) => 302,
+            Self::MissingTermInConclusion(..) => 303,
+            Self::PivotNotFound(..) => 304,
+        };
+        ErrorCode::new(number)
+    }
+}
+
 pub type Literal<'a> = (u32, &'a Rc<Term>);
 
 /// A collection that can be used as a clause during resolution.
@@ -0,0 +1,564 @@
+//! A small, bounded term rewriting engine.
+//!
+//! Rewrite rules are kept in a [`RuleDatabase`], where each rule is identified by a name. A
+//! [`Rewriter`] uses the rules in a database to rewrite terms, either by normalizing them with a
+//! given [`Strategy`], or by searching for a sequence of rewrites between two terms. All of these
+//! operations are bounded, so they always terminate, even if the rules are not terminating.
+//!
+//! Rules can be implemented natively, or loaded from a file of RARE rules (see [`RareRule`]). The
+//! checker uses this engine for umbrella rules like `all_simplify`, whose steps may combine many
+//! individual rewrites, and to check `rare_rewrite` steps.
+
+mod rare;
+
+use crate::{
+    ast::*,
+    eval::{self, Value},
+    CarcaraResult,
+};
+use indexmap::IndexMap;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    io::BufRead,
+    sync::Arc,
+};
+
+pub use rare::RareRule;
+
+/// A rewrite rule. Given a term, it returns the result of rewriting the root of that term, or
+/// `None` if the rule does not apply.
+pub type RewriteFunction = dyn Fn(&Term, &mut dyn TermPool) -> Option<Rc<Term>> + Send + Sync;
+
+/// A collection of named rewrite rules.
+#[derive(Default, Clone)]
+pub struct RuleDatabase {
+    rules: IndexMap<String, Arc<RewriteFunction>>,
+
+    /// The rules that were given in RARE, which can also be instantiated explicitly.
+    rare: HashMap<String, Arc<RareRule>>,
+}
+
+impl fmt::Debug for RuleDatabase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl RuleDatabase {
+    /// Constructs a new, empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a database with the rewrites implemented by the checker's simplification rules,
+    /// plus constant evaluation. Each rewrite is named after the Alethe rule that justifies it.
+    /// Since constant evaluation does not correspond to any fine-grained rule, it is named
+    /// `all_simplify`.
+    pub fn simplification() -> Self {
+        let mut result = Self::new();
+        for &(name, rule) in crate::checker::SIMPLIFICATION_REWRITES {
+            result.add(name, rule);
+        }
+        result.add("all_simplify", evaluate);
+        result
+    }
+
+    /// Parses a file of RARE rewrite rules, such as the ones in cvc5's rewrite database, returning
+    /// a database with the rules it defines.
+    pub fn parse_rare<R: BufRead>(input: R) -> CarcaraResult<Self> {
+        let mut result = Self::new();
+        for (name, rule) in rare::parse_rules(input)? {
+            result.add_rare(name, rule);
+        }
+        Ok(result)
+    }
+
+    /// Adds a rule to the database. If a rule with the same name already exists, it is replaced.
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        rule: impl Fn(&Term, &mut dyn TermPool) -> Option<Rc<Term>> + Send + Sync + 'static,
+    ) {
+        let name = name.into();
+        self.rare.remove(&name);
+        self.rules.insert(name, Arc::new(rule));
+    }
+
+    /// Adds a RARE rule to the database. When used by a [`Rewriter`], the rule is applied by
+    /// matching terms against its left-hand side. If a rule with the same name already exists, it
+    /// is replaced.
+    pub fn add_rare(&mut self, name: impl Into<String>, rule: RareRule) {
+        let name = name.into();
+        let rule = Arc::new(rule);
+        let apply = rule.clone();
+        self.add(name.clone(), move |term: &Term, pool: &mut dyn TermPool| {
+            let term = pool.add(term.clone());
+            apply.apply(&term, pool)
+        });
+        self.rare.insert(name, rule);
+    }
+
+    /// Adds all the rules in `other` to the database, replacing the rules with the same names.
+    pub fn extend(&mut self, other: &RuleDatabase) {
+        for (name, rule) in &other.rules {
+            self.rare.remove(name);
+            self.rules.insert(name.clone(), rule.clone());
+        }
+        self.rare.extend(
+            other
+                .rare
+                .iter()
+                .map(|(name, rule)| (name.clone(), rule.clone())),
+        );
+    }
+
+    /// Returns the rule with the given name, if it exists.
+    pub fn get(&self, name: &str) -> Option<&RewriteFunction> {
+        self.rules.get(name).map(AsRef::as_ref)
+    }
+
+    /// Returns the RARE rule with the given name, if it exists.
+    pub fn get_rare(&self, name: &str) -> Option<&RareRule> {
+        self.rare.get(name).map(AsRef::as_ref)
+    }
+
+    /// Returns `true` if the database contains a rule with the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.rules.contains_key(name)
+    }
+
+    /// Returns an iterator over the names of the rules in the database.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.rules.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// Returns `true` if the term is a constant value that can be passed to the evaluator. Sequences
+/// built from constant values are also considered values.
+fn is_value(term: &Term) -> bool {
+    if term.is_signed_number() || term.is_const() || term.as_bool().is_some() {
+        return true;
+    }
+    match term {
+        Term::Op(Operator::SeqUnit | Operator::SeqConcat, args) => args.iter().all(|a| is_value(a)),
+        Term::ParamOp { op: ParamOperator::SeqEmpty, .. } => true,
+        _ => false,
+    }
+}
+
+/// Evaluates an operator application whose arguments are all constant values. Results that can't
+/// be represented as a single constant, like non-integer reals, are not considered.
+pub fn evaluate(term: &Term, pool: &mut dyn TermPool) -> Option<Rc<Term>> {
+    match term {
+        Term::Op(_, args) | Term::ParamOp { args, .. }
+            if !args.is_empty() && args.iter().all(|a| is_value(a)) => {}
+        _ => return None,
+    }
+    let term = pool.add(term.clone());
+    let result = match eval::evaluate(&term, pool).ok()? {
+        Value::Int(i) if i < 0 => {
+            let n = pool.add(Term::new_int(-i));
+            build_term!(pool, (-{ n }))
+        }
+        Value::Real(r) if !r.is_integer() => return None,
+        Value::Real(r) if r < 0 => {
+            let n = pool.add(Term::new_real(-r));
+            build_term!(pool, (-{ n }))
+        }
+        Value::Abstract(_) => return None,
+        value => value.to_term(pool),
+    };
+    (result != term).then_some(result)
+}
+
+/// The order in which subterms are considered when rewriting a term.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Rewrite the innermost subterms first.
+    #[default]
+    Innermost,
+
+    /// Rewrite the outermost subterms first.
+    Outermost,
+}
+
+/// A single rewrite in a rewrite sequence.
+#[derive(Debug, Clone)]
+pub struct RewriteStep {
+    /// The name of the rule that justifies the rewrite.
+    pub rule: String,
+
+    /// The position of the rewritten subterm, as the sequence of argument indices that lead to it
+    /// from the root of the term.
+    pub position: Vec<usize>,
+
+    /// The subterm before the rewrite.
+    pub redex: Rc<Term>,
+
+    /// The subterm after the rewrite.
+    pub contractum: Rc<Term>,
+
+    /// The whole term after the rewrite.
+    pub result: Rc<Term>,
+}
+
+/// Rewrites terms using the rules in a [`RuleDatabase`].
+pub struct Rewriter<'a> {
+    rules: Vec<(&'a str, &'a RewriteFunction)>,
+
+    /// The order in which subterms are rewritten when normalizing a term.
+    pub strategy: Strategy,
+
+    /// The maximum number of rewrites applied when normalizing a term.
+    pub step_limit: usize,
+
+    /// The maximum length of a rewrite sequence found by the breadth-first search.
+    pub search_depth: usize,
+
+    /// The maximum number of distinct terms visited by the breadth-first search.
+    pub search_states: usize,
+}
+
+impl<'a> Rewriter<'a> {
+    /// Constructs a new rewriter that uses all the rules in `database`.
+    pub fn new(database: &'a RuleDatabase) -> Self {
+        Self {
+            rules: database
+                .rules
+                .iter()
+                .map(|(name, rule)| (name.as_str(), rule.as_ref()))
+                .collect(),
+            strategy: Strategy::default(),
+            step_limit: 1024,
+            search_depth: 4,
+            search_states: 2048,
+        }
+    }
+
+    /// Restricts the rewriter to only use the rules with the given names.
+    pub fn only(mut self, names: &[&str]) -> Self {
+        self.rules.retain(|(name, _)| names.contains(name));
+        self
+    }
+
+    pub fn strategy(mut self, value: Strategy) -> Self {
+        self.strategy = value;
+        self
+    }
+
+    pub fn step_limit(mut self, value: usize) -> Self {
+        self.step_limit = value;
+        self
+    }
+
+    pub fn search_depth(mut self, value: usize) -> Self {
+        self.search_depth = value;
+        self
+    }
+
+    pub fn search_states(mut self, value: usize) -> Self {
+        self.search_states = value;
+        self
+    }
+
+    /// Collects all subterms of `term` that can be rewritten, together with their positions, in
+    /// the order given by the strategy. Binders and `let` terms are not entered.
+    fn collect_positions(
+        &self,
+        term: &Rc<Term>,
+        position: &mut Vec<usize>,
+        acc: &mut Vec<(Vec<usize>, Rc<Term>)>,
+    ) {
+        if self.strategy == Strategy::Outermost {
+            acc.push((position.clone(), term.clone()));
+        }
+        if let Term::Op(_, args) | Term::App(_, args) | Term::ParamOp { args, .. } = term.as_ref() {
+            for (i, arg) in args.iter().enumerate() {
+                position.push(i);
+                self.collect_positions(arg, position, acc);
+                position.pop();
+            }
+        }
+        if self.strategy == Strategy::Innermost {
+            acc.push((position.clone(), term.clone()));
+        }
+    }
+
+    fn rewrites_impl(
+        &self,
+        pool: &mut dyn TermPool,
+        term: &Rc<Term>,
+        first_only: bool,
+    ) -> Vec<RewriteStep> {
+        let mut positions = Vec::new();
+        self.collect_positions(term, &mut Vec::new(), &mut positions);
+
+        let mut result = Vec::new();
+        for (position, redex) in positions {
+            for &(name, rule) in &self.rules {
+                let Some(contractum) = rule(&redex, pool) else {
+                    continue;
+                };
+                if contractum == redex {
+                    continue;
+                }
+                let new_term = replace_at(pool, term, &position, contractum.clone());
+                result.push(RewriteStep {
+                    rule: name.to_owned(),
+                    position: position.clone(),
+                    redex: redex.clone(),
+                    contractum,
+                    result: new_term,
+                });
+                if first_only {
+                    return result;
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns all the rewrites that can be applied to `term`, in the order given by the strategy.
+    pub fn rewrites(&self, pool: &mut dyn TermPool, term: &Rc<Term>) -> Vec<RewriteStep> {
+        self.rewrites_impl(pool, term, false)
+    }
+
+    /// Rewrites `term` until no more rewrites can be applied, always applying the first rewrite
+    /// given by the strategy. Stops early if a cycle is found or if the step limit is reached.
+    pub fn normalize(&self, pool: &mut dyn TermPool, term: &Rc<Term>) -> Vec<RewriteStep> {
+        let mut sequence = Vec::new();
+        let mut seen = HashSet::new();
+        let mut current = term.clone();
+        while sequence.len() < self.step_limit && seen.insert(current.clone()) {
+            match self.rewrites_impl(pool, &current, true).pop() {
+                Some(step) => {
+                    current = step.result.clone();
+                    sequence.push(step);
+                }
+                None => break,
+            }
+        }
+        sequence
+    }
+
+    /// Searches for a sequence of rewrites from `from` to `to` using a bounded breadth-first
+    /// search.
+    pub fn search(
+        &self,
+        pool: &mut dyn TermPool,
+        from: &Rc<Term>,
+        to: &Rc<Term>,
+    ) -> Option<Vec<RewriteStep>> {
+        // Maps each visited term to the term it was reached from, and the rewrite that was used
+        let mut parents: HashMap<Rc<Term>, Option<(Rc<Term>, RewriteStep)>> = HashMap::new();
+        let mut queue = VecDeque::new();
+        parents.insert(from.clone(), None);
+        queue.push_back((from.clone(), 0));
+
+        while let Some((current, depth)) = queue.pop_front() {
+            if current == *to {
+                let mut sequence = Vec::new();
+                let mut current = current;
+                while let Some((previous, step)) = parents[&current].clone() {
+                    sequence.push(step);
+                    current = previous;
+                }
+                sequence.reverse();
+                return Some(sequence);
+            }
+            if depth == self.search_depth {
+                continue;
+            }
+            for step in self.rewrites(pool, &current) {
+                if parents.len() >= self.search_states {
+                    return None;
+                }
+                if !parents.contains_key(&step.result) {
+                    parents.insert(step.result.clone(), Some((current.clone(), step.clone())));
+                    queue.push_back((step.result, depth + 1));
+                }
+            }
+        }
+        None
+    }
+
+    /// Finds a sequence of rewrites that transforms `from` into `to`. Rules are only ever applied
+    /// from left to right, since a rewrite rule may not hold in the other direction.
+    ///
+    /// First, `from` is normalized, and if `to` is reached along the way, the rewrites applied so
+    /// far are returned. If this fails, a bounded breadth-first search is used instead.
+    pub fn find_sequence(
+        &self,
+        pool: &mut dyn TermPool,
+        from: &Rc<Term>,
+        to: &Rc<Term>,
+    ) -> Option<Vec<RewriteStep>> {
+        if from == to {
+            return Some(Vec::new());
+        }
+
+        let mut forward = self.normalize(pool, from);
+        if let Some(i) = forward.iter().position(|s| s.result == *to) {
+            forward.truncate(i + 1);
+            return Some(forward);
+        }
+        self.search(pool, from, to)
+    }
+}
+
+/// Returns the subterm of `term` at `position`.
+pub fn subterm_at<'t>(term: &'t Rc<Term>, position: &[usize]) -> &'t Rc<Term> {
+    position
+        .iter()
+        .fold(term, |current, &i| match current.as_ref() {
+            Term::Op(_, args) | Term::App(_, args) | Term::ParamOp { args, .. } => &args[i],
+            _ => panic!("invalid position for term '{}'", term),
+        })
+}
+
+/// Replaces the subterm of `term` at `position` with `new`.
+fn replace_at(
+    pool: &mut dyn TermPool,
+    term: &Rc<Term>,
+    position: &[usize],
+    new: Rc<Term>,
+) -> Rc<Term> {
+    let Some((&i, rest)) = position.split_first() else {
+        return new;
+    };
+    match term.as_ref() {
+        Term::Op(op, args) => {
+            let mut args = args.clone();
+            args[i] = replace_at(pool, &args[i], rest, new);
+            pool.add(Term::Op(*op, args))
+        }
+        Term::App(f, args) => {
+            let mut args = args.clone();
+            args[i] = replace_at(pool, &args[i], rest, new);
+            pool.add(Term::App(f.clone(), args))
+        }
+        Term::ParamOp { op, op_args, args } => {
+            let mut args = args.clone();
+            args[i] = replace_at(pool, &args[i], rest, new);
+            pool.add(Term::ParamOp {
+                op: *op,
+                op_args: op_args.clone(),
+                args,
+            })
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::tests::parse_terms;
+
+    #[test]
+    fn test_strategies() {
+        let mut pool = PrimitivePool::new();
+        let [term, inner_first, outer_first] = parse_terms(
+            &mut pool,
+            "(declare-const p Bool) (declare-const q Bool)",
+            [
+                "(not (not (ite true p q)))",
+                "(not (not p))",
+                "(ite true p q)",
+            ],
+        );
+        let database = RuleDatabase::simplification();
+
+        let innermost = Rewriter::new(&database).strategy(Strategy::Innermost);
+        let steps = innermost.rewrites(&mut pool, &term);
+        assert_eq!(steps[0].rule, "ite_simplify");
+        assert_eq!(steps[0].position, [0, 0]);
+        assert_eq!(steps[0].result, inner_first);
+
+        let outermost = Rewriter::new(&database).strategy(Strategy::Outermost);
+        let steps = outermost.rewrites(&mut pool, &term);
+        assert_eq!(steps[0].rule, "not_simplify");
+        assert!(steps[0].position.is_empty());
+        assert_eq!(steps[0].result, outer_first);
+
+        // Both strategies reach the same normal form
+        let a = innermost.normalize(&mut pool, &term);
+        let b = outermost.normalize(&mut pool, &term);
+        assert_eq!(a.last().unwrap().result, b.last().unwrap().result);
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut pool = PrimitivePool::new();
+        let [term, target] = parse_terms(
+            &mut pool,
+            "(declare-const p Bool) (declare-const q Bool)",
+            ["(not (not (not (not (ite true p q)))))", "p"],
+        );
+        let database = RuleDatabase::simplification();
+
+        let rewriter = Rewriter::new(&database);
+        assert_eq!(rewriter.normalize(&mut pool, &term).len(), 3);
+        let limited = Rewriter::new(&database).step_limit(1);
+        assert_eq!(limited.normalize(&mut pool, &term).len(), 1);
+        assert!(rewriter.find_sequence(&mut pool, &term, &target).is_some());
+
+        let only_not = Rewriter::new(&database).only(&["not_simplify"]);
+        assert!(only_not.find_sequence(&mut pool, &term, &target).is_none());
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let mut pool = PrimitivePool::new();
+        let [term, target] = parse_terms(
+            &mut pool,
+            "(declare-const p Bool) (declare-const q Bool)",
+            ["(and p q)", "(and q p)"],
+        );
+        let mut database = RuleDatabase::new();
+        database.add("and_comm", |term: &Term, pool: &mut dyn TermPool| {
+            let (a, b) = match_term!((and a b) = term)?;
+            Some(build_term!(pool, (and {b.clone()} {a.clone()})))
+        });
+        assert!(database.contains("and_comm"));
+
+        let sequence = Rewriter::new(&database)
+            .find_sequence(&mut pool, &term, &target)
+            .unwrap();
+        assert_eq!(sequence.len(), 1);
+        assert_eq!(sequence[0].rule, "and_comm");
+    }
+
+    #[test]
+    fn test_rare_rules() {
+        let mut pool = PrimitivePool::new();
+        let [term, target] = parse_terms(
+            &mut pool,
+            "(declare-const p Bool)",
+            ["(not (= p p))", "false"],
+        );
+        let rare = RuleDatabase::parse_rare(
+            "(define-rule bool-eq-refl ((t Bool)) (= t t) true)".as_bytes(),
+        )
+        .unwrap();
+        assert!(rare.get_rare("bool-eq-refl").is_some());
+
+        let mut database = RuleDatabase::simplification();
+        database.extend(&rare);
+        let sequence = Rewriter::new(&database)
+            .only(&["bool-eq-refl", "not_simplify"])
+            .find_sequence(&mut pool, &term, &target)
+            .unwrap();
+        let rules: Vec<_> = sequence.iter().map(|s| s.rule.as_str()).collect();
+        assert_eq!(rules, ["bool-eq-refl", "not_simplify"]);
+    }
+}
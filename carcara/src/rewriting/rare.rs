@@ -0,0 +1,750 @@
+//! Rewrite rules written in RARE, the language cvc5 uses to describe its rewrites.
+//!
+//! A rule file is a sequence of rule definitions, of the forms:
+//!
+//! ```text
+//! (define-rule <name> (<param>*) [<defs>] <lhs> <rhs>)
+//! (define-rule* <name> (<param>*) [<defs>] <lhs> <rhs> [<context>])
+//! (define-cond-rule <name> (<param>*) [<defs>] <condition> <lhs> <rhs>)
+//! ```
+//!
+//! where each parameter is of the form `(<symbol> <sort> [:list])`, and `<defs>` is of the form
+//! `(def (<symbol> <term>)*)`. Sort symbols that start with `?` are polymorphic: `?` stands for any
+//! sort, and, for example, `?BitVec` stands for any bit-vector sort.
+//!
+//! A rule can be used in two ways: it can be instantiated with explicit values for its parameters,
+//! as is done when checking `rare_rewrite` steps, or it can be applied to a term by matching the
+//! term against its left-hand side, as is done by a [`Rewriter`](super::Rewriter).
+
+use crate::{
+    ast::*,
+    checker::error::RareError,
+    eval::{evaluate, Value},
+    parser::{Lexer, ParserError, Reserved, Token},
+    CarcaraResult, Error,
+};
+use rug::Integer;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    io::BufRead,
+    str::FromStr,
+};
+
+/// An S-expression, as read from a rule file.
+enum SExpr {
+    Atom(Token),
+    List(Vec<SExpr>),
+}
+
+/// The left or right-hand side of a rule, its condition, a local definition, or the sort of a
+/// parameter. Patterns are not sorted, and their symbols are only resolved when the rule is
+/// instantiated.
+#[derive(Debug, Clone, PartialEq)]
+enum Pattern {
+    Symbol(String),
+    Constant(Constant),
+    Indexed(String, Vec<Pattern>),
+    App(Box<Pattern>, Vec<Pattern>),
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_list(f: &mut fmt::Formatter, list: &[Pattern]) -> fmt::Result {
+            for p in list {
+                write!(f, " {}", p)?;
+            }
+            write!(f, ")")
+        }
+
+        match self {
+            Pattern::Symbol(s) => write!(f, "{}", s),
+            Pattern::Constant(c) => write!(f, "{}", c),
+            Pattern::Indexed(op, indices) => {
+                write!(f, "(_ {}", op)?;
+                write_list(f, indices)
+            }
+            Pattern::App(head, args) => {
+                write!(f, "({}", head)?;
+                write_list(f, args)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RareParam {
+    name: String,
+    sort: Pattern,
+    is_list: bool,
+}
+
+impl RareParam {
+    /// Returns `true` if `term` has the sort declared for this parameter.
+    fn accepts(&self, term: &Rc<Term>, pool: &mut dyn TermPool) -> bool {
+        sort_matches(&self.sort, pool.sort(term).as_sort().unwrap())
+    }
+}
+
+/// A RARE rewrite rule.
+#[derive(Debug, Clone)]
+pub struct RareRule {
+    params: Vec<RareParam>,
+    definitions: Vec<(String, Pattern)>,
+    condition: Option<Pattern>,
+    lhs: Pattern,
+    rhs: Pattern,
+}
+
+impl RareRule {
+    /// Returns the number of parameters of the rule.
+    pub fn num_params(&self) -> usize {
+        self.params.len()
+    }
+
+    fn param(&self, name: &str) -> Option<&RareParam> {
+        self.params.iter().find(|p| p.name == name)
+    }
+
+    /// Instantiates the rule with the given values for its parameters, in order, and returns its
+    /// left and right-hand sides. List parameters are given as `rare-list` terms. This fails if a
+    /// value does not have the sort declared for its parameter, or if the condition of the rule is
+    /// not satisfied.
+    pub fn instantiate(
+        &self,
+        args: &[Rc<Term>],
+        pool: &mut dyn TermPool,
+    ) -> Result<(Rc<Term>, Rc<Term>), RareError> {
+        let mut bindings = HashMap::new();
+        for (param, arg) in self.params.iter().zip(args) {
+            let value = match arg.as_ref() {
+                Term::Op(Operator::RareList, elems) if param.is_list => {
+                    Binding::List(elems.clone())
+                }
+                _ if param.is_list => Binding::List(vec![arg.clone()]),
+                _ => Binding::Single(arg.clone()),
+            };
+            let elems = match &value {
+                Binding::Single(t) => std::slice::from_ref(t),
+                Binding::List(list) => list.as_slice(),
+            };
+            if let Some(wrong) = elems.iter().find(|t| !param.accepts(t, pool)) {
+                return Err(RareError::WrongSort(
+                    param.name.clone(),
+                    param.sort.to_string(),
+                    pool.sort(wrong),
+                ));
+            }
+            bindings.insert(param.name.as_str(), value);
+        }
+        let mut instantiator = Instantiator { pool, bindings };
+        self.define(&mut instantiator)?;
+        let lhs = instantiator.term(&self.lhs)?;
+        let rhs = instantiator.term(&self.rhs)?;
+        Ok((lhs, rhs))
+    }
+
+    /// Applies the rule to the root of `term`, by matching it against the left-hand side of the
+    /// rule. Returns `None` if the term does not match, or if the condition of the rule is not
+    /// satisfied. Since list parameters may match any number of arguments, rules that have them
+    /// are never applied this way, and can only be instantiated explicitly.
+    pub fn apply(&self, term: &Rc<Term>, pool: &mut dyn TermPool) -> Option<Rc<Term>> {
+        if self.params.iter().any(|p| p.is_list) {
+            return None;
+        }
+        let mut matcher = Matcher {
+            rule: self,
+            bindings: HashMap::new(),
+            deferred: Vec::new(),
+        };
+        if !matcher.term(&self.lhs, term, pool) || matcher.bindings.len() != self.params.len() {
+            return None;
+        }
+
+        let mut instantiator = Instantiator { pool, bindings: matcher.bindings };
+        self.define(&mut instantiator).ok()?;
+        for (pattern, expected, is_index) in matcher.deferred {
+            let actual = if is_index {
+                instantiator.index(pattern)
+            } else {
+                instantiator.term(pattern)
+            };
+            if actual.ok()? != expected {
+                return None;
+            }
+        }
+        instantiator.term(&self.rhs).ok()
+    }
+
+    /// Binds the local definitions of the rule, and checks its condition, if it has one.
+    fn define<'a>(&'a self, instantiator: &mut Instantiator<'a>) -> Result<(), RareError> {
+        for (name, value) in &self.definitions {
+            let value = instantiator.term(value)?;
+            instantiator.bindings.insert(name, Binding::Single(value));
+        }
+        if let Some(condition) = &self.condition {
+            let condition = instantiator.term(condition)?;
+            if !matches!(
+                evaluate(&condition, instantiator.pool),
+                Ok(Value::Bool(true))
+            ) {
+                return Err(RareError::ConditionNotSatisfied(condition));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if `sort` is an instance of the sort `declared` for a parameter. Indices of
+/// indexed sorts that are not numerals are not checked.
+fn sort_matches(declared: &Pattern, sort: &Sort) -> bool {
+    fn matches_term(declared: &Pattern, sort: &Rc<Term>) -> bool {
+        sort.as_sort().is_some_and(|s| sort_matches(declared, s))
+    }
+    fn matches_index(declared: &Pattern, index: &Integer) -> bool {
+        match declared {
+            Pattern::Constant(Constant::Integer(i)) => i == index,
+            Pattern::Symbol(_) => true,
+            _ => false,
+        }
+    }
+
+    match (declared, sort) {
+        (Pattern::Symbol(s), _) if s.starts_with('?') => {
+            let family = &s[1..];
+            family.is_empty()
+                || match sort {
+                    Sort::Array(..) => family == "Array",
+                    Sort::Set(_) => family == "Set",
+                    Sort::Seq(_) => family == "Seq",
+                    Sort::BitVec(_) => family == "BitVec",
+                    Sort::FloatingPoint(..) => family == "FloatingPoint",
+                    Sort::FiniteField(_) => family == "FiniteField",
+                    _ => false,
+                }
+        }
+        (Pattern::Symbol(s), _) => sort.to_string() == *s,
+        (Pattern::Indexed(name, indices), _) => match (name.as_str(), indices.as_slice(), sort) {
+            ("BitVec", [w], Sort::BitVec(width)) => matches_index(w, width),
+            ("FiniteField", [p], Sort::FiniteField(order)) => matches_index(p, order),
+            ("FloatingPoint", [e, s], Sort::FloatingPoint(exp, sig)) => {
+                matches_index(e, exp) && matches_index(s, sig)
+            }
+            _ => false,
+        },
+        (Pattern::App(head, args), _) => {
+            let Pattern::Symbol(head) = head.as_ref() else {
+                return false;
+            };
+            let sorts: Vec<_> = match (head.as_str(), sort) {
+                ("Array", Sort::Array(x, y)) => vec![x, y],
+                ("Set", Sort::Set(x)) | ("Seq", Sort::Seq(x)) => vec![x],
+                ("->", Sort::Function(sorts)) => sorts.iter().collect(),
+                (head, Sort::Atom(name, sorts)) if head == name => sorts.iter().collect(),
+                _ => return false,
+            };
+            args.len() == sorts.len() && args.iter().zip(sorts).all(|(a, s)| matches_term(a, s))
+        }
+        (Pattern::Constant(_), _) => false,
+    }
+}
+
+/// Parses a RARE rule file, returning the rules it defines, in order.
+pub(super) fn parse_rules<R: BufRead>(input: R) -> CarcaraResult<Vec<(String, RareRule)>> {
+    let mut lexer = Lexer::new(input)?;
+    let mut result = Vec::new();
+    let mut seen = HashSet::new();
+    loop {
+        let (token, position) = lexer.next_token()?;
+        match token {
+            Token::Eof => return Ok(result),
+            Token::OpenParen => {
+                let command = read_list(&mut lexer)?;
+                let (name, rule) = parse_rule(command).map_err(|e| Error::Parser(e, position))?;
+                if !seen.insert(name.clone()) {
+                    return Err(Error::Parser(ParserError::RepeatedRareRule(name), position));
+                }
+                result.push((name, rule));
+            }
+            other => return Err(Error::Parser(ParserError::UnexpectedToken(other), position)),
+        }
+    }
+}
+
+/// Reads the remaining elements of a list whose opening parenthesis was already consumed.
+fn read_list<R: BufRead>(lexer: &mut Lexer<R>) -> CarcaraResult<Vec<SExpr>> {
+    let mut result = Vec::new();
+    loop {
+        let (token, position) = lexer.next_token()?;
+        match token {
+            Token::CloseParen => return Ok(result),
+            Token::OpenParen => result.push(SExpr::List(read_list(lexer)?)),
+            Token::Eof => {
+                return Err(Error::Parser(
+                    ParserError::UnexpectedToken(Token::Eof),
+                    position,
+                ))
+            }
+            other => result.push(SExpr::Atom(other)),
+        }
+    }
+}
+
+fn unexpected(sexpr: SExpr) -> ParserError {
+    let token = match sexpr {
+        SExpr::Atom(t) => t,
+        SExpr::List(_) => Token::OpenParen,
+    };
+    ParserError::UnexpectedToken(token)
+}
+
+fn parse_symbol(sexpr: SExpr) -> Result<String, ParserError> {
+    match sexpr {
+        SExpr::Atom(Token::Symbol(s)) => Ok(s),
+        other => Err(unexpected(other)),
+    }
+}
+
+fn parse_rule(command: Vec<SExpr>) -> Result<(String, RareRule), ParserError> {
+    let mut command = command.into_iter();
+    let kind = parse_symbol(command.next().ok_or(ParserError::EmptySequence)?)?;
+    let name = parse_symbol(command.next().ok_or(ParserError::EmptySequence)?)?;
+    let params = match command.next().ok_or(ParserError::EmptySequence)? {
+        SExpr::List(list) => list
+            .into_iter()
+            .map(parse_param)
+            .collect::<Result<_, _>>()?,
+        other @ SExpr::Atom(_) => return Err(unexpected(other)),
+    };
+
+    let mut rest: Vec<_> = command.collect();
+    let definitions = match rest.first() {
+        Some(SExpr::List(list)) if matches!(list.first(), Some(SExpr::Atom(Token::Symbol(s))) if s == "def") =>
+        {
+            let SExpr::List(list) = rest.remove(0) else {
+                unreachable!()
+            };
+            list.into_iter()
+                .skip(1)
+                .map(parse_definition)
+                .collect::<Result<_, _>>()?
+        }
+        _ => Vec::new(),
+    };
+
+    // The optional context of a `define-rule*` rule only describes how the rule is applied
+    // repeatedly, and is irrelevant when checking a single application
+    let num_patterns = match kind.as_str() {
+        "define-rule" => 2,
+        "define-rule*" => rest.len().clamp(2, 3),
+        "define-cond-rule" => 3,
+        _ => return Err(ParserError::UnexpectedToken(Token::Symbol(kind))),
+    };
+    if rest.len() != num_patterns {
+        return Err(ParserError::WrongNumberOfArgs(
+            num_patterns.into(),
+            rest.len(),
+        ));
+    }
+    let mut patterns = rest.into_iter().map(parse_pattern);
+    let mut next = || patterns.next().unwrap();
+    let condition = if kind == "define-cond-rule" {
+        Some(next()?)
+    } else {
+        None
+    };
+    let lhs = next()?;
+    let rhs = next()?;
+
+    let rule = RareRule {
+        params,
+        definitions,
+        condition,
+        lhs,
+        rhs,
+    };
+    Ok((name, rule))
+}
+
+fn parse_param(sexpr: SExpr) -> Result<RareParam, ParserError> {
+    let SExpr::List(list) = sexpr else {
+        return Err(unexpected(sexpr));
+    };
+    let mut list = list.into_iter();
+    let name = parse_symbol(list.next().ok_or(ParserError::EmptySequence)?)?;
+    let sort = parse_pattern(list.next().ok_or(ParserError::EmptySequence)?)?;
+    let is_list = match list.next() {
+        None => false,
+        Some(SExpr::Atom(Token::Keyword(k))) if k == "list" => true,
+        Some(other) => return Err(unexpected(other)),
+    };
+    if let Some(other) = list.next() {
+        return Err(unexpected(other));
+    }
+    Ok(RareParam { name, sort, is_list })
+}
+
+fn parse_definition(sexpr: SExpr) -> Result<(String, Pattern), ParserError> {
+    match sexpr {
+        SExpr::List(list) if list.len() == 2 => {
+            let mut list = list.into_iter();
+            let name = parse_symbol(list.next().unwrap())?;
+            let value = parse_pattern(list.next().unwrap())?;
+            Ok((name, value))
+        }
+        other => Err(unexpected(other)),
+    }
+}
+
+fn parse_pattern(sexpr: SExpr) -> Result<Pattern, ParserError> {
+    let list = match sexpr {
+        SExpr::Atom(token) => {
+            let constant = match token {
+                Token::Symbol(s) => return Ok(Pattern::Symbol(s)),
+                Token::Numeral(n) => Constant::Integer(n),
+                Token::Decimal(r) => Constant::Real(r),
+                Token::String(s) => Constant::String(s),
+                Token::Bitvector { value, width } => Constant::BitVec(value, width.into()),
+                Token::FiniteField { value, order } => {
+                    Constant::FiniteField(value.modulo(&order), order)
+                }
+                other => return Err(ParserError::UnexpectedToken(other)),
+            };
+            return Ok(Pattern::Constant(constant));
+        }
+        SExpr::List(list) => list,
+    };
+    let mut list = list.into_iter();
+    let head = list.next().ok_or(ParserError::EmptySequence)?;
+    if matches!(head, SExpr::Atom(Token::ReservedWord(Reserved::Underscore))) {
+        let op = parse_symbol(list.next().ok_or(ParserError::EmptySequence)?)?;
+        let indices = list.map(parse_pattern).collect::<Result<_, _>>()?;
+        return Ok(Pattern::Indexed(op, indices));
+    }
+    let head = parse_pattern(head)?;
+    let args: Vec<_> = list.map(parse_pattern).collect::<Result<_, _>>()?;
+    if args.is_empty() {
+        return Err(ParserError::EmptySequence);
+    }
+    Ok(Pattern::App(Box::new(head), args))
+}
+
+enum Binding {
+    Single(Rc<Term>),
+    List(Vec<Rc<Term>>),
+}
+
+/// Builds the terms of a rule, given the values of its parameters.
+struct Instantiator<'a> {
+    pool: &'a mut dyn TermPool,
+    bindings: HashMap<&'a str, Binding>,
+}
+
+impl<'a> Instantiator<'a> {
+    fn term(&mut self, pattern: &Pattern) -> Result<Rc<Term>, RareError> {
+        match pattern {
+            Pattern::Symbol(s) => match self.bindings.get(s.as_str()) {
+                Some(Binding::Single(t)) => Ok(t.clone()),
+                Some(Binding::List(list)) if list.len() == 1 => Ok(list[0].clone()),
+                Some(Binding::List(_)) => Err(RareError::ListNotInApplication(s.clone())),
+                None => match Operator::from_str(s) {
+                    Ok(op) => Ok(self.pool.add(Term::Op(op, Vec::new()))),
+                    Err(_) => Err(RareError::UnknownSymbol(s.clone())),
+                },
+            },
+            Pattern::Constant(c) => Ok(self.pool.add(Term::Const(c.clone()))),
+            Pattern::Indexed(..) => Err(RareError::UnknownSymbol(pattern.to_string())),
+            Pattern::App(head, args) => {
+                let args = self.args(args)?;
+                self.application(head, args)
+            }
+        }
+    }
+
+    /// Instantiates an index of an indexed operator. Indices may be given by arbitrary terms in the
+    /// rule, but must evaluate to constants.
+    fn index(&mut self, pattern: &Pattern) -> Result<Rc<Term>, RareError> {
+        let term = self.term(pattern)?;
+        if term.is_const() {
+            return Ok(term);
+        }
+        match evaluate(&term, self.pool) {
+            Ok(value @ Value::Int(_)) => Ok(value.to_term(self.pool)),
+            _ => Err(RareError::InvalidIndex(term)),
+        }
+    }
+
+    /// Instantiates the arguments of an application, expanding list parameters in place.
+    fn args(&mut self, patterns: &[Pattern]) -> Result<Vec<Rc<Term>>, RareError> {
+        let mut result = Vec::with_capacity(patterns.len());
+        for p in patterns {
+            match p {
+                Pattern::Symbol(s) => match self.bindings.get(s.as_str()) {
+                    Some(Binding::List(list)) => result.extend(list.iter().cloned()),
+                    _ => result.push(self.term(p)?),
+                },
+                _ => result.push(self.term(p)?),
+            }
+        }
+        Ok(result)
+    }
+
+    fn application(&mut self, head: &Pattern, args: Vec<Rc<Term>>) -> Result<Rc<Term>, RareError> {
+        match head {
+            Pattern::Symbol(s) if !self.bindings.contains_key(s.as_str()) => {
+                let op = Operator::from_str(s).map_err(|_| RareError::UnknownSymbol(s.clone()))?;
+                self.operator(op, args)
+            }
+            Pattern::Indexed(op, indices) => {
+                let op = ParamOperator::from_str(op)
+                    .map_err(|_| RareError::UnknownSymbol(head.to_string()))?;
+                let op_args = indices
+                    .iter()
+                    .map(|i| self.index(i))
+                    .collect::<Result<_, _>>()?;
+                Ok(self.pool.add(Term::ParamOp { op, op_args, args }))
+            }
+            _ => {
+                let function = self.term(head)?;
+                Ok(self.pool.add(Term::App(function, args)))
+            }
+        }
+    }
+
+    /// Builds an operator application. Since list parameters may be instantiated with any number
+    /// of terms, associative operators applied to a single argument are reduced to that argument,
+    /// and, when they have a neutral element, applications to no arguments are reduced to it.
+    fn operator(&mut self, op: Operator, mut args: Vec<Rc<Term>>) -> Result<Rc<Term>, RareError> {
+        let is_associative = matches!(
+            op,
+            Operator::And
+                | Operator::Or
+                | Operator::Add
+                | Operator::Mult
+                | Operator::StrConcat
+                | Operator::SeqConcat
+                | Operator::ReConcat
+                | Operator::ReUnion
+                | Operator::ReIntersection
+                | Operator::BvAnd
+                | Operator::BvOr
+                | Operator::BvXor
+                | Operator::BvAdd
+                | Operator::BvMul
+                | Operator::BvConcat
+        );
+        if !is_associative {
+            return Ok(self.pool.add(Term::Op(op, args)));
+        }
+        match args.len() {
+            0 => match op {
+                Operator::And => Ok(self.pool.bool_true()),
+                Operator::Or => Ok(self.pool.bool_false()),
+                Operator::StrConcat => Ok(self.pool.add(Term::new_string(""))),
+                _ => Err(RareError::EmptyApplication(op)),
+            },
+            1 => Ok(args.pop().unwrap()),
+            _ => Ok(self.pool.add(Term::Op(op, args))),
+        }
+    }
+}
+
+/// Matches a term against the left-hand side of a rule, binding its parameters.
+struct Matcher<'r> {
+    rule: &'r RareRule,
+    bindings: HashMap<&'r str, Binding>,
+
+    /// The subpatterns that don't bind any parameters, like constants or references to local
+    /// definitions, together with the subterms they were matched against, and whether they are
+    /// indices of an indexed operator. These are only compared once the rule is instantiated.
+    deferred: Vec<(&'r Pattern, Rc<Term>, bool)>,
+}
+
+impl<'r> Matcher<'r> {
+    fn term(&mut self, pattern: &'r Pattern, term: &Rc<Term>, pool: &mut dyn TermPool) -> bool {
+        match pattern {
+            Pattern::Symbol(s) if self.rule.param(s).is_some() => self.bind(s, term, pool),
+            Pattern::Symbol(_) | Pattern::Constant(_) | Pattern::Indexed(..) => {
+                self.deferred.push((pattern, term.clone(), false));
+                true
+            }
+            Pattern::App(head, args) => {
+                let (head_matches, term_args) = match (head.as_ref(), term.as_ref()) {
+                    (Pattern::Symbol(s), Term::Op(op, term_args))
+                        if self.rule.param(s).is_none() =>
+                    {
+                        (Operator::from_str(s).ok() == Some(*op), term_args)
+                    }
+                    (Pattern::Indexed(name, indices), Term::ParamOp { op, op_args, args }) => {
+                        let matches = ParamOperator::from_str(name).ok() == Some(*op)
+                            && indices.len() == op_args.len()
+                            && indices
+                                .iter()
+                                .zip(op_args)
+                                .all(|(i, a)| self.index(i, a, pool));
+                        (matches, args)
+                    }
+                    (_, Term::App(f, term_args)) => (self.term(head, f, pool), term_args),
+                    _ => return false,
+                };
+                head_matches
+                    && args.len() == term_args.len()
+                    && args
+                        .iter()
+                        .zip(term_args)
+                        .all(|(p, t)| self.term(p, t, pool))
+            }
+        }
+    }
+
+    fn index(&mut self, pattern: &'r Pattern, index: &Rc<Term>, pool: &mut dyn TermPool) -> bool {
+        match pattern {
+            Pattern::Symbol(s) if self.rule.param(s).is_some() => self.bind(s, index, pool),
+            _ => {
+                self.deferred.push((pattern, index.clone(), true));
+                true
+            }
+        }
+    }
+
+    fn bind(&mut self, name: &'r str, term: &Rc<Term>, pool: &mut dyn TermPool) -> bool {
+        match self.bindings.get(name) {
+            Some(Binding::Single(bound)) => bound == term,
+            Some(Binding::List(_)) => false,
+            None => {
+                let param = self.rule.param(name).unwrap();
+                if !param.accepts(term, pool) {
+                    return false;
+                }
+                self.bindings.insert(name, Binding::Single(term.clone()));
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::tests::parse_terms;
+
+    const RULES: &str = "
+        (define-rule bool-double-not-elim ((t Bool)) (not (not t)) t)
+        (define-rule bool-and-true ((xs Bool :list) (ys Bool :list))
+            (and xs true ys)
+            (and xs ys))
+        (define-cond-rule arith-div-by-one ((t Int) (c Int)) (= c 1) (div t c) t)
+        (define-rule bv-extract-whole ((x ?BitVec) (n Int))
+            (def (m (- n 1)))
+            ((_ extract m 0) x)
+            x)
+        (define-rule arith-int-lt-to-le ((x Int) (y Int)) (< x y) (<= (+ x 1) y))
+        (define-rule uf-apply ((f (-> Int Int)) (x Int)) (f x) (f x))
+        (define-rule arith-plus-zero ((t Int)) (+ t 0) t)
+    ";
+
+    fn rules() -> HashMap<String, RareRule> {
+        parse_rules(RULES.as_bytes()).unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn parse() {
+        let rules = rules();
+        assert_eq!(rules.len(), 7);
+
+        let rule = &rules["bv-extract-whole"];
+        assert_eq!(rule.definitions.len(), 1);
+        assert_eq!(rule.lhs.to_string(), "((_ extract m 0) x)");
+        assert_eq!(rule.params[0].sort.to_string(), "?BitVec");
+        assert!(rules["bool-and-true"].params[0].is_list);
+        assert_eq!(rules["uf-apply"].params[0].sort.to_string(), "(-> Int Int)");
+
+        let invalid = [
+            "(define-rule r ((x Bool)) x)",
+            "(define-rule r ((x Bool)) x x x)",
+            "(define-cond-rule r ((x Bool)) x x)",
+            "(define-rule r ((x Bool :foo)) x x)",
+            "(define-rule r ((x)) x x)",
+            "(define-rule r ((x Bool)) x x) (define-rule r ((x Bool)) x x)",
+            "(define-lemma r ((x Bool)) x x)",
+            "(define-rule r ((x Bool)) x x",
+        ];
+        for input in invalid {
+            assert!(parse_rules(input.as_bytes()).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn parameter_sorts() {
+        let mut pool = PrimitivePool::new();
+        let [a, b, x, y, bv, f] = parse_terms(
+            &mut pool,
+            "(declare-const a Int) (declare-const b Int)
+            (declare-const x Real) (declare-const y Real)
+            (declare-const bv (_ BitVec 4)) (declare-fun f (Int) Int)",
+            ["a", "b", "x", "y", "bv", "f"],
+        );
+        let rules = rules();
+        let lt_to_le = &rules["arith-int-lt-to-le"];
+        assert!(lt_to_le
+            .instantiate(&[a.clone(), b.clone()], &mut pool)
+            .is_ok());
+        assert!(matches!(
+            lt_to_le.instantiate(&[x.clone(), y.clone()], &mut pool),
+            Err(RareError::WrongSort(..))
+        ));
+
+        let four = pool.add(Term::new_int(4));
+        let extract = &rules["bv-extract-whole"];
+        assert!(extract.instantiate(&[bv, four.clone()], &mut pool).is_ok());
+        assert!(extract.instantiate(&[a.clone(), four], &mut pool).is_err());
+
+        let apply = &rules["uf-apply"];
+        assert!(apply.instantiate(&[f, a.clone()], &mut pool).is_ok());
+        assert!(apply.instantiate(&[a, b], &mut pool).is_err());
+    }
+
+    #[test]
+    fn apply() {
+        let mut pool = PrimitivePool::new();
+        let [int_lt, int_le, real_lt, plus_zero, plus_one, extract, div, a, div_two] = parse_terms(
+            &mut pool,
+            "(declare-const a Int) (declare-const b Int)
+            (declare-const x Real) (declare-const y Real)
+            (declare-const bv (_ BitVec 4))",
+            [
+                "(< a b)",
+                "(<= (+ a 1) b)",
+                "(< x y)",
+                "(+ a 0)",
+                "(+ a 1)",
+                "((_ extract 3 0) bv)",
+                "(div a 1)",
+                "a",
+                "(div a 2)",
+            ],
+        );
+        let rules = rules();
+        let apply =
+            |name: &str, term: &Rc<Term>, pool: &mut PrimitivePool| rules[name].apply(term, pool);
+
+        assert_eq!(
+            apply("arith-int-lt-to-le", &int_lt, &mut pool),
+            Some(int_le)
+        );
+        assert_eq!(apply("arith-int-lt-to-le", &real_lt, &mut pool), None);
+
+        assert_eq!(
+            apply("arith-plus-zero", &plus_zero, &mut pool),
+            Some(a.clone())
+        );
+        assert_eq!(apply("arith-plus-zero", &plus_one, &mut pool), None);
+
+        // `n` only occurs in the local definitions, so it can't be bound by matching
+        assert_eq!(apply("bv-extract-whole", &extract, &mut pool), None);
+
+        assert_eq!(apply("arith-div-by-one", &div, &mut pool), Some(a));
+        assert_eq!(apply("arith-div-by-one", &div_two, &mut pool), None);
+        assert_eq!(apply("bool-and-true", &int_lt, &mut pool), None);
+    }
+}
@@ -0,0 +1,306 @@
+//! Automatic minimization of proofs that Carcara rejects.
+//!
+//! Given a proof that fails to check (or that makes the checker panic), the shrinker repeatedly
+//! tries to replace steps and subproofs with `hole` steps that have the same conclusion, and
+//! removes any commands that are no longer needed, keeping only the changes that preserve the
+//! original failure. Commands inside subproofs are also shrunk, except for the last step of each
+//! subproof, which is needed to close it. The result is a small proof that reproduces the same
+//! problem, which is useful when reporting bugs.
+
+use crate::{ast::*, checker, CheckerError, Error};
+use std::{mem, panic};
+
+/// The observed failure when checking a proof. Two failures are considered the same if they have
+/// the same kind and, for checker errors, happen in the same step, with the same rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Failure {
+    Checker {
+        kind: mem::Discriminant<CheckerError>,
+        rule: String,
+        step: String,
+    },
+    Parser,
+    Io,
+    DoesNotReachEmptyClause,
+    Panic,
+}
+
+fn run_checker(
+    pool: &mut PrimitivePool,
+    config: &checker::Config,
+    problem: &Problem,
+    proof: &Proof,
+) -> Option<Failure> {
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        checker::ProofChecker::new(pool, config.clone()).check(problem, proof)
+    }));
+    match result {
+        Ok(Ok(_)) => None,
+        Ok(Err(Error::Checker { inner, rule, step })) => Some(Failure::Checker {
+            kind: mem::discriminant(&inner),
+            rule,
+            step,
+        }),
+        Ok(Err(Error::Parser(..))) => Some(Failure::Parser),
+        Ok(Err(Error::Io(_))) => Some(Failure::Io),
+        Ok(Err(
+            Error::DoesNotReachEmptyClause
+            | Error::FinalStepIsNotEmptyClause(_)
+            | Error::FinalStepIsNotGoal { .. },
+        )) => Some(Failure::DoesNotReachEmptyClause),
+        Ok(Err(Error::ElaborationGrowth { .. } | Error::ElaborationUnsound { .. })) => {
+            unreachable!("the checker doesn't elaborate")
+        }
+        Ok(Err(Error::StepNotFound(_))) => {
+            unreachable!("the checker only rechecks steps on demand")
+        }
+        Err(_) => Some(Failure::Panic),
+    }
+}
+
+/// Shrinks a proof that fails to check, while preserving the failure.
+///
+/// Returns `None` if the proof is checked successfully, in which case there is nothing to shrink.
+/// If the checker panics, the panic is caught and treated as a failure like any other. Note that
+/// the default panic hook will still print a message for each caught panic, so callers may want to
+/// replace it while shrinking.
+pub fn shrink_proof(
+    pool: &mut PrimitivePool,
+    config: &checker::Config,
+    problem: &Problem,
+    proof: &Proof,
+) -> Option<Proof> {
+    let failure = run_checker(pool, config, problem, proof)?;
+
+    // If the failure happened in a specific step, we only need to keep the top-level command that
+    // contains that step, and its transitive premises. Otherwise, we keep the last command in the
+    // proof, which is the one that should conclude the empty clause.
+    let root_id = match &failure {
+        Failure::Checker { step, .. } => proof
+            .commands
+            .iter()
+            .find(|c| contains_id(c, step))
+            .map(|c| c.id().to_owned()),
+        _ => None,
+    }
+    .or_else(|| proof.commands.last().map(|c| c.id().to_owned()))?;
+
+    let mut current = slice(proof.clone(), &root_id);
+    if run_checker(pool, config, problem, &current).as_ref() != Some(&failure) {
+        current = proof.clone();
+    }
+
+    let mut chunk_size = candidates(&current, &root_id).len().max(1);
+    loop {
+        let mut changed = false;
+        let mut start = 0;
+        loop {
+            let candidates = candidates(&current, &root_id);
+            if start >= candidates.len() {
+                break;
+            }
+            let end = (start + chunk_size).min(candidates.len());
+            let attempt = slice(
+                replace_with_holes(&current, &candidates[start..end]),
+                &root_id,
+            );
+            if run_checker(pool, config, problem, &attempt).as_ref() == Some(&failure) {
+                // The replaced commands are no longer candidates, so we don't advance `start`
+                current = attempt;
+                changed = true;
+            } else {
+                start = end;
+            }
+        }
+        if !changed {
+            if chunk_size == 1 {
+                break;
+            }
+            chunk_size /= 2;
+        }
+    }
+    Some(current)
+}
+
+/// Returns `true` if `command` is, or contains, a command with the given id.
+fn contains_id(command: &ProofCommand, id: &str) -> bool {
+    match command {
+        ProofCommand::Subproof(s) => s.commands.iter().any(|c| contains_id(c, id)),
+        _ => command.id() == id,
+    }
+}
+
+/// Returns the paths of the commands that can still be replaced by a `hole` step. Each path is the
+/// sequence of indices that leads to the command, starting from the top-level commands. The
+/// top-level commands come first, so larger parts of the proof are tried first.
+fn candidates(proof: &Proof, root_id: &str) -> Vec<Vec<usize>> {
+    fn is_candidate(command: &ProofCommand) -> bool {
+        match command {
+            ProofCommand::Assume { .. } => false,
+            ProofCommand::Step(s) => !(s.rule == "hole" && s.premises.is_empty()),
+            ProofCommand::Subproof(_) => true,
+        }
+    }
+
+    fn inner_candidates(commands: &[ProofCommand], prefix: &[usize], result: &mut Vec<Vec<usize>>) {
+        for (i, command) in commands.iter().enumerate() {
+            if let ProofCommand::Subproof(s) = command {
+                let mut path = prefix.to_vec();
+                path.push(i);
+                // The last step of the subproof is not a candidate, since it closes the subproof
+                let (_, inner) = s.commands.split_last().unwrap();
+                for (j, c) in inner.iter().enumerate() {
+                    if is_candidate(c) {
+                        result.push([path.as_slice(), &[j]].concat());
+                    }
+                }
+                inner_candidates(inner, &path, result);
+            }
+        }
+    }
+
+    let mut result: Vec<_> = proof
+        .commands
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| is_candidate(c) && c.id() != root_id)
+        .map(|(i, _)| vec![i])
+        .collect();
+    inner_candidates(&proof.commands, &[], &mut result);
+    result
+}
+
+fn replace_with_holes(proof: &Proof, paths: &[Vec<usize>]) -> Proof {
+    fn get_mut<'a>(commands: &'a mut [ProofCommand], path: &[usize]) -> &'a mut ProofCommand {
+        let (last, prefix) = path.split_last().unwrap();
+        let mut commands = commands;
+        for &i in prefix {
+            let ProofCommand::Subproof(s) = &mut commands[i] else {
+                unreachable!("paths only go through subproofs")
+            };
+            commands = &mut s.commands;
+        }
+        &mut commands[*last]
+    }
+
+    // We replace the innermost commands first, so the paths of the other commands are still valid
+    let mut paths: Vec<_> = paths.iter().collect();
+    paths.sort_by_key(|p| std::cmp::Reverse(p.len()));
+
+    let mut commands = proof.commands.clone();
+    for path in paths {
+        let command = get_mut(&mut commands, path);
+        *command = ProofCommand::Step(ProofStep {
+            id: command.id().to_owned(),
+            clause: command.clause().to_vec(),
+            rule: "hole".to_owned(),
+            premises: Vec::new(),
+            args: Vec::new(),
+            arg_vars: Vec::new(),
+            discharge: Vec::new(),
+            origin: None,
+            attributes: Vec::new(),
+        });
+    }
+    Proof { commands, ..proof.clone() }
+}
+
+/// Removes all commands that are not transitive premises of the command with id `root_id`.
+fn slice(proof: Proof, root_id: &str) -> Proof {
+    match ProofNode::from_commands_with_root_id(proof.commands.clone(), root_id) {
+        Some(node) => Proof {
+            commands: node.into_commands(),
+            ..proof
+        },
+        None => proof,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn test_shrink_proof() {
+        let problem: &[u8] = b"
+            (declare-const a Bool)
+            (declare-const b Bool)
+            (assert a)
+            (assert (not a))
+            (assert b)
+        ";
+        let proof: &[u8] = b"
+            (assume h1 a)
+            (assume h2 (not a))
+            (assume h3 b)
+            (step t1 (cl (or a (not a))) :rule hole)
+            (step t2 (cl a (not a)) :rule or :premises (t1))
+            (step t3 (cl (not b) b) :rule hole)
+            (step t4 (cl b) :rule resolution :premises (t3 h3))
+            (step t5 (cl (not b)) :rule resolution :premises (t2 h1 t4))
+            (step t6 (cl) :rule resolution :premises (h1 h2))
+        ";
+        let (problem, proof, mut pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let config = checker::Config::new();
+
+        let shrunk = shrink_proof(&mut pool, &config, &problem, &proof).unwrap();
+        let ids: Vec<_> = shrunk.commands.iter().map(ProofCommand::id).collect();
+        assert_eq!(ids, ["t2", "h1", "t4", "t5"]);
+        assert!(matches!(
+            &shrunk.commands[0],
+            ProofCommand::Step(s) if s.rule == "hole" && s.premises.is_empty()
+        ));
+
+        // A valid proof can't be shrunk
+        let sliced = slice(proof, "t6");
+        assert!(shrink_proof(&mut pool, &config, &problem, &sliced).is_none());
+    }
+
+    #[test]
+    fn test_shrink_subproof() {
+        let problem: &[u8] = b"
+            (declare-const a Bool)
+            (declare-const b Bool)
+            (declare-fun f (Bool) Bool)
+        ";
+        let proof: &[u8] = b"
+            (anchor :step t1)
+            (assume t1.h1 a)
+            (step t1.t1 (cl (or a b)) :rule hole)
+            (step t1.t2 (cl a b) :rule or :premises (t1.t1))
+            (step t1.t3 (cl (= (f a) (f b))) :rule hole)
+            (step t1.t4 (cl (= (f a) (f a))) :rule refl)
+            (step t1.t5 (cl b) :rule resolution :premises (t1.t2 t1.h1))
+            (step t1 (cl (not a) b) :rule subproof :discharge (t1.h1))
+        ";
+        let (problem, proof, mut pool) =
+            parser::parse_instance(problem, proof, parser::Config::new()).unwrap();
+        let config = checker::Config::new();
+
+        // The failure is in `t1.t5`, so the subproof can't be replaced by a hole. However, the
+        // `or` step inside it can
+        let shrunk = shrink_proof(&mut pool, &config, &problem, &proof).unwrap();
+        let [ProofCommand::Subproof(s)] = shrunk.commands.as_slice() else {
+            panic!("expected a single subproof");
+        };
+        let inner: Vec<_> = s
+            .commands
+            .iter()
+            .map(|c| match c {
+                ProofCommand::Step(s) => (c.id(), s.rule.as_str()),
+                _ => (c.id(), "assume"),
+            })
+            .collect();
+        assert_eq!(
+            inner,
+            [
+                ("t1.h1", "assume"),
+                ("t1.t2", "hole"),
+                ("t1.t5", "resolution"),
+                ("t1", "subproof"),
+            ]
+        );
+    }
+}
@@ -3,6 +3,7 @@ use indexmap::{IndexMap, IndexSet};
 use rug::Integer;
 use std::{
     borrow::Borrow,
+    collections::{BTreeMap, HashMap},
     fmt,
     hash::{Hash, Hasher},
     ops,
@@ -64,6 +65,7 @@ impl<T, I: Iterator<Item = T>> DedupIterator<T> for I {
     }
 }
 
+#[derive(Clone)]
 pub struct HashCache<T> {
     hash: u64,
     value: T,
@@ -101,7 +103,7 @@ impl<T> AsRef<T> for HashCache<T> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HashMapStack<K, V> {
     scopes: Vec<IndexMap<K, V>>,
 }
@@ -123,13 +125,12 @@ impl<K, V> HashMapStack<K, V> {
         self.scopes.push(IndexMap::new());
     }
 
-    pub fn pop_scope(&mut self) {
+    /// Removes the innermost scope, and returns its entries.
+    pub fn pop_scope(&mut self) -> IndexMap<K, V> {
         match self.scopes.len() {
             0 => unreachable!(),
             1 => panic!("trying to pop last scope in `HashMapStack`"),
-            _ => {
-                self.scopes.pop().unwrap();
-            }
+            _ => self.scopes.pop().unwrap(),
         }
     }
 }
@@ -173,6 +174,60 @@ impl<K, V> Default for HashMapStack<K, V> {
     }
 }
 
+/// A map with a bounded number of entries. When inserting into a full cache, the least recently
+/// used entry is evicted.
+#[derive(Debug, Clone)]
+pub struct LruCache<K, V> {
+    capacity: usize,
+
+    // Each entry holds its value and the "time" at which it was last used. The `recency` map holds
+    // the keys ordered by that time, so the least recently used key is always its first entry
+    entries: HashMap<K, (V, u64)>,
+    recency: BTreeMap<u64, K>,
+    clock: u64,
+}
+
+impl<K: Eq + Hash + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Returns the value associated with the key, if it exists, and marks it as the most recently
+    /// used entry.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let (value, last_used) = self.entries.get_mut(key)?;
+        let key = self.recency.remove(last_used).unwrap();
+        self.clock += 1;
+        *last_used = self.clock;
+        self.recency.insert(self.clock, key);
+        Some(value)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        if let Some((_, last_used)) = self.entries.insert(key.clone(), (value, self.clock)) {
+            self.recency.remove(&last_used);
+        } else if self.entries.len() > self.capacity {
+            let (_, evicted) = self.recency.pop_first().unwrap();
+            self.entries.remove(&evicted);
+        }
+        self.recency.insert(self.clock, key);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
 // TODO: Document this struct
 #[derive(Debug)]
 pub struct Range<T = usize>(Option<T>, Option<T>);
@@ -0,0 +1,114 @@
+//! An integration test that runs external SMT solvers on a directory of problems, and checks the
+//! Alethe proofs they produce.
+//!
+//! This test is disabled unless the `CARCARA_TEST_SOLVERS` and `CARCARA_TEST_PROBLEMS` environment
+//! variables are set. `CARCARA_TEST_SOLVERS` is a `;`-separated list of solver commands, each
+//! consisting of the solver binary followed by its arguments, for example:
+//!
+//! ```text
+//! CARCARA_TEST_SOLVERS="cvc5 --dump-proofs --proof-format-mode=alethe;veriT --proof=-"
+//! ```
+//!
+//! Each solver is called with the problem file path as its last argument, and is expected to print
+//! `unsat` followed by the proof. `CARCARA_TEST_PROBLEMS` is the directory where SMT-LIB problems
+//! are searched for, recursively. After running, the test prints how many steps used each rule,
+//! and which implemented rules were never exercised.
+
+use carcara::*;
+use std::{
+    env, fs,
+    io::BufRead,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn find_problems(dir: &Path, acc: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            find_problems(&path, acc);
+        } else if path.extension().is_some_and(|ext| ext == "smt2") {
+            acc.push(path);
+        }
+    }
+}
+
+/// Runs the solver on the problem, returning the proof it produced, or `None` if the solver did
+/// not answer `unsat`.
+fn run_solver(solver: &[&str], problem: &Path) -> Result<Option<Vec<u8>>, String> {
+    let output = Command::new(solver[0])
+        .args(&solver[1..])
+        .arg(problem)
+        .output()
+        .map_err(|e| format!("failed to run solver: {}", e))?;
+
+    let mut stdout = output.stdout.as_slice();
+    let mut first_line = String::new();
+    stdout.read_line(&mut first_line).unwrap();
+    Ok((first_line.trim_end() == "unsat").then(|| stdout.to_vec()))
+}
+
+fn check_proof(
+    problem: &Path,
+    proof: &[u8],
+    coverage: &mut checker::RuleCoverage,
+) -> CarcaraResult<()> {
+    let parser_config = parser::Config {
+        apply_function_defs: false,
+        expand_lets: true,
+        allow_int_real_subtyping: true,
+        strict: false,
+        parse_hole_args: false,
+        strings_profile: parser::StringsProfile::Cvc5,
+        alethe_version: None,
+        rename_duplicate_step_ids: false,
+        detect_mismatch: false,
+    };
+    let problem = fs::read(problem)?;
+    let (problem, proof, mut pool) =
+        parser::parse_instance(problem.as_slice(), proof, parser_config)?;
+
+    coverage.add_proof(&proof);
+    checker::ProofChecker::new(&mut pool, checker::Config::new()).check(&problem, &proof)?;
+    Ok(())
+}
+
+#[test]
+fn solver_integration() {
+    let (Ok(solvers), Ok(problems_dir)) = (
+        env::var("CARCARA_TEST_SOLVERS"),
+        env::var("CARCARA_TEST_PROBLEMS"),
+    ) else {
+        println!("CARCARA_TEST_SOLVERS or CARCARA_TEST_PROBLEMS not set, skipping");
+        return;
+    };
+
+    let mut problems = Vec::new();
+    find_problems(Path::new(&problems_dir), &mut problems);
+    problems.sort();
+
+    let mut coverage = checker::RuleCoverage::new();
+    let mut failures = Vec::new();
+    for solver in solvers.split(';').filter(|s| !s.trim().is_empty()) {
+        let solver: Vec<_> = solver.split_whitespace().collect();
+        for problem in &problems {
+            let result = run_solver(&solver, problem).and_then(|proof| match proof {
+                Some(proof) => check_proof(problem, &proof, &mut coverage)
+                    .map_err(|e| format!("checking failed: {}", e)),
+                None => Ok(()),
+            });
+            if let Err(e) = result {
+                failures.push(format!("{} on '{}': {}", solver[0], problem.display(), e));
+            }
+        }
+    }
+
+    print!("{}", coverage);
+
+    assert!(
+        failures.is_empty(),
+        "{} failures:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
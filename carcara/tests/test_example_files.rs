@@ -30,6 +30,30 @@ fn run_parallel_checker_test(
     Ok(())
 }
 
+fn assert_round_trip(
+    problem_path: &Path,
+    pool: &mut ast::PrimitivePool,
+    prelude: &ast::ProblemPrelude,
+    proof: &ast::Proof,
+) -> CarcaraResult<()> {
+    let problem = fs::read(problem_path)?;
+    for use_sharing in [false, true] {
+        let mut printed = Vec::new();
+        ast::write_proof(pool, prelude, &mut printed, proof, use_sharing)?;
+        let (_, reparsed) = parser::parse_instance_with_pool(
+            problem.as_slice(),
+            printed.as_slice(),
+            parser::Config::new(),
+            pool,
+        )?;
+        assert!(
+            proof.commands == reparsed.commands,
+            "printing and parsing the proof back did not give the same proof"
+        );
+    }
+    Ok(())
+}
+
 fn run_test(problem_path: &Path, proof_path: &Path) -> CarcaraResult<()> {
     let (problem, proof, mut pool) = parser::parse_instance(
         io::BufReader::new(fs::File::open(problem_path)?),
@@ -39,8 +63,16 @@ fn run_test(problem_path: &Path, proof_path: &Path) -> CarcaraResult<()> {
 
     let checker_config = checker::Config {
         elaborated: false,
+        strict_resolution: false,
         ignore_unknown_rules: false,
         allowed_rules: ["all_simplify".to_owned(), "rare_rewrite".to_owned()].into(),
+        trace: None,
+        term_limits: ast::TermLimits::new(),
+        duplicate_conclusion_threshold: None,
+        native_lia_generic: false,
+        expected_conclusion: checker::ExpectedConclusion::AnyEmptyClause,
+        rare_rules: None,
+        certificate_dir: None,
     };
 
     // First, we check the proof normally
@@ -48,30 +80,36 @@ fn run_test(problem_path: &Path, proof_path: &Path) -> CarcaraResult<()> {
 
     // Then we elaborate it
     let config = elaborator::Config {
-        lia_options: None,
-        hole_options: None,
         uncrowd_rotation: true,
+        ..elaborator::Config::new()
     };
     let node = ast::ProofNode::from_commands(proof.commands.clone());
     let elaborated_node = elaborator::Elaborator::new(&mut pool, &problem, config.clone())
-        .elaborate_with_default_pipeline(&node);
+        .elaborate_with_default_pipeline(&node)?;
     let elaborated = ast::Proof {
         constant_definitions: proof.constant_definitions.clone(),
         commands: elaborated_node.into_commands(),
     };
 
     // After that, we check the elaborated proof to make sure it is valid
-    checker::ProofChecker::new(&mut pool, checker_config).check(&problem, &elaborated)?;
+    checker::ProofChecker::new(&mut pool, checker_config.clone()).check(&problem, &elaborated)?;
+
+    // The elaborated proof should also be valid under the strict resolution semantics
+    let strict_config = checker_config.strict_resolution(true);
+    checker::ProofChecker::new(&mut pool, strict_config).check(&problem, &elaborated)?;
 
     // Finally, we elaborate the already elaborated proof, to make sure the elaboration step is
     // idempotent
     let elaborated_twice = elaborator::Elaborator::new(&mut pool, &problem, config)
-        .elaborate_with_default_pipeline(&elaborated_node);
+        .elaborate_with_default_pipeline(&elaborated_node)?;
     assert!(
         elaborated.commands == elaborated_twice.into_commands(),
         "elaboration was not idempotent!"
     );
 
+    // Printing the elaborated proof and parsing it back should give the same proof
+    assert_round_trip(problem_path, &mut pool, &problem.prelude, &elaborated)?;
+
     // We also test the parallel checker, with different values for the number of threads
     run_parallel_checker_test(problem_path, proof_path, 1)?;
     run_parallel_checker_test(problem_path, proof_path, 4)?;
@@ -96,7 +134,17 @@ fn test_file(proof_path: &str) {
             Error::Io(_) => "IO error".to_owned(),
             Error::Parser(_, (line, column)) => format!("parser error at {}:{}", line, column),
             Error::Checker { rule, step, .. } => format!("checker error at '{}' ({})", step, rule),
-            Error::DoesNotReachEmptyClause => format!("{}", e), // This one is already pretty short
+            // These are already pretty short
+            Error::DoesNotReachEmptyClause | Error::StepNotFound(_) => format!("{}", e),
+            Error::FinalStepIsNotEmptyClause(step) | Error::FinalStepIsNotGoal { step, .. } => {
+                format!("final step '{}' has the wrong conclusion", step)
+            }
+            Error::ElaborationGrowth { step, pass, .. } => {
+                format!("elaboration growth limit exceeded at '{}' ({})", step, pass)
+            }
+            Error::ElaborationUnsound { step, pass, .. } => {
+                format!("elaborated step '{}' is falsified ({})", step, pass)
+            }
         };
         panic!(
             "\"{}\" returned error: {}",
@@ -1,3 +1,4 @@
+use crate::cache::{describe_config, CacheKey, CachedResult, ResultCache};
 use carcara::{
     ast,
     benchmarking::{CollectResults, CsvBenchmarkResults, RunMeasurement},
@@ -13,25 +14,44 @@ use std::{
 };
 
 #[derive(Debug, Clone, Copy)]
-struct JobDescriptor<'a> {
-    problem_file: &'a Path,
-    proof_file: &'a Path,
-    run_index: usize,
+pub(crate) struct JobDescriptor<'a> {
+    pub problem_file: &'a Path,
+    pub proof_file: &'a Path,
+    pub run_index: usize,
 }
 
-fn run_job<T: CollectResults + Default + Send>(
+pub(crate) fn run_job<T: CollectResults + Default + Send>(
     results: &mut T,
     job: JobDescriptor,
     parser_config: parser::Config,
     checker_config: checker::Config,
     elaborator_config: Option<(elaborator::Config, Vec<elaborator::ElaborationStep>)>,
 ) -> Result<bool, carcara::Error> {
+    run_measured_job(
+        results,
+        job,
+        parser_config,
+        checker_config,
+        elaborator_config,
+    )
+    .map(|(is_holey, _)| is_holey)
+}
+
+/// Runs a job like [`run_job`], but also returns the measurement of the run if checking succeeded.
+fn run_measured_job<T: CollectResults + Default + Send>(
+    results: &mut T,
+    job: JobDescriptor,
+    parser_config: parser::Config,
+    checker_config: checker::Config,
+    elaborator_config: Option<(elaborator::Config, Vec<elaborator::ElaborationStep>)>,
+) -> Result<(bool, RunMeasurement), carcara::Error> {
     let proof_file_name = job.proof_file.to_str().unwrap();
     let mut checker_stats = checker::CheckerStatistics {
         file_name: proof_file_name,
         polyeq_time: Duration::ZERO,
         assume_time: Duration::ZERO,
         assume_core_time: Duration::ZERO,
+        polyeq_cache: Default::default(),
         results: std::mem::take(results),
     };
 
@@ -57,7 +77,7 @@ fn run_job<T: CollectResults + Default + Send>(
         let node = ast::ProofNode::from_commands(proof.commands);
         let (elaborated, pipeline_durations) =
             elaborator::Elaborator::new(&mut pool, &problem, config)
-                .elaborate_with_stats(&node, pipeline);
+                .elaborate_with_stats(&node, pipeline)?;
         elaborated.into_commands();
         (elaboration.elapsed(), pipeline_durations)
     } else {
@@ -66,22 +86,23 @@ fn run_job<T: CollectResults + Default + Send>(
 
     let total = total.elapsed();
 
+    let measurement = RunMeasurement {
+        parsing,
+        checking,
+        elaboration,
+        scheduling: Duration::ZERO,
+        total,
+        polyeq: checker_stats.polyeq_time,
+        assume: checker_stats.assume_time,
+        assume_core: checker_stats.assume_core_time,
+        elaboration_pipeline: pipeline_durations,
+    };
     checker_stats.results.add_run_measurement(
         &(proof_file_name.to_string(), job.run_index),
-        RunMeasurement {
-            parsing,
-            checking,
-            elaboration,
-            scheduling: Duration::ZERO,
-            total,
-            polyeq: checker_stats.polyeq_time,
-            assume: checker_stats.assume_time,
-            assume_core: checker_stats.assume_core_time,
-            elaboration_pipeline: pipeline_durations,
-        },
+        measurement.clone(),
     );
     *results = checker_stats.results;
-    checking_result
+    checking_result.map(|is_holey| (is_holey, measurement))
 }
 
 fn worker_thread<T: CollectResults + Default + Send>(
@@ -89,17 +110,59 @@ fn worker_thread<T: CollectResults + Default + Send>(
     parser_config: parser::Config,
     checker_config: checker::Config,
     elaborator_config: Option<(elaborator::Config, Vec<elaborator::ElaborationStep>)>,
+    cache: Option<&ResultCache>,
 ) -> T {
     let mut results = T::default();
 
+    let config_description = describe_config(
+        &parser_config,
+        &checker_config,
+        &format!("elaborate={}", elaborator_config.is_some()),
+    );
+
     while let Some(job) = jobs_queue.pop() {
-        let result = run_job(
+        let key = cache.and_then(|cache| {
+            let certificate_dir = checker_config.certificate_dir.as_deref();
+            match CacheKey::from_files(
+                job.problem_file,
+                job.proof_file,
+                &config_description,
+                certificate_dir,
+            ) {
+                Ok(key) => Some((cache, key)),
+                Err(e) => {
+                    log::warn!("couldn't compute cache key: {}", e);
+                    None
+                }
+            }
+        });
+        if let Some(cached) = key.and_then(|(cache, key)| cache.get(&key)) {
+            log::info!("skipping cached file '{}'", job.proof_file.display());
+            let id = (job.proof_file.to_str().unwrap().to_owned(), job.run_index);
+            results.add_run_measurement(&id, cached.measurement);
+            if cached.is_holey {
+                results.register_holey();
+            }
+            continue;
+        }
+
+        let result = run_measured_job(
             &mut results,
             job,
             parser_config,
             checker_config.clone(),
             elaborator_config.clone(),
         );
+        if let (Ok((is_holey, measurement)), Some((cache, key))) = (&result, key) {
+            let cached = CachedResult {
+                is_holey: *is_holey,
+                measurement: measurement.clone(),
+            };
+            if let Err(e) = cache.insert(&key, &cached) {
+                log::warn!("failed to write to result cache: {}", e);
+            }
+        }
+        let result = result.map(|(is_holey, _)| is_holey);
         match result {
             Ok(true) => results.register_holey(),
             Err(e) => {
@@ -120,6 +183,7 @@ pub fn run_benchmark<T: CollectResults + Default + Send>(
     parser_config: parser::Config,
     checker_config: checker::Config,
     elaborator_config: Option<(elaborator::Config, Vec<elaborator::ElaborationStep>)>,
+    cache: Option<&ResultCache>,
 ) -> T {
     const STACK_SIZE: usize = 128 * 1024 * 1024;
 
@@ -148,7 +212,13 @@ pub fn run_benchmark<T: CollectResults + Default + Send>(
                 thread::Builder::new()
                     .stack_size(STACK_SIZE)
                     .spawn_scoped(s, move || {
-                        worker_thread(jobs_queue, parser_config, checker_config, elaborator_config)
+                        worker_thread(
+                            jobs_queue,
+                            parser_config,
+                            checker_config,
+                            elaborator_config,
+                            cache,
+                        )
                     })
                     .unwrap()
             })
@@ -170,6 +240,7 @@ pub fn run_csv_benchmark(
     parser_config: parser::Config,
     checker_config: checker::Config,
     elaborator_config: Option<(elaborator::Config, Vec<elaborator::ElaborationStep>)>,
+    cache: Option<&ResultCache>,
     runs_dest: &mut dyn io::Write,
     steps_dest: &mut dyn io::Write,
 ) -> io::Result<()> {
@@ -180,6 +251,7 @@ pub fn run_csv_benchmark(
         parser_config,
         checker_config,
         elaborator_config,
+        cache,
     );
     println!(
         "{} errors encountered during benchmark",
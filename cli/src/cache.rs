@@ -0,0 +1,462 @@
+use carcara::{benchmarking::RunMeasurement, checker, parser, rewriting::RuleDatabase};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// The prefix of all files created by the cache.
+const FILE_PREFIX: &str = "carcara-result-";
+
+/// The name of the file, inside the cache directory, that stores the version of Carcara that
+/// produced the cached results.
+const VERSION_FILE_NAME: &str = "carcara-result-version";
+
+/// A 64-bit FNV-1a hasher. We can't use `std::hash::DefaultHasher` here because its output is not
+/// guaranteed to be stable across Rust releases, and the hashes are persisted to disk.
+struct Fnv64(u64);
+
+impl Fnv64 {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn hash(bytes: &[u8]) -> u64 {
+        let mut h = Self::new();
+        h.write(bytes);
+        h.0
+    }
+}
+
+/// A stable textual description of the configuration options that can affect the result of
+/// checking a proof.
+pub fn describe_config(
+    parser_config: &parser::Config,
+    checker_config: &checker::Config,
+    extra: &str,
+) -> String {
+    // The checker configuration is destructured without a rest pattern, so that any new option
+    // must be considered here
+    let checker::Config {
+        elaborated,
+        strict_resolution,
+        ignore_unknown_rules,
+        allowed_rules,
+        trace: _,
+        term_limits,
+        duplicate_conclusion_threshold: _,
+        native_lia_generic,
+        expected_conclusion,
+        rare_rules,
+        certificate_dir,
+    } = checker_config;
+    let mut allowed_rules: Vec<_> = allowed_rules.iter().collect();
+    allowed_rules.sort();
+    format!(
+        "{:?};elaborated={};strict_resolution={};ignore_unknown_rules={};allowed_rules={:?};\
+        term_limits={:?};native_lia_generic={};expected_conclusion={:?};rare_rules={:?};\
+        certificate_dir={:?};{}",
+        parser_config,
+        elaborated,
+        strict_resolution,
+        ignore_unknown_rules,
+        allowed_rules,
+        term_limits,
+        native_lia_generic,
+        expected_conclusion,
+        rare_rules.as_deref().map(describe_rules),
+        certificate_dir,
+        extra,
+    )
+}
+
+/// Returns the paths of the certificate files referenced by `drat_refutation` steps in the proof.
+/// These are the string literals that follow a `"drat-file"` or `"lrat-file"` argument. This
+/// doesn't parse the proof, so it may find more paths than are actually read when checking.
+fn certificate_paths(proof: &[u8]) -> Vec<String> {
+    let proof = String::from_utf8_lossy(proof);
+    let mut paths = Vec::new();
+    for format in ["\"drat-file\"", "\"lrat-file\""] {
+        for (i, _) in proof.match_indices(format) {
+            let rest = &proof[i + format.len()..];
+            let Some(start) = rest.find('"') else {
+                continue;
+            };
+
+            // In SMT-LIB, a double quote inside a string literal is escaped as `""`
+            let mut path = String::new();
+            let mut chars = rest[start + 1..].chars().peekable();
+            while let Some(c) = chars.next() {
+                if c != '"' {
+                    path.push(c);
+                } else if chars.next_if_eq(&'"').is_some() {
+                    path.push('"');
+                } else {
+                    paths.push(path);
+                    break;
+                }
+            }
+        }
+    }
+    paths
+}
+
+/// Describes the rules in a rewrite database. RARE rules are described by their full definition,
+/// so any change to a rule file that may affect checking also changes the description.
+fn describe_rules(rules: &RuleDatabase) -> String {
+    let rules: Vec<_> = rules
+        .names()
+        .map(|name| match rules.get_rare(name) {
+            Some(rule) => format!("{}={:?}", name, rule),
+            None => name.to_owned(),
+        })
+        .collect();
+    rules.join(",")
+}
+
+/// The key of a cache entry, made from the hashes of the problem, the proof, and the
+/// configuration used when checking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheKey {
+    problem: u64,
+    proof: u64,
+    config: u64,
+}
+
+impl CacheKey {
+    /// Computes the key for checking `proof` against `problem` with the configuration described by
+    /// `config`. If certificate files may be read from `certificate_dir`, the contents of the files
+    /// referenced by the proof are hashed together with the configuration.
+    pub fn new(problem: &[u8], proof: &[u8], config: &str, certificate_dir: Option<&Path>) -> Self {
+        let mut config_hash = Fnv64::new();
+        config_hash.write(config.as_bytes());
+        if let Some(dir) = certificate_dir {
+            for path in certificate_paths(proof) {
+                let path = dir.join(path);
+                // Only regular files are read when checking, since reading from devices or pipes
+                // may never terminate
+                let contents = match fs::metadata(&path) {
+                    Ok(m) if m.is_file() => fs::read(&path).ok(),
+                    _ => None,
+                };
+                match contents {
+                    Some(contents) => {
+                        config_hash.write(&Fnv64::hash(&contents).to_le_bytes());
+                    }
+                    None => config_hash.write(b"missing"),
+                }
+            }
+        }
+        Self {
+            problem: Fnv64::hash(problem),
+            proof: Fnv64::hash(proof),
+            config: config_hash.0,
+        }
+    }
+
+    pub fn from_files(
+        problem: &Path,
+        proof: &Path,
+        config: &str,
+        certificate_dir: Option<&Path>,
+    ) -> io::Result<Self> {
+        Ok(Self::new(
+            &fs::read(problem)?,
+            &fs::read(proof)?,
+            config,
+            certificate_dir,
+        ))
+    }
+
+    fn file_name(&self) -> String {
+        format!(
+            "{}{:016x}-{:016x}-{:016x}",
+            FILE_PREFIX, self.problem, self.proof, self.config
+        )
+    }
+}
+
+/// A cached checking result. Only proofs that were successfully checked are cached, so the
+/// verdict is either "valid" or "holey". The time measurements of the original run are also
+/// stored, so benchmark statistics can be reported for cached results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedResult {
+    pub is_holey: bool,
+    pub measurement: RunMeasurement,
+}
+
+impl CachedResult {
+    fn write(&self, dest: &mut dyn Write) -> io::Result<()> {
+        let m = &self.measurement;
+        writeln!(dest, "{}", if self.is_holey { "holey" } else { "valid" })?;
+        for (name, time) in [
+            ("parsing", m.parsing),
+            ("checking", m.checking),
+            ("elaboration", m.elaboration),
+            ("scheduling", m.scheduling),
+            ("total", m.total),
+            ("polyeq", m.polyeq),
+            ("assume", m.assume),
+            ("assume_core", m.assume_core),
+        ] {
+            writeln!(dest, "{} {}", name, time.as_nanos())?;
+        }
+        let pipeline: Vec<_> = m
+            .elaboration_pipeline
+            .iter()
+            .map(|d| d.as_nanos().to_string())
+            .collect();
+        writeln!(dest, "elaboration_pipeline {}", pipeline.join(","))
+    }
+
+    fn read(content: &str) -> Option<Self> {
+        let mut lines = content.lines();
+        let is_holey = match lines.next()? {
+            "valid" => false,
+            "holey" => true,
+            _ => return None,
+        };
+        let parse_nanos = |s: &str| s.parse().ok().map(Duration::from_nanos);
+        let mut measurement = RunMeasurement::default();
+        for line in lines {
+            let (name, value) = line.split_once(' ').unwrap_or((line, ""));
+            let field = match name {
+                "parsing" => &mut measurement.parsing,
+                "checking" => &mut measurement.checking,
+                "elaboration" => &mut measurement.elaboration,
+                "scheduling" => &mut measurement.scheduling,
+                "total" => &mut measurement.total,
+                "polyeq" => &mut measurement.polyeq,
+                "assume" => &mut measurement.assume,
+                "assume_core" => &mut measurement.assume_core,
+                "elaboration_pipeline" => {
+                    measurement.elaboration_pipeline = value
+                        .split(',')
+                        .filter(|s| !s.is_empty())
+                        .map(parse_nanos)
+                        .collect::<Option<_>>()?;
+                    continue;
+                }
+                _ => return None,
+            };
+            *field = parse_nanos(value)?;
+        }
+        Some(Self { is_holey, measurement })
+    }
+}
+
+/// An on-disk cache mapping problem, proof and configuration hashes to checking results.
+///
+/// All files created by the cache have the `carcara-result-` prefix, so the cache can share its
+/// directory with other files. Only files with this prefix are ever removed.
+///
+/// The cache is tied to a specific version of Carcara. If the cache was created by a different
+/// version, all its entries are discarded when the cache is opened.
+pub struct ResultCache {
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    pub fn open(dir: impl Into<PathBuf>, version: &str) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let version_file = dir.join(VERSION_FILE_NAME);
+        let cached_version = fs::read_to_string(&version_file).ok();
+        if cached_version.as_deref() != Some(version) {
+            if cached_version.is_some() {
+                log::info!("result cache was created by a different version, invalidating it");
+            }
+            for entry in fs::read_dir(&dir)? {
+                let entry = entry?;
+                let is_cache_file = entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| name.starts_with(FILE_PREFIX));
+                if is_cache_file && entry.file_type()?.is_file() {
+                    fs::remove_file(entry.path())?;
+                }
+            }
+            fs::write(&version_file, version)?;
+        }
+        Ok(Self { dir })
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<CachedResult> {
+        let content = fs::read_to_string(self.dir.join(key.file_name())).ok()?;
+        CachedResult::read(&content)
+    }
+
+    pub fn insert(&self, key: &CacheKey, result: &CachedResult) -> io::Result<()> {
+        // We first write to a temporary file and then rename it, so that concurrent readers never
+        // observe a partially written entry
+        let path = self.dir.join(key.file_name());
+        let temp_path = path.with_extension("tmp");
+        let mut f = fs::File::create(&temp_path)?;
+        result.write(&mut f)?;
+        drop(f);
+        fs::rename(temp_path, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory that is removed when dropped.
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "carcara-cache-test-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn result(is_holey: bool) -> CachedResult {
+        CachedResult {
+            is_holey,
+            measurement: RunMeasurement {
+                parsing: Duration::from_millis(3),
+                total: Duration::from_millis(10),
+                elaboration_pipeline: vec![Duration::from_nanos(7), Duration::from_nanos(11)],
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_open() {
+        let dir = TestDir::new("open");
+        let nested = dir.0.join("a").join("b");
+        ResultCache::open(&nested, "1.0").unwrap();
+        assert_eq!(
+            fs::read_to_string(nested.join(VERSION_FILE_NAME)).unwrap(),
+            "1.0"
+        );
+    }
+
+    #[test]
+    fn test_hit_and_miss() {
+        let dir = TestDir::new("hit");
+        let cache = ResultCache::open(&dir.0, "1.0").unwrap();
+        let key = CacheKey::new(b"problem", b"proof", "config", None);
+        assert_eq!(cache.get(&key), None);
+
+        cache.insert(&key, &result(true)).unwrap();
+        assert_eq!(cache.get(&key), Some(result(true)));
+
+        // Any change to the problem, the proof or the configuration is a miss
+        for other in [
+            CacheKey::new(b"problem!", b"proof", "config", None),
+            CacheKey::new(b"problem", b"proof!", "config", None),
+            CacheKey::new(b"problem", b"proof", "config!", None),
+        ] {
+            assert_eq!(cache.get(&other), None);
+        }
+
+        // Reopening the cache with the same version keeps its entries
+        let cache = ResultCache::open(&dir.0, "1.0").unwrap();
+        assert_eq!(cache.get(&key), Some(result(true)));
+    }
+
+    #[test]
+    fn test_config_description() {
+        let describe =
+            |config: checker::Config| describe_config(&parser::Config::new(), &config, "");
+        let default = describe(checker::Config::new());
+        let goal = checker::ExpectedConclusion::FinalGoal("(cl)".to_owned());
+        for config in [
+            checker::Config::new().strict_resolution(true),
+            checker::Config::new().native_lia_generic(true),
+            checker::Config::new().expected_conclusion(goal),
+            checker::Config::new().certificate_dir(Some(PathBuf::from("a"))),
+        ] {
+            assert_ne!(describe(config), default);
+        }
+
+        // Options that don't affect the result are not part of the description
+        let config = checker::Config::new().duplicate_conclusion_threshold(Some(2));
+        assert_eq!(describe(config), default);
+    }
+
+    #[test]
+    fn test_certificate_files() {
+        let dir = TestDir::new("certificates");
+        let proof =
+            b"(step t1 (cl) :rule drat_refutation :args (\"drat-file\" \"a \"\"b\"\".drat\"))";
+        assert_eq!(certificate_paths(proof), ["a \"b\".drat"]);
+
+        let key = |dir: Option<&Path>| CacheKey::new(b"problem", proof, "config", dir);
+        let missing = key(Some(&dir.0));
+        fs::write(dir.0.join("a \"b\".drat"), "1 0\n").unwrap();
+        let first = key(Some(&dir.0));
+        fs::write(dir.0.join("a \"b\".drat"), "2 0\n").unwrap();
+        let second = key(Some(&dir.0));
+        assert_ne!(missing, first);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_rare_rules_in_config() {
+        let describe = |rules: &str| {
+            let rules = RuleDatabase::parse_rare(rules.as_bytes()).unwrap();
+            let config = checker::Config {
+                rare_rules: Some(std::sync::Arc::new(rules)),
+                ..checker::Config::new()
+            };
+            describe_config(&parser::Config::new(), &config, "")
+        };
+        let rule = describe("(define-rule double-not ((t Bool)) (not (not t)) t)");
+        let same_name = describe("(define-rule double-not ((t Bool)) (not (not t)) (not t))");
+        assert_ne!(rule, same_name);
+        assert_ne!(
+            rule,
+            describe_config(&parser::Config::new(), &checker::Config::new(), "")
+        );
+    }
+
+    #[test]
+    fn test_invalidation() {
+        let dir = TestDir::new("invalidation");
+        let unrelated = dir.0.join("unrelated.txt");
+        fs::write(&unrelated, "keep me").unwrap();
+
+        let cache = ResultCache::open(&dir.0, "1.0").unwrap();
+        let key = CacheKey::new(b"problem", b"proof", "config", None);
+        cache.insert(&key, &result(false)).unwrap();
+
+        let cache = ResultCache::open(&dir.0, "2.0").unwrap();
+        assert_eq!(cache.get(&key), None);
+        assert_eq!(fs::read_to_string(&unrelated).unwrap(), "keep me");
+    }
+
+    #[test]
+    fn test_malformed_entry() {
+        let dir = TestDir::new("malformed");
+        let cache = ResultCache::open(&dir.0, "1.0").unwrap();
+        let key = CacheKey::new(b"problem", b"proof", "config", None);
+        fs::write(dir.0.join(key.file_name()), "valid\ntotal abc\n").unwrap();
+        assert_eq!(cache.get(&key), None);
+    }
+}
@@ -0,0 +1,506 @@
+//! Support for configuration files.
+//!
+//! A configuration file sets default values for command line options, so that the same options
+//! don't have to be repeated in every invocation. This is useful for benchmark scripts and CI
+//! setups, that can keep their configuration checked into a repository. The file uses the TOML
+//! format, where each key is the long name of a command line option. For example:
+//!
+//! ```toml
+//! default-profile = "ci"
+//! ignore-unknown-rules = true
+//!
+//! [profile.ci]
+//! num-threads = 4
+//! allowed-rules = ["hole", "lia_generic"]
+//! lia-solver = "cvc5"
+//! lia-solver-args = "--tlimit=10000 --proof-format-mode=alethe"
+//! ```
+//!
+//! The options at the top level of the file always apply, and the options in the selected profile
+//! (given by `--profile`, or by the `default-profile` key) override them. Options given on the
+//! command line override both. Each option is only applied to the commands that accept it.
+//!
+//! The file is only read by the commands that check proofs, so other commands are not affected by
+//! it. If the file can't be parsed, or sets an unknown option, the command fails, so that a broken
+//! configuration is never silently replaced by the default options.
+
+use crate::error::{CliError, CliResult};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+use toml::{Table, Value};
+
+/// The name of the configuration file that is used if no other file is given.
+pub const DEFAULT_CONFIG_FILE: &str = "carcara.toml";
+
+/// Options that control how the configuration file is found, and that therefore can't be set in
+/// it.
+const RESERVED_OPTIONS: &[&str] = &["config", "no-config", "profile"];
+
+/// The commands that read the configuration file.
+const CONFIG_COMMANDS: &[&str] = &["check", "elaborate", "bench", "shrink", "report", "serve"];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ConfigFile {
+    default_profile: Option<String>,
+
+    #[serde(default, rename = "profile")]
+    profiles: BTreeMap<String, Table>,
+
+    /// The options at the top level of the file, in the order in which they appear.
+    #[serde(flatten)]
+    options: Table,
+}
+
+/// Reads the configuration file, if there is one and the command being invoked reads it, and
+/// returns the command line arguments extended with the options it sets. The file is given by the
+/// `--config` option, and defaults to `carcara.toml` in the current directory. If the
+/// `--no-config` flag is passed, no file is read.
+pub fn apply_config_file(app: &clap::Command, args: Vec<OsString>) -> CliResult<Vec<OsString>> {
+    let strings: Vec<String> = args
+        .iter()
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect();
+    // Arguments after a `--` separator are never options
+    let end = strings
+        .iter()
+        .position(|a| a == "--")
+        .unwrap_or(strings.len());
+    let strings = &strings[..end];
+
+    if strings.iter().any(|a| a == "--no-config") {
+        return Ok(args);
+    }
+    let Some(subcommand) = find_subcommand(app, strings) else {
+        return Ok(args);
+    };
+    if !CONFIG_COMMANDS.contains(&subcommand.get_name()) {
+        return Ok(args);
+    }
+    let path = match find_option_value(strings, "config") {
+        Some(path) => PathBuf::from(path),
+        None if Path::new(DEFAULT_CONFIG_FILE).is_file() => PathBuf::from(DEFAULT_CONFIG_FILE),
+        None => return Ok(args),
+    };
+    let contents = fs::read_to_string(&path)?;
+    let config = parse_config_file(&contents)
+        .map_err(|(line, message)| CliError::InvalidConfigFile(path, line, message))?;
+
+    // The options are ordered by precedence, so the options in the profile come before the ones at
+    // the top level
+    let mut options: Vec<(&String, &Value)> = Vec::new();
+    let profile = find_option_value(strings, "profile").or(config.default_profile.clone());
+    if let Some(profile) = profile {
+        let Some(profile_options) = config.profiles.get(&profile) else {
+            return Err(CliError::UnknownConfigProfile(profile));
+        };
+        options.extend(profile_options);
+    }
+    for option in &config.options {
+        if !options.iter().any(|(key, _)| *key == option.0) {
+            options.push(option);
+        }
+    }
+
+    // An option is not applied if it, or an option that conflicts with it, was already set, either
+    // on the command line or by an option with higher precedence
+    let mut set: Vec<&clap::Arg> = subcommand
+        .get_arguments()
+        .chain(app.get_arguments())
+        .filter(|a| is_option_given(strings, a))
+        .collect();
+    let mut extra = Vec::new();
+    for (key, value) in options {
+        if RESERVED_OPTIONS.contains(&key.as_str()) {
+            return Err(CliError::InvalidConfigOption(key.clone()));
+        }
+        let Some(arg) = find_arg(subcommand, key).or_else(|| find_arg(app, key)) else {
+            // The option may be meant for a different command. Otherwise, it is most likely a typo
+            if app.get_subcommands().any(|s| find_arg(s, key).is_some()) {
+                continue;
+            }
+            return Err(CliError::InvalidConfigOption(key.clone()));
+        };
+        let is_set = |a: &&clap::Arg| a.get_id() == arg.get_id() || conflicts(subcommand, a, arg);
+        if set.iter().any(is_set) {
+            continue;
+        }
+
+        let invalid = || CliError::InvalidConfigOption(key.clone());
+        match (arg.is_takes_value_set(), value) {
+            (false, Value::Boolean(true)) => extra.push(format!("--{}", key)),
+            (false, Value::Boolean(false)) => continue,
+            (true, Value::Array(values)) if arg.is_multiple_values_set() => {
+                for value in values {
+                    let s = scalar_to_string(value).ok_or_else(invalid)?;
+                    extra.push(format!("--{}={}", key, s));
+                }
+            }
+            (true, value) => {
+                let s = scalar_to_string(value).ok_or_else(invalid)?;
+                extra.push(format!("--{}={}", key, s));
+            }
+            _ => return Err(invalid()),
+        }
+        set.push(arg);
+    }
+
+    let mut args = args;
+    args.splice(end..end, extra.into_iter().map(OsString::from));
+    Ok(args)
+}
+
+/// Returns whether two arguments of a command conflict with each other.
+fn conflicts(command: &clap::Command, a: &clap::Arg, b: &clap::Arg) -> bool {
+    let owns = |arg: &clap::Arg| command.get_arguments().any(|x| x.get_id() == arg.get_id());
+    let declares = |x: &clap::Arg, y: &clap::Arg| {
+        let conflicts = command.get_arg_conflicts_with(x);
+        conflicts.iter().any(|c| c.get_id() == y.get_id())
+    };
+    owns(a) && owns(b) && (declares(a, b) || declares(b, a))
+}
+
+fn find_arg<'a, 'h>(command: &'a clap::Command<'h>, long: &str) -> Option<&'a clap::Arg<'h>> {
+    command.get_arguments().find(|a| a.get_long() == Some(long))
+}
+
+/// Finds the value of an option with the given long name, given either as `--name value` or as
+/// `--name=value`.
+fn find_option_value(args: &[String], name: &str) -> Option<String> {
+    let flag = format!("--{}", name);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if *arg == flag {
+            return iter.next().cloned();
+        }
+        if let Some(value) = arg.strip_prefix(&flag).and_then(|s| s.strip_prefix('=')) {
+            return Some(value.to_owned());
+        }
+    }
+    None
+}
+
+/// Finds the subcommand being invoked, which is the first argument that is not an option or the
+/// value of an option.
+fn find_subcommand<'a, 'h>(
+    app: &'a clap::Command<'h>,
+    args: &[String],
+) -> Option<&'a clap::Command<'h>> {
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let takes_next = if let Some(long) = arg.strip_prefix("--") {
+            // If the value is given as `--name=value`, the name won't match any option
+            app.get_arguments()
+                .any(|a| a.get_long() == Some(long) && a.is_takes_value_set())
+        } else if let Some(shorts) = arg.strip_prefix('-') {
+            // Short flags may be grouped, as in `-abc`. The first short option that takes a value
+            // ends the group, and its value is the rest of the argument, or the next argument if
+            // there is nothing left
+            let value_start = shorts.char_indices().find_map(|(i, c)| {
+                let takes_value = app
+                    .get_arguments()
+                    .any(|a| a.get_short() == Some(c) && a.is_takes_value_set());
+                takes_value.then_some(i + c.len_utf8())
+            });
+            value_start == Some(shorts.len())
+        } else {
+            return app.find_subcommand(arg);
+        };
+        if takes_next {
+            iter.next();
+        }
+    }
+    None
+}
+
+fn is_option_given(args: &[String], arg: &clap::Arg) -> bool {
+    args.iter().skip(1).any(|a| {
+        if let Some(long) = a.strip_prefix("--") {
+            let name = long.split_once('=').map_or(long, |(name, _)| name);
+            Some(name) == arg.get_long()
+        } else if let Some(short) = a.strip_prefix('-') {
+            arg.get_short().is_some_and(|c| short.starts_with(c))
+        } else {
+            false
+        }
+    })
+}
+
+/// Parses the contents of a configuration file. On failure, returns the line where the error
+/// happened, and the error message.
+fn parse_config_file(contents: &str) -> Result<ConfigFile, (usize, String)> {
+    toml::from_str(contents).map_err(|e| {
+        let offset = e.span().map_or(0, |span| span.start);
+        let line = contents[..offset].matches('\n').count() + 1;
+        (line, e.message().trim().replace('\n', ", "))
+    })
+}
+
+/// Returns the textual form in which a value is passed to the command line parser, or `None` if it
+/// is not a string or a number.
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Integer(i) => Some(i.to_string()),
+        Value::Float(f) => Some(f.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, Command, CommandFactory};
+
+    /// A directory that is removed when dropped.
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "carcara-config-test-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn write_config(&self, contents: &str) -> String {
+            let path = self.0.join(DEFAULT_CONFIG_FILE);
+            fs::write(&path, contents).unwrap();
+            path.to_string_lossy().into_owned()
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|&a| a.to_owned()).collect()
+    }
+
+    /// Applies the configuration file to the given arguments of the real command line interface,
+    /// returning only the arguments that were added, or the error message.
+    fn apply(config: &str, args: &[&str]) -> Result<Vec<String>, String> {
+        let mut args: Vec<OsString> = args.iter().map(OsString::from).collect();
+        args.splice(1..1, [OsString::from("--config"), OsString::from(config)]);
+        let end = args.iter().position(|a| a == "--").unwrap_or(args.len());
+        let n = args.len();
+        let result = apply_config_file(&crate::Cli::command(), args).map_err(|e| e.to_string())?;
+        Ok(result[end..end + result.len() - n]
+            .iter()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect())
+    }
+
+    #[test]
+    fn test_parse_config_file() {
+        let input = r#"
+            # A comment
+            default-profile = "fast"
+            ignore-unknown-rules = true # Another comment
+            "quoted-key" = 'C:\path'
+
+            [profile.fast]
+            num-threads = 1_000
+            allowed-rules = [
+                "hole",
+                "lia_generic", # A trailing comma is allowed
+            ]
+            lia-solver-args = "--a \"b\"\n"
+        "#;
+        let config = parse_config_file(input).unwrap();
+        assert_eq!(config.default_profile.as_deref(), Some("fast"));
+        let options: Vec<_> = config.options.iter().collect();
+        assert_eq!(
+            options,
+            [
+                (&"ignore-unknown-rules".to_owned(), &Value::Boolean(true)),
+                (&"quoted-key".to_owned(), &Value::from("C:\\path")),
+            ]
+        );
+        let profiles: Vec<_> = config.profiles.keys().collect();
+        assert_eq!(profiles, ["fast"]);
+        let options: Vec<_> = config.profiles["fast"]
+            .iter()
+            .map(|(key, value)| (key.as_str(), scalar_to_string(value)))
+            .collect();
+        assert_eq!(
+            options,
+            [
+                ("num-threads", Some("1000".to_owned())),
+                ("allowed-rules", None),
+                ("lia-solver-args", Some("--a \"b\"\n".to_owned())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_config_file_errors() {
+        let cases = [
+            ("a = ", 1),
+            ("a = 1\nb = yes", 2),
+            ("a = 1\na = 2", 2),
+            ("a = \"abc", 1),
+            ("a = [1 2]", 1),
+            ("a = 1 b", 1),
+            ("[profile.a]\n\n[profile.a]", 3),
+            ("[profile]\na = 1", 2),
+            ("default-profile = [1]", 1),
+        ];
+        for (input, line) in cases {
+            let (error_line, _) = parse_config_file(input).unwrap_err();
+            assert_eq!(error_line, line, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_find_subcommand() {
+        let app = Command::new("app")
+            .arg(Arg::new("verbose").short('v').long("verbose"))
+            .arg(Arg::new("quiet").short('q'))
+            .arg(Arg::new("level").short('l').long("level").takes_value(true))
+            .subcommand(Command::new("check"))
+            .subcommand(Command::new("info"));
+        let find = |a: &[&str]| find_subcommand(&app, &args(a)).map(|s| s.get_name().to_owned());
+
+        assert_eq!(find(&["app", "check"]).as_deref(), Some("check"));
+        assert_eq!(find(&["app", "-v", "check"]).as_deref(), Some("check"));
+        assert_eq!(find(&["app", "-vq", "check"]).as_deref(), Some("check"));
+        assert_eq!(
+            find(&["app", "--verbose", "check"]).as_deref(),
+            Some("check")
+        );
+
+        // The value of an option is never the subcommand, however it is given
+        assert_eq!(
+            find(&["app", "-l", "info", "check"]).as_deref(),
+            Some("check")
+        );
+        assert_eq!(
+            find(&["app", "-vl", "info", "check"]).as_deref(),
+            Some("check")
+        );
+        assert_eq!(find(&["app", "-linfo", "check"]).as_deref(), Some("check"));
+        assert_eq!(find(&["app", "-vlinfo", "check"]).as_deref(), Some("check"));
+        assert_eq!(
+            find(&["app", "--level", "info", "check"]).as_deref(),
+            Some("check")
+        );
+        assert_eq!(
+            find(&["app", "--level=info", "check"]).as_deref(),
+            Some("check")
+        );
+
+        assert_eq!(find(&["app", "-v"]), None);
+        assert_eq!(find(&["app", "unknown", "check"]), None);
+    }
+
+    #[test]
+    fn test_apply_config_file() {
+        let dir = TestDir::new("apply");
+        let config = dir.write_config(
+            r#"
+            default-profile = "many"
+            ignore-unknown-rules = true
+            num-threads = 2
+            allowed-rules = ["hole"]
+
+            [profile.many]
+            num-threads = 8
+
+            [profile.few]
+            num-threads = 1
+            "#,
+        );
+
+        // The profile overrides the top level, and `allowed-rules` is not applied because it
+        // conflicts with `ignore-unknown-rules`, which has precedence over it
+        let added = apply(&config, &["carcara", "check", "a.alethe"]).unwrap();
+        assert_eq!(added, ["--num-threads=8", "--ignore-unknown-rules"]);
+
+        let added = apply(&config, &["carcara", "check", "--profile=few", "a.alethe"]).unwrap();
+        assert_eq!(added, ["--num-threads=1", "--ignore-unknown-rules"]);
+
+        // Options given on the command line, even as short options, take precedence
+        let added = apply(&config, &["carcara", "check", "-u", "3", "a.alethe"]).unwrap();
+        assert_eq!(added, ["--ignore-unknown-rules"]);
+        let added = apply(&config, &["carcara", "check", "--allowed-rules", "x"]).unwrap();
+        assert_eq!(added, ["--num-threads=8"]);
+
+        // Global options before the subcommand don't prevent it from being found
+        let added = apply(&config, &["carcara", "-v", "check", "a.alethe"]).unwrap();
+        assert_eq!(added, ["--num-threads=8", "--ignore-unknown-rules"]);
+
+        // Arguments after `--` are not options
+        let added = apply(&config, &["carcara", "check", "--", "--num-threads=3"]).unwrap();
+        assert_eq!(added, ["--num-threads=8", "--ignore-unknown-rules"]);
+
+        assert_eq!(
+            apply(&config, &["carcara", "check", "--profile", "none"]).unwrap_err(),
+            "no profile named 'none' in config file"
+        );
+    }
+
+    #[test]
+    fn test_config_file_only_read_by_some_commands() {
+        let dir = TestDir::new("commands");
+        let config = dir.write_config("ignore-unknown-rules = true\n");
+        let added = apply(&config, &["carcara", "parse", "a.alethe"]).unwrap();
+        assert!(added.is_empty());
+        let added = apply(&config, &["carcara", "--help"]).unwrap();
+        assert!(added.is_empty());
+
+        // A missing file is only an error for commands that read it
+        let missing = dir.0.join("missing.toml").to_string_lossy().into_owned();
+        assert!(apply(&missing, &["carcara", "parse", "a.alethe"]).is_ok());
+        assert!(apply(&missing, &["carcara", "check", "a.alethe"]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_config_file() {
+        let dir = TestDir::new("invalid");
+
+        let config = dir.write_config("num-threads = 2\nignore-unknown-rules = \n");
+        let error = apply(&config, &["carcara", "check", "a.alethe"]).unwrap_err();
+        assert!(error.starts_with(&format!("invalid config file {}, line 2: ", config)));
+
+        let config = dir.write_config("not-an-option = 1\n");
+        assert_eq!(
+            apply(&config, &["carcara", "check", "a.alethe"]).unwrap_err(),
+            "invalid option in config file: 'not-an-option'"
+        );
+
+        // The `profile` key is reserved for the profile tables
+        let config = dir.write_config("profile = \"a\"\n");
+        let error = apply(&config, &["carcara", "check", "a.alethe"]).unwrap_err();
+        assert!(error.starts_with(&format!("invalid config file {}, line 1: ", config)));
+
+        let config = dir.write_config("no-config = true\n");
+        assert_eq!(
+            apply(&config, &["carcara", "check", "a.alethe"]).unwrap_err(),
+            "invalid option in config file: 'no-config'"
+        );
+
+        // An option for another command is not an error
+        let config = dir.write_config("lia-solver = \"cvc5\"\n");
+        let added = apply(&config, &["carcara", "check", "a.alethe"]).unwrap();
+        assert!(added.is_empty());
+
+        let config = dir.write_config("ignore-unknown-rules = 1\n");
+        assert_eq!(
+            apply(&config, &["carcara", "check", "a.alethe"]).unwrap_err(),
+            "invalid option in config file: 'ignore-unknown-rules'"
+        );
+    }
+}
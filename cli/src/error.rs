@@ -1,3 +1,4 @@
+use carcara::error_code::ErrorCode;
 use std::{fmt, io, path::PathBuf};
 
 #[derive(Debug)]
@@ -5,11 +6,29 @@ pub enum CliError {
     CarcaraError(carcara::Error),
     CantInferProblemFile(PathBuf),
     InvalidSliceId(String),
+    UnknownStepId(String),
     BothFilesStdin,
+    MultipleProofsStdin,
+    NothingToShrink,
+    FalsifiedAssertions(usize),
+    InvalidConfigFile(PathBuf, usize, String),
+    InvalidConfigOption(String),
+    UnknownConfigProfile(String),
+    UnknownErrorCode(String),
 }
 
 pub type CliResult<T> = Result<T, CliError>;
 
+impl CliError {
+    /// Returns the stable code of the error, if it comes from Carcara itself.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            CliError::CarcaraError(e) => Some(e.code()),
+            _ => None,
+        }
+    }
+}
+
 impl From<io::Error> for CliError {
     fn from(e: io::Error) -> Self {
         Self::CarcaraError(carcara::Error::Io(e))
@@ -30,7 +49,31 @@ impl fmt::Display for CliError {
                 write!(f, "can't infer problem file: {}", p.display())
             }
             CliError::BothFilesStdin => write!(f, "problem and proof files can't both be `-`"),
+            CliError::MultipleProofsStdin => {
+                write!(f, "files can't be `-` when checking multiple proofs")
+            }
             CliError::InvalidSliceId(id) => write!(f, "invalid id for slice: {}", id),
+            CliError::UnknownStepId(id) => write!(f, "no step with id '{}' in proof", id),
+            CliError::NothingToShrink => write!(f, "proof is valid, there is nothing to shrink"),
+            CliError::FalsifiedAssertions(n) => {
+                write!(f, "model does not satisfy {} assertion(s)", n)
+            }
+            CliError::InvalidConfigFile(p, line, message) => {
+                write!(
+                    f,
+                    "invalid config file {}, line {}: {}",
+                    p.display(),
+                    line,
+                    message
+                )
+            }
+            CliError::InvalidConfigOption(key) => {
+                write!(f, "invalid option in config file: '{}'", key)
+            }
+            CliError::UnknownConfigProfile(name) => {
+                write!(f, "no profile named '{}' in config file", name)
+            }
+            CliError::UnknownErrorCode(code) => write!(f, "unknown error code: '{}'", code),
         }
     }
 }
@@ -1,22 +1,36 @@
 mod benchmarking;
+mod cache;
+mod config;
+mod environment;
 mod error;
 mod logger;
 mod path_args;
+mod server;
 
 use carcara::{
-    ast, benchmarking::OnlineBenchmarkResults, check, check_and_elaborate, check_parallel, checker,
-    elaborator, generate_lia_smt_instances, parser,
+    ast,
+    benchmarking::{
+        HtmlReport, JsonString, OnlineBenchmarkResults, ProofFeatures, RunMeasurement, StepTimes,
+    },
+    check, check_and_elaborate, check_multiple, check_parallel, check_refutations, checker,
+    elaborator,
+    error_code::{self, ErrorCode},
+    extract_lemmas, generate_lia_smt_instances, model, parser,
+    rewriting::RuleDatabase,
+    shrink,
 };
-use clap::{AppSettings, ArgEnum, Args, Parser, Subcommand};
+use clap::{AppSettings, ArgEnum, Args, CommandFactory, Parser, Subcommand};
 use const_format::{formatcp, str_index};
 use error::{CliError, CliResult};
 use git_version::git_version;
 use path_args::{get_instances_from_paths, infer_problem_path};
 use std::{
+    fmt,
     fs::File,
-    io::{self, BufRead, IsTerminal},
-    path::Path,
-    sync::atomic,
+    io::{self, BufRead, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    sync::{atomic, Arc, OnceLock},
+    time::Duration,
 };
 
 // `git describe --all` will try to find any ref (including tags) that describes the current commit.
@@ -29,6 +43,10 @@ const GIT_BRANCH_NAME: &str = git_version!(
 const GIT_COMMIT_HASH: &str = git_version!(fallback = "unknown");
 const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// The command line arguments, including the ones added from the configuration file. These are
+/// recorded in the environment included in reports.
+static COMMAND_LINE: OnceLock<Vec<String>> = OnceLock::new();
+
 const VERSION_STRING: &str = formatcp!(
     "{} [git {} {}]",
     APP_VERSION,
@@ -59,6 +77,34 @@ struct Cli {
     /// Don't use sharing when printing terms.
     #[clap(global = true, short = 'v', long)]
     no_print_with_sharing: bool,
+
+    /// The variation of the Alethe format used when printing proofs.
+    #[clap(arg_enum, global = true, long, default_value = "alethe-1.2")]
+    output_dialect: OutputDialect,
+
+    /// When printing proofs, reorder the commands to minimize how many of them are still needed as
+    /// premises at any point. This helps consumers that process proofs as a stream. Commands that
+    /// the proof conclusion doesn't depend on are removed.
+    #[clap(global = true, long)]
+    min_live_order: bool,
+
+    /// Reads default values for options from the given configuration file, instead of from
+    /// `carcara.toml` in the current directory. Options given on the command line take precedence.
+    // This and the following two options are handled by `config::apply_config_file` before the
+    // arguments are parsed
+    #[allow(dead_code)]
+    #[clap(global = true, long)]
+    config: Option<PathBuf>,
+
+    /// Don't read any configuration file.
+    #[allow(dead_code)]
+    #[clap(global = true, long, conflicts_with = "config")]
+    no_config: bool,
+
+    /// The profile of the configuration file to use, overriding its `default-profile`.
+    #[allow(dead_code)]
+    #[clap(global = true, long)]
+    profile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -66,6 +112,12 @@ enum Command {
     /// Parses a proof file and prints it back.
     Parse(ParseCommandOptions),
 
+    /// Parses a proof file and prints it as a TPTP derivation, in the TFF dialect.
+    ExportTptp(ParseCommandOptions),
+
+    /// Converts a proof in veriT's legacy proof-trace format to the Alethe format, and prints it.
+    ImportLegacy(ParseCommandOptions),
+
     /// Checks a proof file.
     Check(CheckCommandOptions),
 
@@ -80,6 +132,53 @@ enum Command {
 
     /// Generates the equivalent SMT instance for every `lia_generic` step in a proof.
     GenerateLiaProblems(ParseCommandOptions),
+
+    /// Prints the conclusions of the steps in a proof as an SMT-LIB script, with an `assert`
+    /// command for each distinct lemma. This is useful for building lemma databases and datasets.
+    Lemmas(LemmasCommandOptions),
+
+    /// Renames all user symbols in a problem and proof, so they can be shared without revealing
+    /// proprietary information. The anonymized proof is printed, and the anonymized problem is
+    /// written to the given file.
+    Anonymize(AnonymizeCommandOptions),
+
+    /// Given a proof that fails to check, produces a smaller proof that fails in the same way.
+    Shrink(ShrinkCommandOptions),
+
+    /// Prints the context in effect at a given step: the enclosing subproofs, the variables bound
+    /// by their anchors, and the composed substitution introduced by their assignments.
+    Context(ContextCommandOptions),
+
+    /// Checks a model produced by an SMT solver, by evaluating all assertions in the problem under
+    /// it.
+    CheckModel(CheckModelCommandOptions),
+
+    /// Infers the declarations of all sorts and functions used in a proof that are not declared in
+    /// it, and prints them as SMT-LIB commands. This can be used to recreate a problem prelude
+    /// when the original problem is missing.
+    SynthesizePrelude(SynthesizePreludeCommandOptions),
+
+    /// Extracts a fixed-dimension vector of numeric features from a proof, such as a histogram of
+    /// the rules used, statistics about subproof depth and term size, and the density of arithmetic
+    /// literals. This is intended as input for models that predict checking time or solver
+    /// behavior.
+    Features(FeaturesCommandOptions),
+
+    /// Checks a proof file and writes an HTML report that renders the proof as a collapsible tree,
+    /// with per-step timing and the failing step highlighted. The report is a standalone file that
+    /// can be shared.
+    Report(ReportCommandOptions),
+
+    /// Prints a detailed explanation of an error code, including what the rules involved require
+    /// and common mistakes that cause the error. If no code is given, lists all error codes.
+    Explain(ExplainCommandOptions),
+
+    /// Runs Carcara as a checking service over HTTP. Proofs are checked by sending a request to
+    /// `POST /check?problem=<path>&proof=<path>`, where the paths are relative to the directory
+    /// given by `--root`. Metrics about the proofs checked so far, like the number of failures by error code
+    /// and the time spent in each rule, are exposed at `GET /metrics`, in the OpenMetrics format
+    /// used by Prometheus.
+    Serve(ServeCommandOptions),
 }
 
 #[derive(Args)]
@@ -97,6 +196,21 @@ struct StatsOptions {
     /// Enables the gathering of performance statistics
     #[clap(long)]
     stats: bool,
+
+    /// Prints the environment of the run: the Carcara version, the configuration, the host machine
+    /// and the hashes of the input files.
+    #[clap(long)]
+    print_environment: bool,
+}
+
+#[derive(Args)]
+struct CacheOptions {
+    /// Caches checking results in the given directory. Proofs that were already successfully
+    /// checked with the same problem and configuration are not checked again. The cache is
+    /// invalidated if it was created by a different version of Carcara. When checking a single
+    /// proof, the cache is not used if steps are traced or statistics are collected.
+    #[clap(long)]
+    result_cache: Option<PathBuf>,
 }
 
 #[derive(Args)]
@@ -134,6 +248,34 @@ struct ParsingOptions {
     /// terms. In the future, this will be the default behaviour.
     #[clap(long)]
     parse_hole_args: bool,
+
+    /// Selects which string operators are recognized.
+    ///
+    /// If this is "standard", only the operators from the SMT-LIB theory of strings are
+    /// recognized. If this is "cvc5", the extensions supported by cvc5, like `str.update`,
+    /// `str.rev` and `str.indexof_re`, are also recognized.
+    #[clap(arg_enum, long, default_value = "standard", verbatim_doc_comment)]
+    strings_profile: StringsProfile,
+
+    /// The version of the Alethe format in which the proof is written.
+    ///
+    /// If this is "auto", the version is taken from the proof's `set-info :alethe-version`
+    /// command, or inferred from the rules used in the proof. Proofs in the "legacy" version,
+    /// produced by veriT before the Alethe specification was stabilized, are normalized to the
+    /// current version before checking.
+    #[clap(arg_enum, long, default_value = "auto", verbatim_doc_comment)]
+    alethe_version: AletheVersion,
+
+    /// Renames repeated step ids instead of rejecting the proof. References to a repeated id refer
+    /// to the most recent command that used it.
+    #[clap(long)]
+    rename_duplicate_step_ids: bool,
+
+    /// Before parsing the proof, checks that every function and sort it uses is declared in the
+    /// problem, and reports all undeclared symbols at once. This detects proofs that are paired
+    /// with the wrong problem.
+    #[clap(long)]
+    detect_mismatch: bool,
 }
 
 impl From<ParsingOptions> for parser::Config {
@@ -144,6 +286,10 @@ impl From<ParsingOptions> for parser::Config {
             allow_int_real_subtyping: val.allow_int_real_subtyping,
             strict: val.strict,
             parse_hole_args: val.parse_hole_args,
+            strings_profile: val.strings_profile.into(),
+            alethe_version: val.alethe_version.into(),
+            rename_duplicate_step_ids: val.rename_duplicate_step_ids,
+            detect_mismatch: val.detect_mismatch,
         }
     }
 }
@@ -152,6 +298,7 @@ impl From<ParsingOptions> for parser::Config {
 enum CheckGranularity {
     Normal,
     Elaborated,
+    Strict,
 }
 
 #[derive(Args, Clone)]
@@ -175,16 +322,116 @@ struct CheckingOptions {
     /// Carcara, and will enforce extra restrictions. In particular:
     /// - the implicit reordering of equalities is not allowed
     /// - the pivots for `resolution` steps must be given as arguments
+    ///
+    /// If this is "strict", in addition to the restrictions above, `resolution` steps are checked
+    /// with the strict semantics from the Alethe specification:
+    /// - the conclusion must list the resulting literals in order
+    /// - duplicate literals are not implicitly removed
     #[clap(arg_enum, long, default_value = "normal", verbatim_doc_comment)]
     check_granularity: CheckGranularity,
+
+    /// Prints the premises, arguments, conclusion and context substitution of the given steps as
+    /// they are checked, along with intermediate values computed by their rules. This can be a
+    /// single step id, or a range of steps written as `<first>..<last>`.
+    #[clap(long)]
+    trace: Option<String>,
+
+    /// The maximum size of the terms built by rules while checking, and of the terms introduced
+    /// during elaboration. The size of a term is its number of distinct subterms. Steps that would
+    /// exceed it are considered invalid.
+    #[clap(long)]
+    max_term_size: Option<usize>,
+
+    /// The maximum depth of the terms built by rules while checking, and of the terms introduced
+    /// during elaboration. Steps that would exceed it are considered invalid.
+    #[clap(long)]
+    max_term_depth: Option<usize>,
+
+    /// Warn about each clause that is concluded by at least this many steps in the root proof,
+    /// which usually indicates an inefficiency in the solver. If this is 0, no warnings are given.
+    #[clap(long, default_value = "1000")]
+    duplicate_conclusion_threshold: usize,
+
+    /// Check `lia_generic` steps using Carcara's internal linear integer arithmetic procedure,
+    /// instead of considering them holes.
+    #[clap(long)]
+    native_lia_generic: bool,
+
+    /// Require the last step of the proof to conclude the empty clause. By default, it is enough
+    /// for any step to conclude it.
+    #[clap(long)]
+    require_final_empty_clause: bool,
+
+    /// Require the last step of the proof to conclude the given clause, instead of the empty
+    /// clause. This allows checking proofs that are not refutations. The clause must be written
+    /// as `(cl <term>*)`, and may use the sorts and functions declared in the problem.
+    #[clap(long, conflicts_with = "require-final-empty-clause")]
+    goal: Option<String>,
+
+    /// A file of RARE rewrite rules, such as the ones in cvc5's rewrite database. `rare_rewrite`
+    /// steps that use one of these rules are checked by instantiating it with the step arguments.
+    #[clap(long, parse(try_from_str = load_rare_rules))]
+    rare_rules: Option<Arc<RuleDatabase>>,
+
+    /// Allow `drat_refutation` steps to read their certificates from files. The paths of these
+    /// files are resolved relative to the directory of the proof file, and must be inside it.
+    #[clap(long)]
+    allow_certificate_files: bool,
+}
+
+impl CheckingOptions {
+    /// Builds the checker configuration used to check the proof in `proof_file`. If certificate
+    /// files are allowed, they are resolved relative to the directory of that file.
+    fn config_for(self, proof_file: &str) -> checker::Config {
+        let certificate_dir = self.allow_certificate_files.then(|| match proof_file {
+            "-" => PathBuf::new(),
+            path => Path::new(path)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        });
+        checker::Config { certificate_dir, ..self.into() }
+    }
+}
+
+fn parse_timeout(seconds: &str) -> Result<Duration, String> {
+    let seconds: f64 = seconds.parse().map_err(|e| format!("{}", e))?;
+    Duration::try_from_secs_f64(seconds).map_err(|_| {
+        format!(
+            "timeout must be a finite, non-negative number of seconds, got '{}'",
+            seconds
+        )
+    })
+}
+
+fn load_rare_rules(path: &str) -> Result<Arc<RuleDatabase>, String> {
+    let file = File::open(path).map_err(|e| format!("could not open '{}': {}", path, e))?;
+    RuleDatabase::parse_rare(io::BufReader::new(file))
+        .map(Arc::new)
+        .map_err(|e| format!("invalid RARE rule file '{}': {}", path, e))
 }
 
 impl From<CheckingOptions> for checker::Config {
     fn from(val: CheckingOptions) -> Self {
         Self {
-            elaborated: val.check_granularity == CheckGranularity::Elaborated,
+            elaborated: val.check_granularity != CheckGranularity::Normal,
+            strict_resolution: val.check_granularity == CheckGranularity::Strict,
             ignore_unknown_rules: val.ignore_unknown_rules,
             allowed_rules: val.allowed_rules.unwrap_or_default().into_iter().collect(),
+            trace: val.trace.map(|s| s.parse().unwrap()),
+            term_limits: ast::TermLimits::new()
+                .max_size(val.max_term_size)
+                .max_depth(val.max_term_depth),
+            duplicate_conclusion_threshold: Some(val.duplicate_conclusion_threshold)
+                .filter(|&n| n > 0),
+            native_lia_generic: val.native_lia_generic,
+            expected_conclusion: match (val.goal, val.require_final_empty_clause) {
+                (Some(goal), _) => checker::ExpectedConclusion::FinalGoal(goal),
+                (None, true) => checker::ExpectedConclusion::FinalEmptyClause,
+                (None, false) => checker::ExpectedConclusion::AnyEmptyClause,
+            },
+            rare_rules: val.rare_rules,
+            certificate_dir: None,
         }
     }
 }
@@ -197,6 +444,26 @@ enum ElaborationStep {
     Uncrowd,
     Reordering,
     Hole,
+    Dedup,
+    MergeConclusions,
+    NotNot,
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+enum GrowthPolicy {
+    Fail,
+    Skip,
+    Warn,
+}
+
+impl From<GrowthPolicy> for elaborator::GrowthPolicy {
+    fn from(val: GrowthPolicy) -> Self {
+        match val {
+            GrowthPolicy::Fail => Self::Fail,
+            GrowthPolicy::Skip => Self::Skip,
+            GrowthPolicy::Warn => Self::Warn,
+        }
+    }
 }
 
 #[derive(Args, Clone)]
@@ -215,6 +482,36 @@ struct ElaborationOptions {
     )]
     lia_solver_args: String,
 
+    /// The wall-clock time limit, in seconds, for each run of the `lia_generic` solver. When it
+    /// is exceeded, the solver and any processes it started are killed.
+    #[clap(long, requires = "lia-solver", parse(try_from_str = parse_timeout))]
+    lia_solver_timeout: Option<Duration>,
+
+    /// How many times to retry the `lia_generic` solver if it times out or fails.
+    #[clap(long, requires = "lia-solver", default_value = "0")]
+    lia_solver_retries: usize,
+
+    /// The option used to give the `lia_generic` solver a different random seed on each retry, as
+    /// `<option>=<seed>`. The default is the option understood by cvc5. If this is empty, retries
+    /// run the solver with the same arguments.
+    #[clap(
+        long,
+        requires = "lia-solver",
+        allow_hyphen_values = true,
+        default_value = "--seed"
+    )]
+    lia_solver_seed_option: String,
+
+    /// How many levels of nested `lia_generic` steps in the solver proofs are validated by calling
+    /// the solver again.
+    #[clap(long, requires = "lia-solver", default_value = "2")]
+    lia_solver_max_depth: usize,
+
+    /// Check the `lia_generic` solver proofs strictly, rejecting those with unknown rules, holes,
+    /// or `lia_generic` steps past the depth limit.
+    #[clap(long, requires = "lia-solver")]
+    lia_solver_strict: bool,
+
     /// When uncrowding resolutions steps, also reorder premises to further minimize the number of
     /// `contraction` steps added.
     #[clap(long)]
@@ -242,6 +539,34 @@ struct ElaborationOptions {
         default_values = &["polyeq", "lia-generic", "local", "uncrowd", "reordering", "hole"]
     )]
     pipeline: Vec<ElaborationStep>,
+
+    /// The maximum number of steps that each elaboration pass can introduce when elaborating a
+    /// single step.
+    #[clap(long)]
+    max_growth: Option<usize>,
+
+    /// What to do when the elaboration of a step exceeds the limit given by `--max-growth`.
+    #[clap(arg_enum, long, requires = "max-growth", default_value = "warn")]
+    growth_policy: GrowthPolicy,
+    /// Give steps that were replaced during elaboration their original ids back whenever
+    /// possible.
+    #[clap(long)]
+    preserve_ids: bool,
+
+    /// Annotate each step introduced during elaboration with an `:origin` attribute, recording the
+    /// original step from which it was derived and the elaboration pass that introduced it.
+    #[clap(long)]
+    annotate_origins: bool,
+
+    /// After each elaboration pass, check every step it introduced by evaluating it under this
+    /// many random interpretations of its free symbols. This is meant for debugging the
+    /// elaborator, and may be slow.
+    #[clap(long)]
+    sanity_check_samples: Option<usize>,
+
+    /// The seed used to sample interpretations for `--sanity-check-samples`.
+    #[clap(long, requires = "sanity-check-samples", default_value = "0")]
+    sanity_check_seed: u64,
 }
 
 impl From<ElaborationOptions> for (elaborator::Config, Vec<elaborator::ElaborationStep>) {
@@ -256,6 +581,9 @@ impl From<ElaborationOptions> for (elaborator::Config, Vec<elaborator::Elaborati
                 ElaborationStep::Uncrowd => elaborator::ElaborationStep::Uncrowd,
                 ElaborationStep::Reordering => elaborator::ElaborationStep::Reordering,
                 ElaborationStep::Hole => elaborator::ElaborationStep::Hole,
+                ElaborationStep::Dedup => elaborator::ElaborationStep::Dedup,
+                ElaborationStep::MergeConclusions => elaborator::ElaborationStep::MergeConclusions,
+                ElaborationStep::NotNot => elaborator::ElaborationStep::NotNot,
             })
             .collect();
         let lia_options = val.lia_solver.map(|solver| elaborator::LiaGenericOptions {
@@ -265,6 +593,12 @@ impl From<ElaborationOptions> for (elaborator::Config, Vec<elaborator::Elaborati
                 .split_whitespace()
                 .map(Into::into)
                 .collect(),
+            timeout: val.lia_solver_timeout,
+            retries: val.lia_solver_retries,
+            seed_option: (!val.lia_solver_seed_option.is_empty())
+                .then(|| val.lia_solver_seed_option.into()),
+            max_depth: val.lia_solver_max_depth,
+            strict: val.lia_solver_strict,
         });
 
         let hole_options = val.hole_solver.map(|solver| elaborator::HoleOptions {
@@ -280,6 +614,19 @@ impl From<ElaborationOptions> for (elaborator::Config, Vec<elaborator::Elaborati
             lia_options,
             uncrowd_rotation: val.uncrowd_rotate,
             hole_options,
+            growth_limit: val.max_growth.map(|max_steps| elaborator::GrowthLimit {
+                max_steps,
+                policy: val.growth_policy.into(),
+            }),
+            term_limits: ast::TermLimits::new(),
+            preserve_ids: val.preserve_ids,
+            annotate_origins: val.annotate_origins,
+            sanity_check: val
+                .sanity_check_samples
+                .map(|samples| elaborator::SanityCheckOptions {
+                    samples,
+                    seed: val.sanity_check_seed,
+                }),
         };
         (config, pipeline)
     }
@@ -299,6 +646,40 @@ struct CheckCommandOptions {
     #[clap(flatten)]
     input: Input,
 
+    /// Additional proof files. If any are given, the first argument is interpreted as the problem
+    /// file, and all the others as proofs of it. The problem is parsed only once, and each proof is
+    /// checked in turn, printing a table with the result for each one. In this case, the
+    /// `--num-threads` option sets how many proofs are checked at the same time.
+    additional_proofs: Vec<String>,
+
+    /// Checks the proof without its original problem. All `assume` commands are trusted, and are
+    /// reported as such. Any sorts and functions used in the proof must be declared in the proof
+    /// file itself, using `declare-sort`, `declare-fun` or `declare-const` commands.
+    #[clap(long, conflicts_with_all = &["problem-file", "additional-proofs"])]
+    no_problem: bool,
+
+    /// Reads the problem from the proof file itself, for proofs in which the solver also printed
+    /// the problem's declarations and assertions before the proof. If the assertions are not
+    /// present, all `assume` commands are trusted.
+    #[clap(long, conflicts_with_all = &["problem-file", "additional-proofs", "no-problem"])]
+    embedded_problem: bool,
+
+    /// When checking without a problem, infers the declarations of all sorts and functions used in
+    /// the proof that are not declared in it, instead of requiring them to be declared.
+    #[clap(long, requires = "no-problem")]
+    infer_declarations: bool,
+
+    /// Checks each refutation in the proof separately. This is intended for proofs that
+    /// concatenate the refutations of several goals, and so have more than one step concluding the
+    /// empty clause. Each of these steps is checked together with its transitive premises, and a
+    /// table with the result for each one is printed. Steps that are not used by any refutation
+    /// are not checked.
+    #[clap(
+        long,
+        conflicts_with_all = &["additional-proofs", "no-problem", "json", "expect-fail"]
+    )]
+    split_refutations: bool,
+
     #[clap(flatten)]
     parsing: ParsingOptions,
 
@@ -322,8 +703,46 @@ struct CheckCommandOptions {
     #[clap(flatten)]
     stats: StatsOptions,
 
+    /// Prints how many steps used each rule, and which implemented rules were never used.
+    #[clap(long)]
+    rule_coverage: bool,
+
+    /// Emits a warning listing the declared sorts and functions, and the defined constants, that
+    /// are never used by the problem's assertions or by the proof.
+    #[clap(long)]
+    report_unused_declarations: bool,
+
+    /// Prints the literals of the problem's `check-sat-assuming` command that are used by the
+    /// proof, in the same format as the SMT-LIB `get-unsat-assumptions` command.
+    #[clap(long, conflicts_with = "no-problem")]
+    unsat_assumptions: bool,
+
+    /// Prints the result as a JSON object instead of plain text. If the proof is invalid, the
+    /// object includes the error, with its stable error code.
+    #[clap(long)]
+    json: bool,
+
+    /// Expects the proof to be invalid. If checking fails, the result is "invalid, as expected"
+    /// and Carcara exits successfully. If the proof is valid or holey, or if it fails in a
+    /// different way than expected by `--expect-fail-step` or `--expect-fail-code`, Carcara exits
+    /// with an error. This is useful for maintaining suites of known-bad proofs.
+    #[clap(long, conflicts_with_all = &["additional-proofs", "json"])]
+    expect_fail: bool,
+
+    /// With `--expect-fail`, the id of the step at which checking is expected to fail.
+    #[clap(long, requires = "expect-fail")]
+    expect_fail_step: Option<String>,
+
+    /// With `--expect-fail`, the error code with which checking is expected to fail, such as
+    /// `E0203`.
+    #[clap(long, requires = "expect-fail")]
+    expect_fail_code: Option<String>,
+
     #[clap(flatten)]
     stack: StackOptions,
+
+    #[clap(flatten)]
+    cache: CacheOptions,
 }
 
 #[derive(Args)]
@@ -340,6 +759,16 @@ struct ElaborateCommandOptions {
     #[clap(flatten)]
     elaboration: ElaborationOptions,
 
+    /// Writes a CSV file mapping the id of each command in the elaborated proof to the id of the
+    /// command in the original proof from which it was derived.
+    #[clap(long)]
+    id_map: Option<String>,
+
+    /// Emits a warning listing the declared sorts and functions, and the defined constants, that
+    /// are never used by the problem's assertions or by the elaborated proof.
+    #[clap(long)]
+    report_unused_declarations: bool,
+
     #[clap(flatten)]
     stats: StatsOptions,
 }
@@ -349,6 +778,11 @@ struct BenchCommandOptions {
     #[clap(flatten)]
     parsing: ParsingOptions,
 
+    /// Prints how many steps used each rule across all proofs, and which implemented rules were
+    /// never used.
+    #[clap(long)]
+    rule_coverage: bool,
+
     #[clap(flatten)]
     checking: CheckingOptions,
 
@@ -375,6 +809,15 @@ struct BenchCommandOptions {
     #[clap(long = "dump-to-csv")]
     dump_to_csv: bool,
 
+    /// Prints the environment of the benchmark: the Carcara version, the configuration, the host
+    /// machine and the hashes of the input files. When dumping to csv files, the environment is
+    /// always written to `environment.json`.
+    #[clap(long)]
+    print_environment: bool,
+
+    #[clap(flatten)]
+    cache: CacheOptions,
+
     /// The proof files on which the benchmark will be run. If a directory is passed, the checker
     /// will recursively find all proof files in the directory. The problem files will be
     /// inferred from the proof files.
@@ -409,6 +852,223 @@ struct SliceCommandOptions {
     hole_solver_args: Option<String>,
 }
 
+#[derive(Args)]
+struct AnonymizeCommandOptions {
+    #[clap(flatten)]
+    input: Input,
+
+    #[clap(flatten)]
+    parsing: ParsingOptions,
+
+    /// The file to which the anonymized problem will be written.
+    #[clap(long)]
+    problem_output: PathBuf,
+}
+
+#[derive(Args)]
+struct ShrinkCommandOptions {
+    #[clap(flatten)]
+    input: Input,
+
+    #[clap(flatten)]
+    parsing: ParsingOptions,
+
+    #[clap(flatten)]
+    checking: CheckingOptions,
+}
+
+#[derive(Args)]
+struct ContextCommandOptions {
+    #[clap(flatten)]
+    input: Input,
+
+    #[clap(flatten)]
+    parsing: ParsingOptions,
+
+    /// The id of the step whose context will be printed.
+    #[clap(long)]
+    step: String,
+}
+
+#[derive(Args)]
+struct ReportCommandOptions {
+    #[clap(flatten)]
+    input: Input,
+
+    #[clap(flatten)]
+    parsing: ParsingOptions,
+
+    #[clap(flatten)]
+    checking: CheckingOptions,
+
+    /// The file to which the HTML report is written.
+    #[clap(short, long)]
+    output: PathBuf,
+
+    /// The title of the report. By default, this is the name of the proof file.
+    #[clap(long)]
+    title: Option<String>,
+}
+
+#[derive(Args)]
+struct ExplainCommandOptions {
+    /// The error code to explain, such as `E0203`.
+    code: Option<String>,
+}
+
+#[derive(Args)]
+struct ServeCommandOptions {
+    /// The address on which to listen for requests.
+    #[clap(long, default_value = "127.0.0.1:9090")]
+    listen: String,
+
+    /// The directory containing the problems and proofs that may be checked. Paths in requests are
+    /// resolved relative to it, and requests for files outside of it are rejected.
+    #[clap(long)]
+    root: PathBuf,
+
+    /// The number of requests that are handled at the same time.
+    #[clap(long, default_value = "4", validator = |s: &str| -> Result<(), String> {
+        match s.parse::<usize>() {
+            Ok(0) => Err("The number of workers can't be 0.".to_owned()),
+            Ok(_) => Ok(()),
+            Err(_) => Err(String::from("Not a number.")),
+        }
+    })]
+    num_workers: usize,
+
+    #[clap(flatten)]
+    parsing: ParsingOptions,
+
+    #[clap(flatten)]
+    checking: CheckingOptions,
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+enum FeaturesFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Args)]
+struct FeaturesCommandOptions {
+    #[clap(flatten)]
+    input: Input,
+
+    #[clap(flatten)]
+    parsing: ParsingOptions,
+
+    /// The format in which the features are printed.
+    #[clap(arg_enum, long, default_value = "json")]
+    format: FeaturesFormat,
+
+    /// When printing the features as CSV, don't print the header line with the feature names.
+    #[clap(long)]
+    no_header: bool,
+}
+
+#[derive(Args)]
+struct LemmasCommandOptions {
+    #[clap(flatten)]
+    input: Input,
+
+    #[clap(flatten)]
+    parsing: ParsingOptions,
+
+    /// Only extract the conclusions of steps that use one of these rules, for example
+    /// `lia_generic` or `forall_inst`. By default, the conclusions of all steps are extracted.
+    #[clap(long, multiple = true)]
+    rules: Vec<String>,
+}
+
+#[derive(Args)]
+struct SynthesizePreludeCommandOptions {
+    /// The proof file from which to infer the declarations.
+    proof_file: String,
+
+    #[clap(flatten)]
+    parsing: ParsingOptions,
+}
+
+#[derive(Args)]
+struct CheckModelCommandOptions {
+    /// The original problem file.
+    problem_file: String,
+
+    /// The model file, in the format produced by the `get-model` command. It may start with the
+    /// `sat` response of the solver.
+    model_file: String,
+
+    #[clap(flatten)]
+    parsing: ParsingOptions,
+
+    /// Bounds used to evaluate quantified assertions, given as `<sort>=<n>`. Quantified variables
+    /// of sort `Int` or `Real` with bound `n` range over the integers from `-n` to `n`, while
+    /// variables of uninterpreted sorts range over the first `n` elements declared in the model.
+    /// If the bounds are insufficient to evaluate an assertion, its status is reported as unknown.
+    #[clap(long, multiple = true, validator = |s: &str| parse_quantifier_bound(s).map(|_| ()))]
+    quantifier_bound: Vec<String>,
+}
+
+fn parse_quantifier_bound(s: &str) -> Result<(String, usize), String> {
+    let (sort, n) = s
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected `<sort>=<n>`, got '{}'", s))?;
+    let n = n.parse().map_err(|_| format!("invalid bound: '{}'", n))?;
+    Ok((sort.to_owned(), n))
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+enum OutputDialect {
+    #[clap(name = "alethe-1.2")]
+    Alethe12,
+    VeritLegacy,
+    Cvc5,
+}
+
+impl From<OutputDialect> for ast::OutputDialect {
+    fn from(d: OutputDialect) -> Self {
+        match d {
+            OutputDialect::Alethe12 => Self::Alethe12,
+            OutputDialect::VeritLegacy => Self::VeritLegacy,
+            OutputDialect::Cvc5 => Self::Cvc5,
+        }
+    }
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+enum StringsProfile {
+    Standard,
+    Cvc5,
+}
+
+impl From<StringsProfile> for parser::StringsProfile {
+    fn from(p: StringsProfile) -> Self {
+        match p {
+            StringsProfile::Standard => Self::Standard,
+            StringsProfile::Cvc5 => Self::Cvc5,
+        }
+    }
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+enum AletheVersion {
+    Auto,
+    Legacy,
+    #[clap(name = "1.2")]
+    V1_2,
+}
+
+impl From<AletheVersion> for Option<parser::AletheVersion> {
+    fn from(v: AletheVersion) -> Self {
+        match v {
+            AletheVersion::Auto => None,
+            AletheVersion::Legacy => Some(parser::AletheVersion::Legacy),
+            AletheVersion::V1_2 => Some(parser::AletheVersion::V1_2),
+        }
+    }
+}
+
 #[derive(ArgEnum, Clone)]
 enum LogLevel {
     Off,
@@ -429,7 +1089,17 @@ impl From<LogLevel> for log::LevelFilter {
 }
 
 fn main() {
-    let cli = Cli::parse();
+    let cli = match config::apply_config_file(&Cli::command(), std::env::args_os().collect()) {
+        Ok(args) => {
+            let strings = args.iter().map(|a| a.to_string_lossy().into_owned());
+            COMMAND_LINE.get_or_init(|| strings.collect());
+            Cli::parse_from(args)
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    };
     let colors_enabled = !cli.no_color && std::io::stderr().is_terminal();
 
     ast::USE_SHARING_IN_TERM_DISPLAY.store(!cli.no_print_with_sharing, atomic::Ordering::Relaxed);
@@ -448,17 +1118,90 @@ fn main() {
         }
     }
 
+    let use_sharing = !cli.no_print_with_sharing;
+    let dialect = cli.output_dialect.into();
+    let min_live_order = cli.min_live_order;
     let result = match cli.command {
         Command::Parse(options) => parse_command(options).and_then(|(pb, pf, mut pool)| {
-            ast::print_proof(&mut pool, &pb.prelude, &pf, !cli.no_print_with_sharing)?;
+            print_proof(
+                &mut pool,
+                &pb.prelude,
+                pf,
+                use_sharing,
+                dialect,
+                min_live_order,
+            )?;
             Ok(())
         }),
+        Command::ExportTptp(options) => parse_command(options).and_then(|(pb, pf, mut pool)| {
+            ast::write_tptp_proof(&mut pool, &pb.prelude, &pf, &mut io::stdout())?;
+            Ok(())
+        }),
+        Command::ImportLegacy(options) => {
+            import_legacy_command(options).and_then(|(pb, pf, mut pool)| {
+                print_proof(
+                    &mut pool,
+                    &pb.prelude,
+                    pf,
+                    use_sharing,
+                    dialect,
+                    min_live_order,
+                )?;
+                Ok(())
+            })
+        }
+        Command::Check(options) if !options.additional_proofs.is_empty() => {
+            match check_multiple_command(options) {
+                Ok(true) => (),
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    report_error(&e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Command::Check(options) if options.split_refutations => {
+            match check_refutations_command(options) {
+                Ok(true) => (),
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    report_error(&e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Command::Check(options) if options.expect_fail => {
+            match check_expect_fail_command(options) {
+                Ok(true) => println!("invalid, as expected"),
+                Ok(false) => {
+                    println!("unexpected result");
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    report_error(&e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Command::Check(options) if options.json => {
+            let result = check_command(options);
+            if let Err(e) = print_json_result(&result) {
+                log::error!("{}", e);
+            }
+            if result.is_err() {
+                std::process::exit(1);
+            }
+            return;
+        }
         Command::Check(options) => {
             match check_command(options) {
                 Ok(false) => println!("valid"),
                 Ok(true) => println!("holey"),
                 Err(e) => {
-                    log::error!("{}", e);
+                    report_error(&e);
                     println!("invalid");
                     std::process::exit(1);
                 }
@@ -472,25 +1215,157 @@ fn main() {
                 } else {
                     println!("valid");
                 }
-                ast::print_proof(&mut pool, &pb.prelude, &pf, !cli.no_print_with_sharing)?;
+                print_proof(
+                    &mut pool,
+                    &pb.prelude,
+                    pf,
+                    use_sharing,
+                    dialect,
+                    min_live_order,
+                )?;
                 Ok(())
             })
         }
         Command::Bench(options) => bench_command(options),
         Command::Slice(options) => slice_command(options).and_then(|(pb, pf, mut pool)| {
-            ast::print_proof(&mut pool, &pb.prelude, &pf, !cli.no_print_with_sharing)?;
+            print_proof(
+                &mut pool,
+                &pb.prelude,
+                pf,
+                use_sharing,
+                dialect,
+                min_live_order,
+            )?;
+            Ok(())
+        }),
+        Command::Anonymize(options) => anonymize_command(options).and_then(|(pb, pf, mut pool)| {
+            print_proof(
+                &mut pool,
+                &pb.prelude,
+                pf,
+                use_sharing,
+                dialect,
+                min_live_order,
+            )?;
             Ok(())
         }),
+        Command::Shrink(options) => shrink_command(options).and_then(|(pb, pf, mut pool)| {
+            print_proof(
+                &mut pool,
+                &pb.prelude,
+                pf,
+                use_sharing,
+                dialect,
+                min_live_order,
+            )?;
+            Ok(())
+        }),
+        Command::Context(options) => context_command(options),
+        Command::CheckModel(options) => {
+            match check_model_command(options) {
+                Ok(true) => println!("valid"),
+                Ok(false) => println!("unknown"),
+                Err(e) => {
+                    log::error!("{}", e);
+                    println!("invalid");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Command::Report(options) => {
+            match report_command(options) {
+                Ok(Ok(false)) => println!("valid"),
+                Ok(Ok(true)) => println!("holey"),
+                Ok(Err(e)) => {
+                    report_error(&e.into());
+                    println!("invalid");
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    report_error(&e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Command::SynthesizePrelude(options) => synthesize_prelude_command(options),
+        Command::Features(options) => features_command(options),
+        Command::Explain(options) => explain_command(options),
+        Command::Serve(options) => {
+            // Clients could otherwise make the server read arbitrary files
+            if options.checking.allow_certificate_files {
+                log::warn!("the `--allow-certificate-files` option is ignored by the server");
+            }
+            server::serve(
+                options.listen,
+                &options.root,
+                options.num_workers,
+                options.parsing.into(),
+                options.checking.into(),
+            )
+            .map_err(Into::into)
+        }
         Command::GenerateLiaProblems(options) => {
-            generate_lia_problems_command(options, !cli.no_print_with_sharing)
+            generate_lia_problems_command(options, use_sharing)
         }
+        Command::Lemmas(options) => lemmas_command(options, use_sharing),
     };
     if let Err(e) = result {
-        log::error!("{}", e);
+        report_error(&e);
         std::process::exit(1);
     }
 }
 
+/// Logs an error, prefixed by its error code if it has one.
+fn report_error(e: &CliError) {
+    match e.code() {
+        Some(code) => log::error!("{}: {}", code, e),
+        None => log::error!("{}", e),
+    }
+}
+
+/// Prints the result of checking a proof as a JSON object.
+fn print_json_result(result: &CliResult<bool>) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    match result {
+        Ok(is_holey) => {
+            let result = if *is_holey { "holey" } else { "valid" };
+            writeln!(stdout, "{{\"result\": \"{}\"}}", result)
+        }
+        Err(e) => {
+            write!(stdout, "{{\"result\": \"invalid\", \"error\": ")?;
+            match e {
+                CliError::CarcaraError(e) => e.write_json(&mut stdout)?,
+                e => write!(
+                    stdout,
+                    "{{\"code\": null, \"message\": {}}}",
+                    JsonString(&e.to_string())
+                )?,
+            }
+            writeln!(stdout, "}}")
+        }
+    }
+}
+
+fn print_proof(
+    pool: &mut ast::PrimitivePool,
+    prelude: &ast::ProblemPrelude,
+    proof: ast::Proof,
+    use_sharing: bool,
+    dialect: ast::OutputDialect,
+    min_live_order: bool,
+) -> CliResult<()> {
+    let proof = if min_live_order {
+        let commands = ast::ProofNode::from_commands(proof.commands).into_commands_min_live();
+        ast::Proof { commands, ..proof }
+    } else {
+        proof
+    };
+    ast::print_proof(pool, prelude, &proof, use_sharing, dialect)?;
+    Ok(())
+}
+
 fn get_instance(options: &Input) -> CliResult<(Box<dyn BufRead>, Box<dyn BufRead>)> {
     fn reader_from_path<P: AsRef<Path>>(path: P) -> CliResult<Box<dyn BufRead>> {
         Ok(Box::new(io::BufReader::new(File::open(path)?)))
@@ -517,14 +1392,340 @@ fn parse_command(
     Ok(result)
 }
 
-fn check_command(options: CheckCommandOptions) -> CliResult<bool> {
+fn import_legacy_command(
+    options: ParseCommandOptions,
+) -> CliResult<(ast::Problem, ast::Proof, ast::PrimitivePool)> {
     let (problem, proof) = get_instance(&options.input)?;
+    let result = parser::parse_legacy_instance(problem, proof, options.parsing.into())?;
+    Ok(result)
+}
+
+fn check_command(options: CheckCommandOptions) -> CliResult<bool> {
+    if options.no_problem || options.embedded_problem {
+        return check_without_problem_command(options);
+    }
+
+    let (mut problem, mut proof) = get_instance(&options.input)?;
     let parser_config = options.parsing.into();
-    let checker_config = options.checking.into();
+    let checker_config = options
+        .checking
+        .clone()
+        .config_for(&options.input.proof_file);
+
+    if options.rule_coverage || options.report_unused_declarations || options.unsat_assumptions {
+        let (mut problem_bytes, mut proof_bytes) = (Vec::new(), Vec::new());
+        problem.read_to_end(&mut problem_bytes)?;
+        proof.read_to_end(&mut proof_bytes)?;
+
+        let (parsed_problem, parsed, _) = parser::parse_instance(
+            problem_bytes.as_slice(),
+            proof_bytes.as_slice(),
+            parser_config,
+        )?;
+        if options.rule_coverage {
+            let mut coverage = checker::RuleCoverage::new();
+            coverage.add_proof(&parsed);
+            print!("{}", coverage);
+        }
+        if options.report_unused_declarations {
+            report_unused_declarations(&ast::find_unused_declarations(&parsed_problem, &parsed));
+        }
+        if options.unsat_assumptions {
+            let core = ast::assumption_core(&parsed_problem, &parsed);
+            let core: Vec<_> = core.iter().map(ToString::to_string).collect();
+            println!("({})", core.join(" "));
+        }
+
+        problem = Box::new(io::Cursor::new(problem_bytes));
+        proof = Box::new(io::Cursor::new(proof_bytes));
+    }
+
+    // If we are tracing steps or collecting statistics, we can't use a cached result, since the
+    // steps must be checked again to be traced or measured
+    let use_cache = options.checking.trace.is_none() && !options.stats.stats;
+    let result = match &options.cache.result_cache {
+        Some(dir) if use_cache => {
+            let cache = cache::ResultCache::open(dir, VERSION_STRING)?;
+            let (mut problem_bytes, mut proof_bytes) = (Vec::new(), Vec::new());
+            problem.read_to_end(&mut problem_bytes)?;
+            proof.read_to_end(&mut proof_bytes)?;
+            let key = cache::CacheKey::new(
+                &problem_bytes,
+                &proof_bytes,
+                &cache::describe_config(&parser_config, &checker_config, ""),
+                checker_config.certificate_dir.as_deref(),
+            );
+            if let Some(cached) = cache.get(&key) {
+                log::info!(
+                    "using cached result (originally checked in {:?})",
+                    cached.measurement.total
+                );
+                cached.is_holey
+            } else {
+                let time = std::time::Instant::now();
+                let is_holey = check_instance(
+                    Box::new(problem_bytes.as_slice()),
+                    Box::new(proof_bytes.as_slice()),
+                    parser_config,
+                    checker_config.clone(),
+                    &options,
+                )?;
+                let total = time.elapsed();
+                let result = cache::CachedResult {
+                    is_holey,
+                    measurement: RunMeasurement { total, ..Default::default() },
+                };
+                if let Err(e) = cache.insert(&key, &result) {
+                    log::warn!("failed to write to result cache: {}", e);
+                }
+                is_holey
+            }
+        }
+        _ => check_instance(
+            problem,
+            proof,
+            parser_config,
+            checker_config.clone(),
+            &options,
+        )?,
+    };
+    if options.stats.print_environment {
+        let configuration: [(_, &dyn fmt::Debug); 2] =
+            [("parsing", &parser_config), ("checking", &checker_config)];
+        let paths = input_paths(&options.input)?;
+        print!("{}", environment::capture(&paths, &configuration)?);
+    }
+    Ok(result)
+}
+
+/// Checks a proof that is expected to be invalid. Returns `true` if checking fails at the expected
+/// step and with the expected error code, if they were given. Errors that don't come from parsing
+/// or checking the proof, like failing to read the input files, are returned as errors.
+fn check_expect_fail_command(options: CheckCommandOptions) -> CliResult<bool> {
+    let expected_code = match &options.expect_fail_code {
+        Some(code) => Some(
+            code.parse::<ErrorCode>()
+                .map_err(|()| CliError::UnknownErrorCode(code.clone()))?,
+        ),
+        None => None,
+    };
+    let expected_step = options.expect_fail_step.clone();
+
+    let error = match check_command(options) {
+        Ok(is_holey) => {
+            let result = if is_holey { "holey" } else { "valid" };
+            log::error!("proof was expected to be invalid, but it is {}", result);
+            return Ok(false);
+        }
+        Err(e @ CliError::CarcaraError(carcara::Error::Io(_))) => return Err(e),
+        Err(CliError::CarcaraError(e)) => e,
+        Err(e) => return Err(e),
+    };
+    log::info!("{}: {}", error.code(), error);
+
+    let mut as_expected = true;
+    if let Some(expected) = expected_step {
+        match &error {
+            carcara::Error::Checker { step, .. } if *step == expected => (),
+            carcara::Error::Checker { step, .. } => {
+                log::error!(
+                    "expected checking to fail on step '{}', but it failed on step '{}'",
+                    expected,
+                    step
+                );
+                as_expected = false;
+            }
+            _ => {
+                log::error!(
+                    "expected checking to fail on step '{}', but it failed with: {}",
+                    expected,
+                    error
+                );
+                as_expected = false;
+            }
+        }
+    }
+    if let Some(expected) = expected_code {
+        if error.code() != expected {
+            log::error!(
+                "expected error code {}, but got {}: {}",
+                expected,
+                error.code(),
+                error
+            );
+            as_expected = false;
+        }
+    }
+    Ok(as_expected)
+}
+
+/// Checks a proof without a separate problem file. Unless the problem is embedded in the proof
+/// file, all of its assumptions are trusted.
+fn check_without_problem_command(options: CheckCommandOptions) -> CliResult<bool> {
+    if options.stats.stats || options.num_threads > 1 || options.cache.result_cache.is_some() {
+        log::warn!(
+            "the `--stats`, `--num-threads` and `--result-cache` options are ignored when \
+            checking without a problem"
+        );
+    }
+
+    let proof: Box<dyn BufRead> = match options.input.proof_file.as_str() {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(io::BufReader::new(File::open(path)?)),
+    };
+    let (problem, proof, mut pool) = if options.embedded_problem {
+        parser::parse_embedded_instance(proof, options.parsing.into())?
+    } else if options.infer_declarations {
+        parser::parse_proof_with_synthesized_prelude(proof, options.parsing.into())?
+    } else {
+        parser::parse_proof_without_problem(proof, options.parsing.into())?
+    };
+
+    if options.rule_coverage {
+        let mut coverage = checker::RuleCoverage::new();
+        coverage.add_proof(&proof);
+        print!("{}", coverage);
+    }
+    if options.report_unused_declarations {
+        report_unused_declarations(&ast::find_unused_declarations(&problem, &proof));
+    }
+
+    // When the problem is embedded, the parser already warns if the assumptions are trusted
+    if !options.embedded_problem {
+        let trusted: Vec<_> = proof
+            .commands
+            .iter()
+            .filter(|c| matches!(c, ast::ProofCommand::Assume { .. }))
+            .map(ast::ProofCommand::id)
+            .collect();
+        log::warn!(
+            "no problem was given, so {} assumption(s) were trusted without being checked",
+            trusted.len()
+        );
+        if !trusted.is_empty() {
+            log::warn!("trusted assumptions: {}", trusted.join(", "));
+        }
+    }
+
+    let checker_config = options.checking.config_for(&options.input.proof_file);
+    let mut checker = checker::ProofChecker::new(&mut pool, checker_config);
+    Ok(checker.check(&problem, &proof)?)
+}
+
+/// Checks several proofs of the same problem, and prints a table with the result for each one.
+/// Returns `true` if all proofs are valid or holey.
+fn check_multiple_command(options: CheckCommandOptions) -> CliResult<bool> {
+    if options.stats.stats
+        || options.rule_coverage
+        || options.report_unused_declarations
+        || options.cache.result_cache.is_some()
+        || options.checking.allow_certificate_files
+    {
+        log::warn!(
+            "the `--stats`, `--rule-coverage`, `--report-unused-declarations`, `--result-cache` \
+            and `--allow-certificate-files` options are ignored when checking multiple proofs"
+        );
+    }
+
+    let problem_file = &options.input.proof_file;
+    let proof_files: Vec<&str> = options
+        .input
+        .problem_file
+        .iter()
+        .chain(&options.additional_proofs)
+        .map(String::as_str)
+        .collect();
+
+    let open = |path: &str| -> CliResult<Box<dyn BufRead>> {
+        if path == "-" {
+            return Err(CliError::MultipleProofsStdin);
+        }
+        Ok(Box::new(io::BufReader::new(File::open(path)?)))
+    };
+    let problem = open(problem_file)?;
+    let proofs = proof_files
+        .iter()
+        .map(|&path| open(path))
+        .collect::<CliResult<_>>()?;
+
+    let results = check_multiple(
+        problem,
+        proofs,
+        options.parsing.into(),
+        options.checking.into(),
+        options.num_threads,
+        options.stack.stack_size,
+    )?;
+
+    let width = proof_files.iter().map(|p| p.len()).max().unwrap_or(0);
+    let mut all_passed = true;
+    for (path, result) in proof_files.iter().zip(results) {
+        let verdict = match result {
+            Ok(false) => "valid",
+            Ok(true) => "holey",
+            Err(e) => {
+                log::error!("{}: {}: {}", path, e.code(), e);
+                all_passed = false;
+                "invalid"
+            }
+        };
+        println!("{:<width$}  {}", path, verdict);
+    }
+    Ok(all_passed)
+}
+
+fn check_refutations_command(options: CheckCommandOptions) -> CliResult<bool> {
+    if options.stats.stats
+        || options.rule_coverage
+        || options.report_unused_declarations
+        || options.cache.result_cache.is_some()
+        || options.num_threads > 1
+    {
+        log::warn!(
+            "the `--stats`, `--rule-coverage`, `--report-unused-declarations`, `--result-cache` \
+            and `--num-threads` options are ignored when checking refutations separately"
+        );
+    }
+
+    let (problem, proof) = get_instance(&options.input)?;
+    let results = check_refutations(
+        problem,
+        proof,
+        options.parsing.into(),
+        options.checking.config_for(&options.input.proof_file),
+    )?;
+
+    let width = results.iter().map(|(id, _)| id.len()).max().unwrap_or(0);
+    let mut all_passed = true;
+    for (id, result) in results {
+        let verdict = match result {
+            Ok(false) => "valid",
+            Ok(true) => "holey",
+            Err(e) => {
+                log::error!("{}: {}: {}", id, e.code(), e);
+                all_passed = false;
+                "invalid"
+            }
+        };
+        println!("{:<width$}  {}", id, verdict);
+    }
+    Ok(all_passed)
+}
+
+fn check_instance(
+    problem: Box<dyn BufRead + '_>,
+    proof: Box<dyn BufRead + '_>,
+    parser_config: parser::Config,
+    checker_config: checker::Config,
+    options: &CheckCommandOptions,
+) -> CliResult<bool> {
     let collect_stats = options.stats.stats;
     if options.num_threads == 1 {
         check(problem, proof, parser_config, checker_config, collect_stats)
     } else {
+        if checker_config.trace.is_some() {
+            log::warn!("tracing is not supported when checking with multiple threads");
+        }
         check_parallel(
             problem,
             proof,
@@ -543,17 +1744,62 @@ fn elaborate_command(
 ) -> CliResult<(bool, ast::Problem, ast::Proof, ast::PrimitivePool)> {
     let (problem, proof) = get_instance(&options.input)?;
 
-    let (elab_config, pipeline) = options.elaboration.into();
-    check_and_elaborate(
+    let parser_config: parser::Config = options.parsing.into();
+    let checking_config = options.checking.config_for(&options.input.proof_file);
+    let (mut elab_config, pipeline): (elaborator::Config, _) = options.elaboration.into();
+    elab_config.term_limits = checking_config.term_limits;
+
+    // The environment is captured before running, since the configuration is consumed by it
+    let environment = if options.stats.print_environment {
+        let configuration: [(_, &dyn fmt::Debug); 4] = [
+            ("parsing", &parser_config),
+            ("checking", &checking_config),
+            ("elaboration", &elab_config),
+            ("pipeline", &pipeline),
+        ];
+        let paths = input_paths(&options.input)?;
+        Some(environment::capture(&paths, &configuration)?)
+    } else {
+        None
+    };
+    let (res, problem, proof, pool, id_map) = check_and_elaborate(
         problem,
         proof,
-        options.parsing.into(),
-        options.checking.into(),
+        parser_config,
+        checking_config,
         elab_config,
         pipeline,
         options.stats.stats,
-    )
-    .map_err(CliError::CarcaraError)
+    )?;
+    if let Some(environment) = environment {
+        print!("{}", environment);
+    }
+
+    if let Some(path) = options.id_map {
+        let mut file = io::BufWriter::new(File::create(path)?);
+        writeln!(file, "elaborated_id,original_id")?;
+        for (elaborated, original) in id_map {
+            writeln!(file, "{},{}", elaborated, original)?;
+        }
+    }
+
+    if options.report_unused_declarations {
+        report_unused_declarations(&ast::find_unused_declarations(&problem, &proof));
+    }
+    Ok((res, problem, proof, pool))
+}
+
+fn report_unused_declarations(unused: &ast::UnusedDeclarations) {
+    let lists = [
+        ("sorts", &unused.sorts),
+        ("functions", &unused.functions),
+        ("constant definitions", &unused.definitions),
+    ];
+    for (kind, names) in lists {
+        if !names.is_empty() {
+            log::warn!("unused {}: {}", kind, names.join(", "));
+        }
+    }
 }
 
 fn bench_command(options: BenchCommandOptions) -> CliResult<()> {
@@ -563,23 +1809,70 @@ fn bench_command(options: BenchCommandOptions) -> CliResult<()> {
         return Ok(());
     }
 
+    if options.rule_coverage {
+        let mut coverage = checker::RuleCoverage::new();
+        for (problem, proof) in &instances {
+            let (_, proof, _) = parser::parse_instance(
+                io::BufReader::new(File::open(problem)?),
+                io::BufReader::new(File::open(proof)?),
+                options.parsing.into(),
+            )?;
+            coverage.add_proof(&proof);
+        }
+        print!("{}", coverage);
+    }
+
     log::info!(
         "running benchmark on {} files, doing {} runs each",
         instances.len(),
         options.num_runs
     );
 
+    let cache = options
+        .cache
+        .result_cache
+        .as_ref()
+        .map(|dir| cache::ResultCache::open(dir, VERSION_STRING))
+        .transpose()?;
+
+    let parser_config: parser::Config = options.parsing.into();
+    if options.checking.allow_certificate_files {
+        log::warn!("the `--allow-certificate-files` option is ignored when benchmarking");
+    }
+    let checker_config: checker::Config = options.checking.into();
+    let elaborator_config: Option<(elaborator::Config, _)> =
+        options.elaborate.then(|| options.elaboration.into());
+
+    // The environment is captured before running, since the configuration is consumed by it
+    let environment = if options.dump_to_csv || options.print_environment {
+        let configuration: [(_, &dyn fmt::Debug); 3] = [
+            ("parsing", &parser_config),
+            ("checking", &checker_config),
+            ("elaboration", &elaborator_config),
+        ];
+        let files: Vec<_> = instances.iter().flat_map(|(pb, pf)| [pb, pf]).collect();
+        Some(environment::capture(&files, &configuration)?)
+    } else {
+        None
+    };
+
     if options.dump_to_csv {
         benchmarking::run_csv_benchmark(
             &instances,
             options.num_runs,
             options.num_jobs,
-            options.parsing.into(),
-            options.checking.into(),
-            options.elaborate.then(|| options.elaboration.into()),
+            parser_config,
+            checker_config,
+            elaborator_config,
+            cache.as_ref(),
             &mut File::create("runs.csv")?,
             &mut File::create("steps.csv")?,
         )?;
+        let mut file = File::create("environment.json")?;
+        if let Some(environment) = environment {
+            environment.write_json(&mut file)?;
+        }
+        writeln!(file)?;
         return Ok(());
     }
 
@@ -587,9 +1880,10 @@ fn bench_command(options: BenchCommandOptions) -> CliResult<()> {
         &instances,
         options.num_runs,
         options.num_jobs,
-        options.parsing.into(),
-        options.checking.into(),
-        options.elaborate.then(|| options.elaboration.into()),
+        parser_config,
+        checker_config,
+        elaborator_config,
+        cache.as_ref(),
     );
     if results.is_empty() {
         println!("no benchmark data collected");
@@ -604,9 +1898,22 @@ fn bench_command(options: BenchCommandOptions) -> CliResult<()> {
         println!("valid");
     }
     results.print(options.sort_by_total);
+    if let Some(environment) = environment {
+        print!("{}", environment);
+    }
     Ok(())
 }
 
+/// Returns the paths of the problem and proof files, inferring the problem file if necessary.
+fn input_paths(input: &Input) -> CliResult<Vec<PathBuf>> {
+    let problem = match &input.problem_file {
+        Some(path) => PathBuf::from(path),
+        None if input.proof_file == "-" => return Ok(Vec::new()),
+        None => infer_problem_path(&input.proof_file)?,
+    };
+    Ok(vec![problem, PathBuf::from(&input.proof_file)])
+}
+
 fn slice_command(
     options: SliceCommandOptions,
 ) -> CliResult<(ast::Problem, ast::Proof, ast::PrimitivePool)> {
@@ -624,6 +1931,223 @@ fn slice_command(
     Ok((problem, sliced, pool))
 }
 
+fn anonymize_command(
+    options: AnonymizeCommandOptions,
+) -> CliResult<(ast::Problem, ast::Proof, ast::PrimitivePool)> {
+    use std::io::Write;
+
+    let (problem, proof) = get_instance(&options.input)?;
+    let (problem, proof, mut pool) =
+        parser::parse_instance(problem, proof, options.parsing.into())?;
+
+    let (problem, proof) = ast::anonymize(&mut pool, &problem, &proof);
+    write!(File::create(options.problem_output)?, "{}", problem)?;
+    Ok((problem, proof, pool))
+}
+
+fn shrink_command(
+    options: ShrinkCommandOptions,
+) -> CliResult<(ast::Problem, ast::Proof, ast::PrimitivePool)> {
+    let (problem, proof) = get_instance(&options.input)?;
+    let (problem, proof, mut pool) =
+        parser::parse_instance(problem, proof, options.parsing.into())?;
+
+    // While shrinking, the checker may panic many times, so we silence the default panic messages
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let shrunk = shrink::shrink_proof(
+        &mut pool,
+        &options.checking.config_for(&options.input.proof_file),
+        &problem,
+        &proof,
+    );
+    std::panic::set_hook(default_hook);
+
+    let shrunk = shrunk.ok_or(CliError::NothingToShrink)?;
+    Ok((problem, shrunk, pool))
+}
+
+fn synthesize_prelude_command(options: SynthesizePreludeCommandOptions) -> CliResult<()> {
+    let proof: Box<dyn BufRead> = match options.proof_file.as_str() {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(io::BufReader::new(File::open(path)?)),
+    };
+    print!(
+        "{}",
+        parser::synthesize_prelude(proof, options.parsing.into())?
+    );
+    Ok(())
+}
+
+fn features_command(options: FeaturesCommandOptions) -> CliResult<()> {
+    let (problem, proof) = get_instance(&options.input)?;
+    let parser_config: parser::Config = options.parsing.into();
+    let (_, proof, pool) = parser::parse_instance(problem, proof, parser_config)?;
+
+    let features = ProofFeatures::new(&pool, &proof);
+    let mut stdout = io::stdout().lock();
+    match options.format {
+        FeaturesFormat::Json => {
+            let configuration: [(_, &dyn fmt::Debug); 1] = [("parsing", &parser_config)];
+            let environment = environment::capture(&input_paths(&options.input)?, &configuration)?;
+            features.write_json(&mut stdout, Some(&environment))?;
+        }
+        FeaturesFormat::Csv => features.write_csv(&mut stdout, !options.no_header)?,
+    }
+    Ok(())
+}
+
+/// Checks a proof and writes an HTML report for it. If the proof can't be parsed or the report
+/// can't be written, this returns an error. Otherwise, this returns the result of checking the
+/// proof, which is also included in the report.
+fn report_command(options: ReportCommandOptions) -> CliResult<carcara::CarcaraResult<bool>> {
+    let (problem, proof) = get_instance(&options.input)?;
+    let parser_config: parser::Config = options.parsing.into();
+    let checker_config = options.checking.config_for(&options.input.proof_file);
+    let (problem, proof, mut pool) = parser::parse_instance(problem, proof, parser_config)?;
+
+    let mut stats = checker::CheckerStatistics {
+        file_name: &options.input.proof_file,
+        polyeq_time: Duration::ZERO,
+        assume_time: Duration::ZERO,
+        assume_core_time: Duration::ZERO,
+        polyeq_cache: Default::default(),
+        results: StepTimes::new(),
+    };
+    let result = checker::ProofChecker::new(&mut pool, checker_config.clone())
+        .check_with_stats(&problem, &proof, &mut stats);
+
+    let configuration: [(_, &dyn fmt::Debug); 2] =
+        [("parsing", &parser_config), ("checking", &checker_config)];
+    let environment = environment::capture(&input_paths(&options.input)?, &configuration)?;
+    let title = options
+        .title
+        .unwrap_or_else(|| format!("Proof report: {}", options.input.proof_file));
+    let mut file = io::BufWriter::new(File::create(&options.output)?);
+    HtmlReport::new(&proof)
+        .title(title)
+        .step_times(&stats.results)
+        .error(result.as_ref().err())
+        .environment(&environment)
+        .write(&mut file)?;
+    file.flush()?;
+    Ok(result)
+}
+
+fn context_command(options: ContextCommandOptions) -> CliResult<()> {
+    let (problem, proof) = get_instance(&options.input)?;
+    let (_, proof, mut pool) = parser::parse_instance(problem, proof, options.parsing.into())?;
+
+    let context = ast::context_at(&mut pool, &proof.commands, &options.step)
+        .ok_or_else(|| CliError::UnknownStepId(options.step))?;
+
+    println!("anchors: {}", context.anchors.join(", "));
+    let bound: Vec<_> = context
+        .bound_variables
+        .iter()
+        .map(|(name, sort)| format!("({} {})", name, sort))
+        .collect();
+    println!("bound variables: {}", bound.join(" "));
+    println!("substitution:");
+    for (var, value) in context.substitution.iter() {
+        println!("    {} -> {}", var, value);
+    }
+    Ok(())
+}
+
+/// Returns `true` if all assertions are satisfied by the model, and `false` if some could not be
+/// evaluated.
+fn check_model_command(options: CheckModelCommandOptions) -> CliResult<bool> {
+    let problem = io::BufReader::new(File::open(options.problem_file)?);
+    let model = io::BufReader::new(File::open(options.model_file)?);
+
+    // The model usually doesn't include the functions defined in the problem, so we always expand
+    // their definitions
+    let config = parser::Config {
+        apply_function_defs: true,
+        ..options.parsing.into()
+    };
+    let (problem, model, _) = parser::parse_problem_and_model(problem, model, config)?;
+    let bounds = options
+        .quantifier_bound
+        .iter()
+        .map(|s| parse_quantifier_bound(s).unwrap())
+        .collect();
+
+    let mut num_falsified = 0;
+    let mut all_satisfied = true;
+    for (assertion, status) in model::check_model(&problem, &model, bounds) {
+        match status {
+            model::AssertionStatus::Satisfied => (),
+            model::AssertionStatus::Falsified => {
+                log::error!("assertion is false under the model: {}", assertion);
+                num_falsified += 1;
+            }
+            model::AssertionStatus::Unknown(reason) => {
+                log::warn!("could not evaluate assertion '{}': {}", assertion, reason);
+                all_satisfied = false;
+            }
+        }
+    }
+    if num_falsified > 0 {
+        return Err(CliError::FalsifiedAssertions(num_falsified));
+    }
+    Ok(all_satisfied)
+}
+
+fn explain_command(options: ExplainCommandOptions) -> CliResult<()> {
+    let Some(code) = options.code else {
+        for (code, summary) in error_code::ERROR_CODES {
+            let marker = if code.explanation().is_some() {
+                "*"
+            } else {
+                " "
+            };
+            println!("{}{} {}", code, marker, summary);
+        }
+        println!();
+        println!("Codes marked with `*` have a detailed explanation.");
+        return Ok(());
+    };
+    let code: ErrorCode = code
+        .parse()
+        .map_err(|()| CliError::UnknownErrorCode(code))?;
+
+    println!("{}: {}", code, code.summary());
+    let Some(explanation) = code.explanation() else {
+        println!();
+        println!("There is no detailed explanation for this error code.");
+        return Ok(());
+    };
+    println!();
+    println!("{}", explanation.description);
+    if !explanation.rules.is_empty() {
+        println!();
+        println!("Rules: {}", explanation.rules.join(", "));
+    }
+    if !explanation.mistakes.is_empty() {
+        println!();
+        println!("Common mistakes:");
+        for mistake in explanation.mistakes {
+            println!("  - {}", mistake);
+        }
+    }
+    Ok(())
+}
+
+fn lemmas_command(options: LemmasCommandOptions, use_sharing: bool) -> CliResult<()> {
+    let (problem, proof) = get_instance(&options.input)?;
+    let script = extract_lemmas(
+        problem,
+        proof,
+        options.parsing.into(),
+        &options.rules,
+        use_sharing,
+    )?;
+    print!("{}", script);
+    Ok(())
+}
+
 fn generate_lia_problems_command(options: ParseCommandOptions, use_sharing: bool) -> CliResult<()> {
     use std::io::Write;
 
@@ -0,0 +1,296 @@
+use crate::benchmarking::{run_job, JobDescriptor};
+use carcara::{
+    benchmarking::{CollectResults, OpenMetricsResults},
+    checker, parser,
+};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    panic,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+const STACK_SIZE: usize = 128 * 1024 * 1024;
+
+/// The maximum number of bytes read from the request line and headers of a request, combined.
+const MAX_REQUEST_HEAD_SIZE: u64 = 16 * 1024;
+
+/// How long the server waits for a client to send its request before giving up on it.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The state shared by all connections.
+struct Server {
+    root: PathBuf,
+    parser_config: parser::Config,
+    checker_config: checker::Config,
+    metrics: Mutex<OpenMetricsResults>,
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn text(status: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: body.into(),
+        }
+    }
+
+    fn write_to(&self, mut stream: &TcpStream) -> io::Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.status,
+            self.content_type,
+            self.body.len()
+        )?;
+        stream.write_all(&self.body)?;
+        stream.flush()
+    }
+}
+
+/// Runs the checking service, accepting connections forever. Connections are handled by a fixed
+/// pool of `num_workers` threads, each serving a single request per connection. If all workers are
+/// busy and the queue of pending connections is full, new connections are answered with `503
+/// Service Unavailable`.
+///
+/// The service accepts the following requests:
+/// - `POST /check?problem=<path>&proof=<path>` checks the proof at the given path against the
+///   problem. Both paths are resolved relative to `root`, and requests for files outside of it are
+///   rejected. The response body is `valid`, `holey`, or `invalid` followed by the error, as a
+///   JSON object. If the checker panics, the response is `500 Internal Server Error`.
+/// - `GET /metrics` returns the metrics collected so far, in the OpenMetrics text format.
+pub fn serve(
+    address: impl ToSocketAddrs,
+    root: &Path,
+    num_workers: usize,
+    parser_config: parser::Config,
+    checker_config: checker::Config,
+) -> io::Result<()> {
+    let root = root.canonicalize()?;
+    let listener = TcpListener::bind(address)?;
+    log::info!("listening on {}", listener.local_addr()?);
+    let server = Arc::new(Server {
+        root,
+        parser_config,
+        checker_config,
+        metrics: Mutex::new(OpenMetricsResults::new()),
+    });
+
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(num_workers);
+    let receiver = Arc::new(Mutex::new(receiver));
+    let mut num_spawned = 0;
+    for _ in 0..num_workers {
+        let server = server.clone();
+        let receiver = receiver.clone();
+        let spawned = thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(move || loop {
+                // The lock is released as soon as a connection is received, so the other workers
+                // can wait for the next one while this one is handled
+                let Ok(stream) = receiver.lock().unwrap().recv() else {
+                    return;
+                };
+                if let Err(e) = server.handle_connection(stream) {
+                    log::warn!("failed to handle request: {}", e);
+                }
+            });
+        match spawned {
+            Ok(_) => num_spawned += 1,
+            Err(e) => log::error!("failed to spawn worker thread: {}", e),
+        }
+    }
+    if num_spawned == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "could not spawn any worker threads",
+        ));
+    }
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(s) => s,
+            Err(e) => {
+                log::warn!("failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        match sender.try_send(stream) {
+            Ok(()) => (),
+            Err(mpsc::TrySendError::Full(stream)) => {
+                log::warn!("too many pending connections, rejecting request");
+                let response = Response::text("503 Service Unavailable", "");
+                if let Err(e) = response.write_to(&stream) {
+                    log::warn!("failed to send response: {}", e);
+                }
+            }
+            // This only happens if every worker thread has exited, in which case no more requests
+            // can be handled
+            Err(mpsc::TrySendError::Disconnected(stream)) => {
+                let response = Response::text("500 Internal Server Error", "");
+                let _ = response.write_to(&stream);
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "all worker threads have stopped",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Server {
+    fn handle_connection(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let response = match read_request_head(&stream)? {
+            Some(request_line) => self.respond(&request_line),
+            None => Response::text("400 Bad Request", ""),
+        };
+        response.write_to(&stream)
+    }
+
+    fn respond(&self, request_line: &str) -> Response {
+        let mut parts = request_line.split_whitespace();
+        let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        match (method, path) {
+            ("GET", "/metrics") => {
+                let metrics = self.metrics.lock().unwrap().to_string();
+                Response {
+                    status: "200 OK",
+                    content_type: "application/openmetrics-text; version=1.0.0; charset=utf-8",
+                    body: metrics.into_bytes(),
+                }
+            }
+            ("POST", "/check") => self.check(query),
+            (_, "/metrics" | "/check") => Response::text("405 Method Not Allowed", ""),
+            _ => Response::text("404 Not Found", ""),
+        }
+    }
+
+    /// Resolves a path given in a request relative to the server root. Returns `None` if the file
+    /// does not exist, or if it is outside of the root, including through symbolic links.
+    fn resolve(&self, path: &str) -> Option<PathBuf> {
+        let resolved = self.root.join(path).canonicalize().ok()?;
+        resolved.starts_with(&self.root).then_some(resolved)
+    }
+
+    fn check(&self, query: &str) -> Response {
+        let mut problem = None;
+        let mut proof = None;
+        for pair in query.split('&') {
+            match pair.split_once('=') {
+                Some(("problem", value)) => problem = percent_decode(value),
+                Some(("proof", value)) => proof = percent_decode(value),
+                _ => (),
+            }
+        }
+        let (Some(problem), Some(proof)) = (problem, proof) else {
+            let message = "expected `problem` and `proof` query parameters\n";
+            return Response::text("400 Bad Request", message);
+        };
+
+        let (Some(problem), Some(proof)) = (self.resolve(&problem), self.resolve(&proof)) else {
+            let message = "`problem` and `proof` must be existing files inside the server root\n";
+            return Response::text("403 Forbidden", message);
+        };
+
+        let job = JobDescriptor {
+            problem_file: &problem,
+            proof_file: &proof,
+            run_index: 0,
+        };
+        let mut results = OpenMetricsResults::new();
+
+        // A panic while checking the proof would otherwise kill the worker thread and leave the
+        // client without a response
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            run_job(
+                &mut results,
+                job,
+                self.parser_config,
+                self.checker_config.clone(),
+                None,
+            )
+        }));
+        let Ok(result) = result else {
+            log::error!("checker panicked on proof '{}'", proof.display());
+            return Response::text("500 Internal Server Error", "checker panicked\n");
+        };
+
+        // If the job fails before checking starts, the results passed to it are discarded, so we
+        // only count the proof afterwards
+        results.register_proof();
+        let body = match &result {
+            Ok(false) => b"valid\n".to_vec(),
+            Ok(true) => {
+                results.register_holey();
+                b"holey\n".to_vec()
+            }
+            Err(e) => {
+                results.register_error(e);
+                let mut body = b"invalid\n".to_vec();
+                e.write_json(&mut body).unwrap();
+                body.push(b'\n');
+                body
+            }
+        };
+
+        let mut metrics = self.metrics.lock().unwrap();
+        *metrics = OpenMetricsResults::combine(std::mem::take(&mut *metrics), results);
+        Response::text("200 OK", body)
+    }
+}
+
+/// Decodes a URL query parameter, in which spaces may be written as `+` and other characters as
+/// `%` followed by two hexadecimal digits. Returns `None` if the result is not valid UTF-8 or if an
+/// escape is malformed.
+fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [iter.next()?, iter.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            b => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Reads the request line and headers of a request, returning the request line. We don't use any
+/// of the headers, but they must be consumed before responding. Returns `None` if the request ends
+/// before the blank line that terminates the headers, or if they are longer than
+/// `MAX_REQUEST_HEAD_SIZE`.
+fn read_request_head(stream: &TcpStream) -> io::Result<Option<String>> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_HEAD_SIZE));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    if !request_line.ends_with('\n') {
+        return Ok(None);
+    }
+
+    let mut header = String::new();
+    loop {
+        header.clear();
+        reader.read_line(&mut header)?;
+        if !header.ends_with('\n') {
+            return Ok(None);
+        }
+        if header.trim_end().is_empty() {
+            return Ok(Some(request_line));
+        }
+    }
+}